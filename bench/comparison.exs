@@ -0,0 +1,20 @@
+# Compares SortedSet against :gb_sets, :ordsets and ETS ordered_set tables, see
+# Discord.SortedSet.Comparison for the workloads.
+alias Discord.SortedSet.Comparison
+
+for size <- [1_000, 10_000, 50_000] do
+  %{results: results} = Comparison.run(size: size, runs: 5)
+
+  IO.puts("#{size} items, median microseconds")
+
+  for {workload, timings} <- Enum.sort(results) do
+    row =
+      timings
+      |> Enum.sort_by(fn {_, microseconds} -> microseconds end)
+      |> Enum.map_join("  ", fn {structure, microseconds} -> "#{structure}: #{microseconds}" end)
+
+    IO.puts("  #{String.pad_trailing(Atom.to_string(workload), 8)}#{row}")
+  end
+
+  IO.puts("")
+end
@@ -11,6 +11,10 @@ defmodule Discord.SortedSet do
 
   @default_bucket_size 500
   @default_capacity @default_bucket_size
+  @default_fold_budget 1_000
+
+  # Options whose sets are not in the Erlang term order, so Enum.sort/1 can not build them
+  @custom_order_options [:charlists, :collation, :comparator, :tuple_order]
 
   @doc """
   Construct a new SortedSet with a given capacity and bucket size
@@ -42,11 +46,19 @@ defmodule Discord.SortedSet do
   Second, because the data is stored in the NIF's memory space, the data structure acts more like
   a mutable data structure than a standard immutable data structure.  It's best to treat the
   `t:reference/0` like one would treat an ETS `tid`.
+
+  ## Options
+
+  Additional behavior can be configured by passing a keyword list of options, see
+  `t:Discord.SortedSet.Types.options/0` for the supported options.
   """
-  @spec new(capacity :: pos_integer(), bucket_size :: pos_integer()) ::
-          t() | Types.common_errors()
-  def new(capacity \\ @default_capacity, bucket_size \\ @default_bucket_size) do
-    {:ok, set} = NifBridge.new(capacity, bucket_size)
+  @spec new(
+          capacity :: pos_integer(),
+          bucket_size :: pos_integer(),
+          options :: Types.options()
+        ) :: t() | Types.common_errors()
+  def new(capacity \\ @default_capacity, bucket_size \\ @default_bucket_size, options \\ []) do
+    {:ok, set} = NifBridge.new(capacity, bucket_size, options)
     set
   end
 
@@ -57,16 +69,39 @@ defmodule Discord.SortedSet do
   then the `from_proper_enumerable/2` function should be used as it is slightly faster.
 
   See `from_proper_enumerable/2` for a definition of `proper`.
+
+  Sets created with the `:charlists`, `:collation`, `:comparator` or `:tuple_order` option are
+  built with `from_etf_binary/3`, as the terms have to be sorted in the order of the set rather
+  than by the Erlang term order.  So are terms holding `:infinity` or `:neg_infinity`, which the
+  set orders differently from Erlang.
   """
-  @spec from_enumerable(terms :: [Types.supported_term()], bucket_size :: pos_integer()) ::
-          t() | Types.common_errors()
-  def from_enumerable(terms, bucket_size \\ @default_bucket_size) do
-    terms
-    |> Enum.sort()
-    |> Enum.dedup()
-    |> from_proper_enumerable(bucket_size)
+  @spec from_enumerable(
+          terms :: [Types.supported_term()],
+          bucket_size :: pos_integer(),
+          options :: Types.options()
+        ) :: t() | Types.common_errors()
+  def from_enumerable(terms, bucket_size \\ @default_bucket_size, options \\ []) do
+    terms = Enum.to_list(terms)
+
+    if Enum.any?(@custom_order_options, &Keyword.has_key?(options, &1)) or
+         Enum.any?(terms, &sentinels?/1) do
+      terms
+      |> :erlang.term_to_binary()
+      |> from_etf_binary(bucket_size, options)
+    else
+      terms
+      |> Enum.sort()
+      |> Enum.dedup()
+      |> from_proper_enumerable(bucket_size, options)
+    end
   end
 
+  # True if the term holds an atom that the set orders differently from Erlang
+  defp sentinels?(term) when term in [:infinity, :neg_infinity], do: true
+  defp sentinels?(term) when is_tuple(term), do: sentinels?(Tuple.to_list(term))
+  defp sentinels?([head | tail]), do: sentinels?(head) or sentinels?(tail)
+  defp sentinels?(_term), do: false
+
   @doc """
   Construct a new SortedSet from a proper enumerable
 
@@ -77,16 +112,24 @@ defmodule Discord.SortedSet do
 
   This method of construction is much faster than iterative construction.
 
-  See `from_enumerable/2` for enumerables that are not proper.
+  See `from_enumerable/2` for enumerables that are not proper.  With the `:charlists`,
+  `:collation`, `:comparator` or `:tuple_order` option the enumerable is considered proper if it
+  is sorted in the order of the set, an enumerable sorted by the Erlang term order should be
+  passed to `from_enumerable/3` instead.  The same goes for terms holding `:infinity` or
+  `:neg_infinity`, which sort last and first.
   """
-  @spec from_proper_enumerable(terms :: [Types.supported_term()], bucket_size :: pos_integer()) ::
-          t() | Types.common_errors()
-  def from_proper_enumerable(terms, buckets_size \\ @default_bucket_size)
+  @spec from_proper_enumerable(
+          terms :: [Types.supported_term()],
+          bucket_size :: pos_integer(),
+          options :: Types.options()
+        ) :: t() | Types.common_errors()
+  def from_proper_enumerable(terms, buckets_size \\ @default_bucket_size, options \\ [])
 
-  def from_proper_enumerable([], bucket_size), do: new(@default_capacity, bucket_size)
+  def from_proper_enumerable([], bucket_size, options),
+    do: new(@default_capacity, bucket_size, options)
 
-  def from_proper_enumerable(terms, bucket_size) do
-    {:ok, set} = NifBridge.empty(Enum.count(terms), bucket_size)
+  def from_proper_enumerable(terms, bucket_size, options) do
+    {:ok, set} = NifBridge.empty(Enum.count(terms), bucket_size, options)
 
     terms
     |> Enum.chunk_every(bucket_size - 1)
@@ -101,6 +144,33 @@ defmodule Discord.SortedSet do
     end)
   end
 
+  @doc """
+  Construct a new SortedSet from a binary produced by `:erlang.term_to_binary/1`
+
+  The binary must hold a list of supported terms, the list does not need to be sorted or free of
+  duplicates.  The binary is decoded directly into the set on a dirty CPU scheduler, so unlike
+  `:erlang.binary_to_term/1` followed by `from_enumerable/3` the terms are never materialized as
+  an Elixir list.  Compressed binaries are not supported.
+
+  `{:error, :invalid_etf}` is returned if the binary is not a valid term or nests tuples and
+  lists more than 128 levels deep, `{:error, :unsupported_type}` if it is not a list or holds
+  terms that can not be stored in a SortedSet.
+  """
+  @spec from_etf_binary(
+          binary :: binary(),
+          bucket_size :: pos_integer(),
+          options :: Types.options()
+        ) :: t() | {:error, :invalid_etf} | {:error, :set_full} | Types.common_errors()
+  def from_etf_binary(binary, bucket_size \\ @default_bucket_size, options \\ []) do
+    case NifBridge.from_etf_binary(@default_capacity, bucket_size, options, binary) do
+      {:ok, set} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
   @doc """
   Adds an item to the set.
 
@@ -111,10 +181,73 @@ defmodule Discord.SortedSet do
 
   Unlike a hash based set that has O(1) inserts, the SortedSet is O(log(N/B)) + O(log(B)) where
   `N` is the number of items in the SortedSet and `B` is the Bucket Size.
+
+  ## Capped Sets
+
+  If the set was constructed with the `:max_elements` option and is full, adding an item that is
+  not already present returns `{:error, :set_full}` and leaves the set unchanged.
+
+  ## Coalesced Writes
+
+  If the set was constructed with the `:coalesce_writes` option the item is queued and added by
+  the next call that locks the set, see `flush/1`.  A set that is already frozen or full when the
+  item is queued returns `{:error, :frozen}` or `{:error, :set_full}` right away, but a set that
+  is frozen or fills up before the queue is applied still drops the queued item without an error.
   """
-  @spec add(set :: t(), item :: Types.supported_term()) :: t() | Types.common_errors()
+  @spec add(set :: t(), item :: Types.supported_term()) ::
+          t() | {:error, :set_full} | Types.frozen_error() | Types.common_errors()
   def add(set, item) do
+    case NifBridge.queue_add(set, item) do
+      {:ok, :queued} ->
+        set
+
+      {:ok, _, _} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Adds an item that must not be in the set yet.
+
+  Behaves like `add/2`, except that an item that is already present returns
+  `{:error, :already_exists}` instead of the unchanged set.  Pipelines that treat a duplicate as
+  a bug can fail fast on it without asking for the index.
+  """
+  @spec add_new(set :: t(), item :: Types.supported_term()) ::
+          t()
+          | {:error, :already_exists}
+          | {:error, :set_full}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def add_new(set, item) do
     case NifBridge.add(set, item) do
+      {:ok, :added, _} ->
+        set
+
+      {:ok, :duplicate, _} ->
+        {:error, :already_exists}
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Adds an item to the set using a positional hint.
+
+  The `hint` is the index the caller expects the item to end up at, for example one past the
+  index returned by the previous `index_add/3` when feeding in nearly sorted data.  The hint is
+  checked against the neighboring items, when it is correct the search for the insertion point
+  is skipped entirely and when it is wrong the item is added exactly as `add/2` would add it.  A
+  wrong hint never results in an incorrect set.
+  """
+  @spec add(set :: t(), item :: Types.supported_term(), hint :: non_neg_integer()) ::
+          t() | {:error, :set_full} | Types.frozen_error() | Types.common_errors()
+  def add(set, item, hint) do
+    case NifBridge.add(set, item, hint) do
       {:ok, _, _} ->
         set
 
@@ -135,7 +268,10 @@ defmodule Discord.SortedSet do
   `N` is the number of items in the SortedSet and `B` is the Bucket Size.
   """
   @spec index_add(set :: t(), item :: any()) ::
-          {index :: non_neg_integer() | nil, t()} | Types.common_errors()
+          {index :: non_neg_integer() | nil, t()}
+          | {:error, :set_full}
+          | Types.frozen_error()
+          | Types.common_errors()
   def index_add(set, item) do
     case NifBridge.add(set, item) do
       {:ok, :added, index} ->
@@ -149,6 +285,99 @@ defmodule Discord.SortedSet do
     end
   end
 
+  @doc """
+  Adds an item to the set using a positional hint or options, returning the index.
+
+  Given an integer, the integer is used as the hint.  See `add/3` for how the hint is used, the
+  returned index makes a good basis for the hint of the next item.
+
+  Given a list of options, the options can provide the hint and select which index is returned
+  for an added item, see `t:Discord.SortedSet.Types.add_option/0`.  By default the index the item
+  ended up at is returned, with `index: :displaced` the index the item previously at that
+  position was pushed to is returned instead.  Adding `2` to `[1, 3]` ends up at index 1 and
+  displaces `3` to index 2, adding `4` to `[1, 3]` displaces nothing and returns `nil`.  Both
+  indexes are taken under the write lock, so neither has to be recomputed afterwards.
+
+  Duplicates return the index `nil` regardless of the options.
+  """
+  @spec index_add(
+          set :: t(),
+          item :: any(),
+          hint_or_options :: non_neg_integer() | [Types.add_option()]
+        ) ::
+          {index :: non_neg_integer() | nil, t()}
+          | {:error, :set_full}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def index_add(set, item, options) when is_list(options) do
+    case NifBridge.add_with_options(set, item, options) do
+      {:ok, :added, index} ->
+        {index, set}
+
+      {:ok, :duplicate, _} ->
+        {nil, set}
+
+      {:ok, :added, index, _} ->
+        {index, set}
+
+      {:ok, :duplicate, _, _} ->
+        {nil, set}
+
+      other ->
+        other
+    end
+  end
+
+  def index_add(set, item, hint) do
+    case NifBridge.add(set, item, hint) do
+      {:ok, :added, index} ->
+        {index, set}
+
+      {:ok, :duplicate, _} ->
+        {nil, set}
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Adds an item that may hold unsupported terms, like pids or floats, nested in its tuples or
+  lists, returning the index and the number of nested terms that were dropped or replaced.
+
+  Payloads from third parties often carry a single field that can not be stored, which would
+  otherwise refuse the whole item.  With `:skip` the unsupported terms are left out of their
+  tuple or list, with `{:replace, placeholder}` the placeholder atom takes their place.  The item
+  is stored and compared in its sanitized form, so removing or finding it later takes the
+  sanitized item as well.  An item that is unsupported itself is still refused.
+
+      SortedSet.sanitized_add(set, {:event, self(), "join"}, {:replace, :redacted})
+      # => {0, 1, set}, holding {:event, :redacted, "join"}
+
+  Like `index_add/2` the index is `nil` for duplicates.
+  """
+  @spec sanitized_add(
+          set :: t(),
+          item :: any(),
+          policy :: :skip | {:replace, atom()}
+        ) ::
+          {index :: non_neg_integer() | nil, sanitized :: non_neg_integer(), t()}
+          | {:error, :set_full}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def sanitized_add(set, item, policy) do
+    case NifBridge.add_with_options(set, item, unsupported: policy) do
+      {:ok, :added, index, sanitized} ->
+        {index, sanitized, set}
+
+      {:ok, :duplicate, _, sanitized} ->
+        {nil, sanitized, set}
+
+      other ->
+        other
+    end
+  end
+
   @doc """
   Removes an item from the set.
 
@@ -160,10 +389,30 @@ defmodule Discord.SortedSet do
 
   Unlike a hash based set that has O(1) removes, the SortedSet is O(log(N/B)) + O(log(B)) where
   `N` is the number of items in the SortedSet and `B` is the Bucket Size.
+
+  ## Internal Inconsistency
+
+  If the set finds that its bookkeeping disagrees with its contents it repairs the bookkeeping,
+  logs the positions involved to standard error and returns `{:error, :internal_inconsistency}`
+  without removing the item.  The remove can be retried.
+
+  ## Coalesced Writes
+
+  If the set was constructed with the `:coalesce_writes` option the removal is queued and made
+  by the next call that locks the set, see `flush/1`.  A set that is already frozen when the
+  removal is queued returns `{:error, :frozen}` right away, but a set that is frozen before the
+  queue is applied still drops the queued removal without an error.
   """
-  @spec remove(set :: t(), item :: any()) :: t() | Types.common_errors()
+  @spec remove(set :: t(), item :: any()) ::
+          t()
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
   def remove(set, item) do
-    case NifBridge.remove(set, item) do
+    case NifBridge.queue_remove(set, item) do
+      {:ok, :queued} ->
+        set
+
       {:ok, :removed, _} ->
         set
 
@@ -175,6 +424,34 @@ defmodule Discord.SortedSet do
     end
   end
 
+  @doc """
+  Removes the item at `index` from the set.
+
+  If the index is out of bounds, the set is simply returned.  Callers that already know the rank
+  of an item, for example from `find_index/2`, can remove it without passing the item back to the
+  NIF, the item is found with the same walk as `at/3` and is never compared against other items.
+  This makes a difference for large tuples and lists, which are expensive to convert and compare.
+  A negative index counts back from the end of the set like it does for `at/3`.  To also retrieve
+  the removed item, see `pop_at/3`.
+  """
+  @spec remove_at(set :: t(), index :: integer()) ::
+          t()
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
+  def remove_at(set, index) do
+    case NifBridge.remove_at(set, index) do
+      {:ok, :removed, _} ->
+        set
+
+      {:error, {:index_out_of_bounds, _, _}} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
   @doc """
   Removes an item from the set, returning the index of the item before removal.
 
@@ -188,13 +465,16 @@ defmodule Discord.SortedSet do
   `N` is the number of items in the SortedSet and `B` is the Bucket Size.
   """
   @spec index_remove(set :: t(), item :: any()) ::
-          {index :: non_neg_integer(), t()} | Types.common_errors()
+          {index :: non_neg_integer(), t()}
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
   def index_remove(set, item) do
-    case NifBridge.remove(set, item) do
+    case NifBridge.remove(set, item, missing: :noop) do
       {:ok, :removed, index} ->
         {index, set}
 
-      {:error, :not_found} ->
+      {:ok, :noop} ->
         {nil, set}
 
       other ->
@@ -202,6 +482,119 @@ defmodule Discord.SortedSet do
     end
   end
 
+  @doc """
+  Removes the elements at the given indexes, returning the removed elements in index order along
+  with the set.
+
+  The indexes do not have to be sorted, repeated indexes and indexes past the end of the set are
+  ignored.  Every index refers to the set as it was before the call, so removing `[0, 1]` removes
+  the first two elements.  Subscribers receive a `:removed` event per element, from the last
+  index to the first.
+
+  ## Performance
+
+  Removing `K` elements one by one with `remove/2` searches for and shifts every one of them.
+  The indexes are instead resolved in a single walk over the buckets and the elements of a bucket
+  are removed from the back, so each bucket is only visited once however many of its elements
+  are removed.
+  """
+  @spec remove_at_many(set :: t(), indexes :: [non_neg_integer()]) ::
+          {[Types.supported_term()], t()}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def remove_at_many(set, indexes) do
+    case NifBridge.remove_at_many(set, indexes) do
+      {:ok, removed} ->
+        {removed, set}
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Removes the elements selected by a range of indexes, like `Enum.slice/2` selects them,
+  returning the removed elements in index order along with the set.
+
+  Negative bounds count from the end of the set, `remove_range(set, -3..-1)` removes the last
+  three elements, and a step above 1 removes every `step`-th element of the range.  The bounds are
+  resolved against the size of the set under the same lock as the removal, which otherwise
+  behaves like `remove_at_many/2`.
+  """
+  @spec remove_range(set :: t(), range :: Range.t()) ::
+          {[Types.supported_term()], t()}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def remove_range(set, %Range{} = range) do
+    case NifBridge.remove_range(set, range) do
+      {:ok, removed} ->
+        {removed, set}
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Removes the least element of the set and returns it as `{element, set}`.
+
+  The element is read and removed under a single write lock, so concurrent consumers of the same
+  set never pop the same element, which makes the set usable as a priority queue.  If the set is
+  empty then `{default, set}` is returned, `default` defaults to `nil` if not provided.
+  Subscribers receive a `:removed` event for the element.
+  """
+  @spec pop_first(set :: t(), default :: any()) ::
+          {Types.supported_term() | any(), t()}
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
+  def pop_first(set, default \\ nil) do
+    set
+    |> NifBridge.pop_first()
+    |> pop_result(set, default)
+  end
+
+  @doc """
+  Removes the greatest element of the set and returns it as `{element, set}`.
+
+  Works like `pop_first/2` from the other end of the set.
+  """
+  @spec pop_last(set :: t(), default :: any()) ::
+          {Types.supported_term() | any(), t()}
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
+  def pop_last(set, default \\ nil) do
+    set
+    |> NifBridge.pop_last()
+    |> pop_result(set, default)
+  end
+
+  @doc """
+  Removes the element at `index` and returns it as `{element, set}`.
+
+  The element is found with the same walk over the buckets as `at/3` and removed from its bucket
+  under the same write lock, so it is never compared against other elements and no other process
+  can remove or shift it in between.  A negative index counts back from the end of the set like it
+  does for `at/3`.  If the index is out of bounds then `{default, set}` is returned, `default`
+  defaults to `nil` if not provided.  Subscribers receive a `:removed` event for the element.
+  """
+  @spec pop_at(set :: t(), index :: integer(), default :: any()) ::
+          {Types.supported_term() | any(), t()}
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
+  def pop_at(set, index, default \\ nil) do
+    set
+    |> NifBridge.pop_at(index)
+    |> pop_result(set, default)
+  end
+
+  defp pop_result({:ok, item}, set, _default), do: {item, set}
+  defp pop_result({:error, :empty}, set, default), do: {default, set}
+  defp pop_result({:error, {:index_out_of_bounds, _, _}}, set, default), do: {default, set}
+  defp pop_result({:error, _} = error, _set, _default), do: error
+
   @doc """
   Get the size of a SortedSet
 
@@ -230,20 +623,104 @@ defmodule Discord.SortedSet do
     end
   end
 
+  @doc """
+  Converts a SortedSet into a List in descending order
+
+  Equivalent to `set |> to_list() |> Enum.reverse()` without building the intermediate list, the
+  buckets are walked back-to-front in NIF space.  Like `to_list/1` this copies the entire
+  SortedSet into Elixir space and can be very expensive.
+  """
+  @spec to_list_reverse(set :: t()) :: [Types.supported_term()] | Types.common_errors()
+  def to_list_reverse(set) do
+    case NifBridge.to_list_reverse(set) do
+      result when is_list(result) ->
+        result
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Drains a SortedSet into a List, leaving the SortedSet empty.
+
+  `into_list/1` is intended for "drain the set and process everything" jobs, the elements are
+  moved out of the SortedSet rather than cloned so the peak memory usage is roughly half that of
+  calling `to_list/1` and then discarding the set.  The SortedSet remains usable afterwards.
+  """
+  @spec into_list(set :: t()) ::
+          [Types.supported_term()] | Types.frozen_error() | Types.common_errors()
+  def into_list(set) do
+    case NifBridge.into_list(set) do
+      result when is_list(result) ->
+        result
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Creates an iterator that walks the SortedSet in either direction, see `iterator_next/2`.
+
+  The iterator can be positioned at a term with `:from` or at an index with `:at`, so a "scroll up
+  from here" view is `iterator(set, from: term, direction: :desc)`.  See
+  `t:Discord.SortedSet.Types.iterator_option/0` for the available options.
+
+  Iterators do not hold an index into the set, they remember the last element they returned.
+  Elements added or removed between reads never cause an iterator to skip or repeat an element,
+  elements added past the iterator are returned when it gets to them.
+
+  An iterator created with `snapshot: true` reads a copy of the set taken when it is created
+  instead, so paging through a set that keeps changing returns exactly the elements it held at
+  that moment.  Writers are only held up while the copy is taken, the iterator never locks the
+  set again and the copy is released with the iterator.
+  """
+  @spec iterator(set :: t(), options :: [Types.iterator_option()]) ::
+          Types.iterator() | Types.common_errors()
+  def iterator(set, options \\ []) do
+    case NifBridge.iterator(set, options) do
+      {:ok, iterator} ->
+        iterator
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Reads up to `count` elements from an iterator created by `iterator/2` and advances past them.
+
+  Elements are returned in the direction of the iterator, once the iterator reaches the end of the
+  set fewer than `count` elements are returned.
+  """
+  @spec iterator_next(iterator :: Types.iterator(), count :: pos_integer()) ::
+          [Types.supported_term()] | Types.common_errors()
+  def iterator_next(iterator, count \\ 1) do
+    case NifBridge.iterator_next(iterator, count) do
+      result when is_list(result) ->
+        result
+
+      other ->
+        other
+    end
+  end
+
   @doc """
   Retrieve an item at the given index.
 
+  A negative index counts back from the end of the set, so `at(set, -1)` retrieves the last item.
   If the index is out of bounds then the optional default value is returned instead, this defaults
   to `nil` if not provided.
   """
-  @spec at(set :: t(), index :: non_neg_integer(), default :: any()) ::
+  @spec at(set :: t(), index :: integer(), default :: any()) ::
           (item_or_default :: Types.supported_term() | any()) | Types.common_errors()
   def at(set, index, default \\ nil) do
     case NifBridge.at(set, index) do
       {:ok, item} ->
         item
 
-      {:error, :index_out_of_bounds} ->
+      {:error, {:index_out_of_bounds, _, _}} ->
         default
 
       {:error, _} = other ->
@@ -251,6 +728,64 @@ defmodule Discord.SortedSet do
     end
   end
 
+  @doc """
+  Retrieve the items at each of the given indexes, in the order the indexes were given.
+
+  Every index is resolved under a single lock acquisition.  The indexes are sorted in Rust and
+  resolved with a single forward walk over the buckets, where calling `at/3` once per index walks
+  the bucket sizes again for every index.  Indexes that are out of bounds produce the optional
+  default value, this defaults to `nil` if not provided.
+
+  Negative indexes count from the end of the set like they do for `at/3`.  A range of indexes
+  selects the elements like `slice/2` does, so `at_many(set, -3..-1)` returns the last three
+  elements and a range reaching past the end of the set stops at the end without defaults.
+  """
+  @spec at_many(set :: t(), indexes :: [integer()] | Range.t(), default :: any()) ::
+          [Types.supported_term() | any()] | Types.common_errors()
+  def at_many(set, indexes, default \\ nil) do
+    case NifBridge.at_many(set, indexes, default) do
+      {:ok, items} ->
+        items
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Retrieve the least item of the set.
+
+  The item is read directly from the front of the set instead of resolving an index like
+  `at(set, 0)` does.  If the set is empty then the optional default value is returned instead,
+  this defaults to `nil` if not provided.
+  """
+  @spec first(set :: t(), default :: any()) ::
+          (item_or_default :: Types.supported_term() | any()) | Types.common_errors()
+  def first(set, default \\ nil) do
+    set
+    |> NifBridge.first()
+    |> edge_result(default)
+  end
+
+  @doc """
+  Retrieve the greatest item of the set.
+
+  The item is read directly from the back of the set instead of resolving an index like
+  `at(set, size(set) - 1)` does.  If the set is empty then the optional default value is returned
+  instead, this defaults to `nil` if not provided.
+  """
+  @spec last(set :: t(), default :: any()) ::
+          (item_or_default :: Types.supported_term() | any()) | Types.common_errors()
+  def last(set, default \\ nil) do
+    set
+    |> NifBridge.last()
+    |> edge_result(default)
+  end
+
+  defp edge_result({:ok, item}, _default), do: item
+  defp edge_result({:error, :empty}, default), do: default
+  defp edge_result({:error, _} = error, _default), do: error
+
   @doc """
   Retrieves a slice of the SortedSet starting at the specified index and including up to the
   specified amount.
@@ -259,9 +794,16 @@ defmodule Discord.SortedSet do
   exceeds the number of items from the start index to the end of the set then all terms up to the
   end of the set will be returned.  This means that the length of the list returned by slice will
   fall into the range of [0, `amount`]
+
+  A negative `start` counts back from the end of the set like it does for `Enum.slice/3`, so
+  `slice(set, -10, 10)` returns the last ten items without looking up the size of the set first.
+  A negative `start` reaching past the beginning of the set returns an empty list.
+
+  `{:error, :invalid_range}` is returned if `amount` is negative or if the end of the range is too
+  large to be represented.
   """
-  @spec slice(set :: t(), start :: non_neg_integer(), amount :: non_neg_integer()) ::
-          [Types.supported_term()] | Types.common_errors()
+  @spec slice(set :: t(), start :: integer(), amount :: non_neg_integer()) ::
+          [Types.supported_term()] | {:error, :invalid_range} | Types.common_errors()
   def slice(set, start, amount) do
     case NifBridge.slice(set, start, amount) do
       items when is_list(items) ->
@@ -273,21 +815,48 @@ defmodule Discord.SortedSet do
   end
 
   @doc """
-  Finds the index of the specified term.
-
-  Since SortedSet does enforce uniqueness of terms there is no need to worry about which index
-  gets returned, the term either exists in the set or does not exist in the set.  If the term
-  exists the index of the term is returned, if not then `nil` is returned.
+  Retrieves the items of the SortedSet selected by a range of indexes, like `Enum.slice/2`.
+
+  Negative bounds count from the end of the set, `slice(set, -3..-1)` returns the last three
+  items and `slice(set, 1..-2//1)` every item but the first and the last.  A range reaching past
+  the end of the set returns the items up to the end and a range starting past the end returns an
+  empty list.  A step above 1 samples the slice like `slice_step/4`, `slice(set, 0..99//10)`
+  returns the items at indexes 0, 10, 20 and so on up to 90.  The bounds are resolved against the
+  size of the set under the same lock as the read.
   """
-  @spec find_index(set :: t(), item :: Types.supported_term()) ::
-          non_neg_integer() | nil | Types.common_errors()
-  def find_index(set, item) do
-    case NifBridge.find_index(set, item) do
-      {:ok, index} ->
-        index
+  @spec slice(set :: t(), range :: Range.t()) :: [Types.supported_term()] | Types.common_errors()
+  def slice(set, %Range{} = range) do
+    case NifBridge.slice_range(set, range) do
+      items when is_list(items) ->
+        items
 
-      {:error, :not_found} ->
-        nil
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Retrieves a slice of the SortedSet in descending order, starting `start_from_end` items from the
+  greatest item and including up to the specified amount.
+
+  `slice_desc(set, 0, 10)` returns the greatest ten items, greatest first, and
+  `slice_desc(set, 10, 10)` the ten after those, which makes paging through a leaderboard from the
+  top a matter of advancing `start_from_end` by the page size.  The buckets are walked
+  back-to-front in NIF space, so no reversal happens in Elixir space.
+
+  An empty list is returned if `start_from_end` is past the beginning of the set, and
+  `{:error, :invalid_range}` if either argument is negative or the range is too large to be
+  represented.
+  """
+  @spec slice_desc(
+          set :: t(),
+          start_from_end :: non_neg_integer(),
+          amount :: non_neg_integer()
+        ) :: [Types.supported_term()] | {:error, :invalid_range} | Types.common_errors()
+  def slice_desc(set, start_from_end, amount) do
+    case NifBridge.slice_desc(set, start_from_end, amount) do
+      items when is_list(items) ->
+        items
 
       other ->
         other
@@ -295,15 +864,1408 @@ defmodule Discord.SortedSet do
   end
 
   @doc """
-  Returns a string representation of the underlying Rust data structure.
+  Retrieves every `step`-th item of the slice that `slice/3` would return for `start` and
+  `amount`, beginning with the item at `start`.
 
-  This function is mostly provided as a convenience, since the actual data structure is stored in
-  the NIF memory space it can be difficult to introspect the data structure as it changes.  This
-  function allows the caller to get the view of the data structure as Rust sees it.
+  The slice is sampled while walking the buckets, so only the sampled items are copied out of the
+  set.  Downsampling a large range, like a leaderboard rendered as a sparkline, transfers the
+  sample instead of the whole range.  `slice_step(set, 0, 100, 10)` returns the items at indexes
+  0, 10, 20 and so on up to 90.
+
+  `{:error, :invalid_range}` is returned for the same ranges as `slice/3` and if `step` is not a
+  positive integer.
   """
-  @spec debug(set :: t()) :: String.t()
-  def debug(set) do
-    NifBridge.debug(set)
+  @spec slice_step(
+          set :: t(),
+          start :: non_neg_integer(),
+          amount :: non_neg_integer(),
+          step :: pos_integer()
+        ) :: [Types.supported_term()] | {:error, :invalid_range} | Types.common_errors()
+  def slice_step(set, start, amount, step) do
+    case NifBridge.slice_step(set, start, amount, step) do
+      items when is_list(items) ->
+        items
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Aggregates the integers of the slice that `slice/3` would return for `start` and `amount`.
+
+  The aggregate is computed without copying the items out of the set, so rolling up a range of
+  tens of thousands of integers only transfers the result.  `:sum`, `:min` and `:max` return an
+  integer, `:avg` returns a float.  The sum of an empty slice is `0`, the other aggregates of an
+  empty slice are `nil`.
+
+  `{:error, :not_integer}` is returned if any item of the slice is not an integer and
+  `{:error, :overflow}` if the sum does not fit in 64 bits.  `{:error, :invalid_range}` is
+  returned for the same ranges as `slice/3`.
+  """
+  @spec aggregate(
+          set :: t(),
+          start :: non_neg_integer(),
+          amount :: non_neg_integer(),
+          aggregate :: :sum | :min | :max | :avg
+        ) ::
+          number()
+          | nil
+          | {:error, :invalid_range | :not_integer | :overflow}
+          | Types.common_errors()
+  def aggregate(set, start, amount, aggregate) do
+    case NifBridge.aggregate(set, start, amount, aggregate) do
+      {:ok, result} ->
+        result
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Counts the tuples of the SortedSet by the value of their element at the zero-based `index`.
+
+  Returns a map from every value found at `index` to the number of tuples holding it, like
+  `set |> to_list() |> Enum.frequencies_by(&elem(&1, index))` without copying the set.  Elements
+  that are not tuples or are too short to have an element at `index` are not counted.
+
+      # Members per role among {role, name} members
+      SortedSet.group_counts(set, 0)
+      #=> %{admin: 2, member: 41}
+  """
+  @spec group_counts(set :: t(), index :: non_neg_integer()) ::
+          %{optional(Types.supported_term()) => pos_integer()} | Types.common_errors()
+  def group_counts(set, index) do
+    case NifBridge.group_counts(set, index) do
+      {:ok, counts} ->
+        counts
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Reduces the elements of the SortedSet without copying them out of the set, see
+  `t:Discord.SortedSet.Types.fold_spec/0` for the reductions available.
+
+      # Number of admins among {name, role} members
+      SortedSet.fold(members, {:count, {:_, :admin}})
+
+      # Total of the scores of {score, name} entries
+      SortedSet.fold(leaderboard, {:sum, 0})
+
+  The set is folded in steps of a bounded number of elements, see `fold/4`, so folding a large
+  set never holds the set for long.  The sum of no elements is `0` and the count is `0`, the
+  minimum and maximum of no elements are `nil`.
+
+  `{:error, :missing_element}` is returned if an element is not a tuple or does not have the
+  element at `index`, `{:error, :not_integer}` if an element summed is not an integer and
+  `{:error, :overflow}` if the sum does not fit in 64 bits.
+  """
+  @spec fold(set :: t(), fold :: Types.fold_spec()) ::
+          any() | Types.fold_errors() | Types.common_errors()
+  def fold(set, fold) do
+    do_fold(set, fold, nil)
+  end
+
+  defp do_fold(set, fold, cursor) do
+    case fold(set, fold, cursor, @default_fold_budget) do
+      {:ok, result} ->
+        result
+
+      {:cont, cursor} ->
+        do_fold(set, fold, cursor)
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Runs a single step of a fold, visiting up to `budget` elements following the cursor.
+
+  The first step is given a `nil` cursor.  A step returns `{:cont, cursor}` while there are
+  elements left, the cursor is passed to the next step, and `{:ok, result}` once every element
+  has been visited.  Like the cursor of `iterator/2` it remembers the last element it visited
+  rather than an index, elements added or removed between steps are never visited twice.
+  Raises an `ArgumentError` if the budget is not a positive integer or the cursor belongs to a
+  different fold.
+  """
+  @spec fold(
+          set :: t(),
+          fold :: Types.fold_spec(),
+          cursor :: Types.fold_cursor() | nil,
+          budget :: pos_integer()
+        ) ::
+          {:ok, any()}
+          | {:cont, Types.fold_cursor()}
+          | Types.fold_errors()
+          | Types.common_errors()
+  def fold(set, fold, cursor, budget) do
+    NifBridge.fold(set, fold, cursor, budget)
+  end
+
+  @doc """
+  Splits the SortedSet into lists of `chunk_size` items, equivalent to
+  `set |> to_list() |> Enum.chunk_every(chunk_size)`.
+
+  The chunks are produced in one pass over the buckets under a single lock, so jobs that fan the
+  set out in pages do not have to build the whole list and chunk it again on the Elixir side.
+  Only the last chunk can hold fewer than `chunk_size` items, an empty set has no chunks.
+
+  ## Performance
+
+  Like `to_list/1` this copies every item of the set, prefer `slice/3` or an iterator when only
+  some of the chunks are needed.
+  """
+  @spec chunks(set :: t(), chunk_size :: pos_integer()) ::
+          [[Types.supported_term()]] | Types.common_errors()
+  def chunks(set, chunk_size) do
+    NifBridge.chunks(set, chunk_size)
+  end
+
+  @doc """
+  Pairs up the items at the same indexes of two sets, equivalent to
+  `Enum.zip(slice(a, start, amount), slice(b, start, amount))`.
+
+  Both slices are taken and zipped in a single call, which is handy for comparing two versions of
+  a set index by index.  The pairs stop at the end of the shorter slice.
+
+  `{:error, :invalid_range}` is returned for the same ranges as `slice/3`.
+  """
+  @spec zip(a :: t(), b :: t(), start :: non_neg_integer(), amount :: non_neg_integer()) ::
+          [{Types.supported_term(), Types.supported_term()}]
+          | {:error, :invalid_range}
+          | Types.common_errors()
+  def zip(a, b, start, amount) do
+    NifBridge.zip(a, b, start, amount)
+  end
+
+  @doc """
+  Retrieves several slices of the SortedSet in a single call.
+
+  Each window is a `{start, amount}` tuple and is treated exactly as `slice/3` would treat it, the
+  slices are returned in the same order as the windows.  All of the slices are produced under one
+  lock acquisition and with one pass over the buckets, which is considerably cheaper than calling
+  `slice/3` once per window when rendering several windows of the same set.
+
+  `{:error, :invalid_range}` is returned if any of the windows is invalid.
+  """
+  @spec multi_slice(
+          set :: t(),
+          windows :: [{start :: non_neg_integer(), amount :: non_neg_integer()}]
+        ) :: [[Types.supported_term()]] | {:error, :invalid_range} | Types.common_errors()
+  def multi_slice(set, windows) do
+    case NifBridge.multi_slice(set, windows) do
+      slices when is_list(slices) ->
+        slices
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Returns the index the term would be inserted at, before any element equal to it.
+
+  This is the number of elements of the set less than `item`.  Unlike `find_index/2` a rank is
+  returned whether or not the term is in the set, so ranks can be combined with arithmetic, the
+  number of elements from `a` up to but excluding `b` is `bisect_left(set, b) -
+  bisect_left(set, a)`.
+  """
+  @spec bisect_left(set :: t(), item :: Types.supported_term()) ::
+          non_neg_integer() | {:error, :unsupported_type} | Types.common_errors()
+  def bisect_left(set, item) do
+    case NifBridge.bisect_left(set, item) do
+      {:ok, rank} ->
+        rank
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Returns the index the term would be inserted at, after any element equal to it.
+
+  This is the number of elements of the set less than or equal to `item`, it only differs from
+  `bisect_left/2` by one when the term is in the set.
+  """
+  @spec bisect_right(set :: t(), item :: Types.supported_term()) ::
+          non_neg_integer() | {:error, :unsupported_type} | Types.common_errors()
+  def bisect_right(set, item) do
+    case NifBridge.bisect_right(set, item) do
+      {:ok, rank} ->
+        rank
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Retrieve the greatest element of the set less than or equal to `item`.
+
+  The element is found with the same binary searches as `find_index/2`, without computing its
+  index.  If every element of the set is greater than `item` then the optional default value is
+  returned instead, this defaults to `nil` if not provided.
+  """
+  @spec floor(set :: t(), item :: Types.supported_term(), default :: any()) ::
+          (element_or_default :: Types.supported_term() | any())
+          | {:error, :unsupported_type}
+          | Types.common_errors()
+  def floor(set, item, default \\ nil) do
+    case NifBridge.floor(set, item) do
+      {:ok, element} ->
+        element
+
+      {:error, :not_found} ->
+        default
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Retrieve the least element of the set greater than or equal to `item`.
+
+  Works like `floor/3` in the other direction, the default is returned if every element of the
+  set is less than `item`.
+  """
+  @spec ceiling(set :: t(), item :: Types.supported_term(), default :: any()) ::
+          (element_or_default :: Types.supported_term() | any())
+          | {:error, :unsupported_type}
+          | Types.common_errors()
+  def ceiling(set, item, default \\ nil) do
+    case NifBridge.ceiling(set, item) do
+      {:ok, element} ->
+        element
+
+      {:error, :not_found} ->
+        default
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Checks whether the set holds the specified term.
+
+  Membership is decided by the search of the term's bucket alone.  Unlike `find_index/2` the
+  index of the term is never computed, which saves a walk over the sizes of the preceding buckets
+  and makes this the cheaper call for membership checks on a hot path.
+  """
+  @spec contains?(set :: t(), item :: Types.supported_term()) ::
+          boolean() | {:error, :unsupported_type} | Types.common_errors()
+  def contains?(set, item) do
+    case NifBridge.contains(set, item) do
+      {:ok, contains} ->
+        contains
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Checks whether the set holds each of the specified terms, returning a boolean per term in the
+  order the terms were given.
+
+  Every term is checked under a single lock acquisition, so the answers are consistent with each
+  other.  The terms are sorted in Rust before they are looked up so the search only moves forward
+  through the buckets.  If any of the terms is of an unsupported type
+  `{:error, :unsupported_type}` is returned.
+  """
+  @spec contains_many(set :: t(), items :: [Types.supported_term()]) ::
+          [boolean()] | {:error, :unsupported_type} | Types.common_errors()
+  def contains_many(set, items) do
+    case NifBridge.contains_many(set, items) do
+      {:ok, contained} ->
+        contained
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Finds the index of the specified term.
+
+  Since SortedSet does enforce uniqueness of terms there is no need to worry about which index
+  gets returned, the term either exists in the set or does not exist in the set.  If the term
+  exists the index of the term is returned, if not then `nil` is returned.
+
+  ## Options
+
+  - `insertion_point: true` returns `{:not_found, index}` instead of `nil` when the term is not in
+    the set, where `index` is the index the term would be inserted at.  The index comes out of
+    the same search that looked for the term, so finding it costs nothing extra.
+  """
+  @spec find_index(
+          set :: t(),
+          item :: Types.supported_term(),
+          options :: [Types.find_index_option()]
+        ) ::
+          non_neg_integer()
+          | nil
+          | {:not_found, index :: non_neg_integer()}
+          | Types.common_errors()
+  def find_index(set, item, options \\ []) do
+    case NifBridge.find_index(set, item, options) do
+      {:ok, index} ->
+        index
+
+      {:error, :not_found} ->
+        nil
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Finds the page holding the specified term when the SortedSet is split into pages of
+  `page_size` terms, returning `{page, offset, items}`.
+
+  Pages are numbered from 0, `offset` is the position of the term within its page and `items` are
+  the terms of the page, which `slice(set, page * page_size, page_size)` would return.  The page
+  is read in the same call that finds the term, so it always holds the term even while the set
+  is being changed concurrently.  If the term is not present `nil` is returned.
+
+      # Jump to the page of a member in a list showing 50 members per page
+      {page, offset, members} = SortedSet.page_of(set, member, 50)
+
+  Raises an `ArgumentError` if the page size is not a positive integer.
+  """
+  @spec page_of(set :: t(), item :: Types.supported_term(), page_size :: pos_integer()) ::
+          {page :: non_neg_integer(), offset :: non_neg_integer(), [Types.supported_term()]}
+          | nil
+          | Types.common_errors()
+  def page_of(set, item, page_size) do
+    case NifBridge.page_of(set, item, page_size) do
+      {:ok, page, offset, items} ->
+        {page, offset, items}
+
+      {:error, :not_found} ->
+        nil
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Gets the item and updates it in one pass, following `Access.get_and_update/3`.
+
+  `fun` is called with the item if the set holds it and `nil` otherwise.  It returns either
+  `{get_value, replacement}`, in which case the item is replaced by `replacement`, or `:pop`, in
+  which case the item is removed.  Returns `{get_value, set}`, for `:pop` the get value is the
+  removed item or `nil`.  Replacing an item the set does not hold adds the replacement.
+
+  The replacement is sorted into the set like any added item, so it may end up at a different
+  index than the item it replaces.  The removal and the addition happen under a single write
+  lock, subscribers are notified of both.  `fun` runs before the lock is taken, if another
+  process removes the item in the meantime the replacement is simply added.
+  """
+  @spec get_and_update(
+          set :: t(),
+          item :: Types.supported_term(),
+          fun :: (Types.supported_term() | nil -> {any(), Types.supported_term()} | :pop)
+        ) ::
+          {any(), t()}
+          | {:error, :set_full}
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
+  def get_and_update(set, item, fun) do
+    with {:ok, current} <- current_item(set, item) do
+      case fun.(current) do
+        {get, replacement} ->
+          with {:ok, _} <- update(set, item, {:replace, replacement}) do
+            {get, set}
+          end
+
+        :pop ->
+          with {:ok, removed} <- update(set, item, :pop) do
+            {removed, set}
+          end
+      end
+    end
+  end
+
+  defp current_item(set, item) do
+    case NifBridge.find_index(set, item) do
+      {:ok, _} ->
+        {:ok, item}
+
+      {:error, :not_found} ->
+        {:ok, nil}
+
+      other ->
+        other
+    end
+  end
+
+  defp update(set, item, replacement) do
+    case NifBridge.get_and_update(set, item, replacement) do
+      {:ok, :found, current} ->
+        {:ok, current}
+
+      {:ok, :not_found} ->
+        {:ok, nil}
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Returns the stable ID of the specified term, `nil` is returned if the term is not present.
+
+  The set must have been created with the `stable_ids: true` option, otherwise
+  `{:error, :stable_ids_disabled}` is returned.  An element keeps its ID until it is removed, so
+  unlike its index the ID can be held on to while other elements are added and removed.  IDs are
+  never reused within a set, an element that is removed and added again gets a new ID.
+  """
+  @spec id_of(set :: t(), item :: Types.supported_term()) ::
+          pos_integer() | nil | {:error, :stable_ids_disabled} | Types.common_errors()
+  def id_of(set, item) do
+    case NifBridge.id_of(set, item) do
+      {:ok, id} ->
+        id
+
+      {:error, :not_found} ->
+        nil
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Retrieves the element with the given stable ID, see `id_of/2`.
+
+  If no element has the ID then the optional default value is returned instead, this defaults to
+  `nil` if not provided.
+  """
+  @spec get_by_id(set :: t(), id :: pos_integer(), default :: any()) ::
+          (item_or_default :: Types.supported_term() | any())
+          | {:error, :stable_ids_disabled}
+          | Types.common_errors()
+  def get_by_id(set, id, default \\ nil) do
+    case NifBridge.get_by_id(set, id) do
+      {:ok, item} ->
+        item
+
+      {:error, :not_found} ->
+        default
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Estimates the number of distinct elements across all of the sets, as if they were merged.
+
+  Every set must have been created with the `cardinality_sketch: true` option, otherwise
+  `{:error, :sketch_disabled}` is returned.  The estimate only combines the sketches of the sets,
+  nothing is merged, so it is cheap even across hundreds of large sets and is usually within a
+  couple percent of the true size.  Sketches can not forget elements, a set that had elements
+  removed rebuilds its sketch from its elements on every estimate.
+
+  The sets are read one at a time, mutations that happen during the estimate may or may not be
+  accounted for.
+  """
+  @spec estimate_union_size(sets :: [t()]) ::
+          non_neg_integer() | {:error, :sketch_disabled} | Types.common_errors()
+  def estimate_union_size(sets) do
+    case NifBridge.estimate_union_size(sets) do
+      {:ok, size} ->
+        size
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Returns when the SortedSet was last modified.
+
+  Every successful mutation (an add of a new item, a removal of a present item, etc) records the
+  time it occurred, no-op mutations like adding a duplicate do not.  A set that has never been
+  mutated reports the time it was created.
+
+  Returns a map with the following keys
+
+    - `:system_time` the wall-clock time of the last mutation, in milliseconds since the Unix
+      epoch.
+    - `:idle_time` the number of milliseconds since the last mutation.  This is measured against a
+      monotonic clock and is the value that should be used when deciding if a set has gone idle.
+  """
+  @spec last_modified(set :: t()) ::
+          %{system_time: non_neg_integer(), idle_time: non_neg_integer()}
+          | Types.common_errors()
+  def last_modified(set) do
+    case NifBridge.last_modified(set) do
+      {:ok, system_time, idle_time} ->
+        %{system_time: system_time, idle_time: idle_time}
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Attaches a metadata term to the set.
+
+  Each set can hold a single metadata term (for example a guild id, shard, or schema version),
+  putting a new term replaces the previous one.  The metadata lives alongside the set in NIF space
+  and is released along with the set when it is garbage collected, there is no need to maintain a
+  separate mapping from set to metadata.
+
+  Metadata is not part of the set's contents, it does not affect the size of the set and does not
+  count as a modification.
+  """
+  @spec put_meta(set :: t(), meta :: Types.supported_term()) ::
+          t() | Types.frozen_error() | Types.common_errors()
+  def put_meta(set, meta) do
+    case NifBridge.put_meta(set, meta) do
+      :ok ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Retrieves the metadata term attached to the set.
+
+  If no metadata has been attached then the optional default value is returned instead, this
+  defaults to `nil` if not provided.
+  """
+  @spec get_meta(set :: t(), default :: any()) ::
+          (meta_or_default :: Types.supported_term() | any()) | Types.common_errors()
+  def get_meta(set, default \\ nil) do
+    case NifBridge.get_meta(set) do
+      {:ok, meta} ->
+        meta
+
+      {:error, :not_found} ->
+        default
+
+      {:error, _} = other ->
+        other
+    end
+  end
+
+  @doc """
+  Freezes the set, making it immutable until it is thawed.
+
+  While frozen, every operation that would modify the set (adding, removing, replacing metadata,
+  etc) returns `{:error, :frozen}` and leaves the set unchanged.  This is useful when handing a
+  snapshot to read-only consumers that should not be able to accidentally modify it.
+
+  Frozen sets only ever take the shared read lock, so concurrent readers of a frozen set never
+  contend with each other.
+  """
+  @spec freeze(set :: t()) :: t() | Types.common_errors()
+  def freeze(set) do
+    case NifBridge.freeze(set) do
+      :ok ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Thaws a frozen set, allowing it to be modified again.
+
+  Thawing a set that is not frozen has no effect.
+  """
+  @spec thaw(set :: t()) :: t() | Types.common_errors()
+  def thaw(set) do
+    case NifBridge.thaw(set) do
+      :ok ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Atomically exchanges the contents of two sets.
+
+  This enables the "build a replacement off to the side, then swap it in" pattern, the reference
+  `a` can be stored in other processes' state and will observe the new contents without having to
+  be replaced.  After the swap `b` holds the previous contents of `a`.
+
+  The bucket size and other configuration travel with the contents, metadata attached with
+  `put_meta/2` stays with the reference it was attached to.  If either set is frozen then
+  `{:error, :frozen}` is returned and neither set is modified.
+
+  The `:single_owner` and `:coalesce_writes` options decide how a reference is locked when it is
+  created and cannot travel with the contents, sets that disagree on either option are not
+  swapped and `{:error, :configuration_mismatch}` is returned.
+  """
+  @spec swap(a :: t(), b :: t()) ::
+          t() | {:error, :configuration_mismatch} | Types.frozen_error() | Types.common_errors()
+  def swap(a, b) do
+    case NifBridge.swap(a, b) do
+      :ok ->
+        a
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Splits a set around a pivot into two new sets.
+
+  Returns `{lower, upper}` where `lower` contains every element less than `pivot` and `upper`
+  contains every element greater than or equal to `pivot`, the pivot does not need to be an
+  element of the set.  Both new sets share the configuration of the original set.
+
+  The elements are moved rather than copied, whole buckets are handed to the new sets and only
+  the bucket that straddles the pivot is divided, so the original set is left empty.  This makes
+  re-sharding an oversized set much cheaper than dumping and reloading it.
+  """
+  @spec partition(set :: t(), pivot :: Types.supported_term()) ::
+          {lower :: t(), upper :: t()} | Types.frozen_error() | Types.common_errors()
+  def partition(set, pivot) do
+    case NifBridge.partition(set, pivot) do
+      {:ok, lower, upper} ->
+        {lower, upper}
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Stitches two disjoint sets back together by moving the contents of `b` onto the end of `a`.
+
+  This is intended for data that has been sharded by a naturally increasing key, like time, and
+  needs to be periodically recombined.  Instead of adding every element of `b` to `a` the buckets
+  of `b` are moved as-is, so the cost is proportional to the number of buckets rather than the
+  number of elements.  After a successful concat `a` is returned and `b` is left empty.
+
+  Every element of `b` must be greater than every element of `a`, if the sets overlap then
+  `{:error, :overlapping}` is returned and neither set is modified.  The same is true if the
+  combined set would exceed the `:max_elements` of `a` or if either set is frozen.
+
+  The buckets of `b` are kept as they are, if `b` was built with a larger bucket size than `a` its
+  buckets will be split as elements are added to them.
+
+  Both sets must use the same `:collation`, otherwise `{:error, :collation_mismatch}` is returned.
+  The same is true for the `:comparator` and the `:tuple_order`, a mismatch returns
+  `{:error, :comparator_mismatch}`, and for the `:charlists` option, a mismatch returns
+  `{:error, :charlists_mismatch}`.
+  """
+  @spec concat(a :: t(), b :: t()) ::
+          t()
+          | {:error, :overlapping}
+          | {:error, :collation_mismatch}
+          | {:error, :comparator_mismatch}
+          | {:error, :charlists_mismatch}
+          | {:error, :set_full}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def concat(a, b) do
+    case NifBridge.concat(a, b) do
+      :ok ->
+        a
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Adds every element of `source` to `destination`, returning `destination`.
+
+  Unlike adding the elements one by one from Elixir, both sets are merged in a single linear pass
+  in Rust and the buckets of `destination` are rebuilt from the result.  `source` is left as it
+  is.  Elements `destination` already holds are skipped, the merge is checked against the
+  `:max_elements` of `destination` before anything is added and returns `{:error, :set_full}`
+  without changing it if the merged set would be too large.
+
+  Sets that order their elements differently, because of a `:collation` or a `:comparator`, are
+  merged element by element in the order of `destination`.  Like other bulk operations the merge
+  does not notify subscribers.
+  """
+  @spec merge_into(destination :: t(), source :: t()) ::
+          t() | {:error, :set_full} | Types.frozen_error() | Types.common_errors()
+  def merge_into(destination, source) do
+    case NifBridge.merge_into(destination, source) do
+      {:ok, _added} ->
+        destination
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Constructs a new set holding the distinct elements of every set in a non-empty list.
+
+  The sets are combined with a single k-way merge in Rust that keeps the next element of every
+  set in a heap, so combining dozens of shards costs a few comparisons per element and none of
+  the sets are copied out of NIF space.  The new set uses the configuration of the first set of
+  the list and none of the sets are modified.  For a combined view that is read without being
+  built see `Discord.SortedSet.MergedView`.
+
+  All sets must use the same `:collation`, otherwise `{:error, :collation_mismatch}` is returned.
+  The same is true for the `:comparator`, a mismatch returns `{:error, :comparator_mismatch}`.
+  """
+  @spec merge_many(sets :: [t(), ...]) ::
+          t()
+          | {:error, :set_full}
+          | {:error, :collation_mismatch}
+          | {:error, :comparator_mismatch}
+          | Types.common_errors()
+  def merge_many([_ | _] = sets) do
+    case NifBridge.merge_many(sets) do
+      {:ok, set} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Atomically moves an item from one set to another.
+
+  Both sets are locked for the duration of the move, so no other process can observe the item in
+  both sets or in neither of them.  Returns the index the item was removed from in `from` and the
+  index it now has in `to`.  If `to` already holds the item it is only removed from `from`.
+
+  `{:error, :not_found}` is returned if `from` does not hold the item.  If `to` is full, or either
+  set is frozen, the error is returned and neither set is modified.  Moving within a single set
+  returns `{:error, :same_set}`.
+
+  Subscribers of both sets are notified as if the item had been removed and added separately.
+  """
+  @spec move(from :: t(), to :: t(), item :: any()) ::
+          {from_index :: non_neg_integer(), to_index :: non_neg_integer()}
+          | {:error, :not_found}
+          | {:error, :same_set}
+          | {:error, :set_full}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def move(from, to, item) do
+    case NifBridge.move(from, to, item) do
+      {:ok, from_index, to_index} ->
+        {from_index, to_index}
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Atomically moves every item between `min` and `max`, inclusive, from one set to another.
+
+  This is meant for rebalancing sharded sets.  Instead of removing and adding every item, only the
+  buckets straddling `min` and `max` are divided and the buckets in between are moved as-is.  The
+  buckets are only spliced into `to` when `to` holds nothing within the range and both sets use
+  the same `:collation` and `:comparator`, otherwise the items are added one by one.  Items that
+  `to` already holds are removed from `from`.
+
+  Returns the number of items removed from `from`, the bounds do not have to be items of either
+  set.  If `to` could exceed its `:max_elements`, counting every moved item as new, or either set
+  is frozen then the error is returned and neither set is modified.  Subscribers are not notified
+  of the moved items.
+  """
+  @spec move_range(from :: t(), to :: t(), min :: any(), max :: any()) ::
+          non_neg_integer()
+          | {:error, :same_set}
+          | {:error, :set_full}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def move_range(from, to, min, max) do
+    case NifBridge.move_range(from, to, min, max) do
+      {:ok, count} ->
+        count
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Subscribes a process to mutation events of the set.
+
+  Every successful `add` or `remove` of the set will send a `t:Discord.SortedSet.Types.event/0`
+  message to each subscribed process, so call sites that mutate a set no longer need to publish
+  changes themselves.  Adding a duplicate or removing an element that is not present does not
+  produce an event.  Bulk operations like `concat/2` or `swap/2` do not produce events either,
+  subscribers can detect them through the gap in the `version` of the next event.
+
+  Events are sent while the set is still locked, so they arrive in the order of their versions.
+  Subscribing the same process more than once has no additional effect.  Subscriptions are not
+  monitored, a process that exits stays subscribed until `unsubscribe/2` is called.
+  """
+  @spec subscribe(set :: t(), pid :: pid()) :: t() | Types.common_errors()
+  def subscribe(set, pid \\ self()) do
+    case NifBridge.subscribe(set, pid) do
+      :ok ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Unsubscribes a process from mutation events of the set.
+
+  Unsubscribing a process that is not subscribed has no effect.
+  """
+  @spec unsubscribe(set :: t(), pid :: pid()) :: t() | Types.common_errors()
+  def unsubscribe(set, pid \\ self()) do
+    case NifBridge.unsubscribe(set, pid) do
+      :ok ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Registers a one-shot alert for when the set reaches a watermark.
+
+  The first time the set reaches the `watermark` the NIF sends a
+  `t:Discord.SortedSet.Types.alert/0` message to `pid`, letting operators find out about runaway
+  sets as they happen instead of on the next periodic sweep.  Alerts fire once and are then
+  discarded, register the alert again to keep watching the set.  If the set has already reached
+  the watermark when the alert is registered the message is sent immediately.
+
+  See `t:Discord.SortedSet.Types.watermark/0` for the supported watermarks.  Watermarks are
+  checked by operations that can grow the set, memory watermarks require visiting every bucket so
+  are more expensive to check than size watermarks.
+  """
+  @spec alert(set :: t(), watermark :: Types.watermark(), pid :: pid()) ::
+          t() | Types.common_errors()
+  def alert(set, watermark, pid \\ self()) do
+    case NifBridge.alert(set, watermark, pid) do
+      :ok ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Registers a process to be told about every SortedSet that is garbage collected.
+
+  Whenever the reference of a set is garbage collected the NIF sends a
+  `t:Discord.SortedSet.Types.destroyed/0` message carrying the metadata and the final size of the
+  set to `pid`, which makes it possible to see which sets go away and how big they were.  Only
+  one collector can be registered at a time, registering a process replaces the previous
+  collector.  The collector is not monitored, use `unregister_collector/0` to stop the messages.
+  """
+  @spec register_collector(pid :: pid()) :: :ok | {:error, :lock_fail}
+  def register_collector(pid \\ self()) do
+    NifBridge.register_collector(pid)
+  end
+
+  @doc """
+  Unregisters the collector, see `register_collector/1`.
+  """
+  @spec unregister_collector() :: :ok | {:error, :lock_fail}
+  def unregister_collector() do
+    NifBridge.register_collector(nil)
+  end
+
+  @doc """
+  Returns the number of SortedSets that are alive, the sum of their approximate memory and the
+  number of NIF calls that failed with an internal error since the NIF was loaded.
+
+  The memory of a set is estimated when it is created and refreshed every 64 mutations that grow
+  the set, so the total lags behind the actual usage.  The estimate of a set is removed from the
+  total when the set is garbage collected.
+
+  ## Probe Statistics
+
+  When the NIF is compiled with `PROBE_STATS_NIF=true` the list goes on with counters of the work
+  the buckets of every set have done since the NIF was loaded:
+
+    * `:operations` the number of times an item was located, by adds, removes and lookups.
+    * `:comparisons` the number of comparisons between two items.
+    * `:buckets_probed` the number of buckets whose bounds were compared or whose size was summed
+      up to compute an index.
+    * `:elements_shifted` the number of items moved to make room for an added item, to close the
+      gap of a removed one or to split a bucket.
+
+  Dividing the counters by `:operations` gives the work of an average operation, which is what
+  picking a bucket size trades off.  The counters are not reset, compare two snapshots to look at
+  a stretch of time.  Sets using the `:btree` or `:bitset` backend are not counted.
+  """
+  @spec global_stats() :: [{atom(), non_neg_integer()}]
+  def global_stats() do
+    NifBridge.global_stats()
+  end
+
+  @doc """
+  Converts a SortedSet into a List without blocking the caller.
+
+  Returns a reference immediately and performs the conversion on a separate thread, once the
+  conversion completes a `t:Discord.SortedSet.Types.reply/0` message carrying the reference and
+  the list is sent to the caller.  This lets a GenServer keep serving its mailbox while a large
+  set is copied, use `await/2` to wait for the reply.
+
+  The set is read locked while it is being converted, mutations that happen during that time
+  fail with `{:error, :lock_fail}`.
+  """
+  @spec async_to_list(set :: t()) :: reference() | Types.common_errors()
+  def async_to_list(set) do
+    tag = make_ref()
+
+    case NifBridge.async_to_list(set, tag) do
+      :ok ->
+        tag
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Constructs a new SortedSet from an arbitrary list without blocking the caller.
+
+  This is the asynchronous counterpart of `from_enumerable/3`, the list is sorted, deduplicated
+  and packed into buckets on a separate thread.  Returns a reference immediately, once the set has
+  been built a `t:Discord.SortedSet.Types.reply/0` message with the reference and `{:ok, set}` is
+  sent to the caller.  If the list contains unsupported types the reply carries
+  `{:error, :unsupported_type}` instead.
+  """
+  @spec async_from_list(
+          terms :: [Types.supported_term()],
+          bucket_size :: pos_integer(),
+          options :: Types.options()
+        ) :: reference()
+  def async_from_list(terms, bucket_size \\ @default_bucket_size, options \\ []) do
+    tag = make_ref()
+    :ok = NifBridge.async_from_list(length(terms), bucket_size, options, terms, tag)
+    tag
+  end
+
+  @doc """
+  Constructs the union of two sets as a new set.
+
+  The union is built in Rust with a single linear merge of the buckets of both sets and uses the
+  configuration of `a`, including its `:max_elements`.  Neither `a` nor `b` is modified.  For
+  large sets see `async_union/2`, which builds the union without blocking the caller.
+  """
+  @spec union(a :: t(), b :: t()) :: t() | {:error, :set_full} | Types.common_errors()
+  def union(a, b) do
+    case NifBridge.union(a, b) do
+      {:ok, set} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Constructs the intersection of two sets as a new set, holding the elements present in both.
+
+  Both sets are walked once in order in Rust, so the cost is linear in the size of the sets
+  rather than a lookup per element.  The intersection uses the configuration of `a`.  Neither `a`
+  nor `b` is modified.
+  """
+  @spec intersection(a :: t(), b :: t()) :: t() | {:error, :set_full} | Types.common_errors()
+  def intersection(a, b) do
+    case NifBridge.intersection(a, b) do
+      {:ok, set} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Constructs a new set of the elements of `a` that are not present in `b`.
+
+  Both sets are walked once in order in Rust, so the cost is linear in the size of the sets
+  rather than a lookup per element.  The difference uses the configuration of `a`.  Neither `a`
+  nor `b` is modified.
+  """
+  @spec difference(a :: t(), b :: t()) :: t() | {:error, :set_full} | Types.common_errors()
+  def difference(a, b) do
+    case NifBridge.difference(a, b) do
+      {:ok, set} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Compares the elements of `a` and `b` in order, returning `:lt`, `:eq` or `:gt`.
+
+  The sets are compared like two sorted lists: the first pair of elements that differ decides,
+  otherwise the set that runs out of elements first is the lesser one.  The comparison walks both
+  sets in Rust, so sets can be sorted or used as version vectors without converting them to
+  lists.  On Elixir 1.10 and later `Enum.sort(sets, Discord.SortedSet)` sorts a list of sets.
+
+  Both sets must use the same `:collation`, otherwise `{:error, :collation_mismatch}` is
+  returned.  The same is true for the `:comparator`, a mismatch returns
+  `{:error, :comparator_mismatch}`.
+  """
+  @spec compare(a :: t(), b :: t()) ::
+          :lt
+          | :eq
+          | :gt
+          | {:error, :collation_mismatch}
+          | {:error, :comparator_mismatch}
+          | Types.common_errors()
+  def compare(a, b) do
+    case NifBridge.compare(a, b) do
+      {:ok, ordering} ->
+        ordering
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Lists the changes that turn `old` into `new` as `{added, removed}`.
+
+  `added` holds the elements of `new` missing from `old` and `removed` holds the elements of
+  `old` missing from `new`, both in order.  The sets are compared with a single sweep in Rust, so
+  neither set is copied into a `MapSet` first.  Neither set is modified.
+  """
+  @spec diff(old :: t(), new :: t()) ::
+          {added :: [any()], removed :: [any()]} | Types.common_errors()
+  def diff(old, new) do
+    case NifBridge.diff(old, new) do
+      {:ok, changes} ->
+        changes
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Checks whether `a` and `b` hold the same elements.
+
+  The sizes of the sets are compared first, sets of the same size are then walked side by side
+  in Rust until the first element that differs, so neither set is copied out of NIF space.  The
+  layout of the sets does not matter, sets built with different bucket sizes or backends are
+  equal as long as their elements are.
+  """
+  @spec equal?(a :: t(), b :: t()) :: boolean() | Types.common_errors()
+  def equal?(a, b) do
+    case NifBridge.equal(a, b) do
+      {:ok, equal} ->
+        equal
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Checks whether `a` and `b` have no element in common.
+
+  The buckets of both sets are walked side by side and only the elements of buckets whose ranges
+  overlap are compared, the walk stops at the first element found in both sets.  Sets that only
+  overlap in a few places, like the shards of a partitioned key space, are checked without
+  visiting most of their elements.
+  """
+  @spec disjoint?(a :: t(), b :: t()) :: boolean() | Types.common_errors()
+  def disjoint?(a, b) do
+    case NifBridge.disjoint(a, b) do
+      {:ok, disjoint} ->
+        disjoint
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Constructs the union of two sets without blocking the caller.
+
+  The union is built with a single linear merge of both sets on a separate thread and uses the
+  configuration of `a`.  Returns a reference immediately, once the union has been built a
+  `t:Discord.SortedSet.Types.reply/0` message with the reference and `{:ok, set}` is sent to the
+  caller.  Neither `a` nor `b` is modified.
+  """
+  @spec async_union(a :: t(), b :: t()) :: reference() | Types.common_errors()
+  def async_union(a, b) do
+    tag = make_ref()
+
+    case NifBridge.async_union(a, b, tag) do
+      :ok ->
+        tag
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Waits for the reply of an asynchronous operation.
+
+  Returns the result carried by the reply or `{:error, :timeout}` if no reply arrives within
+  `timeout` milliseconds.
+  """
+  @spec await(tag :: reference(), timeout :: timeout()) :: any() | {:error, :timeout}
+  def await(tag, timeout \\ 5000) do
+    receive do
+      {:sorted_set_reply, ^tag, result} ->
+        result
+    after
+      timeout ->
+        {:error, :timeout}
+    end
+  end
+
+  @doc """
+  Copies the items of the SortedSet into a plain immutable term that is read with
+  `Discord.SortedSet.Static`.
+
+  The term does not reference the NIF at all, which makes it a good fit for `:persistent_term`:
+  read-only replicas can serve lookups by index and by value from it without holding on to the
+  set or taking its lock.  The items are grouped into small tuples so lookups stay logarithmic.
+
+  Lookups by value compare items in term order, sets created with the `:collation`,
+  `:comparator` or `:tuple_order` option can not be exported and return
+  `{:error, :custom_order}`.
+  """
+  @spec export_static(set :: t()) ::
+          Discord.SortedSet.Static.t() | {:error, :custom_order} | Types.common_errors()
+  def export_static(set) do
+    case NifBridge.export_static(set) do
+      {:ok, static} ->
+        static
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Captures the exact state of the SortedSet as a binary that can be attached to a bug report.
+
+  The repro bundle holds the configuration, the bucket layout, the contents, the metadata and the
+  frozen flag of the set.  Exporting the same state always produces the same binary.  The bundle
+  is encoded in the External Term Format, so it can be inspected with `:erlang.binary_to_term/1`.
+
+  Timestamps, the version counter and the values of stable IDs are not captured.
+
+  See `import_repro/1` to replay the bundle.
+  """
+  @spec export_repro(set :: t()) :: binary() | Types.common_errors()
+  def export_repro(set) do
+    case NifBridge.export_repro(set) do
+      {:ok, binary} ->
+        binary
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Rebuilds a SortedSet from a binary produced by `export_repro/1`.
+
+  The buckets are restored exactly as they were exported, without sorting or validating them, so
+  a set that ended up in a bad state is reproduced in that same state.  Sets using the `:btree`
+  backend are bulk loaded from the exported items instead.
+
+  `{:error, :invalid_repro}` is returned if the binary is not a repro bundle.
+  """
+  @spec import_repro(binary :: binary()) :: t() | {:error, :invalid_repro} | Types.common_errors()
+  def import_repro(binary) do
+    case NifBridge.import_repro(binary) do
+      {:ok, set} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Restores the invariants of a SortedSet that ended up in a bad state, for example one replayed
+  with `import_repro/1`, without rebuilding it from the source of its elements.
+
+  Every bucket is sorted and deduplicated, empty buckets are dropped and the size of the set is
+  recounted.  If neighbouring buckets still overlap afterwards every element is sorted and
+  repacked into new buckets.  Sets using the `:btree` backend are always rebuilt from their
+  elements.  Subscribers are not notified of elements that were dropped as duplicates.
+
+  Returns what had to be fixed, a healthy set reports zeros and a `:recorded_size` equal to its
+  `:size`.  A frozen set is not repaired and returns `{:error, :frozen}`.
+  """
+  @spec repair(set :: t()) ::
+          [
+            unsorted_buckets: non_neg_integer(),
+            duplicates: non_neg_integer(),
+            empty_buckets: non_neg_integer(),
+            overlapping_buckets: non_neg_integer(),
+            recorded_size: non_neg_integer(),
+            size: non_neg_integer()
+          ]
+          | Types.frozen_error()
+          | Types.common_errors()
+  def repair(set) do
+    case NifBridge.repair(set) do
+      {:ok, report} ->
+        report
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Drops every tombstone of a SortedSet created with the `:tombstones` option, see
+  `Discord.SortedSet.Types.option/0`.
+
+  Buckets are compacted on their own once their share of tombstones passes the ratio of the set,
+  compacting by hand is useful after removing a large number of elements, for example before a
+  set is kept around unchanged for a while.  Returns the number of tombstones that were dropped,
+  sets without the option always return `0`.
+  """
+  @spec compact(set :: t()) :: non_neg_integer() | Types.common_errors()
+  def compact(set) do
+    case NifBridge.compact(set) do
+      {:ok, removed} ->
+        removed
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Applies the writes queued on a SortedSet created with the `:coalesce_writes` option.
+
+  `add/2` and `remove/2` only queue their writes on such sets, every other function applies the
+  queue before it touches the set.  Reads skip this when another process holds the lock, they
+  may then miss the writes that are still queued.  Flushing applies the queue regardless, for
+  example before handing the set to code that has to see every write.
+
+  Queued writes that fail, like adding to a full or frozen set, are dropped.  Returns the number
+  of writes that were taken off the queue, sets without the option always return `0`.
+  """
+  @spec flush(set :: t()) :: non_neg_integer() | Types.common_errors()
+  def flush(set) do
+    case NifBridge.flush(set) do
+      {:ok, applied} ->
+        applied
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Summarizes the state of the SortedSet in a single call, for dashboards that poll many sets.
+
+  The map holds the `:size` of the set, the number of `:buckets`, the least and greatest
+  elements as `:min` and `:max` (`nil` for an empty set), the `:version` that every mutation
+  increments, whether the set is `:frozen` and the `:memory` estimate in bytes.  Nothing in the
+  summary walks the elements, gathering it costs at most a visit to every bucket.
+  """
+  @spec health(set :: t()) :: Types.health() | Types.common_errors()
+  def health(set) do
+    case NifBridge.health(set) do
+      {:ok, health} ->
+        health
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Renders the metrics of the SortedSet in the Prometheus text exposition format.
+
+  The output covers the size of the set, its memory estimate, the number of mutations, the time
+  of the last mutation, bucket statistics, and the number of subscribers and pending alerts.  It
+  can be served as-is from a scrape endpoint.
+
+  The given labels are attached to every sample, which allows the output of several sets to be
+  told apart, for example `metrics_prometheus(set, shard: "3")`.  Label names must be valid
+  Prometheus label names, values are escaped.
+
+  Since each set renders its own `# HELP` and `# TYPE` lines, the output of several sets should be
+  served from separate scrape targets or merged by metric name before being concatenated.
+  """
+  @spec metrics_prometheus(set :: t(), labels :: Keyword.t() | [{String.t(), String.t()}]) ::
+          String.t() | Types.common_errors()
+  def metrics_prometheus(set, labels \\ []) do
+    labels = Enum.map(labels, fn {name, value} -> {to_string(name), to_string(value)} end)
+
+    case NifBridge.metrics_prometheus(set, labels) do
+      {:ok, text} ->
+        text
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Returns a string representation of the underlying Rust data structure.
+
+  This function is mostly provided as a convenience, since the actual data structure is stored in
+  the NIF memory space it can be difficult to introspect the data structure as it changes.  This
+  function allows the caller to get the view of the data structure as Rust sees it.
+  """
+  @spec debug(set :: t()) :: String.t()
+  def debug(set) do
+    NifBridge.debug(set)
+  end
+
+  @doc """
+  Tallies the allocations held by the SortedSet, for diagnosing suspected leaks on a running node.
+
+  Every item of the set is walked and the allocations reachable from it are counted by category,
+  along with the bytes they hold.  `:buckets` covers the bucket storage as estimated for
+  `global_stats/0`, `:strings` the atoms and binaries, `:tuples` and `:lists` the elements of
+  tuples and lists.  Walking the set is O(N), this is not meant to be called routinely.
+
+  The report is only available when the NIF is built with the `debug-alloc` feature, which is
+  enabled by compiling with `DEBUG_ALLOC_NIF=true`.  Otherwise `{:error, :not_enabled}` is
+  returned.
+  """
+  @spec alloc_report(set :: t()) ::
+          [{category, [count: non_neg_integer(), bytes: non_neg_integer()]}]
+          | {:error, :not_enabled}
+          | Types.common_errors()
+        when category: :buckets | :strings | :tuples | :lists
+  def alloc_report(set) do
+    case NifBridge.alloc_report(set) do
+      {:ok, report} ->
+        report
+
+      other ->
+        other
+    end
   end
 
   @doc """
@@ -0,0 +1,184 @@
+defmodule Discord.SortedSet.Comparison do
+  @moduledoc """
+  Runs the same workloads against SortedSet and the sorted structures that ship with OTP.
+
+  The question of why not use `:gb_sets`, `:ordsets` or an ETS `ordered_set` table comes up often
+  enough that the crate answers it itself.  `run/1` can be called from a shell on any node,
+  including a production node with the real NIF loaded, and returns the timings as a plain term
+  instead of printing them.  See `bench/comparison.exs` for a script that prints them.
+
+  Every workload is timed on a freshly built structure of the requested size holding the
+  integers `0..size - 1` spread out by 10, the reported time is the median over the runs.
+
+    * `:build` adds every item one at a time, in random order.
+    * `:member` looks up every item and as many items that are not present.
+    * `:slice` reads 100 items from the middle of the structure.
+    * `:remove` removes every item, in random order.
+  """
+  alias Discord.SortedSet
+
+  @type structure :: :sorted_set | :gb_sets | :ordsets | :ets
+  @type workload :: :build | :member | :slice | :remove
+
+  @type option ::
+          {:size, pos_integer()}
+          | {:runs, pos_integer()}
+          | {:structures, [structure()]}
+          | {:workloads, [workload()]}
+
+  @type results :: %{
+          size: pos_integer(),
+          runs: pos_integer(),
+          results: %{workload() => %{structure() => microseconds :: non_neg_integer()}}
+        }
+
+  @structures [:sorted_set, :gb_sets, :ordsets, :ets]
+  @workloads [:build, :member, :slice, :remove]
+  @slice_amount 100
+
+  @doc """
+  Runs the comparison, the options default to `size: 10_000, runs: 5` and every structure and
+  workload.  `:ordsets` are plain lists, adding to them is quadratic so keep the size modest.
+  """
+  @spec run(options :: [option()]) :: results()
+  def run(options \\ []) do
+    size = Keyword.get(options, :size, 10_000)
+    runs = Keyword.get(options, :runs, 5)
+    structures = Keyword.get(options, :structures, @structures)
+    workloads = Keyword.get(options, :workloads, @workloads)
+
+    items = Enum.map(0..(size - 1), &(&1 * 10))
+
+    results =
+      Map.new(workloads, fn workload ->
+        timings =
+          Map.new(structures, fn structure ->
+            {structure, median(for _ <- 1..runs, do: time(workload, structure, items))}
+          end)
+
+        {workload, timings}
+      end)
+
+    %{size: size, runs: runs, results: results}
+  end
+
+  defp time(:build, structure, items) do
+    shuffled = Enum.shuffle(items)
+    empty = new(structure)
+
+    {microseconds, built} =
+      :timer.tc(fn -> Enum.reduce(shuffled, empty, &add(structure, &2, &1)) end)
+
+    release(structure, built)
+    microseconds
+  end
+
+  defp time(:member, structure, items) do
+    built = build(structure, items)
+
+    {microseconds, _} =
+      :timer.tc(fn ->
+        Enum.each(items, fn item ->
+          member?(structure, built, item)
+          member?(structure, built, item + 1)
+        end)
+      end)
+
+    release(structure, built)
+    microseconds
+  end
+
+  defp time(:slice, structure, items) do
+    built = build(structure, items)
+    start = div(length(items), 2)
+
+    {microseconds, _} = :timer.tc(fn -> slice(structure, built, start, @slice_amount) end)
+    release(structure, built)
+    microseconds
+  end
+
+  defp time(:remove, structure, items) do
+    built = build(structure, items)
+    shuffled = Enum.shuffle(items)
+
+    {microseconds, emptied} =
+      :timer.tc(fn -> Enum.reduce(shuffled, built, &remove(structure, &2, &1)) end)
+
+    release(structure, emptied)
+    microseconds
+  end
+
+  defp median(timings) do
+    timings
+    |> Enum.sort()
+    |> Enum.at(div(length(timings), 2))
+  end
+
+  # Structures are built outside of the timed section with the fastest constructor each offers
+  defp build(:sorted_set, items), do: SortedSet.from_proper_enumerable(items)
+  defp build(:gb_sets, items), do: :gb_sets.from_ordset(items)
+  defp build(:ordsets, items), do: :ordsets.from_list(items)
+
+  defp build(:ets, items) do
+    table = new(:ets)
+    :ets.insert(table, Enum.map(items, &{&1}))
+    table
+  end
+
+  defp new(:sorted_set), do: SortedSet.new()
+  defp new(:gb_sets), do: :gb_sets.new()
+  defp new(:ordsets), do: :ordsets.new()
+  defp new(:ets), do: :ets.new(__MODULE__, [:ordered_set, :public])
+
+  defp release(:ets, table), do: :ets.delete(table)
+  defp release(_, _), do: :ok
+
+  defp add(:sorted_set, set, item), do: SortedSet.add(set, item)
+  defp add(:gb_sets, set, item), do: :gb_sets.add_element(item, set)
+  defp add(:ordsets, set, item), do: :ordsets.add_element(item, set)
+
+  defp add(:ets, table, item) do
+    :ets.insert(table, {item})
+    table
+  end
+
+  defp member?(:sorted_set, set, item), do: SortedSet.find_index(set, item) != nil
+  defp member?(:gb_sets, set, item), do: :gb_sets.is_element(item, set)
+  defp member?(:ordsets, set, item), do: :ordsets.is_element(item, set)
+  defp member?(:ets, table, item), do: :ets.member(table, item)
+
+  defp slice(:sorted_set, set, start, amount), do: SortedSet.slice(set, start, amount)
+  defp slice(:ordsets, set, start, amount), do: Enum.slice(set, start, amount)
+
+  # Neither gb_sets nor ETS can seek to an index, both have to walk to the start of the slice
+  defp slice(:gb_sets, set, start, amount) do
+    set
+    |> :gb_sets.iterator()
+    |> Stream.unfold(fn iterator ->
+      case :gb_sets.next(iterator) do
+        :none -> nil
+        next -> next
+      end
+    end)
+    |> Enum.slice(start, amount)
+  end
+
+  defp slice(:ets, table, start, amount) do
+    table
+    |> :ets.first()
+    |> Stream.unfold(fn
+      :"$end_of_table" -> nil
+      key -> {key, :ets.next(table, key)}
+    end)
+    |> Enum.slice(start, amount)
+  end
+
+  defp remove(:sorted_set, set, item), do: SortedSet.remove(set, item)
+  defp remove(:gb_sets, set, item), do: :gb_sets.del_element(item, set)
+  defp remove(:ordsets, set, item), do: :ordsets.del_element(item, set)
+
+  defp remove(:ets, table, item) do
+    :ets.delete(table, item)
+    table
+  end
+end
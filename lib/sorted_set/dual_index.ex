@@ -0,0 +1,185 @@
+defmodule Discord.SortedSet.DualIndex do
+  @moduledoc """
+  The same items kept in two different orders behind a single reference.
+
+  Keeping members ordered both by name and by join date used to take two SortedSets that every
+  mutation had to be applied to, and a crash or a missed update in between left them disagreeing.
+  A DualIndex holds both orders in one resource, every add and remove is applied to both before
+  it returns and reads name the order they want, `:primary` or `:secondary`.
+
+  Each order takes the options of `Discord.SortedSet.new/3`, typically a `:comparator` ordering
+  the same tuples by different elements.  Both orders hold the same items so the `:max_elements`
+  of the primary options limits both, the `:single_owner` and `:coalesce_writes` options are not
+  supported.
+
+      # {name, joined_at}, by name and by join date
+      members =
+        DualIndex.new([comparator: [{0, :asc}]], comparator: [{1, :asc}])
+        |> DualIndex.add({"bob", 1_614_556_800})
+        |> DualIndex.add({"alice", 1_642_204_800})
+
+      DualIndex.to_list(members, :secondary)
+      # => [{"bob", 1_614_556_800}, {"alice", 1_642_204_800}]
+  """
+  alias Discord.SortedSet.{NifBridge, Types}
+
+  @type t :: Types.dual_index()
+  @type order :: :primary | :secondary
+
+  @default_bucket_size 500
+
+  @doc """
+  Construct an empty DualIndex, the bucket size applies to both orders.
+  """
+  @spec new(
+          primary_options :: Types.options(),
+          secondary_options :: Types.options(),
+          bucket_size :: pos_integer()
+        ) :: t()
+  def new(primary_options \\ [], secondary_options \\ [], bucket_size \\ @default_bucket_size) do
+    {:ok, set} = NifBridge.dual_index_new(0, bucket_size, primary_options, secondary_options)
+    set
+  end
+
+  @doc """
+  Adds an item to both orders.
+  """
+  @spec add(set :: t(), item :: any()) :: t() | {:error, :set_full} | Types.common_errors()
+  def add(set, item) do
+    case NifBridge.dual_index_add(set, item) do
+      {:ok, _, _, _} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Adds an item to both orders, returning the `{primary, secondary}` indexes of the item.
+
+  If the item was already present `nil` is returned instead of the indexes.
+  """
+  @spec index_add(set :: t(), item :: any()) ::
+          {{non_neg_integer(), non_neg_integer()} | nil, t()}
+          | {:error, :set_full}
+          | Types.common_errors()
+  def index_add(set, item) do
+    case NifBridge.dual_index_add(set, item) do
+      {:ok, :added, primary, secondary} ->
+        {{primary, secondary}, set}
+
+      {:ok, :duplicate, _, _} ->
+        {nil, set}
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Removes an item from both orders.
+
+  Removing an item that is not present returns the DualIndex unchanged.
+  """
+  @spec remove(set :: t(), item :: any()) :: t() | Types.common_errors()
+  def remove(set, item) do
+    case NifBridge.dual_index_remove(set, item) do
+      {:ok, :removed, _, _} ->
+        set
+
+      {:error, :not_found} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Removes an item from both orders, returning the `{primary, secondary}` indexes the item was
+  removed from.
+
+  If the item was not present `nil` is returned instead of the indexes.
+  """
+  @spec index_remove(set :: t(), item :: any()) ::
+          {{non_neg_integer(), non_neg_integer()} | nil, t()} | Types.common_errors()
+  def index_remove(set, item) do
+    case NifBridge.dual_index_remove(set, item) do
+      {:ok, :removed, primary, secondary} ->
+        {{primary, secondary}, set}
+
+      {:error, :not_found} ->
+        {nil, set}
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Get the number of items, which is the same in both orders.
+  """
+  @spec size(set :: t()) :: non_neg_integer() | Types.common_errors()
+  def size(set) do
+    NifBridge.dual_index_size(set)
+  end
+
+  @doc """
+  Converts one order into a List.
+  """
+  @spec to_list(set :: t(), order :: order()) :: [Types.supported_term()] | Types.common_errors()
+  def to_list(set, order) do
+    NifBridge.dual_index_to_list(set, order)
+  end
+
+  @doc """
+  Retrieve an item at the given index of one order, the default is returned if the index is out
+  of bounds.
+  """
+  @spec at(set :: t(), order :: order(), index :: non_neg_integer(), default :: any()) ::
+          (item_or_default :: Types.supported_term() | any()) | Types.common_errors()
+  def at(set, order, index, default \\ nil) do
+    case NifBridge.dual_index_at(set, order, index) do
+      {:ok, item} ->
+        item
+
+      {:error, {:index_out_of_bounds, _, _}} ->
+        default
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Retrieves a slice of one order, see `Discord.SortedSet.slice/3`.
+  """
+  @spec slice(
+          set :: t(),
+          order :: order(),
+          start :: non_neg_integer(),
+          amount :: non_neg_integer()
+        ) :: [Types.supported_term()] | {:error, :invalid_range} | Types.common_errors()
+  def slice(set, order, start, amount) do
+    NifBridge.dual_index_slice(set, order, start, amount)
+  end
+
+  @doc """
+  Finds the index of the item in one order, `nil` is returned if the item is not present.
+  """
+  @spec find_index(set :: t(), order :: order(), item :: any()) ::
+          non_neg_integer() | nil | Types.common_errors()
+  def find_index(set, order, item) do
+    case NifBridge.dual_index_find_index(set, order, item) do
+      {:ok, index} ->
+        index
+
+      {:error, :not_found} ->
+        nil
+
+      other ->
+        other
+    end
+  end
+end
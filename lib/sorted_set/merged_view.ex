@@ -0,0 +1,98 @@
+defmodule Discord.SortedSet.MergedView do
+  @moduledoc """
+  A read-only view over several SortedSets that reads as if it were a single set.
+
+  Sets that are sharded, by region for example, can be read in their global order through a
+  merged view without building their union.  Every read merges the sets on the fly, elements held
+  by more than one set are returned once.  The view holds references to the sets, mutations of
+  the sets are visible through the view immediately.
+
+  All sets of a view must use the same `:collation` and `:comparator`, reads of a view over sets
+  that order their elements differently return `{:error, :collation_mismatch}` or
+  `{:error, :comparator_mismatch}`.
+
+  ## Performance
+
+  `at/3` and `slice/3` have to merge every element in front of the requested index, so they are
+  O(N) in the index.  Iterators remember where they are and only merge the elements they return,
+  use them to walk a view.
+  """
+  alias Discord.SortedSet
+  alias Discord.SortedSet.{NifBridge, Types}
+
+  @type t :: Types.merged_view()
+
+  @type merge_errors :: {:error, :collation_mismatch} | {:error, :comparator_mismatch}
+
+  @doc """
+  Construct a merged view over the given sets.
+  """
+  @spec new(sets :: [SortedSet.t()]) :: t() | Types.common_errors()
+  def new(sets) do
+    case NifBridge.merged_view(sets) do
+      {:ok, view} ->
+        view
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Retrieve the element at the given index of the view, the default is returned if the index is
+  out of bounds.
+  """
+  @spec at(view :: t(), index :: non_neg_integer(), default :: any()) ::
+          (item_or_default :: Types.supported_term() | any())
+          | merge_errors()
+          | Types.common_errors()
+  def at(view, index, default \\ nil) do
+    case NifBridge.merged_view_at(view, index) do
+      {:ok, item} ->
+        item
+
+      {:error, {:index_out_of_bounds, _, _}} ->
+        default
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Retrieves a slice of the view, see `Discord.SortedSet.slice/3`.
+  """
+  @spec slice(view :: t(), start :: non_neg_integer(), amount :: non_neg_integer()) ::
+          [Types.supported_term()]
+          | {:error, :invalid_range}
+          | merge_errors()
+          | Types.common_errors()
+  def slice(view, start, amount) do
+    NifBridge.merged_view_slice(view, start, amount)
+  end
+
+  @doc """
+  Creates an iterator that walks the view in either direction, see `Discord.SortedSet.iterator/2`
+  for the options.  Snapshots are not supported.
+  """
+  @spec iterator(view :: t(), options :: [Types.iterator_option()]) ::
+          Types.merged_view_iterator() | merge_errors() | Types.common_errors()
+  def iterator(view, options \\ []) do
+    case NifBridge.merged_view_iterator(view, options) do
+      {:ok, iterator} ->
+        iterator
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Reads up to `count` elements from an iterator created by `iterator/2` and advances past them.
+  """
+  @spec iterator_next(iterator :: Types.merged_view_iterator(), count :: pos_integer()) ::
+          [Types.supported_term()] | merge_errors() | Types.common_errors()
+  def iterator_next(iterator, count \\ 1) do
+    NifBridge.merged_view_iterator_next(iterator, count)
+  end
+end
@@ -0,0 +1,197 @@
+defmodule Discord.SortedSet.Namespaces do
+  @moduledoc """
+  Many small SortedSets kept behind a single reference.
+
+  Every SortedSet is a resource of its own, with its own lock and bookkeeping.  When a service
+  keeps thousands of tiny sets, one per channel for example, that overhead adds up.  Namespaces
+  holds any number of named sub-sets in one resource that share a configuration and a lock, the
+  namespace is passed along with every operation.
+
+  A namespace is created by the first item added to it and dropped once its last item is
+  removed, there is no need to create or clean up namespaces.  Namespaces can be any supported
+  term.
+
+  The options of `Discord.SortedSet.new/3` apply to every namespace, `:max_elements` limits each
+  namespace separately.  The `:single_owner` and `:coalesce_writes` options are not supported.
+  """
+  alias Discord.SortedSet.{NifBridge, Types}
+
+  @type t :: Types.namespaces()
+
+  @default_bucket_size 500
+
+  @doc """
+  Construct an empty Namespaces resource, the bucket size and options apply to every namespace.
+  """
+  @spec new(bucket_size :: pos_integer(), options :: Types.options()) :: t()
+  def new(bucket_size \\ @default_bucket_size, options \\ []) do
+    {:ok, namespaces} = NifBridge.namespaces_new(0, bucket_size, options)
+    namespaces
+  end
+
+  @doc """
+  Adds an item to a namespace.
+  """
+  @spec add(namespaces :: t(), namespace :: any(), item :: any()) ::
+          t() | {:error, :set_full} | Types.common_errors()
+  def add(namespaces, namespace, item) do
+    case NifBridge.namespaces_add(namespaces, namespace, item) do
+      {:ok, _, _} ->
+        namespaces
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Adds an item to a namespace, returning the index of the item within the namespace.
+
+  If the item was already present the index `nil` is returned.
+  """
+  @spec index_add(namespaces :: t(), namespace :: any(), item :: any()) ::
+          {index :: non_neg_integer() | nil, t()} | {:error, :set_full} | Types.common_errors()
+  def index_add(namespaces, namespace, item) do
+    case NifBridge.namespaces_add(namespaces, namespace, item) do
+      {:ok, :added, index} ->
+        {index, namespaces}
+
+      {:ok, :duplicate, _} ->
+        {nil, namespaces}
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Removes an item from a namespace.
+
+  Removing an item that is not present returns the Namespaces unchanged.
+  """
+  @spec remove(namespaces :: t(), namespace :: any(), item :: any()) ::
+          t() | Types.common_errors()
+  def remove(namespaces, namespace, item) do
+    case NifBridge.namespaces_remove(namespaces, namespace, item) do
+      {:ok, :removed, _} ->
+        namespaces
+
+      {:error, :not_found} ->
+        namespaces
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Removes an item from a namespace, returning the index the item was removed from.
+
+  If the item was not present the index `nil` is returned.
+  """
+  @spec index_remove(namespaces :: t(), namespace :: any(), item :: any()) ::
+          {index :: non_neg_integer() | nil, t()} | Types.common_errors()
+  def index_remove(namespaces, namespace, item) do
+    case NifBridge.namespaces_remove(namespaces, namespace, item) do
+      {:ok, :removed, index} ->
+        {index, namespaces}
+
+      {:error, :not_found} ->
+        {nil, namespaces}
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Drops a namespace along with all of its items, returning the number of items dropped.
+  """
+  @spec delete(namespaces :: t(), namespace :: any()) ::
+          non_neg_integer() | Types.common_errors()
+  def delete(namespaces, namespace) do
+    case NifBridge.namespaces_delete(namespaces, namespace) do
+      {:ok, size} ->
+        size
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Lists the namespaces that hold at least one item, in term order.
+  """
+  @spec namespaces(namespaces :: t()) :: [Types.supported_term()] | Types.common_errors()
+  def namespaces(namespaces) do
+    NifBridge.namespaces_list(namespaces)
+  end
+
+  @doc """
+  Get the size of a namespace, namespaces without items have size 0.
+  """
+  @spec size(namespaces :: t(), namespace :: any()) ::
+          non_neg_integer() | Types.common_errors()
+  def size(namespaces, namespace) do
+    NifBridge.namespaces_size(namespaces, namespace)
+  end
+
+  @doc """
+  Converts a namespace into a List.
+  """
+  @spec to_list(namespaces :: t(), namespace :: any()) ::
+          [Types.supported_term()] | Types.common_errors()
+  def to_list(namespaces, namespace) do
+    NifBridge.namespaces_to_list(namespaces, namespace)
+  end
+
+  @doc """
+  Retrieve an item at the given index of a namespace, the default is returned if the index is
+  out of bounds.
+  """
+  @spec at(namespaces :: t(), namespace :: any(), index :: non_neg_integer(), default :: any()) ::
+          (item_or_default :: Types.supported_term() | any()) | Types.common_errors()
+  def at(namespaces, namespace, index, default \\ nil) do
+    case NifBridge.namespaces_at(namespaces, namespace, index) do
+      {:ok, item} ->
+        item
+
+      {:error, {:index_out_of_bounds, _, _}} ->
+        default
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Retrieves a slice of a namespace, see `Discord.SortedSet.slice/3`.
+  """
+  @spec slice(
+          namespaces :: t(),
+          namespace :: any(),
+          start :: non_neg_integer(),
+          amount :: non_neg_integer()
+        ) :: [Types.supported_term()] | {:error, :invalid_range} | Types.common_errors()
+  def slice(namespaces, namespace, start, amount) do
+    NifBridge.namespaces_slice(namespaces, namespace, start, amount)
+  end
+
+  @doc """
+  Finds the index of the item within a namespace, `nil` is returned if the item is not present.
+  """
+  @spec find_index(namespaces :: t(), namespace :: any(), item :: any()) ::
+          non_neg_integer() | nil | Types.common_errors()
+  def find_index(namespaces, namespace, item) do
+    case NifBridge.namespaces_find_index(namespaces, namespace, item) do
+      {:ok, index} ->
+        index
+
+      {:error, :not_found} ->
+        nil
+
+      other ->
+        other
+    end
+  end
+end
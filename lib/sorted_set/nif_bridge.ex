@@ -20,20 +20,31 @@ defmodule Discord.SortedSet.NifBridge do
 
   To prevent copying the set into and out of NIF space, the NIF returns an opaque reference handle
   that should be used in all subsequent calls to identify the SortedSet.
+
+  See `t:Discord.SortedSet.Types.options/0` for the supported options.
   """
-  @spec new(capacity :: pos_integer(), bucket_size :: pos_integer()) :: {:ok, SortedSet.t()}
-  def new(_capacity, _bucket_size), do: :erlang.nif_error(:nif_not_loaded)
+  @spec new(
+          capacity :: pos_integer(),
+          bucket_size :: pos_integer(),
+          options :: Types.options()
+        ) :: {:ok, SortedSet.t()}
+  def new(_capacity, _bucket_size, _options), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
   Creates an empty SortedSet.
 
   This is mostly an internal implementation detail, it is used to implement the
   `Discord.SortedSet.from_enumerable/2` and `Discord.SortedSet.from_proper_enumerable/2`
-  functions.  The only valid operation that can be performed on an `empty` `Discord.SortedSet` is
-  `append_bucket/2`, all other functions expect that the bucket not be completely empty.
+  functions.  An `empty` `Discord.SortedSet` starts without any buckets so that `append_bucket/2`
+  can fill it bucket by bucket, every other operation works as well and the first `add/2` creates
+  the first bucket.
   """
-  @spec empty(capacity :: pos_integer(), bucket_size :: pos_integer()) :: {:ok, SortedSet.t()}
-  def empty(_capacity, _bucket_size), do: :erlang.nif_error(:nif_not_loaded)
+  @spec empty(
+          capacity :: pos_integer(),
+          bucket_size :: pos_integer(),
+          options :: Types.options()
+        ) :: {:ok, SortedSet.t()}
+  def empty(_capacity, _bucket_size, _options), do: :erlang.nif_error(:nif_not_loaded)
 
   @doc """
   Appends a buckets worth of sorted terms to the SortedSet
@@ -46,9 +57,26 @@ defmodule Discord.SortedSet.NifBridge do
   for a safer and more ergonomic experience, use great care when calling this function directly.
   """
   @spec append_bucket(set :: SortedSet.t(), terms :: [Types.supported_term()]) ::
-          :ok | Types.nif_append_bucket_result() | Types.common_errors()
+          :ok | Types.nif_append_bucket_result() | Types.frozen_error() | Types.common_errors()
   def append_bucket(_set, _terms), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Constructs a SortedSet from a binary produced by `:erlang.term_to_binary/1` holding a list of
+  terms.  The binary is decoded in Rust on a dirty CPU scheduler.
+  """
+  @spec from_etf_binary(
+          capacity :: pos_integer(),
+          bucket_size :: pos_integer(),
+          options :: Types.options(),
+          binary :: binary()
+        ) ::
+          {:ok, SortedSet.t()}
+          | {:error, :invalid_etf}
+          | {:error, :unsupported_type}
+          | {:error, :set_full}
+  def from_etf_binary(_capacity, _bucket_size, _options, _binary),
+    do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Gets the size of the SortedSet.
 
@@ -61,30 +89,310 @@ defmodule Discord.SortedSet.NifBridge do
   @doc """
   Adds an item to the SortedSet.
   """
-  @spec add(set :: SortedSet.t(), item :: any()) :: Types.nif_add_result() | Types.common_errors()
+  @spec add(set :: SortedSet.t(), item :: any()) ::
+          Types.nif_add_result() | Types.frozen_error() | Types.common_errors()
   def add(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Adds an item to the SortedSet using the hint as the expected index of the item, if the hint is
+  wrong the item is added as if no hint had been given.
+  """
+  @spec add(set :: SortedSet.t(), item :: any(), hint :: non_neg_integer()) ::
+          Types.nif_add_result() | Types.frozen_error() | Types.common_errors()
+  def add(_set, _item, _hint), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Adds an item to the SortedSet, the options select the hint and which index is returned.  With
+  the `:unsupported` option the number of nested terms that were skipped or replaced is added to
+  the result.
+  """
+  @spec add_with_options(set :: SortedSet.t(), item :: any(), options :: [Types.add_option()]) ::
+          {:ok, :added, non_neg_integer() | nil}
+          | {:ok, :duplicate, non_neg_integer()}
+          | {:ok, :added, non_neg_integer() | nil, sanitized :: non_neg_integer()}
+          | {:ok, :duplicate, non_neg_integer(), sanitized :: non_neg_integer()}
+          | {:error, :set_full}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def add_with_options(_set, _item, _options), do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Removes an item from the SortedSet.
   """
   @spec remove(set :: SortedSet.t(), item :: any()) ::
-          Types.nif_remove_result() | Types.common_errors()
+          Types.nif_remove_result()
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
   def remove(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Removes an item from the SortedSet, with `missing: :noop` an item that is not present returns
+  `{:ok, :noop}` instead of `{:error, :not_found}`.
+  """
+  @spec remove(set :: SortedSet.t(), item :: any(), options :: [Types.remove_option()]) ::
+          Types.nif_remove_result()
+          | {:ok, :noop}
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
+  def remove(_set, _item, _options), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Removes and returns the item at the specified index of the SortedSet.
+  """
+  @spec pop_at(set :: SortedSet.t(), index :: integer()) ::
+          {:ok, Types.supported_term()}
+          | Types.index_out_of_bounds_error()
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
+  def pop_at(_set, _index), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Removes and returns the least item of the SortedSet.
+  """
+  @spec pop_first(set :: SortedSet.t()) ::
+          {:ok, Types.supported_term()}
+          | {:error, :empty}
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
+  def pop_first(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Removes and returns the greatest item of the SortedSet.
+  """
+  @spec pop_last(set :: SortedSet.t()) ::
+          {:ok, Types.supported_term()}
+          | {:error, :empty}
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
+  def pop_last(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Removes the item at the specified index from the SortedSet.
+  """
+  @spec remove_at(set :: SortedSet.t(), index :: integer()) ::
+          {:ok, :removed, index :: non_neg_integer()}
+          | Types.index_out_of_bounds_error()
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
+  def remove_at(_set, _index), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Removes the items at the given indexes from the SortedSet, returning them in index order.
+  """
+  @spec remove_at_many(set :: SortedSet.t(), indexes :: [non_neg_integer()]) ::
+          {:ok, [Types.supported_term()]}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def remove_at_many(_set, _indexes), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Removes the items selected by an Elixir Range from the SortedSet, following `Enum.slice/2`,
+  returning them in index order.
+  """
+  @spec remove_range(set :: SortedSet.t(), range :: Range.t()) ::
+          {:ok, [Types.supported_term()]}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def remove_range(_set, _range), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Returns the item equal to the given term and atomically replaces or removes it.
+  """
+  @spec get_and_update(
+          set :: SortedSet.t(),
+          item :: any(),
+          replacement :: {:replace, any()} | :pop
+        ) ::
+          {:ok, :found, any()}
+          | {:ok, :not_found}
+          | {:error, :set_full}
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
+  def get_and_update(_set, _item, _replacement), do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Retrieve the item at the specified index
   """
-  @spec at(set :: SortedSet.t(), index :: non_neg_integer()) ::
+  @spec at(set :: SortedSet.t(), index :: integer()) ::
           Types.nif_at_result() | Types.common_errors()
   def at(_set, _index), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Retrieve the items at the specified indexes or selected by an Elixir Range, the default takes
+  the place of indexes that are out of bounds
+  """
+  @spec at_many(set :: SortedSet.t(), indexes :: [integer()] | Range.t(), default :: any()) ::
+          {:ok, [any()]} | Types.common_errors()
+  def at_many(_set, _indexes, _default), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieve the least item of the SortedSet
+  """
+  @spec first(set :: SortedSet.t()) ::
+          {:ok, Types.supported_term()} | {:error, :empty} | Types.common_errors()
+  def first(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieve the greatest item of the SortedSet
+  """
+  @spec last(set :: SortedSet.t()) ::
+          {:ok, Types.supported_term()} | {:error, :empty} | Types.common_errors()
+  def last(_set), do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Retrieve a slice of starting at the start index and taking up to amount
   """
-  @spec slice(set :: SortedSet.t(), start :: non_neg_integer(), amount :: non_neg_integer()) ::
-          [any()] | Types.common_errors()
+  @spec slice(set :: SortedSet.t(), start :: integer(), amount :: non_neg_integer()) ::
+          [any()] | {:error, :invalid_range} | Types.common_errors()
   def slice(_set, _start, _amount), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Retrieve a slice in descending order starting `start_from_end` items from the end and taking
+  up to amount
+  """
+  @spec slice_desc(
+          set :: SortedSet.t(),
+          start_from_end :: non_neg_integer(),
+          amount :: non_neg_integer()
+        ) :: [any()] | {:error, :invalid_range} | Types.common_errors()
+  def slice_desc(_set, _start_from_end, _amount), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieve every step-th item of the slice starting at the start index and taking up to amount
+  """
+  @spec slice_step(
+          set :: SortedSet.t(),
+          start :: non_neg_integer(),
+          amount :: non_neg_integer(),
+          step :: pos_integer()
+        ) :: [any()] | {:error, :invalid_range} | Types.common_errors()
+  def slice_step(_set, _start, _amount, _step), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieve the items selected by an Elixir Range, following `Enum.slice/2`
+  """
+  @spec slice_range(set :: SortedSet.t(), range :: Range.t()) :: [any()] | Types.common_errors()
+  def slice_range(_set, _range), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Aggregate the integers of the slice starting at the start index and taking up to amount
+  """
+  @spec aggregate(
+          set :: SortedSet.t(),
+          start :: non_neg_integer(),
+          amount :: non_neg_integer(),
+          aggregate :: :sum | :min | :max | :avg
+        ) ::
+          {:ok, number() | nil}
+          | {:error, :invalid_range | :not_integer | :overflow}
+          | Types.common_errors()
+  def aggregate(_set, _start, _amount, _aggregate), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Count the tuples in the SortedSet by the value of the element at the given index
+  """
+  @spec group_counts(set :: SortedSet.t(), index :: non_neg_integer()) ::
+          {:ok, %{optional(any()) => pos_integer()}} | Types.common_errors()
+  def group_counts(_set, _index), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Run a fold over up to budget items following the cursor
+  """
+  @spec fold(
+          set :: SortedSet.t(),
+          fold :: Types.fold_spec(),
+          cursor :: Types.fold_cursor() | nil,
+          budget :: pos_integer()
+        ) ::
+          {:ok, any()}
+          | {:cont, Types.fold_cursor()}
+          | Types.fold_errors()
+          | Types.common_errors()
+  def fold(_set, _fold, _cursor, _budget), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Splits the SortedSet into lists of chunk_size items
+  """
+  @spec chunks(set :: SortedSet.t(), chunk_size :: pos_integer()) ::
+          [[any()]] | Types.common_errors()
+  def chunks(_set, _chunk_size), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Pairs up the items at the same indexes of two SortedSets over the given range
+  """
+  @spec zip(
+          a :: SortedSet.t(),
+          b :: SortedSet.t(),
+          start :: non_neg_integer(),
+          amount :: non_neg_integer()
+        ) :: [{any(), any()}] | {:error, :invalid_range} | Types.common_errors()
+  def zip(_a, _b, _start, _amount), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieve several slices under a single lock acquisition, each window is a `{start, amount}`
+  tuple with the same semantics as `slice/3`.
+  """
+  @spec multi_slice(
+          set :: SortedSet.t(),
+          windows :: [{start :: non_neg_integer(), amount :: non_neg_integer()}]
+        ) :: [[any()]] | {:error, :invalid_range} | Types.common_errors()
+  def multi_slice(_set, _windows), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Counts the items of the SortedSet less than the specified item
+  """
+  @spec bisect_left(set :: SortedSet.t(), item :: any()) ::
+          {:ok, non_neg_integer()} | {:error, :unsupported_type} | Types.common_errors()
+  def bisect_left(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Counts the items of the SortedSet less than or equal to the specified item
+  """
+  @spec bisect_right(set :: SortedSet.t(), item :: any()) ::
+          {:ok, non_neg_integer()} | {:error, :unsupported_type} | Types.common_errors()
+  def bisect_right(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Finds the greatest item of the SortedSet less than or equal to the specified item
+  """
+  @spec floor(set :: SortedSet.t(), item :: any()) ::
+          {:ok, Types.supported_term()}
+          | {:error, :not_found}
+          | {:error, :unsupported_type}
+          | Types.common_errors()
+  def floor(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Finds the least item of the SortedSet greater than or equal to the specified item
+  """
+  @spec ceiling(set :: SortedSet.t(), item :: any()) ::
+          {:ok, Types.supported_term()}
+          | {:error, :not_found}
+          | {:error, :unsupported_type}
+          | Types.common_errors()
+  def ceiling(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Checks whether the SortedSet holds the specified item
+  """
+  @spec contains(set :: SortedSet.t(), item :: any()) ::
+          {:ok, boolean()} | {:error, :unsupported_type} | Types.common_errors()
+  def contains(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Checks whether the SortedSet holds each of the specified items
+  """
+  @spec contains_many(set :: SortedSet.t(), items :: [any()]) ::
+          {:ok, [boolean()]} | {:error, :unsupported_type} | Types.common_errors()
+  def contains_many(_set, _items), do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Finds the index of the specified item
   """
@@ -92,6 +400,48 @@ defmodule Discord.SortedSet.NifBridge do
           Types.nif_find_result() | Types.common_errors()
   def find_index(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Finds the index of the specified item with options, see `Discord.SortedSet.find_index/3`
+  """
+  @spec find_index(set :: SortedSet.t(), item :: any(), options :: [Types.find_index_option()]) ::
+          Types.nif_find_result()
+          | {:not_found, index :: non_neg_integer()}
+          | Types.common_errors()
+  def find_index(_set, _item, _options), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Finds the page of page_size items holding the specified item and reads the page
+  """
+  @spec page_of(set :: SortedSet.t(), item :: any(), page_size :: pos_integer()) ::
+          {:ok, page :: non_neg_integer(), offset :: non_neg_integer(), items :: [any()]}
+          | {:error, :not_found}
+          | Types.common_errors()
+  def page_of(_set, _item, _page_size), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Returns the stable ID of the specified item, the SortedSet must have been created with the
+  `:stable_ids` option.
+  """
+  @spec id_of(set :: SortedSet.t(), item :: any()) ::
+          Types.nif_id_of_result() | Types.common_errors()
+  def id_of(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieves the item with the specified stable ID, the SortedSet must have been created with the
+  `:stable_ids` option.
+  """
+  @spec get_by_id(set :: SortedSet.t(), id :: pos_integer()) ::
+          Types.nif_get_by_id_result() | Types.common_errors()
+  def get_by_id(_set, _id), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Estimates the number of distinct items across the SortedSets, every SortedSet must have been
+  created with the `:cardinality_sketch` option.
+  """
+  @spec estimate_union_size(sets :: [SortedSet.t()]) ::
+          {:ok, non_neg_integer()} | {:error, :sketch_disabled} | Types.common_errors()
+  def estimate_union_size(_sets), do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Converts a SortedSet into a standard list
 
@@ -101,6 +451,715 @@ defmodule Discord.SortedSet.NifBridge do
   @spec to_list(set :: SortedSet.t()) :: [any()] | Types.common_errors()
   def to_list(_set), do: :erlang.nif_error(:nif_not_loaded)
 
+  @doc """
+  Converts a SortedSet into a standard list in descending order
+
+  The buckets are walked back-to-front in NIF space, so no reversal is needed in BEAM VM space.
+  """
+  @spec to_list_reverse(set :: SortedSet.t()) :: [any()] | Types.common_errors()
+  def to_list_reverse(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Moves every element out of a SortedSet into a standard list, leaving the SortedSet empty.
+
+  Unlike `to_list/1` the elements are not cloned in NIF space before being copied into BEAM VM
+  space, so the peak memory usage is roughly halved.
+  """
+  @spec into_list(set :: SortedSet.t()) :: [any()] | Types.frozen_error() | Types.common_errors()
+  def into_list(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates an iterator over a SortedSet, see `Discord.SortedSet.iterator/2`.
+  """
+  @spec iterator(set :: SortedSet.t(), options :: [Types.iterator_option()]) ::
+          {:ok, Types.iterator()} | Types.common_errors()
+  def iterator(_set, _options), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Reads up to count elements from an iterator and advances past them.
+  """
+  @spec iterator_next(iterator :: Types.iterator(), count :: non_neg_integer()) ::
+          [any()] | Types.common_errors()
+  def iterator_next(_iterator, _count), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates a read-only view merging the given SortedSets.
+  """
+  @spec merged_view(sets :: [SortedSet.t()]) :: {:ok, Types.merged_view()} | Types.common_errors()
+  def merged_view(_sets), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieves the item at the given index of a merged view.
+  """
+  @spec merged_view_at(view :: Types.merged_view(), index :: non_neg_integer()) ::
+          {:ok, any()}
+          | Types.index_out_of_bounds_error()
+          | {:error, :collation_mismatch}
+          | {:error, :comparator_mismatch}
+          | Types.common_errors()
+  def merged_view_at(_view, _index), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieves a slice of a merged view.
+  """
+  @spec merged_view_slice(
+          view :: Types.merged_view(),
+          start :: non_neg_integer(),
+          amount :: non_neg_integer()
+        ) ::
+          [any()]
+          | {:error, :invalid_range}
+          | {:error, :collation_mismatch}
+          | {:error, :comparator_mismatch}
+          | Types.common_errors()
+  def merged_view_slice(_view, _start, _amount), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates an iterator over a merged view.
+  """
+  @spec merged_view_iterator(view :: Types.merged_view(), options :: [Types.iterator_option()]) ::
+          {:ok, Types.merged_view_iterator()}
+          | {:error, :collation_mismatch}
+          | {:error, :comparator_mismatch}
+          | Types.common_errors()
+  def merged_view_iterator(_view, _options), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Reads up to count elements from a merged view iterator and advances past them.
+  """
+  @spec merged_view_iterator_next(
+          iterator :: Types.merged_view_iterator(),
+          count :: non_neg_integer()
+        ) ::
+          [any()]
+          | {:error, :collation_mismatch}
+          | {:error, :comparator_mismatch}
+          | Types.common_errors()
+  def merged_view_iterator_next(_iterator, _count), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates a read-only view of the items of a SortedSet from min up to and including max.
+  """
+  @spec range_view(set :: SortedSet.t(), min :: any(), max :: any()) ::
+          {:ok, Types.range_view()} | Types.common_errors()
+  def range_view(_set, _min, _max), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Gets the number of items of the SortedSet that fall within a range view.
+  """
+  @spec range_view_size(view :: Types.range_view()) :: non_neg_integer() | Types.common_errors()
+  def range_view_size(_view), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieves the item at the given index of a range view.
+  """
+  @spec range_view_at(view :: Types.range_view(), index :: non_neg_integer()) ::
+          {:ok, any()} | Types.index_out_of_bounds_error() | Types.common_errors()
+  def range_view_at(_view, _index), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieves a slice of a range view.
+  """
+  @spec range_view_slice(
+          view :: Types.range_view(),
+          start :: non_neg_integer(),
+          amount :: non_neg_integer()
+        ) :: [any()] | {:error, :invalid_range} | Types.common_errors()
+  def range_view_slice(_view, _start, _amount), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates an iterator over a range view.
+  """
+  @spec range_view_iterator(view :: Types.range_view(), options :: [Types.iterator_option()]) ::
+          {:ok, Types.range_view_iterator()} | Types.common_errors()
+  def range_view_iterator(_view, _options), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Reads up to count elements from a range view iterator and advances past them.
+  """
+  @spec range_view_iterator_next(
+          iterator :: Types.range_view_iterator(),
+          count :: non_neg_integer()
+        ) :: [any()] | Types.common_errors()
+  def range_view_iterator_next(_iterator, _count), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Returns when the SortedSet was last modified.
+
+  The first element is the wall-clock time of the last successful mutation, in milliseconds since
+  the Unix epoch.  The second element is the number of milliseconds that have elapsed since that
+  mutation, measured against a monotonic clock so it is unaffected by wall-clock adjustments.
+  """
+  @spec last_modified(set :: SortedSet.t()) ::
+          Types.nif_last_modified_result() | Types.common_errors()
+  def last_modified(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Attaches a metadata term to the SortedSet, replacing any previously attached term.
+
+  The metadata term must be a supported term, it is stored alongside the set in NIF space and is
+  released when the set is garbage collected.
+  """
+  @spec put_meta(set :: SortedSet.t(), meta :: Types.supported_term()) ::
+          :ok | Types.frozen_error() | Types.common_errors()
+  def put_meta(_set, _meta), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieves the metadata term attached to the SortedSet.
+  """
+  @spec get_meta(set :: SortedSet.t()) :: Types.nif_get_meta_result() | Types.common_errors()
+  def get_meta(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Freezes the SortedSet, all mutating operations will return `{:error, :frozen}` until the set is
+  thawed with `thaw/1`.
+  """
+  @spec freeze(set :: SortedSet.t()) :: :ok | Types.common_errors()
+  def freeze(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Thaws a frozen SortedSet, allowing mutating operations again.
+  """
+  @spec thaw(set :: SortedSet.t()) :: :ok | Types.common_errors()
+  def thaw(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Atomically exchanges the contents of two SortedSets.
+
+  Both sets are locked for the duration of the swap, the configuration of each set travels with
+  its contents while metadata stays with the reference it was attached to.
+  `{:error, :configuration_mismatch}` is returned if the sets disagree on `:single_owner` or
+  `:coalesce_writes`.
+  """
+  @spec swap(a :: SortedSet.t(), b :: SortedSet.t()) ::
+          :ok
+          | {:error, :configuration_mismatch}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def swap(_a, _b), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Splits a SortedSet around a pivot into two new SortedSets, leaving the original set empty.
+
+  The first set contains every element less than the pivot, the second contains every element
+  greater than or equal to the pivot.
+  """
+  @spec partition(set :: SortedSet.t(), pivot :: any()) ::
+          Types.nif_partition_result() | Types.frozen_error() | Types.common_errors()
+  def partition(_set, _pivot), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Adds every item of `source` to `destination` with a single merge, returning how many items were
+  new to `destination`.
+  """
+  @spec merge_into(destination :: SortedSet.t(), source :: SortedSet.t()) ::
+          {:ok, non_neg_integer()}
+          | {:error, :set_full}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def merge_into(_destination, _source), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Constructs a SortedSet out of the distinct items of a non-empty list of SortedSets.
+  """
+  @spec merge_many(sets :: [SortedSet.t()]) ::
+          {:ok, SortedSet.t()}
+          | {:error, :set_full}
+          | {:error, :collation_mismatch}
+          | {:error, :comparator_mismatch}
+          | Types.common_errors()
+  def merge_many(_sets), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Moves the contents of `b` onto the end of `a`, leaving `b` empty.
+
+  Every element of `b` must be greater than every element of `a`, the buckets of `b` are appended
+  to `a` without searching or copying any elements.
+  """
+  @spec concat(a :: SortedSet.t(), b :: SortedSet.t()) ::
+          Types.nif_concat_result() | Types.frozen_error() | Types.common_errors()
+  def concat(_a, _b), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Moves an item from one set to another under the locks of both sets, returning the index the
+  item was removed from and the index it has in the destination.
+  """
+  @spec move(from :: SortedSet.t(), to :: SortedSet.t(), item :: any()) ::
+          {:ok, from_index :: non_neg_integer(), to_index :: non_neg_integer()}
+          | {:error, :not_found}
+          | {:error, :same_set}
+          | {:error, :set_full}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def move(_from, _to, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Moves every item between `min` and `max`, inclusive, from one set to another under the locks of
+  both sets, returning the number of items moved.
+  """
+  @spec move_range(from :: SortedSet.t(), to :: SortedSet.t(), min :: any(), max :: any()) ::
+          {:ok, non_neg_integer()}
+          | {:error, :same_set}
+          | {:error, :set_full}
+          | Types.frozen_error()
+          | Types.common_errors()
+  def move_range(_from, _to, _min, _max), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Subscribes a process to mutation events of the SortedSet.
+  """
+  @spec subscribe(set :: SortedSet.t(), pid :: pid()) :: :ok | Types.common_errors()
+  def subscribe(_set, _pid), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Unsubscribes a process from mutation events of the SortedSet.
+  """
+  @spec unsubscribe(set :: SortedSet.t(), pid :: pid()) :: :ok | Types.common_errors()
+  def unsubscribe(_set, _pid), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Registers a one-shot alert that sends a message to `pid` once the SortedSet reaches the
+  watermark.
+  """
+  @spec alert(set :: SortedSet.t(), watermark :: Types.watermark(), pid :: pid()) ::
+          :ok | Types.common_errors()
+  def alert(_set, _watermark, _pid), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Registers the process that is sent a message whenever a SortedSet is garbage collected, `nil`
+  unregisters the current collector.
+  """
+  @spec register_collector(pid :: pid() | nil) :: :ok | {:error, :lock_fail}
+  def register_collector(_pid), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Returns the number of live SortedSets, the sum of their approximate memory and the number of
+  NIF calls that panicked, followed by the probe counters if the probe-stats feature is enabled.
+  """
+  @spec global_stats() :: [{atom(), non_neg_integer()}]
+  def global_stats(), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Converts a SortedSet into a standard list on a separate thread, the list is sent to the calling
+  process as `{:sorted_set_reply, tag, list}`.
+  """
+  @spec async_to_list(set :: SortedSet.t(), tag :: reference()) :: :ok | Types.common_errors()
+  def async_to_list(_set, _tag), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Constructs a SortedSet from an arbitrary list on a separate thread, the result is sent to the
+  calling process as `{:sorted_set_reply, tag, {:ok, set}}`.
+  """
+  @spec async_from_list(
+          capacity :: pos_integer(),
+          bucket_size :: pos_integer(),
+          options :: Types.options(),
+          items :: [any()],
+          tag :: reference()
+        ) :: :ok
+  def async_from_list(_capacity, _bucket_size, _options, _items, _tag),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Constructs the union of two SortedSets as a new SortedSet.
+  """
+  @spec union(a :: SortedSet.t(), b :: SortedSet.t()) ::
+          {:ok, SortedSet.t()} | {:error, :set_full} | Types.common_errors()
+  def union(_a, _b), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Constructs the intersection of two SortedSets as a new SortedSet.
+  """
+  @spec intersection(a :: SortedSet.t(), b :: SortedSet.t()) ::
+          {:ok, SortedSet.t()} | {:error, :set_full} | Types.common_errors()
+  def intersection(_a, _b), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Constructs a SortedSet of the items of the first SortedSet that are not in the second.
+  """
+  @spec difference(a :: SortedSet.t(), b :: SortedSet.t()) ::
+          {:ok, SortedSet.t()} | {:error, :set_full} | Types.common_errors()
+  def difference(_a, _b), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Compares the items of two SortedSets in order.
+  """
+  @spec compare(a :: SortedSet.t(), b :: SortedSet.t()) ::
+          {:ok, :lt | :eq | :gt}
+          | {:error, :collation_mismatch}
+          | {:error, :comparator_mismatch}
+          | Types.common_errors()
+  def compare(_a, _b), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Lists the items of the second SortedSet missing from the first and the items of the first
+  SortedSet missing from the second.
+  """
+  @spec diff(old :: SortedSet.t(), new :: SortedSet.t()) ::
+          {:ok, {added :: [any()], removed :: [any()]}} | Types.common_errors()
+  def diff(_old, _new), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Checks whether two SortedSets have no item in common.
+  """
+  @spec disjoint(a :: SortedSet.t(), b :: SortedSet.t()) ::
+          {:ok, boolean()} | Types.common_errors()
+  def disjoint(_a, _b), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Checks whether two SortedSets hold the same items.
+  """
+  @spec equal(a :: SortedSet.t(), b :: SortedSet.t()) :: {:ok, boolean()} | Types.common_errors()
+  def equal(_a, _b), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Constructs the union of two SortedSets on a separate thread, the result is sent to the calling
+  process as `{:sorted_set_reply, tag, {:ok, set}}`.
+  """
+  @spec async_union(a :: SortedSet.t(), b :: SortedSet.t(), tag :: reference()) ::
+          :ok | Types.common_errors()
+  def async_union(_a, _b, _tag), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates a new PersistentSet from an arbitrary list, the list is sorted and deduplicated.
+  """
+  @spec persistent_new(items :: [any()], bucket_size :: pos_integer()) ::
+          {:ok, Types.persistent_set()} | Types.common_errors()
+  def persistent_new(_items, _bucket_size), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates a PersistentSet holding the current contents of a SortedSet.
+  """
+  @spec persistent_snapshot(set :: SortedSet.t()) ::
+          {:ok, Types.persistent_set()} | Types.common_errors()
+  def persistent_snapshot(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Adds an item to a PersistentSet, returning a new version of the set.  The version passed in is
+  left unmodified, adding a duplicate returns the version passed in.
+  """
+  @spec persistent_add(set :: Types.persistent_set(), item :: any()) ::
+          {:ok, :added | :duplicate, index :: non_neg_integer(), Types.persistent_set()}
+          | Types.common_errors()
+  def persistent_add(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Removes an item from a PersistentSet, returning a new version of the set.  The version passed
+  in is left unmodified.
+  """
+  @spec persistent_remove(set :: Types.persistent_set(), item :: any()) ::
+          {:ok, :removed, index :: non_neg_integer(), Types.persistent_set()}
+          | {:error, :not_found}
+          | Types.common_errors()
+  def persistent_remove(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Returns the number of items in a PersistentSet.
+  """
+  @spec persistent_size(set :: Types.persistent_set()) ::
+          non_neg_integer() | Types.common_errors()
+  def persistent_size(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieves the item at the given index of a PersistentSet.
+  """
+  @spec persistent_at(set :: Types.persistent_set(), index :: non_neg_integer()) ::
+          Types.nif_at_result() | Types.common_errors()
+  def persistent_at(_set, _index), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieves a slice of a PersistentSet.
+  """
+  @spec persistent_slice(
+          set :: Types.persistent_set(),
+          start :: non_neg_integer(),
+          amount :: non_neg_integer()
+        ) :: [any()] | {:error, :invalid_range} | Types.common_errors()
+  def persistent_slice(_set, _start, _amount), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Finds the index of an item in a PersistentSet.
+  """
+  @spec persistent_find_index(set :: Types.persistent_set(), item :: any()) ::
+          Types.nif_find_result() | Types.common_errors()
+  def persistent_find_index(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Converts a PersistentSet into a standard list.
+  """
+  @spec persistent_to_list(set :: Types.persistent_set()) :: [any()] | Types.common_errors()
+  def persistent_to_list(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates an empty Namespaces resource, every namespace is configured with the given bucket size
+  and options.
+  """
+  @spec namespaces_new(
+          capacity :: non_neg_integer(),
+          bucket_size :: pos_integer(),
+          options :: Types.options()
+        ) :: {:ok, Types.namespaces()}
+  def namespaces_new(_capacity, _bucket_size, _options), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Adds an item to a namespace, creating the namespace if needed.
+  """
+  @spec namespaces_add(namespaces :: Types.namespaces(), namespace :: any(), item :: any()) ::
+          Types.nif_add_result() | Types.common_errors()
+  def namespaces_add(_namespaces, _namespace, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Removes an item from a namespace, the namespace is dropped once it is empty.
+  """
+  @spec namespaces_remove(namespaces :: Types.namespaces(), namespace :: any(), item :: any()) ::
+          Types.nif_remove_result() | Types.common_errors()
+  def namespaces_remove(_namespaces, _namespace, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Drops a namespace with all of its items, returning the number of items dropped.
+  """
+  @spec namespaces_delete(namespaces :: Types.namespaces(), namespace :: any()) ::
+          {:ok, non_neg_integer()} | Types.common_errors()
+  def namespaces_delete(_namespaces, _namespace), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Lists the namespaces that hold items.
+  """
+  @spec namespaces_list(namespaces :: Types.namespaces()) :: [any()] | Types.common_errors()
+  def namespaces_list(_namespaces), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Gets the size of a namespace.
+  """
+  @spec namespaces_size(namespaces :: Types.namespaces(), namespace :: any()) ::
+          non_neg_integer() | Types.common_errors()
+  def namespaces_size(_namespaces, _namespace), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Converts a namespace into a standard list.
+  """
+  @spec namespaces_to_list(namespaces :: Types.namespaces(), namespace :: any()) ::
+          [any()] | Types.common_errors()
+  def namespaces_to_list(_namespaces, _namespace), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieves the item at the given index of a namespace.
+  """
+  @spec namespaces_at(
+          namespaces :: Types.namespaces(),
+          namespace :: any(),
+          index :: non_neg_integer()
+        ) :: {:ok, any()} | Types.index_out_of_bounds_error() | Types.common_errors()
+  def namespaces_at(_namespaces, _namespace, _index), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieves a slice of a namespace.
+  """
+  @spec namespaces_slice(
+          namespaces :: Types.namespaces(),
+          namespace :: any(),
+          start :: non_neg_integer(),
+          amount :: non_neg_integer()
+        ) :: [any()] | {:error, :invalid_range} | Types.common_errors()
+  def namespaces_slice(_namespaces, _namespace, _start, _amount),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Finds the index of an item in a namespace.
+  """
+  @spec namespaces_find_index(
+          namespaces :: Types.namespaces(),
+          namespace :: any(),
+          item :: any()
+        ) :: Types.nif_find_result() | Types.common_errors()
+  def namespaces_find_index(_namespaces, _namespace, _item),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates an empty DualIndex, each order is configured with its own options.
+  """
+  @spec dual_index_new(
+          capacity :: non_neg_integer(),
+          bucket_size :: pos_integer(),
+          primary_options :: Types.options(),
+          secondary_options :: Types.options()
+        ) :: {:ok, Types.dual_index()}
+  def dual_index_new(_capacity, _bucket_size, _primary_options, _secondary_options),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Adds an item to both orders, returning its index in each.
+  """
+  @spec dual_index_add(set :: Types.dual_index(), item :: any()) ::
+          {:ok, :added | :duplicate, primary :: non_neg_integer(), secondary :: non_neg_integer()}
+          | {:error, :set_full}
+          | Types.common_errors()
+  def dual_index_add(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Removes an item from both orders, returning the index it had in each.
+  """
+  @spec dual_index_remove(set :: Types.dual_index(), item :: any()) ::
+          {:ok, :removed, primary :: non_neg_integer(), secondary :: non_neg_integer()}
+          | {:error, :not_found}
+          | {:error, :internal_inconsistency}
+          | Types.common_errors()
+  def dual_index_remove(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Gets the number of items in a DualIndex.
+  """
+  @spec dual_index_size(set :: Types.dual_index()) :: non_neg_integer() | Types.common_errors()
+  def dual_index_size(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Converts one order of a DualIndex into a standard list.
+  """
+  @spec dual_index_to_list(set :: Types.dual_index(), order :: :primary | :secondary) ::
+          [any()] | Types.common_errors()
+  def dual_index_to_list(_set, _order), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieves the item at the given index of one order.
+  """
+  @spec dual_index_at(
+          set :: Types.dual_index(),
+          order :: :primary | :secondary,
+          index :: non_neg_integer()
+        ) :: {:ok, any()} | Types.index_out_of_bounds_error() | Types.common_errors()
+  def dual_index_at(_set, _order, _index), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieves a slice of one order.
+  """
+  @spec dual_index_slice(
+          set :: Types.dual_index(),
+          order :: :primary | :secondary,
+          start :: non_neg_integer(),
+          amount :: non_neg_integer()
+        ) :: [any()] | {:error, :invalid_range} | Types.common_errors()
+  def dual_index_slice(_set, _order, _start, _amount), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Finds the index of an item in one order.
+  """
+  @spec dual_index_find_index(
+          set :: Types.dual_index(),
+          order :: :primary | :secondary,
+          item :: any()
+        ) :: Types.nif_find_result() | Types.common_errors()
+  def dual_index_find_index(_set, _order, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Creates an empty sharded set split at the pivots, every shard is configured with the options.
+  """
+  @spec sharded_new(
+          capacity :: non_neg_integer(),
+          bucket_size :: pos_integer(),
+          options :: Types.options(),
+          pivots :: [any()]
+        ) ::
+          {:ok, Types.sharded()}
+          | {:error, :unordered_pivots}
+          | {:error, :unsupported_type}
+  def sharded_new(_capacity, _bucket_size, _options, _pivots),
+    do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Adds an item to the shard holding its range, returning the index of the shard.
+  """
+  @spec sharded_add(set :: Types.sharded(), item :: any()) ::
+          {:ok, :added | :duplicate, shard :: non_neg_integer()}
+          | {:error, :set_full}
+          | Types.common_errors()
+  def sharded_add(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Removes an item from the shard holding its range, returning the index of the shard.
+  """
+  @spec sharded_remove(set :: Types.sharded(), item :: any()) ::
+          {:ok, :removed, shard :: non_neg_integer()}
+          | {:error, :not_found}
+          | {:error, :internal_inconsistency}
+          | Types.common_errors()
+  def sharded_remove(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Gets the number of items across all shards.
+  """
+  @spec sharded_size(set :: Types.sharded()) :: non_neg_integer() | Types.common_errors()
+  def sharded_size(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Gets the number of items in every shard, in order.
+  """
+  @spec sharded_shard_sizes(set :: Types.sharded()) :: [non_neg_integer()] | Types.common_errors()
+  def sharded_shard_sizes(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Converts all shards into a single standard list.
+  """
+  @spec sharded_to_list(set :: Types.sharded()) :: [any()] | Types.common_errors()
+  def sharded_to_list(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieves the item at the given index across all shards.
+  """
+  @spec sharded_at(set :: Types.sharded(), index :: non_neg_integer()) ::
+          {:ok, any()} | Types.index_out_of_bounds_error() | Types.common_errors()
+  def sharded_at(_set, _index), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Retrieves a slice across all shards.
+  """
+  @spec sharded_slice(
+          set :: Types.sharded(),
+          start :: non_neg_integer(),
+          amount :: non_neg_integer()
+        ) :: [any()] | {:error, :invalid_range} | Types.common_errors()
+  def sharded_slice(_set, _start, _amount), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Finds the index of an item across all shards.
+  """
+  @spec sharded_find_index(set :: Types.sharded(), item :: any()) ::
+          Types.nif_find_result() | Types.common_errors()
+  def sharded_find_index(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Copies the items of the set into a term that can be read without the NIF.
+  """
+  @spec export_static(set :: SortedSet.t()) ::
+          {:ok, Discord.SortedSet.Static.t()} | {:error, :custom_order} | Types.common_errors()
+  def export_static(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Captures the configuration, bucket layout and contents of the set as a binary.
+  """
+  @spec export_repro(set :: SortedSet.t()) :: {:ok, binary()} | Types.common_errors()
+  def export_repro(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Rebuilds a set from a binary produced by `export_repro/1`.
+  """
+  @spec import_repro(binary :: binary()) ::
+          {:ok, SortedSet.t()} | {:error, :invalid_repro} | Types.common_errors()
+  def import_repro(_binary), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Renders the metrics of the set in the Prometheus text exposition format, every sample carries
+  the given labels.
+  """
+  @spec metrics_prometheus(set :: SortedSet.t(), labels :: [{String.t(), String.t()}]) ::
+          {:ok, String.t()} | Types.common_errors()
+  def metrics_prometheus(_set, _labels), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Returns a map summarizing the state of the SortedSet.
+  """
+  @spec health(set :: SortedSet.t()) :: {:ok, Types.health()} | Types.common_errors()
+  def health(_set), do: :erlang.nif_error(:nif_not_loaded)
+
   @doc """
   Returns a string representation of the underlying Rust data structure.
 
@@ -110,4 +1169,51 @@ defmodule Discord.SortedSet.NifBridge do
   """
   @spec debug(set :: SortedSet.t()) :: String.t() | Types.common_errors()
   def debug(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Tallies the allocations held by the SortedSet by category, requires the debug-alloc feature.
+  """
+  @spec alloc_report(set :: SortedSet.t()) ::
+          {:ok, [{atom(), [count: non_neg_integer(), bytes: non_neg_integer()]}]}
+          | {:error, :not_enabled}
+          | Types.common_errors()
+  def alloc_report(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Sorts and deduplicates the buckets of the SortedSet and recounts its size, reporting what had
+  to be fixed.
+  """
+  @spec repair(set :: SortedSet.t()) ::
+          {:ok, [{atom(), non_neg_integer()}]} | Types.frozen_error() | Types.common_errors()
+  def repair(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Drops the tombstones of the SortedSet, returning how many were dropped.
+  """
+  @spec compact(set :: SortedSet.t()) :: {:ok, non_neg_integer()} | Types.common_errors()
+  def compact(_set), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Adds an item to the SortedSet, queueing the write if the set coalesces its writes.
+  """
+  @spec queue_add(set :: SortedSet.t(), item :: any()) ::
+          {:ok, :queued} | Types.nif_add_result() | Types.frozen_error() | Types.common_errors()
+  def queue_add(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Removes an item from the SortedSet, queueing the write if the set coalesces its writes.
+  """
+  @spec queue_remove(set :: SortedSet.t(), item :: any()) ::
+          {:ok, :queued}
+          | Types.nif_remove_result()
+          | Types.frozen_error()
+          | Types.internal_inconsistency_error()
+          | Types.common_errors()
+  def queue_remove(_set, _item), do: :erlang.nif_error(:nif_not_loaded)
+
+  @doc """
+  Applies the writes queued on the SortedSet, returning how many were taken off the queue.
+  """
+  @spec flush(set :: SortedSet.t()) :: {:ok, non_neg_integer()} | Types.common_errors()
+  def flush(_set), do: :erlang.nif_error(:nif_not_loaded)
 end
@@ -0,0 +1,193 @@
+defmodule Discord.SortedSet.Persistent do
+  @moduledoc """
+  An immutable variant of `Discord.SortedSet`.
+
+  Every mutation of a PersistentSet returns a new version of the set and leaves the version it was
+  called on untouched, which makes it possible to hold on to "the set as of event N" without
+  copying the whole set.  Versions share the buckets they have in common, a mutation only copies
+  the bucket it touches and the list of buckets, so keeping many versions of a large set around is
+  cheap as long as they only differ by a few items.
+
+  A PersistentSet does not support the options of `Discord.SortedSet.new/3`.
+  """
+  alias Discord.SortedSet
+  alias Discord.SortedSet.{NifBridge, Types}
+
+  @type t :: Types.persistent_set()
+
+  @default_bucket_size 500
+
+  @doc """
+  Construct a new empty PersistentSet with a given bucket size.
+  """
+  @spec new(bucket_size :: pos_integer()) :: t() | Types.common_errors()
+  def new(bucket_size \\ @default_bucket_size) do
+    from_enumerable([], bucket_size)
+  end
+
+  @doc """
+  Construct a PersistentSet from any Enumerable, the terms do not need to be sorted or unique.
+  """
+  @spec from_enumerable(terms :: Enumerable.t(), bucket_size :: pos_integer()) ::
+          t() | Types.common_errors()
+  def from_enumerable(terms, bucket_size \\ @default_bucket_size) do
+    case NifBridge.persistent_new(Enum.to_list(terms), bucket_size) do
+      {:ok, set} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Capture the current contents of a SortedSet as a PersistentSet.
+
+  Later mutations of the SortedSet do not affect the snapshot.
+  """
+  @spec snapshot(set :: SortedSet.t()) :: t() | Types.common_errors()
+  def snapshot(set) do
+    case NifBridge.persistent_snapshot(set) do
+      {:ok, persistent} ->
+        persistent
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Returns a new version of the set with the item added.
+
+  Adding an item that is already present returns the set unchanged.
+  """
+  @spec add(set :: t(), item :: any()) :: t() | Types.common_errors()
+  def add(set, item) do
+    case NifBridge.persistent_add(set, item) do
+      {:ok, _, _, version} ->
+        version
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Returns the index of the item along with a new version of the set with the item added.
+
+  If the item was already present the index `nil` is returned along with the unchanged set.
+  """
+  @spec index_add(set :: t(), item :: any()) ::
+          {index :: non_neg_integer() | nil, t()} | Types.common_errors()
+  def index_add(set, item) do
+    case NifBridge.persistent_add(set, item) do
+      {:ok, :added, index, version} ->
+        {index, version}
+
+      {:ok, :duplicate, _, version} ->
+        {nil, version}
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Returns a new version of the set with the item removed.
+
+  Removing an item that is not present returns the set unchanged.
+  """
+  @spec remove(set :: t(), item :: any()) :: t() | Types.common_errors()
+  def remove(set, item) do
+    case NifBridge.persistent_remove(set, item) do
+      {:ok, :removed, _, version} ->
+        version
+
+      {:error, :not_found} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Returns the index the item was removed from along with a new version of the set.
+
+  If the item was not present the index `nil` is returned along with the unchanged set.
+  """
+  @spec index_remove(set :: t(), item :: any()) ::
+          {index :: non_neg_integer() | nil, t()} | Types.common_errors()
+  def index_remove(set, item) do
+    case NifBridge.persistent_remove(set, item) do
+      {:ok, :removed, index, version} ->
+        {index, version}
+
+      {:error, :not_found} ->
+        {nil, set}
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Get the size of a PersistentSet.
+  """
+  @spec size(set :: t()) :: non_neg_integer() | Types.common_errors()
+  def size(set) do
+    NifBridge.persistent_size(set)
+  end
+
+  @doc """
+  Converts a PersistentSet into a List.
+  """
+  @spec to_list(set :: t()) :: [Types.supported_term()] | Types.common_errors()
+  def to_list(set) do
+    NifBridge.persistent_to_list(set)
+  end
+
+  @doc """
+  Retrieve an item at the given index, the default is returned if the index is out of bounds.
+  """
+  @spec at(set :: t(), index :: non_neg_integer(), default :: any()) ::
+          (item_or_default :: Types.supported_term() | any()) | Types.common_errors()
+  def at(set, index, default \\ nil) do
+    case NifBridge.persistent_at(set, index) do
+      {:ok, item} ->
+        item
+
+      {:error, {:index_out_of_bounds, _, _}} ->
+        default
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Retrieves a slice of the PersistentSet, see `Discord.SortedSet.slice/3`.
+  """
+  @spec slice(set :: t(), start :: non_neg_integer(), amount :: non_neg_integer()) ::
+          [Types.supported_term()] | {:error, :invalid_range} | Types.common_errors()
+  def slice(set, start, amount) do
+    NifBridge.persistent_slice(set, start, amount)
+  end
+
+  @doc """
+  Finds the index of the specified term, `nil` is returned if the term is not present.
+  """
+  @spec find_index(set :: t(), item :: any()) :: non_neg_integer() | nil | Types.common_errors()
+  def find_index(set, item) do
+    case NifBridge.persistent_find_index(set, item) do
+      {:ok, index} ->
+        index
+
+      {:error, :not_found} ->
+        nil
+
+      other ->
+        other
+    end
+  end
+end
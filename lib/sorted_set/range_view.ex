@@ -0,0 +1,97 @@
+defmodule Discord.SortedSet.RangeView do
+  @moduledoc """
+  A read-only view of the elements of a SortedSet that fall within a range of terms.
+
+  A view is a cheap handle to one section of a set, the members of a single role of a member list
+  for example, that can be given to code that should not see or change the rest of the set.
+  Nothing is copied, the view only remembers its bounds and every read is translated to the set
+  by offsetting the index with the position of the lower bound.  Elements added to or removed
+  from the set within the range are visible through the view immediately.
+
+  Indexes of a view start at 0 with the first element of the range.  The bounds are inclusive and
+  do not have to be elements of the set, a view whose minimum is larger than its maximum is
+  empty.  `:neg_infinity` and `:infinity` sort before and after every other term, so
+  `new(set, {:admin, :neg_infinity}, {:admin, :infinity})` views every `{:admin, _}` element and
+  `new(set, 100, :infinity)` every element from `100` up.
+  """
+  alias Discord.SortedSet
+  alias Discord.SortedSet.{NifBridge, Types}
+
+  @type t :: Types.range_view()
+
+  @doc """
+  Construct a view of the elements of the set from `min` up to and including `max`.
+  """
+  @spec new(set :: SortedSet.t(), min :: any(), max :: any()) :: t() | Types.common_errors()
+  def new(set, min, max) do
+    case NifBridge.range_view(set, min, max) do
+      {:ok, view} ->
+        view
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Get the number of elements of the set that fall within the view.
+  """
+  @spec size(view :: t()) :: non_neg_integer() | Types.common_errors()
+  def size(view) do
+    NifBridge.range_view_size(view)
+  end
+
+  @doc """
+  Retrieve the element at the given index of the view, the default is returned if the index is
+  out of bounds.
+  """
+  @spec at(view :: t(), index :: non_neg_integer(), default :: any()) ::
+          (item_or_default :: Types.supported_term() | any()) | Types.common_errors()
+  def at(view, index, default \\ nil) do
+    case NifBridge.range_view_at(view, index) do
+      {:ok, item} ->
+        item
+
+      {:error, {:index_out_of_bounds, _, _}} ->
+        default
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Retrieves a slice of the view, see `Discord.SortedSet.slice/3`.  The slice never reaches past
+  the end of the view.
+  """
+  @spec slice(view :: t(), start :: non_neg_integer(), amount :: non_neg_integer()) ::
+          [Types.supported_term()] | {:error, :invalid_range} | Types.common_errors()
+  def slice(view, start, amount) do
+    NifBridge.range_view_slice(view, start, amount)
+  end
+
+  @doc """
+  Creates an iterator that walks the view in either direction, see `Discord.SortedSet.iterator/2`
+  for the options.  The `:at` option is an index of the view, snapshots are not supported.
+  """
+  @spec iterator(view :: t(), options :: [Types.iterator_option()]) ::
+          Types.range_view_iterator() | Types.common_errors()
+  def iterator(view, options \\ []) do
+    case NifBridge.range_view_iterator(view, options) do
+      {:ok, iterator} ->
+        iterator
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Reads up to `count` elements from an iterator created by `iterator/2` and advances past them.
+  """
+  @spec iterator_next(iterator :: Types.range_view_iterator(), count :: pos_integer()) ::
+          [Types.supported_term()] | Types.common_errors()
+  def iterator_next(iterator, count \\ 1) do
+    NifBridge.range_view_iterator_next(iterator, count)
+  end
+end
@@ -0,0 +1,155 @@
+defmodule Discord.SortedSet.Sharded do
+  @moduledoc """
+  A SortedSet split by value range into shards that are locked separately.
+
+  Every write to a SortedSet takes its lock, so the sets of the largest guilds spend a good part
+  of their time with writers waiting on each other.  Splitting such a set into several SortedSets
+  by range spreads the writes out, but keeping track of which set holds which range and
+  answering index queries over all of them had to be done by hand.  A Sharded set does both
+  behind a single reference: writes only lock the shard holding the item, reads by index see the
+  shards as one set.
+
+  The set is split at the pivots given to `new/4`, `n` pivots make `n + 1` shards and every pivot
+  is the smallest item its shard can hold.  The pivots are fixed for the life of the set, pick
+  them so the shards see a similar number of writes.  `shard_sizes/1` shows how the items are
+  spread.
+
+      # Members by user id, split into four shards
+      members = Sharded.new([1_000_000, 2_000_000, 3_000_000])
+
+      Sharded.add(members, 2_500_000)
+      Sharded.shard_sizes(members)
+      # => [0, 0, 1, 0]
+
+  Reads over several shards lock all of them together, so they never see a write to one shard
+  and miss an earlier write to another.
+  """
+  alias Discord.SortedSet.{NifBridge, Types}
+
+  @type t :: Types.sharded()
+
+  @default_bucket_size 500
+
+  @doc """
+  Construct an empty Sharded set split at the pivots, which have to be strictly increasing.
+
+  Every shard is a SortedSet configured with the options, see `Discord.SortedSet.new/3`.  Limits
+  like `:max_elements` apply to each shard on its own, the `:single_owner` and `:coalesce_writes`
+  options are not supported.
+  """
+  @spec new(pivots :: [any()], options :: Types.options(), bucket_size :: pos_integer()) ::
+          t() | {:error, :unordered_pivots} | {:error, :unsupported_type}
+  def new(pivots, options \\ [], bucket_size \\ @default_bucket_size) do
+    case NifBridge.sharded_new(0, bucket_size, options, pivots) do
+      {:ok, set} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Adds an item to the shard holding its range.
+  """
+  @spec add(set :: t(), item :: any()) :: t() | {:error, :set_full} | Types.common_errors()
+  def add(set, item) do
+    case NifBridge.sharded_add(set, item) do
+      {:ok, _, _} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Removes an item from the shard holding its range.
+
+  Removing an item that is not present returns the Sharded set unchanged.
+  """
+  @spec remove(set :: t(), item :: any()) :: t() | Types.common_errors()
+  def remove(set, item) do
+    case NifBridge.sharded_remove(set, item) do
+      {:ok, :removed, _} ->
+        set
+
+      {:error, :not_found} ->
+        set
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Get the number of items across all shards.
+  """
+  @spec size(set :: t()) :: non_neg_integer() | Types.common_errors()
+  def size(set) do
+    NifBridge.sharded_size(set)
+  end
+
+  @doc """
+  Get the number of items in every shard, in order.
+  """
+  @spec shard_sizes(set :: t()) :: [non_neg_integer()] | Types.common_errors()
+  def shard_sizes(set) do
+    NifBridge.sharded_shard_sizes(set)
+  end
+
+  @doc """
+  Converts the shards into a single List.
+  """
+  @spec to_list(set :: t()) :: [Types.supported_term()] | Types.common_errors()
+  def to_list(set) do
+    NifBridge.sharded_to_list(set)
+  end
+
+  @doc """
+  Retrieve an item at the given index across all shards, the default is returned if the index is
+  out of bounds.
+  """
+  @spec at(set :: t(), index :: non_neg_integer(), default :: any()) ::
+          (item_or_default :: Types.supported_term() | any()) | Types.common_errors()
+  def at(set, index, default \\ nil) do
+    case NifBridge.sharded_at(set, index) do
+      {:ok, item} ->
+        item
+
+      {:error, {:index_out_of_bounds, _, _}} ->
+        default
+
+      other ->
+        other
+    end
+  end
+
+  @doc """
+  Retrieves a slice across all shards, see `Discord.SortedSet.slice/3`.
+  """
+  @spec slice(set :: t(), start :: non_neg_integer(), amount :: non_neg_integer()) ::
+          [Types.supported_term()] | {:error, :invalid_range} | Types.common_errors()
+  def slice(set, start, amount) do
+    NifBridge.sharded_slice(set, start, amount)
+  end
+
+  @doc """
+  Finds the index of the item across all shards, `nil` is returned if the item is not present.
+
+  Only the shards up to the one holding the item are read.
+  """
+  @spec find_index(set :: t(), item :: any()) :: non_neg_integer() | nil | Types.common_errors()
+  def find_index(set, item) do
+    case NifBridge.sharded_find_index(set, item) do
+      {:ok, index} ->
+        index
+
+      {:error, :not_found} ->
+        nil
+
+      other ->
+        other
+    end
+  end
+end
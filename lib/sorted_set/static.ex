@@ -0,0 +1,113 @@
+defmodule Discord.SortedSet.Static do
+  @moduledoc """
+  Reads over the immutable copy of a SortedSet produced by `Discord.SortedSet.export_static/1`.
+
+  The copy is a plain term, `{:sorted_set_static, size, chunk_size, chunks}` where `chunks` is a
+  tuple of tuples holding the items in order.  Nothing here calls into the NIF, so the copy can
+  be stored in `:persistent_term` and read from any process:
+
+      :persistent_term.put({:members, guild_id}, SortedSet.export_static(set))
+
+      {:members, guild_id}
+      |> :persistent_term.get()
+      |> Static.find_index(member)
+
+  Reads by index are constant time, lookups by value are two binary searches over the chunks.
+  """
+  alias Discord.SortedSet.Types
+
+  @type t ::
+          {:sorted_set_static, size :: non_neg_integer(), chunk_size :: pos_integer(),
+           chunks :: tuple()}
+
+  @doc """
+  Get the number of items in the copy.
+  """
+  @spec size(static :: t()) :: non_neg_integer()
+  def size({:sorted_set_static, size, _, _}), do: size
+
+  @doc """
+  Converts the copy into a List.
+  """
+  @spec to_list(static :: t()) :: [Types.supported_term()]
+  def to_list({:sorted_set_static, _, _, chunks}) do
+    chunks
+    |> Tuple.to_list()
+    |> Enum.flat_map(&Tuple.to_list/1)
+  end
+
+  @doc """
+  Retrieve an item at the given index, the default is returned if the index is out of bounds.
+  """
+  @spec at(static :: t(), index :: non_neg_integer(), default :: any()) ::
+          item_or_default :: Types.supported_term() | any()
+  def at(static, index, default \\ nil)
+
+  def at({:sorted_set_static, size, chunk_size, chunks}, index, _default)
+      when is_integer(index) and index >= 0 and index < size do
+    chunks
+    |> elem(div(index, chunk_size))
+    |> elem(rem(index, chunk_size))
+  end
+
+  def at(_static, _index, default), do: default
+
+  @doc """
+  Retrieves a slice of the copy, see `Discord.SortedSet.slice/3`.
+  """
+  @spec slice(static :: t(), start :: non_neg_integer(), amount :: non_neg_integer()) ::
+          [Types.supported_term()]
+  def slice({:sorted_set_static, size, _, _} = static, start, amount) do
+    stop = min(start + amount, size)
+
+    if start < stop do
+      Enum.map(start..(stop - 1), &at(static, &1))
+    else
+      []
+    end
+  end
+
+  @doc """
+  Finds the index of the item, `nil` is returned if the item is not present.
+  """
+  @spec find_index(static :: t(), item :: any()) :: non_neg_integer() | nil
+  def find_index({:sorted_set_static, _, chunk_size, chunks}, item) do
+    case find_chunk(chunks, item, 0, tuple_size(chunks) - 1) do
+      -1 ->
+        nil
+
+      chunk_index ->
+        chunk = elem(chunks, chunk_index)
+
+        case search(chunk, item, 0, tuple_size(chunk) - 1) do
+          nil -> nil
+          index -> chunk_index * chunk_size + index
+        end
+    end
+  end
+
+  # Index of the last chunk starting at or before the item, -1 if the item sorts first
+  defp find_chunk(_chunks, _item, low, high) when low > high, do: high
+
+  defp find_chunk(chunks, item, low, high) do
+    middle = div(low + high, 2)
+
+    if elem(elem(chunks, middle), 0) <= item do
+      find_chunk(chunks, item, middle + 1, high)
+    else
+      find_chunk(chunks, item, low, middle - 1)
+    end
+  end
+
+  defp search(_chunk, _item, low, high) when low > high, do: nil
+
+  defp search(chunk, item, low, high) do
+    middle = div(low + high, 2)
+
+    case elem(chunk, middle) do
+      ^item -> middle
+      current when current < item -> search(chunk, item, middle + 1, high)
+      _ -> search(chunk, item, low, middle - 1)
+    end
+  end
+end
@@ -9,6 +9,122 @@ defmodule Discord.SortedSet.Types do
   """
   @type sorted_set :: reference()
 
+  @typedoc """
+  PersistentSets are stored in the NIF's memory space like SortedSets, each reference identifies
+  one immutable version of the set.
+  """
+  @type persistent_set :: reference()
+
+  @typedoc """
+  Namespaces are stored in the NIF's memory space like SortedSets, one reference identifies all of
+  the named sub-sets, see `Discord.SortedSet.Namespaces`.
+  """
+  @type namespaces :: reference()
+
+  @typedoc """
+  DualIndexes are stored in the NIF's memory space like SortedSets, one reference identifies the
+  items in both of their orders, see `Discord.SortedSet.DualIndex`.
+  """
+  @type dual_index :: reference()
+
+  @typedoc """
+  Sharded sets are stored in the NIF's memory space like SortedSets, one reference identifies all
+  of the shards, see `Discord.SortedSet.Sharded`.
+  """
+  @type sharded :: reference()
+
+  @typedoc """
+  Iterators are stored in the NIF's memory space, each reference identifies one cursor over a
+  SortedSet.  The iterator keeps the set alive for as long as it is referenced.
+  """
+  @type iterator :: reference()
+
+  @typedoc """
+  Merged views are stored in the NIF's memory space, each reference identifies a read-only view
+  over several SortedSets, see `Discord.SortedSet.MergedView`.  The view keeps the sets alive for
+  as long as it is referenced.
+  """
+  @type merged_view :: reference()
+
+  @typedoc """
+  Iterators over a merged view, see `Discord.SortedSet.MergedView.iterator/2`.
+  """
+  @type merged_view_iterator :: reference()
+
+  @typedoc """
+  Range views are stored in the NIF's memory space, each reference identifies a read-only view
+  over the elements of a SortedSet within a range, see `Discord.SortedSet.RangeView`.  The view
+  keeps the set alive for as long as it is referenced.
+  """
+  @type range_view :: reference()
+
+  @typedoc """
+  Iterators over a range view, see `Discord.SortedSet.RangeView.iterator/2`.
+  """
+  @type range_view_iterator :: reference()
+
+  @typedoc """
+  Options that can be provided to `Discord.SortedSet.index_add/3`.
+
+  `{:hint, non_neg_integer()}` is the index the item is expected to end up at, see
+  `Discord.SortedSet.add/3`.
+
+  `{:index, :inserted | :displaced}` selects which index is returned for an added item.
+  `:inserted`, the default, returns the index the item ended up at.  `:displaced` returns the
+  index the item that used to be at the insertion point was pushed to, which is always one past
+  the inserted index, or `nil` if the item was added at the end of the set and did not displace
+  anything.
+
+  `{:unsupported, :reject | :skip | {:replace, atom()}}` decides what happens to an item holding
+  a term that can not be stored, like a pid or a float, somewhere inside its tuples or lists.
+  `:reject`, the default, refuses the whole item with `{:error, :unsupported_type}`.  `:skip`
+  leaves the unsupported terms out of their tuple or list and `{:replace, placeholder}` puts the
+  placeholder atom in their place.  An item that is unsupported itself is always refused.  See
+  `Discord.SortedSet.sanitized_add/3` for a variant that reports how many terms were affected.
+
+  `{:iodata, boolean()}` stores an item that is iodata, a binary or a possibly improper list
+  nesting bytes and binaries, as the binary it flattens to, like `IO.iodata_to_binary/1` would.
+  Lists of bytes are iodata too, so `[104, 105]` is stored as `"hi"`.  Items that are not iodata
+  or do not flatten to valid UTF-8 are stored as usual.  Defaults to `false`.
+  """
+  @type add_option ::
+          {:hint, non_neg_integer()}
+          | {:index, :inserted | :displaced}
+          | {:unsupported, :reject | :skip | {:replace, atom()}}
+          | {:iodata, boolean()}
+
+  @typedoc """
+  Options that can be provided to `Discord.SortedSet.NifBridge.remove/3`.
+
+  `{:missing, :error | :noop}` selects the result of removing an item that is not present.
+  `:error`, the default, returns `{:error, :not_found}`, `:noop` returns `{:ok, :noop}` for
+  callers that treat absence as success.
+  """
+  @type remove_option :: {:missing, :error | :noop}
+
+  @typedoc """
+  Options that can be provided when creating an iterator.
+
+  `{:from, supported_term()}` starts the iterator at the given term, or at the next term in the
+  direction of the iterator if it is not present in the set.
+
+  `{:at, non_neg_integer()}` starts the iterator at the element at the given index.  An index past
+  the end of the set starts a `:desc` iterator at the largest element and leaves an `:asc`
+  iterator with nothing to read.
+
+  `{:direction, :asc | :desc}` selects the direction the iterator moves in, defaults to `:asc`.
+  Without `:from` or `:at` an `:asc` iterator starts at the smallest element and a `:desc`
+  iterator at the largest.
+
+  `{:snapshot, boolean()}` makes the iterator read a copy of the set taken when it is created,
+  defaults to `false`.  Only iterators over a SortedSet support snapshots.
+  """
+  @type iterator_option ::
+          {:from, supported_term()}
+          | {:at, non_neg_integer()}
+          | {:direction, :asc | :desc}
+          | {:snapshot, boolean()}
+
   @typedoc """
   There are common errors that can be returned from any SortedSet operation, the common_errors
   type enumerates them.
@@ -18,17 +134,43 @@ defmodule Discord.SortedSet.Types do
 
   `{:error, :lock_fail}` is returned when the NIF can not guarantee concurrency safety.  NIFs are
   not bound by the same guarantees as Erlang / Elixir code executing in the BEAM VM, to safe guard
-  against multiple threads of execution mutating the same SortedSet concurrently a Mutex is used
-  internally to lock the data structure during all operations.
+  against multiple threads of execution mutating the same SortedSet concurrently a read-write lock
+  is used internally to lock the data structure during all operations.  Any number of read
+  operations can hold the lock at the same time, mutations require exclusive access.
+
+  `{:error, :not_owner}` is returned when a set created with the `single_owner: true` option is
+  used by any process other than the one that created it.
 
   `{:error, :unsupported_type}` is returned any time an item is passed to the SortedSet that is
   either in whole or in part an unsupported type.  The following types are not supported in
   SortedSet, Reference, Function, Port, and Pid.  Unsupported types poison other types, so a list
   containing a single element (regardless of nesting) of an unsupported type is unsupported, same
   for tuples.
+
+  `{:error, {:internal_error, reason}}` is returned when the NIF hits a bug, `reason` is the
+  message of the Rust panic.  A panic while a shared set is being mutated leaves the set locked
+  and every later operation on it returns `{:error, :lock_fail}`.  Panics are counted in
+  `Discord.SortedSet.global_stats/0`.
   """
   @type common_errors ::
-          {:error, :bad_reference} | {:error, :lock_fail} | {:error, :unsupported_type}
+          {:error, :bad_reference}
+          | {:error, :lock_fail}
+          | {:error, :not_owner}
+          | {:error, :unsupported_type}
+          | {:error, {:internal_error, reason :: String.t()}}
+
+  @typedoc """
+  Error returned by any operation that would mutate a SortedSet that has been frozen, see
+  `Discord.SortedSet.freeze/1`.
+  """
+  @type frozen_error :: {:error, :frozen}
+
+  @typedoc """
+  Error returned by a remove that found the set's bookkeeping out of sync with its contents.  The
+  set repairs itself before returning this error and leaves the item in place, retrying the remove
+  is safe.
+  """
+  @type internal_inconsistency_error :: {:error, :internal_inconsistency}
 
   @typedoc """
   Success responses returned from the NIF when adding an element to the set.
@@ -40,23 +182,34 @@ defmodule Discord.SortedSet.Types do
   executed successfully but the element already existed within the SortedSet, the index of the
   existing element is returned.
 
+  `{:error, :set_full}` is returned by the NIF to indicate that the element was not present and
+  could not be added because the set has reached its configured `:max_elements`.
+
   The NIF provides more detailed but less conventional return values, these are coerced in the
   `SortedSet` module to more conventional responses.  Due to how the NIF is implemented there is
   no distinction in NIF space between `add` and `index_add`, these more detailed response values
   allow the Elixir wrapper to implement both with the same underlying mechanism
   """
   @type nif_add_result ::
-          {:ok, :added, index :: integer()} | {:ok, :duplicate, index :: integer()}
+          {:ok, :added, index :: integer()}
+          | {:ok, :duplicate, index :: integer()}
+          | {:error, :set_full}
 
   @typedoc """
   Response returned from the NIF when appending a bucket.
 
   `:ok` is returned by the NIF to indicate that the bucket was appended.
 
-  `{:error, :max_bucket_size_exceeded}` is returned by the NIF to indicate that the list of terms
-  passed in meets or exceeds the max_bucket_size of the set.
+  `{:error, {:max_bucket_size_exceeded, got, max}}` is returned by the NIF to indicate that the
+  list of `got` terms passed in meets or exceeds the `max` bucket size of the set.
+
+  `{:error, :set_full}` is returned by the NIF to indicate that appending the terms would grow the
+  set past its configured `:max_elements`.
   """
-  @type nif_append_bucket_result :: :ok | {:error, :max_bucket_size_exceeded}
+  @type nif_append_bucket_result ::
+          :ok
+          | {:error, {:max_bucket_size_exceeded, got :: pos_integer(), max :: pos_integer()}}
+          | {:error, :set_full}
 
   @typedoc """
   Response returned from the NIF when selecting an element at a given index
@@ -64,8 +217,9 @@ defmodule Discord.SortedSet.Types do
   `{:ok, element :: any()}` is returned by the NIF to indicate that the index was in bounds and an
   element was found at the given index
 
-  `{:error, :index_out_of_bounds}` is returned by the NIF to indicate that the index was not
-  within the bounds of the SortedSet.
+  `{:error, {:index_out_of_bounds, index, size}}` is returned by the NIF to indicate that the
+  index was not within the bounds of the SortedSet, `size` is the number of items at the time of
+  the read.
 
   The NIF provides more detailed by less conventional return values, these are coerced in the
   `SortedSet` module to more conventional responses.  Specifically in the case of `at/3` it is a
@@ -73,7 +227,32 @@ defmodule Discord.SortedSet.Types do
   there is no need to pay the penalty of copying this default value into and back out of NIF
   space.
   """
-  @type nif_at_result :: {:ok, element :: any()} | {:error, :index_out_of_bounds}
+  @type nif_at_result :: {:ok, element :: any()} | index_out_of_bounds_error()
+
+  @type index_out_of_bounds_error ::
+          {:error, {:index_out_of_bounds, index :: integer(), size :: non_neg_integer()}}
+
+  @typedoc """
+  A reduction `Discord.SortedSet.fold/4` computes without copying the elements out of the set.
+
+  `{:count, pattern}` counts the elements matching the pattern, where the atom `:_` matches any
+  term, tuples and lists match element by element and any other term matches itself.
+  `{:sum, index}`, `{:min, index}` and `{:max, index}` reduce the element at `index` of every
+  element, which all have to be tuples.
+  """
+  @type fold_spec ::
+          {:count, pattern :: supported_term()}
+          | {:sum, index :: non_neg_integer()}
+          | {:min, index :: non_neg_integer()}
+          | {:max, index :: non_neg_integer()}
+
+  @typedoc """
+  Where a fold left off, passed to the next step of the same fold.  The cursor is a plain term
+  holding the last element visited and the result so far.
+  """
+  @type fold_cursor :: {:sorted_set_fold, after :: supported_term(), accumulator :: term()}
+
+  @type fold_errors :: {:error, :missing_element | :not_integer | :overflow}
 
   @typedoc """
   Responses returned from the NIF when finding an element in the set
@@ -85,6 +264,35 @@ defmodule Discord.SortedSet.Types do
   """
   @type nif_find_result :: {:ok, index :: integer()} | {:error, :not_found}
 
+  @typedoc """
+  Options for `Discord.SortedSet.find_index/3`
+
+  `insertion_point: true` returns `{:not_found, index}` for an element that is not in the set,
+  where `index` is the index the element would be inserted at.  Defaults to `false`, which
+  returns `nil`.
+  """
+  @type find_index_option :: {:insertion_point, boolean()}
+
+  @typedoc """
+  Response returned from the NIF when looking up the stable ID of an element
+
+  `{:ok, id}` is returned by the NIF if the element is present, `{:error, :not_found}` if it is
+  not.  `{:error, :stable_ids_disabled}` is returned if the set was not created with the
+  `:stable_ids` option.
+  """
+  @type nif_id_of_result ::
+          {:ok, id :: pos_integer()} | {:error, :not_found} | {:error, :stable_ids_disabled}
+
+  @typedoc """
+  Response returned from the NIF when looking up an element by its stable ID
+
+  `{:ok, element}` is returned by the NIF if an element with the ID is present,
+  `{:error, :not_found}` if it is not.  `{:error, :stable_ids_disabled}` is returned if the set
+  was not created with the `:stable_ids` option.
+  """
+  @type nif_get_by_id_result ::
+          {:ok, element :: any()} | {:error, :not_found} | {:error, :stable_ids_disabled}
+
   @typedoc """
   Responses returned from the NIF when removing an element in the set
 
@@ -102,9 +310,261 @@ defmodule Discord.SortedSet.Types do
   """
   @type nif_remove_result :: {:ok, :removed, index :: integer()} | {:error, :not_found}
 
+  @typedoc """
+  Response returned from the NIF when requesting the last modification time of the set
+
+  `{:ok, system_time :: non_neg_integer(), idle_time :: non_neg_integer()}` is returned by the
+  NIF, `system_time` is the wall-clock time of the last mutation in milliseconds since the Unix
+  epoch and `idle_time` is the number of milliseconds elapsed since then according to a monotonic
+  clock.
+  A set that has never been mutated reports the time it was created.
+  """
+  @type nif_last_modified_result ::
+          {:ok, system_time :: non_neg_integer(), idle_time :: non_neg_integer()}
+
+  @typedoc """
+  Summary of the state of a set, see `Discord.SortedSet.health/1`.
+  """
+  @type health :: %{
+          size: non_neg_integer(),
+          buckets: non_neg_integer(),
+          min: supported_term() | nil,
+          max: supported_term() | nil,
+          version: non_neg_integer(),
+          frozen: boolean(),
+          memory: non_neg_integer()
+        }
+
+  @typedoc """
+  Response returned from the NIF when retrieving the metadata attached to the set
+
+  `{:ok, meta :: any()}` is returned by the NIF to indicate that a metadata term has been attached
+  to the set.
+
+  `{:error, :not_found}` is returned by the NIF to indicate that no metadata term has been
+  attached to the set.
+  """
+  @type nif_get_meta_result :: {:ok, meta :: any()} | {:error, :not_found}
+
+  @typedoc """
+  Response returned from the NIF when concatenating two sets
+
+  `:ok` is returned by the NIF to indicate that the contents of the second set were moved onto the
+  end of the first set.
+
+  `{:error, :overlapping}` is returned by the NIF to indicate that the largest element of the
+  first set is not less than the smallest element of the second set, neither set is modified.
+
+  `{:error, :set_full}` is returned by the NIF to indicate that the combined set would grow past
+  the configured `:max_elements` of the first set, neither set is modified.
+
+  `{:error, :collation_mismatch}` is returned by the NIF to indicate that the sets were created
+  with different `:collation` options, neither set is modified.
+
+  `{:error, :comparator_mismatch}` is returned by the NIF to indicate that the sets were created
+  with different `:comparator` or `:tuple_order` options, neither set is modified.
+
+  `{:error, :charlists_mismatch}` is returned by the NIF to indicate that only one of the sets was
+  created with `charlists: true`, neither set is modified.
+  """
+  @type nif_concat_result ::
+          :ok
+          | {:error, :overlapping}
+          | {:error, :set_full}
+          | {:error, :collation_mismatch}
+          | {:error, :comparator_mismatch}
+          | {:error, :charlists_mismatch}
+
+  @typedoc """
+  Response returned from the NIF when partitioning a set
+
+  `{:ok, lower :: reference(), upper :: reference()}` is returned by the NIF, `lower` identifies a
+  new SortedSet holding every element less than the pivot and `upper` identifies a new SortedSet
+  holding every element greater than or equal to the pivot.
+  """
+  @type nif_partition_result :: {:ok, lower :: sorted_set(), upper :: sorted_set()}
+
+  @typedoc """
+  Message sent to subscribed processes whenever an element is added to or removed from a set, see
+  `Discord.SortedSet.subscribe/2`.
+
+  `set` is the set that was mutated, `element` is the element that was added or removed and
+  `index` is the index it was added at or removed from.  `version` is incremented by every
+  mutation of the set, including mutations that do not produce an event, so a gap in the versions
+  received indicates that the set was changed by some other operation.
+
+  Sets created with the `:stable_ids` option append the stable ID of the element to the event.
+  """
+  @type event ::
+          {:sorted_set_event, set :: sorted_set(), :added | :removed,
+           element :: supported_term(), index :: non_neg_integer(), version :: pos_integer()}
+          | {:sorted_set_event, set :: sorted_set(), :added | :removed,
+             element :: supported_term(), index :: non_neg_integer(), version :: pos_integer(),
+             id :: pos_integer()}
+
+  @typedoc """
+  Watermarks that can be used to register an alert, see `Discord.SortedSet.alert/3`.
+
+  `{:size, threshold}` is reached once the set holds at least `threshold` elements.
+
+  `{:memory, threshold}` is reached once the approximate memory used by the set's own storage is
+  at least `threshold` bytes.  The estimate includes space reserved for future elements but not
+  heap data owned by the elements, like the contents of binaries.
+  """
+  @type watermark :: {:size, pos_integer()} | {:memory, pos_integer()}
+
+  @typedoc """
+  Message sent to the registered process when a set reaches a watermark, see
+  `Discord.SortedSet.alert/3`.  `current` is the size or approximate memory of the set at the
+  time the alert was triggered.
+  """
+  @type alert :: {:sorted_set_alert, set :: sorted_set(), watermark(), current :: pos_integer()}
+
+  @typedoc """
+  Message sent to the registered collector when a set is garbage collected, see
+  `Discord.SortedSet.register_collector/1`.  `meta` is the metadata of the set or `nil` if none
+  was stored.  `memory` is the approximate memory of the set, estimated the same way as for
+  memory watermarks, and `version` is the number of mutations the set went through.
+  """
+  @type destroyed ::
+          {:sorted_set_destroyed, meta :: supported_term() | nil,
+           [size: non_neg_integer(), memory: non_neg_integer(), version: non_neg_integer()]}
+
+  @typedoc """
+  Message sent to the caller of an asynchronous operation once the operation completes, see
+  `Discord.SortedSet.async_to_list/1`.  `tag` is the reference returned when the operation was
+  started and `result` is the same value the synchronous version of the operation would return.
+  """
+  @type reply :: {:sorted_set_reply, tag :: reference(), result :: any()}
+
   @typedoc """
   Only a subset of Elixir types are supported by the nif, the semantic type `supported_term` can
   be used as a shorthand for terms of these supported types.
+
+  Terms are ordered like Elixir orders them, except for the atoms `:neg_infinity` and
+  `:infinity` which sort before and after every other term, wherever they appear.  They make
+  open-ended bounds for ranges, `{:score, :infinity}` sorts after every other `{:score, _}`.
   """
   @type supported_term :: integer() | atom() | tuple() | list() | String.t()
+
+  @typedoc """
+  Options that can be provided when constructing a SortedSet.
+
+  `{:max_elements, pos_integer()}` places a hard cap on the number of elements the set will hold.
+  Once the cap is reached adding a new element returns `{:error, :set_full}` instead of growing
+  the set, this is intended as a safety valve against runaway producers.
+
+  `{:initial_bucket_capacity, non_neg_integer()}` allocates every bucket with room for that many
+  elements up front, so bulk loads do not reallocate each bucket as it fills up.  The capacity can
+  not exceed the bucket size and can not be combined with the `:btree` backend, either raises an
+  `ArgumentError`.  Defaults to `0`, buckets grow as needed.
+
+  `{:backend, :buckets | :btree | {:bitset, universe}}` selects the storage backing the set.  The
+  default `:buckets` backend keeps the elements in a flat list of buckets and is the fastest
+  choice for most sets, `:btree` keeps the elements in a B+tree which keeps adds and removes cheap
+  on very large sets.  The bucket size has no effect on a `:btree` set.
+
+  `{:bitset, universe}` keeps the integers `0..universe - 1` as a compressed bitmap, which takes a
+  fraction of the memory of buckets for sets of small, dense integers like the shards a user is
+  present on.  The universe can be at most `1_048_576` and the backend can not be combined with a
+  `:collation` or a `:comparator`, either raises an `ArgumentError`.  Adding any other element
+  moves the set into buckets for good, so a bitset set accepts everything a `:buckets` set does.
+
+  `{:stable_ids, boolean()}` assigns every element a stable ID when it is added, see
+  `Discord.SortedSet.id_of/2`.  Unlike the index of an element its ID does not change when other
+  elements are added or removed.  Defaults to `false` as the IDs cost a second index of the
+  elements.
+
+  `{:cardinality_sketch, boolean()}` keeps a HyperLogLog sketch of the elements, see
+  `Discord.SortedSet.estimate_union_size/1`.  The sketch takes 4KiB and a hash of every element
+  added.  Defaults to `false`.
+
+  `{:tombstones, float()}` marks removed elements as tombstones instead of shifting the rest of
+  their bucket, which makes removals from large buckets cheaper.  A bucket is compacted once the
+  share of tombstones in it exceeds the given ratio, which has to be at least `0.0` and below
+  `1.0`.  `Discord.SortedSet.compact/1` drops every tombstone at once.  Only supported by the
+  `:buckets` backend.  Defaults to `0.0`, which removes elements right away.
+
+  `{:interpolation_search, boolean()}` locates integers by estimating their position from the
+  integers around them instead of halving the range with every comparison, which saves
+  comparisons when the integers are spread evenly, like snowflake IDs.  Other elements and
+  integers that are spread unevenly fall back to the usual binary search.  Only supported by the
+  `:buckets` backend.  Defaults to `false`.
+
+  `{:coalesce_writes, boolean()}` queues the writes of `Discord.SortedSet.add/2` and
+  `Discord.SortedSet.remove/2` instead of taking the lock of the set for each of them.  The next
+  call that locks the set applies the whole queue at once, which keeps a storm of writes from
+  fighting over the lock at the cost of reads possibly missing the writes queued last, see
+  `Discord.SortedSet.flush/1`.  Subscribers are notified when a write is applied.  Can not be
+  combined with `:single_owner`.  Defaults to `false`.
+
+  `{:single_owner, boolean()}` declares that the set is only ever used by the process that
+  creates it.  Such sets skip the read-write lock and check the calling process instead, any
+  other process gets `{:error, :not_owner}`.  The async operations run outside of the owning
+  process and return `{:error, :not_owner}` as well.  Defaults to `false`.
+
+  `{:charlists, boolean()}` stores charlists, non-empty lists of the printable Latin-1
+  characters Erlang prints as strings, as the binaries they spell out.  `'abc'` is then the same
+  element as `"abc"` and orders among the strings instead of among the lists, finding or
+  removing either form finds the other.  Charlists nested in tuples and lists are converted as
+  well, so are lists of small integers that merely look printable, like `[65, 66]`.  Reads return
+  the binary, subscribers are told about the element as it was given.  Defaults to `false`.
+
+  `{:collation, String.t()}` orders strings by the collation rules of a locale, like `"de"` or
+  `"sv-SE"`, instead of by their bytes.  Strings nested in tuples and lists are collated as well,
+  strings that the collation considers equal are ordered by their bytes.  Locales without
+  collation rules of their own use the root collation.
+
+  `{:collation, :natural}` orders strings in natural order instead, runs of digits are compared
+  by their numeric value so `"channel-2"` sorts before `"channel-10"`.
+
+  `{:comparator, [{non_neg_integer(), :asc | :desc}]}` orders tuples by a list of keys instead of
+  element by element, each key names a zero-based tuple element and a direction.  The first key
+  is the most significant, `[{1, :desc}, {0, :asc}]` orders `{name, score}` tuples by score
+  descending and then by name.  Tuples missing a key element sort before the tuples that have it
+  and tuples that are equal on every key are ordered as usual.  Elements that are not tuples are
+  not affected.
+
+  Keys can also be given as `{index | [index], :asc | :desc, [transform]}`, which makes the
+  comparator a small ordering program for sorts that the NIF could otherwise only do by calling
+  back into Elixir.  A list of indexes reaches into nested tuples, `{[1, 0], :asc}` orders
+  `{id, {joined_at, name}}` by `joined_at`.  The transforms are applied to the element before it
+  is compared:
+
+    * `{:cast, :integer}` compares strings holding a decimal integer as that integer.
+    * `{:cast, :string}` compares integers and atoms as their text.
+    * `:fold_case` compares strings and atoms by their lowercase form.
+
+  Tuples that only differ in what the transforms discard, like `{"Bob"}` and `{"bob"}` under
+  `:fold_case`, are still distinct items ordered by the tuple order.
+
+  `{:tuple_order, :erlang | :lexicographic}` decides how tuples are compared element by element.
+  Erlang compares the sizes of tuples first, so `{2}` sorts before `{1, 1}`.  With
+  `:lexicographic` tuples of any size are compared element by element like lists, a tuple sorts
+  right before the longer tuples it is a prefix of and `{1, 1}` sorts before `{2}`.  Tuples nested
+  in tuples are compared the same way, tuples nested in lists keep the Erlang order.  Defaults to
+  `:erlang`, the comparator falls back to this order for tuples that are equal on every key.
+  """
+  @type option ::
+          {:max_elements, pos_integer()}
+          | {:initial_bucket_capacity, non_neg_integer()}
+          | {:backend, :buckets | :btree | {:bitset, pos_integer()}}
+          | {:stable_ids, boolean()}
+          | {:cardinality_sketch, boolean()}
+          | {:tombstones, float()}
+          | {:interpolation_search, boolean()}
+          | {:single_owner, boolean()}
+          | {:coalesce_writes, boolean()}
+          | {:charlists, boolean()}
+          | {:collation, String.t() | :natural}
+          | {:comparator, [comparator_key()]}
+          | {:tuple_order, :erlang | :lexicographic}
+
+  @type comparator_key ::
+          {non_neg_integer(), :asc | :desc}
+          | {non_neg_integer() | [non_neg_integer()], :asc | :desc, [comparator_transform()]}
+
+  @type comparator_transform :: :fold_case | {:cast, :integer | :string}
+
+  @type options :: [option()]
 end
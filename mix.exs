@@ -68,7 +68,8 @@ defmodule SortedSet.MixProject do
     [
       sorted_set: [
         path: "native/sorted_set_nif",
-        mode: rustc_mode(Mix.env(), System.get_env("OPTIMIZE_NIF") == "true")
+        mode: rustc_mode(Mix.env(), System.get_env("OPTIMIZE_NIF") == "true"),
+        features: rustc_features()
       ]
     ]
   end
@@ -76,4 +77,10 @@ defmodule SortedSet.MixProject do
   defp rustc_mode(_, true), do: :release
   defp rustc_mode(:prod, _), do: :release
   defp rustc_mode(_, _), do: :debug
+
+  defp rustc_features do
+    [{"DEBUG_ALLOC_NIF", "debug-alloc"}, {"PROBE_STATS_NIF", "probe-stats"}]
+    |> Enum.filter(fn {variable, _} -> System.get_env(variable) == "true" end)
+    |> Enum.map(fn {_, feature} -> feature end)
+  end
 end
@@ -0,0 +1,180 @@
+//! Sums and extremes of a run of integers, computed next to the items.
+//!
+//! Rolling up a range of a set of integers used to copy every integer into the caller just to add
+//! them up.  Aggregating in place only hands back the result, however many items the range spans.
+//! Counting the tuples per value of one of their elements works the same way.
+
+use configuration::Configuration;
+use std::collections::BTreeMap;
+use supported_term::SupportedTerm;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Aggregate {
+    Sum,
+    Min,
+    Max,
+    Avg,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Aggregated {
+    Integer(i64),
+    Float(f64),
+
+    /// The minimum, maximum and average of no items.
+    Empty,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AggregateError {
+    /// An item in the range is not an integer.
+    NotInteger,
+
+    /// The sum does not fit in 64 bits.
+    Overflow,
+}
+
+/// Aggregates the items, which all have to be integers.  The sum of no items is 0, the other
+/// aggregates of no items are `Empty`.  Sums are accumulated in 128 bits so only a final sum
+/// outside of 64 bits overflows, averages never do.
+pub fn aggregate<'a, I>(items: I, aggregate: Aggregate) -> Result<Aggregated, AggregateError>
+where
+    I: IntoIterator<Item = &'a SupportedTerm>,
+{
+    let mut count: u64 = 0;
+    let mut sum: i128 = 0;
+    let mut min = i64::MAX;
+    let mut max = i64::MIN;
+
+    for item in items {
+        let value = match *item {
+            SupportedTerm::Integer(value) => value,
+            _ => return Err(AggregateError::NotInteger),
+        };
+
+        count += 1;
+        sum += i128::from(value);
+        min = min.min(value);
+        max = max.max(value);
+    }
+
+    if count == 0 {
+        return Ok(match aggregate {
+            Aggregate::Sum => Aggregated::Integer(0),
+            _ => Aggregated::Empty,
+        });
+    }
+
+    match aggregate {
+        Aggregate::Sum if sum > i128::from(i64::MAX) || sum < i128::from(i64::MIN) => {
+            Err(AggregateError::Overflow)
+        }
+        Aggregate::Sum => Ok(Aggregated::Integer(sum as i64)),
+        Aggregate::Min => Ok(Aggregated::Integer(min)),
+        Aggregate::Max => Ok(Aggregated::Integer(max)),
+        Aggregate::Avg => Ok(Aggregated::Float(sum as f64 / count as f64)),
+    }
+}
+
+/// Counts the tuples among the items by the value of their element at `index`, in one pass.  The
+/// values are returned in order, items that are not tuples or have no element at `index` are not
+/// counted.
+pub fn group_counts<'a, I>(items: I, index: usize) -> Vec<(SupportedTerm, usize)>
+where
+    I: IntoIterator<Item = &'a SupportedTerm>,
+{
+    let mut counts: BTreeMap<&SupportedTerm, usize> = BTreeMap::new();
+
+    for item in items {
+        let elements = match item {
+            SupportedTerm::Tuple(elements) => elements,
+            SupportedTerm::Keyed(keyed) => &keyed.items,
+            _ => continue,
+        };
+
+        if let Some(value) = elements.get(index) {
+            *counts.entry(value).or_insert(0) += 1;
+        }
+    }
+
+    counts
+        .into_iter()
+        .map(|(value, count)| (Configuration::strip(value.clone()), count))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use aggregate::{aggregate, group_counts, Aggregate, AggregateError, Aggregated};
+    use supported_term::SupportedTerm::{self, Atom, Integer};
+
+    #[test]
+    fn test_aggregates_integers() {
+        let items: Vec<SupportedTerm> = vec![Integer(-4), Integer(1), Integer(9)];
+
+        assert_eq!(
+            aggregate(&items, Aggregate::Sum),
+            Ok(Aggregated::Integer(6))
+        );
+        assert_eq!(
+            aggregate(&items, Aggregate::Min),
+            Ok(Aggregated::Integer(-4))
+        );
+        assert_eq!(
+            aggregate(&items, Aggregate::Max),
+            Ok(Aggregated::Integer(9))
+        );
+        assert_eq!(
+            aggregate(&items, Aggregate::Avg),
+            Ok(Aggregated::Float(2.0))
+        );
+
+        assert_eq!(aggregate(&[], Aggregate::Sum), Ok(Aggregated::Integer(0)));
+        assert_eq!(aggregate(&[], Aggregate::Avg), Ok(Aggregated::Empty));
+    }
+
+    #[test]
+    fn test_refuses_other_items_and_overflows() {
+        let mixed = vec![Integer(1), Atom(String::from("a"))];
+        assert_eq!(
+            aggregate(&mixed, Aggregate::Max),
+            Err(AggregateError::NotInteger)
+        );
+
+        let large = vec![Integer(i64::MAX), Integer(i64::MAX)];
+        assert_eq!(
+            aggregate(&large, Aggregate::Sum),
+            Err(AggregateError::Overflow)
+        );
+        assert_eq!(
+            aggregate(&large, Aggregate::Avg),
+            Ok(Aggregated::Float(i64::MAX as f64))
+        );
+    }
+
+    #[test]
+    fn test_group_counts_by_element() {
+        let role =
+            |name: &str, id: i64| SupportedTerm::Tuple(vec![Atom(String::from(name)), Integer(id)]);
+        let items = vec![
+            Integer(7),
+            role("admin", 1),
+            role("admin", 4),
+            role("member", 2),
+            SupportedTerm::Tuple(vec![]),
+            role("guest", 3),
+            role("member", 5),
+        ];
+
+        assert_eq!(
+            group_counts(&items, 0),
+            vec![
+                (Atom(String::from("admin")), 2),
+                (Atom(String::from("guest")), 1),
+                (Atom(String::from("member")), 2),
+            ]
+        );
+        assert_eq!(group_counts(&items, 1).len(), 5);
+        assert!(group_counts(&items, 2).is_empty());
+    }
+}
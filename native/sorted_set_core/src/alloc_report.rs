@@ -0,0 +1,127 @@
+//! Allocation reports for diagnosing suspected leaks, enabled with the `debug-alloc` feature.
+//!
+//! `SortedSet::memory` only estimates the storage of the buckets, the strings and the elements
+//! of tuples and lists live in allocations of their own that it does not see.  A report walks
+//! every item of a set and tallies the allocations that are reachable from it by category.
+
+use collation::CollatedString;
+use comparator::KeyedTuple;
+use sorted_set::SortedSet;
+use std::mem;
+use supported_term::SupportedTerm;
+
+/// Number of allocations of a category and the bytes they hold.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tally {
+    pub count: usize,
+    pub bytes: usize,
+}
+
+impl Tally {
+    fn add(&mut self, bytes: usize) {
+        self.count += 1;
+        self.bytes += bytes;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AllocReport {
+    /// One allocation per bucket, the bytes are the estimate of `SortedSet::memory`.
+    pub buckets: Tally,
+
+    /// Atoms, binaries and collated strings.
+    pub strings: Tally,
+
+    /// Elements of tuples, including the tuples of a set with a comparator.
+    pub tuples: Tally,
+
+    /// Elements of lists.
+    pub lists: Tally,
+}
+
+impl AllocReport {
+    pub fn of(set: &SortedSet) -> AllocReport {
+        let layout = set.layout();
+        let mut report = AllocReport {
+            buckets: Tally {
+                count: layout.len(),
+                bytes: set.memory(),
+            },
+            ..AllocReport::default()
+        };
+
+        for item in layout.iter().flat_map(|bucket| bucket.iter()) {
+            report.walk(item);
+        }
+
+        report
+    }
+
+    fn walk(&mut self, term: &SupportedTerm) {
+        match *term {
+            SupportedTerm::Integer(_) | SupportedTerm::Infinity | SupportedTerm::NegInfinity => {}
+            SupportedTerm::Atom(ref value) | SupportedTerm::Bitstring(ref value) => {
+                self.strings.add(value.capacity());
+            }
+            SupportedTerm::Collated(ref collated) => {
+                self.strings
+                    .add(mem::size_of::<CollatedString>() + collated.value.capacity());
+            }
+            SupportedTerm::Tuple(ref items) => {
+                self.tuples.add(elements(items));
+                self.walk_all(items);
+            }
+            SupportedTerm::Keyed(ref keyed) => {
+                self.tuples
+                    .add(mem::size_of::<KeyedTuple>() + elements(&keyed.items));
+                self.walk_all(&keyed.items);
+            }
+            SupportedTerm::List(ref items) => {
+                self.lists.add(elements(items));
+                self.walk_all(items);
+            }
+        }
+    }
+
+    fn walk_all(&mut self, terms: &[SupportedTerm]) {
+        for term in terms {
+            self.walk(term);
+        }
+    }
+}
+
+fn elements(items: &Vec<SupportedTerm>) -> usize {
+    items.capacity() * mem::size_of::<SupportedTerm>()
+}
+
+#[cfg(test)]
+mod tests {
+    use alloc_report::{AllocReport, Tally};
+    use sorted_set::SortedSet;
+    use supported_term::SupportedTerm::{Atom, Bitstring, Integer, List, Tuple};
+
+    #[test]
+    fn test_report_tallies_nested_allocations() {
+        let mut set = SortedSet::default();
+        set.add(Integer(1));
+        set.add(Bitstring(String::from("abc")));
+        set.add(Tuple(vec![Atom(String::from("a")), List(vec![Integer(2)])]));
+
+        let report = AllocReport::of(&set);
+
+        assert_eq!(report.buckets.count, 1);
+        assert_eq!(report.buckets.bytes, set.memory());
+        assert_eq!(report.strings.count, 2);
+        assert!(report.strings.bytes >= 4);
+        assert_eq!(report.tuples.count, 1);
+        assert_eq!(report.lists.count, 1);
+    }
+
+    #[test]
+    fn test_empty_set_reports_its_bucket() {
+        let report = AllocReport::of(&SortedSet::default());
+
+        assert_eq!(report.strings, Tally::default());
+        assert_eq!(report.tuples, Tally::default());
+    }
+}
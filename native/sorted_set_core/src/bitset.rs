@@ -0,0 +1,309 @@
+use metrics::BucketStats;
+use roaring::RoaringBitmap;
+use std::mem;
+use std::sync::Arc;
+use AddResult;
+use Item;
+use RemoveResult;
+use RepairReport;
+
+/// Largest universe a bitset backed set can be configured with.  Every item of the universe is
+/// kept once in a table shared by all sets, a universe of this size costs 32MiB of table.
+pub const MAX_UNIVERSE: u32 = 1 << 20;
+
+/// Items of a dense universe `0..universe` kept as a roaring bitmap of their positions.
+///
+/// A set of small integers in the general storage holds every item in a bucket, a bitmap holds
+/// a bit per item instead.  The items themselves live in a table of the whole universe that is
+/// shared between every bitset backed set, so borrowing an item works the same as it does for the
+/// other backends.  Items outside of the universe can not be held, the SortedSet moves such sets
+/// into buckets before adding them.
+#[derive(Debug)]
+pub struct Bitset<T> {
+    bits: RoaringBitmap,
+    universe: u32,
+    items: Arc<[T]>,
+}
+
+impl<T: Item> Bitset<T> {
+    /// Creates an empty Bitset for the universe, None if the type has no dense universe.
+    pub fn new(universe: u32) -> Option<Bitset<T>> {
+        T::dense_universe(universe).map(|items| Bitset {
+            bits: RoaringBitmap::new(),
+            universe,
+            items,
+        })
+    }
+
+    /// Position of the item in the universe, None if the item is not a member of it.
+    fn position(&self, item: &T) -> Option<u32> {
+        item.dense_index()
+            .filter(|&position| position < self.universe)
+    }
+
+    /// True if the item is a member of the universe and can be held.
+    pub fn fits(&self, item: &T) -> bool {
+        self.position(item).is_some()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bits.len() as usize
+    }
+
+    /// Adds an item of the universe, see `fits`.
+    pub fn add(&mut self, item: T) -> AddResult {
+        let position = self
+            .position(&item)
+            .expect("Item outside of the bitset universe");
+        let added = self.bits.insert(position);
+        let idx = self.bits.rank(position) as usize - 1;
+
+        if added {
+            AddResult::Added(idx)
+        } else {
+            AddResult::Duplicate(idx)
+        }
+    }
+
+    pub fn remove(&mut self, item: &T) -> RemoveResult {
+        match self.position(item) {
+            Some(position) if self.bits.remove(position) => {
+                RemoveResult::Removed(self.bits.rank(position) as usize)
+            }
+            _ => RemoveResult::NotFound,
+        }
+    }
+
+    /// True if the bit of `item` is set, without ranking it like `find_index` does.
+    pub fn contains(&self, item: &T) -> bool {
+        match self.position(item) {
+            Some(position) => self.bits.contains(position),
+            None => false,
+        }
+    }
+
+    pub fn find_index(&self, item: &T) -> Option<usize> {
+        match self.position(item) {
+            Some(position) if self.bits.contains(position) => {
+                Some(self.bits.rank(position) as usize - 1)
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the number of items less than `item`.  Items outside of the universe are placed
+    /// by a binary search over the items held.
+    pub fn rank(&self, item: &T) -> usize {
+        if let Some(position) = self.position(item) {
+            return self.bits.rank(position) as usize - self.bits.contains(position) as usize;
+        }
+
+        let (mut low, mut high) = (0, self.len());
+
+        while low < high {
+            let middle = low + (high - low) / 2;
+
+            if self.at(middle).is_some_and(|current| current < item) {
+                low = middle + 1;
+            } else {
+                high = middle;
+            }
+        }
+
+        low
+    }
+
+    pub fn at(&self, index: usize) -> Option<&T> {
+        if index >= self.len() {
+            return None;
+        }
+
+        self.bits
+            .select(index as u32)
+            .map(|position| &self.items[position as usize])
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.bits
+            .iter()
+            .map(move |position| &self.items[position as usize])
+    }
+
+    pub fn iter_rev(&self) -> impl Iterator<Item = &T> {
+        self.bits
+            .iter()
+            .rev()
+            .map(move |position| &self.items[position as usize])
+    }
+
+    pub fn iter_from(&self, index: usize) -> impl Iterator<Item = &T> {
+        let start = if index < self.len() {
+            self.bits.select(index as u32)
+        } else {
+            None
+        };
+        let items = &self.items;
+
+        start
+            .into_iter()
+            .flat_map(move |start| self.bits.range(start..))
+            .map(move |position| &items[position as usize])
+    }
+
+    pub fn drain(&mut self) -> Vec<T> {
+        let items = self.iter().cloned().collect();
+        self.bits.clear();
+        items
+    }
+
+    /// Adds items of the universe, see `fits`.
+    pub fn extend_sorted(&mut self, items: Vec<T>) {
+        for item in items {
+            self.add(item);
+        }
+    }
+
+    /// Splits off the items greater than or equal to `pivot`, the items less than the pivot are
+    /// kept.
+    pub fn split_off(&mut self, pivot: &T) -> Bitset<T> {
+        let mut upper = Bitset {
+            bits: RoaringBitmap::new(),
+            universe: self.universe,
+            items: self.items.clone(),
+        };
+
+        if let Some(start) = self.bits.select(self.rank(pivot) as u32) {
+            upper.bits = self.bits.clone();
+            upper.bits.remove_range(..start);
+            self.bits.remove_range(start..);
+        }
+
+        upper
+    }
+
+    /// The bitmap and the bookkeeping, the shared table of the universe is not included.
+    pub fn memory(&self) -> usize {
+        mem::size_of::<Bitset<T>>() + self.bits.serialized_size()
+    }
+
+    /// Returns every run of consecutive items as a bucket, the runs borrow from the table of the
+    /// universe.
+    pub fn layout(&self) -> Vec<&[T]> {
+        let mut result = Vec::new();
+        let mut positions = self.bits.iter();
+
+        if let Some(first) = positions.next() {
+            let (mut start, mut end) = (first as usize, first as usize + 1);
+
+            for position in positions {
+                let position = position as usize;
+
+                if position != end {
+                    result.push(&self.items[start..end]);
+                    start = position;
+                }
+
+                end = position + 1;
+            }
+
+            result.push(&self.items[start..end]);
+        }
+
+        result
+    }
+
+    /// Reports the runs of consecutive items as buckets.
+    pub fn bucket_stats(&self) -> BucketStats {
+        let mut stats = BucketStats::new();
+
+        for run in self.layout() {
+            stats.observe(run.len());
+        }
+
+        stats
+    }
+
+    /// A bitmap is always sorted and free of duplicates, there is nothing to repair.
+    pub fn repair(&mut self) -> RepairReport {
+        RepairReport {
+            recorded_size: self.len(),
+            size: self.len(),
+            ..RepairReport::default()
+        }
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.bits
+            .min()
+            .map(|position| &self.items[position as usize])
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.bits
+            .max()
+            .map(|position| &self.items[position as usize])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bitset::Bitset;
+    use supported_term::SupportedTerm::{self, Atom, Integer};
+    use AddResult::{Added, Duplicate};
+    use RemoveResult::{NotFound, Removed};
+
+    fn integers(bitset: &Bitset<SupportedTerm>) -> Vec<i64> {
+        bitset
+            .iter()
+            .map(|item| match *item {
+                Integer(value) => value,
+                _ => panic!("Unexpected item"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_positions_are_ranks() {
+        let mut bitset = Bitset::new(100).unwrap();
+
+        assert_eq!(bitset.add(Integer(40)), Added(0));
+        assert_eq!(bitset.add(Integer(7)), Added(0));
+        assert_eq!(bitset.add(Integer(99)), Added(2));
+        assert_eq!(bitset.add(Integer(40)), Duplicate(1));
+
+        assert!(!bitset.fits(&Integer(100)));
+        assert!(!bitset.fits(&Integer(-1)));
+        assert!(!bitset.fits(&Atom(String::from("a"))));
+
+        assert_eq!(bitset.find_index(&Integer(99)), Some(2));
+        assert_eq!(bitset.find_index(&Integer(98)), None);
+        assert_eq!(bitset.rank(&Integer(41)), 2);
+        assert_eq!(bitset.rank(&Integer(-5)), 0);
+        assert_eq!(bitset.rank(&Integer(500)), 3);
+        assert_eq!(bitset.rank(&Atom(String::from("a"))), 3);
+        assert_eq!(bitset.at(1), Some(&Integer(40)));
+        assert_eq!(bitset.at(3), None);
+
+        assert_eq!(bitset.remove(&Integer(7)), Removed(0));
+        assert_eq!(bitset.remove(&Integer(7)), NotFound);
+        assert_eq!(bitset.remove(&Integer(1_000)), NotFound);
+        assert_eq!(integers(&bitset), vec![40, 99]);
+    }
+
+    #[test]
+    fn test_runs_and_splits() {
+        let mut bitset = Bitset::new(64).unwrap();
+        bitset.extend_sorted((0..5).chain(10..12).chain(20..21).map(Integer).collect());
+
+        let runs: Vec<usize> = bitset.layout().iter().map(|run| run.len()).collect();
+        assert_eq!(runs, vec![5, 2, 1]);
+        assert_eq!(bitset.iter_from(4).count(), 4);
+        assert_eq!(bitset.iter_from(8).count(), 0);
+
+        let upper = bitset.split_off(&Integer(8));
+        assert_eq!(integers(&bitset), vec![0, 1, 2, 3, 4]);
+        assert_eq!(integers(&upper), vec![10, 11, 20]);
+        assert_eq!(upper.first(), Some(&Integer(10)));
+        assert_eq!(upper.last(), Some(&Integer(20)));
+    }
+}
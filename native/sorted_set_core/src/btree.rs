@@ -0,0 +1,844 @@
+use metrics::BucketStats;
+use std::cmp::{min, Ordering};
+use std::mem;
+use std::slice;
+use AddResult;
+use Item;
+use RemoveResult;
+use RepairReport;
+
+/// Maximum number of items a leaf holds before it is split.
+const LEAF_CAPACITY: usize = 64;
+
+/// Maximum number of children a branch holds before it is split, the per-child counts of a full
+/// branch fill two 64 byte cache lines.
+const BRANCH_CAPACITY: usize = 16;
+
+/// Nodes holding less than a quarter of their capacity are merged into a neighbor when possible.
+const LEAF_MINIMUM: usize = LEAF_CAPACITY / 4;
+const BRANCH_MINIMUM: usize = BRANCH_CAPACITY / 4;
+
+/// Bulk loading leaves some room in every node so the first inserts after a load do not
+/// immediately split every node.
+const LEAF_FILL: usize = LEAF_CAPACITY * 3 / 4;
+const BRANCH_FILL: usize = BRANCH_CAPACITY * 3 / 4;
+
+/// A B+tree keeping the number of items under every child, which lets the rank of an item and
+/// the item at a rank be found on the way down from the root without any parent pointers.
+///
+/// Unlike the flat Vec of buckets an insert only ever shifts the items of one leaf and the
+/// children of the branches on its path, which keeps inserts cheap on very large sets.
+#[derive(Debug)]
+pub struct BTree<T> {
+    root: Node<T>,
+    size: usize,
+}
+
+#[derive(Debug)]
+enum Node<T> {
+    Leaf(Vec<T>),
+    Branch(Box<Branch<T>>),
+}
+
+#[derive(Debug)]
+struct Branch<T> {
+    /// `keys[i]` is greater than every item under `children[i]` and less than or equal to every
+    /// item under `children[i + 1]`.
+    keys: Vec<T>,
+
+    /// `counts[i]` is the number of items under `children[i]`.
+    counts: Vec<usize>,
+
+    children: Vec<Node<T>>,
+}
+
+impl<T: Item> BTree<T> {
+    pub fn new() -> BTree<T> {
+        BTree {
+            root: Node::Leaf(Vec::new()),
+            size: 0,
+        }
+    }
+
+    /// Bulk loads items that are already sorted and free of duplicates, building the tree from
+    /// the leaves up instead of inserting the items one at a time.
+    pub fn from_sorted(items: Vec<T>) -> BTree<T> {
+        let size = items.len();
+
+        if size == 0 {
+            return BTree::new();
+        }
+
+        let mut level: Vec<(T, Node<T>)> = Vec::with_capacity(size / LEAF_FILL + 1);
+        let mut items = items.into_iter().peekable();
+
+        while items.peek().is_some() {
+            let leaf: Vec<T> = items.by_ref().take(LEAF_FILL).collect();
+            level.push((leaf[0].clone(), Node::Leaf(leaf)));
+        }
+
+        while level.len() > 1 {
+            let mut next_level = Vec::with_capacity(level.len() / BRANCH_FILL + 1);
+            let mut nodes = level.into_iter().peekable();
+
+            while nodes.peek().is_some() {
+                let group: Vec<(T, Node<T>)> = nodes.by_ref().take(BRANCH_FILL).collect();
+                let mut keys = Vec::with_capacity(group.len());
+                let mut counts = Vec::with_capacity(group.len());
+                let mut children = Vec::with_capacity(group.len());
+
+                for (key, node) in group.into_iter() {
+                    keys.push(key);
+                    counts.push(node.len());
+                    children.push(node);
+                }
+
+                // The first key becomes the key of the branch itself
+                let first = keys.remove(0);
+
+                next_level.push((
+                    first,
+                    Node::Branch(Box::new(Branch {
+                        keys,
+                        counts,
+                        children,
+                    })),
+                ));
+            }
+
+            level = next_level;
+        }
+
+        let (_, root) = level.remove(0);
+
+        BTree { root, size }
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn add(&mut self, item: T) -> AddResult {
+        let (result, split) = self.root.insert(item);
+
+        if let Some((key, sibling)) = split {
+            let left = mem::replace(&mut self.root, Node::Leaf(Vec::new()));
+
+            self.root = Node::Branch(Box::new(Branch {
+                keys: vec![key],
+                counts: vec![left.len(), sibling.len()],
+                children: vec![left, sibling],
+            }));
+        }
+
+        if let AddResult::Added(_) = result {
+            self.size += 1;
+        }
+
+        result
+    }
+
+    pub fn remove(&mut self, item: &T) -> RemoveResult {
+        let result = self.root.remove(item);
+
+        if let RemoveResult::Removed(_) = result {
+            self.size -= 1;
+
+            // Collapse a root that has been reduced to a single child
+            loop {
+                let child = match self.root {
+                    Node::Branch(ref mut branch) if branch.children.len() == 1 => {
+                        branch.children.pop()
+                    }
+                    _ => None,
+                };
+
+                match child {
+                    Some(child) => self.root = child,
+                    None => break,
+                }
+            }
+        }
+
+        result
+    }
+
+    /// True if the tree holds `item`, the counts of the branches are not summed on the way down.
+    pub fn contains(&self, item: &T) -> bool {
+        let mut node = &self.root;
+
+        loop {
+            match *node {
+                Node::Leaf(ref items) => return items.binary_search(item).is_ok(),
+                Node::Branch(ref branch) => {
+                    node = &branch.children[child_index(&branch.keys, item)]
+                }
+            }
+        }
+    }
+
+    pub fn find_index(&self, item: &T) -> Option<usize> {
+        let mut node = &self.root;
+        let mut offset = 0;
+
+        loop {
+            match *node {
+                Node::Leaf(ref items) => {
+                    return match items.binary_search(item) {
+                        Ok(idx) => Some(offset + idx),
+                        Err(_) => None,
+                    };
+                }
+                Node::Branch(ref branch) => {
+                    let child_idx = child_index(&branch.keys, item);
+                    offset += branch.counts[..child_idx].iter().sum::<usize>();
+                    node = &branch.children[child_idx];
+                }
+            }
+        }
+    }
+
+    /// Returns the number of items less than `item`.
+    pub fn rank(&self, item: &T) -> usize {
+        let mut node = &self.root;
+        let mut offset = 0;
+
+        loop {
+            match *node {
+                Node::Leaf(ref items) => {
+                    return match items.binary_search(item) {
+                        Ok(idx) | Err(idx) => offset + idx,
+                    };
+                }
+                Node::Branch(ref branch) => {
+                    let child_idx = child_index(&branch.keys, item);
+                    offset += branch.counts[..child_idx].iter().sum::<usize>();
+                    node = &branch.children[child_idx];
+                }
+            }
+        }
+    }
+
+    pub fn at(&self, index: usize) -> Option<&T> {
+        self.iter_from(index).next()
+    }
+
+    /// Iterates over the items in order starting from the item at `index`.
+    pub fn iter_from(&self, mut index: usize) -> Iter<'_, T> {
+        let mut iter = Iter {
+            stack: Vec::new(),
+            items: [].iter(),
+        };
+
+        if index >= self.size {
+            return iter;
+        }
+
+        let mut node = &self.root;
+
+        loop {
+            match *node {
+                Node::Leaf(ref items) => {
+                    iter.items = items[min(index, items.len())..].iter();
+                    return iter;
+                }
+                Node::Branch(ref branch) => {
+                    let mut child_idx = 0;
+
+                    while child_idx < branch.children.len() - 1 && index >= branch.counts[child_idx]
+                    {
+                        index -= branch.counts[child_idx];
+                        child_idx += 1;
+                    }
+
+                    iter.stack.push((branch, child_idx + 1));
+                    node = &branch.children[child_idx];
+                }
+            }
+        }
+    }
+
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.iter_from(0)
+    }
+
+    /// Appends items that are already sorted, free of duplicates and greater than every item in
+    /// the tree.
+    pub fn extend_sorted(&mut self, items: Vec<T>) {
+        if self.size == 0 {
+            *self = BTree::from_sorted(items);
+            return;
+        }
+
+        for item in items.into_iter() {
+            self.add(item);
+        }
+    }
+
+    /// Moves every item out of the tree, leaving it empty.
+    pub fn drain(&mut self) -> Vec<T> {
+        let root = mem::replace(&mut self.root, Node::Leaf(Vec::new()));
+        let mut result = Vec::with_capacity(self.size);
+
+        root.drain_into(&mut result);
+        self.size = 0;
+
+        result
+    }
+
+    /// Restores the invariants of the tree after they were broken, see `SortedSet::repair`.  The
+    /// leaves are reported as buckets.  The tree is always rebuilt from its items, which also
+    /// restores the keys and counts of the branches.
+    pub fn repair(&mut self) -> RepairReport {
+        let mut report = RepairReport {
+            recorded_size: self.size,
+            ..RepairReport::default()
+        };
+
+        {
+            let leaves = self.layout();
+
+            report.unsorted_buckets = leaves
+                .iter()
+                .filter(|leaf| leaf.windows(2).any(|pair| pair[0] > pair[1]))
+                .count();
+            report.overlapping_buckets = leaves
+                .windows(2)
+                .filter(|pair| match (pair[0].last(), pair[1].first()) {
+                    (Some(last), Some(first)) => last >= first,
+                    _ => false,
+                })
+                .count();
+        }
+
+        let mut items = self.drain();
+        let count = items.len();
+
+        items.sort();
+        items.dedup_by(|a, b| a.cmp(&b) == Ordering::Equal);
+        report.duplicates = count - items.len();
+
+        *self = BTree::from_sorted(items);
+        report.size = self.size;
+
+        report
+    }
+
+    pub fn memory(&self) -> usize {
+        self.root.memory()
+    }
+
+    /// Returns the items of every leaf, from left to right.
+    pub fn layout(&self) -> Vec<&[T]> {
+        let mut result = Vec::new();
+        self.root.collect_leaves(&mut result);
+        result
+    }
+
+    /// Reports the leaves as buckets.
+    pub fn bucket_stats(&self) -> BucketStats {
+        let mut stats = BucketStats::new();
+        self.root.observe_leaves(&mut stats);
+        stats
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.iter().next()
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        let mut node = &self.root;
+
+        loop {
+            match *node {
+                Node::Leaf(ref items) => return items.last(),
+                Node::Branch(ref branch) => node = &branch.children[branch.children.len() - 1],
+            }
+        }
+    }
+}
+
+impl<T: Item> Node<T> {
+    fn len(&self) -> usize {
+        match *self {
+            Node::Leaf(ref items) => items.len(),
+            Node::Branch(ref branch) => branch.counts.iter().sum(),
+        }
+    }
+
+    /// Number of entries held directly by the node, items for a leaf and children for a branch.
+    fn occupancy(&self) -> usize {
+        match *self {
+            Node::Leaf(ref items) => items.len(),
+            Node::Branch(ref branch) => branch.children.len(),
+        }
+    }
+
+    fn capacity(&self) -> usize {
+        match *self {
+            Node::Leaf(_) => LEAF_CAPACITY,
+            Node::Branch(_) => BRANCH_CAPACITY,
+        }
+    }
+
+    fn minimum(&self) -> usize {
+        match *self {
+            Node::Leaf(_) => LEAF_MINIMUM,
+            Node::Branch(_) => BRANCH_MINIMUM,
+        }
+    }
+
+    /// Inserts the item into the subtree, if the node overflows it is split and the key and node
+    /// that have to be added to the parent are returned along with the result.
+    fn insert(&mut self, item: T) -> (AddResult, Option<(T, Node<T>)>) {
+        match *self {
+            Node::Leaf(ref mut items) => match items.binary_search(&item) {
+                Ok(idx) => (AddResult::Duplicate(idx), None),
+                Err(idx) => {
+                    items.insert(idx, item);
+
+                    let split = if items.len() > LEAF_CAPACITY {
+                        let at = items.len() / 2;
+                        let right = items.split_off(at);
+                        Some((right[0].clone(), Node::Leaf(right)))
+                    } else {
+                        None
+                    };
+
+                    (AddResult::Added(idx), split)
+                }
+            },
+            Node::Branch(ref mut branch) => {
+                let child_idx = child_index(&branch.keys, &item);
+                let offset: usize = branch.counts[..child_idx].iter().sum();
+
+                let (result, split) = branch.children[child_idx].insert(item);
+
+                let result = match result {
+                    AddResult::Added(idx) => {
+                        branch.counts[child_idx] += 1;
+                        AddResult::Added(offset + idx)
+                    }
+                    AddResult::Duplicate(idx) => AddResult::Duplicate(offset + idx),
+                    AddResult::SetFull => AddResult::SetFull,
+                };
+
+                if let Some((key, sibling)) = split {
+                    let sibling_len = sibling.len();
+
+                    branch.counts[child_idx] -= sibling_len;
+                    branch.keys.insert(child_idx, key);
+                    branch.counts.insert(child_idx + 1, sibling_len);
+                    branch.children.insert(child_idx + 1, sibling);
+                }
+
+                let split = if branch.children.len() > BRANCH_CAPACITY {
+                    Some(branch.split())
+                } else {
+                    None
+                };
+
+                (result, split)
+            }
+        }
+    }
+
+    fn remove(&mut self, item: &T) -> RemoveResult {
+        match *self {
+            Node::Leaf(ref mut items) => match items.binary_search(item) {
+                Ok(idx) => {
+                    items.remove(idx);
+                    RemoveResult::Removed(idx)
+                }
+                Err(_) => RemoveResult::NotFound,
+            },
+            Node::Branch(ref mut branch) => {
+                let child_idx = child_index(&branch.keys, item);
+
+                match branch.children[child_idx].remove(item) {
+                    RemoveResult::Removed(idx) => {
+                        let offset: usize = branch.counts[..child_idx].iter().sum();
+
+                        branch.counts[child_idx] -= 1;
+                        branch.rebalance(child_idx);
+
+                        RemoveResult::Removed(offset + idx)
+                    }
+                    other => other,
+                }
+            }
+        }
+    }
+
+    fn drain_into(self, result: &mut Vec<T>) {
+        match self {
+            Node::Leaf(items) => result.extend(items),
+            Node::Branch(branch) => {
+                for child in branch.children.into_iter() {
+                    child.drain_into(result);
+                }
+            }
+        }
+    }
+
+    fn collect_leaves<'a>(&'a self, out: &mut Vec<&'a [T]>) {
+        match *self {
+            Node::Leaf(ref items) => out.push(&items[..]),
+            Node::Branch(ref branch) => {
+                for child in &branch.children {
+                    child.collect_leaves(out);
+                }
+            }
+        }
+    }
+
+    fn observe_leaves(&self, stats: &mut BucketStats) {
+        match *self {
+            Node::Leaf(ref items) => stats.observe(items.len()),
+            Node::Branch(ref branch) => {
+                for child in &branch.children {
+                    child.observe_leaves(stats);
+                }
+            }
+        }
+    }
+
+    fn memory(&self) -> usize {
+        match *self {
+            Node::Leaf(ref items) => {
+                mem::size_of::<Node<T>>() + items.capacity() * mem::size_of::<T>()
+            }
+            Node::Branch(ref branch) => {
+                let children: usize = branch.children.iter().map(|child| child.memory()).sum();
+
+                mem::size_of::<Node<T>>()
+                    + mem::size_of::<Branch<T>>()
+                    + branch.keys.capacity() * mem::size_of::<T>()
+                    + branch.counts.capacity() * mem::size_of::<usize>()
+                    + children
+            }
+        }
+    }
+}
+
+impl<T: Item> Branch<T> {
+    /// Splits off the upper half of the children, returning the key that separates the halves
+    /// and the new branch holding the upper half.
+    fn split(&mut self) -> (T, Node<T>) {
+        let at = self.children.len() / 2;
+
+        let children = self.children.split_off(at);
+        let counts = self.counts.split_off(at);
+        let mut keys = self.keys.split_off(at - 1);
+        let separator = keys.remove(0);
+
+        (
+            separator,
+            Node::Branch(Box::new(Branch {
+                keys,
+                counts,
+                children,
+            })),
+        )
+    }
+
+    /// Merges the child at `child_idx` into a neighbor if it has become underfull and the merged
+    /// node still fits in a single node.
+    fn rebalance(&mut self, child_idx: usize) {
+        if self.children.len() < 2 {
+            return;
+        }
+
+        let child = &self.children[child_idx];
+        if child.occupancy() >= child.minimum() {
+            return;
+        }
+
+        let left = if child_idx + 1 < self.children.len() {
+            child_idx
+        } else {
+            child_idx - 1
+        };
+
+        let combined = self.children[left].occupancy() + self.children[left + 1].occupancy();
+        if combined > self.children[left].capacity() {
+            return;
+        }
+
+        let right = self.children.remove(left + 1);
+        let right_count = self.counts.remove(left + 1);
+        let separator = self.keys.remove(left);
+
+        self.counts[left] += right_count;
+
+        match (&mut self.children[left], right) {
+            (&mut Node::Leaf(ref mut items), Node::Leaf(right_items)) => {
+                items.extend(right_items);
+            }
+            (&mut Node::Branch(ref mut branch), Node::Branch(mut right_branch)) => {
+                branch.keys.push(separator);
+                branch.keys.append(&mut right_branch.keys);
+                branch.counts.append(&mut right_branch.counts);
+                branch.children.append(&mut right_branch.children);
+            }
+            _ => unreachable!("siblings in a B+tree are always at the same depth"),
+        }
+    }
+}
+
+/// Index of the child of a branch that the item belongs under.
+#[inline]
+fn child_index<T: Ord>(keys: &[T], item: &T) -> usize {
+    match keys.binary_search(item) {
+        Ok(idx) => idx + 1,
+        Err(idx) => idx,
+    }
+}
+
+/// In order iterator over the items of a BTree.
+pub struct Iter<'a, T: 'a> {
+    /// The branches on the path to the current leaf and the index of the next child to visit.
+    stack: Vec<(&'a Branch<T>, usize)>,
+    items: slice::Iter<'a, T>,
+}
+
+impl<'a, T: Item> Iter<'a, T> {
+    fn descend(&mut self, mut node: &'a Node<T>) {
+        loop {
+            match *node {
+                Node::Leaf(ref items) => {
+                    self.items = items.iter();
+                    return;
+                }
+                Node::Branch(ref branch) => {
+                    self.stack.push((branch, 1));
+                    node = &branch.children[0];
+                }
+            }
+        }
+    }
+}
+
+impl<'a, T: Item> Iterator for Iter<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        loop {
+            if let Some(item) = self.items.next() {
+                return Some(item);
+            }
+
+            // The current leaf is exhausted, move on to the next one
+            loop {
+                match self.stack.pop() {
+                    None => return None,
+                    Some((branch, next)) => {
+                        if next < branch.children.len() {
+                            self.stack.push((branch, next + 1));
+                            self.descend(&branch.children[next]);
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use btree::{BTree, Node, BRANCH_CAPACITY, LEAF_CAPACITY};
+    use supported_term::SupportedTerm;
+    use supported_term::SupportedTerm::Integer;
+    use AddResult::{Added, Duplicate};
+    use RemoveResult::{NotFound, Removed};
+
+    /// Deterministic shuffle of `0..n` so the tests exercise out of order inserts
+    fn scrambled(n: i64) -> Vec<i64> {
+        (0..n).map(|i| (i * 7919) % n).collect()
+    }
+
+    /// Walks the tree checking the counts, key bounds and node capacities, returns the number of
+    /// items under the node.
+    fn check_node(
+        node: &Node<SupportedTerm>,
+        lower: Option<&SupportedTerm>,
+        upper: Option<&SupportedTerm>,
+    ) -> usize {
+        match *node {
+            Node::Leaf(ref items) => {
+                assert!(items.len() <= LEAF_CAPACITY);
+                for window in items.windows(2) {
+                    assert!(window[0] < window[1]);
+                }
+                for item in items.iter() {
+                    if let Some(lower) = lower {
+                        assert!(item >= lower);
+                    }
+                    if let Some(upper) = upper {
+                        assert!(item < upper);
+                    }
+                }
+                items.len()
+            }
+            Node::Branch(ref branch) => {
+                assert!(branch.children.len() <= BRANCH_CAPACITY);
+                assert_eq!(branch.keys.len() + 1, branch.children.len());
+                assert_eq!(branch.counts.len(), branch.children.len());
+
+                let mut total = 0;
+                for (idx, child) in branch.children.iter().enumerate() {
+                    let child_lower = if idx == 0 {
+                        lower
+                    } else {
+                        Some(&branch.keys[idx - 1])
+                    };
+                    let child_upper = if idx == branch.keys.len() {
+                        upper
+                    } else {
+                        Some(&branch.keys[idx])
+                    };
+
+                    let count = check_node(child, child_lower, child_upper);
+                    assert_eq!(count, branch.counts[idx]);
+                    total += count;
+                }
+                total
+            }
+        }
+    }
+
+    fn check(tree: &BTree<SupportedTerm>) {
+        assert_eq!(check_node(&tree.root, None, None), tree.len());
+    }
+
+    #[test]
+    fn test_add_reports_rank() {
+        let mut tree = BTree::new();
+
+        for i in scrambled(5_000) {
+            match tree.add(Integer(i)) {
+                Added(idx) => assert_eq!(tree.find_index(&Integer(i)), Some(idx)),
+                Duplicate(idx) => panic!("Unexpected Duplicate({})", idx),
+                _ => panic!("Unexpected result"),
+            }
+        }
+
+        check(&tree);
+        assert_eq!(tree.len(), 5_000);
+        assert_eq!(tree.add(Integer(1234)), Duplicate(1234));
+
+        let items: Vec<SupportedTerm> = tree.iter().cloned().collect();
+        assert_eq!(items, (0..5_000).map(Integer).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_rank() {
+        let mut tree = BTree::new();
+
+        for i in scrambled(3_000) {
+            tree.add(Integer(i * 2));
+        }
+
+        for idx in 0..3_000 {
+            assert_eq!(tree.rank(&Integer(idx * 2)), idx as usize);
+            assert_eq!(tree.rank(&Integer(idx * 2 + 1)), idx as usize + 1);
+        }
+        assert_eq!(tree.rank(&Integer(-1)), 0);
+    }
+
+    #[test]
+    fn test_at_and_iter_from() {
+        let mut tree = BTree::new();
+
+        for i in scrambled(3_000) {
+            tree.add(Integer(i * 2));
+        }
+
+        for idx in 0..3_000 {
+            assert_eq!(tree.at(idx), Some(&Integer(idx as i64 * 2)));
+        }
+        assert_eq!(tree.at(3_000), None);
+
+        let tail: Vec<SupportedTerm> = tree.iter_from(2_990).cloned().collect();
+        assert_eq!(
+            tail,
+            (2_990..3_000).map(|i| Integer(i * 2)).collect::<Vec<_>>()
+        );
+        assert_eq!(tree.iter_from(5_000).next(), None);
+    }
+
+    #[test]
+    fn test_remove_reports_rank_and_rebalances() {
+        let mut tree = BTree::new();
+
+        for i in 0..5_000 {
+            tree.add(Integer(i));
+        }
+
+        for i in scrambled(5_000) {
+            let expected = tree.find_index(&Integer(i)).unwrap();
+            assert_eq!(tree.remove(&Integer(i)), Removed(expected));
+            assert_eq!(tree.remove(&Integer(i)), NotFound);
+
+            if i % 250 == 0 {
+                check(&tree);
+            }
+        }
+
+        check(&tree);
+        assert_eq!(tree.len(), 0);
+        assert_eq!(tree.first(), None);
+
+        match tree.root {
+            Node::Leaf(ref items) => assert!(items.is_empty()),
+            Node::Branch(_) => panic!("Empty tree should collapse to a leaf"),
+        }
+    }
+
+    #[test]
+    fn test_from_sorted_builds_a_valid_tree() {
+        for n in [0, 1, 47, 48, 49, 1_000, 10_000].iter() {
+            let mut tree = BTree::from_sorted((0..*n).map(Integer).collect());
+
+            check(&tree);
+            assert_eq!(tree.len(), *n as usize);
+            assert_eq!(
+                tree.find_index(&Integer(n / 2)),
+                if *n > 0 { Some(*n as usize / 2) } else { None }
+            );
+
+            tree.add(Integer(-1));
+            tree.add(Integer(*n));
+            check(&tree);
+            assert_eq!(tree.first(), Some(&Integer(-1)));
+            assert_eq!(tree.last(), Some(&Integer(*n)));
+
+            let drained = tree.drain();
+            assert_eq!(drained.len(), *n as usize + 2);
+            assert_eq!(tree.len(), 0);
+        }
+    }
+
+    #[test]
+    fn test_repair_rebuilds_the_tree() {
+        let mut tree = BTree::from_sorted((0..200).map(Integer).collect());
+        tree.size = 10;
+
+        if let Node::Branch(ref mut branch) = tree.root {
+            if let Node::Leaf(ref mut items) = branch.children[1] {
+                items.swap(0, 1);
+                items.insert(0, Integer(0));
+            }
+        }
+
+        let report = tree.repair();
+        assert_eq!(report.unsorted_buckets, 1);
+        assert_eq!(report.overlapping_buckets, 1);
+        assert_eq!(report.duplicates, 1);
+        assert_eq!((report.recorded_size, report.size), (10, 200));
+
+        check(&tree);
+        assert_eq!(tree.find_index(&Integer(100)), Some(100));
+    }
+}
@@ -0,0 +1,491 @@
+use interpolation;
+use probes;
+use std::cmp::Ordering;
+use std::iter::{Enumerate, Peekable};
+use std::mem;
+use std::ptr;
+use std::slice;
+use AddResult;
+use AddResult::{Added, Duplicate};
+use Item;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bucket<T> {
+    pub data: Vec<T>,
+
+    /// Positions in `data` of items that have been removed but are still in place, in ascending
+    /// order.  Only sets that remove items lazily have any, see `Configuration::tombstone_ratio`.
+    /// Tombstones keep their place in the order, so they still bound the range of the bucket.
+    pub dead: Vec<u32>,
+}
+
+impl<T: Item> Bucket<T> {
+    /// Number of live items, tombstones are not counted.
+    pub fn len(&self) -> usize {
+        self.data.len() - self.dead.len()
+    }
+
+    /// Number of live items before `position` of `data`.
+    #[inline]
+    fn live_before(&self, position: usize) -> usize {
+        position
+            - self
+                .dead
+                .partition_point(|&dead| (dead as usize) < position)
+    }
+
+    /// Position in `data` of the live item at `index`.
+    #[inline]
+    fn position(&self, index: usize) -> usize {
+        let mut position = index;
+
+        for &dead in &self.dead {
+            if dead as usize > position {
+                break;
+            }
+
+            position += 1;
+        }
+
+        position
+    }
+
+    /// Position of the item in `data`, like `slice::binary_search` the error holds the position the
+    /// item would be inserted at.  With `interpolate` integers are located by interpolation.
+    #[inline]
+    fn locate(&self, item: &T, interpolate: bool) -> Result<usize, usize> {
+        match item.integer_key() {
+            Some(key) if interpolate => interpolation::search(&self.data, item, key),
+            _ => self
+                .data
+                .binary_search_by(|probe| probes::compare(probe, item)),
+        }
+    }
+
+    /// True if the bucket holds `item` as a live item, without counting the live items before it
+    /// like `search` does.
+    pub fn contains(&self, item: &T, interpolate: bool) -> bool {
+        match self.locate(item, interpolate) {
+            Ok(position) => self.dead.binary_search(&(position as u32)).is_err(),
+            Err(_) => false,
+        }
+    }
+
+    /// Searches the live items, like `slice::binary_search` the error holds the index the item
+    /// would be inserted at.
+    pub fn search(&self, item: &T, interpolate: bool) -> Result<usize, usize> {
+        match self.locate(item, interpolate) {
+            Ok(position) if self.dead.binary_search(&(position as u32)).is_err() => {
+                Ok(self.live_before(position))
+            }
+            Ok(position) | Err(position) => Err(self.live_before(position)),
+        }
+    }
+
+    /// Returns the live item at `index`.
+    pub fn get(&self, index: usize) -> Option<&T> {
+        if self.dead.is_empty() {
+            self.data.get(index)
+        } else if index < self.len() {
+            Some(&self.data[self.position(index)])
+        } else {
+            None
+        }
+    }
+
+    pub fn first(&self) -> Option<&T> {
+        self.get(0)
+    }
+
+    pub fn last(&self) -> Option<&T> {
+        self.len().checked_sub(1).and_then(|index| self.get(index))
+    }
+
+    /// Iterates over the live items.
+    pub fn iter(&self) -> Live<'_, T> {
+        Live {
+            items: self.data.iter().enumerate(),
+            dead: self.dead.iter().peekable(),
+        }
+    }
+
+    /// The live items as runs of consecutive items in `data`.
+    pub fn runs(&self) -> Vec<&[T]> {
+        let mut runs = Vec::with_capacity(self.dead.len() + 1);
+        let mut start = 0;
+
+        for &dead in &self.dead {
+            if start < dead as usize {
+                runs.push(&self.data[start..dead as usize]);
+            }
+
+            start = dead as usize + 1;
+        }
+
+        if start < self.data.len() {
+            runs.push(&self.data[start..]);
+        }
+
+        runs
+    }
+
+    pub fn add(&mut self, item: T, interpolate: bool) -> AddResult {
+        match self.locate(&item, interpolate) {
+            Ok(position) => match self.dead.binary_search(&(position as u32)) {
+                // Removed earlier, the item takes the place of its tombstone
+                Ok(dead) => {
+                    self.dead.remove(dead);
+                    self.data[position] = item;
+                    Added(self.live_before(position))
+                }
+                Err(_) => Duplicate(self.live_before(position)),
+            },
+            Err(position) => {
+                probes::shifted(self.data.len() - position);
+                self.data.insert(position, item);
+
+                for dead in self.dead.iter_mut() {
+                    if *dead as usize >= position {
+                        *dead += 1;
+                    }
+                }
+
+                Added(self.live_before(position))
+            }
+        }
+    }
+
+    /// Leaves a tombstone in place of the live item at `index` instead of removing it.
+    pub fn kill(&mut self, index: usize) {
+        let position = self.position(index) as u32;
+        let at = self.dead.partition_point(|&dead| dead < position);
+
+        self.dead.insert(at, position);
+    }
+
+    /// Removes the items under the tombstones, returning how many there were.
+    pub fn compact(&mut self) -> usize {
+        if self.dead.is_empty() {
+            return 0;
+        }
+
+        let dead = mem::take(&mut self.dead);
+        let mut dead_iter = dead.iter().peekable();
+        let mut position = 0;
+
+        self.data.retain(|_| {
+            let keep = dead_iter.peek() != Some(&&position);
+
+            if !keep {
+                dead_iter.next();
+            }
+
+            position += 1;
+            keep
+        });
+
+        dead.len()
+    }
+
+    /// Splits off the upper half of the items, the tombstones are compacted first.
+    pub fn split(&mut self) -> Bucket<T> {
+        self.compact();
+
+        let curr_len = self.data.len();
+        let at = curr_len / 2;
+
+        let other_len = self.data.len() - at;
+        let mut other = Vec::with_capacity(curr_len);
+        probes::shifted(other_len);
+
+        // Unsafely `set_len` and copy items to `other`.
+        unsafe {
+            self.data.set_len(at);
+            other.set_len(other_len);
+
+            ptr::copy_nonoverlapping(
+                self.data.as_ptr().offset(at as isize),
+                other.as_mut_ptr(),
+                other.len(),
+            );
+        }
+
+        Bucket {
+            data: other,
+            dead: Vec::new(),
+        }
+    }
+    pub fn item_compare(&self, item: &T) -> Ordering {
+        let first_item = match self.data.first() {
+            Some(f) => f,
+            None => return Ordering::Equal,
+        };
+
+        let last_item = match self.data.last() {
+            Some(l) => l,
+            None => return Ordering::Equal,
+        };
+
+        if probes::compare(item, first_item) == Ordering::Less {
+            Ordering::Greater
+        } else if probes::compare(last_item, item) == Ordering::Less {
+            Ordering::Less
+        } else {
+            Ordering::Equal
+        }
+    }
+}
+
+/// Iterator over the live items of a bucket, see `Bucket::iter`.
+pub struct Live<'a, T: 'a> {
+    items: Enumerate<slice::Iter<'a, T>>,
+    dead: Peekable<slice::Iter<'a, u32>>,
+}
+
+impl<'a, T> Iterator for Live<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        for (position, item) in self.items.by_ref() {
+            if self.dead.peek() == Some(&&(position as u32)) {
+                self.dead.next();
+                continue;
+            }
+
+            return Some(item);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bucket::Bucket;
+    use std::cmp::Ordering;
+    use supported_term::SupportedTerm;
+    use AddResult;
+
+    #[test]
+    fn test_item_compare_empty_bucket() {
+        let bucket = Bucket {
+            data: Vec::new(),
+            dead: Vec::new(),
+        };
+
+        let item = SupportedTerm::Integer(5);
+
+        assert_eq!(bucket.item_compare(&item), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_item_compare_when_less_than_first_item() {
+        let mut bucket = Bucket {
+            data: Vec::new(),
+            dead: Vec::new(),
+        };
+        let first_item = SupportedTerm::Integer(5);
+        assert_eq!(bucket.add(first_item, false), AddResult::Added(0));
+
+        let item = SupportedTerm::Integer(3);
+
+        assert_eq!(bucket.item_compare(&item), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_item_compare_when_equal_to_first_item() {
+        let mut bucket = Bucket {
+            data: Vec::new(),
+            dead: Vec::new(),
+        };
+        let first_item = SupportedTerm::Integer(5);
+        let item = first_item.clone();
+
+        assert_eq!(bucket.add(first_item, false), AddResult::Added(0));
+        assert_eq!(bucket.item_compare(&item), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_item_compare_when_greater_than_last_item() {
+        let mut bucket = Bucket {
+            data: Vec::new(),
+            dead: Vec::new(),
+        };
+
+        assert_eq!(
+            bucket.add(SupportedTerm::Integer(1), false),
+            AddResult::Added(0)
+        );
+        assert_eq!(
+            bucket.add(SupportedTerm::Integer(2), false),
+            AddResult::Added(1)
+        );
+        assert_eq!(
+            bucket.add(SupportedTerm::Integer(3), false),
+            AddResult::Added(2)
+        );
+
+        let item = SupportedTerm::Integer(5);
+
+        assert_eq!(bucket.item_compare(&item), Ordering::Less);
+    }
+
+    #[test]
+    fn test_item_compare_when_equal_to_last_item() {
+        let mut bucket = Bucket {
+            data: Vec::new(),
+            dead: Vec::new(),
+        };
+
+        assert_eq!(
+            bucket.add(SupportedTerm::Integer(1), false),
+            AddResult::Added(0)
+        );
+        assert_eq!(
+            bucket.add(SupportedTerm::Integer(2), false),
+            AddResult::Added(1)
+        );
+        assert_eq!(
+            bucket.add(SupportedTerm::Integer(3), false),
+            AddResult::Added(2)
+        );
+
+        let item = SupportedTerm::Integer(3);
+
+        assert_eq!(bucket.item_compare(&item), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_item_between_first_and_last_duplicate() {
+        let mut bucket = Bucket {
+            data: Vec::new(),
+            dead: Vec::new(),
+        };
+
+        assert_eq!(
+            bucket.add(SupportedTerm::Integer(1), false),
+            AddResult::Added(0)
+        );
+        assert_eq!(
+            bucket.add(SupportedTerm::Integer(2), false),
+            AddResult::Added(1)
+        );
+        assert_eq!(
+            bucket.add(SupportedTerm::Integer(3), false),
+            AddResult::Added(2)
+        );
+
+        let item = SupportedTerm::Integer(1);
+
+        assert_eq!(bucket.item_compare(&item), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_item_between_first_and_last_unique() {
+        let mut bucket = Bucket {
+            data: Vec::new(),
+            dead: Vec::new(),
+        };
+
+        assert_eq!(
+            bucket.add(SupportedTerm::Integer(2), false),
+            AddResult::Added(0)
+        );
+        assert_eq!(
+            bucket.add(SupportedTerm::Integer(4), false),
+            AddResult::Added(1)
+        );
+        assert_eq!(
+            bucket.add(SupportedTerm::Integer(6), false),
+            AddResult::Added(2)
+        );
+
+        let item = SupportedTerm::Integer(3);
+
+        assert_eq!(bucket.item_compare(&item), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_split_bucket_with_no_items() {
+        let mut bucket: Bucket<SupportedTerm> = Bucket {
+            data: vec![],
+            dead: Vec::new(),
+        };
+
+        assert_eq!(bucket.data.len(), 0);
+        assert_eq!(bucket.data.capacity(), 0);
+
+        let other = bucket.split();
+
+        assert_eq!(bucket.data.len(), 0);
+        assert_eq!(bucket.data.capacity(), 0);
+
+        assert_eq!(other.data.len(), 0);
+        assert_eq!(other.data.capacity(), 0);
+    }
+
+    #[test]
+    fn test_split_bucket_with_odd_number_of_items() {
+        let mut bucket = Bucket {
+            data: vec![
+                SupportedTerm::Integer(0),
+                SupportedTerm::Integer(1),
+                SupportedTerm::Integer(2),
+                SupportedTerm::Integer(3),
+                SupportedTerm::Integer(4),
+                SupportedTerm::Integer(5),
+                SupportedTerm::Integer(6),
+                SupportedTerm::Integer(7),
+                SupportedTerm::Integer(8),
+            ],
+            dead: Vec::new(),
+        };
+
+        // There were 9 items placed in the bucket, it should have length & capacity of 9
+        assert_eq!(bucket.data.len(), 9);
+        assert_eq!(bucket.data.capacity(), 9);
+
+        let other = bucket.split();
+
+        // Initial bucket should retain the same capacity but with half the length.
+        assert_eq!(bucket.data.len(), 4);
+        assert_eq!(bucket.data.capacity(), 9);
+
+        // Other bucket should have the same capacity as the initial bucket and half the length.
+        assert_eq!(other.data.len(), 5);
+        assert_eq!(other.data.capacity(), 9);
+    }
+
+    #[test]
+    fn test_split_bucket_with_even_number_of_items() {
+        let mut bucket = Bucket {
+            data: vec![
+                SupportedTerm::Integer(0),
+                SupportedTerm::Integer(1),
+                SupportedTerm::Integer(2),
+                SupportedTerm::Integer(3),
+                SupportedTerm::Integer(4),
+                SupportedTerm::Integer(5),
+                SupportedTerm::Integer(6),
+                SupportedTerm::Integer(7),
+                SupportedTerm::Integer(8),
+                SupportedTerm::Integer(9),
+            ],
+            dead: Vec::new(),
+        };
+
+        // There were 10 items placed in the bucket, it should have length & capacity of 10
+        assert_eq!(bucket.data.len(), 10);
+        assert_eq!(bucket.data.capacity(), 10);
+
+        let other = bucket.split();
+
+        // Initial bucket should retain the same capacity but with half the length.
+        assert_eq!(bucket.data.len(), 5);
+        assert_eq!(bucket.data.capacity(), 10);
+
+        // Other bucket should have the same capacity as the initial bucket and half the length.
+        assert_eq!(other.data.len(), 5);
+        assert_eq!(other.data.capacity(), 10);
+    }
+}
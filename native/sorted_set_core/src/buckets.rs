@@ -0,0 +1,1104 @@
+use bucket::Bucket;
+use interpolation;
+use metrics::BucketStats;
+use probes;
+use std::cmp::{max, min, Ordering};
+use std::mem;
+use AddResult;
+use FindResult;
+use Item;
+use RemoveResult;
+use RepairReport;
+
+/// The original SortedSet storage, a flat Vec of sorted buckets.  Lookups binary search the
+/// buckets and then the bucket, inserts only shift the items of a single bucket until the bucket
+/// has to be split.
+#[derive(Debug)]
+pub struct Buckets<T> {
+    max_bucket_size: usize,
+    buckets: Vec<Bucket<T>>,
+    size: usize,
+
+    /// Capacity every bucket is allocated with, see `Configuration::initial_bucket_capacity`.
+    bucket_capacity: usize,
+
+    /// Fraction of a bucket that may be tombstones before it is compacted, 0 if items are removed
+    /// right away.  See `Configuration::tombstone_ratio`.
+    tombstone_ratio: f32,
+
+    /// Whether integers are located by interpolation, see `Configuration::interpolation_search`.
+    interpolate: bool,
+}
+
+impl<T: Item> Buckets<T> {
+    /// Storage without any buckets, buckets have to be pushed before items can be added.
+    pub fn empty(max_bucket_size: usize, capacity: usize) -> Buckets<T> {
+        Buckets {
+            max_bucket_size,
+            buckets: Vec::with_capacity(capacity),
+            size: 0,
+            bucket_capacity: 0,
+            tombstone_ratio: 0.0,
+            interpolate: false,
+        }
+    }
+
+    pub fn new(max_bucket_size: usize, capacity: usize) -> Buckets<T> {
+        let mut result = Buckets::empty(max_bucket_size, capacity);
+        result.buckets.push(Bucket {
+            data: Vec::new(),
+            dead: Vec::new(),
+        });
+        result
+    }
+
+    /// Allocates every bucket, present and future, with room for `capacity` items up front so
+    /// that filling a bucket does not go through the usual series of reallocations.  The
+    /// capacity is limited to the max bucket size.
+    pub fn with_bucket_capacity(mut self, capacity: usize) -> Buckets<T> {
+        self.bucket_capacity = min(capacity, self.max_bucket_size);
+
+        for bucket in self.buckets.iter_mut() {
+            let additional = self.bucket_capacity.saturating_sub(bucket.data.len());
+            bucket.data.reserve(additional);
+        }
+
+        self
+    }
+
+    /// Removes items lazily, leaving tombstones behind until a bucket is more than `ratio`
+    /// tombstones.  A ratio of 0 removes items right away.
+    pub fn with_tombstones(mut self, ratio: f32) -> Buckets<T> {
+        self.tombstone_ratio = ratio;
+        self
+    }
+
+    /// Locates integers by interpolating between the integers around them instead of binary
+    /// searching, both to find their bucket and within the bucket.
+    pub fn with_interpolation(mut self, interpolate: bool) -> Buckets<T> {
+        self.interpolate = interpolate;
+        self
+    }
+
+    #[inline]
+    fn keeps_tombstones(&self) -> bool {
+        self.tombstone_ratio > 0.0
+    }
+
+    #[inline]
+    fn new_bucket(&self) -> Bucket<T> {
+        Bucket {
+            data: Vec::with_capacity(self.bucket_capacity),
+            dead: Vec::new(),
+        }
+    }
+
+    /// Packs items that are already sorted and free of duplicates into buckets the same way the
+    /// Elixir `from_proper_enumerable` packs them.
+    pub fn from_sorted(max_bucket_size: usize, items: Vec<T>) -> Buckets<T> {
+        if items.is_empty() {
+            return Buckets::new(max_bucket_size, 0);
+        }
+
+        let chunk_size = max(max_bucket_size - 1, 1);
+        let mut result = Buckets::empty(max_bucket_size, items.len() / chunk_size + 1);
+        let mut items = items.into_iter().peekable();
+
+        while items.peek().is_some() {
+            result.push_bucket(items.by_ref().take(chunk_size).collect());
+        }
+
+        result
+    }
+
+    pub fn len(&self) -> usize {
+        self.size
+    }
+
+    pub fn push_bucket(&mut self, mut items: Vec<T>) {
+        let additional = self.bucket_capacity.saturating_sub(items.len());
+        items.reserve(additional);
+
+        self.size += items.len();
+        self.buckets.push(Bucket {
+            data: items,
+            dead: Vec::new(),
+        });
+    }
+
+    /// Returns the index of the bucket the item belongs in.  Storage without any buckets, as
+    /// created by `empty`, reports bucket 0 even though it does not exist yet.
+    #[inline]
+    pub fn find_bucket_index(&self, item: &T) -> usize {
+        probes::operation();
+
+        if let (true, Some(key)) = (self.interpolate, item.integer_key()) {
+            let located = interpolation::partition(self.buckets.len(), key, |idx| {
+                probes::buckets_probed(1);
+                self.buckets[idx].data.last().and_then(Item::integer_key)
+            });
+
+            if let Some(idx) = located {
+                return min(idx, self.buckets.len().saturating_sub(1));
+            }
+        }
+
+        match self.buckets.binary_search_by(|bucket| {
+            probes::buckets_probed(1);
+            bucket.item_compare(item)
+        }) {
+            Ok(idx) => idx,
+            Err(idx) => min(idx, self.buckets.len().saturating_sub(1)),
+        }
+    }
+
+    /// True if the set holds `item`.  Unlike `find_index` the effective index is not computed,
+    /// the search stops once the bucket has been searched.
+    pub fn contains(&self, item: &T) -> bool {
+        if self.buckets.is_empty() {
+            return false;
+        }
+
+        self.buckets[self.find_bucket_index(item)].contains(item, self.interpolate)
+    }
+
+    /// The greatest item less than or equal to `item`.  Only the bucket of the item is searched,
+    /// a preceding bucket is only read when the item would come first in its bucket.
+    pub fn floor(&self, item: &T) -> Option<&T> {
+        if self.buckets.is_empty() {
+            return None;
+        }
+
+        let bucket_idx = self.find_bucket_index(item);
+        let bucket = &self.buckets[bucket_idx];
+
+        match bucket.search(item, self.interpolate) {
+            Ok(idx) => bucket.get(idx),
+            Err(0) => self.buckets[..bucket_idx]
+                .iter()
+                .rev()
+                .find_map(Bucket::last),
+            Err(idx) => bucket.get(idx - 1),
+        }
+    }
+
+    /// The least item greater than or equal to `item`.  Only the bucket of the item is searched,
+    /// a following bucket is only read when the item would come last in its bucket.
+    pub fn ceiling(&self, item: &T) -> Option<&T> {
+        if self.buckets.is_empty() {
+            return None;
+        }
+
+        let bucket_idx = self.find_bucket_index(item);
+        let bucket = &self.buckets[bucket_idx];
+
+        match bucket.search(item, self.interpolate) {
+            Ok(idx) => bucket.get(idx),
+            Err(idx) if idx < bucket.len() => bucket.get(idx),
+            Err(_) => self.buckets[bucket_idx + 1..]
+                .iter()
+                .find_map(Bucket::first),
+        }
+    }
+
+    /// Whether the set holds each of `items`, which have to be in ascending order.  The buckets
+    /// are searched from the bucket of the previous item onward, so the walk only moves forward.
+    pub fn contains_sorted(&self, items: &[&T]) -> Vec<bool> {
+        let mut bucket_idx = 0;
+
+        items
+            .iter()
+            .map(|item| {
+                bucket_idx += self.buckets[bucket_idx..].partition_point(|bucket| {
+                    probes::buckets_probed(1);
+                    bucket
+                        .data
+                        .last()
+                        .is_some_and(|last| probes::compare(last, item) == Ordering::Less)
+                });
+
+                match self.buckets.get(bucket_idx) {
+                    Some(bucket) => bucket.contains(item, self.interpolate),
+                    None => false,
+                }
+            })
+            .collect()
+    }
+
+    pub fn find_index(&self, item: &T) -> FindResult {
+        if self.buckets.is_empty() {
+            return FindResult::NotFound;
+        }
+
+        let bucket_idx = self.find_bucket_index(item);
+
+        match self.buckets[bucket_idx].search(item, self.interpolate) {
+            Ok(idx) => {
+                return FindResult::Found {
+                    bucket_idx,
+                    inner_idx: idx,
+                    idx: self.effective_index(bucket_idx, idx),
+                }
+            }
+            Err(_) => return FindResult::NotFound,
+        }
+    }
+
+    /// Returns the number of items less than `item`, which is the index of the item if it is
+    /// present and the index it would be inserted at if it is not.
+    pub fn rank(&self, item: &T) -> usize {
+        match self.search(item) {
+            Ok(idx) | Err(idx) => idx,
+        }
+    }
+
+    /// Searches the set for `item` like `slice::binary_search`, the error holds the index the item
+    /// would be inserted at.
+    pub fn search(&self, item: &T) -> Result<usize, usize> {
+        if self.buckets.is_empty() {
+            return Err(0);
+        }
+
+        let bucket_idx = self.find_bucket_index(item);
+
+        match self.buckets[bucket_idx].search(item, self.interpolate) {
+            Ok(idx) => Ok(self.effective_index(bucket_idx, idx)),
+            Err(idx) => Err(self.effective_index(bucket_idx, idx)),
+        }
+    }
+
+    #[inline]
+    fn effective_index(&self, bucket: usize, index: usize) -> usize {
+        let mut result = index;
+        probes::buckets_probed(bucket);
+
+        for bucket_index in 0..bucket {
+            result += self.buckets[bucket_index].len();
+        }
+
+        result
+    }
+
+    pub fn add(&mut self, item: T) -> AddResult {
+        // Storage created by `empty` gets its first bucket on the first add
+        if self.buckets.is_empty() {
+            let bucket = self.new_bucket();
+            self.buckets.push(bucket);
+        }
+
+        let bucket_idx = self.find_bucket_index(&item);
+        let tombstones = self.buckets[bucket_idx].dead.len();
+
+        match self.buckets[bucket_idx].add(item, self.interpolate) {
+            AddResult::Added(idx) if self.buckets[bucket_idx].dead.len() < tombstones => {
+                // The item took the place of its own tombstone, nothing has moved
+                self.size += 1;
+
+                AddResult::Added(self.effective_index(bucket_idx, idx))
+            }
+            AddResult::Added(idx) => {
+                let effective_idx = self.effective_index(bucket_idx, idx);
+                self.inserted(bucket_idx);
+
+                AddResult::Added(effective_idx)
+            }
+            AddResult::Duplicate(idx) => {
+                AddResult::Duplicate(self.effective_index(bucket_idx, idx))
+            }
+            AddResult::SetFull => AddResult::SetFull,
+        }
+    }
+
+    /// Adds an item using a caller supplied guess of the index the item belongs at.  The hint is
+    /// verified against its neighbors and if it turns out to be wrong this falls back to a
+    /// regular `add`, as does any set that keeps tombstones.
+    pub fn add_with_hint(&mut self, item: T, hint: usize) -> AddResult {
+        if self.size == 0 || hint > self.size || self.keeps_tombstones() {
+            return self.add(item);
+        }
+
+        // Locate the hinted position, an index equal to the size points just past the last item
+        let mut bucket_idx = 0;
+        let mut inner_idx = hint;
+        while bucket_idx < self.buckets.len() - 1 && inner_idx >= self.buckets[bucket_idx].len() {
+            inner_idx -= self.buckets[bucket_idx].len();
+            bucket_idx += 1;
+        }
+
+        let previous = if inner_idx > 0 {
+            self.buckets[bucket_idx].data.get(inner_idx - 1)
+        } else if bucket_idx > 0 {
+            self.buckets[bucket_idx - 1].data.last()
+        } else {
+            None
+        };
+
+        if let Some(previous) = previous {
+            if *previous >= item {
+                return self.add(item);
+            }
+        }
+
+        if let Some(next) = self.buckets[bucket_idx].data.get(inner_idx) {
+            match next.cmp(&item) {
+                Ordering::Equal => return AddResult::Duplicate(hint),
+                Ordering::Less => return self.add(item),
+                Ordering::Greater => {}
+            }
+        }
+
+        probes::shifted(self.buckets[bucket_idx].data.len() - inner_idx);
+        self.buckets[bucket_idx].data.insert(inner_idx, item);
+        self.inserted(bucket_idx);
+
+        AddResult::Added(hint)
+    }
+
+    /// Bookkeeping after an item has been inserted into the bucket at `bucket_idx`.
+    #[inline]
+    fn inserted(&mut self, bucket_idx: usize) {
+        if self.buckets[bucket_idx].data.len() >= self.max_bucket_size {
+            // Splitting compacts the bucket, which may leave it small enough to keep
+            self.buckets[bucket_idx].compact();
+        }
+
+        if self.buckets[bucket_idx].len() >= self.max_bucket_size {
+            let new_bucket = self.buckets[bucket_idx].split();
+            self.buckets.insert(bucket_idx + 1, new_bucket);
+        }
+
+        self.size += 1;
+    }
+
+    pub fn remove(&mut self, item: &T) -> RemoveResult {
+        match self.find_index(item) {
+            FindResult::Found {
+                bucket_idx,
+                inner_idx,
+                idx,
+            } => {
+                if self.size == 0 {
+                    // The size is out of sync with the buckets, recount it instead of taking down
+                    // the scheduler.  Only positions are logged, the contents of the set can be
+                    // arbitrarily large.
+                    self.size = self.buckets.iter().map(|bucket| bucket.len()).sum();
+
+                    eprintln!(
+                        "sorted_set_nif: found an item at bucket {} index {} of a set with size 0, \
+                         recounted the size of {} buckets as {}",
+                        bucket_idx,
+                        inner_idx,
+                        self.buckets.len(),
+                        self.size
+                    );
+
+                    return RemoveResult::InternalInconsistency;
+                }
+
+                if self.keeps_tombstones() {
+                    self.bury(bucket_idx, inner_idx);
+                } else {
+                    probes::shifted(self.buckets[bucket_idx].data.len() - inner_idx - 1);
+                    self.buckets[bucket_idx].data.remove(inner_idx);
+
+                    if self.buckets.len() > 1 && self.buckets[bucket_idx].data.is_empty() {
+                        self.buckets.remove(bucket_idx);
+                    }
+                }
+
+                self.size -= 1;
+
+                return RemoveResult::Removed(idx);
+            }
+            FindResult::NotFound => RemoveResult::NotFound,
+        }
+    }
+
+    /// Leaves a tombstone in place of the item at `inner_idx` of the bucket, the bucket is
+    /// compacted once more than the tombstone ratio of it is tombstones.
+    fn bury(&mut self, bucket_idx: usize, inner_idx: usize) {
+        let ratio = self.tombstone_ratio;
+        let only_bucket = self.buckets.len() == 1;
+        let bucket = &mut self.buckets[bucket_idx];
+        bucket.kill(inner_idx);
+
+        if bucket.len() == 0 && !only_bucket {
+            self.buckets.remove(bucket_idx);
+        } else if bucket.dead.len() as f32 > ratio * bucket.data.len() as f32 {
+            bucket.compact();
+        }
+    }
+
+    /// Removes the items at the given indexes, which have to be ascending, free of duplicates and
+    /// in bounds, returning the removed items in the same order.  The items of a bucket are
+    /// removed back to front so that each removal only shifts the items after it that stay.
+    pub fn remove_at_many(&mut self, indexes: &[usize]) -> Vec<T> {
+        let tombstones = self.keeps_tombstones();
+        let mut removed = Vec::with_capacity(indexes.len());
+        let mut indexes = indexes.iter().peekable();
+        let mut offset = 0;
+
+        for bucket_idx in 0..self.buckets.len() {
+            let end = offset + self.buckets[bucket_idx].len();
+            let mut inner = Vec::new();
+
+            while let Some(&&idx) = indexes.peek() {
+                if idx >= end {
+                    break;
+                }
+
+                inner.push(idx - offset);
+                indexes.next();
+            }
+
+            offset = end;
+
+            if inner.is_empty() {
+                continue;
+            }
+
+            let bucket = &mut self.buckets[bucket_idx];
+            let start = removed.len();
+
+            for &inner_idx in inner.iter().rev() {
+                if tombstones {
+                    removed.extend(bucket.get(inner_idx).cloned());
+                    bucket.kill(inner_idx);
+                } else {
+                    probes::shifted(bucket.data.len() - inner_idx - 1);
+                    removed.push(bucket.data.remove(inner_idx));
+                }
+            }
+
+            removed[start..].reverse();
+
+            if bucket.dead.len() as f32 > self.tombstone_ratio * bucket.data.len() as f32 {
+                bucket.compact();
+            }
+        }
+
+        self.size -= removed.len();
+
+        if self.buckets.len() > 1 {
+            self.buckets.retain(|bucket| bucket.len() > 0);
+
+            if self.buckets.is_empty() {
+                let bucket = self.new_bucket();
+                self.buckets.push(bucket);
+            }
+        }
+
+        removed
+    }
+
+    /// Removes the items under every tombstone, returning how many there were.
+    pub fn compact(&mut self) -> usize {
+        self.buckets.iter_mut().map(|bucket| bucket.compact()).sum()
+    }
+
+    /// Number of removed items that are still in place, see `with_tombstones`.
+    pub fn tombstones(&self) -> usize {
+        self.buckets.iter().map(|bucket| bucket.dead.len()).sum()
+    }
+
+    /// Restores the invariants of the buckets after they were broken, see `SortedSet::repair`.
+    /// Every bucket is sorted and deduplicated on its own, if the buckets then still overlap all
+    /// items are sorted and repacked.
+    pub fn repair(&mut self) -> RepairReport {
+        let mut report = RepairReport {
+            recorded_size: self.size,
+            ..RepairReport::default()
+        };
+
+        self.compact();
+
+        for bucket in self.buckets.iter_mut() {
+            if bucket.data.windows(2).any(|pair| pair[0] > pair[1]) {
+                bucket.data.sort();
+                report.unsorted_buckets += 1;
+            }
+
+            let before = bucket.data.len();
+            bucket.data.dedup_by(|a, b| a.cmp(&b) == Ordering::Equal);
+            report.duplicates += before - bucket.data.len();
+        }
+
+        let before = self.buckets.len();
+        self.buckets.retain(|bucket| !bucket.data.is_empty());
+        report.empty_buckets = before - self.buckets.len();
+
+        // A set without items still keeps a single empty bucket to add into
+        if self.buckets.is_empty() && before > 0 {
+            let bucket = self.new_bucket();
+            self.buckets.push(bucket);
+            report.empty_buckets -= 1;
+        }
+
+        report.overlapping_buckets = self
+            .buckets
+            .windows(2)
+            .filter(|pair| match (pair[0].data.last(), pair[1].data.first()) {
+                (Some(last), Some(first)) => last >= first,
+                _ => false,
+            })
+            .count();
+
+        if report.overlapping_buckets > 0 {
+            let mut items: Vec<T> = self
+                .buckets
+                .drain(..)
+                .flat_map(|bucket| bucket.data)
+                .collect();
+
+            items.sort();
+            let before = items.len();
+            items.dedup_by(|a, b| a.cmp(&b) == Ordering::Equal);
+            report.duplicates += before - items.len();
+
+            *self = Buckets::from_sorted(self.max_bucket_size, items)
+                .with_bucket_capacity(self.bucket_capacity)
+                .with_tombstones(self.tombstone_ratio)
+                .with_interpolation(self.interpolate);
+        }
+
+        self.size = self.buckets.iter().map(|bucket| bucket.len()).sum();
+        report.size = self.size;
+
+        report
+    }
+
+    pub fn at(&self, mut index: usize) -> Option<&T> {
+        for bucket in self.buckets.iter() {
+            if index < bucket.len() {
+                // The bucket contains the item to return, return it
+                return bucket.get(index);
+            }
+
+            // Reduce the remaining index by the bucket size and continue
+            index -= bucket.len();
+        }
+
+        // Out of buckets, index is out of bounds
+        None
+    }
+
+    /// Returns the items at each of `indexes`, in the order the indexes were given.  The indexes
+    /// are resolved in ascending order so the bucket sizes are only summed once.
+    pub fn at_many(&self, indexes: &[usize]) -> Vec<Option<&T>> {
+        let mut order: Vec<usize> = (0..indexes.len()).collect();
+        order.sort_by_key(|&position| indexes[position]);
+
+        let mut results = vec![None; indexes.len()];
+        let mut bucket_idx = 0;
+        let mut bucket_start = 0;
+
+        for position in order {
+            let index = indexes[position];
+
+            // Seek forward to the bucket containing the index
+            while bucket_idx < self.buckets.len()
+                && bucket_start + self.buckets[bucket_idx].len() <= index
+            {
+                bucket_start += self.buckets[bucket_idx].len();
+                bucket_idx += 1;
+            }
+
+            results[position] = self
+                .buckets
+                .get(bucket_idx)
+                .and_then(|bucket| bucket.get(index - bucket_start));
+        }
+
+        results
+    }
+
+    pub fn slice(&self, mut index: usize, mut amount: usize) -> Vec<T> {
+        if self.keeps_tombstones() {
+            return self.iter_from(index).take(amount).cloned().collect();
+        }
+
+        let mut result: Vec<T> = Vec::with_capacity(min(amount, self.size));
+        let num_buckets = self.buckets.len();
+        let mut bucket_idx = 0;
+        let mut seeking = true;
+
+        if num_buckets == 0 {
+            return result;
+        }
+
+        loop {
+            if seeking {
+                // Scan to the requested index
+                if index < self.buckets[bucket_idx].len() {
+                    // No longer seeking, this bucket contains the first item in the slice
+                    seeking = false
+                } else {
+                    // Reduce the remaining index by the bucket size and continue
+                    index -= self.buckets[bucket_idx].len();
+                    bucket_idx += 1;
+
+                    if bucket_idx >= num_buckets {
+                        // Out of buckets, index is out of bounds, return the empty vector
+                        return result;
+                    }
+                }
+            } else {
+                // Start filling in the result until amount is satisfied or we are out of items
+                let items_in_bucket = self.buckets[bucket_idx].len() - index;
+
+                if items_in_bucket >= amount {
+                    // Bucket<T> has more than we need, take from index to index + amount
+                    for idx in index..index + amount {
+                        result.push(self.buckets[bucket_idx].data[idx].clone());
+                    }
+
+                    // Return the result
+                    return result;
+                }
+
+                // Bucket<T> can not fully satisfy the request, take from index to len - 1
+                for idx in index..self.buckets[bucket_idx].len() {
+                    result.push(self.buckets[bucket_idx].data[idx].clone());
+                }
+
+                // Reduce the amount remaining to be satisied by the number of items in the bucket
+                amount = amount - items_in_bucket;
+
+                // Set index to 0, we only care to preserve the index from seeking for the bucket
+                // that contains the first element.
+                index = 0;
+                bucket_idx += 1;
+
+                if bucket_idx >= num_buckets {
+                    // Out of buckets, return whatever we have so far
+                    return result;
+                }
+            }
+        }
+    }
+
+    /// Retrieves several slices at once.  The windows are visited in order of their start index so
+    /// the buckets are only traversed once regardless of how many windows are requested, the
+    /// slices are returned in the order the windows were given.
+    pub fn multi_slice(&self, windows: &[(usize, usize)]) -> Vec<Vec<T>> {
+        if self.keeps_tombstones() {
+            return windows
+                .iter()
+                .map(|&(start, amount)| self.slice(start, amount))
+                .collect();
+        }
+
+        let mut order: Vec<usize> = (0..windows.len()).collect();
+        order.sort_by_key(|&window_idx| windows[window_idx].0);
+
+        let mut results = vec![Vec::new(); windows.len()];
+        let mut bucket_idx = 0;
+        let mut bucket_start = 0;
+
+        for window_idx in order {
+            let (start, amount) = windows[window_idx];
+
+            // Seek forward to the bucket containing the start of the window
+            while bucket_idx < self.buckets.len()
+                && bucket_start + self.buckets[bucket_idx].len() <= start
+            {
+                bucket_start += self.buckets[bucket_idx].len();
+                bucket_idx += 1;
+            }
+
+            let mut result = Vec::with_capacity(min(amount, self.size.saturating_sub(start)));
+            let mut offset = start - bucket_start;
+
+            for bucket in self.buckets[bucket_idx..].iter() {
+                if result.len() >= amount {
+                    break;
+                }
+
+                let end = min(bucket.len(), offset.saturating_add(amount - result.len()));
+                result.extend_from_slice(&bucket.data[offset..end]);
+                offset = 0;
+            }
+
+            results[window_idx] = result;
+        }
+
+        results
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.buckets.iter().flat_map(|bucket| bucket.iter())
+    }
+
+    /// Iterates over the items in order starting from the item at `index`.
+    pub fn iter_from(&self, index: usize) -> impl Iterator<Item = &T> {
+        let mut bucket_idx = 0;
+        let mut offset = index;
+
+        while bucket_idx < self.buckets.len() && offset >= self.buckets[bucket_idx].len() {
+            offset -= self.buckets[bucket_idx].len();
+            bucket_idx += 1;
+        }
+
+        self.buckets[bucket_idx..]
+            .iter()
+            .flat_map(|bucket| bucket.iter())
+            .skip(offset)
+    }
+
+    /// Iterates over the items in descending order, walking the buckets back-to-front.
+    pub fn iter_rev(&self) -> impl Iterator<Item = &T> {
+        self.iter_rev_from(0)
+    }
+
+    /// Iterates over the items in descending order starting from the item `index` places from the
+    /// end, whole buckets are skipped over without visiting their items.
+    pub fn iter_rev_from(&self, index: usize) -> impl Iterator<Item = &T> {
+        let mut remaining = self.buckets.len();
+        let mut offset = index;
+
+        while remaining > 0 && offset >= self.buckets[remaining - 1].len() {
+            offset -= self.buckets[remaining - 1].len();
+            remaining -= 1;
+        }
+
+        self.buckets[..remaining]
+            .iter()
+            .rev()
+            .flat_map(|bucket| {
+                bucket
+                    .runs()
+                    .into_iter()
+                    .rev()
+                    .flat_map(|run| run.iter().rev())
+            })
+            .skip(offset)
+    }
+
+    /// Storage without any buckets that packs, removes and locates items the same way as this one.
+    pub fn empty_like(&self) -> Buckets<T> {
+        Buckets::empty(self.max_bucket_size, 0)
+            .with_bucket_capacity(self.bucket_capacity)
+            .with_tombstones(self.tombstone_ratio)
+            .with_interpolation(self.interpolate)
+    }
+
+    /// Packs items that are already sorted and free of duplicates into storage that packs,
+    /// removes and locates items the same way as this one.
+    pub fn sorted_like(&self, items: Vec<T>) -> Buckets<T> {
+        Buckets::from_sorted(self.max_bucket_size, items)
+            .with_bucket_capacity(self.bucket_capacity)
+            .with_tombstones(self.tombstone_ratio)
+            .with_interpolation(self.interpolate)
+    }
+
+    /// Appends items that are already sorted, free of duplicates and greater than every item here.
+    pub fn extend_sorted(&mut self, items: Vec<T>) {
+        let mut other = self.sorted_like(items);
+        self.append(&mut other);
+    }
+
+    /// Moves every item out of the buckets, leaving a single empty bucket behind.
+    pub fn drain(&mut self) -> Vec<T> {
+        self.compact();
+
+        let placeholder = self.new_bucket();
+        let buckets = mem::replace(&mut self.buckets, vec![placeholder]);
+
+        let mut result = Vec::with_capacity(self.size);
+        for bucket in buckets.into_iter() {
+            result.extend(bucket.data.into_iter());
+        }
+
+        self.size = 0;
+
+        result
+    }
+
+    pub fn memory(&self) -> usize {
+        let items: usize = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.data.capacity())
+            .sum();
+        let tombstones: usize = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.dead.capacity())
+            .sum();
+
+        self.buckets.capacity() * mem::size_of::<Bucket<T>>()
+            + items * mem::size_of::<T>()
+            + tombstones * mem::size_of::<u32>()
+    }
+
+    /// Returns the items of every bucket, a bucket holding tombstones is returned as the runs of
+    /// items between them.
+    pub fn layout(&self) -> Vec<&[T]> {
+        if self.keeps_tombstones() {
+            return self
+                .buckets
+                .iter()
+                .flat_map(|bucket| bucket.runs())
+                .collect();
+        }
+
+        self.buckets.iter().map(|bucket| &bucket.data[..]).collect()
+    }
+
+    pub fn bucket_stats(&self) -> BucketStats {
+        let mut stats = BucketStats::new();
+        for bucket in &self.buckets {
+            stats.observe(bucket.len());
+        }
+        stats
+    }
+
+    #[inline]
+    pub fn first(&self) -> Option<&T> {
+        self.buckets.first().and_then(|bucket| bucket.first())
+    }
+
+    #[inline]
+    pub fn last(&self) -> Option<&T> {
+        self.buckets.last().and_then(|bucket| bucket.last())
+    }
+
+    /// Moves every bucket of `other` onto the end of these buckets, leaving `other` empty.  The
+    /// caller is responsible for making sure every item in `other` is greater than every item
+    /// here.
+    pub fn append(&mut self, other: &mut Buckets<T>) {
+        if other.size == 0 {
+            return;
+        }
+
+        let placeholder = other.new_bucket();
+        let buckets = mem::replace(&mut other.buckets, vec![placeholder]);
+
+        if self.size == 0 {
+            // Drop the placeholder bucket of an empty set instead of leaving it in front
+            self.buckets = buckets;
+        } else {
+            self.buckets.extend(buckets);
+        }
+
+        self.size += other.size;
+        other.size = 0;
+    }
+
+    /// Splits the buckets around `pivot`, the items greater than or equal to the pivot are
+    /// returned and the items less than the pivot are kept.  Only the bucket that straddles the
+    /// pivot has to be divided, every other bucket is moved as-is.
+    pub fn split_off(&mut self, pivot: &T) -> Buckets<T> {
+        let mut upper = Buckets::new(self.max_bucket_size, 0)
+            .with_bucket_capacity(self.bucket_capacity)
+            .with_tombstones(self.tombstone_ratio)
+            .with_interpolation(self.interpolate);
+
+        self.compact();
+
+        if self.size == 0 {
+            return upper;
+        }
+
+        let bucket_idx = self.find_bucket_index(pivot);
+        let mut upper_buckets = self.buckets.split_off(bucket_idx);
+
+        let at = match upper_buckets[0].data.binary_search(pivot) {
+            Ok(idx) => idx,
+            Err(idx) => idx,
+        };
+
+        let mut straddling = upper_buckets[0].data.split_off(at);
+        straddling.reserve(self.bucket_capacity.saturating_sub(straddling.len()));
+        if at > 0 {
+            self.buckets.push(Bucket {
+                data: mem::replace(&mut upper_buckets[0].data, straddling),
+                dead: Vec::new(),
+            });
+        } else {
+            upper_buckets[0].data = straddling;
+        }
+
+        if upper_buckets[0].data.is_empty() {
+            upper_buckets.remove(0);
+        }
+
+        let lower_size = self.buckets.iter().map(|bucket| bucket.len()).sum();
+
+        if !upper_buckets.is_empty() {
+            upper.buckets = upper_buckets;
+            upper.size = self.size - lower_size;
+        }
+
+        if self.buckets.is_empty() {
+            let bucket = self.new_bucket();
+            self.buckets.push(bucket);
+        }
+
+        self.size = lower_size;
+
+        upper
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use buckets::Buckets;
+    use supported_term::SupportedTerm;
+    use supported_term::SupportedTerm::Integer;
+    use RemoveResult;
+
+    #[test]
+    fn test_find_bucket_in_empty_set() {
+        let buckets = Buckets::new(5, 0);
+
+        assert_eq!(buckets.find_bucket_index(&Integer(10)), 0);
+    }
+
+    #[test]
+    fn test_rank_counts_the_smaller_items() {
+        let mut buckets = Buckets::new(3, 0);
+        for i in 0..10 {
+            buckets.add(Integer(i * 2));
+        }
+
+        assert_eq!(buckets.rank(&Integer(-1)), 0);
+        assert_eq!(buckets.rank(&Integer(0)), 0);
+        assert_eq!(buckets.rank(&Integer(7)), 4);
+        assert_eq!(buckets.rank(&Integer(8)), 4);
+        assert_eq!(buckets.rank(&Integer(100)), 10);
+        assert_eq!(Buckets::empty(3, 0).rank(&Integer(1)), 0);
+    }
+
+    #[test]
+    fn test_find_bucket_without_buckets() {
+        let buckets = Buckets::empty(5, 0);
+
+        assert_eq!(buckets.find_bucket_index(&Integer(10)), 0);
+    }
+
+    /// In the following bucket tests, we intentionally build a multibucket set
+    /// to test the behavior of finding the correct bucket.
+    ///
+    /// Internally these sets end up looking like this:
+    ///
+    /// [
+    ///     0: Bucket { [2, 4] },
+    ///     1: Bucket { [6, 8] },
+    ///     2: Bucket { [10, 12] },
+    ///     3: Bucket { [14, 16, 18] },
+    /// ]
+    fn populated_buckets() -> Buckets<SupportedTerm> {
+        let mut buckets = Buckets::new(5, 0);
+
+        for i in 1..10 {
+            buckets.add(Integer(i * 2));
+        }
+
+        buckets
+    }
+
+    #[test]
+    fn test_find_bucket_when_less_than_first_item_in_set() {
+        assert_eq!(populated_buckets().find_bucket_index(&Integer(0)), 0);
+    }
+
+    #[test]
+    fn test_find_bucket_when_equal_to_first_item_in_set() {
+        assert_eq!(populated_buckets().find_bucket_index(&Integer(2)), 0);
+    }
+
+    #[test]
+    fn test_find_bucket_when_in_first_bucket_unique() {
+        assert_eq!(populated_buckets().find_bucket_index(&Integer(3)), 0);
+    }
+
+    #[test]
+    fn test_find_bucket_when_in_first_bucket_duplicate() {
+        assert_eq!(populated_buckets().find_bucket_index(&Integer(4)), 0);
+    }
+
+    #[test]
+    fn test_find_bucket_when_between_buckets_selects_the_right_hand_bucket() {
+        assert_eq!(populated_buckets().find_bucket_index(&Integer(5)), 1);
+    }
+
+    #[test]
+    fn test_find_bucket_when_in_interior_bucket_unique() {
+        assert_eq!(populated_buckets().find_bucket_index(&Integer(7)), 1);
+    }
+
+    #[test]
+    fn test_find_bucket_when_in_interior_bucket_duplicate() {
+        assert_eq!(populated_buckets().find_bucket_index(&Integer(8)), 1);
+    }
+
+    #[test]
+    fn test_find_bucket_when_in_last_bucket_unique() {
+        assert_eq!(populated_buckets().find_bucket_index(&Integer(15)), 3);
+    }
+
+    #[test]
+    fn test_find_bucket_when_in_last_bucket_duplicate() {
+        assert_eq!(populated_buckets().find_bucket_index(&Integer(16)), 3);
+    }
+
+    #[test]
+    fn test_find_bucket_when_equal_to_last_item_in_set() {
+        assert_eq!(populated_buckets().find_bucket_index(&Integer(20)), 3);
+    }
+
+    #[test]
+    fn test_find_bucket_when_greater_than_last_item_in_set() {
+        assert_eq!(populated_buckets().find_bucket_index(&Integer(21)), 3);
+    }
+
+    #[test]
+    fn test_from_sorted_packs_buckets() {
+        let buckets = Buckets::from_sorted(3, (0..7).map(Integer).collect());
+
+        assert_eq!(buckets.len(), 7);
+        assert_eq!(buckets.buckets.len(), 4);
+        assert_eq!(
+            buckets.iter().cloned().collect::<Vec<_>>(),
+            (0..7).map(Integer).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_buckets_are_allocated_with_the_bucket_capacity() {
+        let mut buckets = Buckets::new(10, 0).with_bucket_capacity(8);
+        assert!(buckets.buckets[0].data.capacity() >= 8);
+
+        for i in 0..25 {
+            buckets.add(Integer(i));
+        }
+        let upper = buckets.split_off(&Integer(13));
+        assert!(upper
+            .buckets
+            .iter()
+            .all(|bucket| bucket.data.capacity() >= 8));
+
+        let loaded =
+            Buckets::from_sorted(10, (0..20).map(Integer).collect()).with_bucket_capacity(64);
+        assert_eq!(loaded.bucket_capacity, 10);
+        assert!(loaded
+            .buckets
+            .iter()
+            .all(|bucket| bucket.data.capacity() >= 10));
+    }
+
+    #[test]
+    fn test_remove_heals_size() {
+        let mut buckets = Buckets::from_sorted(5, (0..10).map(Integer).collect());
+        buckets.size = 0;
+
+        assert_eq!(
+            buckets.remove(&Integer(3)),
+            RemoveResult::InternalInconsistency
+        );
+        assert_eq!(buckets.len(), 10);
+        assert_eq!(buckets.remove(&Integer(3)), RemoveResult::Removed(3));
+        assert_eq!(buckets.len(), 9);
+    }
+}
@@ -0,0 +1,300 @@
+//! Alternative orderings of Bitstrings.
+//!
+//! A set created with a collation stores its Bitstrings as `SupportedTerm::Collated`, which
+//! orders strings with the collation's comparator instead of by their raw bytes.  Two comparators
+//! are available, the ICU collator of a locale and natural order, which compares runs of digits
+//! by their numeric value so that "channel-2" sorts before "channel-10".
+//!
+//! icu_collator 1.x does not expose sort keys, so instead of a precomputed key every collated
+//! string carries a handle to the collation it was prepared with.  The collator is resolved once
+//! when the set is created, comparisons never look it up.
+
+use icu_collator::{Collator, CollatorOptions};
+use icu_locid::Locale;
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::Peekable;
+use std::str::Chars;
+use std::sync::Arc;
+use supported_term::SupportedTerm;
+
+pub struct Collation {
+    comparator: Comparator,
+}
+
+enum Comparator {
+    Locale(String, Box<Collator>),
+    Natural,
+}
+
+impl Collation {
+    /// Resolves the collator for a BCP-47 locale identifier like `"de"` or `"sv-SE"`.  Locales
+    /// without collation data of their own fall back to the root collation, None is only
+    /// returned for identifiers that do not parse.
+    pub fn new(locale: &str) -> Option<Arc<Collation>> {
+        let parsed: Locale = locale.parse().ok()?;
+        let collator = Collator::try_new(&parsed.into(), CollatorOptions::new()).ok()?;
+
+        Some(Arc::new(Collation {
+            comparator: Comparator::Locale(String::from(locale), Box::new(collator)),
+        }))
+    }
+
+    /// Natural order compares runs of ASCII digits by their numeric value and everything else by
+    /// character.
+    pub fn natural() -> Arc<Collation> {
+        Arc::new(Collation {
+            comparator: Comparator::Natural,
+        })
+    }
+
+    /// The locale of a locale collation, None for natural order.
+    pub fn locale(&self) -> Option<&str> {
+        match self.comparator {
+            Comparator::Locale(ref locale, _) => Some(locale),
+            Comparator::Natural => None,
+        }
+    }
+
+    /// Orders two strings by the collation, strings the comparator considers equal are ordered
+    /// by their bytes so that distinct strings never compare as equal.
+    pub fn compare(&self, left: &str, right: &str) -> Ordering {
+        let ordering = match self.comparator {
+            Comparator::Locale(_, ref collator) => collator.compare(left, right),
+            Comparator::Natural => natural_compare(left, right),
+        };
+
+        match ordering {
+            Ordering::Equal => left.cmp(right),
+            ordering => ordering,
+        }
+    }
+
+    /// Prepares an item for a set using this collation, every Bitstring in the item, including
+    /// the ones nested in tuples and lists, is converted into a collated string.
+    pub fn prepare(collation: &Arc<Collation>, item: SupportedTerm) -> SupportedTerm {
+        match item {
+            SupportedTerm::Bitstring(value) => SupportedTerm::Collated(Box::new(CollatedString {
+                collation: collation.clone(),
+                value,
+            })),
+            SupportedTerm::Tuple(items) => SupportedTerm::Tuple(
+                items
+                    .into_iter()
+                    .map(|item| Collation::prepare(collation, item))
+                    .collect(),
+            ),
+            SupportedTerm::List(items) => SupportedTerm::List(
+                items
+                    .into_iter()
+                    .map(|item| Collation::prepare(collation, item))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
+    /// Reverses `prepare`, turning every collated string back into a plain Bitstring.
+    pub fn strip(item: SupportedTerm) -> SupportedTerm {
+        match item {
+            SupportedTerm::Collated(collated) => SupportedTerm::Bitstring(collated.value),
+            SupportedTerm::Tuple(items) => {
+                SupportedTerm::Tuple(items.into_iter().map(Collation::strip).collect())
+            }
+            SupportedTerm::List(items) => {
+                SupportedTerm::List(items.into_iter().map(Collation::strip).collect())
+            }
+            other => other,
+        }
+    }
+}
+
+/// Compares two strings in natural order.  Runs of digits are compared by value, ignoring
+/// leading zeros, so "a01" and "a1" are equal here and only told apart by `Collation::compare`.
+fn natural_compare(left: &str, right: &str) -> Ordering {
+    let mut left = left.chars().peekable();
+    let mut right = right.chars().peekable();
+
+    loop {
+        let ordering = match (left.peek(), right.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(l), Some(r)) if l.is_ascii_digit() && r.is_ascii_digit() => {
+                let l = digit_run(&mut left);
+                let r = digit_run(&mut right);
+
+                l.len().cmp(&r.len()).then_with(|| l.cmp(&r))
+            }
+            (Some(&l), Some(&r)) => {
+                left.next();
+                right.next();
+
+                l.cmp(&r)
+            }
+        };
+
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Consumes a run of digits and returns it without its leading zeros.
+fn digit_run(chars: &mut Peekable<Chars>) -> String {
+    let mut run = String::new();
+
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        chars.next();
+
+        if run.is_empty() && c == '0' {
+            continue;
+        }
+        run.push(c);
+    }
+
+    run
+}
+
+impl fmt::Debug for Collation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.comparator {
+            Comparator::Locale(ref locale, _) => write!(f, "Collation({:?})", locale),
+            Comparator::Natural => write!(f, "Collation(natural)"),
+        }
+    }
+}
+
+impl PartialEq for Collation {
+    fn eq(&self, other: &Collation) -> bool {
+        self.locale() == other.locale()
+    }
+}
+
+#[derive(Clone)]
+pub struct CollatedString {
+    collation: Arc<Collation>,
+    pub value: String,
+}
+
+impl CollatedString {
+    pub fn compare(&self, other: &CollatedString) -> Ordering {
+        if Arc::ptr_eq(&self.collation, &other.collation) || self.collation == other.collation {
+            self.collation.compare(&self.value, &other.value)
+        } else {
+            // Strings prepared for different sets only meet when one set is rebuilt from the
+            // other, fall back to the byte order every set agrees on
+            self.value.cmp(&other.value)
+        }
+    }
+}
+
+impl PartialEq for CollatedString {
+    fn eq(&self, other: &CollatedString) -> bool {
+        self.compare(other) == Ordering::Equal
+    }
+}
+
+impl Eq for CollatedString {}
+
+impl fmt::Debug for CollatedString {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.value.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use collation::Collation;
+    use std::cmp::Ordering;
+    use supported_term::SupportedTerm;
+    use supported_term::SupportedTerm::{Bitstring, Integer, Tuple};
+
+    fn collated(collation: &::std::sync::Arc<Collation>, value: &str) -> SupportedTerm {
+        Collation::prepare(collation, Bitstring(String::from(value)))
+    }
+
+    #[test]
+    fn test_locale_order() {
+        let german = Collation::new("de").unwrap();
+
+        // Byte order puts every lowercase and accented letter after "Z"
+        assert_eq!(
+            collated(&german, "Äpfel").cmp(&collated(&german, "Zebra")),
+            Ordering::Less
+        );
+        assert_eq!(
+            collated(&german, "apple").cmp(&collated(&german, "Banana")),
+            Ordering::Less
+        );
+        assert_eq!(
+            Bitstring(String::from("apple")).cmp(&Bitstring(String::from("Banana"))),
+            Ordering::Greater
+        );
+
+        let swedish = Collation::new("sv").unwrap();
+
+        // Swedish sorts "ö" after "z", German sorts it with "o"
+        assert_eq!(
+            collated(&swedish, "ö").cmp(&collated(&swedish, "z")),
+            Ordering::Greater
+        );
+        assert_eq!(
+            collated(&german, "ö").cmp(&collated(&german, "z")),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_prepare_nested() {
+        let german = Collation::new("de").unwrap();
+
+        let left = Collation::prepare(
+            &german,
+            Tuple(vec![Integer(1), Bitstring(String::from("äb"))]),
+        );
+        let right = Collation::prepare(
+            &german,
+            Tuple(vec![Integer(1), Bitstring(String::from("b"))]),
+        );
+
+        assert_eq!(left.cmp(&right), Ordering::Less);
+    }
+
+    #[test]
+    fn test_natural_order() {
+        let natural = Collation::natural();
+
+        assert_eq!(
+            collated(&natural, "channel-2").cmp(&collated(&natural, "channel-10")),
+            Ordering::Less
+        );
+        assert_eq!(
+            Bitstring(String::from("channel-2")).cmp(&Bitstring(String::from("channel-10"))),
+            Ordering::Greater
+        );
+        assert_eq!(
+            collated(&natural, "v1.10").cmp(&collated(&natural, "v1.9")),
+            Ordering::Greater
+        );
+        assert_eq!(
+            collated(&natural, "a007").cmp(&collated(&natural, "a7b")),
+            Ordering::Less
+        );
+
+        // Leading zeros only break ties
+        assert_eq!(
+            collated(&natural, "a01").cmp(&collated(&natural, "a1")),
+            Ordering::Less
+        );
+        assert_ne!(collated(&natural, "a01"), collated(&natural, "a1"));
+    }
+
+    #[test]
+    fn test_invalid_locale() {
+        assert!(Collation::new("not a locale!").is_none());
+    }
+}
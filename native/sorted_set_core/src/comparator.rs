@@ -0,0 +1,487 @@
+//! Multi-key ordering of tuples.
+//!
+//! A set created with a comparator orders its tuples by a list of keys, each naming a tuple
+//! element and a direction, so that `{score, id, name}` can be ordered by score descending and
+//! then name ascending without rewriting the tuples on the Elixir side.  Tuples stored in such a
+//! set are prepared into `SupportedTerm::Keyed`, which carries the comparator like collated
+//! strings carry their collation.  Items that are not tuples are ordered as usual.
+//!
+//! The NIF can not call back into Elixir to compare two items, so orders that need more than an
+//! element and a direction are described by the keys as well.  A key can reach into nested
+//! tuples, cast the element to an integer or a string and fold the case of strings, which covers
+//! most custom sorts without running arbitrary code.  The keys form a small program that is
+//! compiled into one closure per key when the comparator is created.
+//!
+//! The comparator also carries the `TupleOrder` of the set, which decides how tuples are compared
+//! element by element.  A set that only changes the tuple order gets a comparator without keys.
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::sync::Arc;
+use supported_term::{compare_tuples, SupportedTerm};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    Ascending,
+    Descending,
+}
+
+/// How tuples are compared against each other.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TupleOrder {
+    /// The Erlang term order, smaller tuples sort first and tuples of the same size are compared
+    /// element by element.
+    Erlang,
+
+    /// Element by element regardless of their size, like lists.  A tuple sorts right before the
+    /// longer tuples it is a prefix of, so `{1, 2}` sorts before `{1, 2, 0}` and `{1, 3}` after
+    /// it.  Tuples nested in tuples are compared the same way.
+    Lexicographic,
+}
+
+impl TupleOrder {
+    pub fn compare(self, left: &[SupportedTerm], right: &[SupportedTerm]) -> Ordering {
+        match self {
+            TupleOrder::Erlang => compare_tuples(left, right),
+            TupleOrder::Lexicographic => {
+                for (l, r) in left.iter().zip(right) {
+                    let ordering = self.compare_elements(l, r);
+
+                    if ordering != Ordering::Equal {
+                        return ordering;
+                    }
+                }
+
+                left.len().cmp(&right.len())
+            }
+        }
+    }
+
+    /// Compares two elements of a tuple, elements that are tuples themselves are compared in this
+    /// order as well.
+    fn compare_elements(self, left: &SupportedTerm, right: &SupportedTerm) -> Ordering {
+        match (left, right) {
+            (SupportedTerm::Tuple(l), SupportedTerm::Tuple(r)) => self.compare(l, r),
+            _ => left.cmp(right),
+        }
+    }
+}
+
+/// Converts the element of a key before it is compared.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Cast {
+    /// Strings holding a decimal integer compare as that integer, `"10"` sorts after `"9"`.
+    Integer,
+
+    /// Integers and atoms compare as their text, `10` sorts before `9`.
+    String,
+}
+
+/// One key of a comparator.  The elements a key extracts are compared in the tuple order of the
+/// comparator, elements a cast does not apply to are compared unchanged.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Key {
+    /// Element indexes leading to the key, every index after the first reaches into the tuple
+    /// found by the previous one.
+    pub path: Vec<usize>,
+    pub direction: Direction,
+    pub cast: Option<Cast>,
+
+    /// Compares strings and atoms by their lowercase form.
+    pub fold_case: bool,
+}
+
+impl Key {
+    /// A key comparing a top-level element as it is.
+    pub fn element(index: usize, direction: Direction) -> Key {
+        Key {
+            path: vec![index],
+            direction,
+            cast: None,
+            fold_case: false,
+        }
+    }
+
+    /// Whether the key only names a top-level element and a direction.
+    pub fn is_element(&self) -> bool {
+        self.path.len() == 1 && self.cast.is_none() && !self.fold_case
+    }
+
+    /// Compiles the key into a closure extracting its element from a tuple, `None` if the tuple
+    /// does not have the element.  Keys that do not transform their element borrow it.
+    fn compile(&self) -> Extractor {
+        if self.is_element() {
+            let index = self.path[0];
+            return Box::new(move |items| items.get(index).map(Cow::Borrowed));
+        }
+
+        let path = self.path.clone();
+        let cast = self.cast;
+        let fold_case = self.fold_case;
+
+        if cast.is_none() && !fold_case {
+            return Box::new(move |items| lookup(items, &path).map(Cow::Borrowed));
+        }
+
+        Box::new(move |items| {
+            let mut element = lookup(items, &path)?.clone();
+
+            if let Some(cast) = cast {
+                element = apply_cast(cast, element);
+            }
+
+            if fold_case {
+                element = match element {
+                    SupportedTerm::Bitstring(string) => {
+                        SupportedTerm::Bitstring(string.to_lowercase())
+                    }
+                    SupportedTerm::Atom(name) => SupportedTerm::Atom(name.to_lowercase()),
+                    other => other,
+                };
+            }
+
+            Some(Cow::Owned(element))
+        })
+    }
+}
+
+type Extractor = Box<dyn Fn(&[SupportedTerm]) -> Option<Cow<'_, SupportedTerm>> + Send + Sync>;
+
+/// Follows a path of element indexes through nested tuples.
+fn lookup<'a>(items: &'a [SupportedTerm], path: &[usize]) -> Option<&'a SupportedTerm> {
+    let (&first, rest) = path.split_first()?;
+    let mut element = items.get(first)?;
+
+    for &index in rest {
+        element = match element {
+            SupportedTerm::Tuple(nested) => nested.get(index)?,
+            _ => return None,
+        };
+    }
+
+    Some(element)
+}
+
+fn apply_cast(cast: Cast, element: SupportedTerm) -> SupportedTerm {
+    match (cast, element) {
+        (Cast::Integer, SupportedTerm::Bitstring(string)) => match string.trim().parse() {
+            Ok(value) => SupportedTerm::Integer(value),
+            Err(_) => SupportedTerm::Bitstring(string),
+        },
+        (Cast::String, SupportedTerm::Integer(value)) => {
+            SupportedTerm::Bitstring(value.to_string())
+        }
+        (Cast::String, SupportedTerm::Atom(name)) => SupportedTerm::Bitstring(name),
+        (_, other) => other,
+    }
+}
+
+pub struct Comparator {
+    keys: Vec<Key>,
+    tuple_order: TupleOrder,
+
+    /// The keys compiled by `Key::compile`, in the same order.
+    extractors: Vec<Extractor>,
+}
+
+impl Comparator {
+    /// Compiles a comparator from `(element index, direction)` keys, the first key is the most
+    /// significant, and the order tuples fall back to.  Returns None if there are no keys and the
+    /// tuples keep the Erlang order, as such a comparator would not change anything.
+    pub fn new(keys: Vec<(usize, Direction)>, tuple_order: TupleOrder) -> Option<Arc<Comparator>> {
+        let keys = keys
+            .into_iter()
+            .map(|(index, direction)| Key::element(index, direction))
+            .collect();
+
+        Comparator::compile(keys, tuple_order)
+    }
+
+    /// Like `new`, for keys that reach into nested tuples or transform their element.
+    pub fn compile(keys: Vec<Key>, tuple_order: TupleOrder) -> Option<Arc<Comparator>> {
+        if keys.is_empty() && tuple_order == TupleOrder::Erlang {
+            return None;
+        }
+
+        let extractors = keys.iter().map(Key::compile).collect();
+
+        Some(Arc::new(Comparator {
+            keys,
+            tuple_order,
+            extractors,
+        }))
+    }
+
+    pub fn keys(&self) -> &[Key] {
+        &self.keys
+    }
+
+    pub fn tuple_order(&self) -> TupleOrder {
+        self.tuple_order
+    }
+
+    /// Orders two tuples by the keys.  Tuples missing a key element sort before the tuples that
+    /// have it, tuples that are equal on every key fall back to the tuple order so that distinct
+    /// tuples never compare as equal.
+    pub fn compare(&self, left: &[SupportedTerm], right: &[SupportedTerm]) -> Ordering {
+        for (key, extract) in self.keys.iter().zip(&self.extractors) {
+            let ordering = match (extract(left), extract(right)) {
+                (Some(l), Some(r)) => match key.direction {
+                    Direction::Ascending => self.tuple_order.compare_elements(&l, &r),
+                    Direction::Descending => self.tuple_order.compare_elements(&r, &l),
+                },
+                (None, Some(_)) => Ordering::Less,
+                (Some(_), None) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            };
+
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+
+        self.tuple_order.compare(left, right)
+    }
+
+    /// Prepares an item for a set using this comparator, tuples are wrapped so they are ordered
+    /// by the keys.
+    pub fn prepare(comparator: &Arc<Comparator>, item: SupportedTerm) -> SupportedTerm {
+        match item {
+            SupportedTerm::Tuple(items) => SupportedTerm::Keyed(Box::new(KeyedTuple {
+                comparator: comparator.clone(),
+                items,
+            })),
+            other => other,
+        }
+    }
+
+    /// Reverses `prepare`, turning a keyed tuple back into a plain Tuple.
+    pub fn strip(item: SupportedTerm) -> SupportedTerm {
+        match item {
+            SupportedTerm::Keyed(keyed) => SupportedTerm::Tuple(keyed.items),
+            other => other,
+        }
+    }
+}
+
+// The compiled closures follow from the keys, comparing and printing the keys is enough
+impl PartialEq for Comparator {
+    fn eq(&self, other: &Comparator) -> bool {
+        self.keys == other.keys && self.tuple_order == other.tuple_order
+    }
+}
+
+impl fmt::Debug for Comparator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Comparator")
+            .field("keys", &self.keys)
+            .field("tuple_order", &self.tuple_order)
+            .finish()
+    }
+}
+
+#[derive(Clone)]
+pub struct KeyedTuple {
+    comparator: Arc<Comparator>,
+    pub items: Vec<SupportedTerm>,
+}
+
+impl KeyedTuple {
+    pub fn compare(&self, other: &KeyedTuple) -> Ordering {
+        if Arc::ptr_eq(&self.comparator, &other.comparator) || self.comparator == other.comparator {
+            self.comparator.compare(&self.items, &other.items)
+        } else {
+            // Tuples prepared for different sets only meet when one set is rebuilt from the
+            // other, fall back to the order every set agrees on
+            compare_tuples(&self.items, &other.items)
+        }
+    }
+}
+
+impl PartialEq for KeyedTuple {
+    fn eq(&self, other: &KeyedTuple) -> bool {
+        self.compare(other) == Ordering::Equal
+    }
+}
+
+impl Eq for KeyedTuple {}
+
+impl fmt::Debug for KeyedTuple {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        SupportedTerm::Tuple(self.items.clone()).fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use comparator::{Cast, Comparator, Direction, Key, TupleOrder};
+    use std::cmp::Ordering;
+    use supported_term::SupportedTerm;
+    use supported_term::SupportedTerm::{Bitstring, Integer, Tuple};
+
+    fn entry(score: i64, name: &str) -> SupportedTerm {
+        Tuple(vec![Integer(score), Bitstring(String::from(name))])
+    }
+
+    #[test]
+    fn test_score_descending_then_name_ascending() {
+        let comparator = Comparator::new(
+            vec![(0, Direction::Descending), (1, Direction::Ascending)],
+            TupleOrder::Erlang,
+        )
+        .unwrap();
+
+        let mut entries: Vec<SupportedTerm> = vec![
+            entry(10, "carol"),
+            entry(30, "bob"),
+            entry(10, "alice"),
+            entry(20, "dave"),
+        ]
+        .into_iter()
+        .map(|item| Comparator::prepare(&comparator, item))
+        .collect();
+        entries.sort();
+
+        let entries: Vec<SupportedTerm> = entries.into_iter().map(Comparator::strip).collect();
+        let expected = vec![
+            entry(30, "bob"),
+            entry(20, "dave"),
+            entry(10, "alice"),
+            entry(10, "carol"),
+        ];
+        assert_eq!(entries.cmp(&expected), Ordering::Equal);
+    }
+
+    #[test]
+    fn test_missing_elements_sort_first() {
+        let comparator =
+            Comparator::new(vec![(2, Direction::Descending)], TupleOrder::Erlang).unwrap();
+
+        let short = Comparator::prepare(&comparator, entry(1, "a"));
+        let long = Comparator::prepare(
+            &comparator,
+            Tuple(vec![Integer(0), Bitstring(String::from("a")), Integer(0)]),
+        );
+
+        assert_eq!(short.cmp(&long), Ordering::Less);
+    }
+
+    #[test]
+    fn test_ties_are_broken_by_the_whole_tuple() {
+        let comparator =
+            Comparator::new(vec![(0, Direction::Ascending)], TupleOrder::Erlang).unwrap();
+
+        let a = Comparator::prepare(&comparator, entry(1, "a"));
+        let b = Comparator::prepare(&comparator, entry(1, "b"));
+
+        assert_eq!(a.cmp(&b), Ordering::Less);
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn test_requires_a_key_or_a_tuple_order() {
+        assert!(Comparator::new(Vec::new(), TupleOrder::Erlang).is_none());
+        assert!(Comparator::new(Vec::new(), TupleOrder::Lexicographic).is_some());
+    }
+
+    #[test]
+    fn test_lexicographic_tuple_order() {
+        let comparator = Comparator::new(Vec::new(), TupleOrder::Lexicographic).unwrap();
+        let tuple = |items: &[i64]| {
+            Comparator::prepare(
+                &comparator,
+                Tuple(items.iter().cloned().map(Integer).collect()),
+            )
+        };
+
+        assert_eq!(tuple(&[1, 2]).cmp(&tuple(&[1, 2, 0])), Ordering::Less);
+        assert_eq!(tuple(&[1, 3]).cmp(&tuple(&[1, 2, 0])), Ordering::Greater);
+        assert_eq!(tuple(&[2]).cmp(&tuple(&[1, 9, 9])), Ordering::Greater);
+        assert_eq!(tuple(&[1, 2]).cmp(&tuple(&[1, 2])), Ordering::Equal);
+
+        let nested = |items: &[i64]| {
+            let inner = Tuple(items.iter().cloned().map(Integer).collect());
+            Comparator::prepare(&comparator, Tuple(vec![Integer(0), inner]))
+        };
+        assert_eq!(nested(&[5]).cmp(&nested(&[1, 1])), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_keys_reach_into_nested_tuples() {
+        let comparator = Comparator::compile(
+            vec![Key {
+                path: vec![1, 0],
+                ..Key::element(0, Direction::Descending)
+            }],
+            TupleOrder::Erlang,
+        )
+        .unwrap();
+        let member = |id: i64, joined: i64| {
+            Comparator::prepare(
+                &comparator,
+                Tuple(vec![Integer(id), Tuple(vec![Integer(joined)])]),
+            )
+        };
+
+        assert_eq!(member(1, 5).cmp(&member(2, 3)), Ordering::Less);
+
+        // A path through an element that is not a tuple does not find a key
+        let flat = Comparator::prepare(&comparator, Tuple(vec![Integer(0), Integer(9)]));
+        assert_eq!(flat.cmp(&member(2, 3)), Ordering::Less);
+    }
+
+    #[test]
+    fn test_casts_and_case_folding() {
+        let numeric = Comparator::compile(
+            vec![Key {
+                cast: Some(Cast::Integer),
+                ..Key::element(0, Direction::Ascending)
+            }],
+            TupleOrder::Erlang,
+        )
+        .unwrap();
+        let version =
+            |text: &str| Comparator::prepare(&numeric, Tuple(vec![Bitstring(String::from(text))]));
+
+        assert_eq!(version("9").cmp(&version(" 10")), Ordering::Less);
+        assert_eq!(version("10").cmp(&version("beta")), Ordering::Less);
+
+        let folded = Comparator::compile(
+            vec![Key {
+                fold_case: true,
+                ..Key::element(1, Direction::Ascending)
+            }],
+            TupleOrder::Erlang,
+        )
+        .unwrap();
+
+        let bob = Comparator::prepare(&folded, entry(1, "bob"));
+        let alice = Comparator::prepare(&folded, entry(2, "Alice"));
+        let upper_bob = Comparator::prepare(&folded, entry(0, "BOB"));
+
+        assert_eq!(alice.cmp(&bob), Ordering::Less);
+
+        // Names that fold to the same string are still distinct items
+        assert_eq!(upper_bob.cmp(&bob), Ordering::Less);
+    }
+
+    #[test]
+    fn test_comparators_are_equal_by_their_keys() {
+        let simple = Comparator::new(vec![(1, Direction::Ascending)], TupleOrder::Erlang);
+        let compiled = Comparator::compile(
+            vec![Key::element(1, Direction::Ascending)],
+            TupleOrder::Erlang,
+        );
+        let folded = Comparator::compile(
+            vec![Key {
+                fold_case: true,
+                ..Key::element(1, Direction::Ascending)
+            }],
+            TupleOrder::Erlang,
+        );
+
+        assert_eq!(simple, compiled);
+        assert_ne!(simple, folded);
+    }
+}
@@ -0,0 +1,444 @@
+use bitset::MAX_UNIVERSE;
+use collation::Collation;
+use comparator::Comparator;
+use std::sync::Arc;
+use Item;
+
+#[derive(Clone, Debug)]
+pub struct Configuration {
+    /// Internally we maintain buckets to reduce the cost of inserts. This configures
+    /// how large a bucket can grow to before it is forced to be split.
+    ///
+    /// Default: 200
+    pub max_bucket_size: usize,
+
+    /// Similarly to a bucket, the SortedSet maintains a Vec of buckets. This lets you
+    /// preallocate to avoid resizing the Vector if you can anticipate the size.
+    ///
+    /// Default: 0
+    pub initial_set_capacity: usize,
+
+    /// Every bucket is allocated with room for this many items up front, so filling a new or
+    /// freshly split bucket during a bulk load does not reallocate it over and over.  Capped at
+    /// `max_bucket_size`, the B+tree backend ignores it.  The builder refuses both.
+    ///
+    /// Default: 0 (buckets grow as needed)
+    pub initial_bucket_capacity: usize,
+
+    /// Hard cap on the number of elements the SortedSet will hold.  Once the cap is reached any
+    /// attempt to add a new element is rejected instead of growing the set.  This is a safety
+    /// valve against runaway producers, not an eviction policy.
+    ///
+    /// Default: None (unbounded)
+    pub max_elements: Option<usize>,
+
+    /// Storage backing the SortedSet.  The flat Vec of buckets is the fastest for small and
+    /// medium sets, the B+tree keeps inserts and removes cheap on very large sets.  The bitset
+    /// holds small non-negative integers in a fraction of the memory, a set that is given any
+    /// other item moves into buckets for good.
+    ///
+    /// Default: BackendKind::Buckets
+    pub backend: BackendKind,
+
+    /// Assign every element a stable ID on insert that, unlike its index, is not affected by
+    /// other elements being added or removed.  Costs a second index of the elements.
+    ///
+    /// Default: false
+    pub stable_ids: bool,
+
+    /// Orders Bitstrings by the collation of a locale instead of by their bytes, so that names
+    /// like "Äpfel" sort next to "Apfel" instead of after "Zebra", or in natural order, so that
+    /// "channel-2" sorts before "channel-10".  Every Bitstring is prepared for the collation when
+    /// it enters the set.
+    ///
+    /// Default: None (byte order)
+    pub collation: Option<Arc<Collation>>,
+
+    /// Stores charlists, non-empty lists of printable Latin-1 characters, as the Bitstrings they
+    /// spell out, so they order next to the binaries they duplicate instead of with the lists.
+    /// Applies to charlists nested in tuples and lists as well.  Lists of small integers that
+    /// happen to be printable are converted too.
+    ///
+    /// Default: false
+    pub charlists: bool,
+
+    /// Orders tuples by a list of element keys, like score descending and then name ascending,
+    /// instead of element by element, and carries the `TupleOrder` tuples are compared in.  Every
+    /// tuple is prepared for the comparator when it enters the set.
+    ///
+    /// Default: None (term order)
+    pub comparator: Option<Arc<Comparator>>,
+
+    /// Declares that the set is only ever used by the process that created it.  The set does not
+    /// enforce this itself, the NIF checks the calling process instead of locking such sets.
+    ///
+    /// Default: false
+    pub single_owner: bool,
+
+    /// Keeps a HyperLogLog sketch of the items, so the number of distinct items across many sets
+    /// can be estimated without merging them.  Costs 4KiB per set and a hash per added item.
+    ///
+    /// Default: false
+    pub cardinality_sketch: bool,
+
+    /// Lets writes that do not need to know where the item ended up be queued instead of taking
+    /// the lock of the set, the queue is applied in one go by the next call that locks the set.
+    /// Like `single_owner` the set does not queue writes itself, the NIF does, and the two can
+    /// not be combined as an owned set takes no lock to begin with.
+    ///
+    /// Default: false
+    pub coalesce_writes: bool,
+
+    /// Removes items by leaving a tombstone in their place instead of shifting the rest of their
+    /// bucket, a bucket is compacted once more than this fraction of it is tombstones.  Makes
+    /// removal heavy workloads cheaper at the cost of skipping over the tombstones on reads.
+    /// Only the buckets backend supports tombstones.
+    ///
+    /// Default: 0.0 (items are removed right away)
+    pub tombstone_ratio: f32,
+
+    /// Locates integers by interpolating between the integers around them instead of binary
+    /// searching, which takes fewer comparisons when the integers are spread evenly, like
+    /// snowflake IDs.  Other items and integers that are spread unevenly fall back to a binary
+    /// search.  Only the buckets backend supports interpolation.
+    ///
+    /// Default: false
+    pub interpolation_search: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum BackendKind {
+    Buckets,
+    BTree,
+
+    /// Integers `0..universe` as a bitmap, see `Item::dense_universe`.
+    Bitset {
+        universe: u32,
+    },
+}
+
+impl Default for Configuration {
+    fn default() -> Self {
+        return Self {
+            max_bucket_size: 200,
+            initial_set_capacity: 0,
+            initial_bucket_capacity: 0,
+            max_elements: None,
+            backend: BackendKind::Buckets,
+            stable_ids: false,
+            collation: None,
+            charlists: false,
+            comparator: None,
+            single_owner: false,
+            cardinality_sketch: false,
+            coalesce_writes: false,
+            tombstone_ratio: 0.0,
+            interpolation_search: false,
+        };
+    }
+}
+
+impl Configuration {
+    /// Converts an item into the form stored by sets with this configuration.  Items have to be
+    /// prepared before they are sorted or compared against the items of the set.
+    pub fn prepare<T: Item>(&self, item: T) -> T {
+        item.prepare(self)
+    }
+
+    /// Reverses `prepare`, returning the item as it was given to the set.
+    pub fn strip<T: Item>(item: T) -> T {
+        item.strip()
+    }
+
+    /// True if items have to be prepared before they enter sets with this configuration.
+    pub fn prepares_items(&self) -> bool {
+        self.charlists || self.has_custom_order()
+    }
+
+    /// True if sets with both configurations store and order items the same way.
+    pub fn prepares_like(&self, other: &Configuration) -> bool {
+        self.charlists == other.charlists
+            && self.collation == other.collation
+            && self.comparator == other.comparator
+    }
+
+    /// True if the items of sets with this configuration are not in term order.
+    pub fn has_custom_order(&self) -> bool {
+        self.collation.is_some() || self.comparator.is_some()
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConfigurationError {
+    /// Buckets have to be able to hold at least one item.
+    ZeroBucketSize,
+
+    /// The initial capacity of a bucket is larger than a bucket can ever grow.
+    BucketCapacityTooLarge,
+
+    /// The initial capacity of a bucket was given for the B+tree backend, which has no buckets.
+    BucketCapacityWithBTree,
+
+    /// The universe of the bitset backend is empty or larger than `bitset::MAX_UNIVERSE`.
+    InvalidUniverse,
+
+    /// The bitset backend orders its items by their position in the universe, it can not be
+    /// combined with a collation or a comparator.
+    BitsetWithCustomOrder,
+
+    /// The tombstone ratio is negative or not below 1.
+    InvalidTombstoneRatio,
+
+    /// Tombstones were asked for with a backend other than buckets.
+    TombstonesWithoutBuckets,
+
+    /// Interpolation search was asked for with a backend other than buckets.
+    InterpolationWithoutBuckets,
+
+    /// Writes were to be coalesced on a set with a single owner.
+    CoalescedSingleOwner,
+}
+
+/// Builds a Configuration option by option and validates the combination, see
+/// `Configuration::builder`.  Options that are not set keep their default.
+#[derive(Clone, Debug, Default)]
+pub struct ConfigurationBuilder {
+    configuration: Configuration,
+    initial_capacity: Option<usize>,
+}
+
+impl Configuration {
+    /// Starts building a Configuration from the defaults.  Unlike filling in the struct directly
+    /// the builder refuses combinations of options that would misbehave or be silently ignored.
+    pub fn builder() -> ConfigurationBuilder {
+        ConfigurationBuilder::default()
+    }
+}
+
+impl ConfigurationBuilder {
+    pub fn max_bucket_size(mut self, max_bucket_size: usize) -> ConfigurationBuilder {
+        self.configuration.max_bucket_size = max_bucket_size;
+        self
+    }
+
+    /// Number of items the set should have room for up front, converted into a number of buckets
+    /// once the bucket size is known.  Takes precedence over `initial_set_capacity`.
+    pub fn initial_capacity(mut self, items: usize) -> ConfigurationBuilder {
+        self.initial_capacity = Some(items);
+        self
+    }
+
+    pub fn initial_set_capacity(mut self, buckets: usize) -> ConfigurationBuilder {
+        self.configuration.initial_set_capacity = buckets;
+        self
+    }
+
+    pub fn initial_bucket_capacity(mut self, items: usize) -> ConfigurationBuilder {
+        self.configuration.initial_bucket_capacity = items;
+        self
+    }
+
+    pub fn max_elements(mut self, max_elements: usize) -> ConfigurationBuilder {
+        self.configuration.max_elements = Some(max_elements);
+        self
+    }
+
+    pub fn backend(mut self, backend: BackendKind) -> ConfigurationBuilder {
+        self.configuration.backend = backend;
+        self
+    }
+
+    pub fn stable_ids(mut self, stable_ids: bool) -> ConfigurationBuilder {
+        self.configuration.stable_ids = stable_ids;
+        self
+    }
+
+    pub fn collation(mut self, collation: Arc<Collation>) -> ConfigurationBuilder {
+        self.configuration.collation = Some(collation);
+        self
+    }
+
+    pub fn charlists(mut self, charlists: bool) -> ConfigurationBuilder {
+        self.configuration.charlists = charlists;
+        self
+    }
+
+    pub fn comparator(mut self, comparator: Arc<Comparator>) -> ConfigurationBuilder {
+        self.configuration.comparator = Some(comparator);
+        self
+    }
+
+    pub fn single_owner(mut self, single_owner: bool) -> ConfigurationBuilder {
+        self.configuration.single_owner = single_owner;
+        self
+    }
+
+    pub fn cardinality_sketch(mut self, cardinality_sketch: bool) -> ConfigurationBuilder {
+        self.configuration.cardinality_sketch = cardinality_sketch;
+        self
+    }
+
+    pub fn coalesce_writes(mut self, coalesce_writes: bool) -> ConfigurationBuilder {
+        self.configuration.coalesce_writes = coalesce_writes;
+        self
+    }
+
+    pub fn tombstone_ratio(mut self, ratio: f32) -> ConfigurationBuilder {
+        self.configuration.tombstone_ratio = ratio;
+        self
+    }
+
+    pub fn interpolation_search(mut self, interpolation_search: bool) -> ConfigurationBuilder {
+        self.configuration.interpolation_search = interpolation_search;
+        self
+    }
+
+    pub fn build(self) -> Result<Configuration, ConfigurationError> {
+        let mut configuration = self.configuration;
+
+        if configuration.max_bucket_size < 1 {
+            return Err(ConfigurationError::ZeroBucketSize);
+        }
+
+        if configuration.initial_bucket_capacity > configuration.max_bucket_size {
+            return Err(ConfigurationError::BucketCapacityTooLarge);
+        }
+
+        if configuration.initial_bucket_capacity > 0 && configuration.backend == BackendKind::BTree
+        {
+            return Err(ConfigurationError::BucketCapacityWithBTree);
+        }
+
+        if let BackendKind::Bitset { universe } = configuration.backend {
+            if universe == 0 || universe > MAX_UNIVERSE {
+                return Err(ConfigurationError::InvalidUniverse);
+            }
+
+            if configuration.has_custom_order() {
+                return Err(ConfigurationError::BitsetWithCustomOrder);
+            }
+        }
+
+        let ratio = configuration.tombstone_ratio;
+
+        if !(0.0..1.0).contains(&ratio) {
+            return Err(ConfigurationError::InvalidTombstoneRatio);
+        }
+
+        if ratio > 0.0 && configuration.backend != BackendKind::Buckets {
+            return Err(ConfigurationError::TombstonesWithoutBuckets);
+        }
+
+        if configuration.interpolation_search && configuration.backend != BackendKind::Buckets {
+            return Err(ConfigurationError::InterpolationWithoutBuckets);
+        }
+
+        if configuration.coalesce_writes && configuration.single_owner {
+            return Err(ConfigurationError::CoalescedSingleOwner);
+        }
+
+        if let Some(items) = self.initial_capacity {
+            configuration.initial_set_capacity = items / configuration.max_bucket_size + 1;
+        }
+
+        Ok(configuration)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use comparator::{Comparator, Direction, TupleOrder};
+    use configuration::{BackendKind, Configuration, ConfigurationError};
+
+    #[test]
+    fn test_builder_applies_options() {
+        let configuration = Configuration::builder()
+            .max_bucket_size(50)
+            .initial_capacity(1_000)
+            .initial_bucket_capacity(10)
+            .max_elements(5_000)
+            .stable_ids(true)
+            .build()
+            .unwrap();
+
+        assert_eq!(configuration.max_bucket_size, 50);
+        assert_eq!(configuration.initial_set_capacity, 21);
+        assert_eq!(configuration.initial_bucket_capacity, 10);
+        assert_eq!(configuration.max_elements, Some(5_000));
+        assert!(configuration.stable_ids);
+        assert!(!configuration.single_owner);
+    }
+
+    #[test]
+    fn test_builder_validates_combinations() {
+        let zero = Configuration::builder().max_bucket_size(0).build();
+        assert_eq!(zero.unwrap_err(), ConfigurationError::ZeroBucketSize);
+
+        let too_large = Configuration::builder()
+            .max_bucket_size(10)
+            .initial_bucket_capacity(11)
+            .build();
+        assert_eq!(
+            too_large.unwrap_err(),
+            ConfigurationError::BucketCapacityTooLarge
+        );
+
+        let btree = Configuration::builder()
+            .backend(BackendKind::BTree)
+            .initial_bucket_capacity(8)
+            .build();
+        assert_eq!(
+            btree.unwrap_err(),
+            ConfigurationError::BucketCapacityWithBTree
+        );
+
+        let universe = Configuration::builder()
+            .backend(BackendKind::Bitset { universe: 0 })
+            .build();
+        assert_eq!(universe.unwrap_err(), ConfigurationError::InvalidUniverse);
+
+        let ordered = Configuration::builder()
+            .backend(BackendKind::Bitset { universe: 64 })
+            .comparator(
+                Comparator::new(vec![(0, Direction::Descending)], TupleOrder::Erlang).unwrap(),
+            )
+            .build();
+        assert_eq!(
+            ordered.unwrap_err(),
+            ConfigurationError::BitsetWithCustomOrder
+        );
+
+        let ratio = Configuration::builder().tombstone_ratio(1.5).build();
+        assert_eq!(
+            ratio.unwrap_err(),
+            ConfigurationError::InvalidTombstoneRatio
+        );
+
+        let tombstones = Configuration::builder()
+            .backend(BackendKind::BTree)
+            .tombstone_ratio(0.25)
+            .build();
+        assert_eq!(
+            tombstones.unwrap_err(),
+            ConfigurationError::TombstonesWithoutBuckets
+        );
+
+        let interpolation = Configuration::builder()
+            .backend(BackendKind::BTree)
+            .interpolation_search(true)
+            .build();
+        assert_eq!(
+            interpolation.unwrap_err(),
+            ConfigurationError::InterpolationWithoutBuckets
+        );
+
+        let coalesced = Configuration::builder()
+            .single_owner(true)
+            .coalesce_writes(true)
+            .build();
+        assert_eq!(
+            coalesced.unwrap_err(),
+            ConfigurationError::CoalescedSingleOwner
+        );
+    }
+}
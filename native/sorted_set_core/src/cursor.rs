@@ -0,0 +1,367 @@
+//! Cursors walk a set in either direction a few items at a time.
+//!
+//! A cursor does not hold an index into the set, it remembers the last item it returned and
+//! finds its place again on every read.  Items added or removed between reads therefore never
+//! cause the cursor to skip or repeat an item, which makes cursors suitable for long lived
+//! "scroll from here" views over a set that keeps changing.
+
+use comparator::Direction;
+use merged;
+use persistent::PersistentSet;
+use sorted_set::SortedSet;
+use supported_term::SupportedTerm;
+use view::RangeView;
+use Item;
+
+#[derive(Debug)]
+enum Position<T> {
+    /// Nothing has been read yet, reading starts at the first item in the direction.
+    Start,
+
+    /// Reading starts at this item or, if it is not present, the next item in the direction.
+    From(T),
+
+    /// Reading starts at the first item past this one in the direction.
+    After(T),
+
+    /// There is nothing left to read.
+    Exhausted,
+}
+
+#[derive(Debug)]
+pub struct Cursor<T: Item = SupportedTerm> {
+    position: Position<T>,
+    direction: Direction,
+}
+
+impl<T: Item> Cursor<T> {
+    /// A cursor positioned at the first item in the direction, the smallest item for ascending
+    /// cursors and the largest for descending ones.
+    pub fn new(direction: Direction) -> Cursor<T> {
+        Cursor {
+            position: Position::Start,
+            direction,
+        }
+    }
+
+    /// A cursor positioned at `item`, the item does not have to be present in the set.
+    pub fn from(item: T, direction: Direction) -> Cursor<T> {
+        Cursor {
+            position: Position::From(item),
+            direction,
+        }
+    }
+
+    /// A cursor positioned at the item at `index`.  An index past the end of the set leaves an
+    /// ascending cursor with nothing to read and starts a descending cursor at the largest item.
+    pub fn at(set: &SortedSet<T>, index: usize, direction: Direction) -> Cursor<T> {
+        let position = match (set.at(index), direction) {
+            (Some(item), _) => Position::From(item.clone()),
+            (None, Direction::Ascending) => Position::Exhausted,
+            (None, Direction::Descending) => Position::Start,
+        };
+
+        Cursor {
+            position,
+            direction,
+        }
+    }
+
+    /// A cursor positioned at the item at `index` of the merged sets, see `Cursor::at`.
+    pub fn at_merged(sets: &[&SortedSet<T>], index: usize, direction: Direction) -> Cursor<T> {
+        let position = match (merged::at(sets, index), direction) {
+            (Some(item), _) => Position::From(item),
+            (None, Direction::Ascending) => Position::Exhausted,
+            (None, Direction::Descending) => Position::Start,
+        };
+
+        Cursor {
+            position,
+            direction,
+        }
+    }
+
+    /// Reads up to `count` items in the direction of the cursor and advances past them.  Fewer
+    /// items are returned once the cursor reaches the end of the set.
+    pub fn next(&mut self, set: &SortedSet<T>, count: usize) -> Vec<T> {
+        let items = self.read(set, count);
+        self.advance(items)
+    }
+
+    /// Reads up to `count` items of the merged sets, see `merged`.  Every set contributes at most
+    /// `count` items to the merge, so a read costs the same no matter how far along the cursor
+    /// is.
+    pub fn next_merged(&mut self, sets: &[&SortedSet<T>], count: usize) -> Vec<T> {
+        let runs = sets.iter().map(|set| self.read(set, count)).collect();
+        let items = merged::merge_runs(runs, self.direction, count);
+        self.advance(items)
+    }
+
+    /// A cursor positioned at the item at `index` of the view, see `Cursor::at`.
+    pub fn at_view(
+        set: &SortedSet<T>,
+        view: &RangeView<T>,
+        index: usize,
+        direction: Direction,
+    ) -> Cursor<T> {
+        let position = match (view.at(set, index), direction) {
+            (Some(item), _) => Position::From(item.clone()),
+            (None, Direction::Ascending) => Position::Exhausted,
+            (None, Direction::Descending) => Position::Start,
+        };
+
+        Cursor {
+            position,
+            direction,
+        }
+    }
+
+    /// Reads up to `count` items of the view, items of the set outside of the view are never
+    /// returned.
+    pub fn next_view(&mut self, set: &SortedSet<T>, view: &RangeView<T>, count: usize) -> Vec<T> {
+        let items = self.read_within(set, view.bounds(set), count);
+        self.advance(items)
+    }
+
+    /// Reads up to `count` items from the position of the cursor without advancing it.
+    fn read(&self, set: &SortedSet<T>, count: usize) -> Vec<T> {
+        self.read_within(set, (0, set.size()), count)
+    }
+
+    /// Same as `read` but only returns items whose index lies within `[low, high)`.
+    fn read_within(&self, set: &SortedSet<T>, (low, high): (usize, usize), count: usize) -> Vec<T> {
+        match self.direction {
+            Direction::Ascending => {
+                let start = self.index(set).max(low);
+
+                set.slice(start, high.saturating_sub(start).min(count))
+            }
+            Direction::Descending => {
+                let end = self.index(set).min(high);
+                let start = end.saturating_sub(count).max(low);
+
+                let mut items = set.slice(start, end.saturating_sub(start));
+                items.reverse();
+                items
+            }
+        }
+    }
+
+    /// The index reading starts at, or for descending cursors the index reading ends before.
+    fn index(&self, set: &SortedSet<T>) -> usize {
+        match (&self.position, self.direction) {
+            (Position::Start, Direction::Ascending) => 0,
+            (Position::Start, Direction::Descending) => set.size(),
+            (Position::From(item), Direction::Ascending) => set.rank(item),
+            (Position::From(item), Direction::Descending) => rank_after(set, item),
+            (Position::After(item), Direction::Ascending) => rank_after(set, item),
+            (Position::After(item), Direction::Descending) => set.rank(item),
+            (Position::Exhausted, Direction::Ascending) => set.size(),
+            (Position::Exhausted, Direction::Descending) => 0,
+        }
+    }
+
+    fn advance(&mut self, items: Vec<T>) -> Vec<T> {
+        if let Some(last) = items.last() {
+            self.position = Position::After(last.clone());
+        }
+
+        items
+    }
+}
+
+/// A cursor reading a copy of a set taken when the cursor was created.
+///
+/// A cursor over the live set returns items added past it and never returns items removed before
+/// it gets to them, so paging through a set that keeps changing sees a mix of versions of the
+/// set.  A snapshot cursor reads a single version of the set from start to end instead, without
+/// any duplicates or gaps.  The copy shares nothing with the set, writers are only held up while
+/// the copy is taken.
+#[derive(Debug)]
+pub struct SnapshotCursor<T: Item = SupportedTerm> {
+    /// The items in the order of the set, which is not necessarily the term order the
+    /// PersistentSet would keep them in.  The copy is only ever read by index.
+    snapshot: PersistentSet<T>,
+
+    /// The index of the next item for ascending cursors, the number of items left to read for
+    /// descending ones.
+    index: usize,
+    direction: Direction,
+}
+
+impl<T: Item> SnapshotCursor<T> {
+    /// Copies the set, reading starts where `cursor` would start reading the set.
+    pub fn new(set: &SortedSet<T>, cursor: &Cursor<T>) -> SnapshotCursor<T> {
+        SnapshotCursor {
+            snapshot: PersistentSet::from_sorted(set.configuration().max_bucket_size, set.to_vec()),
+            index: cursor.index(set),
+            direction: cursor.direction,
+        }
+    }
+
+    /// Reads up to `count` items in the direction of the cursor and advances past them.
+    pub fn next(&mut self, count: usize) -> Vec<T> {
+        match self.direction {
+            Direction::Ascending => {
+                let items = self.snapshot.slice(self.index, count);
+                self.index += items.len();
+                items
+            }
+            Direction::Descending => {
+                let start = self.index.saturating_sub(count);
+                let mut items = self.snapshot.slice(start, self.index - start);
+                self.index = start;
+                items.reverse();
+                items
+            }
+        }
+    }
+}
+
+/// Returns the number of items less than or equal to `item`.
+fn rank_after<T: Item>(set: &SortedSet<T>, item: &T) -> usize {
+    match set.find_index(item) {
+        Some(idx) => idx + 1,
+        None => set.rank(item),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use comparator::Direction;
+    use cursor::{Cursor, SnapshotCursor};
+    use sorted_set::SortedSet;
+    use supported_term::SupportedTerm::Integer;
+
+    fn evens(n: i64) -> SortedSet {
+        let mut set = SortedSet::default();
+        for i in 0..n {
+            set.add(Integer(i * 2));
+        }
+        set
+    }
+
+    #[test]
+    fn test_ascending_from_the_start() {
+        let set = evens(5);
+        let mut cursor = Cursor::new(Direction::Ascending);
+
+        assert_eq!(cursor.next(&set, 2), vec![Integer(0), Integer(2)]);
+        assert_eq!(cursor.next(&set, 2), vec![Integer(4), Integer(6)]);
+        assert_eq!(cursor.next(&set, 2), vec![Integer(8)]);
+        assert_eq!(cursor.next(&set, 2), vec![]);
+    }
+
+    #[test]
+    fn test_descending_from_a_missing_item() {
+        let set = evens(5);
+        let mut cursor = Cursor::from(Integer(5), Direction::Descending);
+
+        assert_eq!(cursor.next(&set, 2), vec![Integer(4), Integer(2)]);
+        assert_eq!(cursor.next(&set, 2), vec![Integer(0)]);
+        assert_eq!(cursor.next(&set, 2), vec![]);
+    }
+
+    #[test]
+    fn test_from_a_present_item_includes_it() {
+        let set = evens(5);
+
+        let mut ascending = Cursor::from(Integer(4), Direction::Ascending);
+        let mut descending = Cursor::from(Integer(4), Direction::Descending);
+
+        assert_eq!(ascending.next(&set, 1), vec![Integer(4)]);
+        assert_eq!(descending.next(&set, 1), vec![Integer(4)]);
+    }
+
+    #[test]
+    fn test_at_index() {
+        let set = evens(5);
+
+        assert_eq!(
+            Cursor::at(&set, 3, Direction::Descending).next(&set, 2),
+            vec![Integer(6), Integer(4)]
+        );
+        assert_eq!(
+            Cursor::at(&set, 10, Direction::Descending).next(&set, 1),
+            vec![Integer(8)]
+        );
+        assert_eq!(
+            Cursor::at(&set, 10, Direction::Ascending).next(&set, 1),
+            vec![]
+        );
+    }
+
+    #[test]
+    fn test_merged_in_either_direction() {
+        let a = evens(5);
+        let b: SortedSet = vec![Integer(3), Integer(4), Integer(9)]
+            .into_iter()
+            .collect();
+        let sets = vec![&a, &b];
+
+        let mut ascending = Cursor::new(Direction::Ascending);
+        assert_eq!(
+            ascending.next_merged(&sets, 3),
+            vec![Integer(0), Integer(2), Integer(3)]
+        );
+        assert_eq!(
+            ascending.next_merged(&sets, 3),
+            vec![Integer(4), Integer(6), Integer(8)]
+        );
+        assert_eq!(ascending.next_merged(&sets, 3), vec![Integer(9)]);
+
+        let mut descending = Cursor::at_merged(&sets, 4, Direction::Descending);
+        assert_eq!(
+            descending.next_merged(&sets, 3),
+            vec![Integer(6), Integer(4), Integer(3)]
+        );
+    }
+
+    #[test]
+    fn test_survives_mutations_between_reads() {
+        let mut set = evens(5);
+        let mut cursor = Cursor::new(Direction::Ascending);
+
+        assert_eq!(cursor.next(&set, 2), vec![Integer(0), Integer(2)]);
+
+        set.remove(&Integer(2));
+        set.add(Integer(-10));
+        set.add(Integer(3));
+
+        assert_eq!(cursor.next(&set, 2), vec![Integer(3), Integer(4)]);
+    }
+
+    #[test]
+    fn test_snapshot_ignores_mutations_between_reads() {
+        let mut set = evens(5);
+        let mut cursor = SnapshotCursor::new(&set, &Cursor::new(Direction::Ascending));
+
+        assert_eq!(cursor.next(2), vec![Integer(0), Integer(2)]);
+
+        set.remove(&Integer(4));
+        set.add(Integer(1));
+        set.add(Integer(5));
+
+        assert_eq!(cursor.next(2), vec![Integer(4), Integer(6)]);
+        assert_eq!(cursor.next(2), vec![Integer(8)]);
+        assert!(cursor.next(2).is_empty());
+    }
+
+    #[test]
+    fn test_snapshot_starts_where_the_cursor_would() {
+        let mut set = evens(5);
+        let mut cursor =
+            SnapshotCursor::new(&set, &Cursor::from(Integer(3), Direction::Descending));
+
+        set.drain();
+
+        assert_eq!(cursor.next(1), vec![Integer(2)]);
+        assert_eq!(cursor.next(5), vec![Integer(0)]);
+        assert!(cursor.next(5).is_empty());
+
+        let set = evens(5);
+        let mut cursor = SnapshotCursor::new(&set, &Cursor::at(&set, 3, Direction::Ascending));
+
+        assert_eq!(cursor.next(10), vec![Integer(6), Integer(8)]);
+    }
+}
@@ -0,0 +1,213 @@
+//! The same items kept in two different orders.
+//!
+//! A DualIndexSet holds two SortedSets, each with a configuration of its own, so members can be
+//! listed by name through one index and by join date through the other.  Every mutation is
+//! applied to both sets before it returns, a caller holding the DualIndexSet behind a single lock
+//! never observes the indexes disagreeing the way two separately maintained sets drift apart.
+
+use sorted_set::SortedSet;
+use supported_term::SupportedTerm;
+use AddResult;
+use Configuration;
+use Item;
+use RemoveResult;
+
+/// Selects which of the two orders a read uses.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Index {
+    Primary,
+    Secondary,
+}
+
+/// Result of adding to both indexes, the indexes are the positions of the item in each order.
+#[derive(Debug, PartialEq)]
+pub enum DualAddResult {
+    Added { primary: usize, secondary: usize },
+    Duplicate { primary: usize, secondary: usize },
+    SetFull,
+}
+
+/// Result of removing from both indexes, the indexes are the positions the item had in each
+/// order.
+#[derive(Debug, PartialEq)]
+pub enum DualRemoveResult {
+    Removed {
+        primary: usize,
+        secondary: usize,
+    },
+    NotFound,
+
+    /// Only one of the indexes held the item, it has been removed from that index so both agree
+    /// again.
+    InternalInconsistency,
+}
+
+#[derive(Debug)]
+pub struct DualIndexSet<T: Item = SupportedTerm> {
+    primary: SortedSet<T>,
+    secondary: SortedSet<T>,
+}
+
+impl<T: Item> DualIndexSet<T> {
+    /// Creates an empty DualIndexSet ordering its items by each configuration.  Both indexes hold
+    /// the same items so they share a single limit, the `max_elements` of the primary
+    /// configuration.
+    pub fn new(primary: Configuration, mut secondary: Configuration) -> DualIndexSet<T> {
+        secondary.max_elements = primary.max_elements;
+
+        DualIndexSet {
+            primary: SortedSet::empty(primary),
+            secondary: SortedSet::empty(secondary),
+        }
+    }
+
+    /// The set backing one of the orders, for reads.
+    pub fn get(&self, index: Index) -> &SortedSet<T> {
+        match index {
+            Index::Primary => &self.primary,
+            Index::Secondary => &self.secondary,
+        }
+    }
+
+    pub fn add(&mut self, item: T) -> DualAddResult {
+        match self.primary.add(item.clone()) {
+            AddResult::SetFull => DualAddResult::SetFull,
+            AddResult::Added(primary) => match self.secondary.add(item.clone()) {
+                AddResult::Added(secondary) | AddResult::Duplicate(secondary) => {
+                    DualAddResult::Added { primary, secondary }
+                }
+                AddResult::SetFull => {
+                    // Take the item back out of the primary index so both keep the same items
+                    self.primary.remove(&item);
+
+                    DualAddResult::SetFull
+                }
+            },
+            AddResult::Duplicate(primary) => match self.secondary.add(item) {
+                AddResult::Added(secondary) | AddResult::Duplicate(secondary) => {
+                    DualAddResult::Duplicate { primary, secondary }
+                }
+                AddResult::SetFull => DualAddResult::SetFull,
+            },
+        }
+    }
+
+    pub fn remove(&mut self, item: &T) -> DualRemoveResult {
+        match (self.primary.remove(item), self.secondary.remove(item)) {
+            (RemoveResult::Removed(primary), RemoveResult::Removed(secondary)) => {
+                DualRemoveResult::Removed { primary, secondary }
+            }
+            (RemoveResult::NotFound, RemoveResult::NotFound) => DualRemoveResult::NotFound,
+            _ => DualRemoveResult::InternalInconsistency,
+        }
+    }
+
+    /// Number of items, which both indexes hold.
+    pub fn size(&self) -> usize {
+        self.primary.size()
+    }
+
+    /// Approximate number of bytes used by both indexes, see `SortedSet::memory`.
+    pub fn memory(&self) -> usize {
+        self.primary.memory() + self.secondary.memory()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use comparator::{Comparator, Direction, TupleOrder};
+    use configuration::Configuration;
+    use dual_index::{DualAddResult, DualIndexSet, DualRemoveResult, Index};
+    use supported_term::SupportedTerm::{self, Bitstring, Integer, Tuple};
+
+    fn member(name: &str, joined: i64) -> SupportedTerm {
+        Tuple(vec![Bitstring(name.to_string()), Integer(joined)])
+    }
+
+    fn by(key: usize) -> Configuration {
+        Configuration::builder()
+            .comparator(
+                Comparator::new(vec![(key, Direction::Ascending)], TupleOrder::Erlang).unwrap(),
+            )
+            .build()
+            .unwrap()
+    }
+
+    /// The join dates in the given order, tuples are compared through `cmp` as `==` does not
+    /// terminate on equal tuples.
+    fn joined(set: &DualIndexSet, index: Index) -> Vec<i64> {
+        set.get(index)
+            .to_vec()
+            .into_iter()
+            .map(|item| match Configuration::strip(item) {
+                Tuple(ref items) => match items[1] {
+                    Integer(joined) => joined,
+                    _ => panic!("Unexpected member"),
+                },
+                _ => panic!("Unexpected member"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_both_orders_follow_mutations() {
+        let mut set: DualIndexSet = DualIndexSet::new(by(0), by(1));
+
+        assert_eq!(
+            set.add(member("bob", 3)),
+            DualAddResult::Added {
+                primary: 0,
+                secondary: 0
+            }
+        );
+        assert_eq!(
+            set.add(member("alice", 7)),
+            DualAddResult::Added {
+                primary: 0,
+                secondary: 1
+            }
+        );
+        assert_eq!(
+            set.add(member("carol", 1)),
+            DualAddResult::Added {
+                primary: 2,
+                secondary: 0
+            }
+        );
+        assert_eq!(
+            set.add(member("bob", 3)),
+            DualAddResult::Duplicate {
+                primary: 1,
+                secondary: 1
+            }
+        );
+
+        assert_eq!(joined(&set, Index::Primary), vec![7, 3, 1]);
+        assert_eq!(joined(&set, Index::Secondary), vec![1, 3, 7]);
+
+        assert_eq!(
+            set.remove(&member("alice", 7)),
+            DualRemoveResult::Removed {
+                primary: 0,
+                secondary: 2
+            }
+        );
+        assert_eq!(set.remove(&member("alice", 7)), DualRemoveResult::NotFound);
+        assert_eq!(set.size(), 2);
+        assert_eq!(set.get(Index::Secondary).size(), 2);
+    }
+
+    #[test]
+    fn test_full_sets_refuse_in_both_orders() {
+        let limited = Configuration {
+            max_elements: Some(1),
+            ..by(0)
+        };
+        let mut set: DualIndexSet = DualIndexSet::new(limited, by(1));
+
+        set.add(member("bob", 3));
+        assert_eq!(set.add(member("alice", 7)), DualAddResult::SetFull);
+        assert_eq!(joined(&set, Index::Primary), vec![3]);
+        assert_eq!(joined(&set, Index::Secondary), vec![3]);
+    }
+}
@@ -0,0 +1,441 @@
+//! Decoder for the subset of the Erlang External Term Format (the output of
+//! `:erlang.term_to_binary/1`) that maps onto SupportedTerm.  Decoding straight from the binary
+//! avoids materializing the terms on the Elixir heap before they are copied into the set.
+//!
+//! Compressed binaries are not supported, the payload must be produced without the `:compressed`
+//! option.
+//!
+//! The encoder produces the same format so the output can be read back with
+//! `:erlang.binary_to_term/1`.
+
+use supported_term::SupportedTerm;
+
+const VERSION: u8 = 131;
+
+const SMALL_INTEGER_EXT: u8 = 97;
+const INTEGER_EXT: u8 = 98;
+const ATOM_EXT: u8 = 100;
+const SMALL_TUPLE_EXT: u8 = 104;
+const LARGE_TUPLE_EXT: u8 = 105;
+const NIL_EXT: u8 = 106;
+const STRING_EXT: u8 = 107;
+const LIST_EXT: u8 = 108;
+const BINARY_EXT: u8 = 109;
+const SMALL_BIG_EXT: u8 = 110;
+const LARGE_BIG_EXT: u8 = 111;
+const SMALL_ATOM_EXT: u8 = 115;
+const ATOM_UTF8_EXT: u8 = 118;
+const SMALL_ATOM_UTF8_EXT: u8 = 119;
+
+/// Deepest nesting of tuples and lists that is decoded.  Decoding recurses once per level and
+/// the payload may come from another node, so a small binary of deeply nested tuples must not be
+/// able to overflow the 320 KB stack of a dirty scheduler.  Unoptimized builds take over a
+/// kilobyte of stack per level, the bound leaves room for them and for the rest of the NIF.
+const MAX_DEPTH: usize = 128;
+
+#[derive(Debug, PartialEq)]
+pub enum EtfError {
+    /// The binary is not valid External Term Format.
+    Invalid,
+
+    /// The binary is valid but contains a term that can not be stored in a SortedSet.
+    Unsupported,
+}
+
+pub fn decode(bytes: &[u8]) -> Result<SupportedTerm, EtfError> {
+    let mut decoder = Decoder {
+        bytes,
+        position: 0,
+        depth: 0,
+    };
+
+    if decoder.u8()? != VERSION {
+        return Err(EtfError::Invalid);
+    }
+
+    let term = decoder.term()?;
+
+    if decoder.position != bytes.len() {
+        return Err(EtfError::Invalid);
+    }
+
+    Ok(term)
+}
+
+/// Encodes a term, including the version byte.  Encoding the same term always produces the same
+/// bytes.
+pub fn encode(term: &SupportedTerm) -> Vec<u8> {
+    let mut out = vec![VERSION];
+    encode_term(term, &mut out);
+    out
+}
+
+fn encode_term(term: &SupportedTerm, out: &mut Vec<u8>) {
+    match *term {
+        SupportedTerm::Integer(value) => {
+            if (0..=255).contains(&value) {
+                out.push(SMALL_INTEGER_EXT);
+                out.push(value as u8);
+            } else if (i32::MIN as i64..=i32::MAX as i64).contains(&value) {
+                out.push(INTEGER_EXT);
+                out.extend_from_slice(&(value as i32).to_be_bytes());
+            } else {
+                // Bignum digits are little endian, the sign is stored separately
+                out.push(SMALL_BIG_EXT);
+                out.push(8);
+                out.push((value < 0) as u8);
+                out.extend_from_slice(&value.unsigned_abs().to_le_bytes());
+            }
+        }
+        SupportedTerm::Atom(_) | SupportedTerm::NegInfinity | SupportedTerm::Infinity => {
+            let name = term.atom_name().unwrap_or_default();
+
+            if name.len() <= 255 {
+                out.push(SMALL_ATOM_UTF8_EXT);
+                out.push(name.len() as u8);
+            } else {
+                out.push(ATOM_UTF8_EXT);
+                out.extend_from_slice(&(name.len() as u16).to_be_bytes());
+            }
+            out.extend_from_slice(name.as_bytes());
+        }
+        SupportedTerm::Tuple(ref items) => encode_tuple(items, out),
+        SupportedTerm::List(ref items) => {
+            if !items.is_empty() {
+                out.push(LIST_EXT);
+                out.extend_from_slice(&(items.len() as u32).to_be_bytes());
+                for item in items {
+                    encode_term(item, out);
+                }
+            }
+            out.push(NIL_EXT);
+        }
+        SupportedTerm::Bitstring(ref string) => encode_binary(string, out),
+        SupportedTerm::Collated(ref collated) => encode_binary(&collated.value, out),
+        SupportedTerm::Keyed(ref keyed) => encode_tuple(&keyed.items, out),
+    }
+}
+
+fn encode_tuple(items: &[SupportedTerm], out: &mut Vec<u8>) {
+    if items.len() <= 255 {
+        out.push(SMALL_TUPLE_EXT);
+        out.push(items.len() as u8);
+    } else {
+        out.push(LARGE_TUPLE_EXT);
+        out.extend_from_slice(&(items.len() as u32).to_be_bytes());
+    }
+    for item in items {
+        encode_term(item, out);
+    }
+}
+
+fn encode_binary(string: &str, out: &mut Vec<u8>) {
+    out.push(BINARY_EXT);
+    out.extend_from_slice(&(string.len() as u32).to_be_bytes());
+    out.extend_from_slice(string.as_bytes());
+}
+
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    position: usize,
+
+    /// Number of tuples and lists the current term is nested in.
+    depth: usize,
+}
+
+impl<'a> Decoder<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], EtfError> {
+        if self.bytes.len() - self.position < len {
+            return Err(EtfError::Invalid);
+        }
+
+        let result = &self.bytes[self.position..self.position + len];
+        self.position += len;
+
+        Ok(result)
+    }
+
+    fn u8(&mut self) -> Result<u8, EtfError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<usize, EtfError> {
+        let bytes = self.take(2)?;
+        Ok(((bytes[0] as usize) << 8) | bytes[1] as usize)
+    }
+
+    fn u32(&mut self) -> Result<usize, EtfError> {
+        let bytes = self.take(4)?;
+        Ok(bytes
+            .iter()
+            .fold(0usize, |acc, byte| (acc << 8) | *byte as usize))
+    }
+
+    fn term(&mut self) -> Result<SupportedTerm, EtfError> {
+        let tag = self.u8()?;
+
+        match tag {
+            SMALL_INTEGER_EXT => Ok(SupportedTerm::Integer(self.u8()? as i64)),
+            INTEGER_EXT => {
+                let bytes = self.take(4)?;
+                let value = ((bytes[0] as u32) << 24)
+                    | ((bytes[1] as u32) << 16)
+                    | ((bytes[2] as u32) << 8)
+                    | bytes[3] as u32;
+                Ok(SupportedTerm::Integer(value as i32 as i64))
+            }
+            SMALL_BIG_EXT => {
+                let len = self.u8()? as usize;
+                self.big(len)
+            }
+            LARGE_BIG_EXT => {
+                let len = self.u32()?;
+                self.big(len)
+            }
+            ATOM_EXT | SMALL_ATOM_EXT => {
+                let len = if tag == ATOM_EXT {
+                    self.u16()?
+                } else {
+                    self.u8()? as usize
+                };
+
+                // Latin-1 maps directly onto the first 256 code points
+                let name = self.take(len)?.iter().map(|byte| *byte as char).collect();
+                Ok(SupportedTerm::atom(name))
+            }
+            ATOM_UTF8_EXT | SMALL_ATOM_UTF8_EXT => {
+                let len = if tag == ATOM_UTF8_EXT {
+                    self.u16()?
+                } else {
+                    self.u8()? as usize
+                };
+
+                match String::from_utf8(self.take(len)?.to_vec()) {
+                    Ok(name) => Ok(SupportedTerm::atom(name)),
+                    Err(_) => Err(EtfError::Invalid),
+                }
+            }
+            SMALL_TUPLE_EXT => {
+                let arity = self.u8()? as usize;
+                Ok(SupportedTerm::Tuple(self.terms(arity)?))
+            }
+            LARGE_TUPLE_EXT => {
+                let arity = self.u32()?;
+                Ok(SupportedTerm::Tuple(self.terms(arity)?))
+            }
+            NIL_EXT => Ok(SupportedTerm::List(Vec::new())),
+            STRING_EXT => {
+                let len = self.u16()?;
+                let items = self
+                    .take(len)?
+                    .iter()
+                    .map(|byte| SupportedTerm::Integer(*byte as i64))
+                    .collect();
+                Ok(SupportedTerm::List(items))
+            }
+            LIST_EXT => {
+                let len = self.u32()?;
+                let items = self.terms(len)?;
+
+                // Improper lists are not supported
+                match self.u8()? {
+                    NIL_EXT => Ok(SupportedTerm::List(items)),
+                    _ => Err(EtfError::Unsupported),
+                }
+            }
+            BINARY_EXT => {
+                let len = self.u32()?;
+
+                match String::from_utf8(self.take(len)?.to_vec()) {
+                    Ok(string) => Ok(SupportedTerm::Bitstring(string)),
+                    Err(_) => Err(EtfError::Unsupported),
+                }
+            }
+            _ => Err(EtfError::Unsupported),
+        }
+    }
+
+    fn terms(&mut self, len: usize) -> Result<Vec<SupportedTerm>, EtfError> {
+        // Every term takes at least one byte, this guards the allocation against bogus lengths
+        if len > self.bytes.len() - self.position {
+            return Err(EtfError::Invalid);
+        }
+
+        if self.depth == MAX_DEPTH {
+            return Err(EtfError::Invalid);
+        }
+
+        self.depth += 1;
+
+        let mut result = Vec::with_capacity(len);
+        for _ in 0..len {
+            result.push(self.term()?);
+        }
+
+        self.depth -= 1;
+
+        Ok(result)
+    }
+
+    /// Decodes a bignum, only bignums that fit into an i64 are supported.
+    fn big(&mut self, len: usize) -> Result<SupportedTerm, EtfError> {
+        let negative = self.u8()? != 0;
+        let digits = self.take(len)?;
+
+        let mut magnitude: u64 = 0;
+        for (idx, digit) in digits.iter().enumerate() {
+            if *digit == 0 {
+                continue;
+            }
+
+            if idx >= 8 {
+                return Err(EtfError::Unsupported);
+            }
+
+            magnitude |= (*digit as u64) << (8 * idx);
+        }
+
+        if negative {
+            if magnitude > (i64::MAX as u64) + 1 {
+                return Err(EtfError::Unsupported);
+            }
+
+            Ok(SupportedTerm::Integer((magnitude as i64).wrapping_neg()))
+        } else {
+            if magnitude > i64::MAX as u64 {
+                return Err(EtfError::Unsupported);
+            }
+
+            Ok(SupportedTerm::Integer(magnitude as i64))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use etf::{decode, encode, EtfError};
+    use std::cmp::Ordering;
+    use std::thread;
+    use supported_term::SupportedTerm::{
+        Atom, Bitstring, Infinity, Integer, List, NegInfinity, Tuple,
+    };
+
+    #[test]
+    fn test_decode_integers() {
+        assert_eq!(decode(&[131, 97, 7]), Ok(Integer(7)));
+        assert_eq!(decode(&[131, 98, 255, 255, 255, 254]), Ok(Integer(-2)));
+        assert_eq!(
+            decode(&[131, 110, 8, 1, 0, 0, 0, 0, 0, 0, 0, 128]),
+            Ok(Integer(i64::MIN))
+        );
+        assert_eq!(
+            decode(&[131, 110, 8, 0, 0, 0, 0, 0, 0, 0, 0, 128]),
+            Err(EtfError::Unsupported)
+        );
+    }
+
+    #[test]
+    fn test_decode_atoms_and_binaries() {
+        assert_eq!(
+            decode(&[131, 119, 2, b'o', b'k']),
+            Ok(Atom(String::from("ok")))
+        );
+        assert_eq!(decode(&[131, 100, 0, 1, 233]), Ok(Atom(String::from("é"))));
+        assert_eq!(
+            decode(&[131, 119, 8, b'i', b'n', b'f', b'i', b'n', b'i', b't', b'y']),
+            Ok(Infinity)
+        );
+        assert_eq!(
+            decode(&[131, 109, 0, 0, 0, 2, b'h', b'i']),
+            Ok(Bitstring(String::from("hi")))
+        );
+    }
+
+    #[test]
+    fn test_decode_collections() {
+        // [1, {2, 'ab'}]
+        let bytes = [
+            131, 108, 0, 0, 0, 2, 97, 1, 104, 2, 97, 2, 107, 0, 2, b'a', b'b', 106,
+        ];
+
+        match decode(&bytes) {
+            Ok(List(items)) => {
+                assert_eq!(items.len(), 2);
+                assert_eq!(items[0], Integer(1));
+
+                match items[1] {
+                    Tuple(ref inner) => {
+                        assert_eq!(inner[0], Integer(2));
+                        match inner[1] {
+                            List(ref chars) => {
+                                assert_eq!(chars, &vec![Integer(97), Integer(98)])
+                            }
+                            ref other => panic!("unexpected {:?}", other),
+                        }
+                    }
+                    ref other => panic!("unexpected {:?}", other),
+                }
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decode_rejects_deep_nesting() {
+        let nested = |depth: usize| -> Vec<u8> {
+            let mut bytes = vec![131];
+            for _ in 0..depth {
+                bytes.extend_from_slice(&[104, 1]);
+            }
+            bytes.extend_from_slice(&[97, 1]);
+            bytes
+        };
+
+        assert!(decode(&nested(128)).is_ok());
+        assert_eq!(decode(&nested(129)), Err(EtfError::Invalid));
+
+        // Far deeper than a dirty scheduler's stack could recurse through, on a stack that size
+        let deep = thread::Builder::new()
+            .stack_size(320 * 1024)
+            .spawn(move || decode(&nested(100_000)))
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(deep, Err(EtfError::Invalid));
+    }
+
+    #[test]
+    fn test_decode_rejects_bad_input() {
+        assert_eq!(decode(&[]), Err(EtfError::Invalid));
+        assert_eq!(decode(&[130, 97, 1]), Err(EtfError::Invalid));
+        assert_eq!(decode(&[131, 108, 0, 0, 0, 9, 97]), Err(EtfError::Invalid));
+        assert_eq!(decode(&[131, 97, 1, 1]), Err(EtfError::Invalid));
+
+        // Floats and improper lists can not be stored in a SortedSet
+        assert_eq!(
+            decode(&[131, 70, 64, 9, 33, 251, 84, 68, 45, 24]),
+            Err(EtfError::Unsupported)
+        );
+        assert_eq!(
+            decode(&[131, 108, 0, 0, 0, 1, 97, 1, 97, 2]),
+            Err(EtfError::Unsupported)
+        );
+    }
+
+    #[test]
+    fn test_encode_round_trips() {
+        let term = List(vec![
+            Integer(0),
+            Integer(-1),
+            Integer(1 << 40),
+            Integer(i64::MIN),
+            Atom(String::from("ok")),
+            NegInfinity,
+            Tuple(vec![Bitstring(String::from("hi")), List(Vec::new())]),
+            Infinity,
+        ]);
+
+        let decoded = decode(&encode(&term)).unwrap();
+        assert_eq!(decoded.cmp(&term), Ordering::Equal);
+    }
+}
@@ -0,0 +1,317 @@
+//! Reductions over the items of a set that run next to the items.
+//!
+//! Counting the members with a given role or summing the scores of a leaderboard used to copy
+//! every item out of the set just to reduce it to a single number.  A fold visits the items in
+//! place and only hands back the result.  Large sets are folded in steps of a bounded number of
+//! items, every step returns a `FoldState` the next step resumes from.  Like a cursor the state
+//! remembers the last item it visited rather than an index, so items added or removed between
+//! steps never cause a step to visit an item twice.
+
+use sorted_set::SortedSet;
+use std::cmp::Ordering;
+use std::ops::Bound;
+use supported_term::SupportedTerm;
+use Configuration;
+
+/// The reductions a fold can compute.
+#[derive(Debug)]
+pub enum Fold {
+    /// Number of items matching the pattern, see `matches`.
+    Count(SupportedTerm),
+
+    /// Sum of the integer element at the index of every item, which all have to be tuples.
+    Sum(usize),
+
+    /// Smallest element at the index of every item, which all have to be tuples.
+    Min(usize),
+
+    /// Largest element at the index of every item, which all have to be tuples.
+    Max(usize),
+}
+
+#[derive(Debug)]
+pub enum Accumulator {
+    Count(u64),
+    Sum(i64),
+
+    /// The smallest or largest element so far, None until an item has been visited.
+    Extreme(Option<SupportedTerm>),
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FoldError {
+    /// An item is not a tuple or is too short to have the element.
+    MissingElement,
+
+    /// The element summed is not an integer.
+    NotInteger,
+
+    /// The sum does not fit in 64 bits.
+    Overflow,
+}
+
+/// Where a fold left off, the accumulator and the last item visited.
+#[derive(Debug)]
+pub struct FoldState {
+    pub accumulator: Accumulator,
+    pub after: Option<SupportedTerm>,
+}
+
+#[derive(Debug)]
+pub enum Step {
+    /// Every item has been visited.
+    Done(Accumulator),
+
+    /// The budget ran out, the fold resumes from the state.
+    Continue(FoldState),
+}
+
+impl Fold {
+    /// The state of a fold that has not visited any item yet.
+    pub fn start(&self) -> FoldState {
+        let accumulator = match *self {
+            Fold::Count(_) => Accumulator::Count(0),
+            Fold::Sum(_) => Accumulator::Sum(0),
+            Fold::Min(_) | Fold::Max(_) => Accumulator::Extreme(None),
+        };
+
+        FoldState {
+            accumulator,
+            after: None,
+        }
+    }
+
+    /// Visits up to `budget` items following the last item of the state.
+    pub fn step(
+        &self,
+        set: &SortedSet,
+        state: FoldState,
+        budget: usize,
+    ) -> Result<Step, FoldError> {
+        let FoldState {
+            mut accumulator,
+            after,
+        } = state;
+        let start = match after {
+            Some(ref item) => Bound::Excluded(item.clone()),
+            None => Bound::Unbounded,
+        };
+        let strip = set.configuration().prepares_items();
+
+        let mut last = None;
+
+        for (visited, item) in set.range((start, Bound::Unbounded)).enumerate() {
+            if visited == budget {
+                return Ok(Step::Continue(FoldState {
+                    accumulator,
+                    after: last.or(after),
+                }));
+            }
+
+            if strip {
+                self.visit(&Configuration::strip(item.clone()), &mut accumulator)?;
+            } else {
+                self.visit(item, &mut accumulator)?;
+            }
+
+            last = Some(item.clone());
+        }
+
+        Ok(Step::Done(accumulator))
+    }
+
+    fn visit(&self, item: &SupportedTerm, accumulator: &mut Accumulator) -> Result<(), FoldError> {
+        match (self, accumulator) {
+            (Fold::Count(pattern), Accumulator::Count(count)) => {
+                if matches(pattern, item) {
+                    *count += 1;
+                }
+            }
+            (Fold::Sum(index), Accumulator::Sum(sum)) => match *element(item, *index)? {
+                SupportedTerm::Integer(value) => {
+                    *sum = sum.checked_add(value).ok_or(FoldError::Overflow)?
+                }
+                _ => return Err(FoldError::NotInteger),
+            },
+            (Fold::Min(index), Accumulator::Extreme(extreme)) => {
+                let value = element(item, *index)?;
+
+                if extreme.as_ref().is_none_or(|current| value < current) {
+                    *extreme = Some(value.clone());
+                }
+            }
+            (Fold::Max(index), Accumulator::Extreme(extreme)) => {
+                let value = element(item, *index)?;
+
+                if extreme.as_ref().is_none_or(|current| value > current) {
+                    *extreme = Some(value.clone());
+                }
+            }
+            _ => unreachable!("Accumulator of a different fold"),
+        }
+
+        Ok(())
+    }
+}
+
+fn element(item: &SupportedTerm, index: usize) -> Result<&SupportedTerm, FoldError> {
+    match *item {
+        SupportedTerm::Tuple(ref elements) => elements.get(index).ok_or(FoldError::MissingElement),
+        _ => Err(FoldError::MissingElement),
+    }
+}
+
+/// True if the item matches the pattern.  The atom `_` matches any term, tuples and lists match
+/// element by element and every other term matches itself.
+pub fn matches(pattern: &SupportedTerm, item: &SupportedTerm) -> bool {
+    match (pattern, item) {
+        (SupportedTerm::Atom(name), _) if name == "_" => true,
+        (SupportedTerm::Tuple(patterns), SupportedTerm::Tuple(elements))
+        | (SupportedTerm::List(patterns), SupportedTerm::List(elements)) => {
+            patterns.len() == elements.len()
+                && patterns
+                    .iter()
+                    .zip(elements.iter())
+                    .all(|(pattern, element)| matches(pattern, element))
+        }
+        (SupportedTerm::Tuple(_), _) | (SupportedTerm::List(_), _) => false,
+        _ => pattern.cmp(item) == Ordering::Equal,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fold::{matches, Accumulator, Fold, FoldError, Step};
+    use sorted_set::SortedSet;
+    use supported_term::SupportedTerm::{self, Atom, Bitstring, Integer, Tuple};
+    use Configuration;
+
+    fn member(name: &str, role: &str, score: i64) -> SupportedTerm {
+        Tuple(vec![
+            Bitstring(name.to_string()),
+            Atom(role.to_string()),
+            Integer(score),
+        ])
+    }
+
+    fn members() -> SortedSet {
+        let mut set = SortedSet::new(Configuration::default());
+        set.add(member("alice", "admin", 7));
+        set.add(member("bob", "member", 3));
+        set.add(member("carol", "admin", 12));
+        set.add(member("dave", "member", -2));
+        set.add(member("erin", "member", 5));
+        set
+    }
+
+    /// Runs the fold to completion in steps of `budget` items, returning the number of steps.
+    fn run(set: &SortedSet, fold: &Fold, budget: usize) -> (Accumulator, usize) {
+        let mut state = fold.start();
+        let mut steps = 1;
+
+        loop {
+            match fold.step(set, state, budget).unwrap() {
+                Step::Done(accumulator) => return (accumulator, steps),
+                Step::Continue(next) => state = next,
+            }
+
+            steps += 1;
+        }
+    }
+
+    #[test]
+    fn test_patterns() {
+        let wildcard = Atom(String::from("_"));
+        let admin = Tuple(vec![
+            wildcard.clone(),
+            Atom(String::from("admin")),
+            wildcard.clone(),
+        ]);
+
+        assert!(matches(&wildcard, &Integer(1)));
+        assert!(matches(&admin, &member("alice", "admin", 7)));
+        assert!(!matches(&admin, &member("bob", "member", 3)));
+        assert!(!matches(&admin, &Tuple(vec![Atom(String::from("admin"))])));
+        assert!(!matches(&admin, &Integer(1)));
+        assert!(matches(&Integer(1), &Integer(1)));
+    }
+
+    #[test]
+    fn test_folds_in_steps() {
+        let set = members();
+        let admin = Tuple(vec![
+            Atom(String::from("_")),
+            Atom(String::from("admin")),
+            Atom(String::from("_")),
+        ]);
+
+        match run(&set, &Fold::Count(admin), 2) {
+            (Accumulator::Count(count), steps) => {
+                assert_eq!(count, 2);
+                assert_eq!(steps, 3);
+            }
+            other => panic!("Unexpected {:?}", other),
+        }
+
+        match run(&set, &Fold::Sum(2), 1) {
+            (Accumulator::Sum(sum), steps) => {
+                assert_eq!(sum, 25);
+                assert_eq!(steps, 5);
+            }
+            other => panic!("Unexpected {:?}", other),
+        }
+
+        match run(&set, &Fold::Min(2), 10) {
+            (Accumulator::Extreme(Some(Integer(min))), 1) => assert_eq!(min, -2),
+            other => panic!("Unexpected {:?}", other),
+        }
+
+        match run(&set, &Fold::Max(0), 3) {
+            (Accumulator::Extreme(Some(Bitstring(ref max))), 2) => assert_eq!(max, "erin"),
+            other => panic!("Unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_resumes_after_concurrent_changes() {
+        let mut set = members();
+        let fold = Fold::Sum(2);
+
+        let state = match fold.step(&set, fold.start(), 2).unwrap() {
+            Step::Continue(state) => state,
+            other => panic!("Unexpected {:?}", other),
+        };
+
+        set.remove(&member("alice", "admin", 7));
+        set.add(member("aaron", "member", 100));
+        set.add(member("zoe", "member", 1));
+
+        match fold.step(&set, state, 10).unwrap() {
+            Step::Done(Accumulator::Sum(sum)) => assert_eq!(sum, 7 + 3 + 12 - 2 + 5 + 1),
+            other => panic!("Unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_refuses_items_without_the_element() {
+        let mut set = members();
+        set.add(Integer(1));
+
+        assert_eq!(
+            fold_error(&set, &Fold::Max(2)),
+            Some(FoldError::MissingElement)
+        );
+        assert_eq!(
+            fold_error(&members(), &Fold::Sum(0)),
+            Some(FoldError::NotInteger)
+        );
+        assert_eq!(
+            fold_error(&members(), &Fold::Sum(5)),
+            Some(FoldError::MissingElement)
+        );
+    }
+
+    fn fold_error(set: &SortedSet, fold: &Fold) -> Option<FoldError> {
+        fold.step(set, fold.start(), 100).err()
+    }
+}
@@ -0,0 +1,124 @@
+//! Interpolation search over integers, used by sets configured with `interpolation_search`.
+//!
+//! A binary search halves the range it looks at with every comparison.  When the integers are
+//! spread evenly, like snowflake IDs, the position of an integer can instead be estimated from
+//! where it falls between the integers at both ends of the range, which usually lands next to it
+//! after a probe or two.  Estimates are given up on after a few probes and the remaining range
+//! is halved instead, so unevenly spread integers never cost more than a few extra comparisons.
+
+use probes;
+use std::cmp::Ordering;
+use Item;
+
+/// Probes that estimate the position before the search falls back to halving the range.
+const MAX_ESTIMATES: usize = 4;
+
+/// Searches the sorted items for `item`, whose `Item::integer_key` is `key`, with the same
+/// result as `slice::binary_search`.  Ranges holding items without an integer key are binary
+/// searched instead.
+pub fn search<T: Item>(items: &[T], item: &T, key: i64) -> Result<usize, usize> {
+    let position = match partition(items.len(), key, |index| items[index].integer_key()) {
+        Some(position) => position,
+        None => return items.binary_search_by(|probe| probes::compare(probe, item)),
+    };
+
+    match items.get(position) {
+        Some(found) if probes::compare(found, item) == Ordering::Equal => Ok(position),
+        _ => Err(position),
+    }
+}
+
+/// The first index in `0..len` whose key is at least `key`, `len` if there is none.  The keys
+/// returned by `key_at` have to be ascending, None if the key of a probed index is missing.
+pub fn partition<F>(len: usize, key: i64, key_at: F) -> Option<usize>
+where
+    F: Fn(usize) -> Option<i64>,
+{
+    // Every key before `low` is less than `key`, every key from `high` on is not
+    let mut low = 0;
+    let mut high = len;
+    let mut estimates = 0;
+
+    while low < high {
+        let low_key = key_at(low)?;
+        let high_key = key_at(high - 1)?;
+
+        if probes::compare(&key, &low_key) != Ordering::Greater {
+            return Some(low);
+        }
+
+        if probes::compare(&key, &high_key) == Ordering::Greater {
+            return Some(high);
+        }
+
+        // low_key < key <= high_key, so the range holds at least two keys
+        let position = if estimates < MAX_ESTIMATES {
+            estimates += 1;
+
+            let span = (high - 1 - low) as i128;
+            let offset = (i128::from(key) - i128::from(low_key)) * span
+                / (i128::from(high_key) - i128::from(low_key));
+
+            low + offset as usize
+        } else {
+            low + (high - low) / 2
+        };
+
+        if probes::compare(&key_at(position)?, &key) == Ordering::Less {
+            low = position + 1;
+        } else {
+            high = position;
+        }
+    }
+
+    Some(low)
+}
+
+#[cfg(test)]
+mod tests {
+    use interpolation::{partition, search};
+    use supported_term::SupportedTerm::{self, Atom, Integer};
+
+    #[test]
+    fn test_search_matches_binary_search() {
+        let even: Vec<SupportedTerm> = (0..100).map(|i| Integer(i * 10)).collect();
+        let skewed: Vec<SupportedTerm> = (0..60).map(|i| Integer(i * i * i)).collect();
+
+        for items in [even, skewed].iter() {
+            for key in -5..220_000 {
+                if key % 7 != 0 && key > 1_000 {
+                    continue;
+                }
+
+                let item = Integer(key);
+                assert_eq!(search(items, &item, key), items.binary_search(&item));
+            }
+        }
+    }
+
+    #[test]
+    fn test_search_falls_back_without_integer_keys() {
+        let items = vec![
+            Integer(1),
+            Integer(5),
+            Atom(String::from("a")),
+            Atom(String::from("b")),
+        ];
+
+        assert_eq!(search(&items, &Integer(5), 5), Ok(1));
+        assert_eq!(search(&items, &Integer(3), 3), Err(1));
+        assert_eq!(search(&items, &Integer(9), 9), Err(2));
+    }
+
+    #[test]
+    fn test_partition_handles_extreme_keys() {
+        let keys = [i64::MIN, -1, 0, i64::MAX];
+        let key_at = |index: usize| Some(keys[index]);
+
+        assert_eq!(partition(keys.len(), i64::MIN, key_at), Some(0));
+        assert_eq!(partition(keys.len(), -2, key_at), Some(1));
+        assert_eq!(partition(keys.len(), 1, key_at), Some(3));
+        assert_eq!(partition(keys.len(), i64::MAX, key_at), Some(3));
+        assert_eq!(partition(0, 7, key_at), Some(0));
+    }
+}
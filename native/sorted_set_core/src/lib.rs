@@ -0,0 +1,203 @@
+//! The data structure behind the SortedSet NIF, without any dependency on the BEAM.
+//!
+//! A SortedSet keeps its items sorted and unique in a list of buckets, or optionally a B+tree,
+//! which makes inserts cheap while keeping lookups by index and by value fast.  The NIF crate
+//! wraps this crate with the rustler bindings, Rust services can use it directly.
+
+extern crate icu_collator;
+extern crate icu_locid;
+extern crate icu_provider;
+extern crate roaring;
+
+pub mod aggregate;
+#[cfg(feature = "debug-alloc")]
+pub mod alloc_report;
+mod bitset;
+mod btree;
+mod bucket;
+mod buckets;
+pub mod collation;
+pub mod comparator;
+pub mod configuration;
+pub mod cursor;
+pub mod dual_index;
+pub mod etf;
+pub mod fold;
+mod interpolation;
+pub mod merged;
+pub mod metrics;
+#[cfg(all(test, feature = "model-test"))]
+mod model_test;
+pub mod namespaces;
+pub mod persistent;
+pub mod probes;
+pub mod repro;
+pub mod sharded;
+pub mod sketch;
+pub mod sorted_set;
+mod stable_ids;
+pub mod supported_term;
+pub mod view;
+
+pub use configuration::{BackendKind, Configuration, ConfigurationBuilder, ConfigurationError};
+pub use sorted_set::SortedSet;
+pub use supported_term::SupportedTerm;
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+
+/// An item that can be kept in a SortedSet.
+///
+/// Any `Ord` type can be stored, the default methods leave items untouched.  `SupportedTerm`
+/// overrides them to apply the collation and the comparator of the set.
+pub trait Item: Ord + Clone + fmt::Debug {
+    /// Prepares the item before it is sorted or compared against the items of a set with the
+    /// given configuration.
+    fn prepare(self, _configuration: &Configuration) -> Self {
+        self
+    }
+
+    /// Reverses `prepare`, returning the item as it was given to the set.
+    fn strip(self) -> Self {
+        self
+    }
+
+    /// Feeds the item into a hasher for the cardinality sketch of its set, items that compare
+    /// equal have to hash the same.  The default hashes the `Debug` output of the item, types
+    /// that implement `Hash` should hash themselves instead.
+    fn hash_item<H: Hasher>(&self, state: &mut H) {
+        format!("{:?}", self).hash(state)
+    }
+
+    /// Position of the item in the dense universe of the bitset backend, None if the item is not
+    /// a member of it.
+    fn dense_index(&self) -> Option<u32> {
+        None
+    }
+
+    /// The item as an integer whose order matches the order of the items, None if it has no
+    /// such integer.  Sets with `interpolation_search` enabled estimate positions from it.
+    fn integer_key(&self) -> Option<i64> {
+        None
+    }
+
+    /// The members of the dense universe `0..universe` in order, where the member at a position
+    /// has that position as its `dense_index`.  The table may hold more members than asked for.
+    /// None if the type has no dense universe, sets of such items keep the bucket backend.
+    fn dense_universe(_universe: u32) -> Option<Arc<[Self]>>
+    where
+        Self: Sized,
+    {
+        None
+    }
+}
+
+macro_rules! impl_item {
+    ($($t:ty),*) => {
+        $(impl Item for $t {
+            fn hash_item<H: Hasher>(&self, state: &mut H) {
+                self.hash(state)
+            }
+        })*
+    };
+}
+
+impl_item!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, bool, char, String);
+
+#[derive(Debug, PartialEq)]
+pub enum AddResult {
+    Added(usize),
+    Duplicate(usize),
+    SetFull,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RemoveResult {
+    Removed(usize),
+    NotFound,
+
+    /// The item was found but the bookkeeping of the set disagreed with its contents, the
+    /// bookkeeping has been recomputed and the item was left in place.
+    InternalInconsistency,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum FindResult {
+    Found {
+        bucket_idx: usize,
+        inner_idx: usize,
+        idx: usize,
+    },
+    NotFound,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AppendBucketResult {
+    Ok,
+    MaxBucketSizeExceeded,
+    SetFull,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConcatResult {
+    Ok,
+    Overlapping,
+    SetFull,
+    CollationMismatch,
+    ComparatorMismatch,
+    CharlistsMismatch,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MoveRangeResult {
+    /// Number of items that were moved out of the source set.
+    Moved(usize),
+    SetFull,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum MergeResult {
+    /// Number of items of the other set that were not present yet.
+    Merged(usize),
+    SetFull,
+}
+
+#[derive(Debug)]
+pub enum BuildResult<T: Item = SupportedTerm> {
+    Built(Box<SortedSet<T>>),
+    SetFull,
+}
+
+/// What `SortedSet::repair` found wrong with a set, and fixed.
+#[derive(Debug, Default, PartialEq)]
+pub struct RepairReport {
+    /// Buckets whose items were out of order and had to be sorted.
+    pub unsorted_buckets: usize,
+
+    /// Items dropped because an equal item was already in the set.
+    pub duplicates: usize,
+
+    /// Empty buckets dropped from the set.
+    pub empty_buckets: usize,
+
+    /// Neighbouring buckets whose items overlapped, any overlap repacks every item of the set.
+    pub overlapping_buckets: usize,
+
+    /// Size the set recorded before the repair.
+    pub recorded_size: usize,
+
+    /// Number of items in the set after the repair.
+    pub size: usize,
+}
+
+impl RepairReport {
+    /// True if nothing had to be fixed.
+    pub fn is_clean(&self) -> bool {
+        self.unsorted_buckets == 0
+            && self.duplicates == 0
+            && self.empty_buckets == 0
+            && self.overlapping_buckets == 0
+            && self.recorded_size == self.size
+    }
+}
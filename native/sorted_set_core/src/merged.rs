@@ -0,0 +1,256 @@
+//! Reads over several sets as if they were one, merging their items on the fly.
+//!
+//! Nothing is materialized, every read walks the sets it needs with a k-way merge.  Items held by
+//! more than one set are returned once.  The sets must order their items the same way, which
+//! `check_order` verifies.
+
+use comparator::Direction;
+use configuration::Configuration;
+use sorted_set::SortedSet;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::iter::Peekable;
+use BuildResult;
+use Item;
+
+#[derive(Debug, PartialEq)]
+pub enum OrderMismatch {
+    Collation,
+    Comparator,
+}
+
+/// Checks that every set orders its items the same way, items of sets with different collations
+/// or comparators can not be compared against each other.
+pub fn check_order<T: Item>(sets: &[&SortedSet<T>]) -> Result<(), OrderMismatch> {
+    let first = match sets.first() {
+        Some(set) => set.configuration(),
+        None => return Ok(()),
+    };
+
+    for set in &sets[1..] {
+        if set.configuration().collation != first.collation {
+            return Err(OrderMismatch::Collation);
+        }
+
+        if set.configuration().comparator != first.comparator {
+            return Err(OrderMismatch::Comparator);
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the item at `index` of the merged sets.
+pub fn at<T: Item>(sets: &[&SortedSet<T>], index: usize) -> Option<T> {
+    Merge::new(sets.iter().map(|set| set.iter()).collect())
+        .nth(index)
+        .cloned()
+}
+
+/// Returns the number of distinct items across the sets, which takes a walk over all of them.
+pub fn size<T: Item>(sets: &[&SortedSet<T>]) -> usize {
+    Merge::new(sets.iter().map(|set| set.iter()).collect()).count()
+}
+
+/// Returns up to `amount` items of the merged sets starting at `start`.  The merge has to walk
+/// past the first `start` items, so the cost grows with the start of the slice.
+pub fn slice<T: Item>(sets: &[&SortedSet<T>], start: usize, amount: usize) -> Vec<T> {
+    Merge::new(sets.iter().map(|set| set.iter()).collect())
+        .skip(start)
+        .take(amount)
+        .cloned()
+        .collect()
+}
+
+/// Builds a new set with the given configuration out of the distinct items of the sets.  Unlike
+/// the reads above the merge keeps the next item of every set in a heap, so each item costs
+/// `O(log k)` comparisons for `k` sets instead of a comparison against every set.
+pub fn merge_many<T: Item>(configuration: Configuration, sets: &[&SortedSet<T>]) -> BuildResult<T> {
+    let mut runs: Vec<_> = sets.iter().map(|set| set.iter()).collect();
+    let mut heap = BinaryHeap::with_capacity(runs.len());
+
+    for (idx, run) in runs.iter_mut().enumerate() {
+        if let Some(item) = run.next() {
+            heap.push(Reverse((item, idx)));
+        }
+    }
+
+    let mut items: Vec<T> = Vec::with_capacity(sets.iter().map(|set| set.size()).sum());
+
+    while let Some(Reverse((item, idx))) = heap.pop() {
+        if let Some(next) = runs[idx].next() {
+            heap.push(Reverse((next, idx)));
+        }
+
+        // Equal items of different sets leave the heap one after another
+        if items.last().map(|last| last.cmp(item)) != Some(Ordering::Equal) {
+            items.push(item.clone());
+        }
+    }
+
+    SortedSet::from_sorted(configuration, items)
+}
+
+/// Merges runs of items that are each sorted in `direction`, keeping the first `count` distinct
+/// items.
+pub fn merge_runs<T: Item>(runs: Vec<Vec<T>>, direction: Direction, count: usize) -> Vec<T> {
+    let runs: Vec<_> = runs.into_iter().map(|run| run.into_iter()).collect();
+    let mut merge = Merge::new(runs);
+
+    if direction == Direction::Descending {
+        merge.order = Ordering::Greater;
+    }
+
+    merge.take(count).collect()
+}
+
+/// k-way merge of iterators sorted in the same order, skipping items equal to the previous one.
+struct Merge<I: Iterator> {
+    runs: Vec<Peekable<I>>,
+
+    /// `Less` for ascending runs, `Greater` for descending ones.
+    order: Ordering,
+}
+
+impl<I: Iterator> Merge<I>
+where
+    I::Item: Ord,
+{
+    fn new(runs: Vec<I>) -> Merge<I> {
+        Merge {
+            runs: runs.into_iter().map(Iterator::peekable).collect(),
+            order: Ordering::Less,
+        }
+    }
+
+    /// Index of the run holding the next item, `None` once every run is exhausted.
+    fn next_run(&mut self) -> Option<usize> {
+        let mut best: Option<(usize, &I::Item)> = None;
+
+        for (idx, run) in self.runs.iter_mut().enumerate() {
+            if let Some(item) = run.peek() {
+                best = match best {
+                    Some((_, current)) if item.cmp(current) != self.order => best,
+                    _ => Some((idx, item)),
+                };
+            }
+        }
+
+        best.map(|(idx, _)| idx)
+    }
+}
+
+impl<I: Iterator> Iterator for Merge<I>
+where
+    I::Item: Ord,
+{
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        let idx = self.next_run()?;
+        let item = self.runs[idx].next()?;
+
+        // Every other run that holds the same item skips past it
+        for run in self.runs.iter_mut() {
+            while run.peek().map(|next| next.cmp(&item)) == Some(Ordering::Equal) {
+                run.next();
+            }
+        }
+
+        Some(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use collation::Collation;
+    use comparator::Direction;
+    use configuration::Configuration;
+    use merged::{at, check_order, merge_many, merge_runs, size, slice, OrderMismatch};
+    use sorted_set::SortedSet;
+    use supported_term::SupportedTerm::{self, Integer};
+    use BuildResult;
+
+    fn set_of(items: &[i64]) -> SortedSet {
+        items.iter().cloned().map(Integer).collect()
+    }
+
+    fn integers(items: &[i64]) -> Vec<SupportedTerm> {
+        items.iter().cloned().map(Integer).collect()
+    }
+
+    #[test]
+    fn test_at_and_slice_merge_the_sets() {
+        let a = set_of(&[1, 4, 7]);
+        let b = set_of(&[2, 4, 8]);
+        let c = set_of(&[]);
+        let sets = vec![&a, &b, &c];
+
+        assert_eq!(slice(&sets, 0, 10), integers(&[1, 2, 4, 7, 8]));
+        assert_eq!(slice(&sets, 2, 2), integers(&[4, 7]));
+        assert_eq!(at(&sets, 3), Some(Integer(7)));
+        assert_eq!(at(&sets, 5), None);
+        assert_eq!(size(&sets), 5);
+        let none: Vec<&SortedSet> = Vec::new();
+        assert_eq!(slice(&none, 0, 10), integers(&[]));
+    }
+
+    #[test]
+    fn test_merge_many_builds_one_set() {
+        let sets: Vec<SortedSet> = (0..12)
+            .map(|shard| {
+                (0..50)
+                    .map(|i| Integer(i * shard % 97))
+                    .collect::<SortedSet>()
+            })
+            .collect();
+        let refs: Vec<&SortedSet> = sets.iter().collect();
+
+        let configuration = Configuration {
+            max_bucket_size: 8,
+            ..Configuration::default()
+        };
+        let merged = match merge_many(configuration, &refs) {
+            BuildResult::Built(set) => *set,
+            BuildResult::SetFull => panic!("unexpected SetFull"),
+        };
+
+        assert_eq!(merged.to_vec(), slice(&refs, 0, 1_000));
+        assert_eq!(merged.configuration().max_bucket_size, 8);
+
+        let none: Vec<&SortedSet> = Vec::new();
+        let none = match merge_many(Configuration::default(), &none) {
+            BuildResult::Built(set) => *set,
+            BuildResult::SetFull => panic!("unexpected SetFull"),
+        };
+        assert_eq!(none.size(), 0);
+    }
+
+    #[test]
+    fn test_merge_runs_in_either_direction() {
+        let ascending = vec![integers(&[1, 3, 5]), integers(&[2, 3, 6])];
+        let descending = vec![integers(&[5, 3, 1]), integers(&[6, 3, 2])];
+
+        assert_eq!(
+            merge_runs(ascending, Direction::Ascending, 4),
+            integers(&[1, 2, 3, 5])
+        );
+        assert_eq!(
+            merge_runs(descending, Direction::Descending, 10),
+            integers(&[6, 5, 3, 2, 1])
+        );
+    }
+
+    #[test]
+    fn test_check_order() {
+        let a = set_of(&[1]);
+        let b = set_of(&[2]);
+        let collated = SortedSet::new(Configuration {
+            collation: Some(Collation::natural()),
+            ..Configuration::default()
+        });
+
+        assert_eq!(check_order(&[&a, &b]), Ok(()));
+        assert_eq!(check_order(&[&a, &collated]), Err(OrderMismatch::Collation));
+    }
+}
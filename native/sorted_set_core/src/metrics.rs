@@ -0,0 +1,263 @@
+//! Renders the statistics of a SortedSet in the Prometheus text exposition format, so a scraper
+//! can be pointed at the output without any aggregation on the Elixir side.
+
+use sorted_set::SortedSet;
+use std::fmt::Write;
+use std::time::UNIX_EPOCH;
+use Item;
+
+/// Size distribution of the buckets of a set, for the B+tree backend the leaves are reported as
+/// buckets.
+#[derive(Debug, Default, PartialEq)]
+pub struct BucketStats {
+    pub count: usize,
+    pub min_size: usize,
+    pub max_size: usize,
+}
+
+impl BucketStats {
+    pub fn new() -> BucketStats {
+        BucketStats {
+            count: 0,
+            min_size: 0,
+            max_size: 0,
+        }
+    }
+
+    pub fn observe(&mut self, size: usize) {
+        if self.count == 0 || size < self.min_size {
+            self.min_size = size;
+        }
+
+        if size > self.max_size {
+            self.max_size = size;
+        }
+
+        self.count += 1;
+    }
+}
+
+/// State that lives on the resource rather than on the set itself.
+pub struct ResourceStats {
+    pub subscribers: usize,
+    pub alerts: usize,
+}
+
+/// Returns true if `name` is a valid Prometheus label name.
+pub fn is_valid_label_name(name: &str) -> bool {
+    let mut chars = name.chars();
+
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {}
+        _ => return false,
+    }
+
+    !name.starts_with("__") && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Renders every metric of the set, `labels` are attached to every sample.  The caller is
+/// responsible for checking the label names with `is_valid_label_name`.
+pub fn render<T: Item>(
+    set: &SortedSet<T>,
+    resource: &ResourceStats,
+    labels: &[(String, String)],
+) -> String {
+    let labels = format_labels(labels);
+    let buckets = set.bucket_stats();
+    let last_modified = match set.last_modified().0.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_millis() as f64 / 1000.0,
+        Err(_) => 0.0,
+    };
+
+    let mut out = String::new();
+
+    metric(
+        &mut out,
+        "sorted_set_elements",
+        "gauge",
+        "Number of elements in the set.",
+        &labels,
+        set.size(),
+    );
+    metric(
+        &mut out,
+        "sorted_set_memory_bytes",
+        "gauge",
+        "Approximate number of bytes used by the set's own storage.",
+        &labels,
+        set.memory(),
+    );
+    metric(
+        &mut out,
+        "sorted_set_mutations_total",
+        "counter",
+        "Number of successful mutations of the set.",
+        &labels,
+        set.version(),
+    );
+    metric(
+        &mut out,
+        "sorted_set_last_modified_seconds",
+        "gauge",
+        "Unix time of the last successful mutation of the set.",
+        &labels,
+        last_modified,
+    );
+    metric(
+        &mut out,
+        "sorted_set_buckets",
+        "gauge",
+        "Number of buckets holding the elements of the set.",
+        &labels,
+        buckets.count,
+    );
+    metric(
+        &mut out,
+        "sorted_set_bucket_size_min",
+        "gauge",
+        "Number of elements in the smallest bucket.",
+        &labels,
+        buckets.min_size,
+    );
+    metric(
+        &mut out,
+        "sorted_set_bucket_size_max",
+        "gauge",
+        "Number of elements in the largest bucket.",
+        &labels,
+        buckets.max_size,
+    );
+    metric(
+        &mut out,
+        "sorted_set_max_bucket_size",
+        "gauge",
+        "Configured maximum bucket size.",
+        &labels,
+        set.configuration().max_bucket_size,
+    );
+    metric(
+        &mut out,
+        "sorted_set_frozen",
+        "gauge",
+        "1 if the set is frozen, 0 otherwise.",
+        &labels,
+        set.is_frozen() as u8,
+    );
+    metric(
+        &mut out,
+        "sorted_set_subscribers",
+        "gauge",
+        "Number of processes subscribed to the set.",
+        &labels,
+        resource.subscribers,
+    );
+    metric(
+        &mut out,
+        "sorted_set_pending_alerts",
+        "gauge",
+        "Number of watermark alerts that have not fired yet.",
+        &labels,
+        resource.alerts,
+    );
+
+    out
+}
+
+fn metric<T: ::std::fmt::Display>(
+    out: &mut String,
+    name: &str,
+    kind: &str,
+    help: &str,
+    labels: &str,
+    value: T,
+) {
+    // Writing into a String can not fail
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{}{} {}", name, labels, value);
+}
+
+fn format_labels(labels: &[(String, String)]) -> String {
+    if labels.is_empty() {
+        return String::new();
+    }
+
+    let pairs: Vec<String> = labels
+        .iter()
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_label_value(value)))
+        .collect();
+
+    format!("{{{}}}", pairs.join(","))
+}
+
+fn escape_label_value(value: &str) -> String {
+    let mut result = String::with_capacity(value.len());
+
+    for c in value.chars() {
+        match c {
+            '\\' => result.push_str("\\\\"),
+            '"' => result.push_str("\\\""),
+            '\n' => result.push_str("\\n"),
+            c => result.push(c),
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use metrics::{is_valid_label_name, render, BucketStats, ResourceStats};
+    use sorted_set::SortedSet;
+    use supported_term::SupportedTerm::Integer;
+
+    #[test]
+    fn test_render() {
+        let mut set = SortedSet::default();
+        for i in 0..10 {
+            set.add(Integer(i));
+        }
+
+        let resource = ResourceStats {
+            subscribers: 2,
+            alerts: 0,
+        };
+        let labels = vec![(String::from("set"), String::from("a \"quoted\"\\name"))];
+        let output = render(&set, &resource, &labels);
+
+        assert!(output.contains("# TYPE sorted_set_elements gauge\n"));
+        assert!(output.contains("sorted_set_elements{set=\"a \\\"quoted\\\"\\\\name\"} 10\n"));
+        assert!(
+            output.contains("sorted_set_mutations_total{set=\"a \\\"quoted\\\"\\\\name\"} 10\n")
+        );
+        assert!(output.contains("sorted_set_subscribers{set=\"a \\\"quoted\\\"\\\\name\"} 2\n"));
+        assert!(render(&set, &resource, &[]).contains("\nsorted_set_buckets 1\n"));
+    }
+
+    #[test]
+    fn test_bucket_stats() {
+        let mut stats = BucketStats::new();
+        stats.observe(5);
+        stats.observe(2);
+        stats.observe(9);
+
+        assert_eq!(
+            stats,
+            BucketStats {
+                count: 3,
+                min_size: 2,
+                max_size: 9,
+            }
+        );
+    }
+
+    #[test]
+    fn test_label_names() {
+        assert!(is_valid_label_name("shard_1"));
+        assert!(is_valid_label_name("_set"));
+        assert!(!is_valid_label_name(""));
+        assert!(!is_valid_label_name("1shard"));
+        assert!(!is_valid_label_name("__name__"));
+        assert!(!is_valid_label_name("set-name"));
+    }
+}
@@ -0,0 +1,449 @@
+//! Model-based soak test, enabled with the `model-test` feature.
+//!
+//!     cargo test --features model-test model_test
+//!
+//! Long random sequences of operations are applied to a SortedSet and to a `BTreeSet` reference
+//! model, every result is compared against the model and the invariants of the set are checked
+//! after every step.  The number of steps and the seed can be set with the `MODEL_TEST_STEPS` and
+//! `MODEL_TEST_SEED` environment variables, a failure reports the seed and the step so it can be
+//! replayed.
+
+use configuration::{BackendKind, Configuration};
+use sorted_set::SortedSet;
+use std::cmp::Ordering;
+use std::collections::BTreeSet;
+use std::env;
+use supported_term::SupportedTerm;
+use supported_term::SupportedTerm::{Atom, Bitstring, Integer, List, Tuple};
+use AddResult;
+use ConcatResult;
+use RemoveResult;
+
+const DEFAULT_STEPS: usize = 20_000;
+const DEFAULT_SEED: u64 = 0x5eed;
+
+/// Keeps the set small enough that every step can afford a full comparison with the model, while
+/// still being large enough to spread over many buckets.
+const VALUE_RANGE: u64 = 600;
+
+/// xorshift64*, a seeded generator is all the soak test needs and keeps runs reproducible.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn below(&mut self, bound: u64) -> u64 {
+        self.next() % bound
+    }
+
+    fn term(&mut self) -> SupportedTerm {
+        let value = self.below(VALUE_RANGE);
+
+        // Mostly integers so that adds and removes collide often, with a sprinkling of the other
+        // types to exercise the cross-type ordering
+        match self.below(10) {
+            0 => Atom(format!("atom_{}", value % 20)),
+            1 => Bitstring(format!("string_{}", value % 50)),
+            2 => Tuple(vec![
+                Integer((value % 10) as i64),
+                Integer((value / 10) as i64),
+            ]),
+            3 => List(vec![Integer((value % 7) as i64)]),
+            _ => Integer(value as i64 - (VALUE_RANGE / 2) as i64),
+        }
+    }
+}
+
+fn env_or(name: &str, default: u64) -> u64 {
+    match env::var(name) {
+        Ok(value) => value
+            .parse()
+            .unwrap_or_else(|_| panic!("{} must be an integer, got {:?}", name, value)),
+        Err(_) => default,
+    }
+}
+
+fn same(left: &SupportedTerm, right: &SupportedTerm) -> bool {
+    // SupportedTerm's PartialEq recurses forever on tuples and lists, compare with Ord instead
+    left.cmp(right) == Ordering::Equal
+}
+
+fn same_items<'a, I, J>(left: I, right: J) -> bool
+where
+    I: IntoIterator<Item = &'a SupportedTerm>,
+    J: IntoIterator<Item = &'a SupportedTerm>,
+{
+    let mut left = left.into_iter();
+    let mut right = right.into_iter();
+
+    loop {
+        match (left.next(), right.next()) {
+            (None, None) => return true,
+            (Some(l), Some(r)) if same(l, r) => continue,
+            _ => return false,
+        }
+    }
+}
+
+struct Harness {
+    seed: u64,
+    step: usize,
+    rng: Rng,
+    set: SortedSet,
+    model: BTreeSet<SupportedTerm>,
+}
+
+impl Harness {
+    fn new(seed: u64, configuration: Configuration) -> Harness {
+        Harness {
+            seed,
+            step: 0,
+            rng: Rng::new(seed),
+            set: SortedSet::new(configuration),
+            model: BTreeSet::new(),
+        }
+    }
+
+    fn fail(&self, message: String) -> ! {
+        panic!(
+            "model mismatch with seed {} at step {} ({:?} backend): {}",
+            self.seed,
+            self.step,
+            self.set.configuration().backend,
+            message
+        )
+    }
+
+    fn rank(&self, item: &SupportedTerm) -> usize {
+        self.model.range(..item).count()
+    }
+
+    fn run(&mut self, steps: usize) {
+        for step in 0..steps {
+            self.step = step;
+            self.apply();
+            self.check_invariants();
+        }
+    }
+
+    fn apply(&mut self) {
+        match self.rng.below(100) {
+            0..=39 => self.add(),
+            40..=49 => self.add_with_hint(),
+            50..=77 => self.remove(),
+            78..=79 => self.remove_at_many(),
+            80..=87 => self.find_index(),
+            88..=93 => self.at(),
+            94..=97 => self.slice(),
+            _ => self.partition_and_concat(),
+        }
+    }
+
+    fn add(&mut self) {
+        let item = self.rng.term();
+        let expected = self.rank(&item);
+        let inserted = self.model.insert(item.clone());
+
+        match (self.set.add(item.clone()), inserted) {
+            (AddResult::Added(idx), true) if idx == expected => {}
+            (AddResult::Duplicate(idx), false) if idx == expected => {}
+            (result, _) => self.fail(format!(
+                "add({:?}) returned {:?}, expected index {} (new: {})",
+                item, result, expected, inserted
+            )),
+        }
+    }
+
+    fn add_with_hint(&mut self) {
+        let item = self.rng.term();
+        let expected = self.rank(&item);
+        let inserted = self.model.insert(item.clone());
+
+        // Hints are only advisory, a wrong hint must not change the result
+        let hint = match self.rng.below(2) {
+            0 => expected,
+            _ => self.rng.below(self.model.len() as u64 + 1) as usize,
+        };
+
+        match (self.set.add_with_hint(item.clone(), hint), inserted) {
+            (AddResult::Added(idx), true) if idx == expected => {}
+            (AddResult::Duplicate(idx), false) if idx == expected => {}
+            (result, _) => self.fail(format!(
+                "add_with_hint({:?}, {}) returned {:?}, expected index {} (new: {})",
+                item, hint, result, expected, inserted
+            )),
+        }
+    }
+
+    fn remove(&mut self) {
+        let item = self.rng.term();
+        let expected = self.rank(&item);
+        let removed = self.model.remove(&item);
+
+        match (self.set.remove(&item), removed) {
+            (RemoveResult::Removed(idx), true) if idx == expected => {}
+            (RemoveResult::NotFound, false) => {}
+            (result, _) => self.fail(format!(
+                "remove({:?}) returned {:?}, expected index {} (present: {})",
+                item, result, expected, removed
+            )),
+        }
+    }
+
+    fn remove_at_many(&mut self) {
+        let count = self.rng.below(8);
+        let indexes: Vec<usize> = (0..count)
+            .map(|_| self.rng.below(self.model.len() as u64 + 2) as usize)
+            .collect();
+
+        let mut expected_indexes: Vec<usize> = indexes
+            .iter()
+            .cloned()
+            .filter(|&index| index < self.model.len())
+            .collect();
+        expected_indexes.sort_unstable();
+        expected_indexes.dedup();
+
+        let expected: Vec<SupportedTerm> = expected_indexes
+            .iter()
+            .filter_map(|&index| self.model.iter().nth(index).cloned())
+            .collect();
+
+        for item in expected.iter() {
+            self.model.remove(item);
+        }
+
+        let removed = self.set.remove_at_many(&indexes);
+        if !same_items(&removed, &expected) {
+            self.fail(format!(
+                "remove_at_many({:?}) returned {:?}, expected {:?}",
+                indexes, removed, expected
+            ));
+        }
+    }
+
+    fn find_index(&mut self) {
+        let item = self.rng.term();
+        let expected = if self.model.contains(&item) {
+            Some(self.rank(&item))
+        } else {
+            None
+        };
+
+        let result = self.set.find_index(&item);
+        if result != expected {
+            self.fail(format!(
+                "find_index({:?}) returned {:?}, expected {:?}",
+                item, result, expected
+            ));
+        }
+
+        let floor = self.model.range(..=item.clone()).next_back();
+        if !same_items(self.set.floor(&item), floor) {
+            self.fail(format!(
+                "floor({:?}) returned {:?}, expected {:?}",
+                item,
+                self.set.floor(&item),
+                floor
+            ));
+        }
+
+        let ceiling = self.model.range(item.clone()..).next();
+        if !same_items(self.set.ceiling(&item), ceiling) {
+            self.fail(format!(
+                "ceiling({:?}) returned {:?}, expected {:?}",
+                item,
+                self.set.ceiling(&item),
+                ceiling
+            ));
+        }
+
+        if self.set.contains(&item) != expected.is_some() {
+            self.fail(format!(
+                "contains({:?}) returned {}, expected {}",
+                item,
+                self.set.contains(&item),
+                expected.is_some()
+            ));
+        }
+    }
+
+    fn at(&mut self) {
+        let index = self.rng.below(self.model.len() as u64 + 2) as usize;
+        let expected = self.model.iter().nth(index);
+
+        let matches = match (self.set.at(index), expected) {
+            (Some(actual), Some(expected)) => same(actual, expected),
+            (None, None) => true,
+            _ => false,
+        };
+
+        if !matches {
+            self.fail(format!(
+                "at({}) returned {:?}, expected {:?}",
+                index,
+                self.set.at(index),
+                expected
+            ));
+        }
+    }
+
+    fn slice(&mut self) {
+        let start = self.rng.below(self.model.len() as u64 + 2) as usize;
+        let amount = self.rng.below(3 * VALUE_RANGE / 2) as usize;
+
+        let actual = self.set.slice(start, amount);
+        let expected = self.model.iter().skip(start).take(amount);
+
+        if !same_items(&actual, expected) {
+            self.fail(format!(
+                "slice({}, {}) returned {:?}",
+                start, amount, actual
+            ));
+        }
+
+        let start_from_end = self.rng.below(self.model.len() as u64 + 2) as usize;
+        let actual = self.set.slice_desc(start_from_end, amount);
+        let expected = self.model.iter().rev().skip(start_from_end).take(amount);
+
+        if !same_items(&actual, expected) {
+            self.fail(format!(
+                "slice_desc({}, {}) returned {:?}",
+                start_from_end, amount, actual
+            ));
+        }
+    }
+
+    /// Partitions the set around a random pivot and concatenates the halves back together, which
+    /// must leave the contents unchanged.
+    fn partition_and_concat(&mut self) {
+        let pivot = self.rng.term();
+        let expected_lower = self.rank(&pivot);
+
+        let (mut lower, mut upper) = self.set.partition(&pivot);
+
+        if self.set.size() != 0 {
+            self.fail(format!("partition({:?}) left items behind", pivot));
+        }
+
+        if lower.size() != expected_lower || upper.size() != self.model.len() - expected_lower {
+            self.fail(format!(
+                "partition({:?}) split into {} and {}, expected {} and {}",
+                pivot,
+                lower.size(),
+                upper.size(),
+                expected_lower,
+                self.model.len() - expected_lower
+            ));
+        }
+
+        let result = lower.concat(&mut upper);
+        if result != ConcatResult::Ok {
+            self.fail(format!("concat after partition returned {:?}", result));
+        }
+
+        self.set.swap(&mut lower);
+    }
+
+    fn check_invariants(&self) {
+        if self.set.size() != self.model.len() {
+            self.fail(format!(
+                "size is {}, expected {}",
+                self.set.size(),
+                self.model.len()
+            ));
+        }
+
+        let mut previous: Option<&SupportedTerm> = None;
+        for item in self.set.iter() {
+            if let Some(previous) = previous {
+                if previous >= item {
+                    self.fail(format!("{:?} is not less than {:?}", previous, item));
+                }
+            }
+            previous = Some(item);
+        }
+
+        if !same_items(self.set.iter(), self.model.iter()) {
+            self.fail(String::from("contents differ from the model"));
+        }
+
+        if !same_items(&self.set.to_vec_rev(), self.model.iter().rev()) {
+            self.fail(String::from("reversed contents differ from the model"));
+        }
+
+        // The B+tree sizes its leaves on its own
+        let configuration = self.set.configuration();
+        if configuration.backend != BackendKind::Buckets {
+            return;
+        }
+
+        let stats = self.set.bucket_stats();
+        if stats.max_size > self.set.configuration().max_bucket_size {
+            self.fail(format!(
+                "bucket of {} items exceeds the max bucket size of {}",
+                stats.max_size, configuration.max_bucket_size
+            ));
+        }
+    }
+}
+
+fn soak(backend: BackendKind, max_bucket_size: usize) {
+    soak_with(Configuration {
+        max_bucket_size,
+        backend,
+        ..Configuration::default()
+    });
+}
+
+fn soak_with(configuration: Configuration) {
+    let steps = env_or("MODEL_TEST_STEPS", DEFAULT_STEPS as u64) as usize;
+    let seed = env_or("MODEL_TEST_SEED", DEFAULT_SEED);
+
+    Harness::new(seed, configuration).run(steps);
+}
+
+#[test]
+fn test_model_buckets() {
+    soak(BackendKind::Buckets, 8);
+}
+
+#[test]
+fn test_model_buckets_default_size() {
+    soak(
+        BackendKind::Buckets,
+        Configuration::default().max_bucket_size,
+    );
+}
+
+#[test]
+fn test_model_buckets_tombstones() {
+    soak_with(Configuration {
+        max_bucket_size: 8,
+        tombstone_ratio: 0.5,
+        ..Configuration::default()
+    });
+}
+
+#[test]
+fn test_model_buckets_interpolation() {
+    soak_with(Configuration {
+        max_bucket_size: 8,
+        interpolation_search: true,
+        ..Configuration::default()
+    });
+}
+
+#[test]
+fn test_model_btree() {
+    soak(BackendKind::BTree, 8);
+}
@@ -0,0 +1,168 @@
+use sorted_set::SortedSet;
+use std::collections::btree_map::{self, BTreeMap};
+use std::mem;
+use supported_term::SupportedTerm;
+use AddResult;
+use Configuration;
+use Item;
+use RemoveResult;
+
+/// Many named SortedSets sharing a single configuration.
+///
+/// A namespace is created by the first item added to it and dropped as soon as its last item is
+/// removed, so only namespaces that hold items take up memory.  Every namespace is a SortedSet of
+/// its own, the `max_elements` of the configuration limits each namespace separately.
+#[derive(Debug)]
+pub struct Namespaces<T: Item = SupportedTerm> {
+    configuration: Configuration,
+    sets: BTreeMap<T, SortedSet<T>>,
+}
+
+impl<T: Item> Namespaces<T> {
+    pub fn new(configuration: Configuration) -> Namespaces<T> {
+        if configuration.max_bucket_size < 1 {
+            panic!("Namespaces max_bucket_size must be greater than 0");
+        }
+
+        Namespaces {
+            configuration,
+            sets: BTreeMap::new(),
+        }
+    }
+
+    pub fn configuration(&self) -> &Configuration {
+        &self.configuration
+    }
+
+    /// Returns the set of a namespace, `None` if the namespace holds no items.
+    pub fn get(&self, namespace: &T) -> Option<&SortedSet<T>> {
+        self.sets.get(namespace)
+    }
+
+    pub fn add(&mut self, namespace: T, item: T) -> AddResult {
+        if let Some(set) = self.sets.get_mut(&namespace) {
+            return set.add(item);
+        }
+
+        let mut set = SortedSet::empty(self.configuration.clone());
+        let result = set.add(item);
+
+        if let AddResult::Added(_) = result {
+            self.sets.insert(namespace, set);
+        }
+
+        result
+    }
+
+    pub fn remove(&mut self, namespace: &T, item: &T) -> RemoveResult {
+        let (result, empty) = match self.sets.get_mut(namespace) {
+            None => return RemoveResult::NotFound,
+            Some(set) => (set.remove(item), set.size() == 0),
+        };
+
+        if empty {
+            self.sets.remove(namespace);
+        }
+
+        result
+    }
+
+    /// Removes a namespace along with all of its items, returning its set.
+    pub fn delete(&mut self, namespace: &T) -> Option<SortedSet<T>> {
+        self.sets.remove(namespace)
+    }
+
+    /// The namespaces that hold items, in order.
+    pub fn names(&self) -> btree_map::Keys<'_, T, SortedSet<T>> {
+        self.sets.keys()
+    }
+
+    /// Number of namespaces that hold items.
+    pub fn len(&self) -> usize {
+        self.sets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sets.is_empty()
+    }
+
+    /// Number of items across all namespaces.
+    pub fn size(&self) -> usize {
+        self.sets.values().map(SortedSet::size).sum()
+    }
+
+    /// Approximate number of bytes used by the namespaces, see `SortedSet::memory`.
+    pub fn memory(&self) -> usize {
+        self.sets
+            .values()
+            .map(|set| mem::size_of::<T>() + set.memory())
+            .sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use namespaces::Namespaces;
+    use supported_term::SupportedTerm::{self, Integer};
+    use AddResult::{Added, Duplicate, SetFull};
+    use Configuration;
+    use RemoveResult::{NotFound, Removed};
+
+    #[test]
+    fn test_namespaces_are_independent() {
+        let mut namespaces: Namespaces = Namespaces::new(Configuration::default());
+
+        assert_eq!(namespaces.add(Integer(1), Integer(10)), Added(0));
+        assert_eq!(namespaces.add(Integer(1), Integer(5)), Added(0));
+        assert_eq!(namespaces.add(Integer(2), Integer(10)), Added(0));
+        assert_eq!(namespaces.add(Integer(2), Integer(10)), Duplicate(0));
+
+        assert_eq!(
+            namespaces.get(&Integer(1)).unwrap().to_vec(),
+            vec![Integer(5), Integer(10)]
+        );
+        assert_eq!(
+            namespaces.get(&Integer(2)).unwrap().to_vec(),
+            vec![Integer(10)]
+        );
+        assert!(namespaces.get(&Integer(3)).is_none());
+        assert_eq!(namespaces.len(), 2);
+        assert_eq!(namespaces.size(), 3);
+    }
+
+    #[test]
+    fn test_empty_namespaces_are_dropped() {
+        let mut namespaces: Namespaces = Namespaces::new(Configuration::default());
+        namespaces.add(Integer(1), Integer(10));
+        namespaces.add(Integer(2), Integer(10));
+
+        assert_eq!(namespaces.remove(&Integer(1), &Integer(5)), NotFound);
+        assert_eq!(namespaces.remove(&Integer(3), &Integer(10)), NotFound);
+        assert_eq!(namespaces.remove(&Integer(1), &Integer(10)), Removed(0));
+
+        let names: Vec<&SupportedTerm> = namespaces.names().collect();
+        assert_eq!(names, vec![&Integer(2)]);
+
+        assert_eq!(namespaces.delete(&Integer(2)).unwrap().size(), 1);
+        assert!(namespaces.is_empty());
+    }
+
+    #[test]
+    fn test_max_elements_applies_per_namespace() {
+        let mut namespaces: Namespaces = Namespaces::new(Configuration {
+            max_elements: Some(1),
+            ..Configuration::default()
+        });
+
+        assert_eq!(namespaces.add(Integer(1), Integer(10)), Added(0));
+        assert_eq!(namespaces.add(Integer(1), Integer(11)), SetFull);
+        assert_eq!(namespaces.add(Integer(2), Integer(11)), Added(0));
+
+        let mut empty: Namespaces = Namespaces::new(Configuration {
+            max_elements: Some(0),
+            ..Configuration::default()
+        });
+        assert_eq!(empty.add(Integer(1), Integer(10)), SetFull);
+        assert!(empty.is_empty());
+    }
+}
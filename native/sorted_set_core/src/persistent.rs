@@ -0,0 +1,261 @@
+use bucket::Bucket;
+use std::cmp::{max, min};
+use std::sync::Arc;
+use supported_term::SupportedTerm;
+use AddResult;
+use Item;
+use RemoveResult;
+
+/// An immutable SortedSet, every mutation returns a new set and leaves the original untouched.
+///
+/// The buckets are shared between versions, a mutation copies the Vec of bucket pointers and the
+/// single bucket it modifies (path copying) so holding on to many versions of a large set only
+/// costs the buckets that actually differ between them.
+#[derive(Clone, Debug)]
+pub struct PersistentSet<T: Item = SupportedTerm> {
+    max_bucket_size: usize,
+    buckets: Arc<Vec<Arc<Bucket<T>>>>,
+    size: usize,
+}
+
+impl<T: Item> PersistentSet<T> {
+    pub fn new(max_bucket_size: usize) -> PersistentSet<T> {
+        if max_bucket_size < 1 {
+            panic!("PersistentSet<T> max_bucket_size must be greater than 0");
+        }
+
+        PersistentSet {
+            max_bucket_size,
+            buckets: Arc::new(vec![Arc::new(Bucket {
+                data: Vec::new(),
+                dead: Vec::new(),
+            })]),
+            size: 0,
+        }
+    }
+
+    /// Builds a set from items that are already sorted and free of duplicates.
+    pub fn from_sorted(max_bucket_size: usize, items: Vec<T>) -> PersistentSet<T> {
+        if items.is_empty() {
+            return PersistentSet::new(max_bucket_size);
+        }
+
+        let size = items.len();
+        let chunk_size = max(max_bucket_size - 1, 1);
+        let mut buckets = Vec::with_capacity(size / chunk_size + 1);
+        let mut items = items.into_iter().peekable();
+
+        while items.peek().is_some() {
+            let data: Vec<T> = items.by_ref().take(chunk_size).collect();
+            buckets.push(Arc::new(Bucket {
+                data,
+                dead: Vec::new(),
+            }));
+        }
+
+        PersistentSet {
+            max_bucket_size,
+            buckets: Arc::new(buckets),
+            size,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    #[inline]
+    fn find_bucket_index(&self, item: &T) -> usize {
+        match self
+            .buckets
+            .binary_search_by(|bucket| bucket.item_compare(item))
+        {
+            Ok(idx) => idx,
+            Err(idx) => min(idx, self.buckets.len() - 1),
+        }
+    }
+
+    #[inline]
+    fn effective_index(&self, bucket: usize, index: usize) -> usize {
+        let mut result = index;
+
+        for bucket_index in 0..bucket {
+            result += self.buckets[bucket_index].len();
+        }
+
+        result
+    }
+
+    pub fn find_index(&self, item: &T) -> Option<usize> {
+        let bucket_idx = self.find_bucket_index(item);
+
+        match self.buckets[bucket_idx].data.binary_search(item) {
+            Ok(idx) => Some(self.effective_index(bucket_idx, idx)),
+            Err(_) => None,
+        }
+    }
+
+    /// Returns the new version of the set with the item added, adding a duplicate returns a set
+    /// sharing everything with this one.
+    pub fn add(&self, item: T) -> (AddResult, PersistentSet<T>) {
+        let bucket_idx = self.find_bucket_index(&item);
+
+        let idx = match self.buckets[bucket_idx].data.binary_search(&item) {
+            Ok(idx) => {
+                return (
+                    AddResult::Duplicate(self.effective_index(bucket_idx, idx)),
+                    self.clone(),
+                )
+            }
+            Err(idx) => idx,
+        };
+
+        let mut buckets: Vec<Arc<Bucket<T>>> = (*self.buckets).clone();
+        let mut bucket: Bucket<T> = (*buckets[bucket_idx]).clone();
+
+        bucket.data.insert(idx, item);
+
+        if bucket.len() >= self.max_bucket_size {
+            let new_bucket = bucket.split();
+            buckets.insert(bucket_idx + 1, Arc::new(new_bucket));
+        }
+
+        buckets[bucket_idx] = Arc::new(bucket);
+
+        let result = PersistentSet {
+            max_bucket_size: self.max_bucket_size,
+            buckets: Arc::new(buckets),
+            size: self.size + 1,
+        };
+
+        (
+            AddResult::Added(self.effective_index(bucket_idx, idx)),
+            result,
+        )
+    }
+
+    /// Returns the new version of the set with the item removed, removing an item that is not
+    /// present returns a set sharing everything with this one.
+    pub fn remove(&self, item: &T) -> (RemoveResult, PersistentSet<T>) {
+        let bucket_idx = self.find_bucket_index(item);
+
+        let idx = match self.buckets[bucket_idx].data.binary_search(item) {
+            Ok(idx) => idx,
+            Err(_) => return (RemoveResult::NotFound, self.clone()),
+        };
+
+        let mut buckets: Vec<Arc<Bucket<T>>> = (*self.buckets).clone();
+        let mut bucket: Bucket<T> = (*buckets[bucket_idx]).clone();
+
+        bucket.data.remove(idx);
+
+        if buckets.len() > 1 && bucket.data.is_empty() {
+            buckets.remove(bucket_idx);
+        } else {
+            buckets[bucket_idx] = Arc::new(bucket);
+        }
+
+        let result = PersistentSet {
+            max_bucket_size: self.max_bucket_size,
+            buckets: Arc::new(buckets),
+            size: self.size - 1,
+        };
+
+        (
+            RemoveResult::Removed(self.effective_index(bucket_idx, idx)),
+            result,
+        )
+    }
+
+    pub fn at(&self, index: usize) -> Option<&T> {
+        self.iter().nth(index)
+    }
+
+    pub fn slice(&self, index: usize, amount: usize) -> Vec<T> {
+        self.iter().skip(index).take(amount).cloned().collect()
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        self.iter().cloned().collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.buckets.iter().flat_map(|bucket| bucket.data.iter())
+    }
+
+    /// Number of buckets shared with `other`, used to verify that versions share structure.
+    #[cfg(test)]
+    fn shared_buckets(&self, other: &PersistentSet<T>) -> usize {
+        self.buckets
+            .iter()
+            .filter(|bucket| other.buckets.iter().any(|o| Arc::ptr_eq(bucket, o)))
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use persistent::PersistentSet;
+    use supported_term::SupportedTerm::Integer;
+    use AddResult::{Added, Duplicate};
+    use RemoveResult::{NotFound, Removed};
+
+    #[test]
+    fn test_add_leaves_the_original_untouched() {
+        let empty = PersistentSet::new(5);
+
+        let (result, one) = empty.add(Integer(1));
+        assert_eq!(result, Added(0));
+
+        let (result, two) = one.add(Integer(0));
+        assert_eq!(result, Added(0));
+
+        assert_eq!(empty.to_vec(), vec![]);
+        assert_eq!(one.to_vec(), vec![Integer(1)]);
+        assert_eq!(two.to_vec(), vec![Integer(0), Integer(1)]);
+        assert_eq!(two.size(), 2);
+
+        let (result, same) = two.add(Integer(1));
+        assert_eq!(result, Duplicate(1));
+        assert_eq!(same.to_vec(), two.to_vec());
+    }
+
+    #[test]
+    fn test_remove_leaves_the_original_untouched() {
+        let set = PersistentSet::from_sorted(5, (0..20).map(Integer).collect());
+
+        let (result, removed) = set.remove(&Integer(7));
+        assert_eq!(result, Removed(7));
+        assert_eq!(removed.size(), 19);
+        assert_eq!(removed.find_index(&Integer(7)), None);
+        assert_eq!(set.find_index(&Integer(7)), Some(7));
+
+        let (result, _) = removed.remove(&Integer(7));
+        assert_eq!(result, NotFound);
+    }
+
+    #[test]
+    fn test_versions_share_untouched_buckets() {
+        let set = PersistentSet::from_sorted(5, (0..40).map(|i| Integer(i * 2)).collect());
+        let buckets = set.buckets.len();
+
+        let (_, added) = set.add(Integer(21));
+        assert_eq!(added.shared_buckets(&set), buckets - 1);
+
+        let (_, removed) = added.remove(&Integer(0));
+        assert_eq!(removed.shared_buckets(&added), added.buckets.len() - 1);
+    }
+
+    #[test]
+    fn test_at_and_slice() {
+        let mut set = PersistentSet::new(3);
+
+        for i in (0..10).rev() {
+            set = set.add(Integer(i)).1;
+        }
+
+        assert_eq!(set.at(4), Some(&Integer(4)));
+        assert_eq!(set.at(10), None);
+        assert_eq!(set.slice(8, 5), vec![Integer(8), Integer(9)]);
+    }
+}
@@ -0,0 +1,111 @@
+//! Counts of the work done locating and moving items, enabled with the `probe-stats` feature.
+//!
+//! Wall time alone does not tell whether a slow set searches too many buckets, compares too many
+//! items or shifts too many items on every write, which is what tuning `max_bucket_size` comes
+//! down to.  The counters are totals across every set of the process since it started, divided by
+//! the number of operations they give the work of an average operation.  Without the feature the
+//! counting compiles away and `snapshot` returns None.  Only the buckets backend is counted.
+
+use std::cmp::Ordering;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ProbeStats {
+    /// Items located in the buckets, every add, remove and lookup by item is an operation.
+    pub operations: usize,
+
+    /// Comparisons between two items.
+    pub comparisons: usize,
+
+    /// Buckets whose bounds were compared while locating an item, or whose size was summed up
+    /// to turn a position in a bucket into an index.
+    pub buckets_probed: usize,
+
+    /// Items moved within a bucket to make room for an added item or to close the gap of a
+    /// removed one, and items moved into a new bucket when a bucket is split.
+    pub elements_shifted: usize,
+}
+
+static OPERATIONS: AtomicUsize = AtomicUsize::new(0);
+static COMPARISONS: AtomicUsize = AtomicUsize::new(0);
+static BUCKETS_PROBED: AtomicUsize = AtomicUsize::new(0);
+static ELEMENTS_SHIFTED: AtomicUsize = AtomicUsize::new(0);
+
+#[inline]
+fn count(counter: &AtomicUsize, amount: usize) {
+    if cfg!(feature = "probe-stats") {
+        counter.fetch_add(amount, AtomicOrdering::Relaxed);
+    }
+}
+
+#[inline]
+pub fn operation() {
+    count(&OPERATIONS, 1);
+}
+
+/// Compares two items like `Ord::cmp`, counting the comparison.
+#[inline]
+pub fn compare<T: Ord>(a: &T, b: &T) -> Ordering {
+    count(&COMPARISONS, 1);
+    a.cmp(b)
+}
+
+#[inline]
+pub fn buckets_probed(amount: usize) {
+    count(&BUCKETS_PROBED, amount);
+}
+
+#[inline]
+pub fn shifted(amount: usize) {
+    count(&ELEMENTS_SHIFTED, amount);
+}
+
+/// The counters so far, None if they are not enabled.
+pub fn snapshot() -> Option<ProbeStats> {
+    if !cfg!(feature = "probe-stats") {
+        return None;
+    }
+
+    Some(ProbeStats {
+        operations: OPERATIONS.load(AtomicOrdering::Relaxed),
+        comparisons: COMPARISONS.load(AtomicOrdering::Relaxed),
+        buckets_probed: BUCKETS_PROBED.load(AtomicOrdering::Relaxed),
+        elements_shifted: ELEMENTS_SHIFTED.load(AtomicOrdering::Relaxed),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use probes;
+    use sorted_set::SortedSet;
+    use supported_term::SupportedTerm::Integer;
+    use Configuration;
+
+    #[test]
+    fn test_counts_the_work_of_the_buckets() {
+        let before = probes::snapshot();
+
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 4,
+            ..Configuration::default()
+        });
+
+        for i in (0..20).rev() {
+            set.add(Integer(i));
+        }
+        set.find_index(&Integer(7));
+
+        // Other tests count into the same totals at the same time
+        match (before, probes::snapshot()) {
+            (Some(before), Some(after)) => {
+                assert!(after.operations >= before.operations + 21);
+                assert!(after.comparisons >= before.comparisons + 21);
+                assert!(after.buckets_probed >= before.buckets_probed + 21);
+                assert!(after.elements_shifted >= before.elements_shifted + 19);
+            }
+            // Built without the feature
+            (None, None) => {}
+            other => panic!("Unexpected {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,506 @@
+//! Repro bundles capture the configuration, bucket layout and contents of a set so the exact state
+//! of a misbehaving set can be attached to a bug report and replayed elsewhere.
+//!
+//! A bundle is an External Term Format encoded tuple, so it can also be inspected with
+//! `:erlang.binary_to_term/1`
+//!
+//! ```text
+//! {:sorted_set_repro, 1,
+//!  {max_bucket_size, initial_set_capacity, max_elements | nil, backend, stable_ids,
+//!   locale | :natural | nil, [{element, :asc | :desc}] | nil, :erlang | :lexicographic},
+//!  {frozen, meta_or_empty_list},
+//!  [bucket, ...]}
+//! ```
+//!
+//! Where every bucket is a list of the items it holds.  The encoding does not depend on anything
+//! but the state of the set, exporting the same state twice produces the same bytes.  Timestamps,
+//! the version counter and the values of stable IDs are not part of the bundle.
+
+use collation::Collation;
+use comparator::{Cast, Comparator, Direction, Key, TupleOrder};
+use configuration::{BackendKind, Configuration};
+use etf;
+use sorted_set::SortedSet;
+use supported_term::SupportedTerm;
+use supported_term::SupportedTerm::{Atom, Bitstring, Integer, List, Tuple};
+
+const TAG: &str = "sorted_set_repro";
+const FORMAT_VERSION: i64 = 1;
+
+#[derive(Debug, PartialEq)]
+pub enum ReproError {
+    /// The binary is not a repro bundle this version understands.
+    Invalid,
+}
+
+pub fn export(set: &SortedSet) -> Vec<u8> {
+    let configuration = set.configuration();
+
+    let max_elements = match configuration.max_elements {
+        Some(max_elements) => Integer(max_elements as i64),
+        None => atom("nil"),
+    };
+    let backend = match configuration.backend {
+        BackendKind::Buckets => atom("buckets"),
+        BackendKind::BTree => atom("btree"),
+        BackendKind::Bitset { universe } => Tuple(vec![atom("bitset"), Integer(universe as i64)]),
+    };
+    let collation = match configuration.collation {
+        Some(ref collation) => match collation.locale() {
+            Some(locale) => Bitstring(String::from(locale)),
+            None => atom("natural"),
+        },
+        None => atom("nil"),
+    };
+    let comparator = match configuration.comparator {
+        Some(ref comparator) => List(comparator.keys().iter().map(encode_key).collect()),
+        None => atom("nil"),
+    };
+    let tuple_order = match configuration.comparator {
+        Some(ref comparator) if comparator.tuple_order() == TupleOrder::Lexicographic => {
+            atom("lexicographic")
+        }
+        _ => atom("erlang"),
+    };
+    let meta = match set.meta() {
+        Some(meta) => Tuple(vec![atom("meta"), meta.clone()]),
+        None => List(Vec::new()),
+    };
+
+    let bundle = Tuple(vec![
+        atom(TAG),
+        Integer(FORMAT_VERSION),
+        Tuple(vec![
+            Integer(configuration.max_bucket_size as i64),
+            Integer(configuration.initial_set_capacity as i64),
+            max_elements,
+            backend,
+            boolean(configuration.stable_ids),
+            collation,
+            comparator,
+            tuple_order,
+        ]),
+        Tuple(vec![boolean(set.is_frozen()), meta]),
+        List(
+            set.layout()
+                .into_iter()
+                .map(|bucket| List(bucket.to_vec()))
+                .collect(),
+        ),
+    ]);
+
+    etf::encode(&bundle)
+}
+
+pub fn import(bytes: &[u8]) -> Result<SortedSet, ReproError> {
+    let bundle = match etf::decode(bytes) {
+        Ok(Tuple(fields)) => fields,
+        _ => return Err(ReproError::Invalid),
+    };
+
+    let mut fields = bundle.into_iter();
+    match (fields.next(), fields.next()) {
+        (Some(Atom(ref tag)), Some(Integer(FORMAT_VERSION))) if tag == TAG => {}
+        _ => return Err(ReproError::Invalid),
+    }
+
+    let (configuration, state, layout) = match (fields.next(), fields.next(), fields.next()) {
+        (Some(Tuple(configuration)), Some(Tuple(state)), Some(List(layout))) => {
+            (configuration, state, layout)
+        }
+        _ => return Err(ReproError::Invalid),
+    };
+
+    if fields.next().is_some() {
+        return Err(ReproError::Invalid);
+    }
+
+    let configuration = decode_configuration(configuration)?;
+
+    let mut buckets = Vec::with_capacity(layout.len());
+    for bucket in layout {
+        match bucket {
+            List(items) => buckets.push(items),
+            _ => return Err(ReproError::Invalid),
+        }
+    }
+
+    let mut set = SortedSet::from_layout(configuration, buckets);
+
+    let mut state = state.into_iter();
+    match (state.next(), state.next(), state.next()) {
+        (Some(frozen), Some(meta), None) => {
+            match meta {
+                Tuple(mut pair) => match (pair.pop(), pair.pop()) {
+                    (Some(meta), Some(Atom(ref tag))) if tag == "meta" && pair.is_empty() => {
+                        set.put_meta(meta)
+                    }
+                    _ => return Err(ReproError::Invalid),
+                },
+                List(ref empty) if empty.is_empty() => {}
+                _ => return Err(ReproError::Invalid),
+            }
+
+            if decode_boolean(frozen)? {
+                set.freeze();
+            }
+        }
+        _ => return Err(ReproError::Invalid),
+    }
+
+    Ok(set)
+}
+
+fn decode_configuration(fields: Vec<SupportedTerm>) -> Result<Configuration, ReproError> {
+    let mut fields = fields.into_iter();
+
+    let max_bucket_size = match fields.next() {
+        Some(Integer(size)) if size > 0 => size as usize,
+        _ => return Err(ReproError::Invalid),
+    };
+    let initial_set_capacity = match fields.next() {
+        Some(Integer(capacity)) if capacity >= 0 => capacity as usize,
+        _ => return Err(ReproError::Invalid),
+    };
+    let max_elements = match fields.next() {
+        Some(Integer(max_elements)) if max_elements >= 0 => Some(max_elements as usize),
+        Some(Atom(ref name)) if name == "nil" => None,
+        _ => return Err(ReproError::Invalid),
+    };
+    let backend = match fields.next() {
+        Some(Atom(ref name)) if name == "buckets" => BackendKind::Buckets,
+        Some(Atom(ref name)) if name == "btree" => BackendKind::BTree,
+        Some(Tuple(ref elements)) => match elements.as_slice() {
+            [Atom(name), Integer(universe)]
+                if name == "bitset" && *universe > 0 && *universe <= i64::from(u32::MAX) =>
+            {
+                BackendKind::Bitset {
+                    universe: *universe as u32,
+                }
+            }
+            _ => return Err(ReproError::Invalid),
+        },
+        _ => return Err(ReproError::Invalid),
+    };
+    let stable_ids = match fields.next() {
+        Some(stable_ids) => decode_boolean(stable_ids)?,
+        None => return Err(ReproError::Invalid),
+    };
+    let collation = match fields.next() {
+        Some(Bitstring(ref locale)) => match Collation::new(locale) {
+            Some(collation) => Some(collation),
+            None => return Err(ReproError::Invalid),
+        },
+        Some(Atom(ref name)) if name == "natural" => Some(Collation::natural()),
+        Some(Atom(ref name)) if name == "nil" => None,
+        _ => return Err(ReproError::Invalid),
+    };
+    let keys = match fields.next() {
+        Some(List(keys)) => decode_comparator_keys(keys)?,
+        Some(Atom(ref name)) if name == "nil" => Vec::new(),
+        _ => return Err(ReproError::Invalid),
+    };
+    // Bundles exported before tuple orders existed end with the comparator
+    let tuple_order = match fields.next() {
+        Some(Atom(ref name)) if name == "lexicographic" => TupleOrder::Lexicographic,
+        Some(Atom(ref name)) if name == "erlang" => TupleOrder::Erlang,
+        None => TupleOrder::Erlang,
+        _ => return Err(ReproError::Invalid),
+    };
+    let comparator = Comparator::compile(keys, tuple_order);
+
+    if fields.next().is_some() {
+        return Err(ReproError::Invalid);
+    }
+
+    let mut builder = Configuration::builder()
+        .max_bucket_size(max_bucket_size)
+        .initial_set_capacity(initial_set_capacity)
+        .backend(backend)
+        .stable_ids(stable_ids);
+
+    if let Some(max_elements) = max_elements {
+        builder = builder.max_elements(max_elements);
+    }
+
+    if let Some(collation) = collation {
+        builder = builder.collation(collation);
+    }
+
+    if let Some(comparator) = comparator {
+        builder = builder.comparator(comparator);
+    }
+
+    // Bundles are replayed by other processes, so the owner of the set is not part of them and
+    // neither are the options that have no effect on the items
+    builder.build().map_err(|_| ReproError::Invalid)
+}
+
+/// Keys that only name an element are written as `{index, direction}` like the `:comparator`
+/// option, other keys as `{path, direction, transforms}`.
+fn encode_key(key: &Key) -> SupportedTerm {
+    let direction = match key.direction {
+        Direction::Ascending => atom("asc"),
+        Direction::Descending => atom("desc"),
+    };
+
+    if key.is_element() {
+        return Tuple(vec![Integer(key.path[0] as i64), direction]);
+    }
+
+    let path = List(
+        key.path
+            .iter()
+            .map(|&index| Integer(index as i64))
+            .collect(),
+    );
+    let mut transforms = Vec::new();
+
+    match key.cast {
+        Some(Cast::Integer) => transforms.push(Tuple(vec![atom("cast"), atom("integer")])),
+        Some(Cast::String) => transforms.push(Tuple(vec![atom("cast"), atom("string")])),
+        None => {}
+    }
+
+    if key.fold_case {
+        transforms.push(atom("fold_case"));
+    }
+
+    Tuple(vec![path, direction, List(transforms)])
+}
+
+fn decode_comparator_keys(keys: Vec<SupportedTerm>) -> Result<Vec<Key>, ReproError> {
+    let mut compiled = Vec::with_capacity(keys.len());
+
+    for key in keys {
+        let key = match key {
+            Tuple(key) => key,
+            _ => return Err(ReproError::Invalid),
+        };
+
+        let (path, direction, transforms) = match key.as_slice() {
+            [Integer(index), Atom(direction)] if *index >= 0 => {
+                (vec![*index as usize], direction, &[][..])
+            }
+            [List(path), Atom(direction), List(transforms)] if !path.is_empty() => {
+                let mut indexes = Vec::with_capacity(path.len());
+
+                for index in path {
+                    match *index {
+                        Integer(index) if index >= 0 => indexes.push(index as usize),
+                        _ => return Err(ReproError::Invalid),
+                    }
+                }
+
+                (indexes, direction, transforms.as_slice())
+            }
+            _ => return Err(ReproError::Invalid),
+        };
+
+        let direction = match direction.as_str() {
+            "asc" => Direction::Ascending,
+            "desc" => Direction::Descending,
+            _ => return Err(ReproError::Invalid),
+        };
+
+        let mut key = Key {
+            path,
+            direction,
+            cast: None,
+            fold_case: false,
+        };
+
+        for transform in transforms {
+            match transform {
+                Atom(name) if name == "fold_case" => key.fold_case = true,
+                Tuple(cast) => match cast.as_slice() {
+                    [Atom(name), Atom(to)] if name == "cast" && to == "integer" => {
+                        key.cast = Some(Cast::Integer)
+                    }
+                    [Atom(name), Atom(to)] if name == "cast" && to == "string" => {
+                        key.cast = Some(Cast::String)
+                    }
+                    _ => return Err(ReproError::Invalid),
+                },
+                _ => return Err(ReproError::Invalid),
+            }
+        }
+
+        compiled.push(key);
+    }
+
+    Ok(compiled)
+}
+
+fn atom(name: &str) -> SupportedTerm {
+    Atom(String::from(name))
+}
+
+fn boolean(value: bool) -> SupportedTerm {
+    atom(if value { "true" } else { "false" })
+}
+
+fn decode_boolean(term: SupportedTerm) -> Result<bool, ReproError> {
+    match term {
+        Atom(ref name) if name == "true" => Ok(true),
+        Atom(ref name) if name == "false" => Ok(false),
+        _ => Err(ReproError::Invalid),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use collation::Collation;
+    use comparator::{Cast, Comparator, Direction, Key, TupleOrder};
+    use configuration::{BackendKind, Configuration};
+    use repro::{export, import, ReproError};
+    use sorted_set::SortedSet;
+    use supported_term::SupportedTerm::{Bitstring, Integer, Tuple};
+
+    #[test]
+    fn test_round_trip_keeps_layout() {
+        let configuration = Configuration {
+            max_bucket_size: 4,
+            max_elements: Some(100),
+            ..Configuration::default()
+        };
+        let mut set = SortedSet::new(configuration);
+        for i in 0..20 {
+            set.add(Integer(i * 3 % 20));
+        }
+        set.put_meta(Bitstring(String::from("shard 3")));
+        set.freeze();
+
+        let bytes = export(&set);
+        let imported = import(&bytes).unwrap();
+
+        assert_eq!(imported.layout(), set.layout());
+        assert_eq!(imported.configuration().max_elements, Some(100));
+        assert_eq!(imported.meta(), Some(&Bitstring(String::from("shard 3"))));
+        assert!(imported.is_frozen());
+        assert_eq!(export(&imported), bytes);
+    }
+
+    #[test]
+    fn test_round_trip_btree() {
+        let configuration = Configuration {
+            backend: BackendKind::BTree,
+            stable_ids: true,
+            ..Configuration::default()
+        };
+        let mut set = SortedSet::new(configuration);
+        for i in 0..500 {
+            set.add(Integer(i));
+        }
+
+        let imported = import(&export(&set)).unwrap();
+
+        assert_eq!(imported.to_vec(), set.to_vec());
+        assert_eq!(imported.configuration().backend, BackendKind::BTree);
+        assert!(imported.has_stable_ids());
+    }
+
+    #[test]
+    fn test_round_trip_bitset() {
+        let configuration = Configuration {
+            backend: BackendKind::Bitset { universe: 1_024 },
+            ..Configuration::default()
+        };
+        let mut set = SortedSet::new(configuration);
+        for i in 0..300 {
+            set.add(Integer(i * 3));
+        }
+
+        let imported = import(&export(&set)).unwrap();
+
+        assert_eq!(imported.to_vec(), set.to_vec());
+        assert_eq!(
+            imported.configuration().backend,
+            BackendKind::Bitset { universe: 1_024 }
+        );
+    }
+
+    #[test]
+    fn test_round_trip_collation() {
+        let configuration = Configuration {
+            collation: Some(Collation::natural()),
+            ..Configuration::default()
+        };
+        let mut set = SortedSet::new(configuration);
+        for i in 0..12 {
+            set.add(Bitstring(format!("channel-{}", i)));
+        }
+
+        let imported = import(&export(&set)).unwrap();
+
+        assert_eq!(
+            imported.configuration().collation,
+            Some(Collation::natural())
+        );
+        assert_eq!(imported.to_vec(), set.to_vec());
+        assert_eq!(
+            imported.find_index(&Bitstring(String::from("channel-10"))),
+            Some(10)
+        );
+    }
+
+    #[test]
+    fn test_round_trip_comparator() {
+        let configuration = Configuration {
+            comparator: Comparator::new(
+                vec![(1, Direction::Descending), (0, Direction::Ascending)],
+                TupleOrder::Lexicographic,
+            ),
+            ..Configuration::default()
+        };
+        let mut set = SortedSet::new(configuration);
+        for i in 0..12 {
+            set.add(Tuple(vec![Integer(i), Integer(i % 3)]));
+        }
+
+        let imported = import(&export(&set)).unwrap();
+
+        assert_eq!(
+            imported.configuration().comparator,
+            set.configuration().comparator
+        );
+        assert_eq!(imported.to_vec(), set.to_vec());
+        assert_eq!(
+            imported.find_index(&Tuple(vec![Integer(2), Integer(2)])),
+            Some(0)
+        );
+    }
+
+    #[test]
+    fn test_round_trip_compiled_keys() {
+        let configuration = Configuration {
+            comparator: Comparator::compile(
+                vec![
+                    Key {
+                        path: vec![1, 0],
+                        direction: Direction::Descending,
+                        cast: Some(Cast::Integer),
+                        fold_case: true,
+                    },
+                    Key::element(0, Direction::Ascending),
+                ],
+                TupleOrder::Erlang,
+            ),
+            ..Configuration::default()
+        };
+        let set: SortedSet = SortedSet::new(configuration);
+
+        let imported = import(&export(&set)).unwrap();
+
+        assert_eq!(
+            imported.configuration().comparator,
+            set.configuration().comparator
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_terms() {
+        assert_eq!(import(&[131, 97, 1]).err(), Some(ReproError::Invalid));
+        assert_eq!(import(&[]).err(), Some(ReproError::Invalid));
+    }
+}
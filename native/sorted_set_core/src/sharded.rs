@@ -0,0 +1,215 @@
+//! A set split by value range into shards that are locked separately.
+//!
+//! Very large sets see enough writes that a single lock becomes the bottleneck.  Splitting the
+//! set at fixed pivots into shards that each hold a range of the items lets writes to different
+//! ranges proceed in parallel.  Every item belongs to exactly one shard and the shards are in
+//! order, so reads by index over the whole set are the shards one after the other and need no
+//! merge.
+//!
+//! `Shards` only routes items, holding and locking the shards is up to the caller.  The functions
+//! reading across shards take the shards in order.
+
+use sorted_set::SortedSet;
+use supported_term::SupportedTerm;
+use Configuration;
+use Item;
+
+/// Routes items to the shard holding their range.
+#[derive(Debug)]
+pub struct Shards<T: Item = SupportedTerm> {
+    configuration: Configuration,
+
+    /// Smallest item of every shard but the first, prepared for the configuration and strictly
+    /// increasing.
+    pivots: Vec<T>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ShardError {
+    /// The pivots are not strictly increasing in the order of the configuration.
+    UnorderedPivots,
+}
+
+impl<T: Item> Shards<T> {
+    /// Splits the items at the pivots, `n` pivots make `n + 1` shards.  Every shard is a set with
+    /// the configuration, so limits like `max_elements` apply to each shard on its own.
+    pub fn new(configuration: Configuration, pivots: Vec<T>) -> Result<Shards<T>, ShardError> {
+        let pivots: Vec<T> = pivots
+            .into_iter()
+            .map(|pivot| configuration.prepare(pivot))
+            .collect();
+
+        if pivots.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err(ShardError::UnorderedPivots);
+        }
+
+        Ok(Shards {
+            configuration,
+            pivots,
+        })
+    }
+
+    /// Number of shards.
+    pub fn count(&self) -> usize {
+        self.pivots.len() + 1
+    }
+
+    /// Creates the empty shards, in order.
+    pub fn create(&self) -> Vec<SortedSet<T>> {
+        (0..self.count())
+            .map(|_| SortedSet::empty(self.configuration.clone()))
+            .collect()
+    }
+
+    /// Index of the shard that holds `item`, or would hold it.
+    pub fn shard_of(&self, item: &T) -> usize {
+        if self.configuration.prepares_items() {
+            let item = self.configuration.prepare(item.clone());
+            self.pivots.partition_point(|pivot| *pivot <= item)
+        } else {
+            self.pivots.partition_point(|pivot| pivot <= item)
+        }
+    }
+}
+
+/// Total number of items held by the shards.
+pub fn size<T: Item>(shards: &[&SortedSet<T>]) -> usize {
+    shards.iter().map(|shard| shard.size()).sum()
+}
+
+/// Returns the item at `index` of the shards.
+pub fn at<'a, T: Item>(shards: &[&'a SortedSet<T>], index: usize) -> Option<&'a T> {
+    let mut index = index;
+
+    for shard in shards {
+        if index < shard.size() {
+            return shard.at(index);
+        }
+
+        index -= shard.size();
+    }
+
+    None
+}
+
+/// Returns up to `amount` items of the shards starting at `start`.
+pub fn slice<T: Item>(shards: &[&SortedSet<T>], start: usize, amount: usize) -> Vec<T> {
+    let mut start = start;
+    let mut amount = amount;
+    let mut items = Vec::with_capacity(amount.min(size(shards).saturating_sub(start)));
+
+    for shard in shards {
+        if amount == 0 {
+            break;
+        }
+
+        if start >= shard.size() {
+            start -= shard.size();
+            continue;
+        }
+
+        let part = shard.slice(start, amount);
+        amount -= part.len();
+        items.extend(part);
+        start = 0;
+    }
+
+    items
+}
+
+/// Returns the index of `item` across the shards, `shards` are the shards up to and including
+/// the one holding the item.
+pub fn find_index<T: Item>(shards: &[&SortedSet<T>], item: &T) -> Option<usize> {
+    let (last, before) = shards.split_last()?;
+
+    last.find_index(item).map(|index| index + size(before))
+}
+
+#[cfg(test)]
+mod tests {
+    use comparator::{Comparator, Direction, TupleOrder};
+    use sharded::{self, ShardError, Shards};
+    use sorted_set::SortedSet;
+    use supported_term::SupportedTerm::{self, Integer, Tuple};
+    use Configuration;
+
+    fn integers(items: &[SupportedTerm]) -> Vec<i64> {
+        items
+            .iter()
+            .map(|item| match *item {
+                Integer(value) => value,
+                _ => panic!("Unexpected item"),
+            })
+            .collect()
+    }
+
+    fn fill(shards: &Shards, items: &[i64]) -> Vec<SortedSet> {
+        let mut sets = shards.create();
+
+        for &item in items {
+            let item = Integer(item);
+            sets[shards.shard_of(&item)].add(item);
+        }
+
+        sets
+    }
+
+    #[test]
+    fn test_routes_by_range() {
+        let shards = Shards::new(Configuration::default(), vec![Integer(10), Integer(20)]).unwrap();
+
+        assert_eq!(shards.count(), 3);
+        assert_eq!(shards.shard_of(&Integer(-5)), 0);
+        assert_eq!(shards.shard_of(&Integer(9)), 0);
+        assert_eq!(shards.shard_of(&Integer(10)), 1);
+        assert_eq!(shards.shard_of(&Integer(19)), 1);
+        assert_eq!(shards.shard_of(&Integer(20)), 2);
+        assert_eq!(shards.shard_of(&Tuple(vec![])), 2);
+
+        assert_eq!(
+            Shards::new(Configuration::default(), vec![Integer(20), Integer(10)]).unwrap_err(),
+            ShardError::UnorderedPivots
+        );
+        assert_eq!(
+            Shards::new(Configuration::default(), vec![Integer(1), Integer(1)]).unwrap_err(),
+            ShardError::UnorderedPivots
+        );
+    }
+
+    #[test]
+    fn test_reads_across_shards() {
+        let shards = Shards::new(Configuration::default(), vec![Integer(10), Integer(20)]).unwrap();
+        let sets = fill(&shards, &[25, 3, 12, 1, 30, 15, 7]);
+        let sets: Vec<&SortedSet> = sets.iter().collect();
+
+        assert_eq!(sharded::size(&sets), 7);
+        assert_eq!(sharded::at(&sets, 0), Some(&Integer(1)));
+        assert_eq!(sharded::at(&sets, 3), Some(&Integer(12)));
+        assert_eq!(sharded::at(&sets, 6), Some(&Integer(30)));
+        assert_eq!(sharded::at(&sets, 7), None);
+
+        assert_eq!(integers(&sharded::slice(&sets, 2, 3)), vec![7, 12, 15]);
+        assert_eq!(integers(&sharded::slice(&sets, 5, 10)), vec![25, 30]);
+        assert!(sharded::slice(&sets, 7, 10).is_empty());
+
+        assert_eq!(sharded::find_index(&sets[..2], &Integer(15)), Some(4));
+        assert_eq!(sharded::find_index(&sets[..3], &Integer(30)), Some(6));
+        assert_eq!(sharded::find_index(&sets[..2], &Integer(14)), None);
+    }
+
+    #[test]
+    fn test_routes_in_the_order_of_the_configuration() {
+        let configuration = Configuration::builder()
+            .comparator(
+                Comparator::new(vec![(0, Direction::Descending)], TupleOrder::Erlang).unwrap(),
+            )
+            .build()
+            .unwrap();
+        let pivot = Tuple(vec![Integer(10)]);
+        let shards = Shards::new(configuration, vec![pivot]).unwrap();
+
+        assert_eq!(shards.shard_of(&Tuple(vec![Integer(50)])), 0);
+        assert_eq!(shards.shard_of(&Tuple(vec![Integer(10)])), 1);
+        assert_eq!(shards.shard_of(&Tuple(vec![Integer(5)])), 1);
+    }
+}
@@ -0,0 +1,184 @@
+//! HyperLogLog sketches estimating how many distinct items sets hold.
+//!
+//! A sketch keeps one small register per bucket of hash values, the registers of several sketches
+//! combine with a maximum, so the size of the union of any number of sets can be estimated from
+//! their sketches without looking at a single item.  With 4096 registers the estimate is usually
+//! within a couple percent of the true size.
+//!
+//! Sketches can only grow.  Removing an item marks the sketch stale and a stale sketch is rebuilt
+//! from the items of its set whenever it is read, see `SortedSet::sketch`.
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::mem;
+use Item;
+
+/// Number of bits of the hash selecting the register.
+const PRECISION: u32 = 12;
+
+const REGISTERS: usize = 1 << PRECISION;
+
+#[derive(Clone, Debug)]
+pub struct Sketch {
+    registers: Vec<u8>,
+    stale: bool,
+}
+
+impl Sketch {
+    pub fn new() -> Sketch {
+        Sketch {
+            registers: vec![0; REGISTERS],
+            stale: false,
+        }
+    }
+
+    /// Builds a sketch of the given items.
+    pub fn of<'a, T: Item + 'a, I: IntoIterator<Item = &'a T>>(items: I) -> Sketch {
+        let mut sketch = Sketch::new();
+
+        for item in items {
+            sketch.insert(item);
+        }
+
+        sketch
+    }
+
+    /// Hashes an item, items that are equal in any set hash the same.
+    pub fn hash<T: Item>(item: &T) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        item.hash_item(&mut hasher);
+        hasher.finish()
+    }
+
+    pub fn insert<T: Item>(&mut self, item: &T) {
+        self.insert_hash(Sketch::hash(item));
+    }
+
+    pub fn insert_hash(&mut self, hash: u64) {
+        let index = (hash >> (64 - PRECISION)) as usize;
+
+        // The marker bit caps the run of zeros for hashes whose remaining bits are all zero
+        let rest = (hash << PRECISION) | (1 << (PRECISION - 1));
+        let rank = rest.leading_zeros() as u8 + 1;
+
+        if rank > self.registers[index] {
+            self.registers[index] = rank;
+        }
+    }
+
+    /// Folds the registers of `other` into this sketch, which then describes the union of both.
+    pub fn merge(&mut self, other: &Sketch) {
+        for (register, &theirs) in self.registers.iter_mut().zip(other.registers.iter()) {
+            if theirs > *register {
+                *register = theirs;
+            }
+        }
+    }
+
+    /// Estimated number of distinct items inserted into the sketch.
+    pub fn estimate(&self) -> u64 {
+        let m = REGISTERS as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+
+        let sum: f64 = self
+            .registers
+            .iter()
+            .map(|&register| 1.0 / f64::from(1u32 << register))
+            .sum();
+        let raw = alpha * m * m / sum;
+
+        // Small cardinalities are estimated far better by the number of untouched registers
+        let zeros = self
+            .registers
+            .iter()
+            .filter(|&&register| register == 0)
+            .count();
+        let estimate = if raw <= 2.5 * m && zeros > 0 {
+            m * (m / zeros as f64).ln()
+        } else {
+            raw
+        };
+
+        estimate.round() as u64
+    }
+
+    /// Forgets every item, leaving the sketch as if it was new.
+    pub fn clear(&mut self) {
+        for register in self.registers.iter_mut() {
+            *register = 0;
+        }
+        self.stale = false;
+    }
+
+    /// Marks the sketch as holding items that have since been removed from its set.
+    pub fn mark_stale(&mut self) {
+        self.stale = true;
+    }
+
+    pub fn is_stale(&self) -> bool {
+        self.stale
+    }
+
+    pub fn memory(&self) -> usize {
+        mem::size_of::<Sketch>() + self.registers.capacity()
+    }
+}
+
+impl Default for Sketch {
+    fn default() -> Sketch {
+        Sketch::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use collation::Collation;
+    use configuration::Configuration;
+    use sketch::Sketch;
+    use supported_term::SupportedTerm::{Bitstring, Integer, List, Tuple};
+
+    fn within(estimate: u64, actual: u64, percent: u64) -> bool {
+        let error = estimate.abs_diff(actual);
+        error * 100 <= actual * percent
+    }
+
+    #[test]
+    fn test_estimates_are_close() {
+        for &count in &[10u64, 1_000, 100_000] {
+            let items: Vec<_> = (0..count as i64).map(Integer).collect();
+            let estimate = Sketch::of(items.iter()).estimate();
+
+            assert!(within(estimate, count, 5), "{} for {}", estimate, count);
+        }
+
+        assert_eq!(Sketch::new().estimate(), 0);
+    }
+
+    #[test]
+    fn test_merged_sketches_estimate_the_union() {
+        let a: Vec<_> = (0..30_000).map(Integer).collect();
+        let b: Vec<_> = (20_000..50_000).map(Integer).collect();
+
+        let mut union = Sketch::of(a.iter());
+        union.merge(&Sketch::of(b.iter()));
+
+        assert!(within(union.estimate(), 50_000, 5));
+    }
+
+    #[test]
+    fn test_hash_ignores_how_terms_are_stored() {
+        let collated = Configuration {
+            collation: Some(Collation::natural()),
+            ..Configuration::default()
+        };
+        let plain = Bitstring("channel-2".to_string());
+        let prepared = collated.prepare(plain.clone());
+
+        assert_eq!(Sketch::hash(&plain), Sketch::hash(&prepared));
+        assert_ne!(
+            Sketch::hash(&Tuple(vec![Integer(1)])),
+            Sketch::hash(&List(vec![Integer(1)]))
+        );
+        assert_ne!(Sketch::hash(&Integer(1)), Sketch::hash(&Integer(2)));
+    }
+}
@@ -0,0 +1,3988 @@
+use bitset::Bitset;
+use btree::BTree;
+use buckets::Buckets;
+use configuration::{BackendKind, Configuration};
+use metrics::BucketStats;
+use sketch::Sketch;
+use stable_ids::StableIds;
+use std::borrow::Cow;
+use std::cmp::{max, min, Ordering};
+use std::iter::{FromIterator, Peekable};
+use std::mem;
+use std::ops::{Bound, Index, RangeBounds};
+use std::slice;
+use std::time::{Instant, SystemTime};
+use std::vec;
+use supported_term::SupportedTerm;
+use AddResult;
+use AppendBucketResult;
+use BuildResult;
+use ConcatResult;
+use FindResult;
+use Item;
+use MergeResult;
+use MoveRangeResult;
+use RemoveResult;
+use RepairReport;
+
+/// Borrowing iterator over the items of a SortedSet in order.
+pub type Iter<'a, T> = Box<dyn Iterator<Item = &'a T> + 'a>;
+
+#[derive(Debug)]
+pub struct SortedSet<T: Item = SupportedTerm> {
+    configuration: Configuration,
+    backend: Backend<T>,
+    modified_at: Instant,
+    modified_at_system: SystemTime,
+    meta: Option<T>,
+    frozen: bool,
+    version: u64,
+    ids: Option<Box<StableIds<T>>>,
+    sketch: Option<Box<Sketch>>,
+}
+
+/// Storage holding the items of a SortedSet, selected by `Configuration::backend`.  The
+/// SortedSet itself takes care of limits, timestamps and metadata and leaves keeping the items
+/// in order to the backend.
+#[derive(Debug)]
+enum Backend<T> {
+    Buckets(Buckets<T>),
+    BTree(BTree<T>),
+
+    /// Moves into buckets configured like `fallback` once it is given an item outside of its
+    /// universe, `fallback` holds no buckets until then.
+    Bitset {
+        bitset: Bitset<T>,
+        fallback: Buckets<T>,
+    },
+}
+
+impl<T: Item> Backend<T> {
+    fn empty(configuration: &Configuration) -> Backend<T> {
+        match configuration.backend {
+            BackendKind::Buckets => Backend::Buckets(
+                Buckets::empty(
+                    configuration.max_bucket_size,
+                    configuration.initial_set_capacity,
+                )
+                .with_bucket_capacity(configuration.initial_bucket_capacity)
+                .with_tombstones(configuration.tombstone_ratio)
+                .with_interpolation(configuration.interpolation_search),
+            ),
+            BackendKind::BTree => Backend::BTree(BTree::new()),
+            BackendKind::Bitset { universe } => Backend::bitset(configuration, universe),
+        }
+    }
+
+    fn new(configuration: &Configuration) -> Backend<T> {
+        match configuration.backend {
+            BackendKind::Buckets => Backend::Buckets(
+                Buckets::new(
+                    configuration.max_bucket_size,
+                    configuration.initial_set_capacity,
+                )
+                .with_bucket_capacity(configuration.initial_bucket_capacity)
+                .with_tombstones(configuration.tombstone_ratio)
+                .with_interpolation(configuration.interpolation_search),
+            ),
+            BackendKind::BTree => Backend::BTree(BTree::new()),
+            BackendKind::Bitset { universe } => Backend::bitset(configuration, universe),
+        }
+    }
+
+    fn from_sorted(configuration: &Configuration, items: Vec<T>) -> Backend<T> {
+        match configuration.backend {
+            BackendKind::Buckets => Backend::Buckets(
+                Buckets::from_sorted(configuration.max_bucket_size, items)
+                    .with_bucket_capacity(configuration.initial_bucket_capacity)
+                    .with_tombstones(configuration.tombstone_ratio)
+                    .with_interpolation(configuration.interpolation_search),
+            ),
+            BackendKind::BTree => Backend::BTree(BTree::from_sorted(items)),
+            BackendKind::Bitset { universe } => {
+                let mut backend = Backend::bitset(configuration, universe);
+                backend.extend_sorted(items);
+                backend
+            }
+        }
+    }
+
+    /// A bitset for types with a dense universe, other types keep their items in buckets.
+    fn bitset(configuration: &Configuration, universe: u32) -> Backend<T> {
+        match Bitset::new(universe) {
+            Some(bitset) => Backend::Bitset {
+                bitset,
+                fallback: Buckets::empty(configuration.max_bucket_size, 0)
+                    .with_bucket_capacity(configuration.initial_bucket_capacity)
+                    .with_tombstones(configuration.tombstone_ratio)
+                    .with_interpolation(configuration.interpolation_search),
+            },
+            None => Backend::Buckets(
+                Buckets::new(
+                    configuration.max_bucket_size,
+                    configuration.initial_set_capacity,
+                )
+                .with_bucket_capacity(configuration.initial_bucket_capacity)
+                .with_tombstones(configuration.tombstone_ratio)
+                .with_interpolation(configuration.interpolation_search),
+            ),
+        }
+    }
+
+    /// Moves the items of a bitset into buckets if any of the items about to be added is outside
+    /// of its universe, the other backends hold any item.
+    fn admit(&mut self, items: &[T]) {
+        let buckets = match *self {
+            Backend::Bitset {
+                ref mut bitset,
+                ref fallback,
+            } if items.iter().any(|item| !bitset.fits(item)) => {
+                fallback.sorted_like(bitset.drain())
+            }
+            _ => return,
+        };
+
+        *self = Backend::Buckets(buckets);
+    }
+
+    fn len(&self) -> usize {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.len(),
+            Backend::BTree(ref tree) => tree.len(),
+            Backend::Bitset { ref bitset, .. } => bitset.len(),
+        }
+    }
+
+    fn add(&mut self, item: T) -> AddResult {
+        self.admit(slice::from_ref(&item));
+
+        match *self {
+            Backend::Buckets(ref mut buckets) => buckets.add(item),
+            Backend::BTree(ref mut tree) => tree.add(item),
+            Backend::Bitset { ref mut bitset, .. } => bitset.add(item),
+        }
+    }
+
+    fn add_with_hint(&mut self, item: T, hint: usize) -> AddResult {
+        self.admit(slice::from_ref(&item));
+
+        match *self {
+            Backend::Buckets(ref mut buckets) => buckets.add_with_hint(item, hint),
+            // Descending the tree is already logarithmic, there is nothing for a hint to save
+            Backend::BTree(ref mut tree) => tree.add(item),
+            Backend::Bitset { ref mut bitset, .. } => bitset.add(item),
+        }
+    }
+
+    fn remove(&mut self, item: &T) -> RemoveResult {
+        match *self {
+            Backend::Buckets(ref mut buckets) => buckets.remove(item),
+            Backend::BTree(ref mut tree) => tree.remove(item),
+            Backend::Bitset { ref mut bitset, .. } => bitset.remove(item),
+        }
+    }
+
+    /// Removes the items at the given ascending, distinct and in bounds indexes.  Buckets remove
+    /// them bucket by bucket, the other backends look the items up and remove them one by one.
+    fn remove_at_many(&mut self, indexes: &[usize]) -> Vec<T> {
+        if let Backend::Buckets(ref mut buckets) = *self {
+            return buckets.remove_at_many(indexes);
+        }
+
+        let items: Vec<T> = indexes
+            .iter()
+            .filter_map(|&index| self.at(index).cloned())
+            .collect();
+
+        for item in items.iter() {
+            self.remove(item);
+        }
+
+        items
+    }
+
+    fn contains(&self, item: &T) -> bool {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.contains(item),
+            Backend::BTree(ref tree) => tree.contains(item),
+            Backend::Bitset { ref bitset, .. } => bitset.contains(item),
+        }
+    }
+
+    fn find_index(&self, item: &T) -> Option<usize> {
+        match *self {
+            Backend::Buckets(ref buckets) => match buckets.find_index(item) {
+                FindResult::Found { idx, .. } => Some(idx),
+                FindResult::NotFound => None,
+            },
+            Backend::BTree(ref tree) => tree.find_index(item),
+            Backend::Bitset { ref bitset, .. } => bitset.find_index(item),
+        }
+    }
+
+    fn rank(&self, item: &T) -> usize {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.rank(item),
+            Backend::BTree(ref tree) => tree.rank(item),
+            Backend::Bitset { ref bitset, .. } => bitset.rank(item),
+        }
+    }
+
+    fn search(&self, item: &T) -> Result<usize, usize> {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.search(item),
+            Backend::BTree(_) | Backend::Bitset { .. } => match self.find_index(item) {
+                Some(idx) => Ok(idx),
+                None => Err(self.rank(item)),
+            },
+        }
+    }
+
+    fn floor(&self, item: &T) -> Option<&T> {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.floor(item),
+            Backend::BTree(_) | Backend::Bitset { .. } => {
+                let past = match self.find_index(item) {
+                    Some(idx) => idx + 1,
+                    None => self.rank(item),
+                };
+
+                past.checked_sub(1).and_then(|idx| self.at(idx))
+            }
+        }
+    }
+
+    fn ceiling(&self, item: &T) -> Option<&T> {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.ceiling(item),
+            Backend::BTree(_) | Backend::Bitset { .. } => self.at(self.rank(item)),
+        }
+    }
+
+    fn at(&self, index: usize) -> Option<&T> {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.at(index),
+            Backend::BTree(ref tree) => tree.at(index),
+            Backend::Bitset { ref bitset, .. } => bitset.at(index),
+        }
+    }
+
+    fn at_many(&self, indexes: &[usize]) -> Vec<Option<&T>> {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.at_many(indexes),
+            Backend::BTree(_) | Backend::Bitset { .. } => {
+                indexes.iter().map(|&index| self.at(index)).collect()
+            }
+        }
+    }
+
+    fn slice(&self, index: usize, amount: usize) -> Vec<T> {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.slice(index, amount),
+            Backend::BTree(ref tree) => tree.iter_from(index).take(amount).cloned().collect(),
+            Backend::Bitset { ref bitset, .. } => {
+                bitset.iter_from(index).take(amount).cloned().collect()
+            }
+        }
+    }
+
+    fn contains_sorted(&self, items: &[&T]) -> Vec<bool> {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.contains_sorted(items),
+            Backend::BTree(_) | Backend::Bitset { .. } => {
+                items.iter().map(|item| self.contains(item)).collect()
+            }
+        }
+    }
+
+    fn multi_slice(&self, windows: &[(usize, usize)]) -> Vec<Vec<T>> {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.multi_slice(windows),
+            Backend::BTree(_) | Backend::Bitset { .. } => windows
+                .iter()
+                .map(|&(start, amount)| self.slice(start, amount))
+                .collect(),
+        }
+    }
+
+    fn iter(&self) -> Iter<'_, T> {
+        match *self {
+            Backend::Buckets(ref buckets) => Box::new(buckets.iter()),
+            Backend::BTree(ref tree) => Box::new(tree.iter()),
+            Backend::Bitset { ref bitset, .. } => Box::new(bitset.iter()),
+        }
+    }
+
+    fn iter_rev(&self) -> Iter<'_, T> {
+        match *self {
+            Backend::Buckets(ref buckets) => Box::new(buckets.iter_rev()),
+            Backend::BTree(ref tree) => {
+                let items: Vec<&T> = tree.iter().collect();
+                Box::new(items.into_iter().rev())
+            }
+            Backend::Bitset { ref bitset, .. } => Box::new(bitset.iter_rev()),
+        }
+    }
+
+    fn slice_desc(&self, start_from_end: usize, amount: usize) -> Vec<T> {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets
+                .iter_rev_from(start_from_end)
+                .take(amount)
+                .cloned()
+                .collect(),
+            _ => {
+                // The other backends seek by index cheaply, the window is sliced forward instead
+                let end = self.len().saturating_sub(start_from_end);
+                let start = end.saturating_sub(amount);
+                let mut result = self.slice(start, end - start);
+                result.reverse();
+                result
+            }
+        }
+    }
+
+    fn iter_from(&self, index: usize) -> Iter<'_, T> {
+        match *self {
+            Backend::Buckets(ref buckets) => Box::new(buckets.iter_from(index)),
+            Backend::BTree(ref tree) => Box::new(tree.iter_from(index)),
+            Backend::Bitset { ref bitset, .. } => Box::new(bitset.iter_from(index)),
+        }
+    }
+
+    fn drain(&mut self) -> Vec<T> {
+        match *self {
+            Backend::Buckets(ref mut buckets) => buckets.drain(),
+            Backend::BTree(ref mut tree) => tree.drain(),
+            Backend::Bitset { ref mut bitset, .. } => bitset.drain(),
+        }
+    }
+
+    fn extend_sorted(&mut self, items: Vec<T>) {
+        self.admit(&items);
+
+        match *self {
+            Backend::Buckets(ref mut buckets) => buckets.extend_sorted(items),
+            Backend::BTree(ref mut tree) => tree.extend_sorted(items),
+            Backend::Bitset { ref mut bitset, .. } => bitset.extend_sorted(items),
+        }
+    }
+
+    /// Moves every item of `other` onto the end of this backend, the buckets of a bucketed
+    /// backend are moved as-is when both sides are bucketed.
+    fn append(&mut self, other: &mut Backend<T>) {
+        match (self, other) {
+            (&mut Backend::Buckets(ref mut buckets), &mut Backend::Buckets(ref mut other)) => {
+                buckets.append(other)
+            }
+            (backend, other) => {
+                let items = other.drain();
+                backend.extend_sorted(items);
+            }
+        }
+    }
+
+    /// Splits off the items greater than or equal to `pivot`, the items less than the pivot are
+    /// kept.
+    fn split_off(&mut self, pivot: &T) -> Backend<T> {
+        match *self {
+            Backend::Buckets(ref mut buckets) => Backend::Buckets(buckets.split_off(pivot)),
+            Backend::BTree(ref mut tree) => {
+                let mut items = tree.drain();
+                let at = match items.binary_search(pivot) {
+                    Ok(idx) => idx,
+                    Err(idx) => idx,
+                };
+                let upper = items.split_off(at);
+
+                *tree = BTree::from_sorted(items);
+                Backend::BTree(BTree::from_sorted(upper))
+            }
+            Backend::Bitset {
+                ref mut bitset,
+                ref fallback,
+            } => Backend::Bitset {
+                bitset: bitset.split_off(pivot),
+                fallback: fallback.empty_like(),
+            },
+        }
+    }
+
+    fn memory(&self) -> usize {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.memory(),
+            Backend::BTree(ref tree) => tree.memory(),
+            Backend::Bitset { ref bitset, .. } => bitset.memory(),
+        }
+    }
+
+    fn layout(&self) -> Vec<&[T]> {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.layout(),
+            Backend::BTree(ref tree) => tree.layout(),
+            Backend::Bitset { ref bitset, .. } => bitset.layout(),
+        }
+    }
+
+    fn bucket_stats(&self) -> BucketStats {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.bucket_stats(),
+            Backend::BTree(ref tree) => tree.bucket_stats(),
+            Backend::Bitset { ref bitset, .. } => bitset.bucket_stats(),
+        }
+    }
+
+    fn repair(&mut self) -> RepairReport {
+        match *self {
+            Backend::Buckets(ref mut buckets) => buckets.repair(),
+            Backend::BTree(ref mut tree) => tree.repair(),
+            Backend::Bitset { ref mut bitset, .. } => bitset.repair(),
+        }
+    }
+
+    fn compact(&mut self) -> usize {
+        match *self {
+            Backend::Buckets(ref mut buckets) => buckets.compact(),
+            Backend::BTree(_) | Backend::Bitset { .. } => 0,
+        }
+    }
+
+    fn tombstones(&self) -> usize {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.tombstones(),
+            Backend::BTree(_) | Backend::Bitset { .. } => 0,
+        }
+    }
+
+    fn first(&self) -> Option<&T> {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.first(),
+            Backend::BTree(ref tree) => tree.first(),
+            Backend::Bitset { ref bitset, .. } => bitset.first(),
+        }
+    }
+
+    fn last(&self) -> Option<&T> {
+        match *self {
+            Backend::Buckets(ref buckets) => buckets.last(),
+            Backend::BTree(ref tree) => tree.last(),
+            Backend::Bitset { ref bitset, .. } => bitset.last(),
+        }
+    }
+}
+
+impl<T: Item> SortedSet<T> {
+    pub fn empty(configuration: Configuration) -> SortedSet<T> {
+        if configuration.max_bucket_size < 1 {
+            panic!("SortedSet max_bucket_size must be greater than 0");
+        }
+
+        let backend = Backend::empty(&configuration);
+        SortedSet::with_backend(configuration, backend)
+    }
+
+    pub fn new(configuration: Configuration) -> SortedSet<T> {
+        if configuration.max_bucket_size < 1 {
+            panic!("SortedSet max_bucket_size must be greater than 0");
+        }
+
+        let backend = Backend::new(&configuration);
+        SortedSet::with_backend(configuration, backend)
+    }
+
+    fn with_backend(configuration: Configuration, backend: Backend<T>) -> SortedSet<T> {
+        let ids = if configuration.stable_ids {
+            Some(Box::new(StableIds::new()))
+        } else {
+            None
+        };
+
+        let sketch = if configuration.cardinality_sketch {
+            Some(Box::new(Sketch::new()))
+        } else {
+            None
+        };
+
+        SortedSet {
+            configuration,
+            backend,
+            modified_at: Instant::now(),
+            modified_at_system: SystemTime::now(),
+            meta: None,
+            frozen: false,
+            version: 0,
+            ids,
+            sketch,
+        }
+    }
+
+    /// Builds a set from items in any order, the items are sorted and deduplicated by the
+    /// ordering of the configuration.
+    pub fn from_unsorted(configuration: Configuration, items: Vec<T>) -> BuildResult<T> {
+        let mut items: Vec<T> = items
+            .into_iter()
+            .map(|item| configuration.prepare(item))
+            .collect();
+
+        items.sort();
+        items.dedup_by(|a, b| a.cmp(&b) == Ordering::Equal);
+
+        SortedSet::from_sorted(configuration, items)
+    }
+
+    /// Builds a set from items that are already sorted and free of duplicates, the items are
+    /// packed into buckets the same way the Elixir `from_proper_enumerable` packs them.  With a
+    /// collation the items have to be sorted by the collation, with `charlists` as if every
+    /// charlist was a Bitstring.
+    pub fn from_sorted(configuration: Configuration, items: Vec<T>) -> BuildResult<T> {
+        if let Some(max_elements) = configuration.max_elements {
+            if items.len() > max_elements {
+                return BuildResult::SetFull;
+            }
+        }
+
+        if configuration.max_bucket_size < 1 {
+            panic!("SortedSet max_bucket_size must be greater than 0");
+        }
+
+        let items: Vec<T> = if configuration.prepares_items() {
+            items
+                .into_iter()
+                .map(|item| configuration.prepare(item))
+                .collect()
+        } else {
+            items
+        };
+
+        let mut ids = if configuration.stable_ids {
+            Some(Box::new(StableIds::new()))
+        } else {
+            None
+        };
+
+        if let Some(ref mut ids) = ids {
+            for item in items.iter() {
+                ids.assign(item.clone());
+            }
+        }
+
+        let sketch = if configuration.cardinality_sketch {
+            Some(Box::new(Sketch::of(items.iter())))
+        } else {
+            None
+        };
+
+        let backend = Backend::from_sorted(&configuration, items);
+        let mut result = SortedSet::with_backend(configuration, backend);
+        result.ids = ids;
+        result.sketch = sketch;
+
+        BuildResult::Built(Box::new(result))
+    }
+
+    /// Rebuilds a set from the buckets returned by `layout`.  The buckets are used as-is without
+    /// checking that they are sorted or within the max bucket size, so a set whose buckets ended
+    /// up in a bad state can be reproduced exactly.  The B+tree and bitset backends are loaded from
+    /// the items instead, they do not take their layout from the caller.
+    pub fn from_layout(configuration: Configuration, layout: Vec<Vec<T>>) -> SortedSet<T> {
+        if configuration.max_bucket_size < 1 {
+            panic!("SortedSet max_bucket_size must be greater than 0");
+        }
+
+        let layout: Vec<Vec<T>> = if configuration.prepares_items() {
+            layout
+                .into_iter()
+                .map(|bucket| {
+                    bucket
+                        .into_iter()
+                        .map(|item| configuration.prepare(item))
+                        .collect()
+                })
+                .collect()
+        } else {
+            layout
+        };
+
+        let backend = match configuration.backend {
+            BackendKind::Buckets => {
+                let capacity = max(configuration.initial_set_capacity, layout.len());
+                let mut buckets = Buckets::empty(configuration.max_bucket_size, capacity)
+                    .with_bucket_capacity(configuration.initial_bucket_capacity)
+                    .with_tombstones(configuration.tombstone_ratio)
+                    .with_interpolation(configuration.interpolation_search);
+                for bucket in layout {
+                    buckets.push_bucket(bucket);
+                }
+                Backend::Buckets(buckets)
+            }
+            BackendKind::BTree => {
+                Backend::BTree(BTree::from_sorted(layout.into_iter().flatten().collect()))
+            }
+            BackendKind::Bitset { universe } => {
+                let mut backend = Backend::bitset(&configuration, universe);
+                backend.extend_sorted(layout.into_iter().flatten().collect());
+                backend
+            }
+        };
+
+        let mut result = SortedSet::with_backend(configuration, backend);
+
+        if let Some(ref mut ids) = result.ids {
+            for item in result.backend.iter() {
+                ids.assign(item.clone());
+            }
+        }
+
+        if let Some(ref mut sketch) = result.sketch {
+            for item in result.backend.iter() {
+                sketch.insert(item);
+            }
+        }
+
+        result
+    }
+
+    pub fn append_bucket(&mut self, items: Vec<T>) -> AppendBucketResult {
+        let items: Vec<T> = if self.configuration.prepares_items() {
+            items
+                .into_iter()
+                .map(|item| self.configuration.prepare(item))
+                .collect()
+        } else {
+            items
+        };
+
+        if self.configuration.max_bucket_size <= items.len() {
+            return AppendBucketResult::MaxBucketSizeExceeded;
+        }
+
+        if let Some(max_elements) = self.configuration.max_elements {
+            if self.size() + items.len() > max_elements {
+                return AppendBucketResult::SetFull;
+            }
+        }
+
+        if let Some(ref mut ids) = self.ids {
+            for item in items.iter() {
+                ids.assign(item.clone());
+            }
+        }
+
+        if let Some(ref mut sketch) = self.sketch {
+            for item in items.iter() {
+                sketch.insert(item);
+            }
+        }
+
+        match self.backend {
+            Backend::Buckets(ref mut buckets) => buckets.push_bucket(items),
+            ref mut backend => backend.extend_sorted(items),
+        }
+        self.touch();
+
+        AppendBucketResult::Ok
+    }
+
+    pub fn find_index(&self, item: &T) -> Option<usize> {
+        self.backend.find_index(&self.prepared(item))
+    }
+
+    /// True if the set holds `item`.  The search stops once the item has been found in its
+    /// bucket, the index of the item is never computed.
+    pub fn contains(&self, item: &T) -> bool {
+        self.backend.contains(&self.prepared(item))
+    }
+
+    /// Whether the set holds each of `items`, in the order the items were given.  The items are
+    /// looked up in ascending order so the search moves through the buckets in a single direction.
+    pub fn contains_many(&self, items: &[T]) -> Vec<bool> {
+        let items: Vec<Cow<T>> = items.iter().map(|item| self.prepared(item)).collect();
+
+        let mut order: Vec<usize> = (0..items.len()).collect();
+        order.sort_by(|&a, &b| items[a].cmp(&items[b]));
+
+        let sorted: Vec<&T> = order.iter().map(|&index| &*items[index]).collect();
+        let mut result = vec![false; items.len()];
+
+        for (&index, found) in order.iter().zip(self.backend.contains_sorted(&sorted)) {
+            result[index] = found;
+        }
+
+        result
+    }
+
+    /// Searches the set for `item` like `slice::binary_search`, the error holds the index the item
+    /// would be inserted at.  Sets with buckets find both with a single search.
+    pub fn search(&self, item: &T) -> Result<usize, usize> {
+        self.backend.search(&self.prepared(item))
+    }
+
+    /// Returns the number of items less than `item`, which is the index the item has or would
+    /// have in the set.
+    pub fn rank(&self, item: &T) -> usize {
+        self.backend.rank(&self.prepared(item))
+    }
+
+    /// Returns the number of items less than or equal to `item`, which is the index just past the
+    /// item whether or not it is in the set.
+    pub fn rank_past(&self, item: &T) -> usize {
+        let item = self.prepared(item);
+
+        match self.backend.find_index(&item) {
+            Some(idx) => idx + 1,
+            None => self.backend.rank(&item),
+        }
+    }
+
+    /// The greatest item of the set less than or equal to `item`, found with the searches of
+    /// `find_index` but without computing an index.
+    pub fn floor(&self, item: &T) -> Option<&T> {
+        self.backend.floor(&self.prepared(item))
+    }
+
+    /// The least item of the set greater than or equal to `item`, found with the searches of
+    /// `find_index` but without computing an index.
+    pub fn ceiling(&self, item: &T) -> Option<&T> {
+        self.backend.ceiling(&self.prepared(item))
+    }
+
+    /// Returns the item in the form stored by this set, only sets with a collation have to
+    /// convert it.
+    #[inline]
+    fn prepared<'a>(&self, item: &'a T) -> Cow<'a, T> {
+        if self.configuration.prepares_items() {
+            Cow::Owned(self.configuration.prepare(item.clone()))
+        } else {
+            Cow::Borrowed(item)
+        }
+    }
+
+    /// True if the set holds its `max_elements`, sets without the option are never full.
+    #[inline]
+    pub fn is_full(&self) -> bool {
+        match self.configuration.max_elements {
+            Some(max_elements) => self.size() >= max_elements,
+            None => false,
+        }
+    }
+
+    pub fn add(&mut self, item: T) -> AddResult {
+        let item = self.configuration.prepare(item);
+
+        if self.is_full() {
+            // A full set still reports duplicates as a success, only growth is rejected.
+            return match self.find_index(&item) {
+                Some(idx) => AddResult::Duplicate(idx),
+                None => AddResult::SetFull,
+            };
+        }
+
+        let key = self.ids.as_ref().map(|_| item.clone());
+        let hash = self.sketch.as_ref().map(|_| Sketch::hash(&item));
+
+        let result = self.backend.add(item);
+        if let AddResult::Added(_) = result {
+            self.assign_id(key);
+            self.sketch_hash(hash);
+            self.touch();
+        }
+
+        result
+    }
+
+    /// Adds an item using a caller supplied guess of the index the item belongs at, for example
+    /// one past the index of the previously added item when feeding in nearly sorted data.  The
+    /// hint is verified against its neighbors and if it turns out to be wrong this falls back to
+    /// a regular `add`, so a bad hint costs a couple of comparisons but never correctness.
+    pub fn add_with_hint(&mut self, item: T, hint: usize) -> AddResult {
+        let item = self.configuration.prepare(item);
+
+        if self.is_full() {
+            return self.add(item);
+        }
+
+        let key = self.ids.as_ref().map(|_| item.clone());
+        let hash = self.sketch.as_ref().map(|_| Sketch::hash(&item));
+
+        let result = self.backend.add_with_hint(item, hint);
+        if let AddResult::Added(_) = result {
+            self.assign_id(key);
+            self.sketch_hash(hash);
+            self.touch();
+        }
+
+        result
+    }
+
+    pub fn remove(&mut self, item: &T) -> RemoveResult {
+        let item = self.prepared(item).into_owned();
+
+        let result = self.backend.remove(&item);
+        if let RemoveResult::Removed(_) = result {
+            if let Some(ref mut ids) = self.ids {
+                ids.release(&item);
+            }
+
+            self.mark_sketch_stale();
+            self.touch();
+        }
+
+        result
+    }
+
+    /// Removes the items at the given indexes, returning them in the order of their indexes.
+    /// Indexes past the end of the set and repeated indexes are ignored.  Every bucket is only
+    /// visited once, however many of its items are removed.
+    pub fn remove_at_many(&mut self, indexes: &[usize]) -> Vec<T> {
+        let size = self.size();
+        let mut indexes: Vec<usize> = indexes
+            .iter()
+            .cloned()
+            .filter(|&index| index < size)
+            .collect();
+        indexes.sort_unstable();
+        indexes.dedup();
+
+        if indexes.is_empty() {
+            return Vec::new();
+        }
+
+        let removed = self.backend.remove_at_many(&indexes);
+
+        if let Some(ref mut ids) = self.ids {
+            for item in removed.iter() {
+                ids.release(item);
+            }
+        }
+
+        self.mark_sketch_stale();
+        self.touch();
+
+        removed
+    }
+
+    /// Removes and returns the least item of the set, None if the set is empty.
+    pub fn pop_first(&mut self) -> Option<T> {
+        self.pop_at(0)
+    }
+
+    /// Removes and returns the greatest item of the set, None if the set is empty.
+    pub fn pop_last(&mut self) -> Option<T> {
+        let last = self.size().checked_sub(1)?;
+        self.pop_at(last)
+    }
+
+    /// Removes and returns the item at `index`, None if the index is past the end of the set.
+    /// The item is found with the same walk as `at` and removed from its bucket in place, so it
+    /// is never compared against other items.
+    pub fn pop_at(&mut self, index: usize) -> Option<T> {
+        self.remove_at_many(&[index]).pop()
+    }
+
+    #[inline]
+    fn assign_id(&mut self, key: Option<T>) {
+        if let (Some(ids), Some(key)) = (self.ids.as_mut(), key) {
+            ids.assign(key);
+        }
+    }
+
+    #[inline]
+    fn sketch_hash(&mut self, hash: Option<u64>) {
+        if let (Some(sketch), Some(hash)) = (self.sketch.as_mut(), hash) {
+            sketch.insert_hash(hash);
+        }
+    }
+
+    #[inline]
+    fn mark_sketch_stale(&mut self) {
+        if let Some(ref mut sketch) = self.sketch {
+            sketch.mark_stale();
+        }
+    }
+
+    /// Returns the cardinality sketch of the set, `None` if the set was not configured with
+    /// `cardinality_sketch`.  Sketches can not forget items, once items have been removed the
+    /// sketch is rebuilt from the items of the set on every read, which costs a hash per item.
+    pub fn sketch(&self) -> Option<Cow<'_, Sketch>> {
+        let sketch = self.sketch.as_ref()?;
+
+        if sketch.is_stale() {
+            Some(Cow::Owned(Sketch::of(self.backend.iter())))
+        } else {
+            Some(Cow::Borrowed(sketch))
+        }
+    }
+
+    /// Returns the stable ID of an item, `None` if the item is not present or the set was not
+    /// configured with `stable_ids`.
+    pub fn id_of(&self, item: &T) -> Option<u64> {
+        self.ids
+            .as_ref()
+            .and_then(|ids| ids.id_of(&self.prepared(item)))
+    }
+
+    /// Returns the item with the given stable ID.
+    pub fn get_by_id(&self, id: u64) -> Option<&T> {
+        self.ids.as_ref().and_then(|ids| ids.get(id))
+    }
+
+    pub fn has_stable_ids(&self) -> bool {
+        self.ids.is_some()
+    }
+
+    pub fn at(&self, index: usize) -> Option<&T> {
+        self.backend.at(index)
+    }
+
+    /// Resolves an index that may count back from the end of the set, -1 is the index of the last
+    /// item.  None if the index falls outside the set.
+    pub fn resolve_index(&self, index: i64) -> Option<usize> {
+        let size = self.size();
+
+        let index = if index < 0 {
+            size.checked_sub(index.unsigned_abs() as usize)?
+        } else {
+            index as usize
+        };
+
+        if index < size {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the items at each of `indexes` in the order the indexes were given, None for an
+    /// index past the end of the set.  The bucket sizes are summed once for the whole batch
+    /// instead of once per index.
+    pub fn at_many(&self, indexes: &[usize]) -> Vec<Option<&T>> {
+        self.backend.at_many(indexes)
+    }
+
+    /// The least item of the set, found without a search.
+    pub fn first(&self) -> Option<&T> {
+        self.backend.first()
+    }
+
+    /// The greatest item of the set, found without a search.
+    pub fn last(&self) -> Option<&T> {
+        self.backend.last()
+    }
+
+    pub fn slice(&self, index: usize, amount: usize) -> Vec<T> {
+        self.backend.slice(index, amount)
+    }
+
+    /// Retrieves up to `amount` items in descending order, starting from the item `start_from_end`
+    /// places from the end of the set, so `slice_desc(0, 10)` is the greatest ten items.  The
+    /// buckets are walked back-to-front rather than slicing forward and reversing.
+    pub fn slice_desc(&self, start_from_end: usize, amount: usize) -> Vec<T> {
+        self.backend.slice_desc(start_from_end, amount)
+    }
+
+    /// Retrieves several slices at once, each window is a `(start, amount)` pair with the same
+    /// semantics as `slice`.  The windows are visited in order of their start index so the buckets
+    /// are only traversed once regardless of how many windows are requested, the slices are
+    /// returned in the order the windows were given.
+    pub fn multi_slice(&self, windows: &[(usize, usize)]) -> Vec<Vec<T>> {
+        self.backend.multi_slice(windows)
+    }
+
+    /// Returns every `step`-th item of the slice starting at `index` and spanning `amount` items,
+    /// beginning with the item at `index`.  The walk jumps over the skipped items rather than
+    /// cloning them, so a sparse sample of a large range costs little more than the sample.
+    ///
+    /// Panics if `step` is 0.
+    pub fn slice_step(&self, index: usize, amount: usize, step: usize) -> Vec<T> {
+        self.backend
+            .iter_from(index)
+            .take(amount)
+            .step_by(step)
+            .cloned()
+            .collect()
+    }
+
+    /// Splits the items into consecutive chunks of `size` items in a single pass over the set,
+    /// only the last chunk can hold fewer items.
+    ///
+    /// Panics if `size` is 0.
+    pub fn chunks(&self, size: usize) -> Vec<Vec<T>> {
+        assert!(size > 0, "chunk size must be greater than 0");
+
+        let mut chunks = Vec::new();
+        let mut items = self.iter();
+        let mut remaining = self.size();
+
+        while remaining > 0 {
+            let len = min(size, remaining);
+            chunks.push(items.by_ref().take(len).cloned().collect());
+            remaining -= len;
+        }
+
+        chunks
+    }
+
+    pub fn to_vec(&self) -> Vec<T> {
+        let mut new_vec = Vec::with_capacity(self.size());
+        new_vec.extend(self.iter().cloned());
+        new_vec
+    }
+
+    /// Clones the items into a vector in descending order, without building the ascending vector
+    /// first.
+    pub fn to_vec_rev(&self) -> Vec<T> {
+        let mut new_vec = Vec::with_capacity(self.size());
+        new_vec.extend(self.backend.iter_rev().cloned());
+        new_vec
+    }
+
+    /// Moves every item out of the set, leaving it empty.  Unlike `to_vec` the items are not
+    /// cloned, so the peak memory of draining a set is roughly the size of the set itself.
+    pub fn drain(&mut self) -> Vec<T> {
+        let result = self.backend.drain();
+
+        if let Some(ref mut ids) = self.ids {
+            ids.clear();
+        }
+
+        if let Some(ref mut sketch) = self.sketch {
+            sketch.clear();
+        }
+
+        if !result.is_empty() {
+            self.touch();
+        }
+
+        result
+    }
+
+    pub fn size(&self) -> usize {
+        self.backend.len()
+    }
+
+    /// Approximate number of bytes used by the set's own storage, including space reserved for
+    /// future items.  Heap data owned by the items themselves, like the contents of binaries and
+    /// nested lists, is not counted.
+    pub fn memory(&self) -> usize {
+        let ids = self.ids.as_ref().map(|ids| ids.memory()).unwrap_or(0);
+        let sketch = self
+            .sketch
+            .as_ref()
+            .map(|sketch| sketch.memory())
+            .unwrap_or(0);
+
+        mem::size_of::<SortedSet<T>>() + self.backend.memory() + ids + sketch
+    }
+
+    /// Returns the items of every bucket, for the B+tree backend the leaves are reported as
+    /// buckets and buckets holding tombstones are reported as the runs of items between them.
+    pub fn layout(&self) -> Vec<&[T]> {
+        self.backend.layout()
+    }
+
+    pub fn bucket_stats(&self) -> BucketStats {
+        self.backend.bucket_stats()
+    }
+
+    /// Restores the invariants of a set whose storage ended up in a bad state: items are sorted,
+    /// duplicates and empty buckets are dropped and the size is recounted.  Returns what had to be
+    /// fixed, a clean report means the set was healthy.
+    pub fn repair(&mut self) -> RepairReport {
+        let report = self.backend.repair();
+
+        if !report.is_clean() {
+            self.touch();
+        }
+
+        report
+    }
+
+    /// Removes the items under every tombstone right away instead of waiting for their buckets to
+    /// cross the tombstone ratio, see `Configuration::tombstone_ratio`.  Returns the number of
+    /// tombstones removed.  The items of the set do not change, so neither does its version.
+    pub fn compact(&mut self) -> usize {
+        self.backend.compact()
+    }
+
+    /// Number of removed items that are still in place, waiting to be compacted.
+    pub fn tombstones(&self) -> usize {
+        self.backend.tombstones()
+    }
+
+    /// Returns the wall-clock and monotonic timestamps of the last successful mutation, a set that
+    /// has never been mutated reports the time it was created.
+    pub fn last_modified(&self) -> (SystemTime, Instant) {
+        (self.modified_at_system, self.modified_at)
+    }
+
+    /// Exchanges the contents of two sets.  The configuration travels with the contents so that
+    /// each set's buckets stay consistent with the configuration that built them, metadata and the
+    /// frozen flag stay with the set they were attached to.
+    pub fn swap(&mut self, other: &mut SortedSet<T>) {
+        mem::swap(&mut self.configuration, &mut other.configuration);
+        mem::swap(&mut self.backend, &mut other.backend);
+        mem::swap(&mut self.ids, &mut other.ids);
+        mem::swap(&mut self.sketch, &mut other.sketch);
+
+        self.touch();
+        other.touch();
+    }
+
+    /// Moves every item of `other` onto the end of this set, leaving `other` empty.  This is only
+    /// valid when every item in `other` is greater than every item in this set, in which case
+    /// bucketed sets can move their buckets as-is without searching or copying any items.
+    pub fn concat(&mut self, other: &mut SortedSet<T>) -> ConcatResult {
+        if other.size() == 0 {
+            return ConcatResult::Ok;
+        }
+
+        if self.configuration.collation != other.configuration.collation {
+            return ConcatResult::CollationMismatch;
+        }
+
+        if self.configuration.comparator != other.configuration.comparator {
+            return ConcatResult::ComparatorMismatch;
+        }
+
+        // Items are moved as they are, a charlist left as a list must not land in a set that
+        // stores charlists as bitstrings
+        if !self.configuration.prepares_like(&other.configuration) {
+            return ConcatResult::CharlistsMismatch;
+        }
+
+        if let (Some(last), Some(first)) = (self.backend.last(), other.backend.first()) {
+            if last >= first {
+                return ConcatResult::Overlapping;
+            }
+        }
+
+        if let Some(max_elements) = self.configuration.max_elements {
+            if self.size() + other.size() > max_elements {
+                return ConcatResult::SetFull;
+            }
+        }
+
+        // Moved items are new to this set so they are assigned IDs from this set's counter
+        if let Some(ref mut ids) = self.ids {
+            for item in other.iter() {
+                ids.assign(item.clone());
+            }
+        }
+
+        if let Some(ref mut ids) = other.ids {
+            ids.clear();
+        }
+
+        if let Some(ref mut sketch) = self.sketch {
+            for item in other.iter() {
+                sketch.insert(item);
+            }
+        }
+
+        if let Some(ref mut sketch) = other.sketch {
+            sketch.clear();
+        }
+
+        self.backend.append(&mut other.backend);
+
+        self.touch();
+        other.touch();
+
+        ConcatResult::Ok
+    }
+
+    /// Splits the set around `pivot` into a set of the items less than the pivot and a set of the
+    /// items greater than or equal to the pivot, leaving this set empty.  For bucketed sets only
+    /// the bucket that straddles the pivot has to be divided, every other bucket is moved as-is.
+    pub fn partition(&mut self, pivot: &T) -> (SortedSet<T>, SortedSet<T>) {
+        if self.size() == 0 {
+            return (
+                SortedSet::new(self.configuration.clone()),
+                SortedSet::new(self.configuration.clone()),
+            );
+        }
+
+        let pivot = self.prepared(pivot).into_owned();
+        let upper = self.backend.split_off(&pivot);
+        let lower = mem::replace(&mut self.backend, Backend::new(&self.configuration));
+
+        let mut lower = SortedSet::with_backend(self.configuration.clone(), lower);
+        let mut upper = SortedSet::with_backend(self.configuration.clone(), upper);
+
+        // The halves keep the IDs their items had in this set
+        if let Some(ref mut ids) = self.ids {
+            let (lower_ids, upper_ids) = ids.split(&pivot);
+            lower.ids = Some(Box::new(lower_ids));
+            upper.ids = Some(Box::new(upper_ids));
+        }
+
+        // Each half holds only some of the items the sketch saw
+        lower.mark_sketch_stale();
+        upper.mark_sketch_stale();
+
+        self.touch();
+
+        (lower, upper)
+    }
+
+    /// Moves every item between `min` and `max`, inclusive, from this set into `other`.  For
+    /// bucketed sets only the buckets straddling the bounds have to be divided, the buckets in
+    /// between are moved as-is when `other` holds nothing within the range and orders its items
+    /// the same way.  Otherwise the items are added to `other` one by one, items `other` already
+    /// holds are simply dropped from this set.
+    ///
+    /// The `max_elements` of `other` is checked as if none of the items were present in `other`.
+    pub fn move_range(&mut self, other: &mut SortedSet<T>, min: &T, max: &T) -> MoveRangeResult {
+        let start = self.rank(min);
+        let end = self.rank_past(max);
+
+        if end <= start {
+            return MoveRangeResult::Moved(0);
+        }
+
+        let count = end - start;
+
+        if let Some(max_elements) = other.configuration.max_elements {
+            if other.size() + count > max_elements {
+                return MoveRangeResult::SetFull;
+            }
+        }
+
+        let min = self.prepared(min).into_owned();
+        let max = self.prepared(max).into_owned();
+
+        let mut middle = self.backend.split_off(&min);
+        let mut tail = middle.split_off(&max);
+
+        // The split leaves `max` itself at the front of the tail
+        let last = match tail.first() {
+            Some(first) if first.cmp(&max) == Ordering::Equal => Some(first.clone()),
+            _ => None,
+        };
+        if let Some(last) = last {
+            tail.remove(&last);
+            middle.extend_sorted(vec![last]);
+        }
+
+        self.backend.append(&mut tail);
+
+        if let Some(ref mut ids) = self.ids {
+            for item in middle.iter() {
+                ids.release(item);
+            }
+        }
+
+        self.mark_sketch_stale();
+
+        let same_order = self.configuration.prepares_like(&other.configuration);
+
+        if same_order && other.rank(&min) == other.rank_past(&max) {
+            if let Some(ref mut ids) = other.ids {
+                for item in middle.iter() {
+                    ids.assign(item.clone());
+                }
+            }
+
+            if let Some(ref mut sketch) = other.sketch {
+                for item in middle.iter() {
+                    sketch.insert(item);
+                }
+            }
+
+            let mut upper = other.backend.split_off(&min);
+            other.backend.append(&mut middle);
+            other.backend.append(&mut upper);
+            other.touch();
+        } else {
+            for item in middle.drain() {
+                other.add(Configuration::strip(item));
+            }
+        }
+
+        self.touch();
+
+        MoveRangeResult::Moved(count)
+    }
+
+    /// Adds every item of `other` to this set, leaving `other` untouched.  When both sets order
+    /// their items the same way the items are merged in a single linear pass and the storage is
+    /// rebuilt from the result, otherwise the items are added one by one.
+    ///
+    /// The `max_elements` of this set is checked before anything is added, a merge that would
+    /// grow the set past it adds nothing.
+    pub fn merge_from(&mut self, other: &SortedSet<T>) -> MergeResult {
+        if !self.configuration.prepares_like(&other.configuration) {
+            // The sets disagree on the form or order of their items, add them one by one
+            let items: Vec<T> = other
+                .iter()
+                .cloned()
+                .map(Configuration::strip)
+                .filter(|item| self.find_index(item).is_none())
+                .collect();
+
+            if self.exceeds_max_elements(items.len()) {
+                return MergeResult::SetFull;
+            }
+
+            let count = items.len();
+            for item in items {
+                self.add(item);
+            }
+
+            return MergeResult::Merged(count);
+        }
+
+        // The IDs and the sketch only have to hear about the items that are new to this set
+        let track = self.ids.is_some() || self.sketch.is_some();
+        let mut fresh = Vec::new();
+        let mut count = 0;
+
+        let mut items = Vec::with_capacity(self.size() + other.size());
+        {
+            let mut left = self.iter().peekable();
+            let mut right = other.iter().peekable();
+
+            loop {
+                let ordering = match (left.peek(), right.peek()) {
+                    (Some(l), Some(r)) => l.cmp(r),
+                    (Some(_), None) => Ordering::Less,
+                    (None, Some(_)) => Ordering::Greater,
+                    (None, None) => break,
+                };
+
+                match ordering {
+                    Ordering::Less => items.push(next_cloned(&mut left)),
+                    Ordering::Greater => {
+                        let item = next_cloned(&mut right);
+                        if track {
+                            fresh.push(item.clone());
+                        }
+                        count += 1;
+                        items.push(item);
+                    }
+                    Ordering::Equal => {
+                        right.next();
+                        items.push(next_cloned(&mut left));
+                    }
+                }
+            }
+        }
+
+        if count == 0 {
+            return MergeResult::Merged(0);
+        }
+
+        if self.exceeds_max_elements(count) {
+            return MergeResult::SetFull;
+        }
+
+        self.backend = Backend::from_sorted(&self.configuration, items);
+
+        for item in fresh {
+            if let Some(ref mut sketch) = self.sketch {
+                sketch.insert(&item);
+            }
+
+            if let Some(ref mut ids) = self.ids {
+                ids.assign(item);
+            }
+        }
+
+        self.touch();
+
+        MergeResult::Merged(count)
+    }
+
+    /// Whether adding `count` new items would grow the set past its `max_elements`.
+    #[inline]
+    fn exceeds_max_elements(&self, count: usize) -> bool {
+        match self.configuration.max_elements {
+            Some(max_elements) => self.size() + count > max_elements,
+            None => false,
+        }
+    }
+
+    /// Builds a new set containing every item present in either set with a single linear merge of
+    /// the two sets.  The new set is built with this set's configuration.
+    pub fn union(&self, other: &SortedSet<T>) -> BuildResult<T> {
+        if !self.configuration.prepares_like(&other.configuration) {
+            // The sets disagree on the form or order of their items, rebuild in this set's order
+            let items = self
+                .iter()
+                .chain(other.iter())
+                .cloned()
+                .map(Configuration::strip)
+                .collect();
+
+            return SortedSet::from_unsorted(self.configuration.clone(), items);
+        }
+
+        let mut left = self.iter().peekable();
+        let mut right = other.iter().peekable();
+        let mut items = Vec::with_capacity(self.size() + other.size());
+
+        loop {
+            let ordering = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) => l.cmp(r),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+
+            match ordering {
+                Ordering::Less => items.push(next_cloned(&mut left)),
+                Ordering::Greater => items.push(next_cloned(&mut right)),
+                Ordering::Equal => {
+                    right.next();
+                    items.push(next_cloned(&mut left));
+                }
+            }
+        }
+
+        SortedSet::from_sorted(self.configuration.clone(), items)
+    }
+
+    /// Builds a new set containing the items present in both sets with a single linear walk of
+    /// the two sets.  The new set is built with this set's configuration.
+    pub fn intersection(&self, other: &SortedSet<T>) -> BuildResult<T> {
+        if !self.configuration.prepares_like(&other.configuration) {
+            // The sets disagree on the form or order of their items, look each item up instead
+            let items = self
+                .iter()
+                .cloned()
+                .map(Configuration::strip)
+                .filter(|item| other.find_index(item).is_some())
+                .collect();
+
+            return SortedSet::from_unsorted(self.configuration.clone(), items);
+        }
+
+        let mut left = self.iter().peekable();
+        let mut right = other.iter().peekable();
+        let mut items = Vec::new();
+
+        while let (Some(l), Some(r)) = (left.peek(), right.peek()) {
+            match l.cmp(r) {
+                Ordering::Less => {
+                    left.next();
+                }
+                Ordering::Greater => {
+                    right.next();
+                }
+                Ordering::Equal => {
+                    right.next();
+                    items.push(next_cloned(&mut left));
+                }
+            }
+        }
+
+        SortedSet::from_sorted(self.configuration.clone(), items)
+    }
+
+    /// Builds a new set containing the items of this set that are not present in `other` with a
+    /// single linear walk of the two sets.  The new set is built with this set's configuration.
+    pub fn difference(&self, other: &SortedSet<T>) -> BuildResult<T> {
+        if !self.configuration.prepares_like(&other.configuration) {
+            // The sets disagree on the form or order of their items, look each item up instead
+            let items = self
+                .iter()
+                .cloned()
+                .map(Configuration::strip)
+                .filter(|item| other.find_index(item).is_none())
+                .collect();
+
+            return SortedSet::from_unsorted(self.configuration.clone(), items);
+        }
+
+        let mut left = self.iter().peekable();
+        let mut right = other.iter().peekable();
+        let mut items = Vec::with_capacity(self.size());
+
+        while let Some(l) = left.peek() {
+            let ordering = match right.peek() {
+                Some(r) => l.cmp(r),
+                None => Ordering::Less,
+            };
+
+            match ordering {
+                Ordering::Less => items.push(next_cloned(&mut left)),
+                Ordering::Greater => {
+                    right.next();
+                }
+                Ordering::Equal => {
+                    left.next();
+                    right.next();
+                }
+            }
+        }
+
+        SortedSet::from_sorted(self.configuration.clone(), items)
+    }
+
+    /// The items of `other` missing from this set and the items of this set missing from
+    /// `other`, in order, with a single linear walk of the two sets.
+    pub fn diff(&self, other: &SortedSet<T>) -> (Vec<T>, Vec<T>) {
+        if !self.configuration.prepares_like(&other.configuration) {
+            // The sets disagree on the form or order of their items, look each item up instead
+            let added = other
+                .iter()
+                .cloned()
+                .map(Configuration::strip)
+                .filter(|item| self.find_index(item).is_none())
+                .collect();
+
+            let removed = self
+                .iter()
+                .cloned()
+                .map(Configuration::strip)
+                .filter(|item| other.find_index(item).is_none())
+                .collect();
+
+            return (added, removed);
+        }
+
+        let mut left = self.iter().peekable();
+        let mut right = other.iter().peekable();
+        let mut added = Vec::new();
+        let mut removed = Vec::new();
+
+        loop {
+            let ordering = match (left.peek(), right.peek()) {
+                (Some(l), Some(r)) => l.cmp(r),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => break,
+            };
+
+            match ordering {
+                Ordering::Less => removed.push(next_cloned(&mut left)),
+                Ordering::Greater => added.push(next_cloned(&mut right)),
+                Ordering::Equal => {
+                    left.next();
+                    right.next();
+                }
+            }
+        }
+
+        (added, removed)
+    }
+
+    /// Compares the items of the two sets in order, like two sorted lists are compared: the first
+    /// pair of items that differ decides, otherwise the set that runs out of items first is the
+    /// lesser one.
+    pub fn compare_items(&self, other: &SortedSet<T>) -> Ordering {
+        if !self.configuration.prepares_like(&other.configuration) {
+            // The sets hold their items in different forms, compare them as they were given
+            return self
+                .iter()
+                .cloned()
+                .map(Configuration::strip)
+                .cmp(other.iter().cloned().map(Configuration::strip));
+        }
+
+        self.iter().cmp(other.iter())
+    }
+
+    /// Whether the two sets hold the same items, regardless of how the items are laid out.  The
+    /// sizes are compared first, sets of the same size are then walked side by side until the
+    /// first item that differs.
+    pub fn same_items(&self, other: &SortedSet<T>) -> bool {
+        if self.size() != other.size() {
+            return false;
+        }
+
+        if !self.configuration.prepares_like(&other.configuration) {
+            // The sets disagree on the form or order of their items, look each item up instead
+            return self
+                .iter()
+                .cloned()
+                .map(Configuration::strip)
+                .all(|item| other.find_index(&item).is_some());
+        }
+
+        self.iter()
+            .zip(other.iter())
+            .all(|(a, b)| a.cmp(b) == Ordering::Equal)
+    }
+
+    /// Whether the two sets have no item in common.  The buckets of both sets are walked side by
+    /// side and only the items of buckets whose ranges overlap are compared, so sets that overlap
+    /// in few places are checked without visiting most of their items.  Stops at the first item
+    /// found in both.
+    pub fn is_disjoint(&self, other: &SortedSet<T>) -> bool {
+        if !self.configuration.prepares_like(&other.configuration) {
+            // The sets disagree on the form or order of their items, look each item up instead
+            return self
+                .iter()
+                .cloned()
+                .map(Configuration::strip)
+                .all(|item| other.find_index(&item).is_none());
+        }
+
+        let left = self.layout();
+        let right = other.layout();
+        let (mut i, mut j) = (0, 0);
+
+        while i < left.len() && j < right.len() {
+            let (a, b) = (left[i], right[j]);
+
+            let (a_first, a_last, b_first, b_last) =
+                match (a.first(), a.last(), b.first(), b.last()) {
+                    (Some(a_first), Some(a_last), Some(b_first), Some(b_last)) => {
+                        (a_first, a_last, b_first, b_last)
+                    }
+                    _ => {
+                        // An empty bucket has no range to compare
+                        if a.is_empty() {
+                            i += 1;
+                        } else {
+                            j += 1;
+                        }
+                        continue;
+                    }
+                };
+
+            if a_last < b_first {
+                i += 1;
+            } else if b_last < a_first {
+                j += 1;
+            } else if slices_intersect(a, b) {
+                return false;
+            } else if a_last < b_last {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        true
+    }
+
+    /// Iterates over every item in the set in order.
+    pub fn iter(&self) -> Iter<'_, T> {
+        self.backend.iter()
+    }
+
+    /// Iterates in order over the items starting at `index`.
+    pub fn iter_from(&self, index: usize) -> Iter<'_, T> {
+        self.backend.iter_from(index)
+    }
+
+    /// Iterates in order over the items that fall within `range`, the bounds do not have to be
+    /// items of the set.  A range whose start lies past its end is empty.
+    pub fn range<R: RangeBounds<T>>(&self, range: R) -> Iter<'_, T> {
+        let start = match range.start_bound() {
+            Bound::Included(item) => self.rank(item),
+            Bound::Excluded(item) => self.rank_past(item),
+            Bound::Unbounded => 0,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(item) => self.rank_past(item),
+            Bound::Excluded(item) => self.rank(item),
+            Bound::Unbounded => self.size(),
+        };
+
+        Box::new(
+            self.backend
+                .iter_from(start)
+                .take(end.saturating_sub(start)),
+        )
+    }
+
+    /// Attaches an arbitrary term to the set, replacing any previously attached term.  Metadata is
+    /// not part of the set's contents so this does not count as a modification.
+    pub fn put_meta(&mut self, meta: T) {
+        self.meta = Some(meta);
+    }
+
+    pub fn meta(&self) -> Option<&T> {
+        self.meta.as_ref()
+    }
+
+    /// Marks the set as frozen.  The SortedSet does not enforce the flag itself, it is up to the
+    /// caller to refuse mutations while the set is frozen.
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+    }
+
+    pub fn thaw(&mut self) {
+        self.frozen = false;
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    pub fn configuration(&self) -> &Configuration {
+        &self.configuration
+    }
+
+    /// Returns a counter that is incremented by every successful mutation.
+    pub fn version(&self) -> u64 {
+        self.version
+    }
+
+    #[inline]
+    fn touch(&mut self) {
+        self.version += 1;
+        self.modified_at = Instant::now();
+        self.modified_at_system = SystemTime::now();
+    }
+
+    pub fn debug(&self) -> String {
+        format!("{:#?}", self)
+    }
+}
+
+#[inline]
+fn next_cloned<'a, T, I>(iter: &mut Peekable<I>) -> T
+where
+    T: Item + 'a,
+    I: Iterator<Item = &'a T>,
+{
+    match iter.next() {
+        Some(item) => item.clone(),
+        None => unreachable!("next_cloned is only called after a successful peek"),
+    }
+}
+
+/// Whether two sorted slices have an item in common.
+fn slices_intersect<T: Item>(a: &[T], b: &[T]) -> bool {
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => return true,
+        }
+    }
+
+    false
+}
+
+impl<T: Item> Default for SortedSet<T> {
+    fn default() -> Self {
+        return Self::new(Configuration::default());
+    }
+}
+
+impl<T: Item> Index<usize> for SortedSet<T> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &T {
+        match self.at(index) {
+            Some(item) => item,
+            None => panic!(
+                "index {} out of range for SortedSet of size {}",
+                index,
+                self.size()
+            ),
+        }
+    }
+}
+
+impl<'a, T: Item> IntoIterator for &'a SortedSet<T> {
+    type Item = &'a T;
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Iter<'a, T> {
+        self.iter()
+    }
+}
+
+/// Consumes the set, yielding the items in order as they were given to the set.
+impl<T: Item> IntoIterator for SortedSet<T> {
+    type Item = T;
+    type IntoIter = vec::IntoIter<T>;
+
+    fn into_iter(mut self) -> vec::IntoIter<T> {
+        let items = self.backend.drain();
+
+        if self.configuration.prepares_items() {
+            items
+                .into_iter()
+                .map(Configuration::strip)
+                .collect::<Vec<T>>()
+                .into_iter()
+        } else {
+            items.into_iter()
+        }
+    }
+}
+
+/// Collects items in any order into a set with the default configuration.
+impl<T: Item> FromIterator<T> for SortedSet<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> SortedSet<T> {
+        match SortedSet::from_unsorted(Configuration::default(), iter.into_iter().collect()) {
+            BuildResult::Built(set) => *set,
+            BuildResult::SetFull => unreachable!("the default configuration has no max_elements"),
+        }
+    }
+}
+
+/// Adds every item, items that do not fit under `max_elements` are dropped.
+impl<T: Item> Extend<T> for SortedSet<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for item in iter {
+            self.add(item);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use collation::Collation;
+    use configuration::{BackendKind, Configuration};
+    use sketch::Sketch;
+    use std::cmp::Ordering;
+    use std::mem;
+    use std::ops::Bound;
+    use std::time::Instant;
+    use supported_term::SupportedTerm;
+    use supported_term::SupportedTerm::{Bitstring, Integer, Tuple};
+    use AddResult::{Added, Duplicate, SetFull};
+    use AppendBucketResult;
+    use BuildResult;
+    use ConcatResult;
+    use MergeResult;
+    use MoveRangeResult;
+    use RemoveResult::{InternalInconsistency, NotFound, Removed};
+    use RepairReport;
+    use SortedSet;
+
+    #[test]
+    fn test_sorted() {
+        let mut set: SortedSet = SortedSet::default();
+        let mut v: Vec<SupportedTerm> = Vec::new();
+
+        for i in 0..10_000 {
+            v.push(Bitstring(format!("test-item-{}", i)));
+            set.add(Bitstring(format!("test-item-{}", i)));
+        }
+        v.sort();
+        v.dedup();
+
+        let vec_from_set = set.to_vec();
+        assert_eq!(vec_from_set, v);
+    }
+
+    #[test]
+    fn test_plain_rust_items() {
+        // Plain integers have no dense universe, the bitset backend keeps them in buckets
+        for backend in [
+            BackendKind::Buckets,
+            BackendKind::BTree,
+            BackendKind::Bitset { universe: 1_000 },
+        ]
+        .iter()
+        {
+            let mut set: SortedSet<i64> = SortedSet::new(Configuration {
+                max_bucket_size: 4,
+                backend: *backend,
+                ..Configuration::default()
+            });
+
+            for i in (0..100).rev() {
+                assert_eq!(set.add(i * 2), Added(0));
+            }
+
+            assert_eq!(set.add(10), Duplicate(5));
+            assert_eq!(set.find_index(&42), Some(21));
+            assert_eq!(set.rank(&43), 22);
+            assert_eq!(set.remove(&0), Removed(0));
+            assert_eq!(set.slice(0, 3), vec![2, 4, 6]);
+            assert_eq!(set.size(), 99);
+        }
+    }
+
+    #[test]
+    fn test_collect_and_iterate() {
+        let set: SortedSet<i64> = vec![5, 3, 9, 3, 1].into_iter().collect();
+
+        assert_eq!(set.size(), 4);
+        assert_eq!(
+            (&set).into_iter().cloned().collect::<Vec<i64>>(),
+            vec![1, 3, 5, 9]
+        );
+        assert_eq!(set.into_iter().collect::<Vec<i64>>(), vec![1, 3, 5, 9]);
+    }
+
+    #[test]
+    fn test_extend_respects_max_elements() {
+        let mut set: SortedSet<i64> = SortedSet::new(Configuration {
+            max_elements: Some(3),
+            ..Configuration::default()
+        });
+
+        set.extend(vec![4, 2, 4, 8, 6]);
+
+        assert_eq!(set.to_vec(), vec![2, 4, 8]);
+    }
+
+    #[test]
+    fn test_bitset_moves_into_buckets_for_items_outside_the_universe() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 4,
+            backend: BackendKind::Bitset { universe: 64 },
+            ..Configuration::default()
+        });
+
+        for i in (0..20).rev() {
+            assert_eq!(set.add(Integer(i * 3)), Added(0));
+        }
+
+        let bitset_memory = set.memory();
+        assert_eq!(set.find_index(&Integer(30)), Some(10));
+        assert_eq!(set.rank(&Integer(31)), 11);
+        assert_eq!(set.remove(&Integer(0)), Removed(0));
+        assert_eq!(set.slice(0, 3), vec![Integer(3), Integer(6), Integer(9)]);
+        assert_eq!(set.layout().len(), 19);
+
+        assert_eq!(set.add(Integer(64)), Added(19));
+        assert!(set.memory() > bitset_memory);
+        assert_eq!(set.layout().len(), 7);
+        assert_eq!(set.add(Bitstring(String::from("a"))), Added(20));
+        assert_eq!(set.find_index(&Integer(30)), Some(9));
+        assert_eq!(set.size(), 21);
+    }
+
+    #[test]
+    fn test_bitset_keeps_the_bucket_options_when_moving_into_buckets() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 4,
+            tombstone_ratio: 0.5,
+            backend: BackendKind::Bitset { universe: 64 },
+            ..Configuration::default()
+        });
+
+        for i in 0..10 {
+            set.add(Integer(i));
+        }
+
+        assert_eq!(set.add(Integer(64)), Added(10));
+        assert_eq!(set.remove(&Integer(1)), Removed(1));
+        assert_eq!(set.tombstones(), 1);
+        assert_eq!(set.find_index(&Integer(2)), Some(1));
+    }
+
+    #[test]
+    fn test_index() {
+        let set: SortedSet<i64> = (0..500).rev().collect();
+
+        assert_eq!(set[0], 0);
+        assert_eq!(set[250], 250);
+        assert_eq!(set[499], 499);
+    }
+
+    #[test]
+    #[should_panic(expected = "index 3 out of range for SortedSet of size 3")]
+    fn test_index_out_of_range() {
+        let set: SortedSet<i64> = (0..3).collect();
+        let _ = set[3];
+    }
+
+    #[test]
+    fn test_first_and_last_skip_tombstones() {
+        let configuration = Configuration::builder()
+            .max_bucket_size(4)
+            .tombstone_ratio(0.9)
+            .build()
+            .unwrap();
+        let mut set: SortedSet<i64> = SortedSet::new(configuration);
+
+        assert_eq!(set.first(), None);
+        assert_eq!(set.last(), None);
+
+        set.extend(0..10);
+        set.remove(&0);
+        set.remove(&9);
+
+        assert_eq!(set.first(), Some(&1));
+        assert_eq!(set.last(), Some(&8));
+    }
+
+    #[test]
+    fn test_range() {
+        for backend in [BackendKind::Buckets, BackendKind::BTree].iter() {
+            let mut set: SortedSet<i64> = SortedSet::new(Configuration {
+                max_bucket_size: 4,
+                backend: *backend,
+                ..Configuration::default()
+            });
+            set.extend((0..50).map(|i| i * 2));
+
+            let range = |set: &SortedSet<i64>, bounds: (Bound<i64>, Bound<i64>)| {
+                set.range(bounds).cloned().collect::<Vec<i64>>()
+            };
+
+            assert_eq!(
+                set.range(10..16).cloned().collect::<Vec<i64>>(),
+                vec![10, 12, 14]
+            );
+            assert_eq!(
+                set.range(9..=16).cloned().collect::<Vec<i64>>(),
+                vec![10, 12, 14, 16]
+            );
+            assert_eq!(set.range(95..).cloned().collect::<Vec<i64>>(), vec![96, 98]);
+            assert_eq!(set.range(..3).cloned().collect::<Vec<i64>>(), vec![0, 2]);
+            assert_eq!(set.range(..).count(), 50);
+            assert_eq!(
+                range(&set, (Bound::Excluded(10), Bound::Excluded(16))),
+                vec![12, 14]
+            );
+            assert_eq!(
+                range(&set, (Bound::Excluded(11), Bound::Unbounded)).len(),
+                44
+            );
+            assert!(range(&set, (Bound::Included(40), Bound::Excluded(20))).is_empty());
+            assert!(set.range(200..).next().is_none());
+        }
+    }
+
+    #[test]
+    fn test_duplicate_item() {
+        let mut set: SortedSet = SortedSet::default();
+        assert_eq!(set.size(), 0);
+
+        let item = Bitstring(String::from("test-item"));
+        match set.add(item) {
+            Added(idx) => assert_eq!(idx, 0),
+            Duplicate(idx) => panic!(format!("Unexpected Duplicate({}) on initial add", idx)),
+            SetFull => panic!("Unexpected SetFull on initial add"),
+        };
+        assert_eq!(set.size(), 1);
+
+        let item = Bitstring(String::from("test-item"));
+        match set.add(item) {
+            Added(idx) => panic!(format!("Unexpected Added({}) on subsequent add", idx)),
+            Duplicate(idx) => assert_eq!(idx, 0),
+            SetFull => panic!("Unexpected SetFull on subsequent add"),
+        }
+        assert_eq!(set.size(), 1);
+    }
+
+    #[test]
+    fn test_add_rejects_new_items_when_full() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            max_elements: Some(5),
+            ..Configuration::default()
+        });
+
+        for i in 0..5 {
+            assert_eq!(set.add(Integer(i)), Added(i as usize));
+        }
+
+        assert_eq!(set.add(Integer(10)), SetFull);
+        assert_eq!(set.size(), 5);
+
+        // Duplicates do not grow the set so they are still reported
+        assert_eq!(set.add(Integer(3)), Duplicate(3));
+
+        // Once an item is removed there is room for a new one
+        set.remove(&Integer(0));
+        assert_eq!(set.add(Integer(10)), Added(4));
+        assert_eq!(set.size(), 5);
+    }
+
+    #[test]
+    fn test_append_bucket_rejects_items_past_max_elements() {
+        let mut set = SortedSet::empty(Configuration {
+            max_bucket_size: 5,
+            max_elements: Some(6),
+            ..Configuration::default()
+        });
+
+        assert_eq!(
+            set.append_bucket(vec![Integer(1), Integer(2), Integer(3)]),
+            AppendBucketResult::Ok
+        );
+        assert_eq!(
+            set.append_bucket(vec![Integer(4), Integer(5), Integer(6), Integer(7)]),
+            AppendBucketResult::SetFull
+        );
+        assert_eq!(set.size(), 3);
+    }
+
+    #[test]
+    fn test_retrieving_an_item() {
+        let mut set: SortedSet = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        });
+
+        set.add(Bitstring(String::from("aaa")));
+        set.add(Bitstring(String::from("bbb")));
+        set.add(Bitstring(String::from("ccc")));
+
+        assert_eq!(*set.at(0).unwrap(), Bitstring(String::from("aaa")));
+        assert_eq!(*set.at(1).unwrap(), Bitstring(String::from("bbb")));
+        assert_eq!(*set.at(2).unwrap(), Bitstring(String::from("ccc")));
+
+        match set.at(3) {
+            Some(item) => panic!(format!(
+                "Unexpected item found after end of set: {:?}",
+                item
+            )),
+            None => assert!(true),
+        };
+    }
+
+    #[test]
+    fn test_removing_a_present_item() {
+        let mut set: SortedSet = SortedSet::default();
+
+        set.add(Bitstring(String::from("aaa")));
+        set.add(Bitstring(String::from("bbb")));
+        set.add(Bitstring(String::from("ccc")));
+
+        assert_eq!(
+            set.to_vec(),
+            vec![
+                Bitstring(String::from("aaa")),
+                Bitstring(String::from("bbb")),
+                Bitstring(String::from("ccc")),
+            ]
+        );
+
+        let item = Bitstring(String::from("bbb"));
+
+        match set.remove(&item) {
+            Removed(idx) => assert_eq!(idx, 1),
+            NotFound => panic!(format!(
+                "Unexpected NotFound for item that should be present: {:?}",
+                item
+            )),
+            InternalInconsistency => panic!("Unexpected InternalInconsistency"),
+        }
+
+        assert_eq!(
+            set.to_vec(),
+            vec![
+                Bitstring(String::from("aaa")),
+                Bitstring(String::from("ccc")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_removing_a_not_found_item() {
+        let mut set: SortedSet = SortedSet::default();
+
+        set.add(Bitstring(String::from("aaa")));
+        set.add(Bitstring(String::from("bbb")));
+        set.add(Bitstring(String::from("ccc")));
+
+        assert_eq!(
+            set.to_vec(),
+            vec![
+                Bitstring(String::from("aaa")),
+                Bitstring(String::from("bbb")),
+                Bitstring(String::from("ccc")),
+            ]
+        );
+
+        let item = Bitstring(String::from("zzz"));
+
+        match set.remove(&item) {
+            Removed(idx) => panic!(
+                "Unexpected Removed({}) for item that should not be present",
+                idx
+            ),
+            NotFound => assert!(true),
+            InternalInconsistency => panic!("Unexpected InternalInconsistency"),
+        }
+
+        assert_eq!(
+            set.to_vec(),
+            vec![
+                Bitstring(String::from("aaa")),
+                Bitstring(String::from("bbb")),
+                Bitstring(String::from("ccc")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_removing_from_non_leading_bucket() {
+        let mut set: SortedSet = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        });
+
+        set.add(Bitstring(String::from("aaa")));
+        set.add(Bitstring(String::from("bbb")));
+        set.add(Bitstring(String::from("ccc")));
+        set.add(Bitstring(String::from("ddd")));
+        set.add(Bitstring(String::from("eee")));
+
+        assert_eq!(
+            set.to_vec(),
+            vec![
+                Bitstring(String::from("aaa")),
+                Bitstring(String::from("bbb")),
+                Bitstring(String::from("ccc")),
+                Bitstring(String::from("ddd")),
+                Bitstring(String::from("eee")),
+            ]
+        );
+
+        let item = Bitstring(String::from("ddd"));
+
+        match set.remove(&item) {
+            Removed(idx) => assert_eq!(idx, 3),
+            NotFound => panic!(format!(
+                "Unexpected NotFound for item that should be present: {:?}",
+                item
+            )),
+            InternalInconsistency => panic!("Unexpected InternalInconsistency"),
+        }
+
+        assert_eq!(
+            set.to_vec(),
+            vec![
+                Bitstring(String::from("aaa")),
+                Bitstring(String::from("bbb")),
+                Bitstring(String::from("ccc")),
+                Bitstring(String::from("eee")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_removing_decrements_the_size_on_successful_removal() {
+        let mut set = SortedSet::new(Configuration::default());
+
+        set.add(Bitstring(String::from("aaa")));
+        set.add(Bitstring(String::from("bbb")));
+        set.add(Bitstring(String::from("ccc")));
+        set.add(Bitstring(String::from("ddd")));
+        set.add(Bitstring(String::from("eee")));
+
+        // First assert that the size is what we expect
+        assert_eq!(set.size(), 5);
+
+        // Perform various removals until the set is empty, checking the size after each removal
+        set.remove(&Bitstring(String::from("ccc")));
+        assert_eq!(set.size(), 4);
+
+        set.remove(&Bitstring(String::from("eee")));
+        assert_eq!(set.size(), 3);
+
+        set.remove(&Bitstring(String::from("aaa")));
+        assert_eq!(set.size(), 2);
+
+        set.remove(&Bitstring(String::from("ddd")));
+        assert_eq!(set.size(), 1);
+
+        set.remove(&Bitstring(String::from("bbb")));
+        assert_eq!(set.size(), 0);
+    }
+
+    #[test]
+    fn test_multiple_removes_of_the_same_value_do_not_decrement_size() {
+        let mut set = SortedSet::new(Configuration::default());
+
+        set.add(Bitstring(String::from("aaa")));
+        set.add(Bitstring(String::from("bbb")));
+        set.add(Bitstring(String::from("ccc")));
+        set.add(Bitstring(String::from("ddd")));
+        set.add(Bitstring(String::from("eee")));
+
+        // First assert that the size is what we expect
+        assert_eq!(set.size(), 5);
+
+        // Perform various removals until the set is empty, checking the size after each removal
+        set.remove(&Bitstring(String::from("ccc")));
+        assert_eq!(set.size(), 4);
+
+        set.remove(&Bitstring(String::from("ccc")));
+        assert_eq!(set.size(), 4);
+
+        set.remove(&Bitstring(String::from("ccc")));
+        assert_eq!(set.size(), 4);
+    }
+
+    #[test]
+    fn test_removing_item_not_present_does_nothing() {
+        let mut set = SortedSet::new(Configuration::default());
+
+        set.add(Bitstring(String::from("aaa")));
+        set.add(Bitstring(String::from("bbb")));
+        set.add(Bitstring(String::from("ccc")));
+        set.add(Bitstring(String::from("ddd")));
+        set.add(Bitstring(String::from("eee")));
+
+        // First assert that the size is what we expect
+        assert_eq!(set.size(), 5);
+
+        let before_removal = set.to_vec();
+
+        // Remove an item that doesn't exist in the set and assert that nothing changes
+        set.remove(&Bitstring(String::from("xxx")));
+        assert_eq!(set.size(), 5);
+
+        let after_removal = set.to_vec();
+
+        assert_eq!(before_removal, after_removal);
+    }
+
+    #[test]
+    fn test_remove_at_many_removes_the_items_at_the_indexes() {
+        let configurations = vec![
+            Configuration::builder().max_bucket_size(5),
+            Configuration::builder()
+                .max_bucket_size(5)
+                .tombstone_ratio(0.5),
+            Configuration::builder().backend(BackendKind::BTree),
+        ];
+
+        for builder in configurations {
+            let mut set: SortedSet<i64> =
+                match SortedSet::from_sorted(builder.build().unwrap(), (0..30).collect()) {
+                    BuildResult::Built(set) => *set,
+                    BuildResult::SetFull => panic!("unexpected SetFull"),
+                };
+
+            // Unsorted, repeated and out of bounds indexes, emptying the buckets of 10..15
+            let removed = set.remove_at_many(&[29, 3, 10, 11, 12, 13, 14, 3, 0, 99]);
+
+            assert_eq!(removed, vec![0, 3, 10, 11, 12, 13, 14, 29]);
+            assert_eq!(set.size(), 22);
+            assert_eq!(
+                set.to_vec(),
+                (0..30)
+                    .filter(|item| !removed.contains(item))
+                    .collect::<Vec<i64>>()
+            );
+            assert_eq!(set.find_index(&15), Some(8));
+            assert_eq!(set.remove_at_many(&[22, 40]), Vec::<i64>::new());
+
+            assert_eq!(
+                set.remove_at_many(&(0..22).collect::<Vec<usize>>()).len(),
+                22
+            );
+            assert_eq!(set.size(), 0);
+            assert_eq!(set.add(7), Added(0));
+        }
+    }
+
+    #[test]
+    fn test_pop_first_and_pop_last_remove_the_ends() {
+        let mut set: SortedSet<i64> = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        });
+
+        for item in (0..10).rev() {
+            set.add(item);
+        }
+
+        assert_eq!(set.pop_first(), Some(0));
+        assert_eq!(set.pop_last(), Some(9));
+        assert_eq!(set.pop_first(), Some(1));
+        assert_eq!(set.size(), 7);
+        assert_eq!(set.to_vec(), (2..9).collect::<Vec<i64>>());
+
+        while set.pop_last().is_some() {}
+
+        assert_eq!(set.size(), 0);
+        assert_eq!(set.pop_first(), None);
+        assert_eq!(set.pop_last(), None);
+    }
+
+    #[test]
+    fn test_pop_at_removes_the_item_at_the_index() {
+        let configurations = vec![
+            Configuration::builder().max_bucket_size(4),
+            Configuration::builder()
+                .max_bucket_size(4)
+                .tombstone_ratio(0.5),
+            Configuration::builder().backend(BackendKind::BTree),
+        ];
+
+        for builder in configurations {
+            let mut set: SortedSet<i64> =
+                match SortedSet::from_sorted(builder.build().unwrap(), (0..20).collect()) {
+                    BuildResult::Built(set) => *set,
+                    BuildResult::SetFull => panic!("unexpected SetFull"),
+                };
+
+            assert_eq!(set.pop_at(5), Some(5));
+            assert_eq!(set.pop_at(5), Some(6));
+            assert_eq!(set.pop_at(17), Some(19));
+            assert_eq!(set.pop_at(17), None);
+            assert_eq!(set.size(), 17);
+            assert_eq!(set.find_index(&7), Some(5));
+        }
+    }
+
+    #[test]
+    fn test_mutations_update_last_modified() {
+        let mut set = SortedSet::default();
+        let (_, created_at) = set.last_modified();
+
+        let before_add = Instant::now();
+        set.add(Integer(1));
+        let (_, added_at) = set.last_modified();
+        assert!(added_at >= before_add);
+        assert!(added_at >= created_at);
+
+        let before_remove = Instant::now();
+        set.remove(&Integer(1));
+        let (_, removed_at) = set.last_modified();
+        assert!(removed_at >= before_remove);
+    }
+
+    #[test]
+    fn test_no_op_operations_do_not_update_last_modified() {
+        let mut set = SortedSet::default();
+        set.add(Integer(1));
+        let before = set.last_modified();
+
+        set.add(Integer(1));
+        set.remove(&Integer(2));
+        set.find_index(&Integer(1));
+
+        assert_eq!(set.last_modified(), before);
+    }
+
+    #[test]
+    fn test_meta_is_replaced_on_put() {
+        let mut set = SortedSet::default();
+        assert_eq!(set.meta(), None);
+
+        set.put_meta(Integer(1));
+        assert_eq!(set.meta(), Some(&Integer(1)));
+
+        set.put_meta(Bitstring(String::from("guild")));
+        assert_eq!(set.meta(), Some(&Bitstring(String::from("guild"))));
+        assert_eq!(set.size(), 0);
+    }
+
+    #[test]
+    fn test_freeze_and_thaw_toggle_the_frozen_flag() {
+        let mut set: SortedSet = SortedSet::default();
+        assert!(!set.is_frozen());
+
+        set.freeze();
+        assert!(set.is_frozen());
+
+        set.thaw();
+        assert!(!set.is_frozen());
+    }
+
+    #[test]
+    fn test_swap_exchanges_contents() {
+        let mut a = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        });
+        let mut b = SortedSet::default();
+
+        for i in 0..10 {
+            a.add(Integer(i));
+        }
+        b.add(Integer(100));
+        b.put_meta(Bitstring(String::from("b")));
+
+        a.swap(&mut b);
+
+        assert_eq!(a.to_vec(), vec![Integer(100)]);
+        assert_eq!(a.size(), 1);
+        assert_eq!(b.size(), 10);
+        assert_eq!(b.to_vec(), (0..10).map(Integer).collect::<Vec<_>>());
+
+        // Metadata stays with the set it was attached to
+        assert_eq!(a.meta(), None);
+        assert_eq!(b.meta(), Some(&Bitstring(String::from("b"))));
+
+        // The swapped contents keep working with the configuration they were built with
+        b.add(Integer(10));
+        assert_eq!(b.at(10), Some(&Integer(10)));
+    }
+
+    #[test]
+    fn test_drain_moves_items_out_and_leaves_an_empty_set() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        });
+
+        for i in (0..10).rev() {
+            set.add(Integer(i));
+        }
+
+        let drained = set.drain();
+
+        assert_eq!(drained, (0..10).map(Integer).collect::<Vec<_>>());
+        assert_eq!(set.size(), 0);
+        assert_eq!(set.to_vec(), vec![]);
+
+        // The drained set remains usable
+        assert_eq!(set.add(Integer(5)), Added(0));
+        assert_eq!(set.size(), 1);
+    }
+
+    #[test]
+    fn test_to_vec_rev_skips_removed_items() {
+        let mut buckets = SortedSet::new(Configuration {
+            max_bucket_size: 4,
+            tombstone_ratio: 0.5,
+            ..Configuration::default()
+        });
+        let mut tree = SortedSet::new(Configuration {
+            backend: BackendKind::BTree,
+            ..Configuration::default()
+        });
+
+        for i in 0..20 {
+            buckets.add(Integer(i));
+            tree.add(Integer(i));
+        }
+
+        for i in (0..20).step_by(3) {
+            buckets.remove(&Integer(i));
+            tree.remove(&Integer(i));
+        }
+
+        let mut expected = buckets.to_vec();
+        expected.reverse();
+
+        assert_eq!(buckets.to_vec_rev(), expected);
+        assert_eq!(tree.to_vec_rev(), expected);
+
+        let empty: SortedSet = SortedSet::default();
+        assert_eq!(empty.to_vec_rev(), vec![]);
+    }
+
+    #[test]
+    fn test_slice_desc_walks_back_from_the_end() {
+        let mut buckets = SortedSet::new(Configuration {
+            max_bucket_size: 4,
+            tombstone_ratio: 0.5,
+            ..Configuration::default()
+        });
+        let mut tree = SortedSet::new(Configuration {
+            backend: BackendKind::BTree,
+            ..Configuration::default()
+        });
+
+        for i in 0..20 {
+            buckets.add(Integer(i));
+            tree.add(Integer(i));
+        }
+
+        buckets.remove(&Integer(17));
+        tree.remove(&Integer(17));
+
+        for set in &[buckets, tree] {
+            assert_eq!(
+                set.slice_desc(0, 3),
+                vec![Integer(19), Integer(18), Integer(16)]
+            );
+            assert_eq!(
+                set.slice_desc(16, 10),
+                vec![Integer(2), Integer(1), Integer(0)]
+            );
+            assert_eq!(set.slice_desc(19, 1), vec![]);
+            assert_eq!(set.slice_desc(5, 0), vec![]);
+        }
+    }
+
+    #[test]
+    fn test_concat_moves_buckets_of_disjoint_sets() {
+        let mut a = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        });
+        let mut b = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        });
+
+        for i in 0..5 {
+            a.add(Integer(i));
+            b.add(Integer(i + 5));
+        }
+
+        assert_eq!(a.concat(&mut b), ConcatResult::Ok);
+
+        assert_eq!(a.to_vec(), (0..10).map(Integer).collect::<Vec<_>>());
+        assert_eq!(a.size(), 10);
+        assert_eq!(a.at(7), Some(&Integer(7)));
+
+        assert_eq!(b.size(), 0);
+        assert_eq!(b.to_vec(), vec![]);
+    }
+
+    #[test]
+    fn test_concat_into_empty_set() {
+        let mut a = SortedSet::default();
+        let mut b = SortedSet::default();
+
+        b.add(Integer(1));
+        b.add(Integer(2));
+
+        assert_eq!(a.concat(&mut b), ConcatResult::Ok);
+
+        assert_eq!(a.to_vec(), vec![Integer(1), Integer(2)]);
+        assert_eq!(a.add(Integer(0)), Added(0));
+    }
+
+    #[test]
+    fn test_concat_rejects_overlapping_sets() {
+        let mut a = SortedSet::default();
+        let mut b = SortedSet::default();
+
+        a.add(Integer(1));
+        a.add(Integer(5));
+        b.add(Integer(5));
+        b.add(Integer(6));
+
+        assert_eq!(a.concat(&mut b), ConcatResult::Overlapping);
+
+        assert_eq!(a.to_vec(), vec![Integer(1), Integer(5)]);
+        assert_eq!(b.to_vec(), vec![Integer(5), Integer(6)]);
+    }
+
+    #[test]
+    fn test_concat_rejects_sets_that_disagree_on_charlists() {
+        let mut a = SortedSet::new(Configuration {
+            charlists: true,
+            ..Configuration::default()
+        });
+        let mut b = SortedSet::default();
+
+        a.add(Integer(1));
+        b.add(SupportedTerm::List(vec![Integer(104), Integer(105)]));
+
+        assert_eq!(a.concat(&mut b), ConcatResult::CharlistsMismatch);
+        assert_eq!(b.concat(&mut a), ConcatResult::CharlistsMismatch);
+
+        assert_eq!(a.to_vec(), vec![Integer(1)]);
+        assert_eq!(b.size(), 1);
+    }
+
+    #[test]
+    fn test_partition_splits_around_the_pivot() {
+        for pivot in -1..12 {
+            let mut source = SortedSet::new(Configuration {
+                max_bucket_size: 3,
+                ..Configuration::default()
+            });
+            for i in 0..10 {
+                source.add(Integer(i));
+            }
+
+            let (mut lower, mut upper) = source.partition(&Integer(pivot));
+
+            let expected_lower: Vec<_> = (0..10).filter(|i| *i < pivot).map(Integer).collect();
+            let expected_upper: Vec<_> = (0..10).filter(|i| *i >= pivot).map(Integer).collect();
+
+            assert_eq!(lower.to_vec(), expected_lower);
+            assert_eq!(lower.size(), expected_lower.len());
+            assert_eq!(upper.to_vec(), expected_upper);
+            assert_eq!(upper.size(), expected_upper.len());
+            assert_eq!(source.size(), 0);
+            assert_eq!(source.to_vec(), vec![]);
+
+            // Both halves remain usable
+            lower.add(Integer(-5));
+            upper.add(Integer(50));
+            assert_eq!(lower.at(0), Some(&Integer(-5)));
+            assert_eq!(upper.at(upper.size() - 1), Some(&Integer(50)));
+        }
+    }
+
+    #[test]
+    fn test_partition_of_an_empty_set() {
+        let mut set = SortedSet::default();
+
+        let (lower, upper) = set.partition(&Integer(1));
+
+        assert_eq!(lower.size(), 0);
+        assert_eq!(upper.size(), 0);
+    }
+
+    #[test]
+    fn test_move_range_splices_into_a_disjoint_set() {
+        let configuration = Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        };
+        let mut a = SortedSet::new(configuration.clone());
+        let mut b = SortedSet::new(configuration);
+
+        for i in 0..20 {
+            a.add(Integer(i));
+        }
+        b.add(Integer(-1));
+        b.add(Integer(100));
+
+        assert_eq!(
+            a.move_range(&mut b, &Integer(5), &Integer(14)),
+            MoveRangeResult::Moved(10)
+        );
+
+        let mut expected_a: Vec<_> = (0..5).map(Integer).collect();
+        expected_a.extend((15..20).map(Integer));
+        let mut expected_b = vec![Integer(-1)];
+        expected_b.extend((5..15).map(Integer));
+        expected_b.push(Integer(100));
+
+        assert_eq!(a.to_vec(), expected_a);
+        assert_eq!(b.to_vec(), expected_b);
+        assert_eq!(a.size(), 10);
+        assert_eq!(b.size(), 12);
+        assert_eq!(b.find_index(&Integer(14)), Some(10));
+
+        // Both sets keep working after the splice
+        assert_eq!(a.add(Integer(7)), Added(5));
+        assert_eq!(b.add(Integer(50)), Added(11));
+    }
+
+    #[test]
+    fn test_move_range_adds_into_an_overlapping_set() {
+        let mut a = SortedSet::default();
+        let mut b = SortedSet::default();
+
+        for i in 0..10 {
+            a.add(Integer(i));
+        }
+        b.add(Integer(4));
+        b.add(Integer(6));
+
+        assert_eq!(
+            a.move_range(&mut b, &Integer(3), &Integer(6)),
+            MoveRangeResult::Moved(4)
+        );
+
+        assert_eq!(
+            a.to_vec(),
+            vec![0, 1, 2, 7, 8, 9]
+                .into_iter()
+                .map(Integer)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(b.to_vec(), (3..7).map(Integer).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_move_range_bounds_do_not_have_to_be_items() {
+        let mut a = SortedSet::default();
+        let mut b = SortedSet::default();
+
+        for i in 0..10 {
+            a.add(Integer(i * 10));
+        }
+
+        assert_eq!(
+            a.move_range(&mut b, &Integer(15), &Integer(45)),
+            MoveRangeResult::Moved(3)
+        );
+        assert_eq!(b.to_vec(), vec![Integer(20), Integer(30), Integer(40)]);
+
+        assert_eq!(
+            a.move_range(&mut b, &Integer(45), &Integer(15)),
+            MoveRangeResult::Moved(0)
+        );
+        assert_eq!(
+            a.move_range(&mut b, &Integer(91), &Integer(99)),
+            MoveRangeResult::Moved(0)
+        );
+        assert_eq!(a.size(), 7);
+    }
+
+    #[test]
+    fn test_move_range_respects_max_elements() {
+        let mut a = SortedSet::default();
+        let mut b = SortedSet::new(Configuration {
+            max_elements: Some(2),
+            ..Configuration::default()
+        });
+
+        for i in 0..5 {
+            a.add(Integer(i));
+        }
+
+        assert_eq!(
+            a.move_range(&mut b, &Integer(0), &Integer(2)),
+            MoveRangeResult::SetFull
+        );
+        assert_eq!(a.size(), 5);
+        assert_eq!(b.size(), 0);
+    }
+
+    #[test]
+    fn test_multi_slice_matches_individual_slices() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        });
+
+        for i in 0..20 {
+            set.add(Integer(i));
+        }
+
+        let windows = vec![(15, 10), (0, 3), (4, 0), (2, 7), (25, 5), (19, 1), (2, 7)];
+        let expected: Vec<_> = windows
+            .iter()
+            .map(|&(start, amount)| set.slice(start, amount))
+            .collect();
+
+        assert_eq!(set.multi_slice(&windows), expected);
+    }
+
+    #[test]
+    fn test_slice_step_matches_filtered_slices() {
+        for &backend in &[BackendKind::Buckets, BackendKind::BTree] {
+            let mut set = SortedSet::new(Configuration {
+                max_bucket_size: 3,
+                backend,
+                ..Configuration::default()
+            });
+
+            for i in 0..20 {
+                set.add(Integer(i));
+            }
+
+            for &(start, amount, step) in
+                &[(0, 20, 1), (0, 20, 5), (3, 10, 4), (18, 10, 3), (25, 5, 2)]
+            {
+                let expected: Vec<_> = set.slice(start, amount).into_iter().step_by(step).collect();
+
+                assert_eq!(set.slice_step(start, amount, step), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn test_chunks() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        });
+
+        for i in 0..7 {
+            set.add(Integer(i));
+        }
+
+        let chunks = set.chunks(3);
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[1], vec![Integer(3), Integer(4), Integer(5)]);
+        assert_eq!(chunks[2], vec![Integer(6)]);
+        assert_eq!(set.chunks(100), vec![set.to_vec()]);
+        assert!(SortedSet::<SupportedTerm>::default().chunks(3).is_empty());
+    }
+
+    #[test]
+    fn test_multi_slice_empty_set() {
+        let set: SortedSet = SortedSet::empty(Configuration::default());
+
+        assert_eq!(set.multi_slice(&[(0, 5), (3, 1)]), vec![vec![], vec![]]);
+        assert_eq!(set.multi_slice(&[]), Vec::<Vec<SupportedTerm>>::new());
+    }
+
+    #[test]
+    fn test_add_with_hint_matches_add() {
+        let items = [5, 1, 9, 3, 7, 2, 8, 4, 6, 0, 10, 5];
+
+        // Every hint, right or wrong, must produce the same result as a plain add
+        for hint in 0..15 {
+            let mut hinted = SortedSet::new(Configuration {
+                max_bucket_size: 3,
+                ..Configuration::default()
+            });
+            let mut plain = SortedSet::new(Configuration {
+                max_bucket_size: 3,
+                ..Configuration::default()
+            });
+
+            for item in items.iter() {
+                assert_eq!(
+                    hinted.add_with_hint(Integer(*item), hint),
+                    plain.add(Integer(*item))
+                );
+            }
+
+            assert_eq!(hinted.to_vec(), plain.to_vec());
+            assert_eq!(hinted.size(), plain.size());
+        }
+    }
+
+    #[test]
+    fn test_add_with_hint_sorted_feed() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 4,
+            ..Configuration::default()
+        });
+
+        for i in 0..20 {
+            assert_eq!(
+                set.add_with_hint(Integer(i * 2), i as usize),
+                Added(i as usize)
+            );
+        }
+
+        assert_eq!(set.add_with_hint(Integer(6), 3), Duplicate(3));
+        assert_eq!(set.add_with_hint(Integer(7), 4), Added(4));
+        assert_eq!(set.at(4), Some(&Integer(7)));
+        assert_eq!(set.size(), 21);
+    }
+
+    #[test]
+    fn test_mutations_increment_the_version() {
+        let mut set = SortedSet::default();
+        assert_eq!(set.version(), 0);
+
+        set.add(Integer(1));
+        assert_eq!(set.version(), 1);
+
+        set.add(Integer(1));
+        set.remove(&Integer(2));
+        assert_eq!(set.version(), 1);
+
+        set.remove(&Integer(1));
+        assert_eq!(set.version(), 2);
+    }
+
+    #[test]
+    fn test_memory_grows_with_the_set() {
+        let mut set = SortedSet::default();
+        let initial = set.memory();
+
+        assert!(initial >= mem::size_of::<SortedSet>());
+
+        for i in 0..1000 {
+            set.add(Integer(i));
+        }
+
+        assert!(set.memory() >= initial + 1000 * mem::size_of::<SupportedTerm>());
+    }
+
+    #[test]
+    fn test_from_sorted_packs_buckets() {
+        let set = match SortedSet::from_sorted(
+            Configuration {
+                max_bucket_size: 3,
+                ..Configuration::default()
+            },
+            (0..7).map(Integer).collect(),
+        ) {
+            BuildResult::Built(set) => *set,
+            BuildResult::SetFull => panic!("unexpected SetFull"),
+        };
+
+        assert_eq!(set.size(), 7);
+        assert_eq!(set.to_vec(), (0..7).map(Integer).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_from_sorted_respects_max_elements() {
+        let result = SortedSet::from_sorted(
+            Configuration {
+                max_elements: Some(2),
+                ..Configuration::default()
+            },
+            (0..3).map(Integer).collect(),
+        );
+
+        match result {
+            BuildResult::SetFull => {}
+            BuildResult::Built(_) => panic!("expected SetFull"),
+        }
+    }
+
+    #[test]
+    fn test_union_merges_both_sets() {
+        let mut a = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        });
+        let mut b = SortedSet::default();
+
+        for i in 0..10 {
+            a.add(Integer(i * 2));
+            b.add(Integer(i * 3));
+        }
+
+        let union = match a.union(&b) {
+            BuildResult::Built(set) => *set,
+            BuildResult::SetFull => panic!("unexpected SetFull"),
+        };
+
+        let mut expected: Vec<i64> = (0..10)
+            .map(|i| i * 2)
+            .chain((0..10).map(|i| i * 3))
+            .collect();
+        expected.sort();
+        expected.dedup();
+
+        assert_eq!(
+            union.to_vec(),
+            expected.into_iter().map(Integer).collect::<Vec<_>>()
+        );
+        assert_eq!(union.size(), union.to_vec().len());
+
+        // The sources are untouched
+        assert_eq!(a.size(), 10);
+        assert_eq!(b.size(), 10);
+    }
+
+    #[test]
+    fn test_intersection_keeps_the_common_items() {
+        let mut a = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        });
+        let mut b = SortedSet::default();
+
+        for i in 0..20 {
+            a.add(Integer(i * 2));
+            b.add(Integer(i * 3));
+        }
+
+        let intersection = match a.intersection(&b) {
+            BuildResult::Built(set) => *set,
+            BuildResult::SetFull => panic!("unexpected SetFull"),
+        };
+
+        assert_eq!(
+            intersection.to_vec(),
+            (0..7).map(|i| Integer(i * 6)).collect::<Vec<_>>()
+        );
+        assert_eq!(intersection.configuration().max_bucket_size, 3);
+
+        // Sets that order their items differently are intersected item by item
+        let mut collated = SortedSet::new(
+            Configuration::builder()
+                .collation(Collation::natural())
+                .build()
+                .unwrap(),
+        );
+        collated.add(Integer(6));
+        collated.add(Integer(7));
+        collated.add(Bitstring(String::from("6")));
+
+        let intersection = match a.intersection(&collated) {
+            BuildResult::Built(set) => *set,
+            BuildResult::SetFull => panic!("unexpected SetFull"),
+        };
+
+        assert_eq!(intersection.to_vec(), vec![Integer(6)]);
+        assert_eq!(a.size(), 20);
+        assert_eq!(b.size(), 20);
+    }
+
+    #[test]
+    fn test_difference_drops_the_items_of_the_other_set() {
+        let mut a = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        });
+        let mut b = SortedSet::default();
+
+        for i in 0..20 {
+            a.add(Integer(i * 2));
+            b.add(Integer(i * 3));
+        }
+
+        let difference = match a.difference(&b) {
+            BuildResult::Built(set) => *set,
+            BuildResult::SetFull => panic!("unexpected SetFull"),
+        };
+
+        assert_eq!(
+            difference.to_vec(),
+            (0..20)
+                .map(|i| i * 2)
+                .filter(|i| i % 6 != 0 || *i >= 60)
+                .map(Integer)
+                .collect::<Vec<_>>()
+        );
+
+        let empty = match a.difference(&a) {
+            BuildResult::Built(set) => *set,
+            BuildResult::SetFull => panic!("unexpected SetFull"),
+        };
+
+        assert_eq!(empty.size(), 0);
+        assert_eq!(a.size(), 20);
+        assert_eq!(b.size(), 20);
+    }
+
+    #[test]
+    fn test_same_items_ignores_the_layout() {
+        let mut a = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        });
+        let mut b = SortedSet::new(Configuration {
+            backend: BackendKind::BTree,
+            ..Configuration::default()
+        });
+
+        for i in 0..30 {
+            a.add(Tuple(vec![Integer(i % 3), Integer(i)]));
+            b.add(Tuple(vec![Integer((29 - i) % 3), Integer(29 - i)]));
+        }
+
+        assert!(a.same_items(&b));
+        assert!(b.same_items(&a));
+
+        b.remove(&Tuple(vec![Integer(0), Integer(0)]));
+        assert!(!a.same_items(&b));
+
+        b.add(Tuple(vec![Integer(0), Integer(1)]));
+        assert!(!a.same_items(&b));
+        assert!(SortedSet::<i64>::default().same_items(&SortedSet::default()));
+    }
+
+    #[test]
+    fn test_diff_splits_added_and_removed_items() {
+        let mut old = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            ..Configuration::default()
+        });
+        let mut new = SortedSet::default();
+
+        for i in 0..20 {
+            old.add(Integer(i * 2));
+            new.add(Integer(i * 3));
+        }
+
+        let (added, removed) = old.diff(&new);
+
+        assert_eq!(
+            added,
+            (0..20)
+                .map(|i| i * 3)
+                .filter(|i| i % 2 != 0 || *i >= 40)
+                .map(Integer)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            removed,
+            (0..20)
+                .map(|i| i * 2)
+                .filter(|i| i % 3 != 0 || *i >= 60)
+                .map(Integer)
+                .collect::<Vec<_>>()
+        );
+
+        assert_eq!(old.diff(&old), (vec![], vec![]));
+    }
+
+    #[test]
+    fn test_compare_items_orders_sets_like_lists() {
+        let mut a = SortedSet::new(Configuration {
+            max_bucket_size: 2,
+            ..Configuration::default()
+        });
+        let mut prefix = SortedSet::default();
+        let mut larger = SortedSet::default();
+
+        for i in 1..4 {
+            a.add(Integer(i));
+        }
+        prefix.add(Integer(1));
+        larger.add(Integer(1));
+        larger.add(Integer(4));
+
+        for &(left, right) in [(&a, &prefix), (&larger, &a)].iter() {
+            assert_eq!(left.compare_items(right), Ordering::Greater);
+            assert_eq!(right.compare_items(left), Ordering::Less);
+            assert_eq!(left.compare_items(left), Ordering::Equal);
+        }
+
+        let empty: SortedSet<SupportedTerm> = SortedSet::default();
+        assert_eq!(empty.compare_items(&prefix), Ordering::Less);
+    }
+
+    #[test]
+    fn test_contains_agrees_with_find_index() {
+        let configurations = vec![
+            Configuration::builder().max_bucket_size(4),
+            Configuration::builder()
+                .max_bucket_size(4)
+                .tombstone_ratio(0.9),
+            Configuration::builder()
+                .max_bucket_size(4)
+                .interpolation_search(true),
+            Configuration::builder().backend(BackendKind::BTree),
+            Configuration::builder().backend(BackendKind::Bitset { universe: 100 }),
+        ];
+
+        for builder in configurations {
+            let mut set = SortedSet::new(builder.build().unwrap());
+
+            for i in 0..40 {
+                set.add(Integer(i * 2));
+            }
+            for i in 0..10 {
+                set.remove(&Integer(i * 8));
+            }
+
+            for i in -2..100 {
+                let item = Integer(i);
+                assert_eq!(set.contains(&item), set.find_index(&item).is_some());
+            }
+        }
+
+        assert!(!SortedSet::default().contains(&Integer(1)));
+    }
+
+    #[test]
+    fn test_contains_many_answers_in_the_given_order() {
+        let configurations = vec![
+            Configuration::builder().max_bucket_size(3),
+            Configuration::builder()
+                .max_bucket_size(3)
+                .tombstone_ratio(0.9),
+            Configuration::builder().backend(BackendKind::BTree),
+        ];
+
+        for builder in configurations {
+            let mut set = SortedSet::new(builder.build().unwrap());
+
+            for i in 0..30 {
+                set.add(Integer(i * 3));
+            }
+            set.remove(&Integer(30));
+
+            let items: Vec<SupportedTerm> = [88, 3, -1, 30, 87, 3, 200, 0, 31]
+                .iter()
+                .map(|&i| Integer(i))
+                .collect();
+
+            assert_eq!(
+                set.contains_many(&items),
+                vec![false, true, false, false, true, true, false, true, false]
+            );
+        }
+
+        let empty: SortedSet = SortedSet::default();
+        assert_eq!(empty.contains_many(&[Integer(1)]), vec![false]);
+        assert_eq!(empty.contains_many(&[]), Vec::<bool>::new());
+    }
+
+    #[test]
+    fn test_rank_and_rank_past_bracket_the_item() {
+        let configurations = vec![
+            Configuration::builder().max_bucket_size(3),
+            Configuration::builder().backend(BackendKind::BTree),
+            Configuration::builder().backend(BackendKind::Bitset { universe: 100 }),
+        ];
+
+        for builder in configurations {
+            let mut set = SortedSet::new(builder.build().unwrap());
+
+            for i in 0..20 {
+                set.add(Integer(i * 2));
+            }
+
+            assert_eq!(
+                (set.rank(&Integer(-1)), set.rank_past(&Integer(-1))),
+                (0, 0)
+            );
+            assert_eq!((set.rank(&Integer(0)), set.rank_past(&Integer(0))), (0, 1));
+            assert_eq!((set.rank(&Integer(7)), set.rank_past(&Integer(7))), (4, 4));
+            assert_eq!((set.rank(&Integer(8)), set.rank_past(&Integer(8))), (4, 5));
+            assert_eq!(
+                (set.rank(&Integer(99)), set.rank_past(&Integer(99))),
+                (20, 20)
+            );
+        }
+    }
+
+    #[test]
+    fn test_floor_and_ceiling_match_a_scan() {
+        let configurations = vec![
+            Configuration::builder().max_bucket_size(3),
+            Configuration::builder()
+                .max_bucket_size(3)
+                .tombstone_ratio(0.9),
+            Configuration::builder().backend(BackendKind::BTree),
+            Configuration::builder().backend(BackendKind::Bitset { universe: 100 }),
+        ];
+
+        for builder in configurations {
+            let mut set = SortedSet::new(builder.build().unwrap());
+
+            for i in 0..30 {
+                set.add(Integer(i * 3));
+            }
+            for i in 3..9 {
+                // Empties whole buckets of the tombstoned sets
+                set.remove(&Integer(i * 3));
+            }
+
+            let items = set.to_vec();
+
+            for i in -2..95 {
+                let item = Integer(i);
+
+                assert_eq!(
+                    set.floor(&item),
+                    items.iter().rev().find(|current| **current <= item)
+                );
+                assert_eq!(
+                    set.ceiling(&item),
+                    items.iter().find(|current| **current >= item)
+                );
+            }
+        }
+
+        let empty: SortedSet = SortedSet::default();
+        assert_eq!(empty.floor(&Integer(1)), None);
+        assert_eq!(empty.ceiling(&Integer(1)), None);
+    }
+
+    #[test]
+    fn test_search_returns_the_insertion_point_on_a_miss() {
+        let configurations = vec![
+            Configuration::builder().max_bucket_size(3),
+            Configuration::builder()
+                .max_bucket_size(3)
+                .tombstone_ratio(0.9),
+            Configuration::builder().backend(BackendKind::BTree),
+            Configuration::builder().backend(BackendKind::Bitset { universe: 100 }),
+        ];
+
+        for builder in configurations {
+            let mut set = SortedSet::new(builder.build().unwrap());
+
+            for i in 0..20 {
+                set.add(Integer(i * 2));
+            }
+            set.remove(&Integer(10));
+
+            let items = set.to_vec();
+
+            for i in -2..45 {
+                let item = Integer(i);
+                assert_eq!(set.search(&item), items.binary_search(&item));
+            }
+        }
+
+        let empty: SortedSet = SortedSet::default();
+        assert_eq!(empty.search(&Integer(1)), Err(0));
+    }
+
+    #[test]
+    fn test_at_many_matches_at() {
+        let configurations = vec![
+            Configuration::builder().max_bucket_size(3),
+            Configuration::builder()
+                .max_bucket_size(3)
+                .tombstone_ratio(0.9),
+            Configuration::builder().backend(BackendKind::BTree),
+        ];
+
+        for builder in configurations {
+            let mut set = SortedSet::new(builder.build().unwrap());
+
+            for i in 0..30 {
+                set.add(Integer(i));
+            }
+            for i in 5..12 {
+                set.remove(&Integer(i));
+            }
+
+            let indexes = [22, 0, 5, 23, 5, 40, 4, 12];
+            let expected: Vec<Option<&SupportedTerm>> =
+                indexes.iter().map(|&index| set.at(index)).collect();
+
+            assert_eq!(set.at_many(&indexes), expected);
+            assert_eq!(set.at_many(&[]), Vec::<Option<&SupportedTerm>>::new());
+        }
+    }
+
+    #[test]
+    fn test_resolve_index_counts_negative_indexes_from_the_end() {
+        let mut set = SortedSet::default();
+
+        for i in 0..5 {
+            set.add(Integer(i));
+        }
+
+        assert_eq!(set.resolve_index(0), Some(0));
+        assert_eq!(set.resolve_index(4), Some(4));
+        assert_eq!(set.resolve_index(5), None);
+        assert_eq!(set.resolve_index(-1), Some(4));
+        assert_eq!(set.resolve_index(-5), Some(0));
+        assert_eq!(set.resolve_index(-6), None);
+        assert_eq!(set.resolve_index(i64::MIN), None);
+        assert_eq!(SortedSet::<i64>::default().resolve_index(-1), None);
+    }
+
+    #[test]
+    fn test_is_disjoint_stops_at_a_common_item() {
+        let configurations = vec![
+            Configuration::builder().max_bucket_size(4),
+            Configuration::builder()
+                .max_bucket_size(4)
+                .tombstone_ratio(0.9),
+            Configuration::builder().backend(BackendKind::BTree),
+        ];
+
+        for builder in configurations {
+            let configuration = builder.build().unwrap();
+            let mut evens = SortedSet::new(configuration.clone());
+            let mut odds = SortedSet::new(configuration.clone());
+
+            for i in 0..50 {
+                evens.add(i * 2);
+                odds.add(i * 2 + 1);
+            }
+
+            assert!(evens.is_disjoint(&odds));
+            assert!(odds.is_disjoint(&evens));
+            assert!(evens.is_disjoint(&SortedSet::new(configuration.clone())));
+            assert!(!evens.is_disjoint(&evens));
+
+            odds.add(98);
+            assert!(!evens.is_disjoint(&odds));
+            assert!(!odds.is_disjoint(&evens));
+
+            // Removed items are not common items, even while their tombstones are in place
+            odds.remove(&98);
+            assert!(evens.is_disjoint(&odds));
+        }
+    }
+
+    #[test]
+    fn test_merge_from_adds_the_items_of_the_other_set() {
+        let mut a: SortedSet<i64> = SortedSet::new(
+            Configuration::builder()
+                .max_bucket_size(4)
+                .stable_ids(true)
+                .max_elements(40)
+                .build()
+                .unwrap(),
+        );
+        let mut b = SortedSet::default();
+
+        for i in 0..10 {
+            a.add(i * 2);
+            b.add(i * 3);
+        }
+
+        let id = a.id_of(&4);
+
+        assert_eq!(a.merge_from(&b), MergeResult::Merged(6));
+        assert_eq!(a.merge_from(&b), MergeResult::Merged(0));
+
+        let mut expected: Vec<i64> = (0..10)
+            .map(|i| i * 2)
+            .chain((0..10).map(|i| i * 3))
+            .collect();
+        expected.sort();
+        expected.dedup();
+
+        assert_eq!(a.to_vec(), expected);
+        assert_eq!(b.size(), 10);
+        assert_eq!(a.id_of(&4), id);
+        assert!(a.id_of(&27).is_some());
+        assert!(a.bucket_stats().max_size <= 4);
+
+        let mut c = SortedSet::default();
+        c.extend(100..130);
+
+        assert_eq!(a.merge_from(&c), MergeResult::SetFull);
+        assert_eq!(a.to_vec(), expected);
+    }
+
+    #[test]
+    fn test_into_iter_strips_collation() {
+        let set: SortedSet = match SortedSet::from_unsorted(
+            Configuration {
+                collation: Collation::new("de"),
+                ..Configuration::default()
+            },
+            vec![Bitstring(String::from("b")), Bitstring(String::from("ä"))],
+        ) {
+            BuildResult::Built(set) => *set,
+            BuildResult::SetFull => panic!("Unexpected SetFull"),
+        };
+
+        assert_eq!(
+            set.into_iter().collect::<Vec<SupportedTerm>>(),
+            vec![Bitstring(String::from("ä")), Bitstring(String::from("b"))]
+        );
+    }
+
+    #[test]
+    fn test_collation_orders_strings_by_locale() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 2,
+            collation: Collation::new("de"),
+            ..Configuration::default()
+        });
+
+        assert_eq!(set.add(Bitstring(String::from("Zebra"))), Added(0));
+        assert_eq!(set.add(Bitstring(String::from("Äpfel"))), Added(0));
+        assert_eq!(set.add(Bitstring(String::from("apple"))), Added(1));
+        assert_eq!(set.add(Bitstring(String::from("Banana"))), Added(2));
+
+        let words: Vec<SupportedTerm> = set.to_vec().into_iter().map(Collation::strip).collect();
+        assert_eq!(
+            words,
+            vec![
+                Bitstring(String::from("Äpfel")),
+                Bitstring(String::from("apple")),
+                Bitstring(String::from("Banana")),
+                Bitstring(String::from("Zebra")),
+            ]
+        );
+
+        assert_eq!(set.find_index(&Bitstring(String::from("Banana"))), Some(2));
+        assert_eq!(set.remove(&Bitstring(String::from("Äpfel"))), Removed(0));
+        assert_eq!(set.find_index(&Bitstring(String::from("Zebra"))), Some(2));
+    }
+
+    #[test]
+    fn test_sentinels_sort_before_and_after_everything() {
+        let mut set = SortedSet::new(Configuration::default());
+
+        set.add(Bitstring(String::from("z")));
+        set.add(SupportedTerm::atom(String::from("infinity")));
+        set.add(SupportedTerm::atom(String::from("atom")));
+        set.add(SupportedTerm::atom(String::from("neg_infinity")));
+        set.add(Integer(-5));
+
+        assert_eq!(set.find_index(&SupportedTerm::NegInfinity), Some(0));
+        assert_eq!(set.find_index(&SupportedTerm::Infinity), Some(4));
+        assert_eq!(set.rank(&SupportedTerm::Tuple(vec![])), 3);
+
+        let key = |inner: SupportedTerm| SupportedTerm::Tuple(vec![Integer(1), inner]);
+        assert!(key(SupportedTerm::NegInfinity) < key(Integer(i64::MIN)));
+        assert!(key(SupportedTerm::Infinity) > key(Bitstring(String::from("z"))));
+    }
+
+    #[test]
+    fn test_charlists_are_stored_as_bitstrings() {
+        let chars = |text: &str| -> SupportedTerm {
+            SupportedTerm::List(text.bytes().map(|byte| Integer(byte as i64)).collect())
+        };
+
+        let mut set = SortedSet::new(Configuration {
+            charlists: true,
+            ..Configuration::default()
+        });
+
+        assert_eq!(set.add(chars("bees")), Added(0));
+        assert_eq!(set.add(Bitstring(String::from("bees"))), Duplicate(0));
+        assert_eq!(set.add(Bitstring(String::from("ants"))), Added(0));
+        assert_eq!(set.add(SupportedTerm::List(vec![Integer(1)])), Added(0));
+        assert_eq!(set.add(SupportedTerm::List(vec![])), Added(0));
+        assert_eq!(
+            set.add(SupportedTerm::Tuple(vec![chars("ab"), Integer(1)])),
+            Added(0)
+        );
+
+        assert_eq!(set.find_index(&Bitstring(String::from("bees"))), Some(4));
+        assert_eq!(set.find_index(&chars("ants")), Some(3));
+        assert_eq!(
+            set.find_index(&SupportedTerm::Tuple(vec![
+                Bitstring(String::from("ab")),
+                Integer(1)
+            ])),
+            Some(0)
+        );
+        assert_eq!(set.remove(&chars("bees")), Removed(4));
+    }
+
+    #[test]
+    fn test_btree_backend_matches_buckets() {
+        let mut buckets = SortedSet::new(Configuration {
+            max_bucket_size: 5,
+            ..Configuration::default()
+        });
+        let mut tree = SortedSet::new(Configuration {
+            backend: BackendKind::BTree,
+            ..Configuration::default()
+        });
+
+        for i in 0..2_000 {
+            let item = Integer((i * 7919) % 1_000);
+            assert_eq!(tree.add(item.clone()), buckets.add(item));
+        }
+
+        for i in 0..500 {
+            let item = Integer(i * 2);
+            assert_eq!(tree.remove(&item), buckets.remove(&item));
+        }
+
+        assert_eq!(tree.size(), buckets.size());
+        assert_eq!(tree.to_vec(), buckets.to_vec());
+        assert_eq!(tree.at(250), buckets.at(250));
+        assert_eq!(tree.slice(495, 10), buckets.slice(495, 10));
+        assert_eq!(
+            tree.find_index(&Integer(777)),
+            buckets.find_index(&Integer(777))
+        );
+        assert_eq!(
+            tree.multi_slice(&[(10, 5), (0, 2), (499, 5)]),
+            buckets.multi_slice(&[(10, 5), (0, 2), (499, 5)])
+        );
+
+        let (mut lower, mut upper) = tree.partition(&Integer(600));
+        assert_eq!(lower.size(), 300);
+        assert_eq!(upper.at(0), Some(&Integer(601)));
+        assert_eq!(tree.size(), 0);
+
+        assert_eq!(lower.concat(&mut upper), ConcatResult::Ok);
+        assert_eq!(lower.to_vec(), buckets.to_vec());
+        assert_eq!(upper.size(), 0);
+    }
+
+    #[test]
+    fn test_stable_ids_survive_rank_shifts() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 3,
+            stable_ids: true,
+            ..Configuration::default()
+        });
+
+        set.add(Integer(10));
+        let id = set.id_of(&Integer(10)).unwrap();
+
+        for i in 0..10 {
+            set.add(Integer(i));
+        }
+
+        assert_eq!(set.id_of(&Integer(10)), Some(id));
+        assert_eq!(set.get_by_id(id), Some(&Integer(10)));
+
+        set.remove(&Integer(10));
+        assert_eq!(set.id_of(&Integer(10)), None);
+        assert_eq!(set.get_by_id(id), None);
+
+        // A re-added item gets a new ID
+        set.add(Integer(10));
+        assert!(set.id_of(&Integer(10)).unwrap() > id);
+
+        let kept = set.id_of(&Integer(7)).unwrap();
+        let (lower, upper) = set.partition(&Integer(5));
+        assert_eq!(upper.id_of(&Integer(7)), Some(kept));
+        assert_eq!(lower.id_of(&Integer(7)), None);
+        assert!(lower.id_of(&Integer(4)).is_some());
+    }
+
+    #[test]
+    fn test_stable_ids_are_disabled_by_default() {
+        let mut set = SortedSet::default();
+        set.add(Integer(1));
+
+        assert!(!set.has_stable_ids());
+        assert_eq!(set.id_of(&Integer(1)), None);
+    }
+
+    #[test]
+    fn test_sketch_follows_the_items() {
+        // A maintained sketch estimates exactly what a sketch built from the items does
+        fn estimate_of(range: ::std::ops::Range<i64>) -> u64 {
+            let items: Vec<SupportedTerm> = range.map(Integer).collect();
+            Sketch::of(items.iter()).estimate()
+        }
+
+        let configuration = Configuration {
+            cardinality_sketch: true,
+            ..Configuration::default()
+        };
+        let mut set = SortedSet::new(configuration.clone());
+
+        for i in 0..100 {
+            set.add(Integer(i));
+        }
+        set.add(Integer(1));
+        assert_eq!(set.sketch().unwrap().estimate(), estimate_of(0..100));
+
+        for i in 0..50 {
+            set.remove(&Integer(i));
+        }
+        assert_eq!(set.sketch().unwrap().estimate(), estimate_of(50..100));
+
+        let (lower, upper) = set.partition(&Integer(75));
+        assert_eq!(lower.sketch().unwrap().estimate(), estimate_of(50..75));
+        assert_eq!(upper.sketch().unwrap().estimate(), estimate_of(75..100));
+
+        let built = match SortedSet::from_sorted(configuration, vec![Integer(1), Integer(2)]) {
+            BuildResult::Built(set) => *set,
+            BuildResult::SetFull => panic!("no max_elements"),
+        };
+        assert_eq!(built.sketch().unwrap().estimate(), estimate_of(1..3));
+        assert!(SortedSet::<i64>::default().sketch().is_none());
+    }
+
+    #[test]
+    fn test_tombstones_are_skipped_and_compacted() {
+        let configuration = Configuration::builder()
+            .max_bucket_size(10)
+            .tombstone_ratio(0.5)
+            .build()
+            .unwrap();
+        let mut set: SortedSet<i64> = match SortedSet::from_sorted(configuration, (0..40).collect())
+        {
+            BuildResult::Built(set) => *set,
+            BuildResult::SetFull => panic!("unexpected SetFull"),
+        };
+
+        for i in (0..40).step_by(3) {
+            assert_eq!(set.remove(&i), Removed(i as usize - i as usize / 3));
+        }
+
+        let expected: Vec<i64> = (0..40).filter(|i| i % 3 != 0).collect();
+        assert!(set.tombstones() > 0);
+        assert_eq!(set.size(), expected.len());
+        assert_eq!(set.to_vec(), expected);
+        assert_eq!(set.at(5), Some(&expected[5]));
+        assert_eq!(set.slice(7, 4), expected[7..11].to_vec());
+        assert_eq!(set.find_index(&3), None);
+        assert_eq!(set.find_index(&4), Some(2));
+        assert_eq!(set.rank(&3), 2);
+        assert_eq!(set.at(0), Some(&1));
+        assert_eq!(set.at(expected.len() - 1), Some(&38));
+        assert_eq!(
+            set.layout().iter().map(|run| run.len()).sum::<usize>(),
+            expected.len()
+        );
+
+        // Adding a removed item takes the place of its tombstone
+        let tombstones = set.tombstones();
+        assert_eq!(set.add(9), Added(6));
+        assert_eq!(set.tombstones(), tombstones - 1);
+
+        assert_eq!(set.compact(), tombstones - 1);
+        assert_eq!(set.tombstones(), 0);
+        assert_eq!(set.find_index(&9), Some(6));
+        assert_eq!(set.size(), expected.len() + 1);
+    }
+
+    #[test]
+    fn test_tombstones_compact_buckets_past_the_ratio() {
+        let configuration = Configuration::builder()
+            .max_bucket_size(10)
+            .tombstone_ratio(0.25)
+            .build()
+            .unwrap();
+        let mut set: SortedSet<i64> = match SortedSet::from_sorted(configuration, (0..9).collect())
+        {
+            BuildResult::Built(set) => *set,
+            BuildResult::SetFull => panic!("unexpected SetFull"),
+        };
+
+        set.remove(&0);
+        set.remove(&1);
+        assert_eq!(set.tombstones(), 2);
+
+        // A third tombstone in a bucket of nine is more than a quarter of it
+        set.remove(&2);
+        assert_eq!(set.tombstones(), 0);
+        assert_eq!(set.layout(), vec![&[3, 4, 5, 6, 7, 8][..]]);
+
+        for i in 3..9 {
+            set.remove(&i);
+        }
+        assert_eq!(set.size(), 0);
+        assert!(set.to_vec().is_empty());
+        assert_eq!(set.add(4), Added(0));
+    }
+
+    #[test]
+    fn test_repair_restores_a_broken_layout() {
+        let layout = vec![vec![3, 1], vec![], vec![2, 2, 5], vec![4]];
+        let mut set: SortedSet<i64> = SortedSet::from_layout(Configuration::default(), layout);
+
+        assert_eq!(
+            set.repair(),
+            RepairReport {
+                unsorted_buckets: 1,
+                duplicates: 1,
+                empty_buckets: 1,
+                overlapping_buckets: 2,
+                recorded_size: 6,
+                size: 5,
+            }
+        );
+        assert_eq!(set.to_vec(), vec![1, 2, 3, 4, 5]);
+        assert_eq!(set.find_index(&4), Some(3));
+        assert!(set.repair().is_clean());
+
+        let mut tree: SortedSet<i64> = SortedSet::new(Configuration {
+            backend: BackendKind::BTree,
+            ..Configuration::default()
+        });
+        for i in 0..1_000 {
+            tree.add(i);
+        }
+        assert!(tree.repair().is_clean());
+        assert_eq!(tree.to_vec(), (0..1_000).collect::<Vec<i64>>());
+    }
+
+    #[test]
+    fn test_slice_starting_at_0_amount_0() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 5,
+            ..Configuration::default()
+        });
+
+        for i in 1..10 {
+            set.add(Integer(i * 2));
+        }
+
+        assert_eq!(set.slice(0, 0), vec![]);
+    }
+
+    #[test]
+    fn test_slice_new_set() {
+        let set: SortedSet = SortedSet::new(Configuration::default());
+
+        assert_eq!(set.slice(0, 100), vec![]);
+    }
+
+    #[test]
+    fn test_slice_empty_set() {
+        let set: SortedSet = SortedSet::empty(Configuration::default());
+
+        assert_eq!(set.slice(0, 100), vec![]);
+    }
+
+    #[test]
+    fn test_empty_set_is_usable() {
+        let mut set = SortedSet::empty(Configuration::default());
+
+        assert_eq!(set.at(0), None);
+        assert_eq!(set.find_index(&Integer(1)), None);
+        assert_eq!(set.remove(&Integer(1)), NotFound);
+        assert_eq!(set.multi_slice(&[(0, 10)]), vec![vec![]]);
+
+        assert_eq!(set.add_with_hint(Integer(2), 5), Added(0));
+        assert_eq!(set.add(Integer(1)), Added(0));
+        assert_eq!(set.at(1), Some(&Integer(2)));
+        assert_eq!(set.remove(&Integer(1)), Removed(0));
+        assert_eq!(set.to_vec(), vec![Integer(2)]);
+    }
+
+    #[test]
+    fn test_slice_single_bucket_satisfiable() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 5,
+            ..Configuration::default()
+        });
+
+        for i in 1..10 {
+            set.add(Integer(i * 2));
+        }
+
+        assert_eq!(set.slice(1, 1), vec![SupportedTerm::Integer(4)]);
+    }
+
+    #[test]
+    fn test_slice_multi_cell_satisfiable() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 5,
+            ..Configuration::default()
+        });
+
+        for i in 1..10 {
+            set.add(Integer(i * 2));
+        }
+
+        assert_eq!(
+            set.slice(1, 4),
+            vec![
+                SupportedTerm::Integer(4),
+                SupportedTerm::Integer(6),
+                SupportedTerm::Integer(8),
+                SupportedTerm::Integer(10),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_slice_exactly_exhausted_from_non_terminal() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 5,
+            ..Configuration::default()
+        });
+
+        for i in 1..10 {
+            set.add(Integer(i * 2));
+        }
+
+        assert_eq!(
+            set.slice(3, 6),
+            vec![
+                SupportedTerm::Integer(8),
+                SupportedTerm::Integer(10),
+                SupportedTerm::Integer(12),
+                SupportedTerm::Integer(14),
+                SupportedTerm::Integer(16),
+                SupportedTerm::Integer(18),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_slice_over_exhausted_from_non_terminal() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 5,
+            ..Configuration::default()
+        });
+
+        for i in 1..10 {
+            set.add(Integer(i * 2));
+        }
+
+        assert_eq!(
+            set.slice(3, 10),
+            vec![
+                SupportedTerm::Integer(8),
+                SupportedTerm::Integer(10),
+                SupportedTerm::Integer(12),
+                SupportedTerm::Integer(14),
+                SupportedTerm::Integer(16),
+                SupportedTerm::Integer(18),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_slice_exactly_exhausted_from_terminal() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 5,
+            ..Configuration::default()
+        });
+
+        for i in 1..10 {
+            set.add(Integer(i * 2));
+        }
+
+        assert_eq!(
+            set.slice(7, 2),
+            vec![SupportedTerm::Integer(16), SupportedTerm::Integer(18)]
+        )
+    }
+
+    #[test]
+    fn test_slice_over_exhausted_from_terminal() {
+        let mut set = SortedSet::new(Configuration {
+            max_bucket_size: 5,
+            ..Configuration::default()
+        });
+
+        for i in 1..10 {
+            set.add(Integer(i * 2));
+        }
+
+        assert_eq!(
+            set.slice(7, 10),
+            vec![SupportedTerm::Integer(16), SupportedTerm::Integer(18)]
+        )
+    }
+}
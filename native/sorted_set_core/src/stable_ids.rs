@@ -0,0 +1,126 @@
+use std::collections::{BTreeMap, HashMap};
+use std::mem;
+use Item;
+
+/// Assigns every element of a SortedSet an ID when it is inserted.  Unlike the index of an
+/// element the ID does not change when other elements are added or removed, it is only released
+/// when the element itself is removed.  IDs are never reused within a set.
+#[derive(Debug)]
+pub struct StableIds<T> {
+    next: u64,
+    by_term: BTreeMap<T, u64>,
+    by_id: HashMap<u64, T>,
+}
+
+impl<T: Item> StableIds<T> {
+    pub fn new() -> StableIds<T> {
+        StableIds::starting_at(1)
+    }
+
+    fn starting_at(next: u64) -> StableIds<T> {
+        StableIds {
+            next,
+            by_term: BTreeMap::new(),
+            by_id: HashMap::new(),
+        }
+    }
+
+    /// Assigns the next ID to an element that was just inserted.
+    pub fn assign(&mut self, item: T) -> u64 {
+        let id = self.next;
+        self.next += 1;
+
+        self.by_id.insert(id, item.clone());
+        self.by_term.insert(item, id);
+
+        id
+    }
+
+    /// Releases the ID of an element that was just removed.
+    pub fn release(&mut self, item: &T) -> Option<u64> {
+        let id = self.by_term.remove(item)?;
+        self.by_id.remove(&id);
+        Some(id)
+    }
+
+    pub fn id_of(&self, item: &T) -> Option<u64> {
+        self.by_term.get(item).cloned()
+    }
+
+    pub fn get(&self, id: u64) -> Option<&T> {
+        self.by_id.get(&id)
+    }
+
+    /// Releases every ID, the counter keeps going so IDs handed out before are not reused.
+    pub fn clear(&mut self) {
+        self.by_term.clear();
+        self.by_id.clear();
+    }
+
+    /// Splits the IDs around `pivot` the same way `SortedSet::partition` splits the elements,
+    /// returning the IDs of the elements less than the pivot and of the elements greater than or
+    /// equal to the pivot.  Both halves and these IDs continue from the same counter.
+    pub fn split(&mut self, pivot: &T) -> (StableIds<T>, StableIds<T>) {
+        let mut lower = StableIds::starting_at(self.next);
+        let mut upper = StableIds::starting_at(self.next);
+
+        lower.by_term = mem::take(&mut self.by_term);
+        upper.by_term = lower.by_term.split_off(pivot);
+        self.by_id.clear();
+
+        lower.by_id = lower
+            .by_term
+            .iter()
+            .map(|(item, id)| (*id, item.clone()))
+            .collect();
+        upper.by_id = upper
+            .by_term
+            .iter()
+            .map(|(item, id)| (*id, item.clone()))
+            .collect();
+
+        (lower, upper)
+    }
+
+    /// Rough estimate of the bytes used by the two indexes.
+    pub fn memory(&self) -> usize {
+        self.by_term.len() * 2 * (mem::size_of::<T>() + mem::size_of::<u64>())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use stable_ids::StableIds;
+    use supported_term::SupportedTerm::Integer;
+
+    #[test]
+    fn test_ids_are_not_reused() {
+        let mut ids = StableIds::new();
+
+        let first = ids.assign(Integer(1));
+        assert_eq!(ids.release(&Integer(1)), Some(first));
+        assert_eq!(ids.release(&Integer(1)), None);
+
+        let second = ids.assign(Integer(1));
+        assert!(second > first);
+        assert_eq!(ids.id_of(&Integer(1)), Some(second));
+        assert_eq!(ids.get(second), Some(&Integer(1)));
+        assert_eq!(ids.get(first), None);
+    }
+
+    #[test]
+    fn test_split_keeps_ids() {
+        let mut ids = StableIds::new();
+        for i in 0..10 {
+            ids.assign(Integer(i));
+        }
+
+        let (lower, upper) = ids.split(&Integer(4));
+
+        assert_eq!(lower.id_of(&Integer(3)), Some(4));
+        assert_eq!(lower.id_of(&Integer(4)), None);
+        assert_eq!(upper.id_of(&Integer(4)), Some(5));
+        assert_eq!(upper.get(10), Some(&Integer(9)));
+        assert_eq!(ids.id_of(&Integer(0)), None);
+    }
+}
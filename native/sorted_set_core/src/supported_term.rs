@@ -0,0 +1,386 @@
+use collation::{CollatedString, Collation};
+use comparator::{Comparator, KeyedTuple};
+use configuration::Configuration;
+use std::cmp::min;
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use Item;
+
+/// SupportedTerm is an enum that covers all the Erlang / Elixir term types that can be stored in
+/// a SortedSet.
+///
+/// There are a number of types that are not supported because of their complexity and the
+/// difficulty of safely implementing their storage.
+///
+/// Types that are not supported
+///   - Reference
+///   - Function
+///   - Port
+///   - Pid
+///
+/// Types that are supported but not explicitly listed
+///   - Boolean (Note that booleans in Erlang / Elixir are just atoms)
+#[derive(Eq, Debug, Clone)]
+pub enum SupportedTerm {
+    Integer(i64),
+    Atom(String),
+    Tuple(Vec<SupportedTerm>),
+    List(Vec<SupportedTerm>),
+    Bitstring(String),
+
+    /// A Bitstring in a set created with a collation, ordered by the set's locale instead of by
+    /// its bytes.  Encodes back into a plain binary.
+    Collated(Box<CollatedString>),
+
+    /// A Tuple in a set created with a comparator, ordered by the comparator's keys instead of
+    /// element by element.  Encodes back into a plain tuple.
+    Keyed(Box<KeyedTuple>),
+
+    /// The `:neg_infinity` atom, ordered before every other term so it can bound a range from
+    /// below.
+    NegInfinity,
+
+    /// The `:infinity` atom, ordered after every other term so it can bound a range from above.
+    Infinity,
+}
+
+impl SupportedTerm {
+    /// The term for an atom, `infinity` and `neg_infinity` become the sentinels.
+    pub fn atom(name: String) -> SupportedTerm {
+        match name.as_str() {
+            "infinity" => SupportedTerm::Infinity,
+            "neg_infinity" => SupportedTerm::NegInfinity,
+            _ => SupportedTerm::Atom(name),
+        }
+    }
+
+    /// The name of the atom the term is, including the sentinels.
+    pub fn atom_name(&self) -> Option<&str> {
+        match self {
+            SupportedTerm::Atom(name) => Some(name),
+            SupportedTerm::Infinity => Some("infinity"),
+            SupportedTerm::NegInfinity => Some("neg_infinity"),
+            _ => None,
+        }
+    }
+}
+
+/// Orders the elements of two tuples the way Erlang does, shorter tuples sort first and tuples
+/// of the same size are compared element by element.
+pub fn compare_tuples(left: &[SupportedTerm], right: &[SupportedTerm]) -> Ordering {
+    if left.len() == right.len() {
+        left.cmp(right)
+    } else {
+        left.len().cmp(&right.len())
+    }
+}
+
+/// Converts every charlist in the item, including the ones nested in tuples and lists, into the
+/// Bitstring it spells out.  A charlist is a non-empty list of printable Latin-1 characters, the
+/// same lists Erlang prints as strings.
+pub fn charlists_to_bitstrings(item: SupportedTerm) -> SupportedTerm {
+    match item {
+        SupportedTerm::List(items) => match charlist_to_string(&items) {
+            Some(string) => SupportedTerm::Bitstring(string),
+            None => SupportedTerm::List(items.into_iter().map(charlists_to_bitstrings).collect()),
+        },
+        SupportedTerm::Tuple(items) => {
+            SupportedTerm::Tuple(items.into_iter().map(charlists_to_bitstrings).collect())
+        }
+        other => other,
+    }
+}
+
+fn charlist_to_string(items: &[SupportedTerm]) -> Option<String> {
+    if items.is_empty() {
+        return None;
+    }
+
+    items
+        .iter()
+        .map(|item| match *item {
+            SupportedTerm::Integer(code) => printable_char(code),
+            _ => None,
+        })
+        .collect()
+}
+
+/// The characters `io_lib:printable_latin1_list/1` accepts.
+fn printable_char(code: i64) -> Option<char> {
+    match code {
+        8..=13 | 27 | 32..=126 | 160..=255 => Some(code as u8 as char),
+        _ => None,
+    }
+}
+
+impl Ord for SupportedTerm {
+    fn cmp(&self, other: &SupportedTerm) -> Ordering {
+        match (self, other) {
+            (SupportedTerm::NegInfinity, SupportedTerm::NegInfinity)
+            | (SupportedTerm::Infinity, SupportedTerm::Infinity) => return Ordering::Equal,
+            (SupportedTerm::NegInfinity, _) | (_, SupportedTerm::Infinity) => {
+                return Ordering::Less
+            }
+            (SupportedTerm::Infinity, _) | (_, SupportedTerm::NegInfinity) => {
+                return Ordering::Greater
+            }
+            _ => {}
+        }
+
+        match self {
+            SupportedTerm::Integer(self_inner) => match other {
+                SupportedTerm::Integer(inner) => self_inner.cmp(inner),
+                _ => Ordering::Less,
+            },
+            SupportedTerm::Atom(self_inner) => match other {
+                SupportedTerm::Integer(_) => Ordering::Greater,
+                SupportedTerm::Atom(inner) => self_inner.cmp(inner),
+                _ => Ordering::Less,
+            },
+            SupportedTerm::Tuple(self_inner) => match other {
+                SupportedTerm::Integer(_) => Ordering::Greater,
+                SupportedTerm::Atom(_) => Ordering::Greater,
+                SupportedTerm::Tuple(inner) => compare_tuples(self_inner, inner),
+                SupportedTerm::Keyed(inner) => compare_tuples(self_inner, &inner.items),
+                _ => Ordering::Less,
+            },
+            SupportedTerm::Keyed(self_inner) => match other {
+                SupportedTerm::Integer(_) => Ordering::Greater,
+                SupportedTerm::Atom(_) => Ordering::Greater,
+                SupportedTerm::Keyed(inner) => self_inner.compare(inner),
+                SupportedTerm::Tuple(inner) => compare_tuples(&self_inner.items, inner),
+                _ => Ordering::Less,
+            },
+            SupportedTerm::List(self_inner) => match other {
+                SupportedTerm::Integer(_) => Ordering::Greater,
+                SupportedTerm::Atom(_) => Ordering::Greater,
+                SupportedTerm::Tuple(_) => Ordering::Greater,
+                SupportedTerm::Keyed(_) => Ordering::Greater,
+                SupportedTerm::List(inner) => {
+                    let self_length = self_inner.len();
+                    let other_length = inner.len();
+
+                    let max_common = min(self_length, other_length);
+                    let mut idx = 0;
+
+                    while idx < max_common {
+                        match self_inner[idx].cmp(&inner[idx]) {
+                            Ordering::Greater => return Ordering::Greater,
+                            Ordering::Less => return Ordering::Less,
+                            _ => idx += 1,
+                        }
+                    }
+
+                    if self_length == other_length {
+                        Ordering::Equal
+                    } else if self_length < other_length {
+                        Ordering::Less
+                    } else {
+                        Ordering::Greater
+                    }
+                }
+                _ => Ordering::Less,
+            },
+            SupportedTerm::Bitstring(self_inner) => match other {
+                SupportedTerm::Bitstring(inner) => self_inner.cmp(inner),
+                SupportedTerm::Collated(inner) => self_inner.cmp(&inner.value),
+                _ => Ordering::Greater,
+            },
+            SupportedTerm::Collated(self_inner) => match other {
+                SupportedTerm::Collated(inner) => self_inner.compare(inner),
+                SupportedTerm::Bitstring(inner) => self_inner.value.cmp(inner),
+                _ => Ordering::Greater,
+            },
+            SupportedTerm::NegInfinity | SupportedTerm::Infinity => {
+                unreachable!("sentinels are compared above")
+            }
+        }
+    }
+}
+
+impl Item for SupportedTerm {
+    fn prepare(self, configuration: &Configuration) -> SupportedTerm {
+        let item = if configuration.charlists {
+            charlists_to_bitstrings(self)
+        } else {
+            self
+        };
+
+        let item = match configuration.collation {
+            Some(ref collation) => Collation::prepare(collation, item),
+            None => item,
+        };
+
+        match configuration.comparator {
+            Some(ref comparator) => Comparator::prepare(comparator, item),
+            None => item,
+        }
+    }
+
+    fn strip(self) -> SupportedTerm {
+        Collation::strip(Comparator::strip(self))
+    }
+
+    /// Collated strings and keyed tuples hash like the plain terms they were prepared from, so
+    /// the sketches of sets with different configurations can be combined.  The sentinels hash
+    /// like atoms.
+    fn hash_item<H: Hasher>(&self, state: &mut H) {
+        match self {
+            SupportedTerm::Integer(inner) => {
+                0u8.hash(state);
+                inner.hash(state);
+            }
+            SupportedTerm::Atom(inner) => {
+                1u8.hash(state);
+                inner.hash(state);
+            }
+            SupportedTerm::Tuple(inner) => hash_elements(2, inner, state),
+            SupportedTerm::Keyed(inner) => hash_elements(2, &inner.items, state),
+            SupportedTerm::List(inner) => hash_elements(3, inner, state),
+            SupportedTerm::Bitstring(inner) => {
+                4u8.hash(state);
+                inner.hash(state);
+            }
+            SupportedTerm::Collated(inner) => {
+                4u8.hash(state);
+                inner.value.hash(state);
+            }
+            SupportedTerm::NegInfinity => {
+                1u8.hash(state);
+                "neg_infinity".hash(state);
+            }
+            SupportedTerm::Infinity => {
+                1u8.hash(state);
+                "infinity".hash(state);
+            }
+        }
+    }
+
+    fn dense_index(&self) -> Option<u32> {
+        match *self {
+            SupportedTerm::Integer(value) if value >= 0 && value <= i64::from(u32::MAX) => {
+                Some(value as u32)
+            }
+            _ => None,
+        }
+    }
+
+    fn integer_key(&self) -> Option<i64> {
+        match *self {
+            SupportedTerm::Integer(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn dense_universe(universe: u32) -> Option<Arc<[SupportedTerm]>> {
+        Some(dense_integers(universe))
+    }
+}
+
+/// The integers `0..universe`, shared by every bitset backed set.  The largest table built so far
+/// is kept and serves every smaller universe as well.
+fn dense_integers(universe: u32) -> Arc<[SupportedTerm]> {
+    static INTEGERS: Mutex<Option<Arc<[SupportedTerm]>>> = Mutex::new(None);
+
+    let mut integers = INTEGERS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    if let Some(ref table) = *integers {
+        if table.len() >= universe as usize {
+            return table.clone();
+        }
+    }
+
+    let table: Arc<[SupportedTerm]> = (0..universe)
+        .map(|value| SupportedTerm::Integer(i64::from(value)))
+        .collect();
+    *integers = Some(table.clone());
+
+    table
+}
+
+fn hash_elements<H: Hasher>(tag: u8, elements: &[SupportedTerm], state: &mut H) {
+    tag.hash(state);
+    elements.len().hash(state);
+
+    for element in elements {
+        element.hash_item(state);
+    }
+}
+
+impl PartialOrd for SupportedTerm {
+    fn partial_cmp(&self, other: &SupportedTerm) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SupportedTerm {
+    fn eq(&self, other: &SupportedTerm) -> bool {
+        match self {
+            SupportedTerm::Integer(self_inner) => match other {
+                SupportedTerm::Integer(inner) => self_inner == inner,
+                _ => false,
+            },
+            SupportedTerm::Atom(self_inner) => match other {
+                SupportedTerm::Atom(inner) => self_inner == inner,
+                _ => false,
+            },
+            SupportedTerm::Tuple(self_inner) => match other {
+                SupportedTerm::Tuple(inner) => {
+                    let length = self_inner.len();
+
+                    if length != inner.len() {
+                        return false;
+                    }
+
+                    let mut idx = 0;
+
+                    while idx < length {
+                        if self_inner[idx] != inner[idx] {
+                            return false;
+                        }
+                    }
+
+                    true
+                }
+                _ => false,
+            },
+            SupportedTerm::List(self_inner) => match other {
+                SupportedTerm::List(inner) => {
+                    let length = self_inner.len();
+
+                    if length != inner.len() {
+                        return false;
+                    }
+
+                    let mut idx = 0;
+
+                    while idx < length {
+                        if self_inner[idx] != inner[idx] {
+                            return false;
+                        }
+                    }
+
+                    true
+                }
+                _ => false,
+            },
+            SupportedTerm::Bitstring(self_inner) => match other {
+                SupportedTerm::Bitstring(inner) => self_inner == inner,
+                _ => false,
+            },
+            SupportedTerm::Collated(self_inner) => match other {
+                SupportedTerm::Collated(inner) => self_inner == inner,
+                _ => false,
+            },
+            SupportedTerm::Keyed(self_inner) => match other {
+                SupportedTerm::Keyed(inner) => self_inner == inner,
+                _ => false,
+            },
+            SupportedTerm::NegInfinity => matches!(other, SupportedTerm::NegInfinity),
+            SupportedTerm::Infinity => matches!(other, SupportedTerm::Infinity),
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Read-only windows onto the items of a set that fall within a value range.
+//!
+//! A view only remembers its bounds, every read finds the indexes of the bounds in the set and
+//! translates the index it was given by the start of the window.  Nothing is copied and items
+//! added to or removed from the set within the range show up in the view right away.
+
+use sorted_set::SortedSet;
+use supported_term::SupportedTerm;
+use Item;
+
+#[derive(Debug)]
+pub struct RangeView<T: Item = SupportedTerm> {
+    min: T,
+    max: T,
+}
+
+impl<T: Item> RangeView<T> {
+    /// A view of the items from `min` up to and including `max`, the bounds do not have to be
+    /// items of the set.  A view whose `min` lies past its `max` is always empty.
+    pub fn new(min: T, max: T) -> RangeView<T> {
+        RangeView { min, max }
+    }
+
+    pub fn min(&self) -> &T {
+        &self.min
+    }
+
+    pub fn max(&self) -> &T {
+        &self.max
+    }
+
+    /// Returns the indexes `[start, end)` of the set that fall within the view.
+    pub fn bounds(&self, set: &SortedSet<T>) -> (usize, usize) {
+        let start = set.rank(&self.min);
+        let end = match set.find_index(&self.max) {
+            Some(idx) => idx + 1,
+            None => set.rank(&self.max),
+        };
+
+        (start, end.max(start))
+    }
+
+    pub fn size(&self, set: &SortedSet<T>) -> usize {
+        let (start, end) = self.bounds(set);
+        end - start
+    }
+
+    pub fn at<'s>(&self, set: &'s SortedSet<T>, index: usize) -> Option<&'s T> {
+        let (start, end) = self.bounds(set);
+
+        if index < end - start {
+            set.at(start + index)
+        } else {
+            None
+        }
+    }
+
+    /// Returns up to `amount` items of the view starting at `index`, the slice never reaches past
+    /// the end of the view.
+    pub fn slice(&self, set: &SortedSet<T>, index: usize, amount: usize) -> Vec<T> {
+        let (start, end) = self.bounds(set);
+        let available = (end - start).saturating_sub(index);
+
+        set.slice(start + index.min(end - start), amount.min(available))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use comparator::Direction;
+    use cursor::Cursor;
+    use sorted_set::SortedSet;
+    use supported_term::SupportedTerm::{self, Integer};
+    use view::RangeView;
+
+    fn integers(items: &[i64]) -> Vec<SupportedTerm> {
+        items.iter().cloned().map(Integer).collect()
+    }
+
+    fn tens() -> SortedSet {
+        (0..10).map(|i| Integer(i * 10)).collect()
+    }
+
+    #[test]
+    fn test_reads_are_translated_to_the_set() {
+        let set = tens();
+        let view = RangeView::new(Integer(25), Integer(60));
+
+        assert_eq!(view.bounds(&set), (3, 7));
+        assert_eq!(view.size(&set), 4);
+        assert_eq!(view.at(&set, 0), Some(&Integer(30)));
+        assert_eq!(view.at(&set, 3), Some(&Integer(60)));
+        assert_eq!(view.at(&set, 4), None);
+        assert_eq!(view.slice(&set, 1, 10), integers(&[40, 50, 60]));
+        assert_eq!(view.slice(&set, 10, 10), integers(&[]));
+    }
+
+    #[test]
+    fn test_view_follows_the_set() {
+        let mut set = tens();
+        let view = RangeView::new(Integer(25), Integer(60));
+
+        set.add(Integer(35));
+        set.remove(&Integer(60));
+        set.add(Integer(61));
+
+        assert_eq!(view.slice(&set, 0, 10), integers(&[30, 35, 40, 50]));
+    }
+
+    #[test]
+    fn test_inverted_bounds_are_empty() {
+        let set = tens();
+        let view = RangeView::new(Integer(60), Integer(25));
+
+        assert_eq!(view.size(&set), 0);
+        assert_eq!(view.slice(&set, 0, 10), integers(&[]));
+    }
+
+    #[test]
+    fn test_cursor_stays_within_the_view() {
+        let set = tens();
+        let view = RangeView::new(Integer(25), Integer(60));
+
+        let mut ascending = Cursor::new(Direction::Ascending);
+        assert_eq!(ascending.next_view(&set, &view, 3), integers(&[30, 40, 50]));
+        assert_eq!(ascending.next_view(&set, &view, 3), integers(&[60]));
+        assert_eq!(ascending.next_view(&set, &view, 3), integers(&[]));
+
+        let mut descending = Cursor::at_view(&set, &view, 10, Direction::Descending);
+        assert_eq!(
+            descending.next_view(&set, &view, 3),
+            integers(&[60, 50, 40])
+        );
+        assert_eq!(descending.next_view(&set, &view, 3), integers(&[30]));
+
+        let mut from = Cursor::from(Integer(0), Direction::Ascending);
+        assert_eq!(from.next_view(&set, &view, 1), integers(&[30]));
+    }
+}
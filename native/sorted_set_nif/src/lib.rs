@@ -2,19 +2,55 @@
 extern crate rustler;
 #[macro_use]
 extern crate lazy_static;
+extern crate sorted_set_core;
 
-mod bucket;
-mod configuration;
-mod sorted_set;
+mod panics;
+mod set_lock;
 mod supported_term;
+#[macro_use]
+mod upgrade;
+mod write_queue;
 
-use configuration::Configuration;
-use rustler::resource::ResourceArc;
-use rustler::types::tuple::get_tuple;
-use rustler::{Encoder, Env, NifResult, Term};
-use sorted_set::SortedSet;
-use std::sync::Mutex;
-use supported_term::SupportedTerm;
+use rustler::env::OwnedEnv;
+use rustler::resource::{ResourceArc, NIF_RESOURCE_FLAGS};
+use rustler::schedule::SchedulerFlags;
+use rustler::types::atom::Atom;
+use rustler::types::binary::{Binary, OwnedBinary};
+use rustler::types::pid::Pid;
+use rustler::types::tuple::{get_tuple, make_tuple};
+use rustler::{Encoder, Env, Error, NifResult, Term};
+use set_lock::{LockError, ReadGuard, SetLock, WriteGuard};
+use sorted_set_core::aggregate::{self, Aggregate, AggregateError, Aggregated};
+#[cfg(feature = "debug-alloc")]
+use sorted_set_core::alloc_report::{AllocReport, Tally};
+use sorted_set_core::collation::Collation;
+use sorted_set_core::comparator::{Cast, Comparator, Direction, Key, TupleOrder};
+use sorted_set_core::cursor::{Cursor, SnapshotCursor};
+use sorted_set_core::dual_index::{DualAddResult, DualIndexSet, DualRemoveResult, Index};
+use sorted_set_core::etf::{self, EtfError};
+use sorted_set_core::fold::{Accumulator, Fold, FoldError, FoldState, Step};
+use sorted_set_core::merged::{self, OrderMismatch};
+use sorted_set_core::metrics;
+use sorted_set_core::namespaces::Namespaces;
+use sorted_set_core::persistent::PersistentSet;
+use sorted_set_core::probes;
+use sorted_set_core::repro;
+use sorted_set_core::sharded::{self, ShardError, Shards};
+use sorted_set_core::sketch::Sketch;
+use sorted_set_core::view::RangeView;
+use sorted_set_core::{
+    AddResult, AppendBucketResult, BackendKind, BuildResult, ConcatResult, Configuration,
+    MergeResult, MoveRangeResult, RemoveResult, SortedSet, SupportedTerm,
+};
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::sync::atomic::{AtomicUsize, Ordering as AtomicOrdering};
+use std::sync::{Mutex, RwLock, RwLockReadGuard};
+use std::thread;
+use std::time::UNIX_EPOCH;
+use write_queue::{Write, WriteQueue};
 
 mod atoms {
     rustler_atoms! {
@@ -25,103 +61,503 @@ mod atoms {
         // Resource Atoms
         atom bad_reference;
         atom lock_fail;
+        atom not_owner;
 
         // Success Atoms
         atom added;
         atom duplicate;
         atom removed;
+        atom found;
+        atom noop;
+        atom queued;
 
         // Error Atoms
         atom unsupported_type;
         atom not_found;
         atom index_out_of_bounds;
+        atom invalid_range;
+        atom internal_inconsistency;
+        atom unordered_pivots;
         atom max_bucket_size_exceeded;
+        atom set_full;
+        atom frozen;
+        atom invalid_etf;
+        atom invalid_repro;
+        atom stable_ids_disabled;
+        atom sketch_disabled;
+        atom overlapping;
+        atom collation_mismatch;
+        atom comparator_mismatch;
+        atom charlists_mismatch;
+        atom configuration_mismatch;
+        atom custom_order;
+        atom internal_error;
+        atom not_enabled;
+        atom not_integer;
+        atom overflow;
+        atom missing_element;
+        atom empty;
+
+        // Event Atoms
+        atom sorted_set_event;
+        atom sorted_set_alert;
+        atom sorted_set_reply;
+        atom sorted_set_destroyed;
+        atom sorted_set_static;
+
+        // Sentinel Atoms
+        atom infinity;
+        atom neg_infinity;
+
+        // Watermark Atoms
+        atom size;
+        atom memory;
+
+        // Stats Atoms
+        atom sets;
+        atom panics;
+        atom operations;
+        atom comparisons;
+        atom buckets_probed;
+        atom elements_shifted;
+        atom same_set;
+        atom version;
+        atom count;
+        atom bytes;
+        atom strings;
+        atom tuples;
+        atom lists;
+        atom unsorted_buckets;
+        atom duplicates;
+        atom empty_buckets;
+        atom overlapping_buckets;
+        atom recorded_size;
+
+        // Option Atoms
+        atom max_elements;
+        atom initial_bucket_capacity;
+        atom backend;
+        atom buckets;
+        atom btree;
+        atom bitset;
+        atom stable_ids;
+        atom single_owner;
+        atom cardinality_sketch;
+        atom coalesce_writes;
+        atom tombstones;
+        atom interpolation_search;
+        atom charlists;
+        atom collation;
+        atom natural;
+        atom comparator;
+        atom tuple_order;
+        atom erlang;
+        atom lexicographic;
+        atom asc;
+        atom desc;
+        atom fold_case;
+        atom cast;
+        atom integer;
+        atom string;
+        atom from;
+        atom at;
+        atom direction;
+        atom hint;
+        atom index;
+        atom inserted;
+        atom displaced;
+        atom pop;
+        atom replace;
+        atom unsupported;
+        atom reject;
+        atom skip;
+        atom iodata;
+        atom missing;
+        atom insertion_point;
+        atom primary;
+        atom secondary;
+        atom snapshot;
+
+        // Range Atoms
+        atom first;
+        atom last;
+        atom step;
+
+        // Aggregate Atoms
+        atom sum;
+        atom min;
+        atom max;
+        atom avg;
+
+        // Fold Atoms
+        atom cont;
+        atom sorted_set_fold;
+
+        // Comparison Atoms
+        atom lt;
+        atom eq;
+        atom gt;
+    }
+}
+
+pub struct SortedSetResource {
+    set: SetLock,
+    subscribers: Mutex<Vec<Pid>>,
+    alerts: Mutex<Vec<Alert>>,
+
+    /// Memory estimate of the set that is included in the global accounting.
+    accounted: AtomicUsize,
+
+    /// Writes waiting to be applied, only sets configured with `coalesce_writes` have a queue.
+    queue: Option<WriteQueue>,
+}
+
+impl SortedSetResource {
+    /// Wraps a set, sets configured with `single_owner` belong to `caller` from here on.
+    fn new(set: SortedSet, caller: Pid) -> SortedSetResource {
+        let memory = set.memory();
+        let globals = upgrade::globals();
+        let queue = if set.configuration().coalesce_writes {
+            Some(WriteQueue::new())
+        } else {
+            None
+        };
+
+        globals.live_sets.fetch_add(1, AtomicOrdering::Relaxed);
+        globals
+            .live_memory
+            .fetch_add(memory, AtomicOrdering::Relaxed);
+
+        SortedSetResource {
+            set: SetLock::new(set, caller),
+            subscribers: Mutex::new(Vec::new()),
+            alerts: Mutex::new(Vec::new()),
+            accounted: AtomicUsize::new(memory),
+            queue,
+        }
+    }
+
+    /// Replaces the memory estimate of the set in the global accounting.
+    fn account(&self, memory: usize) {
+        let previous = self.accounted.swap(memory, AtomicOrdering::Relaxed);
+        let globals = upgrade::globals();
+
+        // Adding before subtracting keeps the total from wrapping around
+        globals
+            .live_memory
+            .fetch_add(memory, AtomicOrdering::Relaxed);
+        globals
+            .live_memory
+            .fetch_sub(previous, AtomicOrdering::Relaxed);
+    }
+}
+
+impl Drop for SortedSetResource {
+    fn drop(&mut self) {
+        let globals = upgrade::globals();
+
+        globals.live_sets.fetch_sub(1, AtomicOrdering::Relaxed);
+        globals.live_memory.fetch_sub(
+            self.accounted.load(AtomicOrdering::Relaxed),
+            AtomicOrdering::Relaxed,
+        );
+
+        let collector = match globals.collector.lock() {
+            Err(_) => return,
+            Ok(guard) => match *guard {
+                None => return,
+                Some(ref pid) => pid.clone(),
+            },
+        };
+
+        let set = self.set.get_mut();
+        let meta = set.meta().cloned();
+        let stats = (set.size(), set.memory(), set.version());
+
+        // Destructors run on scheduler threads, which are not allowed to send from an OwnedEnv
+        thread::spawn(move || {
+            OwnedEnv::new().send_and_clear(&collector, |env| {
+                let meta = match meta {
+                    Some(ref meta) => supported_term::encode(env, meta),
+                    None => rustler::types::atom::nil().encode(env),
+                };
+                let (size, memory, version) = stats;
+                let stats = vec![
+                    (atoms::size(), size as u64),
+                    (atoms::memory(), memory as u64),
+                    (atoms::version(), version),
+                ];
+
+                (atoms::sorted_set_destroyed(), meta, stats).encode(env)
+            });
+        });
     }
 }
 
-pub struct SortedSetResource(Mutex<SortedSet>);
+/// Handle to one version of a PersistentSet.  Versions are immutable so unlike the
+/// SortedSetResource no lock is needed to read them.
+pub struct PersistentSetResource {
+    set: PersistentSet,
+}
+
+/// Many small sets behind a single lock, see `Namespaces`.
+pub struct NamespacesResource {
+    namespaces: RwLock<Namespaces>,
+}
+
+/// The same items in two orders behind a single lock, see `DualIndexSet`.
+pub struct DualIndexResource {
+    set: RwLock<DualIndexSet>,
+}
+
+/// A set split by value range into shards behind a lock each, see `sharded`.
+pub struct ShardedSetResource {
+    shards: Shards,
+    sets: Vec<RwLock<SortedSet>>,
+}
+
+/// Read-only view merging several SortedSets, see `merged`.  The view holds on to the sets and
+/// locks them for every read.
+pub struct MergedViewResource {
+    sets: Vec<ResourceArc<SortedSetResource>>,
+}
+
+/// Iterator over a merged view, see `IteratorResource`.
+pub struct MergedIteratorResource {
+    view: ResourceArc<MergedViewResource>,
+    cursor: Mutex<Cursor>,
+}
+
+/// Read-only view of the items of a SortedSet within a value range, see `view`.
+pub struct RangeViewResource {
+    set: ResourceArc<SortedSetResource>,
+    view: RangeView,
+}
+
+/// Iterator over a range view, see `IteratorResource`.
+pub struct RangeIteratorResource {
+    view: ResourceArc<RangeViewResource>,
+    cursor: Mutex<Cursor>,
+}
+
+/// Iterator over a SortedSet, the cursor is locked separately from the set so iterators over the
+/// same set can be read concurrently.
+pub struct IteratorResource {
+    set: ResourceArc<SortedSetResource>,
+    cursor: Mutex<IteratorCursor>,
+}
 
-#[derive(Debug, PartialEq)]
-pub enum AddResult {
-    Added(usize),
-    Duplicate(usize),
+/// Iterators read the live set unless created with the `snapshot` option, which reads a copy of
+/// the set taken when the iterator was created and never locks the set again.
+enum IteratorCursor {
+    Live(Cursor),
+    Snapshot(SnapshotCursor),
 }
 
-#[derive(Debug, PartialEq)]
-pub enum RemoveResult {
-    Removed(usize),
-    NotFound,
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Watermark {
+    Size(usize),
+    Memory(usize),
 }
 
-#[derive(Debug, PartialEq)]
-pub enum FindResult {
-    Found {
-        bucket_idx: usize,
-        inner_idx: usize,
-        idx: usize,
-    },
-    NotFound,
+impl Encoder for Watermark {
+    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
+        match *self {
+            Watermark::Size(threshold) => (atoms::size(), threshold).encode(env),
+            Watermark::Memory(threshold) => (atoms::memory(), threshold).encode(env),
+        }
+    }
 }
 
-#[derive(Debug, PartialEq)]
-pub enum AppendBucketResult {
-    Ok,
-    MaxBucketSizeExceeded,
+/// A one-shot alert, `pid` is sent a message the first time the set reaches the watermark.
+pub struct Alert {
+    pid: Pid,
+    watermark: Watermark,
 }
 
-rustler_export_nifs! {
+export_nifs! {
     "Elixir.Discord.SortedSet.NifBridge",
     [
         ("add", 2, add),
+        ("add", 3, add_with_hint),
+        ("add_with_options", 3, add_with_options),
+        ("aggregate", 4, aggregate, SchedulerFlags::DirtyCpu),
+        ("alert", 3, alert),
+        ("alloc_report", 1, alloc_report),
         ("append_bucket", 2, append_bucket),
+        ("async_from_list", 5, async_from_list),
+        ("async_to_list", 2, async_to_list),
+        ("async_union", 3, async_union),
         ("at", 2, at),
+        ("at_many", 3, at_many),
+        ("chunks", 2, chunks),
+        ("compact", 1, compact, SchedulerFlags::DirtyCpu),
+        ("compare", 2, compare, SchedulerFlags::DirtyCpu),
+        ("bisect_left", 2, bisect_left),
+        ("bisect_right", 2, bisect_right),
+        ("ceiling", 2, ceiling),
+        ("concat", 2, concat),
+        ("contains", 2, contains),
+        ("contains_many", 2, contains_many),
         ("debug", 1, debug),
-        ("empty", 2, empty),
+        ("diff", 2, diff, SchedulerFlags::DirtyCpu),
+        ("difference", 2, difference, SchedulerFlags::DirtyCpu),
+        ("disjoint", 2, disjoint, SchedulerFlags::DirtyCpu),
+        ("dual_index_add", 2, dual_index_add),
+        ("dual_index_at", 3, dual_index_at),
+        ("dual_index_find_index", 3, dual_index_find_index),
+        ("dual_index_new", 4, dual_index_new),
+        ("dual_index_remove", 2, dual_index_remove),
+        ("dual_index_size", 1, dual_index_size),
+        ("dual_index_slice", 4, dual_index_slice),
+        ("dual_index_to_list", 2, dual_index_to_list),
+        ("empty", 3, empty),
+        ("equal", 2, equal, SchedulerFlags::DirtyCpu),
+        (
+            "estimate_union_size",
+            1,
+            estimate_union_size,
+            SchedulerFlags::DirtyCpu
+        ),
+        ("export_repro", 1, export_repro, SchedulerFlags::DirtyCpu),
+        ("export_static", 1, export_static, SchedulerFlags::DirtyCpu),
         ("find_index", 2, find_index),
-        ("new", 2, new),
+        ("find_index", 3, find_index_with_options),
+        ("first", 1, first),
+        ("floor", 2, floor),
+        ("flush", 1, flush),
+        ("fold", 4, fold),
+        ("get_and_update", 3, get_and_update),
+        ("get_by_id", 2, get_by_id),
+        ("global_stats", 0, global_stats),
+        ("group_counts", 2, group_counts, SchedulerFlags::DirtyCpu),
+        ("health", 1, health),
+        ("freeze", 1, freeze),
+        ("from_etf_binary", 4, from_etf_binary, SchedulerFlags::DirtyCpu),
+        ("intersection", 2, intersection, SchedulerFlags::DirtyCpu),
+        ("into_list", 1, into_list),
+        ("iterator", 2, iterator),
+        ("iterator_next", 2, iterator_next),
+        ("get_meta", 1, get_meta),
+        ("id_of", 2, id_of),
+        ("import_repro", 1, import_repro, SchedulerFlags::DirtyCpu),
+        ("last", 1, last),
+        ("last_modified", 1, last_modified),
+        ("merge_into", 2, merge_into, SchedulerFlags::DirtyCpu),
+        ("merge_many", 1, merge_many, SchedulerFlags::DirtyCpu),
+        ("merged_view", 1, merged_view),
+        ("merged_view_at", 2, merged_view_at),
+        ("merged_view_iterator", 2, merged_view_iterator),
+        ("merged_view_iterator_next", 2, merged_view_iterator_next),
+        ("merged_view_slice", 3, merged_view_slice),
+        ("metrics_prometheus", 2, metrics_prometheus),
+        ("move", 3, move_item),
+        ("move_range", 4, move_range),
+        ("multi_slice", 2, multi_slice),
+        ("namespaces_add", 3, namespaces_add),
+        ("namespaces_at", 3, namespaces_at),
+        ("namespaces_delete", 2, namespaces_delete),
+        ("namespaces_find_index", 3, namespaces_find_index),
+        ("namespaces_list", 1, namespaces_list),
+        ("namespaces_new", 3, namespaces_new),
+        ("namespaces_remove", 3, namespaces_remove),
+        ("namespaces_size", 2, namespaces_size),
+        ("namespaces_slice", 4, namespaces_slice),
+        ("namespaces_to_list", 2, namespaces_to_list),
+        ("new", 3, new),
+        ("page_of", 3, page_of),
+        ("partition", 2, partition),
+        ("persistent_add", 2, persistent_add),
+        ("persistent_at", 2, persistent_at),
+        ("persistent_find_index", 2, persistent_find_index),
+        ("persistent_new", 2, persistent_new),
+        ("persistent_remove", 2, persistent_remove),
+        ("persistent_size", 1, persistent_size),
+        ("persistent_slice", 3, persistent_slice),
+        ("persistent_snapshot", 1, persistent_snapshot),
+        ("persistent_to_list", 1, persistent_to_list),
+        ("pop_at", 2, pop_at),
+        ("pop_first", 1, pop_first),
+        ("pop_last", 1, pop_last),
+        ("put_meta", 2, put_meta),
+        ("queue_add", 2, queue_add),
+        ("queue_remove", 2, queue_remove),
+        ("range_view", 3, range_view),
+        ("range_view_at", 2, range_view_at),
+        ("range_view_iterator", 2, range_view_iterator),
+        ("range_view_iterator_next", 2, range_view_iterator_next),
+        ("range_view_size", 1, range_view_size),
+        ("range_view_slice", 3, range_view_slice),
+        ("register_collector", 1, register_collector),
         ("remove", 2, remove),
+        ("remove", 3, remove_with_options),
+        ("remove_at", 2, remove_at),
+        ("remove_at_many", 2, remove_at_many),
+        ("remove_range", 2, remove_range),
+        ("repair", 1, repair, SchedulerFlags::DirtyCpu),
+        ("sharded_add", 2, sharded_add),
+        ("sharded_at", 2, sharded_at),
+        ("sharded_find_index", 2, sharded_find_index),
+        ("sharded_new", 4, sharded_new),
+        ("sharded_remove", 2, sharded_remove),
+        ("sharded_shard_sizes", 1, sharded_shard_sizes),
+        ("sharded_size", 1, sharded_size),
+        ("sharded_slice", 3, sharded_slice),
+        ("sharded_to_list", 1, sharded_to_list),
         ("size", 1, size),
+        ("subscribe", 2, subscribe),
         ("slice", 3, slice),
+        ("slice_desc", 3, slice_desc),
+        ("slice_step", 4, slice_step),
+        ("slice_range", 2, slice_range),
+        ("swap", 2, swap),
+        ("thaw", 1, thaw),
         ("to_list", 1, to_list),
-    ],
-    Some(load)
+        ("to_list_reverse", 1, to_list_reverse),
+        ("union", 2, union, SchedulerFlags::DirtyCpu),
+        ("unsubscribe", 2, unsubscribe),
+        ("zip", 4, zip),
+    ]
 }
 
-fn load(env: Env, _info: Term) -> bool {
-    resource_struct_init!(SortedSetResource, env);
+/// The memory estimate of a set in the global accounting is taken when the set is created and
+/// refreshed every `ACCOUNTING_INTERVAL` mutations, so it lags behind the actual usage somewhat.
+const ACCOUNTING_INTERVAL: u64 = 64;
+
+/// Opens the resource types, creating them on the first load and taking them over from the old
+/// library on an upgrade.
+fn init_resources(env: Env, flags: NIF_RESOURCE_FLAGS) -> bool {
+    open_resource_type!(SortedSetResource, env, flags);
+    open_resource_type!(PersistentSetResource, env, flags);
+    open_resource_type!(IteratorResource, env, flags);
+    open_resource_type!(NamespacesResource, env, flags);
+    open_resource_type!(MergedViewResource, env, flags);
+    open_resource_type!(MergedIteratorResource, env, flags);
+    open_resource_type!(RangeViewResource, env, flags);
+    open_resource_type!(RangeIteratorResource, env, flags);
+    open_resource_type!(DualIndexResource, env, flags);
+    open_resource_type!(ShardedSetResource, env, flags);
     true
 }
 
 fn empty<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
-    let initial_item_capacity: usize = args[0].decode()?;
-    let max_bucket_size: usize = args[1].decode()?;
-
-    let initial_set_capacity: usize = (initial_item_capacity / max_bucket_size) + 1;
-
-    let configuration = Configuration {
-        max_bucket_size,
-        initial_set_capacity,
-    };
+    let configuration = decode_configuration(args)?;
 
-    let resource = ResourceArc::new(SortedSetResource(Mutex::new(SortedSet::empty(
-        configuration,
-    ))));
+    let resource = ResourceArc::new(SortedSetResource::new(
+        SortedSet::empty(configuration),
+        env.pid(),
+    ));
 
     Ok((atoms::ok(), resource).encode(env))
 }
 
 fn new<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
-    let initial_item_capacity: usize = args[0].decode()?;
-    let max_bucket_size: usize = args[1].decode()?;
-
-    let initial_set_capacity: usize = (initial_item_capacity / max_bucket_size) + 1;
-
-    let configuration = Configuration {
-        max_bucket_size,
-        initial_set_capacity,
-    };
+    let configuration = decode_configuration(args)?;
 
-    let resource = ResourceArc::new(SortedSetResource(Mutex::new(SortedSet::new(configuration))));
+    let resource = ResourceArc::new(SortedSetResource::new(
+        SortedSet::new(configuration),
+        env.pid(),
+    ));
 
     Ok((atoms::ok(), resource).encode(env))
 }
@@ -137,127 +573,520 @@ fn append_bucket<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
         _ => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
     };
 
-    let mut set = match resource.0.try_lock() {
-        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+    let mut set = match write_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
         Ok(guard) => guard,
     };
 
+    if set.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
+    }
+
+    let got = items.len();
+
     match set.append_bucket(items) {
-        AppendBucketResult::Ok => Ok(atoms::ok().encode(env)),
+        AppendBucketResult::Ok => {
+            check_alerts(env, &resource, &set);
+            Ok(atoms::ok().encode(env))
+        }
         AppendBucketResult::MaxBucketSizeExceeded => {
-            Ok((atoms::error(), atoms::max_bucket_size_exceeded()).encode(env))
+            let max = set.configuration().max_bucket_size;
+            let detail = (atoms::max_bucket_size_exceeded(), got, max);
+
+            Ok((atoms::error(), detail).encode(env))
         }
+        AppendBucketResult::SetFull => Ok((atoms::error(), atoms::set_full()).encode(env)),
     }
 }
 
 fn add<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    insert(env, args, None, AddIndex::Inserted, None, false)
+}
+
+fn add_with_hint<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let hint: usize = args[2].decode()?;
+
+    insert(env, args, Some(hint), AddIndex::Inserted, None, false)
+}
+
+fn add_with_options<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let options: Vec<(Atom, Term)> = args[2].decode()?;
+
+    let mut hint = None;
+    let mut index = AddIndex::Inserted;
+    let mut unsupported = None;
+    let mut iodata = false;
+
+    for (key, value) in options {
+        if key == atoms::unsupported() {
+            unsupported = Some(decode_unsupported(value)?);
+        } else if key == atoms::iodata() {
+            iodata = value.decode()?;
+        } else if key == atoms::hint() {
+            hint = Some(value.decode::<usize>()?);
+        } else if key == atoms::index() {
+            let value: Atom = value.decode()?;
+
+            index = if value == atoms::inserted() {
+                AddIndex::Inserted
+            } else if value == atoms::displaced() {
+                AddIndex::Displaced
+            } else {
+                return Err(Error::BadArg);
+            };
+        } else {
+            return Err(Error::BadArg);
+        }
+    }
+
+    insert(env, args, hint, index, unsupported.as_ref(), iodata)
+}
+
+/// Decodes the `:unsupported` option of an add, `:reject`, `:skip` or `{:replace, atom}`.
+fn decode_unsupported(term: Term) -> NifResult<Unsupported> {
+    if term.is_atom() {
+        let policy: Atom = term.decode()?;
+
+        if policy == atoms::reject() {
+            Ok(Unsupported::Reject)
+        } else if policy == atoms::skip() {
+            Ok(Unsupported::Skip)
+        } else {
+            Err(Error::BadArg)
+        }
+    } else {
+        let (policy, placeholder): (Atom, Term) = term.decode()?;
+
+        if policy != atoms::replace() || !placeholder.is_atom() {
+            return Err(Error::BadArg);
+        }
+
+        Ok(Unsupported::Replace(placeholder.atom_to_string()?))
+    }
+}
+
+/// Which index an add reports for an item that was added.
+#[derive(Clone, Copy, PartialEq)]
+enum AddIndex {
+    /// The index the item ended up at.
+    Inserted,
+
+    /// The index the item that used to be at the insertion point was pushed to, `nil` if the
+    /// item was added at the end of the set and did not displace anything.
+    Displaced,
+}
+
+/// Adds the item in `args[1]` to the set in `args[0]`, shared by the add NIFs.  With an
+/// `unsupported` policy the result carries the number of nested terms that were skipped or
+/// replaced as a fourth element.  With `iodata` an item that is iodata is stored as the Bitstring
+/// it flattens to.
+fn insert<'a>(
+    env: Env<'a>,
+    args: &[Term<'a>],
+    hint: Option<usize>,
+    index: AddIndex,
+    unsupported: Option<&Unsupported>,
+    iodata: bool,
+) -> NifResult<Term<'a>> {
     let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
         Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
         Ok(r) => r,
     };
 
-    let item = match convert_to_supported_term(&args[1]) {
+    let mut sanitized = 0;
+    let flattened = if iodata {
+        flatten_iodata(args[1])
+    } else {
+        None
+    };
+    let reencode = flattened.is_some() && !args[1].is_binary();
+    let converted = match flattened {
+        Some(bitstring) => Some(SupportedTerm::Bitstring(bitstring)),
+        None => convert_sanitized(
+            &args[1],
+            unsupported.unwrap_or(&Unsupported::Reject),
+            &mut sanitized,
+        ),
+    };
+
+    let item = match converted {
         None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
         Some(term) => term,
     };
 
-    let mut set = match resource.0.try_lock() {
-        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+    // Subscribers are told about the item as it was stored
+    let element = if sanitized > 0 || reencode {
+        supported_term::encode(env, &item)
+    } else {
+        args[1]
+    };
+
+    let mut set = match write_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
         Ok(guard) => guard,
     };
 
-    match set.add(item) {
-        AddResult::Added(idx) => Ok((atoms::ok(), atoms::added(), idx).encode(env)),
-        AddResult::Duplicate(idx) => Ok((atoms::ok(), atoms::duplicate(), idx).encode(env)),
+    if set.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
     }
-}
 
-fn remove<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
-    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
-        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
-        Ok(r) => r,
+    let key = if set.has_stable_ids() {
+        Some(item.clone())
+    } else {
+        None
     };
 
-    let item = match convert_to_supported_term(&args[1]) {
-        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
-        Some(term) => term,
+    let result = match hint {
+        Some(hint) => set.add_with_hint(item, hint),
+        None => set.add(item),
     };
 
-    let mut set = match resource.0.try_lock() {
-        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
-        Ok(guard) => guard,
+    let (status, idx) = match result {
+        AddResult::Added(idx) => {
+            let id = key.and_then(|key| set.id_of(&key));
+            notify(
+                env,
+                &resource,
+                atoms::added(),
+                element,
+                idx,
+                set.version(),
+                id,
+            );
+            check_alerts(env, &resource, &set);
+
+            let idx = match index {
+                AddIndex::Inserted => Some(idx),
+                AddIndex::Displaced if idx + 1 < set.size() => Some(idx + 1),
+                AddIndex::Displaced => None,
+            };
+
+            (atoms::added(), idx)
+        }
+        AddResult::Duplicate(idx) => (atoms::duplicate(), Some(idx)),
+        AddResult::SetFull => return Ok((atoms::error(), atoms::set_full()).encode(env)),
     };
 
-    match set.remove(&item) {
-        RemoveResult::Removed(idx) => Ok((atoms::ok(), atoms::removed(), idx).encode(env)),
-        RemoveResult::NotFound => Ok((atoms::error(), atoms::not_found()).encode(env)),
+    match unsupported {
+        Some(_) => Ok((atoms::ok(), status, idx, sanitized).encode(env)),
+        None => Ok((atoms::ok(), status, idx).encode(env)),
     }
 }
 
-fn size<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+fn remove<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    delete(env, args, false)
+}
+
+fn remove_with_options<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let options: Vec<(Atom, Term)> = args[2].decode()?;
+
+    let mut noop = false;
+
+    for (key, value) in options {
+        if key == atoms::missing() {
+            let value: Atom = value.decode()?;
+
+            noop = if value == atoms::error() {
+                false
+            } else if value == atoms::noop() {
+                true
+            } else {
+                return Err(Error::BadArg);
+            };
+        } else {
+            return Err(Error::BadArg);
+        }
+    }
+
+    delete(env, args, noop)
+}
+
+/// Removes the items at the indexes in `args[1]` from the set in `args[0]` under a single write
+/// lock, returning the removed items in index order.  Subscribers are told about the removals
+/// from the last index to the first, so every notified index is still valid when it is applied.
+fn remove_at_many<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let indexes: Vec<usize> = args[1].decode()?;
+
+    remove_indexes(env, args, move |_| indexes)
+}
+
+/// Removes the items the Elixir Range in `args[1]` selects, following `Enum.slice/2`.
+fn remove_range<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let (first, last, step) = decode_enum_range(env, args[1])?;
+
+    remove_indexes(env, args, move |set| {
+        enum_slice_indexes(first, last, step, set.size())
+    })
+}
+
+/// Removes the items at the indexes `select` picks once the set in `args[0]` is locked, indexes
+/// past the end and repeated indexes are ignored.
+fn remove_indexes<'a, F>(env: Env<'a>, args: &[Term<'a>], select: F) -> NifResult<Term<'a>>
+where
+    F: FnOnce(&SortedSet) -> Vec<usize>,
+{
     let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
         Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
         Ok(r) => r,
     };
 
-    let set = match resource.0.try_lock() {
-        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+    let mut set = match write_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
         Ok(guard) => guard,
     };
 
-    Ok(set.size().encode(env))
+    if set.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
+    }
+
+    let mut indexes = select(&set);
+
+    // The same indexes the set removes, the IDs have to be looked up before the items are gone
+    let size = set.size();
+    indexes.retain(|&index| index < size);
+    indexes.sort_unstable();
+    indexes.dedup();
+
+    let ids: Vec<Option<u64>> = indexes
+        .iter()
+        .map(|&index| set.at(index).and_then(|item| set.id_of(item)))
+        .collect();
+
+    let removed = set.remove_at_many(&indexes);
+    let version = set.version();
+
+    for ((item, &index), &id) in removed.iter().zip(indexes.iter()).zip(ids.iter()).rev() {
+        notify(
+            env,
+            &resource,
+            atoms::removed(),
+            supported_term::encode(env, item),
+            index,
+            version,
+            id,
+        );
+    }
+
+    Ok((atoms::ok(), supported_term::encode_list(env, &removed)).encode(env))
 }
 
-fn to_list<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+/// Removes and returns the least item of the set in `args[0]`, `{:error, :empty}` if the set holds
+/// no items.
+fn pop_first<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    pop(env, args, Position::First, reply_item)
+}
+
+/// Removes and returns the greatest item of the set in `args[0]`, `{:error, :empty}` if the set
+/// holds no items.
+fn pop_last<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    pop(env, args, Position::Last, reply_item)
+}
+
+/// Removes and returns the item at the index in `args[1]` of the set in `args[0]`.
+fn pop_at<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let index: i64 = args[1].decode()?;
+
+    pop(env, args, Position::At(index), reply_item)
+}
+
+/// Removes the item at the index in `args[1]` of the set in `args[0]` and returns
+/// `{:ok, :removed, index}` like `remove`.
+fn remove_at<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let index: i64 = args[1].decode()?;
+
+    pop(env, args, Position::At(index), |env, _item, index| {
+        (atoms::ok(), atoms::removed(), index).encode(env)
+    })
+}
+
+fn reply_item<'a>(env: Env<'a>, item: Term<'a>, _index: usize) -> Term<'a> {
+    (atoms::ok(), item).encode(env)
+}
+
+/// The item `pop` removes from a set.
+enum Position {
+    First,
+    Last,
+
+    /// A negative index counts back from the end of the set.
+    At(i64),
+}
+
+/// Removes the item at `position` of the set in `args[0]`, the reply is built by `reply` out of the
+/// removed item and its index.
+fn pop<'a>(
+    env: Env<'a>,
+    args: &[Term<'a>],
+    position: Position,
+    reply: fn(Env<'a>, Term<'a>, usize) -> Term<'a>,
+) -> NifResult<Term<'a>> {
     let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
         Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
         Ok(r) => r,
     };
 
-    let set = match resource.0.try_lock() {
-        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+    let mut set = match write_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
         Ok(guard) => guard,
     };
 
-    Ok(set.to_vec().encode(env))
+    if set.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
+    }
+
+    let size = set.size();
+    let index = match position {
+        Position::At(index) => match set.resolve_index(index) {
+            None => return Ok(index_out_of_bounds(env, index, size)),
+            Some(index) => index,
+        },
+        _ if size == 0 => return Ok((atoms::error(), atoms::empty()).encode(env)),
+        Position::First => 0,
+        Position::Last => size - 1,
+    };
+
+    let id = set.at(index).and_then(|item| set.id_of(item));
+
+    let item = match set.pop_at(index) {
+        None => return Ok((atoms::error(), atoms::internal_inconsistency()).encode(env)),
+        Some(item) => supported_term::encode(env, &item),
+    };
+
+    notify(
+        env,
+        &resource,
+        atoms::removed(),
+        item,
+        index,
+        set.version(),
+        id,
+    );
+
+    Ok(reply(env, item, index))
 }
 
-fn at<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+/// Adds the item in `args[1]` to the set in `args[0]`, the write is queued as `{:ok, :queued}` if
+/// the set is configured with `coalesce_writes` and made right away like `add` otherwise.
+fn queue_add<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    match enqueue(env, args, Write::Add) {
+        Some(queued) => Ok(queued),
+        None => insert(env, args, None, AddIndex::Inserted, None, false),
+    }
+}
+
+/// Removes the item in `args[1]` from the set in `args[0]`, the write is queued as
+/// `{:ok, :queued}` if the set is configured with `coalesce_writes` and made right away like
+/// `remove` otherwise.
+fn queue_remove<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    match enqueue(env, args, Write::Remove) {
+        Some(queued) => Ok(queued),
+        None => delete(env, args, false),
+    }
+}
+
+/// Queues the write of the item in `args[1]` if the set in `args[0]` coalesces its writes, None
+/// if the write has to be made right away.
+fn enqueue<'a>(
+    env: Env<'a>,
+    args: &[Term<'a>],
+    write: fn(SupportedTerm) -> Write,
+) -> Option<Term<'a>> {
+    // Bad references are reported by the direct write
+    let resource: ResourceArc<SortedSetResource> = args[0].decode().ok()?;
+    let queue = resource.queue.as_ref()?;
+
+    let write = match convert_to_supported_term(&args[1]) {
+        None => return Some((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(item) => write(item),
+    };
+
+    // Writes to a set that is already frozen or full would be dropped when the queue is applied,
+    // refuse them while the caller can still tell.  A busy set is not waited for, the check is
+    // only a cheap look at the set as it is right now.
+    if let Ok(set) = resource.set.try_read(env) {
+        if set.is_frozen() {
+            return Some((atoms::error(), atoms::frozen()).encode(env));
+        }
+
+        if let Write::Add(_) = write {
+            if set.is_full() {
+                return Some((atoms::error(), atoms::set_full()).encode(env));
+            }
+        }
+    }
+
+    queue.push(write);
+
+    Some((atoms::ok(), atoms::queued()).encode(env))
+}
+
+/// Applies the writes queued on a set configured with `coalesce_writes`, returning how many were
+/// taken off the queue.
+fn flush<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
     let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
         Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
         Ok(r) => r,
     };
-    let index: usize = args[1].decode()?;
 
-    let set = match resource.0.try_lock() {
-        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+    let mut set = match resource.set.try_write(env) {
+        Err(error) => return Ok(lock_error(env, error)),
         Ok(guard) => guard,
     };
 
-    match set.at(index) {
-        None => Ok((atoms::error(), atoms::index_out_of_bounds()).encode(env)),
-        Some(value) => Ok((atoms::ok(), value).encode(env)),
-    }
+    let applied = apply_queued(env, &resource, &mut set);
+
+    Ok((atoms::ok(), applied).encode(env))
 }
 
-fn slice<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+/// Removes the item in `args[1]` from the set in `args[0]`, shared by the remove NIFs.  With
+/// `noop` an item that is not present returns `{:ok, :noop}` instead of an error.
+fn delete<'a>(env: Env<'a>, args: &[Term<'a>], noop: bool) -> NifResult<Term<'a>> {
     let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
         Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
         Ok(r) => r,
     };
 
-    let start: usize = args[1].decode()?;
-    let amount: usize = args[2].decode()?;
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
 
-    let set = match resource.0.try_lock() {
-        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+    let mut set = match write_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
         Ok(guard) => guard,
     };
 
-    Ok(set.slice(start, amount).encode(env))
+    if set.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
+    }
+
+    let id = set.id_of(&item);
+
+    match set.remove(&item) {
+        RemoveResult::Removed(idx) => {
+            notify(
+                env,
+                &resource,
+                atoms::removed(),
+                args[1],
+                idx,
+                set.version(),
+                id,
+            );
+            Ok((atoms::ok(), atoms::removed(), idx).encode(env))
+        }
+        RemoveResult::NotFound if noop => Ok((atoms::ok(), atoms::noop()).encode(env)),
+        RemoveResult::NotFound => Ok((atoms::error(), atoms::not_found()).encode(env)),
+        RemoveResult::InternalInconsistency => {
+            Ok((atoms::error(), atoms::internal_inconsistency()).encode(env))
+        }
+    }
 }
 
-fn find_index<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+/// Returns the item equal to `args[1]` and replaces it with the item of a `{:replace, item}`
+/// tuple or removes it for `:pop`, all under a single write lock.  Replacing an item that is not
+/// present adds the replacement.
+fn get_and_update<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
     let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
         Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
         Ok(r) => r,
@@ -268,76 +1097,3753 @@ fn find_index<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
         Some(term) => term,
     };
 
-    let set = match resource.0.try_lock() {
-        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+    let replacement = if args[2].is_atom() {
+        if args[2].decode::<Atom>()? != atoms::pop() {
+            return Err(Error::BadArg);
+        }
+
+        None
+    } else {
+        let (tag, term): (Atom, Term) = args[2].decode()?;
+
+        if tag != atoms::replace() {
+            return Err(Error::BadArg);
+        }
+
+        match convert_to_supported_term(&term) {
+            None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+            Some(replacement) => Some((replacement, term)),
+        }
+    };
+
+    let mut set = match write_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
         Ok(guard) => guard,
     };
 
-    match set.find_index(&item) {
-        FindResult::Found {
-            bucket_idx: _,
-            inner_idx: _,
-            idx,
-        } => Ok((atoms::ok(), idx).encode(env)),
-        FindResult::NotFound => Ok((atoms::error(), atoms::not_found()).encode(env)),
+    if set.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
+    }
+
+    let current = set.find_index(&item).and_then(|idx| set.at(idx)).cloned();
+
+    if let (Some(ref current), Some((ref replacement, _))) = (&current, &replacement) {
+        if current.cmp(replacement) == Ordering::Equal {
+            return Ok((
+                atoms::ok(),
+                atoms::found(),
+                supported_term::encode(env, current),
+            )
+                .encode(env));
+        }
+    }
+
+    if current.is_some() {
+        let id = set.id_of(&item);
+
+        match set.remove(&item) {
+            RemoveResult::Removed(idx) => {
+                notify(
+                    env,
+                    &resource,
+                    atoms::removed(),
+                    args[1],
+                    idx,
+                    set.version(),
+                    id,
+                );
+            }
+            RemoveResult::NotFound => {
+                return Ok((atoms::error(), atoms::not_found()).encode(env));
+            }
+            RemoveResult::InternalInconsistency => {
+                return Ok((atoms::error(), atoms::internal_inconsistency()).encode(env));
+            }
+        }
+    }
+
+    if let Some((replacement, term)) = replacement {
+        let key = if set.has_stable_ids() {
+            Some(replacement.clone())
+        } else {
+            None
+        };
+
+        match set.add(replacement) {
+            AddResult::Added(idx) => {
+                let id = key.and_then(|key| set.id_of(&key));
+                notify(env, &resource, atoms::added(), term, idx, set.version(), id);
+                check_alerts(env, &resource, &set);
+            }
+            AddResult::Duplicate(_) => {}
+            // Only reachable when nothing was removed, so the set is left untouched
+            AddResult::SetFull => return Ok((atoms::error(), atoms::set_full()).encode(env)),
+        }
+    }
+
+    match current {
+        Some(current) => Ok((
+            atoms::ok(),
+            atoms::found(),
+            supported_term::encode(env, &current),
+        )
+            .encode(env)),
+        None => Ok((atoms::ok(), atoms::not_found()).encode(env)),
     }
 }
 
-fn debug<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+fn size<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
     let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
         Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
         Ok(r) => r,
     };
 
-    let set = match resource.0.try_lock() {
-        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
         Ok(guard) => guard,
     };
 
-    Ok((atoms::ok(), set.debug()).encode(env))
+    Ok(set.size().encode(env))
 }
 
-fn convert_to_supported_term(term: &Term) -> Option<SupportedTerm> {
-    if term.is_number() {
-        match term.decode() {
-            Ok(i) => Some(SupportedTerm::Integer(i)),
-            Err(_) => None,
-        }
-    } else if term.is_atom() {
+fn to_list<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    Ok(supported_term::encode_list(env, &set.to_vec()))
+}
+
+fn to_list_reverse<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    Ok(supported_term::encode_list(env, &set.to_vec_rev()))
+}
+
+fn into_list<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let mut set = match write_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    if set.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
+    }
+
+    Ok(supported_term::encode_list(env, &set.drain()))
+}
+
+fn at<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+    let index: i64 = args[1].decode()?;
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    match set.resolve_index(index).and_then(|index| set.at(index)) {
+        None => Ok(index_out_of_bounds(env, index, set.size())),
+        Some(value) => Ok((atoms::ok(), supported_term::encode(env, value)).encode(env)),
+    }
+}
+
+/// Returns `{:ok, items}` with the item at each index of the list in `args[1]` of the set in
+/// `args[0]`, the term in `args[2]` stands in for the indexes past the end of the set.
+fn at_many<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+    // Either an Elixir Range selecting like `Enum.slice/2` or a list of indexes, negative indexes
+    // count from the end of the set
+    let range = decode_enum_range(env, args[1]).ok();
+    let listed: Vec<i64> = match range {
+        Some(_) => Vec::new(),
+        None => args[1].decode()?,
+    };
+    let default = args[2];
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let size = set.size();
+    let indexes: Vec<usize> = match range {
+        Some((first, last, step)) => enum_slice_indexes(first, last, step, size),
+        // An index that does not resolve is looked up past the end, producing the default
+        None => listed
+            .into_iter()
+            .map(|index| set.resolve_index(index).unwrap_or(size))
+            .collect(),
+    };
+
+    let items: Vec<Term<'a>> = set
+        .at_many(&indexes)
+        .into_iter()
+        .map(|item| match item {
+            Some(item) => supported_term::encode(env, item),
+            None => default,
+        })
+        .collect();
+
+    Ok((atoms::ok(), items).encode(env))
+}
+
+/// Returns the least item of the set in `args[0]` without a search, `{:error, :empty}` if the set
+/// holds no items.
+fn first<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    edge(env, args, SortedSet::first)
+}
+
+/// Returns the greatest item of the set in `args[0]` without a search, `{:error, :empty}` if the
+/// set holds no items.
+fn last<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    edge(env, args, SortedSet::last)
+}
+
+/// Returns one end of the set in `args[0]`, the item `item` reads from it.
+fn edge<'a>(
+    env: Env<'a>,
+    args: &[Term<'a>],
+    item: fn(&SortedSet) -> Option<&SupportedTerm>,
+) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    match item(&set) {
+        None => Ok((atoms::error(), atoms::empty()).encode(env)),
+        Some(value) => Ok((atoms::ok(), supported_term::encode(env, value)).encode(env)),
+    }
+}
+
+fn slice<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    // A negative start counts back from the end of the set, it is resolved once the set is locked
+    let from_end = match args[1].decode::<i64>() {
+        Ok(start) if start < 0 => Some(start),
+        _ => None,
+    };
+
+    let range = match from_end {
+        Some(_) => args[2].decode::<usize>().ok().map(|amount| (0, amount)),
+        None => decode_range(args[1], args[2]),
+    };
+
+    let (start, amount) = match range {
+        Some(range) => range,
+        None => return Ok((atoms::error(), atoms::invalid_range()).encode(env)),
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let start = match from_end.map(|start| set.resolve_index(start)) {
+        None => start,
+        Some(Some(start)) => start,
+        Some(None) => return Ok(supported_term::encode_list(env, &[])),
+    };
+
+    Ok(supported_term::encode_list(env, &set.slice(start, amount)))
+}
+
+fn slice_desc<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (start_from_end, amount) = match decode_range(args[1], args[2]) {
+        Some(range) => range,
+        None => return Ok((atoms::error(), atoms::invalid_range()).encode(env)),
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    Ok(supported_term::encode_list(
+        env,
+        &set.slice_desc(start_from_end, amount),
+    ))
+}
+
+/// Aggregates `amount` items starting at `start`, `{:error, :not_integer}` is returned if any of
+/// them is not an integer.
+fn aggregate<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (start, amount) = match decode_range(args[1], args[2]) {
+        Some(range) => range,
+        None => return Ok((atoms::error(), atoms::invalid_range()).encode(env)),
+    };
+
+    let kind: Atom = args[3].decode()?;
+    let kind = if kind == atoms::sum() {
+        Aggregate::Sum
+    } else if kind == atoms::min() {
+        Aggregate::Min
+    } else if kind == atoms::max() {
+        Aggregate::Max
+    } else if kind == atoms::avg() {
+        Aggregate::Avg
+    } else {
+        return Err(Error::BadArg);
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    match aggregate::aggregate(set.iter_from(start).take(amount), kind) {
+        Ok(Aggregated::Integer(value)) => Ok((atoms::ok(), value).encode(env)),
+        Ok(Aggregated::Float(value)) => Ok((atoms::ok(), value).encode(env)),
+        Ok(Aggregated::Empty) => Ok((atoms::ok(), rustler::types::atom::nil()).encode(env)),
+        Err(AggregateError::NotInteger) => Ok((atoms::error(), atoms::not_integer()).encode(env)),
+        Err(AggregateError::Overflow) => Ok((atoms::error(), atoms::overflow()).encode(env)),
+    }
+}
+
+/// Counts the tuples of the set by the value of their element at `args[1]`, returned as a map
+/// from each value to its count.
+fn group_counts<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let index: usize = args[1].decode()?;
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let counts = aggregate::group_counts(set.iter(), index);
+
+    let mut map = Term::map_new(env);
+    for (value, count) in counts {
+        map = map.map_put(supported_term::encode(env, &value), count.encode(env))?;
+    }
+
+    Ok((atoms::ok(), map).encode(env))
+}
+
+/// Runs a fold over up to `budget` items.  The cursor is `nil` for the first step and the cursor
+/// returned by the previous step otherwise, a step returns `{:cont, cursor}` until every item has
+/// been visited and `{:ok, result}` after that.
+fn fold<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let fold = decode_fold(args[1])?;
+    let state = if args[2] == rustler::types::atom::nil().encode(env) {
+        fold.start()
+    } else {
+        decode_fold_cursor(&fold, args[2])?
+    };
+
+    let budget: usize = args[3].decode()?;
+    if budget == 0 {
+        return Err(Error::BadArg);
+    }
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    match fold.step(&set, state, budget) {
+        Ok(Step::Done(accumulator)) => {
+            let result = match accumulator {
+                Accumulator::Count(count) => count.encode(env),
+                Accumulator::Sum(sum) => sum.encode(env),
+                Accumulator::Extreme(Some(ref value)) => supported_term::encode(env, value),
+                Accumulator::Extreme(None) => rustler::types::atom::nil().encode(env),
+            };
+
+            Ok((atoms::ok(), result).encode(env))
+        }
+        Ok(Step::Continue(state)) => {
+            let accumulator = match state.accumulator {
+                Accumulator::Count(count) => count.encode(env),
+                Accumulator::Sum(sum) => sum.encode(env),
+                Accumulator::Extreme(ref extreme) => {
+                    let elements: Vec<Term> = extreme
+                        .iter()
+                        .map(|value| supported_term::encode(env, value))
+                        .collect();
+                    make_tuple(env, &elements)
+                }
+            };
+            let after = match state.after {
+                Some(ref item) => supported_term::encode(env, item),
+                None => rustler::types::atom::nil().encode(env),
+            };
+            let cursor = (atoms::sorted_set_fold(), after, accumulator).encode(env);
+
+            Ok((atoms::cont(), cursor).encode(env))
+        }
+        Err(FoldError::MissingElement) => {
+            Ok((atoms::error(), atoms::missing_element()).encode(env))
+        }
+        Err(FoldError::NotInteger) => Ok((atoms::error(), atoms::not_integer()).encode(env)),
+        Err(FoldError::Overflow) => Ok((atoms::error(), atoms::overflow()).encode(env)),
+    }
+}
+
+/// Decodes a fold, `{:count, pattern}`, `{:sum, index}`, `{:min, index}` or `{:max, index}`.
+fn decode_fold(term: Term) -> NifResult<Fold> {
+    let (kind, argument): (Atom, Term) = term.decode()?;
+
+    if kind == atoms::count() {
+        return match convert_to_supported_term(&argument) {
+            Some(pattern) => Ok(Fold::Count(pattern)),
+            None => Err(Error::BadArg),
+        };
+    }
+
+    let index: usize = argument.decode()?;
+
+    if kind == atoms::sum() {
+        Ok(Fold::Sum(index))
+    } else if kind == atoms::min() {
+        Ok(Fold::Min(index))
+    } else if kind == atoms::max() {
+        Ok(Fold::Max(index))
+    } else {
+        Err(Error::BadArg)
+    }
+}
+
+/// Decodes the cursor returned by a previous step of the same fold.
+fn decode_fold_cursor(fold: &Fold, term: Term) -> NifResult<FoldState> {
+    let (tag, after, accumulator): (Atom, Term, Term) = term.decode()?;
+
+    if tag != atoms::sorted_set_fold() {
+        return Err(Error::BadArg);
+    }
+
+    let after = match convert_to_supported_term(&after) {
+        Some(item) => item,
+        None => return Err(Error::BadArg),
+    };
+
+    let accumulator = match fold.start().accumulator {
+        Accumulator::Count(_) => Accumulator::Count(accumulator.decode()?),
+        Accumulator::Sum(_) => Accumulator::Sum(accumulator.decode()?),
+        Accumulator::Extreme(_) => match get_tuple(accumulator)?.as_slice() {
+            [] => Accumulator::Extreme(None),
+            [value] => match convert_to_supported_term(value) {
+                Some(value) => Accumulator::Extreme(Some(value)),
+                None => return Err(Error::BadArg),
+            },
+            _ => return Err(Error::BadArg),
+        },
+    };
+
+    Ok(FoldState {
+        accumulator,
+        after: Some(after),
+    })
+}
+
+fn slice_step<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (start, amount) = match decode_range(args[1], args[2]) {
+        Some(range) => range,
+        None => return Ok((atoms::error(), atoms::invalid_range()).encode(env)),
+    };
+
+    let step = match args[3].decode::<usize>() {
+        Ok(step) if step > 0 => step,
+        _ => return Ok((atoms::error(), atoms::invalid_range()).encode(env)),
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    Ok(supported_term::encode_list(
+        env,
+        &set.slice_step(start, amount, step),
+    ))
+}
+
+/// Slices the set with an Elixir Range the way `Enum.slice/2` slices a list, negative bounds count
+/// from the end of the set and a step above 1 samples the slice like `slice_step`.
+fn slice_range<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (first, last, step) = decode_enum_range(env, args[1])?;
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let items = match enum_slice_bounds(first, last, set.size()) {
+        None => Vec::new(),
+        Some((start, amount)) if step == 1 => set.slice(start, amount),
+        Some((start, amount)) => set.slice_step(start, amount, step),
+    };
+
+    Ok(supported_term::encode_list(env, &items))
+}
+
+/// Decodes the first, last and step of an Elixir Range.
+fn decode_enum_range<'a>(env: Env<'a>, range: Term<'a>) -> NifResult<(i64, i64, usize)> {
+    let first: i64 = range.map_get(atoms::first().encode(env))?.decode()?;
+    let last: i64 = range.map_get(atoms::last().encode(env))?.decode()?;
+
+    // Ranges from before Elixir 1.12 have no step, decreasing ones slice like increasing ones
+    let step = match range.map_get(atoms::step().encode(env)) {
+        Ok(step) => step.decode::<i64>()?.max(1) as usize,
+        Err(_) => 1,
+    };
+
+    Ok((first, last, step))
+}
+
+/// The indexes `first..last//step` selects from `size` items, following `Enum.slice/2`.
+fn enum_slice_indexes(first: i64, last: i64, step: usize, size: usize) -> Vec<usize> {
+    match enum_slice_bounds(first, last, size) {
+        None => Vec::new(),
+        Some((start, amount)) => (start..start + amount).step_by(step).collect(),
+    }
+}
+
+/// The start and amount of the items `first..last` selects from `size` items, following
+/// `Enum.slice/2`.  A negative `first` or `last` counts from the end, a `first` before the start
+/// is moved to the start.  None if the range selects nothing.
+fn enum_slice_bounds(first: i64, last: i64, size: usize) -> Option<(usize, usize)> {
+    let size = size as i64;
+
+    let first = if first >= 0 {
+        first
+    } else {
+        (first + size).max(0)
+    };
+    let last = if last >= 0 { last } else { last + size };
+
+    if first >= size || last < first {
+        return None;
+    }
+
+    let amount = (last - first + 1).min(size - first);
+
+    Some((first as usize, amount as usize))
+}
+
+fn multi_slice<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let windows: Vec<(Term, Term)> = args[1].decode()?;
+    let windows: Vec<(usize, usize)> = match windows
+        .into_iter()
+        .map(|(start, amount)| decode_range(start, amount))
+        .collect()
+    {
+        Some(windows) => windows,
+        None => return Ok((atoms::error(), atoms::invalid_range()).encode(env)),
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let slices: Vec<Term> = set
+        .multi_slice(&windows)
+        .iter()
+        .map(|slice| supported_term::encode_list(env, slice))
+        .collect();
+
+    Ok(slices.encode(env))
+}
+
+fn chunks<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let chunk_size: usize = args[1].decode()?;
+
+    if chunk_size < 1 {
+        return Err(Error::BadArg);
+    }
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let chunks: Vec<Term> = set
+        .chunks(chunk_size)
+        .iter()
+        .map(|chunk| supported_term::encode_list(env, chunk))
+        .collect();
+
+    Ok(chunks.encode(env))
+}
+
+/// Pairs the items at the same indexes of two sets, stopping at the end of the shorter slice.
+fn zip<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource_a: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let resource_b: ResourceArc<SortedSetResource> = match args[1].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (start, amount) = match decode_range(args[2], args[3]) {
+        Some(range) => range,
+        None => return Ok((atoms::error(), atoms::invalid_range()).encode(env)),
+    };
+
+    let a = match read_set(env, &resource_a) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let left = a.slice(start, amount);
+    let right = if ptr::eq(&*resource_a, &*resource_b) {
+        // Taking a second read lock on the same set from one thread can deadlock
+        left.clone()
+    } else {
+        match read_set(env, &resource_b) {
+            Err(error) => return Ok(lock_error(env, error)),
+            Ok(b) => b.slice(start, amount),
+        }
+    };
+
+    let pairs: Vec<Term> = left
+        .iter()
+        .zip(right.iter())
+        .map(|(l, r)| {
+            (
+                supported_term::encode(env, l),
+                supported_term::encode(env, r),
+            )
+                .encode(env)
+        })
+        .collect();
+
+    Ok(pairs.encode(env))
+}
+
+fn find_index<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    match set.find_index(&item) {
+        Some(idx) => Ok((atoms::ok(), idx).encode(env)),
+        None => Ok((atoms::error(), atoms::not_found()).encode(env)),
+    }
+}
+
+/// Returns `{:ok, rank}`, the number of items of the set in `args[0]` less than the item in
+/// `args[1]`.
+fn bisect_left<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    bisect(env, args, SortedSet::rank)
+}
+
+/// Returns `{:ok, rank}`, the number of items of the set in `args[0]` less than or equal to the
+/// item in `args[1]`.
+fn bisect_right<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    bisect(env, args, SortedSet::rank_past)
+}
+
+fn bisect<'a>(
+    env: Env<'a>,
+    args: &[Term<'a>],
+    rank: fn(&SortedSet, &SupportedTerm) -> usize,
+) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    Ok((atoms::ok(), rank(&set, &item)).encode(env))
+}
+
+/// Returns `{:ok, item}` with the greatest item of the set in `args[0]` less than or equal to the
+/// item in `args[1]`, `{:error, :not_found}` if there is none.
+fn floor<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    neighbor(env, args, SortedSet::floor)
+}
+
+/// Returns `{:ok, item}` with the least item of the set in `args[0]` greater than or equal to the
+/// item in `args[1]`, `{:error, :not_found}` if there is none.
+fn ceiling<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    neighbor(env, args, SortedSet::ceiling)
+}
+
+fn neighbor<'a>(
+    env: Env<'a>,
+    args: &[Term<'a>],
+    find: for<'s> fn(&'s SortedSet, &SupportedTerm) -> Option<&'s SupportedTerm>,
+) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    match find(&set, &item) {
+        None => Ok((atoms::error(), atoms::not_found()).encode(env)),
+        Some(value) => Ok((atoms::ok(), supported_term::encode(env, value)).encode(env)),
+    }
+}
+
+/// Returns `{:ok, boolean}`, whether the set in `args[0]` holds the item in `args[1]`.
+fn contains<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    Ok((atoms::ok(), set.contains(&item)).encode(env))
+}
+
+/// Returns `{:ok, [boolean]}`, whether the set in `args[0]` holds each item of the list in
+/// `args[1]`, all under a single read lock.
+fn contains_many<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let items = match convert_to_supported_term(&args[1]) {
+        Some(SupportedTerm::List(terms)) => terms,
+        _ => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    Ok((atoms::ok(), set.contains_many(&items)).encode(env))
+}
+
+/// Finds the index of the item in `args[1]` like `find_index`, with the options in `args[2]`.
+/// With `insertion_point: true` a miss returns `{:not_found, index}` with the index the item
+/// would be inserted at, taken from the same search.
+fn find_index_with_options<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let options: Vec<(Atom, Term)> = args[2].decode()?;
+
+    let mut insertion_point = false;
+
+    for (key, value) in options {
+        if key == atoms::insertion_point() {
+            insertion_point = value.decode()?;
+        } else {
+            return Err(Error::BadArg);
+        }
+    }
+
+    if !insertion_point {
+        return find_index(env, args);
+    }
+
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    match set.search(&item) {
+        Ok(idx) => Ok((atoms::ok(), idx).encode(env)),
+        Err(idx) => Ok((atoms::not_found(), idx).encode(env)),
+    }
+}
+
+/// Finds the page of `page_size` items holding the item, returning the page, the offset of the
+/// item within it and the items of the page read under the same lock.
+fn page_of<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let page_size: usize = args[2].decode()?;
+    if page_size == 0 {
+        return Err(Error::BadArg);
+    }
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    match set.find_index(&item) {
+        Some(idx) => {
+            let page = idx / page_size;
+            let items = set.slice(page * page_size, page_size);
+
+            Ok((
+                atoms::ok(),
+                page,
+                idx % page_size,
+                supported_term::encode_list(env, &items),
+            )
+                .encode(env))
+        }
+        None => Ok((atoms::error(), atoms::not_found()).encode(env)),
+    }
+}
+
+fn id_of<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    if !set.has_stable_ids() {
+        return Ok((atoms::error(), atoms::stable_ids_disabled()).encode(env));
+    }
+
+    match set.id_of(&item) {
+        Some(id) => Ok((atoms::ok(), id).encode(env)),
+        None => Ok((atoms::error(), atoms::not_found()).encode(env)),
+    }
+}
+
+fn get_by_id<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+    let id: u64 = args[1].decode()?;
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    if !set.has_stable_ids() {
+        return Ok((atoms::error(), atoms::stable_ids_disabled()).encode(env));
+    }
+
+    match set.get_by_id(id) {
+        Some(item) => Ok((atoms::ok(), supported_term::encode(env, item)).encode(env)),
+        None => Ok((atoms::error(), atoms::not_found()).encode(env)),
+    }
+}
+
+fn last_modified<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let (system_time, instant) = set.last_modified();
+
+    let system_time_ms = match system_time.duration_since(UNIX_EPOCH) {
+        Ok(duration) => duration.as_millis() as u64,
+        Err(_) => 0,
+    };
+    let idle_ms = instant.elapsed().as_millis() as u64;
+
+    Ok((atoms::ok(), system_time_ms, idle_ms).encode(env))
+}
+
+fn put_meta<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let meta = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let mut set = match write_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    if set.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
+    }
+
+    set.put_meta(meta);
+
+    Ok(atoms::ok().encode(env))
+}
+
+fn get_meta<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    match set.meta() {
+        None => Ok((atoms::error(), atoms::not_found()).encode(env)),
+        Some(meta) => Ok((atoms::ok(), supported_term::encode(env, meta)).encode(env)),
+    }
+}
+
+fn freeze<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let mut set = match write_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    set.freeze();
+
+    Ok(atoms::ok().encode(env))
+}
+
+fn thaw<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let mut set = match write_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    set.thaw();
+
+    Ok(atoms::ok().encode(env))
+}
+
+fn swap<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource_a: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let resource_b: ResourceArc<SortedSetResource> = match args[1].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (first, second) = lock_order(&resource_a, &resource_b);
+
+    if ptr::eq(&**first, &**second) {
+        // Swapping a set with itself is a no-op
+        return Ok(atoms::ok().encode(env));
+    }
+
+    let first_guard = match write_set(env, first) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let second_guard = match write_set(env, second) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let (mut a, mut b) = if ptr::eq(&**first, &*resource_a) {
+        (first_guard, second_guard)
+    } else {
+        (second_guard, first_guard)
+    };
+
+    if a.is_frozen() || b.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
+    }
+
+    // The lock and the write queue of a resource are chosen from these options when it is
+    // created, they cannot travel with the contents
+    let (config_a, config_b) = (a.configuration(), b.configuration());
+    if config_a.single_owner != config_b.single_owner
+        || config_a.coalesce_writes != config_b.coalesce_writes
+    {
+        return Ok((atoms::error(), atoms::configuration_mismatch()).encode(env));
+    }
+
+    a.swap(&mut b);
+
+    check_alerts(env, &resource_a, &a);
+    check_alerts(env, &resource_b, &b);
+
+    Ok(atoms::ok().encode(env))
+}
+
+fn partition<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let pivot = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let mut set = match write_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    if set.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
+    }
+
+    let (lower, upper) = set.partition(&pivot);
+
+    let lower = ResourceArc::new(SortedSetResource::new(lower, env.pid()));
+    let upper = ResourceArc::new(SortedSetResource::new(upper, env.pid()));
+
+    Ok((atoms::ok(), lower, upper).encode(env))
+}
+
+fn concat<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource_a: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let resource_b: ResourceArc<SortedSetResource> = match args[1].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (first, second) = lock_order(&resource_a, &resource_b);
+
+    if ptr::eq(&**first, &**second) {
+        // A set always overlaps itself
+        return Ok((atoms::error(), atoms::overlapping()).encode(env));
+    }
+
+    let first_guard = match write_set(env, first) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let second_guard = match write_set(env, second) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let (mut a, mut b) = if ptr::eq(&**first, &*resource_a) {
+        (first_guard, second_guard)
+    } else {
+        (second_guard, first_guard)
+    };
+
+    if a.is_frozen() || b.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
+    }
+
+    match a.concat(&mut b) {
+        ConcatResult::Ok => {
+            check_alerts(env, &resource_a, &a);
+            Ok(atoms::ok().encode(env))
+        }
+        ConcatResult::Overlapping => Ok((atoms::error(), atoms::overlapping()).encode(env)),
+        ConcatResult::SetFull => Ok((atoms::error(), atoms::set_full()).encode(env)),
+        ConcatResult::CollationMismatch => {
+            Ok((atoms::error(), atoms::collation_mismatch()).encode(env))
+        }
+        ConcatResult::ComparatorMismatch => {
+            Ok((atoms::error(), atoms::comparator_mismatch()).encode(env))
+        }
+        ConcatResult::CharlistsMismatch => {
+            Ok((atoms::error(), atoms::charlists_mismatch()).encode(env))
+        }
+    }
+}
+
+/// Adds every item of the set in `args[1]` to the set in `args[0]`, returning how many items were
+/// new to it.  The source set is left untouched, subscribers of the destination are not notified
+/// of the items, like for other bulk operations.
+fn merge_into<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource_a: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let resource_b: ResourceArc<SortedSetResource> = match args[1].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (first, second) = lock_order(&resource_a, &resource_b);
+
+    if ptr::eq(&**first, &**second) {
+        // A set already holds every item of itself
+        return match write_set(env, first) {
+            Err(error) => Ok(lock_error(env, error)),
+            Ok(ref set) if set.is_frozen() => Ok((atoms::error(), atoms::frozen()).encode(env)),
+            Ok(_) => Ok((atoms::ok(), 0).encode(env)),
+        };
+    }
+
+    let first_guard = match write_set(env, first) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let second_guard = match write_set(env, second) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let (mut destination, source) = if ptr::eq(&**first, &*resource_a) {
+        (first_guard, second_guard)
+    } else {
+        (second_guard, first_guard)
+    };
+
+    if destination.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
+    }
+
+    match destination.merge_from(&source) {
+        MergeResult::Merged(count) => {
+            check_alerts(env, &resource_a, &destination);
+            Ok((atoms::ok(), count).encode(env))
+        }
+        MergeResult::SetFull => Ok((atoms::error(), atoms::set_full()).encode(env)),
+    }
+}
+
+/// Moves an item from one set to another while holding both locks, so no reader can see the
+/// item in both sets or in neither.
+fn move_item<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let from_resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let to_resource: ResourceArc<SortedSetResource> = match args[1].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[2]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let (first, second) = lock_order(&from_resource, &to_resource);
+
+    if ptr::eq(&**first, &**second) {
+        return Ok((atoms::error(), atoms::same_set()).encode(env));
+    }
+
+    let first_guard = match write_set(env, first) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let second_guard = match write_set(env, second) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let (mut from, mut to) = if ptr::eq(&**first, &*from_resource) {
+        (first_guard, second_guard)
+    } else {
+        (second_guard, first_guard)
+    };
+
+    if from.is_frozen() || to.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
+    }
+
+    if from.find_index(&item).is_none() {
+        return Ok((atoms::error(), atoms::not_found()).encode(env));
+    }
+
+    // Adding first leaves both sets untouched when the destination is full
+    let from_id = from.id_of(&item);
+    let to_index = match to.add(item.clone()) {
+        AddResult::SetFull => return Ok((atoms::error(), atoms::set_full()).encode(env)),
+        AddResult::Duplicate(idx) => idx,
+        AddResult::Added(idx) => {
+            let id = to.id_of(&item);
+            notify(
+                env,
+                &to_resource,
+                atoms::added(),
+                args[2],
+                idx,
+                to.version(),
+                id,
+            );
+            check_alerts(env, &to_resource, &to);
+            idx
+        }
+    };
+
+    match from.remove(&item) {
+        RemoveResult::Removed(from_index) => {
+            notify(
+                env,
+                &from_resource,
+                atoms::removed(),
+                args[2],
+                from_index,
+                from.version(),
+                from_id,
+            );
+            Ok((atoms::ok(), from_index, to_index).encode(env))
+        }
+        RemoveResult::NotFound => Ok((atoms::error(), atoms::not_found()).encode(env)),
+        RemoveResult::InternalInconsistency => {
+            Ok((atoms::error(), atoms::internal_inconsistency()).encode(env))
+        }
+    }
+}
+
+fn move_range<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let from_resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let to_resource: ResourceArc<SortedSetResource> = match args[1].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (min, max) = match (
+        convert_to_supported_term(&args[2]),
+        convert_to_supported_term(&args[3]),
+    ) {
+        (Some(min), Some(max)) => (min, max),
+        _ => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+    };
+
+    let (first, second) = lock_order(&from_resource, &to_resource);
+
+    if ptr::eq(&**first, &**second) {
+        return Ok((atoms::error(), atoms::same_set()).encode(env));
+    }
+
+    let first_guard = match write_set(env, first) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let second_guard = match write_set(env, second) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let (mut from, mut to) = if ptr::eq(&**first, &*from_resource) {
+        (first_guard, second_guard)
+    } else {
+        (second_guard, first_guard)
+    };
+
+    if from.is_frozen() || to.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
+    }
+
+    match from.move_range(&mut to, &min, &max) {
+        MoveRangeResult::Moved(count) => {
+            check_alerts(env, &to_resource, &to);
+            Ok((atoms::ok(), count).encode(env))
+        }
+        MoveRangeResult::SetFull => Ok((atoms::error(), atoms::set_full()).encode(env)),
+    }
+}
+
+fn subscribe<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let pid: Pid = args[1].decode()?;
+
+    let mut subscribers = match resource.subscribers.try_lock() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    if !subscribers
+        .iter()
+        .any(|subscriber| subscriber.encode(env) == args[1])
+    {
+        subscribers.push(pid);
+    }
+
+    Ok(atoms::ok().encode(env))
+}
+
+fn unsubscribe<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let _: Pid = args[1].decode()?;
+
+    let mut subscribers = match resource.subscribers.try_lock() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    subscribers.retain(|subscriber| subscriber.encode(env) != args[1]);
+
+    Ok(atoms::ok().encode(env))
+}
+
+fn alert<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (kind, threshold): (Atom, usize) = args[1].decode()?;
+    let pid: Pid = args[2].decode()?;
+
+    let watermark = if kind == atoms::size() {
+        Watermark::Size(threshold)
+    } else if kind == atoms::memory() {
+        Watermark::Memory(threshold)
+    } else {
+        return Err(Error::BadArg);
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    match resource.alerts.try_lock() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(mut alerts) => alerts.push(Alert { pid, watermark }),
+    };
+
+    // A set that is already past the watermark alerts immediately
+    check_alerts(env, &resource, &set);
+
+    Ok(atoms::ok().encode(env))
+}
+
+fn async_to_list<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    // The job runs outside of any process and can not notify subscribers, queued writes are
+    // applied here instead
+    try_flush(env, &resource);
+
+    reply_async(env, args[1], &[], move |env, _| match resource.set.read() {
+        Err(error) => lock_error(env, error),
+        Ok(set) => supported_term::encode_list(env, &set.to_vec()),
+    });
+
+    Ok(atoms::ok().encode(env))
+}
+
+fn async_from_list<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let configuration = decode_configuration(&args[0..3])?;
+
+    if !args[3].is_list() {
+        return Err(Error::BadArg);
+    }
+
+    let caller = env.pid();
+
+    reply_async(env, args[4], &args[3..4], move |env, inputs| {
+        let items = match convert_to_supported_term(&inputs[0]) {
+            Some(SupportedTerm::List(items)) => items,
+            _ => return (atoms::error(), atoms::unsupported_type()).encode(env),
+        };
+
+        encode_build_result(env, caller, SortedSet::from_unsorted(configuration, items))
+    });
+
+    Ok(atoms::ok().encode(env))
+}
+
+/// Bulk loads a SortedSet from a binary produced by `:erlang.term_to_binary/1` holding a list of
+/// terms.  Runs on a dirty scheduler as decoding and sorting large payloads takes a while.
+fn from_etf_binary<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let configuration = decode_configuration(&args[0..3])?;
+    let binary: Binary = args[3].decode()?;
+
+    let items = match etf::decode(binary.as_slice()) {
+        Ok(SupportedTerm::List(items)) => items,
+        Ok(_) | Err(EtfError::Unsupported) => {
+            return Ok((atoms::error(), atoms::unsupported_type()).encode(env))
+        }
+        Err(EtfError::Invalid) => return Ok((atoms::error(), atoms::invalid_etf()).encode(env)),
+    };
+
+    Ok(encode_build_result(
+        env,
+        env.pid(),
+        SortedSet::from_unsorted(configuration, items),
+    ))
+}
+
+fn async_union<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let left: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let right: ResourceArc<SortedSetResource> = match args[1].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let caller = env.pid();
+
+    try_flush(env, &left);
+    try_flush(env, &right);
+
+    reply_async(env, args[2], &[], move |env, _| {
+        let left_set = match left.set.read() {
+            Err(error) => return lock_error(env, error),
+            Ok(guard) => guard,
+        };
+
+        if ptr::eq(&*left, &*right) {
+            // Taking a second read lock on the same set from one thread can deadlock
+            return encode_build_result(env, caller, left_set.union(&left_set));
+        }
+
+        let right_set = match right.set.read() {
+            Err(error) => return lock_error(env, error),
+            Ok(guard) => guard,
+        };
+
+        encode_build_result(env, caller, left_set.union(&right_set))
+    });
+
+    Ok(atoms::ok().encode(env))
+}
+
+/// Builds the union of the sets in `args[0]` and `args[1]` with a single merge of both, like
+/// `async_union` but on the calling process.
+fn union<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    combine(env, args, SortedSet::union)
+}
+
+/// Builds the intersection of the sets in `args[0]` and `args[1]` with a single walk of both.
+fn intersection<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    combine(env, args, SortedSet::intersection)
+}
+
+/// Builds a set of the items of the set in `args[0]` that are not in the set in `args[1]` with a
+/// single walk of both.
+fn difference<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    combine(env, args, SortedSet::difference)
+}
+
+/// Whether the sets in `args[0]` and `args[1]` have no item in common, see
+/// `SortedSet::is_disjoint`.
+fn disjoint<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    check_pair(env, args, SortedSet::is_disjoint)
+}
+
+/// Whether the sets in `args[0]` and `args[1]` hold the same items, see `SortedSet::same_items`.
+fn equal<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    check_pair(env, args, SortedSet::same_items)
+}
+
+/// Returns `{:ok, :lt | :eq | :gt}` comparing the items of the sets in `args[0]` and `args[1]`
+/// in order, see `SortedSet::compare_items`.
+fn compare<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    read_pair(env, args, |left, right| {
+        if let Err(mismatch) = merged::check_order(&[left, right]) {
+            return order_mismatch(env, mismatch);
+        }
+
+        let ordering = match left.compare_items(right) {
+            Ordering::Less => atoms::lt(),
+            Ordering::Equal => atoms::eq(),
+            Ordering::Greater => atoms::gt(),
+        };
+
+        (atoms::ok(), ordering).encode(env)
+    })
+}
+
+/// Returns `{:ok, {added, removed}}`, the items of the set in `args[1]` missing from the set in
+/// `args[0]` and the items of the set in `args[0]` missing from the set in `args[1]`, see
+/// `SortedSet::diff`.
+fn diff<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    read_pair(env, args, |old, new| {
+        let (added, removed) = old.diff(new);
+
+        (
+            atoms::ok(),
+            (
+                supported_term::encode_list(env, &added),
+                supported_term::encode_list(env, &removed),
+            ),
+        )
+            .encode(env)
+    })
+}
+
+/// Reads the sets in `args[0]` and `args[1]` and returns `{:ok, boolean}` with the answer of
+/// `check` about them.
+fn check_pair<'a>(
+    env: Env<'a>,
+    args: &[Term<'a>],
+    check: fn(&SortedSet, &SortedSet) -> bool,
+) -> NifResult<Term<'a>> {
+    read_pair(env, args, |left, right| {
+        (atoms::ok(), check(left, right)).encode(env)
+    })
+}
+
+/// Read locks the sets in `args[0]` and `args[1]` and returns the term `answer` builds from them.
+fn read_pair<'a, F>(env: Env<'a>, args: &[Term<'a>], answer: F) -> NifResult<Term<'a>>
+where
+    F: FnOnce(&SortedSet, &SortedSet) -> Term<'a>,
+{
+    let left: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let right: ResourceArc<SortedSetResource> = match args[1].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let left_set = match read_set(env, &left) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    if ptr::eq(&*left, &*right) {
+        // Taking a second read lock on the same set from one thread can deadlock
+        return Ok(answer(&left_set, &left_set));
+    }
+
+    let right_set = match read_set(env, &right) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    Ok(answer(&left_set, &right_set))
+}
+
+/// Builds a new set out of the sets in `args[0]` and `args[1]` with `build`, the new set has the
+/// configuration of the first set.
+fn combine<'a>(
+    env: Env<'a>,
+    args: &[Term<'a>],
+    build: fn(&SortedSet, &SortedSet) -> BuildResult,
+) -> NifResult<Term<'a>> {
+    let left: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let right: ResourceArc<SortedSetResource> = match args[1].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let left_set = match read_set(env, &left) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    if ptr::eq(&*left, &*right) {
+        // Taking a second read lock on the same set from one thread can deadlock
+        return Ok(encode_build_result(
+            env,
+            env.pid(),
+            build(&left_set, &left_set),
+        ));
+    }
+
+    let right_set = match read_set(env, &right) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    Ok(encode_build_result(
+        env,
+        env.pid(),
+        build(&left_set, &right_set),
+    ))
+}
+
+fn debug<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    Ok((atoms::ok(), set.debug()).encode(env))
+}
+
+/// A map summarizing the state of a set for dashboards that poll many sets, every entry is read
+/// without visiting the items.
+fn health<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let encode_item = |item: Option<&SupportedTerm>| match item {
+        Some(item) => supported_term::encode(env, item),
+        None => rustler::types::atom::nil().encode(env),
+    };
+
+    let health = Term::map_new(env)
+        .map_put(atoms::size().encode(env), set.size().encode(env))?
+        .map_put(
+            atoms::buckets().encode(env),
+            set.bucket_stats().count.encode(env),
+        )?
+        .map_put(atoms::min().encode(env), encode_item(set.first()))?
+        .map_put(atoms::max().encode(env), encode_item(set.last()))?
+        .map_put(atoms::version().encode(env), set.version().encode(env))?
+        .map_put(atoms::frozen().encode(env), set.is_frozen().encode(env))?
+        .map_put(atoms::memory().encode(env), set.memory().encode(env))?;
+
+    Ok((atoms::ok(), health).encode(env))
+}
+
+/// Tallies the allocations reachable from a set by category, see `AllocReport`.  Only built with
+/// the `debug-alloc` feature, without it the NIF returns `{:error, :not_enabled}`.
+#[cfg(feature = "debug-alloc")]
+fn alloc_report<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let report = AllocReport::of(&set);
+    let tally = |tally: Tally| vec![(atoms::count(), tally.count), (atoms::bytes(), tally.bytes)];
+    let report = vec![
+        (atoms::buckets(), tally(report.buckets)),
+        (atoms::strings(), tally(report.strings)),
+        (atoms::tuples(), tally(report.tuples)),
+        (atoms::lists(), tally(report.lists)),
+    ];
+
+    Ok((atoms::ok(), report).encode(env))
+}
+
+#[cfg(not(feature = "debug-alloc"))]
+fn alloc_report<'a>(env: Env<'a>, _args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    Ok((atoms::error(), atoms::not_enabled()).encode(env))
+}
+
+fn repair<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let mut set = match write_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    if set.is_frozen() {
+        return Ok((atoms::error(), atoms::frozen()).encode(env));
+    }
+
+    let report = set.repair();
+    let report = vec![
+        (atoms::unsorted_buckets(), report.unsorted_buckets),
+        (atoms::duplicates(), report.duplicates),
+        (atoms::empty_buckets(), report.empty_buckets),
+        (atoms::overlapping_buckets(), report.overlapping_buckets),
+        (atoms::recorded_size(), report.recorded_size),
+        (atoms::size(), report.size),
+    ];
+
+    Ok((atoms::ok(), report).encode(env))
+}
+
+/// Drops every tombstone of a set configured with the `tombstones` option, returning how many
+/// were dropped.
+fn compact<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let mut set = match write_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let removed = set.compact();
+    resource.account(set.memory());
+
+    Ok((atoms::ok(), removed).encode(env))
+}
+
+fn export_repro<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let bytes = repro::export(&set);
+
+    let mut binary = match OwnedBinary::new(bytes.len()) {
+        Some(binary) => binary,
+        None => return Err(Error::RaiseAtom("enomem")),
+    };
+    binary.as_mut_slice().copy_from_slice(&bytes);
+
+    Ok((atoms::ok(), binary.release(env)).encode(env))
+}
+
+/// Number of items per chunk of a static export, see `export_static`.
+const STATIC_CHUNK_SIZE: usize = 64;
+
+/// Copies the items of the set into a plain term, `{:sorted_set_static, size, chunk_size, chunks}`
+/// where `chunks` is a tuple of tuples holding `chunk_size` items each, the last one possibly
+/// fewer.  Reads are answered by `Discord.SortedSet.Static` from the term alone, lookups by value
+/// rely on the term order so sets with a collation or a comparator can not be exported.
+fn export_static<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    if set.configuration().has_custom_order() {
+        return Ok((atoms::error(), atoms::custom_order()).encode(env));
+    }
+
+    let mut chunks = Vec::with_capacity(set.size() / STATIC_CHUNK_SIZE + 1);
+    let mut chunk = Vec::with_capacity(STATIC_CHUNK_SIZE);
+
+    for item in set.iter() {
+        chunk.push(supported_term::encode(env, item));
+
+        if chunk.len() == STATIC_CHUNK_SIZE {
+            chunks.push(make_tuple(env, &chunk));
+            chunk.clear();
+        }
+    }
+
+    if !chunk.is_empty() {
+        chunks.push(make_tuple(env, &chunk));
+    }
+
+    Ok((
+        atoms::ok(),
+        (
+            atoms::sorted_set_static(),
+            set.size(),
+            STATIC_CHUNK_SIZE,
+            make_tuple(env, &chunks),
+        ),
+    )
+        .encode(env))
+}
+
+fn import_repro<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let binary: Binary = args[0].decode()?;
+
+    match repro::import(binary.as_slice()) {
+        Ok(set) => {
+            let resource = ResourceArc::new(SortedSetResource::new(set, env.pid()));
+            Ok((atoms::ok(), resource).encode(env))
+        }
+        Err(_) => Ok((atoms::error(), atoms::invalid_repro()).encode(env)),
+    }
+}
+
+fn metrics_prometheus<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let labels: Vec<(String, String)> = args[1].decode()?;
+    if !labels
+        .iter()
+        .all(|(name, _)| metrics::is_valid_label_name(name))
+    {
+        return Err(Error::BadArg);
+    }
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let stats = metrics::ResourceStats {
+        subscribers: match resource.subscribers.lock() {
+            Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+            Ok(guard) => guard.len(),
+        },
+        alerts: match resource.alerts.lock() {
+            Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+            Ok(guard) => guard.len(),
+        },
+    };
+
+    Ok((atoms::ok(), metrics::render(&set, &stats, &labels)).encode(env))
+}
+
+/// Registers the process that is sent a message whenever a SortedSet is garbage collected, `nil`
+/// unregisters the current collector.
+fn register_collector<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let pid: Option<Pid> = if args[0] == rustler::types::atom::nil().encode(env) {
+        None
+    } else {
+        Some(args[0].decode()?)
+    };
+
+    match upgrade::globals().collector.lock() {
+        Err(_) => Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(mut guard) => {
+            *guard = pid;
+            Ok(atoms::ok().encode(env))
+        }
+    }
+}
+
+/// Counts of the live sets, their memory and the panics caught.  Built with the `probe-stats`
+/// feature the comparison and probe counters follow, see `probes`.
+fn global_stats<'a>(env: Env<'a>, _args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let globals = upgrade::globals();
+    let mut stats = vec![
+        (
+            atoms::sets(),
+            globals.live_sets.load(AtomicOrdering::Relaxed),
+        ),
+        (
+            atoms::memory(),
+            globals.live_memory.load(AtomicOrdering::Relaxed),
+        ),
+        (
+            atoms::panics(),
+            globals.panics.load(AtomicOrdering::Relaxed),
+        ),
+    ];
+
+    if let Some(probes) = probes::snapshot() {
+        stats.extend(vec![
+            (atoms::operations(), probes.operations),
+            (atoms::comparisons(), probes.comparisons),
+            (atoms::buckets_probed(), probes.buckets_probed),
+            (atoms::elements_shifted(), probes.elements_shifted),
+        ]);
+    }
+
+    Ok(stats.encode(env))
+}
+
+/// Sends a `{:sorted_set_event, set, event, element, index, version}` message to every process
+/// subscribed to the set.  This must be called while the write lock on the set is still held so
+/// that subscribers observe events in the same order as the versions they carry.
+fn notify<'a>(
+    env: Env<'a>,
+    resource: &ResourceArc<SortedSetResource>,
+    event: Atom,
+    element: Term<'a>,
+    index: usize,
+    version: u64,
+    id: Option<u64>,
+) {
+    let subscribers = match resource.subscribers.lock() {
+        Err(_) => return,
+        Ok(guard) => guard,
+    };
+
+    if subscribers.is_empty() {
+        return;
+    }
+
+    let message = match id {
+        None => (
+            atoms::sorted_set_event(),
+            resource.clone(),
+            event,
+            element,
+            index,
+            version,
+        )
+            .encode(env),
+        Some(id) => (
+            atoms::sorted_set_event(),
+            resource.clone(),
+            event,
+            element,
+            index,
+            version,
+            id,
+        )
+            .encode(env),
+    };
+
+    for subscriber in subscribers.iter() {
+        env.send(subscriber, message);
+    }
+}
+
+/// Sends a `{:sorted_set_alert, set, watermark, current}` message for every alert whose watermark
+/// the set has reached, alerts are one-shot and are discarded once sent.  This must be called
+/// while a lock on the set is still held.  Every `ACCOUNTING_INTERVAL` versions the memory
+/// estimate of the set in the global accounting is refreshed as well.
+fn check_alerts<'a>(env: Env<'a>, resource: &ResourceArc<SortedSetResource>, set: &SortedSet) {
+    // Estimating the memory usage has to visit every bucket, only do it when asked for
+    let mut memory = None;
+
+    if set.version() % ACCOUNTING_INTERVAL == 0 {
+        resource.account(*memory.get_or_insert_with(|| set.memory()));
+    }
+
+    let mut alerts = match resource.alerts.lock() {
+        Err(_) => return,
+        Ok(guard) => guard,
+    };
+
+    if alerts.is_empty() {
+        return;
+    }
+
+    alerts.retain(|alert| {
+        let (threshold, current) = match alert.watermark {
+            Watermark::Size(threshold) => (threshold, set.size()),
+            Watermark::Memory(threshold) => {
+                (threshold, *memory.get_or_insert_with(|| set.memory()))
+            }
+        };
+
+        if current < threshold {
+            return true;
+        }
+
+        let message = (
+            atoms::sorted_set_alert(),
+            resource.clone(),
+            alert.watermark,
+            current,
+        )
+            .encode(env);
+
+        env.send(&alert.pid, message);
+
+        false
+    });
+}
+
+/// Runs `job` on a separate thread and sends `{:sorted_set_reply, tag, result}` to the calling
+/// process once it completes.  A dirty scheduler would still block the caller until the NIF
+/// returned, running the job on a thread of its own lets the caller keep serving its mailbox.
+///
+/// The job may not hold on to terms from the calling environment, any terms it needs have to be
+/// passed as `inputs` so they can be copied into an environment that outlives the call.
+fn reply_async<'a, F>(env: Env<'a>, tag: Term<'a>, inputs: &[Term<'a>], job: F)
+where
+    F: for<'b> FnOnce(Env<'b>, &[Term<'b>]) -> Term<'b> + Send + 'static,
+{
+    let pid = env.pid();
+    let mut owned_env = OwnedEnv::new();
+    let tag = owned_env.save(tag);
+    let inputs: Vec<_> = inputs.iter().map(|input| owned_env.save(*input)).collect();
+
+    thread::spawn(move || {
+        owned_env.send_and_clear(&pid, |env| {
+            let inputs: Vec<Term> = inputs.iter().map(|input| input.load(env)).collect();
+
+            let result = match panic::catch_unwind(AssertUnwindSafe(|| job(env, &inputs))) {
+                Ok(result) => result,
+                Err(payload) => panics::internal_error(env, payload),
+            };
+
+            (atoms::sorted_set_reply(), tag.load(env), result).encode(env)
+        });
+    });
+}
+
+fn lock_error<'a>(env: Env<'a>, error: LockError) -> Term<'a> {
+    match error {
+        LockError::Busy => (atoms::error(), atoms::lock_fail()).encode(env),
+        LockError::NotOwner => (atoms::error(), atoms::not_owner()).encode(env),
+    }
+}
+
+/// Read access to the set of a resource.  The queued writes of a set configured with
+/// `coalesce_writes` are applied first if the write lock can be had right away, otherwise they
+/// stay queued and the read sees the set without them.
+fn read_set<'a, 'b>(
+    env: Env<'a>,
+    resource: &'b ResourceArc<SortedSetResource>,
+) -> Result<ReadGuard<'b>, LockError> {
+    try_flush(env, resource);
+
+    resource.set.try_read(env)
+}
+
+/// Write access to the set of a resource, with the queued writes of a set configured with
+/// `coalesce_writes` applied so direct writes land after every write queued before them.
+fn write_set<'a, 'b>(
+    env: Env<'a>,
+    resource: &'b ResourceArc<SortedSetResource>,
+) -> Result<WriteGuard<'b>, LockError> {
+    let mut set = resource.set.try_write(env)?;
+    apply_queued(env, resource, &mut set);
+
+    Ok(set)
+}
+
+/// Applies the queued writes of the set if there are any and the write lock can be had right
+/// away, for calls that read the set without going through `read_set`.
+fn try_flush<'a>(env: Env<'a>, resource: &ResourceArc<SortedSetResource>) {
+    if resource.queue.as_ref().is_none_or(|queue| queue.is_empty()) {
+        return;
+    }
+
+    if let Ok(mut set) = resource.set.try_write(env) {
+        apply_queued(env, resource, &mut set);
+    }
+}
+
+/// Applies the writes queued on the set in the order they were queued, subscribers and alerts
+/// are notified as if the writes had been made one by one.  Writes to a frozen or full set are
+/// dropped, nobody is waiting to be told they failed.  Returns the number of writes taken off the
+/// queue.
+fn apply_queued<'a>(
+    env: Env<'a>,
+    resource: &ResourceArc<SortedSetResource>,
+    set: &mut SortedSet,
+) -> usize {
+    let writes = match resource.queue {
+        Some(ref queue) if !queue.is_empty() => queue.take(),
+        _ => return 0,
+    };
+    let taken = writes.len();
+
+    if set.is_frozen() {
+        return taken;
+    }
+
+    for write in writes {
+        match write {
+            Write::Add(item) => {
+                let element = supported_term::encode(env, &item);
+                let key = if set.has_stable_ids() {
+                    Some(item.clone())
+                } else {
+                    None
+                };
+
+                if let AddResult::Added(idx) = set.add(item) {
+                    let id = key.and_then(|key| set.id_of(&key));
+                    notify(
+                        env,
+                        resource,
+                        atoms::added(),
+                        element,
+                        idx,
+                        set.version(),
+                        id,
+                    );
+                }
+            }
+            Write::Remove(item) => {
+                let id = set.id_of(&item);
+
+                if let RemoveResult::Removed(idx) = set.remove(&item) {
+                    notify(
+                        env,
+                        resource,
+                        atoms::removed(),
+                        supported_term::encode(env, &item),
+                        idx,
+                        set.version(),
+                        id,
+                    );
+                }
+            }
+        }
+    }
+
+    check_alerts(env, resource, set);
+
+    taken
+}
+
+/// `{:error, {:index_out_of_bounds, index, size}}`, the size is the number of items that could
+/// have been read.
+fn index_out_of_bounds<I: Encoder>(env: Env, index: I, size: usize) -> Term {
+    (atoms::error(), (atoms::index_out_of_bounds(), index, size)).encode(env)
+}
+
+/// Encodes the result of building a set, sets configured with `single_owner` belong to `caller`.
+fn encode_build_result<'a>(env: Env<'a>, caller: Pid, result: BuildResult) -> Term<'a> {
+    match result {
+        BuildResult::Built(set) => (
+            atoms::ok(),
+            ResourceArc::new(SortedSetResource::new(*set, caller)),
+        )
+            .encode(env),
+        BuildResult::SetFull => (atoms::error(), atoms::set_full()).encode(env),
+    }
+}
+
+/// Orders a pair of resources by address, operations that need to lock more than one set must
+/// acquire the locks in this order so that concurrent multi-set operations can not deadlock.
+fn lock_order<'r>(
+    a: &'r ResourceArc<SortedSetResource>,
+    b: &'r ResourceArc<SortedSetResource>,
+) -> (
+    &'r ResourceArc<SortedSetResource>,
+    &'r ResourceArc<SortedSetResource>,
+) {
+    if (&**a as *const SortedSetResource) <= (&**b as *const SortedSetResource) {
+        (a, b)
+    } else {
+        (b, a)
+    }
+}
+
+fn persistent_new<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let max_bucket_size: usize = args[1].decode()?;
+    if max_bucket_size < 1 {
+        return Err(Error::BadArg);
+    }
+
+    let mut items = match convert_to_supported_term(&args[0]) {
+        Some(SupportedTerm::List(terms)) => terms,
+        _ => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+    };
+
+    items.sort();
+    items.dedup_by(|a, b| a.cmp(&b) == Ordering::Equal);
+
+    let resource = ResourceArc::new(PersistentSetResource {
+        set: PersistentSet::from_sorted(max_bucket_size, items),
+    });
+
+    Ok((atoms::ok(), resource).encode(env))
+}
+
+fn persistent_snapshot<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let set = match read_set(env, &resource) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    let snapshot = ResourceArc::new(PersistentSetResource {
+        set: PersistentSet::from_sorted(set.configuration().max_bucket_size, snapshot_items(&set)),
+    });
+
+    Ok((atoms::ok(), snapshot).encode(env))
+}
+
+/// PersistentSets always use the term order, the items of a set with a collation or comparator
+/// are converted back and put in that order.
+fn snapshot_items(set: &SortedSet) -> Vec<SupportedTerm> {
+    if !set.configuration().has_custom_order() {
+        return set.to_vec();
+    }
+
+    let mut items: Vec<SupportedTerm> = set.iter().cloned().map(Configuration::strip).collect();
+    items.sort();
+    items
+}
+
+fn persistent_add<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<PersistentSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    match resource.set.add(item) {
+        (AddResult::Added(idx), set) => {
+            let version = ResourceArc::new(PersistentSetResource { set });
+            Ok((atoms::ok(), atoms::added(), idx, version).encode(env))
+        }
+        (AddResult::Duplicate(idx), _) => {
+            Ok((atoms::ok(), atoms::duplicate(), idx, resource).encode(env))
+        }
+        (AddResult::SetFull, _) => Ok((atoms::error(), atoms::set_full()).encode(env)),
+    }
+}
+
+fn persistent_remove<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<PersistentSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    match resource.set.remove(&item) {
+        (RemoveResult::Removed(idx), set) => {
+            let version = ResourceArc::new(PersistentSetResource { set });
+            Ok((atoms::ok(), atoms::removed(), idx, version).encode(env))
+        }
+        (RemoveResult::NotFound, _) => Ok((atoms::error(), atoms::not_found()).encode(env)),
+        (RemoveResult::InternalInconsistency, _) => {
+            Ok((atoms::error(), atoms::internal_inconsistency()).encode(env))
+        }
+    }
+}
+
+fn persistent_size<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<PersistentSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    Ok(resource.set.size().encode(env))
+}
+
+fn persistent_at<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<PersistentSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+    let index: usize = args[1].decode()?;
+
+    match resource.set.at(index) {
+        None => Ok(index_out_of_bounds(env, index, resource.set.size())),
+        Some(value) => Ok((atoms::ok(), supported_term::encode(env, value)).encode(env)),
+    }
+}
+
+fn persistent_slice<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<PersistentSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (start, amount) = match decode_range(args[1], args[2]) {
+        Some(range) => range,
+        None => return Ok((atoms::error(), atoms::invalid_range()).encode(env)),
+    };
+
+    Ok(supported_term::encode_list(
+        env,
+        &resource.set.slice(start, amount),
+    ))
+}
+
+fn persistent_find_index<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<PersistentSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    match resource.set.find_index(&item) {
+        Some(idx) => Ok((atoms::ok(), idx).encode(env)),
+        None => Ok((atoms::error(), atoms::not_found()).encode(env)),
+    }
+}
+
+fn persistent_to_list<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<PersistentSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    Ok(supported_term::encode_list(env, &resource.set.to_vec()))
+}
+
+fn namespaces_new<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let configuration = decode_configuration(args)?;
+
+    // The namespaces share one lock, there is no owner to hand a single namespace to and no queue
+    // of their own to coalesce writes in
+    if configuration.single_owner || configuration.coalesce_writes {
+        return Err(Error::BadArg);
+    }
+
+    let resource = ResourceArc::new(NamespacesResource {
+        namespaces: RwLock::new(Namespaces::new(configuration)),
+    });
+
+    Ok((atoms::ok(), resource).encode(env))
+}
+
+fn namespaces_add<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<NamespacesResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (namespace, item) = match (
+        convert_to_supported_term(&args[1]),
+        convert_to_supported_term(&args[2]),
+    ) {
+        (Some(namespace), Some(item)) => (namespace, item),
+        _ => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+    };
+
+    let mut namespaces = match resource.namespaces.try_write() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    match namespaces.add(namespace, item) {
+        AddResult::Added(idx) => Ok((atoms::ok(), atoms::added(), idx).encode(env)),
+        AddResult::Duplicate(idx) => Ok((atoms::ok(), atoms::duplicate(), idx).encode(env)),
+        AddResult::SetFull => Ok((atoms::error(), atoms::set_full()).encode(env)),
+    }
+}
+
+fn namespaces_remove<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<NamespacesResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (namespace, item) = match (
+        convert_to_supported_term(&args[1]),
+        convert_to_supported_term(&args[2]),
+    ) {
+        (Some(namespace), Some(item)) => (namespace, item),
+        _ => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+    };
+
+    let mut namespaces = match resource.namespaces.try_write() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    match namespaces.remove(&namespace, &item) {
+        RemoveResult::Removed(idx) => Ok((atoms::ok(), atoms::removed(), idx).encode(env)),
+        RemoveResult::NotFound => Ok((atoms::error(), atoms::not_found()).encode(env)),
+        RemoveResult::InternalInconsistency => {
+            Ok((atoms::error(), atoms::internal_inconsistency()).encode(env))
+        }
+    }
+}
+
+fn namespaces_delete<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<NamespacesResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let namespace = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let mut namespaces = match resource.namespaces.try_write() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    let size = namespaces.delete(&namespace).map_or(0, |set| set.size());
+
+    Ok((atoms::ok(), size).encode(env))
+}
+
+fn namespaces_list<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<NamespacesResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let namespaces = match resource.namespaces.try_read() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    let names: Vec<Term<'a>> = namespaces
+        .names()
+        .map(|name| supported_term::encode(env, name))
+        .collect();
+
+    Ok(names.encode(env))
+}
+
+fn namespaces_size<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<NamespacesResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let namespace = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let namespaces = match resource.namespaces.try_read() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    let size = namespaces.get(&namespace).map_or(0, |set| set.size());
+
+    Ok(size.encode(env))
+}
+
+fn namespaces_to_list<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<NamespacesResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let namespace = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let namespaces = match resource.namespaces.try_read() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    match namespaces.get(&namespace) {
+        None => Ok(supported_term::encode_list(env, &[])),
+        Some(set) => Ok(supported_term::encode_list(env, &set.to_vec())),
+    }
+}
+
+fn namespaces_at<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<NamespacesResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let namespace = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+    let index: usize = args[2].decode()?;
+
+    let namespaces = match resource.namespaces.try_read() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    match namespaces.get(&namespace).and_then(|set| set.at(index)) {
+        None => {
+            let size = namespaces.get(&namespace).map_or(0, |set| set.size());
+            Ok(index_out_of_bounds(env, index, size))
+        }
+        Some(value) => Ok((atoms::ok(), supported_term::encode(env, value)).encode(env)),
+    }
+}
+
+fn namespaces_slice<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<NamespacesResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let namespace = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let (start, amount) = match decode_range(args[2], args[3]) {
+        Some(range) => range,
+        None => return Ok((atoms::error(), atoms::invalid_range()).encode(env)),
+    };
+
+    let namespaces = match resource.namespaces.try_read() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    match namespaces.get(&namespace) {
+        None => Ok(supported_term::encode_list(env, &[])),
+        Some(set) => Ok(supported_term::encode_list(env, &set.slice(start, amount))),
+    }
+}
+
+fn namespaces_find_index<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<NamespacesResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (namespace, item) = match (
+        convert_to_supported_term(&args[1]),
+        convert_to_supported_term(&args[2]),
+    ) {
+        (Some(namespace), Some(item)) => (namespace, item),
+        _ => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+    };
+
+    let namespaces = match resource.namespaces.try_read() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    match namespaces
+        .get(&namespace)
+        .and_then(|set| set.find_index(&item))
+    {
+        Some(idx) => Ok((atoms::ok(), idx).encode(env)),
+        None => Ok((atoms::error(), atoms::not_found()).encode(env)),
+    }
+}
+
+fn dual_index_new<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let primary = decode_configuration(&[args[0], args[1], args[2]])?;
+    let secondary = decode_configuration(&[args[0], args[1], args[3]])?;
+
+    // Both orders share one lock, there is no owner to hand a single order to and no queue to
+    // coalesce writes in
+    let configurations = [&primary, &secondary];
+    if configurations
+        .iter()
+        .any(|configuration| configuration.single_owner || configuration.coalesce_writes)
+    {
+        return Err(Error::BadArg);
+    }
+
+    let resource = ResourceArc::new(DualIndexResource {
+        set: RwLock::new(DualIndexSet::new(primary, secondary)),
+    });
+
+    Ok((atoms::ok(), resource).encode(env))
+}
+
+fn dual_index_add<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<DualIndexResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let mut set = match resource.set.try_write() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    match set.add(item) {
+        DualAddResult::Added { primary, secondary } => {
+            Ok((atoms::ok(), atoms::added(), primary, secondary).encode(env))
+        }
+        DualAddResult::Duplicate { primary, secondary } => {
+            Ok((atoms::ok(), atoms::duplicate(), primary, secondary).encode(env))
+        }
+        DualAddResult::SetFull => Ok((atoms::error(), atoms::set_full()).encode(env)),
+    }
+}
+
+fn dual_index_remove<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<DualIndexResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let mut set = match resource.set.try_write() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    match set.remove(&item) {
+        DualRemoveResult::Removed { primary, secondary } => {
+            Ok((atoms::ok(), atoms::removed(), primary, secondary).encode(env))
+        }
+        DualRemoveResult::NotFound => Ok((atoms::error(), atoms::not_found()).encode(env)),
+        DualRemoveResult::InternalInconsistency => {
+            Ok((atoms::error(), atoms::internal_inconsistency()).encode(env))
+        }
+    }
+}
+
+fn dual_index_size<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<DualIndexResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let set = match resource.set.try_read() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    Ok(set.size().encode(env))
+}
+
+fn dual_index_to_list<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<DualIndexResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let index = decode_index(args[1])?;
+
+    let set = match resource.set.try_read() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    Ok(supported_term::encode_list(env, &set.get(index).to_vec()))
+}
+
+fn dual_index_at<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<DualIndexResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let index = decode_index(args[1])?;
+    let position: usize = args[2].decode()?;
+
+    let set = match resource.set.try_read() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    match set.get(index).at(position) {
+        None => Ok(index_out_of_bounds(env, position, set.size())),
+        Some(value) => Ok((atoms::ok(), supported_term::encode(env, value)).encode(env)),
+    }
+}
+
+fn dual_index_slice<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<DualIndexResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let index = decode_index(args[1])?;
+
+    let (start, amount) = match decode_range(args[2], args[3]) {
+        Some(range) => range,
+        None => return Ok((atoms::error(), atoms::invalid_range()).encode(env)),
+    };
+
+    let set = match resource.set.try_read() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    Ok(supported_term::encode_list(
+        env,
+        &set.get(index).slice(start, amount),
+    ))
+}
+
+fn dual_index_find_index<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<DualIndexResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let index = decode_index(args[1])?;
+
+    let item = match convert_to_supported_term(&args[2]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let set = match resource.set.try_read() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    match set.get(index).find_index(&item) {
+        Some(idx) => Ok((atoms::ok(), idx).encode(env)),
+        None => Ok((atoms::error(), atoms::not_found()).encode(env)),
+    }
+}
+
+/// Decodes which order of a DualIndexSet to read, `:primary` or `:secondary`.
+fn decode_index(term: Term) -> NifResult<Index> {
+    let index: Atom = term.decode()?;
+
+    if index == atoms::primary() {
+        Ok(Index::Primary)
+    } else if index == atoms::secondary() {
+        Ok(Index::Secondary)
+    } else {
+        Err(Error::BadArg)
+    }
+}
+
+fn sharded_new<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let configuration = decode_configuration(&args[..3])?;
+
+    // Every shard has a lock of its own, there is no owner to hand them to and no queue to
+    // coalesce writes in
+    if configuration.single_owner || configuration.coalesce_writes {
+        return Err(Error::BadArg);
+    }
+
+    let pivots: Vec<Term> = args[3].decode()?;
+    let pivots: Option<Vec<SupportedTerm>> = pivots.iter().map(convert_to_supported_term).collect();
+    let pivots = match pivots {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(pivots) => pivots,
+    };
+
+    let shards = match Shards::new(configuration, pivots) {
+        Err(ShardError::UnorderedPivots) => {
+            return Ok((atoms::error(), atoms::unordered_pivots()).encode(env))
+        }
+        Ok(shards) => shards,
+    };
+
+    let resource = ResourceArc::new(ShardedSetResource {
+        sets: shards.create().into_iter().map(RwLock::new).collect(),
+        shards,
+    });
+
+    Ok((atoms::ok(), resource).encode(env))
+}
+
+/// Read locks the shards, in order.  The shards are locked together so reads across them see a
+/// single version of every shard.
+fn read_shards(sets: &[RwLock<SortedSet>]) -> Option<Vec<RwLockReadGuard<'_, SortedSet>>> {
+    sets.iter().map(|set| set.try_read().ok()).collect()
+}
+
+fn sharded_add<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<ShardedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let shard = resource.shards.shard_of(&item);
+
+    let mut set = match resource.sets[shard].try_write() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    match set.add(item) {
+        AddResult::Added(_) => Ok((atoms::ok(), atoms::added(), shard).encode(env)),
+        AddResult::Duplicate(_) => Ok((atoms::ok(), atoms::duplicate(), shard).encode(env)),
+        AddResult::SetFull => Ok((atoms::error(), atoms::set_full()).encode(env)),
+    }
+}
+
+fn sharded_remove<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<ShardedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    let shard = resource.shards.shard_of(&item);
+
+    let mut set = match resource.sets[shard].try_write() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    match set.remove(&item) {
+        RemoveResult::Removed(_) => Ok((atoms::ok(), atoms::removed(), shard).encode(env)),
+        RemoveResult::NotFound => Ok((atoms::error(), atoms::not_found()).encode(env)),
+        RemoveResult::InternalInconsistency => {
+            Ok((atoms::error(), atoms::internal_inconsistency()).encode(env))
+        }
+    }
+}
+
+fn sharded_find_index<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<ShardedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let item = match convert_to_supported_term(&args[1]) {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(term) => term,
+    };
+
+    // Only the shards up to the one holding the item count towards its index
+    let shard = resource.shards.shard_of(&item);
+
+    let guards = match read_shards(&resource.sets[..=shard]) {
+        None => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Some(guards) => guards,
+    };
+    let sets: Vec<&SortedSet> = guards.iter().map(|guard| &**guard).collect();
+
+    match sharded::find_index(&sets, &item) {
+        Some(idx) => Ok((atoms::ok(), idx).encode(env)),
+        None => Ok((atoms::error(), atoms::not_found()).encode(env)),
+    }
+}
+
+fn sharded_size<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<ShardedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let guards = match read_shards(&resource.sets) {
+        None => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Some(guards) => guards,
+    };
+    let sets: Vec<&SortedSet> = guards.iter().map(|guard| &**guard).collect();
+
+    Ok(sharded::size(&sets).encode(env))
+}
+
+fn sharded_shard_sizes<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<ShardedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let guards = match read_shards(&resource.sets) {
+        None => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Some(guards) => guards,
+    };
+    let sizes: Vec<usize> = guards.iter().map(|guard| guard.size()).collect();
+
+    Ok(sizes.encode(env))
+}
+
+fn sharded_to_list<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<ShardedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let guards = match read_shards(&resource.sets) {
+        None => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Some(guards) => guards,
+    };
+    let items: Vec<SupportedTerm> = guards.iter().flat_map(|guard| guard.to_vec()).collect();
+
+    Ok(supported_term::encode_list(env, &items))
+}
+
+fn sharded_at<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<ShardedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+    let index: usize = args[1].decode()?;
+
+    let guards = match read_shards(&resource.sets) {
+        None => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Some(guards) => guards,
+    };
+    let sets: Vec<&SortedSet> = guards.iter().map(|guard| &**guard).collect();
+
+    match sharded::at(&sets, index) {
+        None => Ok(index_out_of_bounds(env, index, sharded::size(&sets))),
+        Some(value) => Ok((atoms::ok(), supported_term::encode(env, value)).encode(env)),
+    }
+}
+
+fn sharded_slice<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<ShardedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (start, amount) = match decode_range(args[1], args[2]) {
+        Some(range) => range,
+        None => return Ok((atoms::error(), atoms::invalid_range()).encode(env)),
+    };
+
+    let guards = match read_shards(&resource.sets) {
+        None => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Some(guards) => guards,
+    };
+    let sets: Vec<&SortedSet> = guards.iter().map(|guard| &**guard).collect();
+
+    Ok(supported_term::encode_list(
+        env,
+        &sharded::slice(&sets, start, amount),
+    ))
+}
+
+fn iterator<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (options, snapshot) = match decode_iterator_options(args[1])? {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some(options) => options,
+    };
+
+    let cursor = match (options, snapshot) {
+        (IteratorStart::Cursor(cursor), false) => IteratorCursor::Live(cursor),
+        (options, _) => {
+            let set = match read_set(env, &resource) {
+                Err(error) => return Ok(lock_error(env, error)),
+                Ok(set) => set,
+            };
+            let cursor = match options {
+                IteratorStart::Index(index, direction) => Cursor::at(&set, index, direction),
+                IteratorStart::Cursor(cursor) => cursor,
+            };
+
+            if snapshot {
+                IteratorCursor::Snapshot(SnapshotCursor::new(&set, &cursor))
+            } else {
+                IteratorCursor::Live(cursor)
+            }
+        }
+    };
+
+    let iterator = ResourceArc::new(IteratorResource {
+        set: resource,
+        cursor: Mutex::new(cursor),
+    });
+
+    Ok((atoms::ok(), iterator).encode(env))
+}
+
+/// Where an iterator starts, positioning a cursor at an index needs to read the set.
+enum IteratorStart {
+    Cursor(Cursor),
+    Index(usize, Direction),
+}
+
+/// Decodes the options of the iterator NIFs and whether the `snapshot` option is set, `None` if
+/// the `from` term is not supported.
+fn decode_iterator_options(options: Term) -> NifResult<Option<(IteratorStart, bool)>> {
+    let options: Vec<(Atom, Term)> = options.decode()?;
+
+    let mut from = None;
+    let mut at = None;
+    let mut direction = Direction::Ascending;
+    let mut snapshot = false;
+
+    for (key, value) in options {
+        if key == atoms::from() {
+            from = match convert_to_supported_term(&value) {
+                Some(term) => Some(term),
+                None => return Ok(None),
+            };
+        } else if key == atoms::at() {
+            at = Some(value.decode::<usize>()?);
+        } else if key == atoms::direction() {
+            let value: Atom = value.decode()?;
+
+            direction = if value == atoms::asc() {
+                Direction::Ascending
+            } else if value == atoms::desc() {
+                Direction::Descending
+            } else {
+                return Err(Error::BadArg);
+            };
+        } else if key == atoms::snapshot() {
+            snapshot = value.decode()?;
+        } else {
+            return Err(Error::BadArg);
+        }
+    }
+
+    let start = match (from, at) {
+        (Some(_), Some(_)) => return Err(Error::BadArg),
+        (Some(item), None) => IteratorStart::Cursor(Cursor::from(item, direction)),
+        (None, Some(index)) => IteratorStart::Index(index, direction),
+        (None, None) => IteratorStart::Cursor(Cursor::new(direction)),
+    };
+
+    Ok(Some((start, snapshot)))
+}
+
+fn iterator_next<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<IteratorResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let count: usize = args[1].decode()?;
+
+    let mut cursor = match resource.cursor.try_lock() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    let items = match *cursor {
+        IteratorCursor::Snapshot(ref mut cursor) => cursor.next(count),
+        IteratorCursor::Live(ref mut cursor) => match read_set(env, &resource.set) {
+            Err(error) => return Ok(lock_error(env, error)),
+            Ok(set) => cursor.next(&set, count),
+        },
+    };
+
+    Ok(supported_term::encode_list(env, &items))
+}
+
+/// Estimates the number of distinct items across the sets from their cardinality sketches.  The
+/// sets are locked one at a time, so the estimate is not a snapshot of all sets at once.
+fn estimate_union_size<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let terms: Vec<Term<'a>> = args[0].decode()?;
+    let mut union = Sketch::new();
+
+    for term in terms {
+        let resource: ResourceArc<SortedSetResource> = match term.decode() {
+            Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+            Ok(r) => r,
+        };
+
+        let set = match read_set(env, &resource) {
+            Err(error) => return Ok(lock_error(env, error)),
+            Ok(guard) => guard,
+        };
+
+        match set.sketch() {
+            None => return Ok((atoms::error(), atoms::sketch_disabled()).encode(env)),
+            Some(sketch) => union.merge(&sketch),
+        }
+    }
+
+    Ok((atoms::ok(), union.estimate()).encode(env))
+}
+
+fn merged_view<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let terms: Vec<Term<'a>> = args[0].decode()?;
+    let mut sets = Vec::with_capacity(terms.len());
+
+    for term in terms {
+        match term.decode() {
+            Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+            Ok(resource) => sets.push(resource),
+        }
+    }
+
+    let view = ResourceArc::new(MergedViewResource { sets });
+
+    Ok((atoms::ok(), view).encode(env))
+}
+
+/// Builds a new set out of the distinct items of every set in `args[0]`, a non-empty list.  The
+/// new set has the configuration of the first set of the list.
+fn merge_many<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let terms: Vec<Term<'a>> = args[0].decode()?;
+    let mut resources: Vec<ResourceArc<SortedSetResource>> = Vec::with_capacity(terms.len());
+
+    for term in terms {
+        let resource: ResourceArc<SortedSetResource> = match term.decode() {
+            Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+            Ok(r) => r,
+        };
+
+        // Taking a second read lock on the same set from one thread can deadlock
+        if !resources.iter().any(|other| ptr::eq(&**other, &*resource)) {
+            resources.push(resource);
+        }
+    }
+
+    if resources.is_empty() {
+        return Err(Error::BadArg);
+    }
+
+    let mut guards = Vec::with_capacity(resources.len());
+    for resource in resources.iter() {
+        match read_set(env, resource) {
+            Err(error) => return Ok(lock_error(env, error)),
+            Ok(guard) => guards.push(guard),
+        }
+    }
+    let sets: Vec<&SortedSet> = guards.iter().map(|guard| &**guard).collect();
+
+    if let Err(mismatch) = merged::check_order(&sets) {
+        return Ok(order_mismatch(env, mismatch));
+    }
+
+    let configuration = sets[0].configuration().clone();
+
+    Ok(encode_build_result(
+        env,
+        env.pid(),
+        merged::merge_many(configuration, &sets),
+    ))
+}
+
+/// Read locks every set of a view, the sets are only ever read locked together so the order the
+/// locks are taken in does not matter.
+fn read_merged<'r>(
+    env: Env,
+    view: &'r MergedViewResource,
+) -> Result<Vec<ReadGuard<'r>>, LockError> {
+    view.sets.iter().map(|set| read_set(env, set)).collect()
+}
+
+fn order_mismatch<'a>(env: Env<'a>, mismatch: OrderMismatch) -> Term<'a> {
+    match mismatch {
+        OrderMismatch::Collation => (atoms::error(), atoms::collation_mismatch()).encode(env),
+        OrderMismatch::Comparator => (atoms::error(), atoms::comparator_mismatch()).encode(env),
+    }
+}
+
+fn merged_view_at<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let view: ResourceArc<MergedViewResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+    let index: usize = args[1].decode()?;
+
+    let guards = match read_merged(env, &view) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guards) => guards,
+    };
+    let sets: Vec<&SortedSet> = guards.iter().map(|guard| &**guard).collect();
+
+    if let Err(mismatch) = merged::check_order(&sets) {
+        return Ok(order_mismatch(env, mismatch));
+    }
+
+    match merged::at(&sets, index) {
+        None => Ok(index_out_of_bounds(env, index, merged::size(&sets))),
+        Some(value) => Ok((atoms::ok(), supported_term::encode(env, &value)).encode(env)),
+    }
+}
+
+fn merged_view_slice<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let view: ResourceArc<MergedViewResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (start, amount) = match decode_range(args[1], args[2]) {
+        Some(range) => range,
+        None => return Ok((atoms::error(), atoms::invalid_range()).encode(env)),
+    };
+
+    let guards = match read_merged(env, &view) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guards) => guards,
+    };
+    let sets: Vec<&SortedSet> = guards.iter().map(|guard| &**guard).collect();
+
+    if let Err(mismatch) = merged::check_order(&sets) {
+        return Ok(order_mismatch(env, mismatch));
+    }
+
+    Ok(supported_term::encode_list(
+        env,
+        &merged::slice(&sets, start, amount),
+    ))
+}
+
+fn merged_view_iterator<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let view: ResourceArc<MergedViewResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let options = match decode_iterator_options(args[1])? {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some((_, true)) => return Err(Error::BadArg),
+        Some((start, false)) => start,
+    };
+
+    let cursor = match options {
+        IteratorStart::Index(index, direction) => {
+            let guards = match read_merged(env, &view) {
+                Err(error) => return Ok(lock_error(env, error)),
+                Ok(guards) => guards,
+            };
+            let sets: Vec<&SortedSet> = guards.iter().map(|guard| &**guard).collect();
+
+            if let Err(mismatch) = merged::check_order(&sets) {
+                return Ok(order_mismatch(env, mismatch));
+            }
+
+            Cursor::at_merged(&sets, index, direction)
+        }
+        IteratorStart::Cursor(cursor) => cursor,
+    };
+
+    let iterator = ResourceArc::new(MergedIteratorResource {
+        view,
+        cursor: Mutex::new(cursor),
+    });
+
+    Ok((atoms::ok(), iterator).encode(env))
+}
+
+fn merged_view_iterator_next<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<MergedIteratorResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let count: usize = args[1].decode()?;
+
+    let mut cursor = match resource.cursor.try_lock() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    let guards = match read_merged(env, &resource.view) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guards) => guards,
+    };
+    let sets: Vec<&SortedSet> = guards.iter().map(|guard| &**guard).collect();
+
+    if let Err(mismatch) = merged::check_order(&sets) {
+        return Ok(order_mismatch(env, mismatch));
+    }
+
+    Ok(supported_term::encode_list(
+        env,
+        &cursor.next_merged(&sets, count),
+    ))
+}
+
+fn range_view<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let set: ResourceArc<SortedSetResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (min, max) = match (
+        convert_to_supported_term(&args[1]),
+        convert_to_supported_term(&args[2]),
+    ) {
+        (Some(min), Some(max)) => (min, max),
+        _ => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+    };
+
+    let view = ResourceArc::new(RangeViewResource {
+        set,
+        view: RangeView::new(min, max),
+    });
+
+    Ok((atoms::ok(), view).encode(env))
+}
+
+fn range_view_size<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<RangeViewResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let set = match read_set(env, &resource.set) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    Ok(resource.view.size(&set).encode(env))
+}
+
+fn range_view_at<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<RangeViewResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+    let index: usize = args[1].decode()?;
+
+    let set = match read_set(env, &resource.set) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    match resource.view.at(&set, index) {
+        None => Ok(index_out_of_bounds(env, index, resource.view.size(&set))),
+        Some(value) => Ok((atoms::ok(), supported_term::encode(env, value)).encode(env)),
+    }
+}
+
+fn range_view_slice<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<RangeViewResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let (start, amount) = match decode_range(args[1], args[2]) {
+        Some(range) => range,
+        None => return Ok((atoms::error(), atoms::invalid_range()).encode(env)),
+    };
+
+    let set = match read_set(env, &resource.set) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    Ok(supported_term::encode_list(
+        env,
+        &resource.view.slice(&set, start, amount),
+    ))
+}
+
+fn range_view_iterator<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<RangeViewResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let options = match decode_iterator_options(args[1])? {
+        None => return Ok((atoms::error(), atoms::unsupported_type()).encode(env)),
+        Some((_, true)) => return Err(Error::BadArg),
+        Some((start, false)) => start,
+    };
+
+    let cursor = match options {
+        IteratorStart::Index(index, direction) => match read_set(env, &resource.set) {
+            Err(error) => return Ok(lock_error(env, error)),
+            Ok(set) => Cursor::at_view(&set, &resource.view, index, direction),
+        },
+        IteratorStart::Cursor(cursor) => cursor,
+    };
+
+    let iterator = ResourceArc::new(RangeIteratorResource {
+        view: resource,
+        cursor: Mutex::new(cursor),
+    });
+
+    Ok((atoms::ok(), iterator).encode(env))
+}
+
+fn range_view_iterator_next<'a>(env: Env<'a>, args: &[Term<'a>]) -> NifResult<Term<'a>> {
+    let resource: ResourceArc<RangeIteratorResource> = match args[0].decode() {
+        Err(_) => return Ok((atoms::error(), atoms::bad_reference()).encode(env)),
+        Ok(r) => r,
+    };
+
+    let count: usize = args[1].decode()?;
+
+    let mut cursor = match resource.cursor.try_lock() {
+        Err(_) => return Ok((atoms::error(), atoms::lock_fail()).encode(env)),
+        Ok(guard) => guard,
+    };
+
+    let set = match read_set(env, &resource.view.set) {
+        Err(error) => return Ok(lock_error(env, error)),
+        Ok(guard) => guard,
+    };
+
+    Ok(supported_term::encode_list(
+        env,
+        &cursor.next_view(&set, &resource.view.view, count),
+    ))
+}
+
+/// Builds a Configuration from the `capacity`, `bucket_size` and `options` arguments shared by
+/// the constructor NIFs.
+fn decode_configuration<'a>(args: &[Term<'a>]) -> NifResult<Configuration> {
+    let initial_item_capacity: usize = args[0].decode()?;
+    let max_bucket_size: usize = args[1].decode()?;
+    let options: Vec<(Atom, Term)> = args[2].decode()?;
+
+    let mut builder = Configuration::builder()
+        .max_bucket_size(max_bucket_size)
+        .initial_capacity(initial_item_capacity);
+
+    // The comparator keys and the tuple order end up in the same comparator
+    let mut comparator_keys = Vec::new();
+    let mut tuple_order = TupleOrder::Erlang;
+
+    for (key, value) in options {
+        if key == atoms::max_elements() {
+            builder = builder.max_elements(value.decode()?);
+        } else if key == atoms::initial_bucket_capacity() {
+            builder = builder.initial_bucket_capacity(value.decode()?);
+        } else if key == atoms::stable_ids() {
+            builder = builder.stable_ids(value.decode()?);
+        } else if key == atoms::single_owner() {
+            builder = builder.single_owner(value.decode()?);
+        } else if key == atoms::cardinality_sketch() {
+            builder = builder.cardinality_sketch(value.decode()?);
+        } else if key == atoms::coalesce_writes() {
+            builder = builder.coalesce_writes(value.decode()?);
+        } else if key == atoms::tombstones() {
+            builder = builder.tombstone_ratio(value.decode()?);
+        } else if key == atoms::interpolation_search() {
+            builder = builder.interpolation_search(value.decode()?);
+        } else if key == atoms::charlists() {
+            builder = builder.charlists(value.decode()?);
+        } else if key == atoms::collation() {
+            let collation = if value.is_atom() {
+                let collation: Atom = value.decode()?;
+
+                if collation != atoms::natural() {
+                    return Err(Error::BadArg);
+                }
+                Collation::natural()
+            } else {
+                let locale: String = value.decode()?;
+
+                match Collation::new(&locale) {
+                    Some(collation) => collation,
+                    None => return Err(Error::BadArg),
+                }
+            };
+
+            builder = builder.collation(collation);
+        } else if key == atoms::comparator() {
+            let keys: Vec<Term> = value.decode()?;
+            let mut compiled = Vec::with_capacity(keys.len());
+
+            for key in keys {
+                compiled.push(decode_comparator_key(key)?);
+            }
+
+            if compiled.is_empty() {
+                return Err(Error::BadArg);
+            }
+
+            comparator_keys = compiled;
+        } else if key == atoms::tuple_order() {
+            let order: Atom = value.decode()?;
+
+            tuple_order = if order == atoms::erlang() {
+                TupleOrder::Erlang
+            } else if order == atoms::lexicographic() {
+                TupleOrder::Lexicographic
+            } else {
+                return Err(Error::BadArg);
+            };
+        } else if key == atoms::backend() {
+            builder = builder.backend(decode_backend(value)?);
+        } else {
+            return Err(Error::BadArg);
+        }
+    }
+
+    if let Some(comparator) = Comparator::compile(comparator_keys, tuple_order) {
+        builder = builder.comparator(comparator);
+    }
+
+    builder.build().map_err(|_| Error::BadArg)
+}
+
+/// Decodes the `:backend` option, `:buckets`, `:btree` or `{:bitset, universe}`.
+fn decode_backend(term: Term) -> NifResult<BackendKind> {
+    if term.is_atom() {
+        let backend: Atom = term.decode()?;
+
+        if backend == atoms::buckets() {
+            Ok(BackendKind::Buckets)
+        } else if backend == atoms::btree() {
+            Ok(BackendKind::BTree)
+        } else {
+            Err(Error::BadArg)
+        }
+    } else {
+        let (backend, universe): (Atom, u32) = term.decode()?;
+
+        if backend != atoms::bitset() {
+            return Err(Error::BadArg);
+        }
+
+        Ok(BackendKind::Bitset { universe })
+    }
+}
+
+/// Decodes one key of the `:comparator` option, either `{index, direction}` or
+/// `{index | path, direction, transforms}` where the transforms are `:fold_case` and
+/// `{:cast, :integer | :string}`.
+fn decode_comparator_key(term: Term) -> NifResult<Key> {
+    let elements = get_tuple(term)?;
+
+    let (path, transforms) = match elements.len() {
+        2 => (elements[0], Vec::new()),
+        3 => (elements[0], elements[2].decode::<Vec<Term>>()?),
+        _ => return Err(Error::BadArg),
+    };
+
+    let path: Vec<usize> = if path.is_list() {
+        path.decode()?
+    } else {
+        vec![path.decode()?]
+    };
+
+    if path.is_empty() {
+        return Err(Error::BadArg);
+    }
+
+    let direction: Atom = elements[1].decode()?;
+    let direction = if direction == atoms::asc() {
+        Direction::Ascending
+    } else if direction == atoms::desc() {
+        Direction::Descending
+    } else {
+        return Err(Error::BadArg);
+    };
+
+    let mut key = Key {
+        path,
+        direction,
+        cast: None,
+        fold_case: false,
+    };
+
+    for transform in transforms {
+        if transform.is_atom() {
+            let transform: Atom = transform.decode()?;
+
+            if transform != atoms::fold_case() {
+                return Err(Error::BadArg);
+            }
+            key.fold_case = true;
+        } else {
+            let (name, to): (Atom, Atom) = transform.decode()?;
+
+            if name != atoms::cast() {
+                return Err(Error::BadArg);
+            }
+
+            key.cast = Some(if to == atoms::integer() {
+                Cast::Integer
+            } else if to == atoms::string() {
+                Cast::String
+            } else {
+                return Err(Error::BadArg);
+            });
+        }
+    }
+
+    Ok(key)
+}
+
+/// Decodes the start and amount of a slice, returning None for negative values and for ranges
+/// whose end does not fit into a usize.
+fn decode_range<'a>(start: Term<'a>, amount: Term<'a>) -> Option<(usize, usize)> {
+    let start: usize = start.decode().ok()?;
+    let amount: usize = amount.decode().ok()?;
+
+    start.checked_add(amount)?;
+
+    Some((start, amount))
+}
+
+fn convert_to_supported_term(term: &Term) -> Option<SupportedTerm> {
+    convert_sanitized(term, &Unsupported::Reject, &mut 0)
+}
+
+/// What happens to an unsupported term nested in a tuple or a list.  An unsupported term that is
+/// not nested in anything is always rejected.
+enum Unsupported {
+    /// The whole term is rejected, the default.
+    Reject,
+
+    /// The unsupported term is left out of its tuple or list.
+    Skip,
+
+    /// The unsupported term is replaced by an atom with the given name.
+    Replace(String),
+}
+
+/// Converts a term like `convert_to_supported_term`, applying `policy` to unsupported nested
+/// terms and counting them in `sanitized`.
+fn convert_sanitized(
+    term: &Term,
+    policy: &Unsupported,
+    sanitized: &mut usize,
+) -> Option<SupportedTerm> {
+    if term.is_number() {
+        match term.decode() {
+            Ok(i) => Some(SupportedTerm::Integer(i)),
+            Err(_) => None,
+        }
+    } else if term.is_atom() {
         match term.atom_to_string() {
-            Ok(a) => Some(SupportedTerm::Atom(a)),
+            Ok(a) => Some(SupportedTerm::atom(a)),
             Err(_) => None,
         }
     } else if term.is_tuple() {
         match get_tuple(*term) {
-            Ok(t) => {
-                let initial_length = t.len();
-                let inner_terms: Vec<SupportedTerm> = t
-                    .into_iter()
-                    .filter_map(|i: Term| convert_to_supported_term(&i))
-                    .collect();
-                if initial_length == inner_terms.len() {
-                    Some(SupportedTerm::Tuple(inner_terms))
-                } else {
-                    None
-                }
-            }
+            Ok(t) => convert_elements(t, policy, sanitized).map(SupportedTerm::Tuple),
             Err(_) => None,
         }
     } else if term.is_list() {
         match term.decode::<Vec<Term>>() {
-            Ok(l) => {
-                let initial_length = l.len();
-                let inner_terms: Vec<SupportedTerm> = l
-                    .into_iter()
-                    .filter_map(|i: Term| convert_to_supported_term(&i))
-                    .collect();
-                if initial_length == inner_terms.len() {
-                    Some(SupportedTerm::List(inner_terms))
-                } else {
-                    None
-                }
-            }
+            Ok(l) => convert_elements(l, policy, sanitized).map(SupportedTerm::List),
             Err(_) => None,
         }
     } else if term.is_binary() {
@@ -349,3 +4855,73 @@ fn convert_to_supported_term(term: &Term) -> Option<SupportedTerm> {
         None
     }
 }
+
+/// Flattens iodata, a binary or a possibly improper list nesting bytes and binaries, into the
+/// string it spells out.  None if the term is not iodata or the bytes are not valid UTF-8, the
+/// term is then converted like any other term.
+fn flatten_iodata(term: Term) -> Option<String> {
+    if !term.is_binary() && !term.is_list() {
+        return None;
+    }
+
+    let mut bytes = Vec::new();
+    push_iodata(term, &mut bytes)?;
+
+    String::from_utf8(bytes).ok()
+}
+
+fn push_iodata(term: Term, bytes: &mut Vec<u8>) -> Option<()> {
+    let mut rest = term;
+
+    loop {
+        if rest.is_binary() {
+            let binary: Binary = rest.decode().ok()?;
+            bytes.extend_from_slice(binary.as_slice());
+
+            return Some(());
+        }
+
+        if !rest.is_list() {
+            return None;
+        }
+
+        // Only fails on the empty list that ends a proper list
+        let (head, tail) = match rest.list_get_cell() {
+            Err(_) => return Some(()),
+            Ok(cell) => cell,
+        };
+
+        if head.is_number() {
+            let byte: u32 = head.decode().ok()?;
+            bytes.push(u8::try_from(byte).ok()?);
+        } else {
+            push_iodata(head, bytes)?;
+        }
+
+        rest = tail;
+    }
+}
+
+fn convert_elements(
+    elements: Vec<Term>,
+    policy: &Unsupported,
+    sanitized: &mut usize,
+) -> Option<Vec<SupportedTerm>> {
+    let mut converted = Vec::with_capacity(elements.len());
+
+    for element in elements {
+        match convert_sanitized(&element, policy, sanitized) {
+            Some(term) => converted.push(term),
+            None => match *policy {
+                Unsupported::Reject => return None,
+                Unsupported::Skip => *sanitized += 1,
+                Unsupported::Replace(ref name) => {
+                    *sanitized += 1;
+                    converted.push(SupportedTerm::atom(name.clone()));
+                }
+            },
+        }
+    }
+
+    Some(converted)
+}
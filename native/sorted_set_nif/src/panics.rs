@@ -0,0 +1,62 @@
+//! Turns panics into error returns.
+//!
+//! rustler raises a `nif_panic` exception when a NIF panics, which takes the calling process down
+//! with it.  Every NIF is called through `handle_nif_call` instead, a panic is counted in the
+//! global stats and returned as `{:error, {:internal_error, reason}}` like any other error.
+//!
+//! A panic while a set is locked for writing poisons the lock, later operations on that set fail
+//! with `{:error, :lock_fail}` rather than seeing a half finished mutation.
+
+use atoms;
+use rustler::codegen_runtime::{c_int, NIF_ENV, NIF_TERM};
+use rustler::{Encoder, Env, NifResult, Term};
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::slice;
+use std::sync::atomic::Ordering;
+use upgrade;
+
+/// Counts the panic and encodes it as `{:error, {:internal_error, reason}}`.
+pub fn internal_error<'a>(env: Env<'a>, payload: Box<dyn Any + Send>) -> Term<'a> {
+    upgrade::globals().panics.fetch_add(1, Ordering::Relaxed);
+
+    let reason = match payload.downcast::<String>() {
+        Ok(reason) => *reason,
+        Err(payload) => match payload.downcast::<&'static str>() {
+            Ok(reason) => String::from(*reason),
+            Err(_) => String::from("unknown panic"),
+        },
+    };
+
+    (atoms::error(), (atoms::internal_error(), reason)).encode(env)
+}
+
+/// Same as rustler's `handle_nif_call` but panics become error returns, see `internal_error`.
+///
+/// # Unsafe
+///
+/// The arguments have to be the ones the BEAM passed to the NIF.
+pub unsafe fn handle_nif_call(
+    function: for<'a> fn(Env<'a>, &[Term<'a>]) -> NifResult<Term<'a>>,
+    r_env: NIF_ENV,
+    argc: c_int,
+    argv: *const NIF_TERM,
+) -> NIF_TERM {
+    let env_lifetime = ();
+    let env = Env::new(&env_lifetime, r_env);
+
+    let terms = slice::from_raw_parts(argv, argc as usize)
+        .iter()
+        .map(|term| Term::new(env, *term))
+        .collect::<Vec<Term>>();
+
+    let result = panic::catch_unwind(AssertUnwindSafe(|| match function(env, &terms) {
+        Ok(term) => term,
+        Err(error) => error.encode(env),
+    }));
+
+    match result {
+        Ok(term) => term.as_c_arg(),
+        Err(payload) => internal_error(env, payload).as_c_arg(),
+    }
+}
@@ -0,0 +1,150 @@
+//! Guards the SortedSet held by a resource.
+//!
+//! Shared sets are behind a read-write lock so any process can use them.  Sets created with the
+//! `single_owner` option belong to the process that created them, every call checks the calling
+//! pid instead of taking a lock.  A process only ever runs one NIF at a time, so as long as the
+//! set is only touched by its owner there can be no concurrent access.  The RefCell still catches
+//! a single call borrowing the same set twice, like `concat` of a set with itself.
+
+use rustler::types::pid::Pid;
+use rustler::{Encoder, Env};
+use sorted_set_core::SortedSet;
+use std::cell::{Ref, RefCell, RefMut};
+use std::ops::{Deref, DerefMut};
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+pub enum LockError {
+    /// The set is locked by a conflicting operation.
+    Busy,
+
+    /// The set has a single owner and the caller is not it.
+    NotOwner,
+}
+
+pub enum SetLock {
+    Shared(RwLock<SortedSet>),
+    Owned { owner: Pid, set: RefCell<SortedSet> },
+}
+
+// The owned set is only handed out to its owner, which can not run two NIFs at the same time.
+unsafe impl Sync for SetLock {}
+
+pub enum ReadGuard<'a> {
+    Shared(RwLockReadGuard<'a, SortedSet>),
+    Owned(Ref<'a, SortedSet>),
+}
+
+pub enum WriteGuard<'a> {
+    Shared(RwLockWriteGuard<'a, SortedSet>),
+    Owned(RefMut<'a, SortedSet>),
+}
+
+impl SetLock {
+    /// Wraps the set, sets configured with `single_owner` belong to `caller`.
+    pub fn new(set: SortedSet, caller: Pid) -> SetLock {
+        if set.configuration().single_owner {
+            SetLock::Owned {
+                owner: caller,
+                set: RefCell::new(set),
+            }
+        } else {
+            SetLock::Shared(RwLock::new(set))
+        }
+    }
+
+    pub fn try_read<'a>(&'a self, env: Env) -> Result<ReadGuard<'a>, LockError> {
+        match *self {
+            SetLock::Shared(ref lock) => match lock.try_read() {
+                Err(_) => Err(LockError::Busy),
+                Ok(guard) => Ok(ReadGuard::Shared(guard)),
+            },
+            SetLock::Owned { ref owner, ref set } => {
+                if !is_owner(env, owner) {
+                    return Err(LockError::NotOwner);
+                }
+
+                match set.try_borrow() {
+                    Err(_) => Err(LockError::Busy),
+                    Ok(set) => Ok(ReadGuard::Owned(set)),
+                }
+            }
+        }
+    }
+
+    pub fn try_write<'a>(&'a self, env: Env) -> Result<WriteGuard<'a>, LockError> {
+        match *self {
+            SetLock::Shared(ref lock) => match lock.try_write() {
+                Err(_) => Err(LockError::Busy),
+                Ok(guard) => Ok(WriteGuard::Shared(guard)),
+            },
+            SetLock::Owned { ref owner, ref set } => {
+                if !is_owner(env, owner) {
+                    return Err(LockError::NotOwner);
+                }
+
+                match set.try_borrow_mut() {
+                    Err(_) => Err(LockError::Busy),
+                    Ok(set) => Ok(WriteGuard::Owned(set)),
+                }
+            }
+        }
+    }
+
+    /// Blocks until the set can be read, for jobs running outside of any process.  Those can never
+    /// be the owner of a set, so owned sets are refused.
+    pub fn read(&self) -> Result<ReadGuard<'_>, LockError> {
+        match *self {
+            SetLock::Shared(ref lock) => match lock.read() {
+                Err(_) => Err(LockError::Busy),
+                Ok(guard) => Ok(ReadGuard::Shared(guard)),
+            },
+            SetLock::Owned { .. } => Err(LockError::NotOwner),
+        }
+    }
+
+    /// Exclusive access to the set, a poisoned lock is ignored as nothing else can hold it.
+    pub fn get_mut(&mut self) -> &mut SortedSet {
+        match *self {
+            SetLock::Shared(ref mut lock) => match lock.get_mut() {
+                Ok(set) => set,
+                Err(poisoned) => poisoned.into_inner(),
+            },
+            SetLock::Owned { ref mut set, .. } => set.get_mut(),
+        }
+    }
+}
+
+fn is_owner(env: Env, owner: &Pid) -> bool {
+    owner.encode(env) == env.pid().encode(env)
+}
+
+impl<'a> Deref for ReadGuard<'a> {
+    type Target = SortedSet;
+
+    fn deref(&self) -> &SortedSet {
+        match *self {
+            ReadGuard::Shared(ref guard) => guard,
+            ReadGuard::Owned(ref set) => set,
+        }
+    }
+}
+
+impl<'a> Deref for WriteGuard<'a> {
+    type Target = SortedSet;
+
+    fn deref(&self) -> &SortedSet {
+        match *self {
+            WriteGuard::Shared(ref guard) => guard,
+            WriteGuard::Owned(ref set) => set,
+        }
+    }
+}
+
+impl<'a> DerefMut for WriteGuard<'a> {
+    fn deref_mut(&mut self) -> &mut SortedSet {
+        match *self {
+            WriteGuard::Shared(ref mut guard) => guard,
+            WriteGuard::Owned(ref mut set) => set,
+        }
+    }
+}
@@ -4,183 +4,32 @@ use rustler::types::tuple::make_tuple;
 use rustler::Encoder;
 use rustler::Env;
 use rustler::Term;
-use std::cmp::min;
-use std::cmp::Ordering;
-
-/// SupportedTerm is an enum that covers all the Erlang / Elixir term types that can be stored in
-/// a SortedSet.
-///
-/// There are a number of types that are not supported because of their complexity and the
-/// difficulty of safely implementing their storage.
-///
-/// Types that are not supported
-///   - Reference
-///   - Function
-///   - Port
-///   - Pid
-///
-/// Types that are supported but not explicitly listed
-///   - Boolean (Note that booleans in Erlang / Elixir are just atoms)
-#[derive(Eq, Debug, Clone)]
-pub enum SupportedTerm {
-    Integer(i64),
-    Atom(String),
-    Tuple(Vec<SupportedTerm>),
-    List(Vec<SupportedTerm>),
-    Bitstring(String),
-}
-
-impl Ord for SupportedTerm {
-    fn cmp(&self, other: &SupportedTerm) -> Ordering {
-        match self {
-            SupportedTerm::Integer(self_inner) => match other {
-                SupportedTerm::Integer(inner) => self_inner.cmp(inner),
-                _ => Ordering::Less,
-            },
-            SupportedTerm::Atom(self_inner) => match other {
-                SupportedTerm::Integer(_) => Ordering::Greater,
-                SupportedTerm::Atom(inner) => self_inner.cmp(inner),
-                _ => Ordering::Less,
-            },
-            SupportedTerm::Tuple(self_inner) => match other {
-                SupportedTerm::Integer(_) => Ordering::Greater,
-                SupportedTerm::Atom(_) => Ordering::Greater,
-                SupportedTerm::Tuple(inner) => {
-                    let self_length = self_inner.len();
-                    let other_length = inner.len();
-
-                    if self_length == other_length {
-                        let mut idx = 0;
-                        while idx < self_length {
-                            match self_inner[idx].cmp(&inner[idx]) {
-                                Ordering::Less => return Ordering::Less,
-                                Ordering::Greater => return Ordering::Greater,
-                                _ => idx += 1,
-                            }
-                        }
-                        Ordering::Equal
-                    } else {
-                        self_length.cmp(&other_length)
-                    }
-                }
-                _ => Ordering::Less,
-            },
-            SupportedTerm::List(self_inner) => match other {
-                SupportedTerm::Integer(_) => Ordering::Greater,
-                SupportedTerm::Atom(_) => Ordering::Greater,
-                SupportedTerm::Tuple(_) => Ordering::Greater,
-                SupportedTerm::List(inner) => {
-                    let self_length = self_inner.len();
-                    let other_length = inner.len();
-
-                    let max_common = min(self_length, other_length);
-                    let mut idx = 0;
-
-                    while idx < max_common {
-                        match self_inner[idx].cmp(&inner[idx]) {
-                            Ordering::Greater => return Ordering::Greater,
-                            Ordering::Less => return Ordering::Less,
-                            _ => idx += 1,
-                        }
-                    }
-
-                    if self_length == other_length {
-                        Ordering::Equal
-                    } else if self_length < other_length {
-                        Ordering::Less
-                    } else {
-                        Ordering::Greater
-                    }
-                }
-                _ => Ordering::Less,
-            },
-            SupportedTerm::Bitstring(self_inner) => match other {
-                SupportedTerm::Bitstring(inner) => self_inner.cmp(inner),
-                _ => Ordering::Greater,
-            },
-        }
-    }
-}
-
-impl PartialOrd for SupportedTerm {
-    fn partial_cmp(&self, other: &SupportedTerm) -> Option<Ordering> {
-        Some(self.cmp(other))
+use sorted_set_core::supported_term::SupportedTerm;
+
+/// Converts a SupportedTerm back into the Erlang term it was created from.
+pub fn encode<'a>(env: Env<'a>, term: &SupportedTerm) -> Term<'a> {
+    match term {
+        SupportedTerm::Integer(inner) => inner.encode(env),
+        SupportedTerm::Atom(inner) => match Atom::from_str(env, inner) {
+            Ok(atom) => atom.encode(env),
+            Err(_) => atoms::error().encode(env),
+        },
+        SupportedTerm::NegInfinity => atoms::neg_infinity().encode(env),
+        SupportedTerm::Infinity => atoms::infinity().encode(env),
+        SupportedTerm::Tuple(inner) => encode_tuple(env, inner),
+        SupportedTerm::List(inner) => encode_list(env, inner),
+        SupportedTerm::Bitstring(inner) => inner.encode(env),
+        SupportedTerm::Collated(inner) => inner.value.encode(env),
+        SupportedTerm::Keyed(inner) => encode_tuple(env, &inner.items),
     }
 }
 
-impl PartialEq for SupportedTerm {
-    fn eq(&self, other: &SupportedTerm) -> bool {
-        match self {
-            SupportedTerm::Integer(self_inner) => match other {
-                SupportedTerm::Integer(inner) => self_inner == inner,
-                _ => false,
-            },
-            SupportedTerm::Atom(self_inner) => match other {
-                SupportedTerm::Atom(inner) => self_inner == inner,
-                _ => false,
-            },
-            SupportedTerm::Tuple(self_inner) => match other {
-                SupportedTerm::Tuple(inner) => {
-                    let length = self_inner.len();
-
-                    if length != inner.len() {
-                        return false;
-                    }
-
-                    let mut idx = 0;
-
-                    while idx < length {
-                        if self_inner[idx] != inner[idx] {
-                            return false;
-                        }
-                    }
-
-                    true
-                }
-                _ => false,
-            },
-            SupportedTerm::List(self_inner) => match other {
-                SupportedTerm::List(inner) => {
-                    let length = self_inner.len();
-
-                    if length != inner.len() {
-                        return false;
-                    }
-
-                    let mut idx = 0;
-
-                    while idx < length {
-                        if self_inner[idx] != inner[idx] {
-                            return false;
-                        }
-                    }
-
-                    true
-                }
-                _ => false,
-            },
-            SupportedTerm::Bitstring(self_inner) => match other {
-                SupportedTerm::Bitstring(inner) => self_inner == inner,
-                _ => false,
-            },
-        }
-    }
+pub fn encode_list<'a>(env: Env<'a>, terms: &[SupportedTerm]) -> Term<'a> {
+    let terms: Vec<Term<'a>> = terms.iter().map(|t| encode(env, t)).collect();
+    terms.encode(env)
 }
 
-impl Encoder for SupportedTerm {
-    fn encode<'a>(&self, env: Env<'a>) -> Term<'a> {
-        match self {
-            SupportedTerm::Integer(inner) => inner.encode(env),
-            SupportedTerm::Atom(inner) => match Atom::from_str(env, inner) {
-                Ok(atom) => atom.encode(env),
-                Err(_) => atoms::error().encode(env),
-            },
-            SupportedTerm::Tuple(inner) => {
-                let terms: Vec<_> = inner.into_iter().map(|t| t.encode(env)).collect();
-                make_tuple(env, terms.as_ref()).encode(env)
-            }
-            SupportedTerm::List(inner) => inner.encode(env),
-            SupportedTerm::Bitstring(inner) => inner.encode(env),
-        }
-    }
+fn encode_tuple<'a>(env: Env<'a>, terms: &[SupportedTerm]) -> Term<'a> {
+    let terms: Vec<Term<'a>> = terms.iter().map(|t| encode(env, t)).collect();
+    make_tuple(env, terms.as_ref()).encode(env)
 }
@@ -0,0 +1,234 @@
+//! Hot code upgrades of the NIF library.
+//!
+//! rustler leaves the upgrade callback of the NIF entry unset, which makes the BEAM refuse to load
+//! a new version of the library while processes still run the old one.  `export_nifs!` fills in
+//! the callback, the new library then takes over the resource types of the old one so that every
+//! set created before the upgrade keeps working, and is eventually destroyed, with the new code.
+//!
+//! Taking over a resource type means the new code reads objects laid out by the old code.  Every
+//! library leaves a description of its resource layout in its private data, the upgrade is refused
+//! unless the new library agrees with it and the old code simply keeps running.  `LAYOUT_VERSION`
+//! has to be bumped whenever a resource, or anything held by one, changes shape.  Building with a
+//! different compiler version counts as a change of shape.
+//!
+//! The global accounting and the collector live in the private data as well, so they survive the
+//! upgrade along with the sets.
+
+use rustler::resource::NIF_RESOURCE_FLAGS;
+use rustler::types::pid::Pid;
+use rustler::Env;
+use std::mem;
+use std::ptr;
+use std::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+use std::sync::Mutex;
+use DualIndexResource;
+use IteratorResource;
+use MergedIteratorResource;
+use MergedViewResource;
+use NamespacesResource;
+use PersistentSetResource;
+use RangeIteratorResource;
+use RangeViewResource;
+use ShardedSetResource;
+use SortedSetResource;
+
+const LAYOUT_VERSION: usize = 16;
+
+/// Describes the layout of the resources, must never change itself.
+#[repr(C)]
+#[derive(Clone, Copy, PartialEq)]
+struct Layout {
+    version: usize,
+    sizes: [usize; 11],
+}
+
+impl Layout {
+    fn current() -> Layout {
+        Layout {
+            version: LAYOUT_VERSION,
+            sizes: [
+                mem::size_of::<SortedSetResource>(),
+                mem::size_of::<PersistentSetResource>(),
+                mem::size_of::<IteratorResource>(),
+                mem::size_of::<NamespacesResource>(),
+                mem::size_of::<MergedViewResource>(),
+                mem::size_of::<MergedIteratorResource>(),
+                mem::size_of::<RangeViewResource>(),
+                mem::size_of::<RangeIteratorResource>(),
+                mem::size_of::<DualIndexResource>(),
+                mem::size_of::<ShardedSetResource>(),
+                mem::size_of::<Globals>(),
+            ],
+        }
+    }
+}
+
+/// State shared by every version of the library, stored as the private data of the library.
+#[repr(C)]
+pub struct Globals {
+    layout: Layout,
+
+    /// Number of SortedSetResources that are alive.
+    pub live_sets: AtomicUsize,
+
+    /// Sum of the memory estimates of the live SortedSetResources.
+    pub live_memory: AtomicUsize,
+
+    /// Number of NIF calls that panicked.
+    pub panics: AtomicUsize,
+
+    /// Process that is sent a `{:sorted_set_destroyed, meta, stats}` message for every
+    /// SortedSetResource that is garbage collected.
+    pub collector: Mutex<Option<Pid>>,
+}
+
+static GLOBALS: AtomicPtr<Globals> = AtomicPtr::new(ptr::null_mut());
+
+pub fn globals() -> &'static Globals {
+    // Set by `load` or `upgrade` before any NIF can run, and never freed
+    unsafe { &*GLOBALS.load(Ordering::Acquire) }
+}
+
+/// Loads the library into a VM that is not running an older version of it.
+pub fn load(env: Env, priv_data: *mut *mut ::rustler::codegen_runtime::c_void) -> bool {
+    if !::init_resources(env, NIF_RESOURCE_FLAGS::ERL_NIF_RT_CREATE) {
+        return false;
+    }
+
+    let globals = Box::into_raw(Box::new(Globals {
+        layout: Layout::current(),
+        live_sets: AtomicUsize::new(0),
+        live_memory: AtomicUsize::new(0),
+        panics: AtomicUsize::new(0),
+        collector: Mutex::new(None),
+    }));
+
+    GLOBALS.store(globals, Ordering::Release);
+    unsafe { *priv_data = globals as *mut _ };
+
+    true
+}
+
+/// Loads the library while an older version of it is still loaded, taking over its resources.
+pub fn upgrade(
+    env: Env,
+    priv_data: *mut *mut ::rustler::codegen_runtime::c_void,
+    old_priv_data: *mut *mut ::rustler::codegen_runtime::c_void,
+) -> bool {
+    // Versions of the library from before upgrades were supported leave no private data
+    let globals = unsafe { *old_priv_data } as *mut Globals;
+    if globals.is_null() || unsafe { (*globals).layout } != Layout::current() {
+        return false;
+    }
+
+    if !::init_resources(env, NIF_RESOURCE_FLAGS::ERL_NIF_RT_TAKEOVER) {
+        return false;
+    }
+
+    GLOBALS.store(globals, Ordering::Release);
+    unsafe { *priv_data = globals as *mut _ };
+
+    true
+}
+
+/// Same as `resource_struct_init!` but opens the resource type with the given flags.
+macro_rules! open_resource_type {
+    ($struct_name:ty, $env:ident, $flags:expr) => {{
+        static mut STRUCT_TYPE: Option<::rustler::resource::ResourceType<$struct_name>> = None;
+
+        let struct_type = match ::rustler::resource::open_struct_resource_type::<$struct_name>(
+            $env,
+            concat!(stringify!($struct_name), "\x00"),
+            $flags,
+        ) {
+            Some(struct_type) => struct_type,
+            None => return false,
+        };
+        unsafe { STRUCT_TYPE = Some(struct_type) };
+
+        impl ::rustler::resource::ResourceTypeProvider for $struct_name {
+            fn get_type() -> &'static ::rustler::resource::ResourceType<Self> {
+                unsafe { &*::std::ptr::addr_of!(STRUCT_TYPE) }
+                    .as_ref()
+                    .expect("resource types are opened when the library is loaded")
+            }
+        }
+    }};
+}
+
+/// Same as `rustler_export_nifs!` but with the load and upgrade callbacks of this module, and
+/// with every NIF called through `panics::handle_nif_call`.
+macro_rules! export_nifs {
+    (internal_item_init, ($nif_name:expr, $nif_arity:expr, $nif_fun:path)) => {
+        export_nifs!(internal_item_init, ($nif_name, $nif_arity, $nif_fun, ::rustler::schedule::SchedulerFlags::Normal))
+    };
+    (internal_item_init, ($nif_name:expr, $nif_arity:expr, $nif_fun:path, $nif_flag:expr)) => {
+        ::rustler::codegen_runtime::DEF_NIF_FUNC {
+            name: concat!($nif_name, "\x00") as *const str as *const u8,
+            arity: $nif_arity,
+            function: {
+                extern "C" fn nif_func(
+                    env: ::rustler::codegen_runtime::NIF_ENV,
+                    argc: ::rustler::codegen_runtime::c_int,
+                    argv: *const ::rustler::codegen_runtime::NIF_TERM,
+                ) -> ::rustler::codegen_runtime::NIF_TERM {
+                    unsafe { ::panics::handle_nif_call($nif_fun, env, argc, argv) }
+                }
+                nif_func
+            },
+            flags: ($nif_flag as ::rustler::schedule::SchedulerFlags) as u32,
+        }
+    };
+    ($name:expr, [$( $exported_nif:tt ),+,]) => {
+        export_nifs!($name, [$( $exported_nif ),*]);
+    };
+    ($name:expr, [$( $exported_nif:tt ),*]) => {
+        static mut NIF_ENTRY: Option<::rustler::codegen_runtime::DEF_NIF_ENTRY> = None;
+
+        rustler_export_nifs!(internal_platform_init, ({
+            use rustler::codegen_runtime::{c_int, c_void, NIF_ENV, NIF_TERM};
+
+            unsafe extern "C" fn nif_load(
+                env: NIF_ENV,
+                priv_data: *mut *mut c_void,
+                _load_info: NIF_TERM,
+            ) -> c_int {
+                let env = ::rustler::Env::new(&(), env);
+                if ::upgrade::load(env, priv_data) { 0 } else { 1 }
+            }
+
+            unsafe extern "C" fn nif_upgrade(
+                env: NIF_ENV,
+                priv_data: *mut *mut c_void,
+                old_priv_data: *mut *mut c_void,
+                _load_info: NIF_TERM,
+            ) -> c_int {
+                let env = ::rustler::Env::new(&(), env);
+                if ::upgrade::upgrade(env, priv_data, old_priv_data) { 0 } else { 1 }
+            }
+
+            const FUN_ENTRIES: &'static [::rustler::codegen_runtime::DEF_NIF_FUNC] = &[
+                $(export_nifs!(internal_item_init, $exported_nif)),*
+            ];
+
+            let entry = ::rustler::codegen_runtime::DEF_NIF_ENTRY {
+                major: ::rustler::codegen_runtime::NIF_MAJOR_VERSION,
+                minor: ::rustler::codegen_runtime::NIF_MINOR_VERSION,
+                name: concat!($name, "\x00") as *const str as *const u8,
+                num_of_funcs: FUN_ENTRIES.len() as c_int,
+                funcs: FUN_ENTRIES.as_ptr(),
+                load: Some(nif_load),
+                reload: None,
+                upgrade: Some(nif_upgrade),
+                unload: None,
+                vm_variant: b"beam.vanilla\x00".as_ptr(),
+                options: 0,
+                sizeof_ErlNifResourceTypeInit:
+                    ::rustler::codegen_runtime::get_nif_resource_type_init_size(),
+            };
+            unsafe { NIF_ENTRY = Some(entry) };
+
+            unsafe { (*::std::ptr::addr_of!(NIF_ENTRY)).as_ref().unwrap() }
+        }));
+    };
+}
@@ -0,0 +1,62 @@
+//! Writes queued on a set created with the `coalesce_writes` option.
+//!
+//! During an event storm every add and remove takes the write lock of the set on its own, so the
+//! writers spend more time handing the lock around than changing the set.  A set that coalesces
+//! its writes has them pushed onto a channel instead, which never touches the lock of the set.
+//! The next call that locks the set takes every queued write off the channel and applies them in
+//! one go, a read may therefore miss the writes that were queued while it was waiting.
+
+use sorted_set_core::SupportedTerm;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+
+pub enum Write {
+    Add(SupportedTerm),
+    Remove(SupportedTerm),
+}
+
+pub struct WriteQueue {
+    sender: Sender<Write>,
+    receiver: Mutex<Receiver<Write>>,
+
+    /// Writes pushed and not taken yet, lets callers skip the receiver while nothing is queued.
+    pending: AtomicUsize,
+}
+
+impl WriteQueue {
+    pub fn new() -> WriteQueue {
+        let (sender, receiver) = mpsc::channel();
+
+        WriteQueue {
+            sender,
+            receiver: Mutex::new(receiver),
+            pending: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn push(&self, write: Write) {
+        self.pending.fetch_add(1, Ordering::AcqRel);
+
+        // The receiver lives as long as the queue, sending can not fail
+        let _ = self.sender.send(write);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.load(Ordering::Acquire) == 0
+    }
+
+    /// Takes every queued write off the queue, in the order they were pushed.  Callers hold the
+    /// write lock of the set so batches are applied in order as well.
+    pub fn take(&self) -> Vec<Write> {
+        let receiver = match self.receiver.lock() {
+            Err(poisoned) => poisoned.into_inner(),
+            Ok(guard) => guard,
+        };
+
+        let writes: Vec<Write> = receiver.try_iter().collect();
+        self.pending.fetch_sub(writes.len(), Ordering::AcqRel);
+
+        writes
+    }
+}
@@ -0,0 +1,29 @@
+defmodule Discord.SortedSet.AddNew.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "add_new/2" do
+    test "adds items that are not present" do
+      set = SortedSet.new()
+
+      assert ^set = SortedSet.add_new(set, 2)
+      assert ^set = SortedSet.add_new(set, 1)
+      assert SortedSet.to_list(set) == [1, 2]
+    end
+
+    test "refuses items that are already present" do
+      set = SortedSet.new() |> SortedSet.add(1)
+
+      assert SortedSet.add_new(set, 1) == {:error, :already_exists}
+      assert SortedSet.to_list(set) == [1]
+    end
+
+    test "passes other errors through" do
+      set = SortedSet.new(500, 5, max_elements: 1) |> SortedSet.add(1)
+
+      assert SortedSet.add_new(set, 2) == {:error, :set_full}
+      assert SortedSet.add_new(set, self()) == {:error, :unsupported_type}
+    end
+  end
+end
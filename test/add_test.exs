@@ -320,4 +320,93 @@ defmodule Discord.SortedSet.Add.Test do
       end
     end
   end
+
+  describe "max_elements option" do
+    test "new items are rejected once the set is full" do
+      assert set = SortedSet.new(10, 3, max_elements: 3)
+
+      for i <- 1..3 do
+        assert ^set = SortedSet.add(set, i)
+      end
+
+      assert {:error, :set_full} = SortedSet.add(set, 4)
+      assert {:error, :set_full} = SortedSet.index_add(set, 4)
+      assert SortedSet.to_list(set) == [1, 2, 3]
+    end
+
+    test "duplicates are still accepted when the set is full" do
+      assert set = SortedSet.new(10, 3, max_elements: 3)
+
+      for i <- 1..3 do
+        assert ^set = SortedSet.add(set, i)
+      end
+
+      assert ^set = SortedSet.add(set, 2)
+      assert SortedSet.size(set) == 3
+    end
+
+    test "removing an item makes room for a new one" do
+      assert set = SortedSet.new(10, 3, max_elements: 2)
+      assert ^set = SortedSet.add(set, 1)
+      assert ^set = SortedSet.add(set, 2)
+      assert {:error, :set_full} = SortedSet.add(set, 3)
+
+      assert ^set = SortedSet.remove(set, 1)
+      assert ^set = SortedSet.add(set, 3)
+      assert SortedSet.to_list(set) == [2, 3]
+    end
+
+    test "bulk construction past the limit is rejected" do
+      assert {:error, :set_full} = SortedSet.from_proper_enumerable(1..10, 3, max_elements: 5)
+    end
+  end
+
+  describe "positional hint" do
+    test "sorted feed using the previous index as the hint" do
+      assert set = SortedSet.new(10, 5)
+
+      Enum.reduce(1..100, 0, fn item, hint ->
+        assert {index, ^set} = SortedSet.index_add(set, item, hint)
+        assert index == hint
+        index + 1
+      end)
+
+      assert SortedSet.to_list(set) == Enum.to_list(1..100)
+    end
+
+    test "wrong hints fall back to a full search" do
+      assert set = SortedSet.from_enumerable([10, 20, 30, 40], 3)
+
+      assert ^set = SortedSet.add(set, 25, 0)
+      assert ^set = SortedSet.add(set, 5, 100)
+      assert ^set = SortedSet.add(set, 45, 2)
+
+      assert SortedSet.to_list(set) == [5, 10, 20, 25, 30, 40, 45]
+    end
+
+    test "duplicates are detected with a correct or incorrect hint" do
+      assert set = SortedSet.from_enumerable([10, 20, 30])
+
+      assert {nil, ^set} = SortedSet.index_add(set, 20, 1)
+      assert {nil, ^set} = SortedSet.index_add(set, 20, 0)
+      assert SortedSet.size(set) == 3
+    end
+
+    property "any hint produces the same set as add/2" do
+      check all items <- list_of(integer()),
+                hints <- list_of(integer(0..50), length: length(items)) do
+        hinted = SortedSet.new(10, 5)
+        plain = SortedSet.new(10, 5)
+
+        items
+        |> Enum.zip(hints)
+        |> Enum.each(fn {item, hint} ->
+          SortedSet.add(hinted, item, hint)
+          SortedSet.add(plain, item)
+        end)
+
+        assert SortedSet.to_list(hinted) == SortedSet.to_list(plain)
+      end
+    end
+  end
 end
@@ -0,0 +1,48 @@
+defmodule Discord.SortedSet.Aggregate.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "aggregate/4" do
+    test "aggregates the integers of the slice" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(1..100))
+
+      assert SortedSet.aggregate(set, 0, 100, :sum) == 5_050
+      assert SortedSet.aggregate(set, 10, 5, :sum) == 11 + 12 + 13 + 14 + 15
+      assert SortedSet.aggregate(set, 10, 5, :min) == 11
+      assert SortedSet.aggregate(set, 10, 5, :max) == 15
+      assert SortedSet.aggregate(set, 10, 4, :avg) == 12.5
+      assert SortedSet.aggregate(set, 95, 100, :max) == 100
+    end
+
+    test "empty slices" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+
+      assert SortedSet.aggregate(set, 5, 10, :sum) == 0
+      assert SortedSet.aggregate(set, 5, 10, :min) == nil
+      assert SortedSet.aggregate(set, 0, 0, :avg) == nil
+    end
+
+    test "refuses slices with other items" do
+      set = SortedSet.from_enumerable([1, 2, :three])
+
+      assert SortedSet.aggregate(set, 0, 2, :sum) == 3
+      assert SortedSet.aggregate(set, 0, 3, :sum) == {:error, :not_integer}
+    end
+
+    test "refuses sums that do not fit in 64 bits" do
+      max = 9_223_372_036_854_775_807
+      set = SortedSet.from_proper_enumerable([max - 1, max])
+
+      assert SortedSet.aggregate(set, 0, 2, :sum) == {:error, :overflow}
+      assert SortedSet.aggregate(set, 0, 2, :max) == max
+    end
+
+    test "invalid ranges and aggregates" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+
+      assert SortedSet.aggregate(set, -1, 2, :sum) == {:error, :invalid_range}
+      assert_raise ArgumentError, fn -> SortedSet.aggregate(set, 0, 2, :median) end
+    end
+  end
+end
@@ -0,0 +1,85 @@
+defmodule Discord.SortedSet.Alert.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "size watermark" do
+    test "alert is sent once the set reaches the watermark" do
+      set = SortedSet.alert(SortedSet.new(), {:size, 3})
+
+      SortedSet.add(set, 1)
+      SortedSet.add(set, 2)
+      refute_receive {:sorted_set_alert, _, _, _}
+
+      SortedSet.add(set, 3)
+      assert_receive {:sorted_set_alert, ^set, {:size, 3}, 3}
+    end
+
+    test "alert is only sent once" do
+      set = SortedSet.alert(SortedSet.new(), {:size, 1})
+
+      SortedSet.add(set, 1)
+      assert_receive {:sorted_set_alert, ^set, {:size, 1}, 1}
+
+      SortedSet.remove(set, 1)
+      SortedSet.add(set, 1)
+      SortedSet.add(set, 2)
+      refute_receive {:sorted_set_alert, _, _, _}
+    end
+
+    test "set already past the watermark alerts immediately" do
+      set = SortedSet.from_enumerable([1, 2, 3])
+
+      SortedSet.alert(set, {:size, 2})
+
+      assert_receive {:sorted_set_alert, ^set, {:size, 2}, 3}
+    end
+
+    test "bulk construction triggers the alert" do
+      set = SortedSet.new()
+      SortedSet.alert(set, {:size, 5})
+
+      other = SortedSet.from_enumerable(1..10)
+      SortedSet.swap(set, other)
+
+      assert_receive {:sorted_set_alert, ^set, {:size, 5}, 10}
+    end
+
+    test "alert can be sent to another process" do
+      parent = self()
+
+      watcher =
+        spawn(fn ->
+          receive do
+            alert -> send(parent, {:forwarded, alert})
+          end
+        end)
+
+      set = SortedSet.alert(SortedSet.new(), {:size, 1}, watcher)
+      SortedSet.add(set, 1)
+
+      assert_receive {:forwarded, {:sorted_set_alert, ^set, {:size, 1}, 1}}
+    end
+  end
+
+  describe "memory watermark" do
+    test "alert is sent once the set grows past the watermark" do
+      set = SortedSet.alert(SortedSet.new(), {:memory, 64 * 1024})
+
+      Enum.each(1..100, &SortedSet.add(set, &1))
+      refute_receive {:sorted_set_alert, _, _, _}
+
+      Enum.each(101..10_000, &SortedSet.add(set, &1))
+      assert_receive {:sorted_set_alert, ^set, {:memory, 65_536}, memory}
+      assert memory >= 64 * 1024
+    end
+  end
+
+  describe "invalid watermark" do
+    test "unknown watermark is rejected" do
+      assert_raise ArgumentError, fn ->
+        SortedSet.alert(SortedSet.new(), {:elements, 10})
+      end
+    end
+  end
+end
@@ -0,0 +1,20 @@
+defmodule Discord.SortedSet.AllocReport.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  test "reports every category or that the feature is disabled" do
+    set = SortedSet.from_enumerable([1, "binary", {:tuple, [:list]}])
+
+    case SortedSet.alloc_report(set) do
+      {:error, :not_enabled} ->
+        :ok
+
+      report ->
+        assert Keyword.keys(report) == [:buckets, :strings, :tuples, :lists]
+        assert report[:strings][:count] == 3
+        assert report[:tuples][:count] == 1
+        assert report[:lists][:count] == 1
+    end
+  end
+end
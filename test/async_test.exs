@@ -0,0 +1,79 @@
+defmodule Discord.SortedSet.Async.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "async_to_list/1" do
+    test "replies with the contents of the set" do
+      set = SortedSet.from_enumerable([3, 1, 2])
+
+      tag = SortedSet.async_to_list(set)
+
+      assert is_reference(tag)
+      assert_receive {:sorted_set_reply, ^tag, [1, 2, 3]}
+    end
+
+    test "await returns the reply" do
+      set = SortedSet.from_enumerable(1..1000)
+
+      assert set |> SortedSet.async_to_list() |> SortedSet.await() == Enum.to_list(1..1000)
+    end
+
+    test "bad reference is reported synchronously" do
+      assert {:error, :bad_reference} = SortedSet.async_to_list(make_ref())
+    end
+  end
+
+  describe "async_from_list/3" do
+    test "replies with a new set" do
+      tag = SortedSet.async_from_list([5, 3, 1, 3, 4], 3)
+
+      assert {:ok, set} = SortedSet.await(tag)
+      assert SortedSet.to_list(set) == [1, 3, 4, 5]
+      assert SortedSet.size(set) == 4
+    end
+
+    test "empty list replies with an empty set" do
+      assert {:ok, set} = [] |> SortedSet.async_from_list() |> SortedSet.await()
+      assert SortedSet.to_list(set) == []
+    end
+
+    test "unsupported types are reported in the reply" do
+      tag = SortedSet.async_from_list([1, 2, self()])
+
+      assert {:error, :unsupported_type} = SortedSet.await(tag)
+    end
+
+    test "options are respected" do
+      tag = SortedSet.async_from_list([1, 2, 3], 5, max_elements: 2)
+
+      assert {:error, :set_full} = SortedSet.await(tag)
+    end
+  end
+
+  describe "async_union/2" do
+    test "replies with the union of both sets" do
+      a = SortedSet.from_enumerable([1, 3, 5, 7])
+      b = SortedSet.from_enumerable([2, 3, 4, 8])
+
+      assert {:ok, union} = a |> SortedSet.async_union(b) |> SortedSet.await()
+      assert SortedSet.to_list(union) == [1, 2, 3, 4, 5, 7, 8]
+
+      assert SortedSet.to_list(a) == [1, 3, 5, 7]
+      assert SortedSet.to_list(b) == [2, 3, 4, 8]
+    end
+
+    test "union of a set with itself" do
+      a = SortedSet.from_enumerable([1, 2, 3])
+
+      assert {:ok, union} = a |> SortedSet.async_union(a) |> SortedSet.await()
+      assert SortedSet.to_list(union) == [1, 2, 3]
+    end
+  end
+
+  describe "await/2" do
+    test "times out when no reply arrives" do
+      assert {:error, :timeout} = SortedSet.await(make_ref(), 10)
+    end
+  end
+end
@@ -0,0 +1,46 @@
+defmodule Discord.SortedSet.AtMany.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "at_many/3" do
+    test "returns the elements in the order of the indexes" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(100..129), 4)
+
+      assert SortedSet.at_many(set, [29, 0, 7, 7, 15]) == [129, 100, 107, 107, 115]
+    end
+
+    test "agrees with at after removals" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(0..99), 6)
+
+      Enum.each(Enum.take_every(0..99, 3), &SortedSet.remove(set, &1))
+
+      indexes = Enum.shuffle(0..70)
+
+      assert SortedSet.at_many(set, indexes) == Enum.map(indexes, &SortedSet.at(set, &1))
+    end
+
+    test "returns the default for indexes out of bounds" do
+      set = SortedSet.from_proper_enumerable([:a, :b])
+
+      assert SortedSet.at_many(set, [1, 2, 0]) == [:b, nil, :a]
+      assert SortedSet.at_many(set, [5], :none) == [:none]
+      assert SortedSet.at_many(set, []) == []
+    end
+
+    test "negative indexes count from the end" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(0..9), 3)
+
+      assert SortedSet.at_many(set, [-1, 0, -10, -11]) == [9, 0, 0, nil]
+    end
+
+    test "a range selects like Enum.slice/2" do
+      items = Enum.to_list(0..19)
+      set = SortedSet.from_proper_enumerable(items, 4)
+
+      for range <- [0..4, -3..-1, 15..30, 25..30, 1..-2//1, 0..19//5, 4..2//1] do
+        assert SortedSet.at_many(set, range) == Enum.slice(items, range), inspect(range)
+      end
+    end
+  end
+end
@@ -2,6 +2,7 @@ defmodule Discord.SortedSet.At.Test do
   use ExUnit.Case
 
   alias Discord.SortedSet
+  alias Discord.SortedSet.NifBridge
 
   describe "at/2" do
     setup [:at_fixtures]
@@ -39,5 +40,9 @@ defmodule Discord.SortedSet.At.Test do
       assert SortedSet.at(ctx.populated_set, 1, :default) == 2
       assert SortedSet.at(ctx.populated_set, 2, :default) == 3
     end
+
+    test "the NIF reports the index and the size when out of bounds", ctx do
+      assert NifBridge.at(ctx.populated_set, 7) == {:error, {:index_out_of_bounds, 7, 3}}
+    end
   end
 end
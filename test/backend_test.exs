@@ -0,0 +1,117 @@
+defmodule Discord.SortedSet.Backend.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "btree backend" do
+    test "behaves like the buckets backend" do
+      terms = Enum.map(1..2_000, &rem(&1 * 7919, 1_000))
+
+      buckets = SortedSet.from_enumerable(terms, 5)
+      btree = SortedSet.from_enumerable(terms, 5, backend: :btree)
+
+      assert SortedSet.to_list(btree) == SortedSet.to_list(buckets)
+      assert SortedSet.size(btree) == 1_000
+      assert SortedSet.at(btree, 250) == 250
+      assert SortedSet.slice(btree, 995, 10) == [995, 996, 997, 998, 999]
+      assert SortedSet.find_index(btree, 777) == 777
+    end
+
+    test "adds and removes report the index of the element" do
+      set = SortedSet.new(500, 500, backend: :btree)
+
+      for i <- 0..999 do
+        assert {^i, ^set} = SortedSet.index_add(set, i * 2)
+      end
+
+      assert {nil, ^set} = SortedSet.index_add(set, 10)
+      assert {6, ^set} = SortedSet.index_add(set, 11)
+      assert {6, ^set} = SortedSet.index_remove(set, 11)
+      assert {nil, ^set} = SortedSet.index_remove(set, 11)
+      assert SortedSet.size(set) == 1_000
+    end
+
+    test "supports partition and concat" do
+      set = SortedSet.from_enumerable(1..200, 5, backend: :btree)
+
+      {lower, upper} = SortedSet.partition(set, 101)
+
+      assert SortedSet.to_list(lower) == Enum.to_list(1..100)
+      assert SortedSet.to_list(upper) == Enum.to_list(101..200)
+
+      assert SortedSet.concat(lower, upper) == lower
+      assert SortedSet.to_list(lower) == Enum.to_list(1..200)
+    end
+
+    test "rejects an unknown backend" do
+      assert_raise ArgumentError, fn ->
+        SortedSet.new(500, 500, backend: :skiplist)
+      end
+    end
+  end
+
+  describe "bitset backend" do
+    test "behaves like the buckets backend" do
+      terms = Enum.map(1..2_000, &rem(&1 * 7919, 1_000))
+
+      buckets = SortedSet.from_enumerable(terms, 5)
+      bitset = SortedSet.from_enumerable(terms, 5, backend: {:bitset, 1_024})
+
+      assert SortedSet.to_list(bitset) == SortedSet.to_list(buckets)
+      assert SortedSet.size(bitset) == 1_000
+      assert SortedSet.at(bitset, 250) == 250
+      assert SortedSet.slice(bitset, 995, 10) == [995, 996, 997, 998, 999]
+      assert SortedSet.find_index(bitset, 777) == 777
+      assert SortedSet.find_index(bitset, 1_000) == nil
+    end
+
+    test "adds and removes report the index of the element" do
+      set = SortedSet.new(500, 500, backend: {:bitset, 4_096})
+
+      for i <- 0..999 do
+        assert {^i, ^set} = SortedSet.index_add(set, i * 2)
+      end
+
+      assert {nil, ^set} = SortedSet.index_add(set, 10)
+      assert {6, ^set} = SortedSet.index_add(set, 11)
+      assert {6, ^set} = SortedSet.index_remove(set, 11)
+      assert {nil, ^set} = SortedSet.index_remove(set, 11)
+      assert SortedSet.size(set) == 1_000
+    end
+
+    test "elements outside of the universe move the set into buckets" do
+      set = SortedSet.from_enumerable(0..99, 5, backend: {:bitset, 100})
+
+      assert {100, ^set} = SortedSet.index_add(set, 100)
+      assert {0, ^set} = SortedSet.index_add(set, -1)
+      assert {102, ^set} = SortedSet.index_add(set, "shard")
+      assert SortedSet.to_list(set) == Enum.to_list(-1..100) ++ ["shard"]
+    end
+
+    test "supports partition and concat" do
+      set = SortedSet.from_enumerable(1..200, 5, backend: {:bitset, 256})
+
+      {lower, upper} = SortedSet.partition(set, 101)
+
+      assert SortedSet.to_list(lower) == Enum.to_list(1..100)
+      assert SortedSet.to_list(upper) == Enum.to_list(101..200)
+
+      assert SortedSet.concat(lower, upper) == lower
+      assert SortedSet.to_list(lower) == Enum.to_list(1..200)
+    end
+
+    test "rejects invalid universes and custom orders" do
+      assert_raise ArgumentError, fn ->
+        SortedSet.new(500, 500, backend: {:bitset, 0})
+      end
+
+      assert_raise ArgumentError, fn ->
+        SortedSet.new(500, 500, backend: {:bitset, 2_000_000})
+      end
+
+      assert_raise ArgumentError, fn ->
+        SortedSet.new(500, 500, backend: {:bitset, 64}, collation: :natural)
+      end
+    end
+  end
+end
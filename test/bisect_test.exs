@@ -0,0 +1,38 @@
+defmodule Discord.SortedSet.Bisect.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "bisect_left/2 and bisect_right/2" do
+    setup do
+      {:ok, set: SortedSet.from_proper_enumerable(Enum.take_every(0..38, 2), 3)}
+    end
+
+    test "bracket an element of the set", %{set: set} do
+      assert SortedSet.bisect_left(set, 8) == 4
+      assert SortedSet.bisect_right(set, 8) == 5
+      assert SortedSet.bisect_left(set, 0) == 0
+      assert SortedSet.bisect_right(set, 38) == 20
+    end
+
+    test "return the insertion index of an absent element", %{set: set} do
+      assert SortedSet.bisect_left(set, 7) == 4
+      assert SortedSet.bisect_right(set, 7) == 4
+      assert SortedSet.bisect_left(set, -1) == 0
+      assert SortedSet.bisect_right(set, :atom) == 20
+    end
+
+    test "count the elements of a range", %{set: set} do
+      assert SortedSet.bisect_right(set, 20) - SortedSet.bisect_left(set, 5) == 8
+    end
+
+    test "return 0 for an empty set" do
+      assert SortedSet.bisect_left(SortedSet.new(), 1) == 0
+      assert SortedSet.bisect_right(SortedSet.new(), 1) == 0
+    end
+
+    test "reject unsupported types", %{set: set} do
+      assert SortedSet.bisect_left(set, self()) == {:error, :unsupported_type}
+    end
+  end
+end
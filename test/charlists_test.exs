@@ -0,0 +1,64 @@
+defmodule Discord.SortedSet.Charlists.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "charlists: true" do
+    test "charlists are stored as the strings they spell out" do
+      set =
+        SortedSet.new(0, 5, charlists: true)
+        |> SortedSet.add('bees')
+        |> SortedSet.add("ants")
+        |> SortedSet.add({'cows', 1})
+        |> SortedSet.add([1, 2])
+
+      assert SortedSet.to_list(set) == [{"cows", 1}, [1, 2], "ants", "bees"]
+      assert {nil, ^set} = SortedSet.index_add(set, "bees")
+      assert SortedSet.find_index(set, 'ants') == 2
+      assert SortedSet.find_index(set, {"cows", 1}) == 0
+    end
+
+    test "from_enumerable/3 sorts the strings the charlists become" do
+      set = SortedSet.from_enumerable(['bees', "cows", 'ants'], 5, charlists: true)
+
+      assert SortedSet.to_list(set) == ["ants", "bees", "cows"]
+    end
+
+    test "removing either form removes the string" do
+      set =
+        SortedSet.new(0, 5, charlists: true)
+        |> SortedSet.add("ants")
+        |> SortedSet.remove('ants')
+
+      assert SortedSet.size(set) == 0
+    end
+
+    test "empty lists and lists of other terms stay lists" do
+      set =
+        SortedSet.new(0, 5, charlists: true)
+        |> SortedSet.add([])
+        |> SortedSet.add([?a, :b])
+
+      assert SortedSet.to_list(set) == [[], [?a, :b]]
+    end
+
+    test "charlists are lists without the option" do
+      set =
+        SortedSet.new()
+        |> SortedSet.add('bees')
+        |> SortedSet.add("ants")
+
+      assert SortedSet.to_list(set) == ['bees', "ants"]
+    end
+
+    test "concat/2 refuses to mix sets that disagree on the option" do
+      a = SortedSet.new(0, 5, charlists: true) |> SortedSet.add(1)
+      b = SortedSet.new() |> SortedSet.add('hi')
+
+      assert SortedSet.concat(a, b) == {:error, :charlists_mismatch}
+      assert SortedSet.concat(b, a) == {:error, :charlists_mismatch}
+      assert SortedSet.to_list(a) == [1]
+      assert SortedSet.to_list(b) == ['hi']
+    end
+  end
+end
@@ -0,0 +1,32 @@
+defmodule Discord.SortedSet.Chunks.Test do
+  use ExUnit.Case
+  use ExUnitProperties
+
+  alias Discord.SortedSet
+
+  describe "chunks/2" do
+    test "splits the set into chunks of the given size" do
+      set = SortedSet.from_enumerable(1..7, 3)
+
+      assert SortedSet.chunks(set, 3) == [[1, 2, 3], [4, 5, 6], [7]]
+      assert SortedSet.chunks(set, 10) == [[1, 2, 3, 4, 5, 6, 7]]
+    end
+
+    test "an empty set has no chunks" do
+      assert SortedSet.chunks(SortedSet.new(), 3) == []
+    end
+
+    test "rejects chunk sizes that are not positive" do
+      assert_raise ArgumentError, fn -> SortedSet.chunks(SortedSet.new(), 0) end
+    end
+
+    property "matches Enum.chunk_every/2" do
+      check all items <- list_of(integer()), chunk_size <- integer(1..10) do
+        set = SortedSet.from_enumerable(items, 4)
+
+        assert SortedSet.chunks(set, chunk_size) ==
+                 set |> SortedSet.to_list() |> Enum.chunk_every(chunk_size)
+      end
+    end
+  end
+end
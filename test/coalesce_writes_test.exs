@@ -0,0 +1,83 @@
+defmodule Discord.SortedSet.CoalesceWrites.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "coalesce_writes" do
+    test "writes are applied by the next call that locks the set" do
+      set = SortedSet.new(0, 5, coalesce_writes: true)
+
+      assert ^set = SortedSet.add(set, 3)
+      assert ^set = SortedSet.add(set, 1)
+      assert ^set = SortedSet.add(set, 2)
+      assert ^set = SortedSet.remove(set, 3)
+      assert ^set = SortedSet.remove(set, 10)
+
+      assert SortedSet.size(set) == 2
+      assert SortedSet.to_list(set) == [1, 2]
+      assert SortedSet.flush(set) == 0
+    end
+
+    test "direct writes land after the queued writes" do
+      set = SortedSet.new(0, 5, coalesce_writes: true)
+
+      SortedSet.add(set, 1)
+      SortedSet.add(set, 3)
+
+      assert {1, ^set} = SortedSet.index_add(set, 2)
+      assert {2, ^set} = SortedSet.index_remove(set, 3)
+    end
+
+    test "flush/1 applies the queue and notifies subscribers" do
+      set = SortedSet.subscribe(SortedSet.new(0, 5, coalesce_writes: true))
+
+      SortedSet.add(set, 5)
+      SortedSet.add(set, 3)
+      SortedSet.remove(set, 5)
+
+      refute_receive {:sorted_set_event, _, _, _, _, _}
+
+      assert SortedSet.flush(set) == 3
+      assert_receive {:sorted_set_event, ^set, :added, 5, 0, 1}
+      assert_receive {:sorted_set_event, ^set, :added, 3, 0, 2}
+      assert_receive {:sorted_set_event, ^set, :removed, 5, 1, 3}
+    end
+
+    test "unsupported items are refused when queued" do
+      set = SortedSet.new(0, 5, coalesce_writes: true)
+
+      assert SortedSet.add(set, self()) == {:error, :unsupported_type}
+      assert SortedSet.flush(set) == 0
+    end
+
+    test "writes to a frozen or full set are refused instead of queued" do
+      frozen = SortedSet.freeze(SortedSet.new(0, 5, coalesce_writes: true))
+
+      assert SortedSet.add(frozen, 1) == {:error, :frozen}
+      assert SortedSet.remove(frozen, 1) == {:error, :frozen}
+      assert SortedSet.flush(frozen) == 0
+
+      full = SortedSet.from_enumerable([1, 2], 5, coalesce_writes: true, max_elements: 2)
+
+      assert SortedSet.add(full, 3) == {:error, :set_full}
+      assert ^full = SortedSet.remove(full, 1)
+      assert SortedSet.flush(full) == 1
+      assert SortedSet.to_list(full) == [2]
+    end
+
+    test "sets without the option write right away" do
+      set = SortedSet.new()
+
+      SortedSet.add(set, 1)
+
+      assert SortedSet.flush(set) == 0
+      assert SortedSet.to_list(set) == [1]
+    end
+
+    test "can not be combined with single owner sets" do
+      assert_raise ArgumentError, fn ->
+        SortedSet.new(0, 5, coalesce_writes: true, single_owner: true)
+      end
+    end
+  end
+end
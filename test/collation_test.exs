@@ -0,0 +1,51 @@
+defmodule Discord.SortedSet.Collation.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "collation option" do
+    test "orders strings by the locale instead of by their bytes" do
+      terms = ["Zebra", "apple", "Äpfel", "Banana"]
+
+      set = SortedSet.from_enumerable(terms, 500, collation: "de")
+
+      assert SortedSet.to_list(set) == ["Äpfel", "apple", "Banana", "Zebra"]
+    end
+
+    test "adds, finds and removes strings in collation order" do
+      set = SortedSet.new(500, 5, collation: "sv")
+
+      assert {0, ^set} = SortedSet.index_add(set, "z")
+      assert {1, ^set} = SortedSet.index_add(set, "ö")
+      assert {0, ^set} = SortedSet.index_add(set, "o")
+
+      assert SortedSet.find_index(set, "ö") == 2
+      assert {1, ^set} = SortedSet.index_remove(set, "z")
+      assert SortedSet.to_list(set) == ["o", "ö"]
+    end
+
+    test "collates strings nested in tuples" do
+      set = SortedSet.from_enumerable([{1, "b"}, {1, "äa"}, {0, "z"}], 500, collation: "de")
+
+      assert SortedSet.to_list(set) == [{0, "z"}, {1, "äa"}, {1, "b"}]
+    end
+
+    test "orders numbers in strings by value with natural order" do
+      terms = ["channel-10", "channel-2", "channel-1", "channel-002"]
+
+      set = SortedSet.from_enumerable(terms, 500, collation: :natural)
+
+      assert SortedSet.to_list(set) == ["channel-1", "channel-002", "channel-2", "channel-10"]
+      assert SortedSet.find_index(set, "channel-10") == 3
+    end
+
+    test "rejects sets with a different collation in concat" do
+      a = SortedSet.from_enumerable(["a"], 500, collation: "de")
+      b = SortedSet.from_enumerable(["b"], 500)
+
+      assert SortedSet.concat(a, b) == {:error, :collation_mismatch}
+      assert SortedSet.to_list(a) == ["a"]
+      assert SortedSet.to_list(b) == ["b"]
+    end
+  end
+end
@@ -0,0 +1,56 @@
+defmodule Discord.SortedSet.Collector.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  setup do
+    on_exit(fn -> SortedSet.unregister_collector() end)
+  end
+
+  describe "register_collector/1" do
+    test "is told when a set is garbage collected" do
+      assert SortedSet.register_collector() == :ok
+
+      spawn(fn ->
+        1..100
+        |> SortedSet.from_enumerable()
+        |> SortedSet.put_meta({:shard, 7})
+      end)
+
+      assert_receive {:sorted_set_destroyed, {:shard, 7}, stats}, 1000
+      assert stats[:size] == 100
+      assert stats[:memory] > 0
+      assert is_integer(stats[:version])
+    end
+
+    test "reports nil for sets without metadata" do
+      assert SortedSet.register_collector() == :ok
+
+      spawn(fn -> SortedSet.new() end)
+
+      assert_receive {:sorted_set_destroyed, nil, [size: 0, memory: _, version: _]}, 1000
+    end
+
+    test "stops after unregister_collector/0" do
+      assert SortedSet.register_collector() == :ok
+      assert SortedSet.unregister_collector() == :ok
+
+      spawn(fn -> SortedSet.new() end)
+
+      refute_receive {:sorted_set_destroyed, _, _}, 100
+    end
+  end
+
+  describe "global_stats/0" do
+    test "counts live sets" do
+      set = SortedSet.new()
+
+      assert [sets: sets, memory: memory, panics: panics] = SortedSet.global_stats()
+      assert sets >= 1
+      assert memory > 0
+      assert panics >= 0
+
+      SortedSet.size(set)
+    end
+  end
+end
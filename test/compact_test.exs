@@ -0,0 +1,50 @@
+defmodule Discord.SortedSet.Compact.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "compact/1" do
+    test "drops the tombstones of removed elements" do
+      set = SortedSet.from_enumerable(1..20, 10, tombstones: 0.9)
+
+      set =
+        set
+        |> SortedSet.remove(2)
+        |> SortedSet.remove(4)
+        |> SortedSet.remove(6)
+
+      assert SortedSet.size(set) == 17
+      assert SortedSet.find_index(set, 4) == nil
+      assert SortedSet.find_index(set, 7) == 3
+      assert SortedSet.slice(set, 0, 4) == [1, 3, 5, 7]
+
+      assert SortedSet.compact(set) == 3
+      assert SortedSet.compact(set) == 0
+      assert SortedSet.to_list(set) == Enum.to_list(1..20) -- [2, 4, 6]
+    end
+
+    test "removed elements can be added again" do
+      set =
+        1..10
+        |> SortedSet.from_enumerable(10, tombstones: 0.9)
+        |> SortedSet.remove(5)
+        |> SortedSet.add(5)
+
+      assert SortedSet.size(set) == 10
+      assert SortedSet.compact(set) == 0
+      assert SortedSet.to_list(set) == Enum.to_list(1..10)
+    end
+
+    test "sets without tombstones have nothing to compact" do
+      set = SortedSet.from_enumerable(1..20, 10)
+
+      assert SortedSet.compact(SortedSet.remove(set, 2)) == 0
+    end
+
+    test "refuses ratios out of range and other backends" do
+      assert_raise ArgumentError, fn -> SortedSet.new(0, 10, tombstones: 1.0) end
+      assert_raise ArgumentError, fn -> SortedSet.new(0, 10, tombstones: -0.5) end
+      assert_raise ArgumentError, fn -> SortedSet.new(0, 10, backend: :btree, tombstones: 0.5) end
+    end
+  end
+end
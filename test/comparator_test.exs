@@ -0,0 +1,113 @@
+defmodule Discord.SortedSet.Comparator.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "comparator option" do
+    test "orders tuples by score descending and then name ascending" do
+      entries = [{"carol", 10}, {"bob", 30}, {"alice", 10}, {"dave", 20}]
+
+      set = SortedSet.from_enumerable(entries, 500, comparator: [{1, :desc}, {0, :asc}])
+
+      assert SortedSet.to_list(set) == [{"bob", 30}, {"dave", 20}, {"alice", 10}, {"carol", 10}]
+    end
+
+    test "adds, finds and removes tuples in comparator order" do
+      set = SortedSet.new(500, 5, comparator: [{1, :desc}])
+
+      assert {0, ^set} = SortedSet.index_add(set, {:a, 1})
+      assert {0, ^set} = SortedSet.index_add(set, {:b, 2})
+      assert {1, ^set} = SortedSet.index_add(set, {:c, 2})
+
+      assert SortedSet.find_index(set, {:a, 1}) == 2
+      assert {0, ^set} = SortedSet.index_remove(set, {:b, 2})
+      assert SortedSet.to_list(set) == [{:c, 2}, {:a, 1}]
+    end
+
+    test "leaves elements that are not tuples in term order" do
+      set = SortedSet.from_enumerable([{1, 2}, 5, {2, 1}, 3], 500, comparator: [{1, :asc}])
+
+      assert SortedSet.to_list(set) == [3, 5, {2, 1}, {1, 2}]
+    end
+
+    test "rejects invalid specs" do
+      assert_raise ArgumentError, fn -> SortedSet.new(500, 5, comparator: []) end
+      assert_raise ArgumentError, fn -> SortedSet.new(500, 5, comparator: [{0, :up}]) end
+    end
+
+    test "keys reach into nested tuples and transform their element" do
+      entries = [{1, {"10", "bob"}}, {2, {"9", "Alice"}}, {3, {"9", "carol"}}]
+
+      by_number =
+        SortedSet.from_enumerable(entries, 500, comparator: [{[1, 0], :asc, [cast: :integer]}])
+
+      by_name =
+        SortedSet.from_enumerable(entries, 500, comparator: [{[1, 1], :asc, [:fold_case]}])
+
+      assert SortedSet.to_list(by_number) == [
+               {2, {"9", "Alice"}},
+               {3, {"9", "carol"}},
+               {1, {"10", "bob"}}
+             ]
+
+      assert SortedSet.to_list(by_name) == [
+               {2, {"9", "Alice"}},
+               {1, {"10", "bob"}},
+               {3, {"9", "carol"}}
+             ]
+    end
+
+    test "rejects invalid ordering programs" do
+      assert_raise ArgumentError, fn -> SortedSet.new(500, 5, comparator: [{[], :asc}]) end
+
+      assert_raise ArgumentError, fn ->
+        SortedSet.new(500, 5, comparator: [{0, :asc, [cast: :float]}])
+      end
+
+      assert_raise ArgumentError, fn ->
+        SortedSet.new(500, 5, comparator: [{0, :asc, [:upcase]}])
+      end
+    end
+
+    test "rejects sets with a different comparator in concat" do
+      a = SortedSet.from_enumerable([{1, 1}], 500, comparator: [{0, :desc}])
+      b = SortedSet.from_enumerable([{2, 2}], 500)
+
+      assert SortedSet.concat(a, b) == {:error, :comparator_mismatch}
+    end
+  end
+
+  describe "tuple_order option" do
+    test "erlang compares the sizes of tuples first" do
+      set = SortedSet.from_enumerable([{2}, {1, 1}, {1, 2, 0}], 500, tuple_order: :erlang)
+
+      assert SortedSet.to_list(set) == [{2}, {1, 1}, {1, 2, 0}]
+    end
+
+    test "lexicographic compares tuples of any size element by element" do
+      terms = [{2}, {1, 3}, {1, 2, 0}, {1, 2}]
+      set = SortedSet.from_enumerable(terms, 500, tuple_order: :lexicographic)
+
+      assert SortedSet.to_list(set) == [{1, 2}, {1, 2, 0}, {1, 3}, {2}]
+      assert SortedSet.find_index(set, {1, 2, 0}) == 1
+    end
+
+    test "is the fallback of the comparator" do
+      set = SortedSet.new(500, 5, comparator: [{0, :desc}], tuple_order: :lexicographic)
+
+      SortedSet.add(set, {1, 9})
+      SortedSet.add(set, {1, 1, 1})
+      SortedSet.add(set, {2})
+
+      assert SortedSet.to_list(set) == [{2}, {1, 1, 1}, {1, 9}]
+    end
+
+    test "rejects sets with a different tuple order in concat" do
+      a = SortedSet.from_enumerable([{1}], 500, tuple_order: :lexicographic)
+      b = SortedSet.from_enumerable([{2}], 500)
+
+      assert SortedSet.concat(a, b) == {:error, :comparator_mismatch}
+      assert_raise ArgumentError, fn -> SortedSet.new(500, 5, tuple_order: :reverse) end
+    end
+  end
+end
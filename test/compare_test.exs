@@ -0,0 +1,39 @@
+defmodule Discord.SortedSet.Compare.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "compare/2" do
+    test "compares the elements like sorted lists" do
+      a = SortedSet.from_proper_enumerable([1, 2, 3], 2)
+      prefix = SortedSet.from_proper_enumerable([1, 2])
+      larger = SortedSet.from_proper_enumerable([1, 4])
+
+      assert SortedSet.compare(a, prefix) == :gt
+      assert SortedSet.compare(prefix, a) == :lt
+      assert SortedSet.compare(larger, a) == :gt
+      assert SortedSet.compare(a, SortedSet.from_proper_enumerable([1, 2, 3], 50)) == :eq
+      assert SortedSet.compare(a, a) == :eq
+    end
+
+    test "an empty set is less than any other set" do
+      assert SortedSet.compare(SortedSet.new(), SortedSet.from_proper_enumerable([:a])) == :lt
+      assert SortedSet.compare(SortedSet.new(), SortedSet.new()) == :eq
+    end
+
+    test "sets can be sorted" do
+      sets = Enum.map([[3], [1, 5], [1], [2, 2]], &SortedSet.from_enumerable/1)
+
+      sorted = Enum.sort(sets, &(SortedSet.compare(&1, &2) != :gt))
+
+      assert Enum.map(sorted, &SortedSet.to_list/1) == [[1], [1, 5], [2], [3]]
+    end
+
+    test "rejects sets with different collations" do
+      a = SortedSet.from_enumerable(["a"], 500, collation: "de")
+      b = SortedSet.from_enumerable(["a"], 500)
+
+      assert SortedSet.compare(a, b) == {:error, :collation_mismatch}
+    end
+  end
+end
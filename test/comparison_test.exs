@@ -0,0 +1,26 @@
+defmodule Discord.SortedSet.Comparison.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet.Comparison
+
+  describe "run/1" do
+    test "times every workload for every structure" do
+      assert %{size: 200, runs: 1, results: results} = Comparison.run(size: 200, runs: 1)
+
+      assert Map.keys(results) == [:build, :member, :remove, :slice]
+
+      for {_, timings} <- results do
+        assert Map.keys(timings) == [:ets, :gb_sets, :ordsets, :sorted_set]
+        assert Enum.all?(Map.values(timings), &(is_integer(&1) and &1 >= 0))
+      end
+    end
+
+    test "runs only the requested structures and workloads" do
+      assert %{results: %{slice: timings} = results} =
+               Comparison.run(size: 10, runs: 1, structures: [:ets], workloads: [:slice])
+
+      assert map_size(results) == 1
+      assert Map.keys(timings) == [:ets]
+    end
+  end
+end
@@ -0,0 +1,80 @@
+defmodule Discord.SortedSet.Concat.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "concat/2" do
+    test "moves the contents of the second set onto the end of the first" do
+      a = SortedSet.from_enumerable(1..10, 3)
+      b = SortedSet.from_enumerable(11..20, 3)
+
+      assert ^a = SortedSet.concat(a, b)
+
+      assert SortedSet.to_list(a) == Enum.to_list(1..20)
+      assert SortedSet.size(a) == 20
+      assert SortedSet.find_index(a, 15) == 14
+
+      assert SortedSet.to_list(b) == []
+      assert SortedSet.size(b) == 0
+    end
+
+    test "concatenated set remains sorted on further adds" do
+      a = SortedSet.from_enumerable([1, 3, 5])
+      b = SortedSet.from_enumerable([7, 9])
+
+      a = SortedSet.concat(a, b)
+      a = SortedSet.add(a, 6)
+
+      assert SortedSet.to_list(a) == [1, 3, 5, 6, 7, 9]
+    end
+
+    test "concatenating onto an empty set" do
+      a = SortedSet.new()
+      b = SortedSet.from_enumerable([1, 2, 3])
+
+      assert ^a = SortedSet.concat(a, b)
+      assert SortedSet.to_list(a) == [1, 2, 3]
+    end
+
+    test "concatenating an empty set" do
+      a = SortedSet.from_enumerable([1, 2, 3])
+
+      assert ^a = SortedSet.concat(a, SortedSet.new())
+      assert SortedSet.to_list(a) == [1, 2, 3]
+    end
+
+    test "overlapping sets are rejected" do
+      a = SortedSet.from_enumerable([1, 5, 10])
+      b = SortedSet.from_enumerable([10, 11])
+
+      assert {:error, :overlapping} = SortedSet.concat(a, b)
+
+      assert SortedSet.to_list(a) == [1, 5, 10]
+      assert SortedSet.to_list(b) == [10, 11]
+    end
+
+    test "a set can not be concatenated with itself" do
+      a = SortedSet.from_enumerable([1, 2, 3])
+
+      assert {:error, :overlapping} = SortedSet.concat(a, a)
+    end
+
+    test "concat respects max_elements of the first set" do
+      a = SortedSet.from_enumerable([1, 2], SortedSet.default_bucket_size(), max_elements: 3)
+      b = SortedSet.from_enumerable([3, 4])
+
+      assert {:error, :set_full} = SortedSet.concat(a, b)
+
+      assert SortedSet.to_list(a) == [1, 2]
+      assert SortedSet.to_list(b) == [3, 4]
+    end
+
+    test "frozen sets can not be concatenated" do
+      a = SortedSet.from_enumerable([1, 2])
+      b = SortedSet.freeze(SortedSet.from_enumerable([3, 4]))
+
+      assert {:error, :frozen} = SortedSet.concat(a, b)
+      assert {:error, :frozen} = SortedSet.concat(b, SortedSet.new())
+    end
+  end
+end
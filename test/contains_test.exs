@@ -0,0 +1,58 @@
+defmodule Discord.SortedSet.Contains.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "contains?/2" do
+    test "agrees with find_index" do
+      set = SortedSet.from_proper_enumerable(Enum.take_every(0..199, 2), 8)
+
+      Enum.each(0..50, &SortedSet.remove(set, &1 * 4))
+
+      for item <- -1..201 do
+        assert SortedSet.contains?(set, item) == (SortedSet.find_index(set, item) != nil)
+      end
+    end
+
+    test "checks terms of every supported type" do
+      set = SortedSet.from_enumerable([1, :a, {:b, "c"}, [1, 2], "d"])
+
+      assert SortedSet.contains?(set, {:b, "c"})
+      assert SortedSet.contains?(set, [1, 2])
+      refute SortedSet.contains?(set, {:b, "d"})
+      refute SortedSet.contains?(SortedSet.new(), 1)
+    end
+
+    test "rejects unsupported types" do
+      set = SortedSet.from_enumerable([1, 2, 3])
+
+      assert SortedSet.contains?(set, self()) == {:error, :unsupported_type}
+    end
+  end
+
+  describe "contains_many/2" do
+    test "answers in the order the terms were given" do
+      set = SortedSet.from_proper_enumerable(Enum.take_every(0..89, 3), 4)
+
+      assert SortedSet.contains_many(set, [87, 3, -1, 30, 88, 3, :a, 0]) ==
+               [true, true, false, true, false, true, false, true]
+    end
+
+    test "agrees with contains?" do
+      set = SortedSet.from_enumerable(Enum.map(1..300, &rem(&1 * 7, 500)), 9)
+      terms = Enum.shuffle(-10..510)
+
+      assert SortedSet.contains_many(set, terms) == Enum.map(terms, &SortedSet.contains?(set, &1))
+    end
+
+    test "handles an empty list of terms" do
+      assert SortedSet.contains_many(SortedSet.new(), []) == []
+    end
+
+    test "rejects unsupported types" do
+      set = SortedSet.from_enumerable([1, 2, 3])
+
+      assert SortedSet.contains_many(set, [1, self()]) == {:error, :unsupported_type}
+    end
+  end
+end
@@ -0,0 +1,32 @@
+defmodule Discord.SortedSet.Diff.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "diff/2" do
+    test "lists the added and removed elements in order" do
+      old = SortedSet.from_proper_enumerable(Enum.take_every(0..99, 2), 7)
+      new = SortedSet.from_proper_enumerable(Enum.take_every(0..99, 3), 5)
+
+      {added, removed} = SortedSet.diff(old, new)
+
+      assert added == Enum.reject(Enum.take_every(0..99, 3), &(rem(&1, 2) == 0))
+      assert removed == Enum.reject(Enum.take_every(0..99, 2), &(rem(&1, 3) == 0))
+      assert SortedSet.size(old) == 50
+    end
+
+    test "a set has no changes against itself" do
+      set = SortedSet.from_proper_enumerable([1, :a, "b"])
+
+      assert SortedSet.diff(set, set) == {[], []}
+    end
+
+    test "an empty set is all additions" do
+      old = SortedSet.new()
+      new = SortedSet.from_proper_enumerable([1, 2, 3])
+
+      assert SortedSet.diff(old, new) == {[1, 2, 3], []}
+      assert SortedSet.diff(new, old) == {[], [1, 2, 3]}
+    end
+  end
+end
@@ -0,0 +1,32 @@
+defmodule Discord.SortedSet.Difference.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "difference/2" do
+    test "drops the elements present in the second set" do
+      a = SortedSet.from_proper_enumerable(Enum.to_list(0..99), 10)
+      b = SortedSet.from_proper_enumerable(Enum.take_every(0..99, 3), 10)
+
+      difference = SortedSet.difference(a, b)
+
+      assert SortedSet.to_list(difference) == Enum.to_list(0..99) -- Enum.take_every(0..99, 3)
+      assert SortedSet.size(a) == 100
+      assert SortedSet.size(b) == 34
+    end
+
+    test "keeps every element of a set disjoint from the second set" do
+      a = SortedSet.from_proper_enumerable([1, 2, 3])
+      b = SortedSet.from_proper_enumerable([:a, :b])
+
+      assert SortedSet.to_list(SortedSet.difference(a, b)) == [1, 2, 3]
+      assert SortedSet.to_list(SortedSet.difference(b, a)) == [:a, :b]
+    end
+
+    test "the difference of a set with itself is empty" do
+      set = SortedSet.from_proper_enumerable(["a", "b"])
+
+      assert SortedSet.size(SortedSet.difference(set, set)) == 0
+    end
+  end
+end
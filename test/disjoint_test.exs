@@ -0,0 +1,35 @@
+defmodule Discord.SortedSet.Disjoint.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "disjoint?/2" do
+    test "sets without common elements are disjoint" do
+      a = SortedSet.from_proper_enumerable(Enum.to_list(0..999), 10)
+      b = SortedSet.from_proper_enumerable(Enum.to_list(1_000..1_999), 10)
+
+      assert SortedSet.disjoint?(a, b)
+      assert SortedSet.disjoint?(b, a)
+      assert SortedSet.disjoint?(a, SortedSet.new())
+    end
+
+    test "a single common element makes sets overlap" do
+      a = SortedSet.from_proper_enumerable(Enum.take_every(0..999, 2), 10)
+      b = SortedSet.from_proper_enumerable(Enum.take_every(1..999, 2), 10)
+
+      assert SortedSet.disjoint?(a, b)
+
+      SortedSet.add(b, 500)
+
+      refute SortedSet.disjoint?(a, b)
+      refute SortedSet.disjoint?(b, a)
+    end
+
+    test "a non-empty set is not disjoint from itself" do
+      set = SortedSet.from_proper_enumerable([:a])
+
+      refute SortedSet.disjoint?(set, set)
+      assert SortedSet.disjoint?(SortedSet.new(), SortedSet.new())
+    end
+  end
+end
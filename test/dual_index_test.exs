@@ -0,0 +1,66 @@
+defmodule Discord.SortedSet.DualIndex.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet.DualIndex
+
+  defp members do
+    DualIndex.new([comparator: [{0, :asc}]], comparator: [{1, :asc}])
+    |> DualIndex.add({"bob", 3})
+    |> DualIndex.add({"alice", 7})
+    |> DualIndex.add({"carol", 1})
+  end
+
+  describe "add/2 and remove/2" do
+    test "mutations apply to both orders" do
+      members = members()
+
+      assert DualIndex.to_list(members, :primary) == [{"alice", 7}, {"bob", 3}, {"carol", 1}]
+      assert DualIndex.to_list(members, :secondary) == [{"carol", 1}, {"bob", 3}, {"alice", 7}]
+
+      members = DualIndex.remove(members, {"bob", 3})
+
+      assert DualIndex.to_list(members, :primary) == [{"alice", 7}, {"carol", 1}]
+      assert DualIndex.to_list(members, :secondary) == [{"carol", 1}, {"alice", 7}]
+      assert DualIndex.size(members) == 2
+    end
+
+    test "index variants report the index in both orders" do
+      members = members()
+
+      assert {{2, 3}, ^members} = DualIndex.index_add(members, {"bob", 9})
+      assert {nil, ^members} = DualIndex.index_add(members, {"bob", 9})
+      assert {{0, 2}, ^members} = DualIndex.index_remove(members, {"alice", 7})
+      assert {nil, ^members} = DualIndex.index_remove(members, {"alice", 7})
+    end
+
+    test "max_elements of the primary options limits both orders" do
+      set = DualIndex.new([max_elements: 1], [])
+
+      assert ^set = DualIndex.add(set, 1)
+      assert DualIndex.add(set, 2) == {:error, :set_full}
+      assert DualIndex.to_list(set, :secondary) == [1]
+    end
+
+    test "single_owner is refused" do
+      assert_raise ArgumentError, fn -> DualIndex.new([single_owner: true], []) end
+    end
+  end
+
+  describe "reads" do
+    test "name the order they use" do
+      members = members()
+
+      assert DualIndex.at(members, :primary, 0) == {"alice", 7}
+      assert DualIndex.at(members, :secondary, 0) == {"carol", 1}
+      assert DualIndex.at(members, :secondary, 3, :missing) == :missing
+      assert DualIndex.slice(members, :secondary, 1, 5) == [{"bob", 3}, {"alice", 7}]
+      assert DualIndex.find_index(members, :primary, {"carol", 1}) == 2
+      assert DualIndex.find_index(members, :secondary, {"carol", 1}) == 0
+      assert DualIndex.find_index(members, :secondary, {"dave", 2}) == nil
+    end
+
+    test "unknown orders are refused" do
+      assert_raise ArgumentError, fn -> DualIndex.to_list(members(), :tertiary) end
+    end
+  end
+end
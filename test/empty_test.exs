@@ -0,0 +1,35 @@
+defmodule Discord.SortedSet.Empty.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+  alias Discord.SortedSet.NifBridge
+
+  setup do
+    {:ok, set} = NifBridge.empty(10, 5, [])
+    {:ok, set: set}
+  end
+
+  describe "a set without buckets" do
+    test "supports reads", ctx do
+      assert SortedSet.size(ctx.set) == 0
+      assert SortedSet.to_list(ctx.set) == []
+      assert SortedSet.at(ctx.set, 0) == nil
+      assert SortedSet.slice(ctx.set, 0, 10) == []
+      assert SortedSet.multi_slice(ctx.set, [{0, 1}, {3, 3}]) == [[], []]
+      assert SortedSet.find_index(ctx.set, 1) == nil
+    end
+
+    test "supports mutations", ctx do
+      assert SortedSet.remove(ctx.set, 1) == ctx.set
+      assert SortedSet.index_add(ctx.set, 2) == {0, ctx.set}
+      assert SortedSet.index_add(ctx.set, 1) == {0, ctx.set}
+      assert SortedSet.index_remove(ctx.set, 2) == {1, ctx.set}
+      assert SortedSet.to_list(ctx.set) == [1]
+    end
+
+    test "reports the size of buckets that are too large", ctx do
+      assert NifBridge.append_bucket(ctx.set, Enum.to_list(1..8)) ==
+               {:error, {:max_bucket_size_exceeded, 8, 5}}
+    end
+  end
+end
@@ -0,0 +1,34 @@
+defmodule Discord.SortedSet.Equal.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "equal?/2" do
+    test "sets built differently with the same elements are equal" do
+      elements = Enum.to_list(1..500)
+      a = SortedSet.from_proper_enumerable(elements, 10)
+      b = SortedSet.from_enumerable(Enum.reverse(elements), 50, backend: :btree)
+
+      assert SortedSet.equal?(a, b)
+      assert SortedSet.equal?(b, a)
+      assert SortedSet.equal?(a, a)
+    end
+
+    test "sets differing in one element are not equal" do
+      a = SortedSet.from_proper_enumerable([1, 2, 3])
+      b = SortedSet.from_proper_enumerable([1, 2, 4])
+
+      refute SortedSet.equal?(a, b)
+
+      SortedSet.remove(b, 4)
+      refute SortedSet.equal?(a, b)
+
+      SortedSet.add(b, 3)
+      assert SortedSet.equal?(a, b)
+    end
+
+    test "empty sets are equal" do
+      assert SortedSet.equal?(SortedSet.new(), SortedSet.new())
+    end
+  end
+end
@@ -0,0 +1,38 @@
+defmodule Discord.SortedSet.EstimateUnionSize.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  defp sketched(items) do
+    Enum.reduce(items, SortedSet.new(0, 500, cardinality_sketch: true), &SortedSet.add(&2, &1))
+  end
+
+  defp within?(estimate, actual), do: abs(estimate - actual) <= actual * 0.05
+
+  describe "estimate_union_size/1" do
+    test "estimates the size of the union" do
+      a = sketched(1..20_000)
+      b = sketched(10_001..30_000)
+      c = sketched(25_001..40_000)
+
+      assert within?(SortedSet.estimate_union_size([a]), 20_000)
+      assert within?(SortedSet.estimate_union_size([a, b]), 30_000)
+      assert within?(SortedSet.estimate_union_size([a, b, c, a]), 40_000)
+      assert SortedSet.estimate_union_size([]) == 0
+    end
+
+    test "removed elements stop counting" do
+      a = sketched(1..1_000)
+      Enum.each(1..1_000, &SortedSet.remove(a, &1))
+
+      assert SortedSet.estimate_union_size([a]) == 0
+    end
+
+    test "every set needs a sketch" do
+      a = sketched([1, 2, 3])
+
+      assert SortedSet.estimate_union_size([a, SortedSet.new()]) == {:error, :sketch_disabled}
+      assert SortedSet.estimate_union_size([a, :nope]) == {:error, :bad_reference}
+    end
+  end
+end
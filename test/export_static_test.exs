@@ -0,0 +1,62 @@
+defmodule Discord.SortedSet.ExportStatic.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+  alias Discord.SortedSet.Static
+
+  describe "export_static/1" do
+    test "copies the items into a term that is read without the set" do
+      items = Enum.map(0..199, &(&1 * 2))
+      static = items |> SortedSet.from_proper_enumerable() |> SortedSet.export_static()
+
+      assert Static.size(static) == 200
+      assert Static.to_list(static) == items
+      assert Static.at(static, 0) == 0
+      assert Static.at(static, 130) == 260
+      assert Static.at(static, 200, :missing) == :missing
+      assert Static.slice(static, 62, 4) == [124, 126, 128, 130]
+      assert Static.slice(static, 198, 10) == [396, 398]
+      assert Static.slice(static, 300, 10) == []
+    end
+
+    test "finds items by value" do
+      static =
+        0..199
+        |> Enum.map(&(&1 * 2))
+        |> SortedSet.from_proper_enumerable()
+        |> SortedSet.export_static()
+
+      assert Static.find_index(static, 0) == 0
+      assert Static.find_index(static, 128) == 64
+      assert Static.find_index(static, 398) == 199
+      assert Static.find_index(static, 129) == nil
+      assert Static.find_index(static, -1) == nil
+      assert Static.find_index(static, :atom) == nil
+    end
+
+    test "survives a round trip through persistent_term" do
+      set = SortedSet.from_enumerable([{:b, 1}, "c", :a])
+      :persistent_term.put({__MODULE__, :set}, SortedSet.export_static(set))
+
+      static = :persistent_term.get({__MODULE__, :set})
+      :persistent_term.erase({__MODULE__, :set})
+
+      assert Static.to_list(static) == SortedSet.to_list(set)
+      assert Static.find_index(static, "c") == 2
+    end
+
+    test "empty sets export an empty copy" do
+      static = SortedSet.export_static(SortedSet.new())
+
+      assert Static.size(static) == 0
+      assert Static.to_list(static) == []
+      assert Static.find_index(static, 1) == nil
+    end
+
+    test "sets with a custom order are refused" do
+      set = SortedSet.new(500, 5, comparator: [{1, :desc}])
+
+      assert SortedSet.export_static(set) == {:error, :custom_order}
+    end
+  end
+end
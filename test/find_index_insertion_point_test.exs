@@ -0,0 +1,36 @@
+defmodule Discord.SortedSet.FindIndexInsertionPoint.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "find_index/3 with insertion_point: true" do
+    test "returns the index of a present term" do
+      set = SortedSet.from_proper_enumerable(Enum.take_every(0..58, 2), 4)
+
+      assert SortedSet.find_index(set, 20, insertion_point: true) == 10
+    end
+
+    test "returns the insertion point of an absent term" do
+      set = SortedSet.from_proper_enumerable(Enum.take_every(0..58, 2), 4)
+
+      assert SortedSet.find_index(set, 21, insertion_point: true) == {:not_found, 11}
+      assert SortedSet.find_index(set, -1, insertion_point: true) == {:not_found, 0}
+      assert SortedSet.find_index(set, :atom, insertion_point: true) == {:not_found, 30}
+      assert SortedSet.find_index(SortedSet.new(), 1, insertion_point: true) == {:not_found, 0}
+    end
+
+    test "the insertion point is where add puts the term" do
+      set = SortedSet.from_proper_enumerable(Enum.take_every(0..58, 2), 4)
+
+      {:not_found, index} = SortedSet.find_index(set, 33, insertion_point: true)
+      {^index, _} = SortedSet.index_add(set, 33)
+    end
+
+    test "returns nil without the option" do
+      set = SortedSet.from_proper_enumerable([1, 3])
+
+      assert SortedSet.find_index(set, 2) == nil
+      assert SortedSet.find_index(set, 2, insertion_point: false) == nil
+    end
+  end
+end
@@ -0,0 +1,39 @@
+defmodule Discord.SortedSet.FirstLast.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "first/2 and last/2" do
+    test "return the least and greatest elements" do
+      set = SortedSet.from_enumerable([5, :b, 1, "c", {1, 2}], 2)
+
+      assert SortedSet.first(set) == 1
+      assert SortedSet.last(set) == "c"
+    end
+
+    test "follow adds and removes" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(1..100), 7)
+
+      set
+      |> SortedSet.remove(1)
+      |> SortedSet.remove(100)
+      |> SortedSet.add(0)
+
+      assert SortedSet.first(set) == 0
+      assert SortedSet.last(set) == 99
+    end
+
+    test "return the default for an empty set" do
+      set = SortedSet.new()
+
+      assert SortedSet.first(set) == nil
+      assert SortedSet.last(set) == nil
+      assert SortedSet.first(set, :none) == :none
+      assert SortedSet.last(set, :none) == :none
+    end
+
+    test "reject a bad reference" do
+      assert SortedSet.first(make_ref()) == {:error, :bad_reference}
+    end
+  end
+end
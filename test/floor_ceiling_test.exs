@@ -0,0 +1,47 @@
+defmodule Discord.SortedSet.FloorCeiling.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "floor/3 and ceiling/3" do
+    test "return the element itself when it is in the set" do
+      set = SortedSet.from_proper_enumerable(Enum.take_every(0..99, 5), 3)
+
+      assert SortedSet.floor(set, 25) == 25
+      assert SortedSet.ceiling(set, 25) == 25
+    end
+
+    test "return the neighbors of an absent element" do
+      set = SortedSet.from_proper_enumerable(Enum.take_every(0..99, 5), 3)
+
+      assert SortedSet.floor(set, 27) == 25
+      assert SortedSet.ceiling(set, 27) == 30
+      assert SortedSet.floor(set, :atom) == 95
+      assert SortedSet.ceiling(set, -1) == 0
+    end
+
+    test "cross bucket boundaries after removals" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(1..30), 3)
+
+      Enum.each(10..20, &SortedSet.remove(set, &1))
+
+      assert SortedSet.floor(set, 15) == 9
+      assert SortedSet.ceiling(set, 15) == 21
+    end
+
+    test "return the default past the ends of the set" do
+      set = SortedSet.from_proper_enumerable([10, 20])
+
+      assert SortedSet.floor(set, 5) == nil
+      assert SortedSet.ceiling(set, 25) == nil
+      assert SortedSet.floor(set, 5, :none) == :none
+      assert SortedSet.ceiling(SortedSet.new(), 1, :none) == :none
+    end
+
+    test "reject unsupported types" do
+      set = SortedSet.from_proper_enumerable([1, 2])
+
+      assert SortedSet.floor(set, self()) == {:error, :unsupported_type}
+    end
+  end
+end
@@ -0,0 +1,81 @@
+defmodule Discord.SortedSet.Fold.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  defp members do
+    SortedSet.from_enumerable([
+      {"alice", :admin, 7},
+      {"bob", :member, 3},
+      {"carol", :admin, 12},
+      {"dave", :member, -2},
+      {"erin", :member, 5}
+    ])
+  end
+
+  describe "fold/2" do
+    test "computes the predefined folds" do
+      members = members()
+
+      assert SortedSet.fold(members, {:count, {:_, :admin, :_}}) == 2
+      assert SortedSet.fold(members, {:count, :_}) == 5
+      assert SortedSet.fold(members, {:sum, 2}) == 25
+      assert SortedSet.fold(members, {:min, 2}) == -2
+      assert SortedSet.fold(members, {:max, 0}) == "erin"
+    end
+
+    test "folds over many steps" do
+      set = SortedSet.from_proper_enumerable(Enum.map(1..5_000, &{&1, rem(&1, 3)}))
+
+      assert SortedSet.fold(set, {:sum, 0}) == Enum.sum(1..5_000)
+      assert SortedSet.fold(set, {:count, {:_, 0}}) == 1_666
+    end
+
+    test "empty sets" do
+      set = SortedSet.new()
+
+      assert SortedSet.fold(set, {:count, :_}) == 0
+      assert SortedSet.fold(set, {:sum, 0}) == 0
+      assert SortedSet.fold(set, {:max, 0}) == nil
+    end
+
+    test "refuses elements the fold does not apply to" do
+      members = SortedSet.add(members(), 1)
+
+      assert SortedSet.fold(members, {:sum, 2}) == {:error, :missing_element}
+      assert SortedSet.fold(members(), {:sum, 0}) == {:error, :not_integer}
+      assert SortedSet.fold(members(), {:max, 3}) == {:error, :missing_element}
+    end
+  end
+
+  describe "fold/4" do
+    test "continues from the cursor" do
+      members = members()
+
+      assert {:cont, cursor} = SortedSet.fold(members, {:sum, 2}, nil, 2)
+      assert {:cont, cursor} = SortedSet.fold(members, {:sum, 2}, cursor, 2)
+      assert SortedSet.fold(members, {:sum, 2}, cursor, 2) == {:ok, 25}
+    end
+
+    test "steps never visit an element twice" do
+      members = members()
+
+      assert {:cont, cursor} = SortedSet.fold(members, {:max, 2}, nil, 2)
+
+      members
+      |> SortedSet.remove({"alice", :admin, 7})
+      |> SortedSet.add({"aaron", :member, 100})
+
+      assert SortedSet.fold(members, {:max, 2}, cursor, 10) == {:ok, 12}
+    end
+
+    test "refuses invalid budgets and cursors of other folds" do
+      members = members()
+      {:cont, cursor} = SortedSet.fold(members, {:max, 2}, nil, 2)
+
+      assert_raise ArgumentError, fn -> SortedSet.fold(members, {:sum, 2}, nil, 0) end
+      assert_raise ArgumentError, fn -> SortedSet.fold(members, {:sum, 2}, cursor, 2) end
+      assert_raise ArgumentError, fn -> SortedSet.fold(members, {:median, 2}, nil, 2) end
+    end
+  end
+end
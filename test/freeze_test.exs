@@ -0,0 +1,64 @@
+defmodule Discord.SortedSet.Freeze.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "freeze/1" do
+    setup do
+      set =
+        SortedSet.new()
+        |> SortedSet.add(1)
+        |> SortedSet.add(2)
+        |> SortedSet.add(3)
+        |> SortedSet.freeze()
+
+      {:ok, set: set}
+    end
+
+    test "mutating operations are rejected", ctx do
+      assert {:error, :frozen} = SortedSet.add(ctx.set, 4)
+      assert {:error, :frozen} = SortedSet.index_add(ctx.set, 4)
+      assert {:error, :frozen} = SortedSet.remove(ctx.set, 1)
+      assert {:error, :frozen} = SortedSet.index_remove(ctx.set, 1)
+      assert {:error, :frozen} = SortedSet.put_meta(ctx.set, :meta)
+
+      assert SortedSet.to_list(ctx.set) == [1, 2, 3]
+    end
+
+    test "read operations are allowed", ctx do
+      assert SortedSet.size(ctx.set) == 3
+      assert SortedSet.at(ctx.set, 1) == 2
+      assert SortedSet.slice(ctx.set, 0, 2) == [1, 2]
+      assert SortedSet.find_index(ctx.set, 3) == 2
+      assert SortedSet.to_list(ctx.set) == [1, 2, 3]
+    end
+
+    test "freezing is idempotent", ctx do
+      set = ctx.set
+      assert ^set = SortedSet.freeze(set)
+      assert {:error, :frozen} = SortedSet.add(set, 4)
+    end
+  end
+
+  describe "thaw/1" do
+    test "thawed sets can be modified again" do
+      set =
+        SortedSet.new()
+        |> SortedSet.add(1)
+        |> SortedSet.freeze()
+
+      assert {:error, :frozen} = SortedSet.add(set, 2)
+
+      assert ^set = SortedSet.thaw(set)
+      assert ^set = SortedSet.add(set, 2)
+      assert SortedSet.to_list(set) == [1, 2]
+    end
+
+    test "thawing a set that is not frozen has no effect" do
+      set = SortedSet.new()
+
+      assert ^set = SortedSet.thaw(set)
+      assert ^set = SortedSet.add(set, 1)
+    end
+  end
+end
@@ -0,0 +1,41 @@
+defmodule Discord.SortedSet.FromEtfBinary.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "from_etf_binary/3" do
+    test "builds a set from an encoded list" do
+      terms = [5, :atom, "string", {1, 2}, [3, 4], -1, 1_099_511_627_776, 5, "string"]
+
+      set = SortedSet.from_etf_binary(:erlang.term_to_binary(terms))
+
+      assert SortedSet.to_list(set) == terms |> Enum.sort() |> Enum.uniq()
+    end
+
+    test "matches from_enumerable for a large payload" do
+      terms = Enum.map(1..10_000, &rem(&1 * 7919, 5_000))
+
+      set = SortedSet.from_etf_binary(:erlang.term_to_binary(terms), 100)
+
+      assert SortedSet.to_list(set) == SortedSet.to_list(SortedSet.from_enumerable(terms, 100))
+    end
+
+    test "accepts options" do
+      binary = :erlang.term_to_binary([1, 2, 3])
+
+      assert SortedSet.from_etf_binary(binary, 500, max_elements: 2) == {:error, :set_full}
+    end
+
+    test "rejects invalid binaries" do
+      assert SortedSet.from_etf_binary(<<1, 2, 3>>) == {:error, :invalid_etf}
+    end
+
+    test "rejects unsupported terms" do
+      assert SortedSet.from_etf_binary(:erlang.term_to_binary([1.5])) ==
+               {:error, :unsupported_type}
+
+      assert SortedSet.from_etf_binary(:erlang.term_to_binary(:not_a_list)) ==
+               {:error, :unsupported_type}
+    end
+  end
+end
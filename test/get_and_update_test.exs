@@ -0,0 +1,46 @@
+defmodule Discord.SortedSet.GetAndUpdate.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  test "replaces the item with the result of the function" do
+    set = SortedSet.from_enumerable([{1, :a}, {2, :b}, {3, :c}])
+
+    assert {{1, :a}, ^set} =
+             SortedSet.get_and_update(set, {1, :a}, fn current -> {current, {4, :a}} end)
+
+    assert SortedSet.to_list(set) == [{2, :b}, {3, :c}, {4, :a}]
+  end
+
+  test "passes nil for missing items and adds the replacement" do
+    set = SortedSet.from_enumerable([1, 3])
+
+    assert {nil, ^set} = SortedSet.get_and_update(set, 2, fn nil -> {nil, 2} end)
+    assert SortedSet.to_list(set) == [1, 2, 3]
+  end
+
+  test ":pop removes the item" do
+    set = SortedSet.from_enumerable([1, 2, 3])
+
+    assert {2, ^set} = SortedSet.get_and_update(set, 2, fn _ -> :pop end)
+    assert {nil, ^set} = SortedSet.get_and_update(set, 5, fn _ -> :pop end)
+    assert SortedSet.to_list(set) == [1, 3]
+  end
+
+  test "subscribers see a removal and an addition" do
+    set = SortedSet.from_enumerable([1, 2])
+    SortedSet.subscribe(set)
+
+    SortedSet.get_and_update(set, 1, fn current -> {current, 5} end)
+
+    assert_receive {:sorted_set_event, ^set, :removed, 1, 0, _}
+    assert_receive {:sorted_set_event, ^set, :added, 5, 1, _}
+  end
+
+  test "frozen sets are left alone" do
+    set = SortedSet.from_enumerable([1])
+    SortedSet.freeze(set)
+
+    assert SortedSet.get_and_update(set, 1, fn _ -> :pop end) == {:error, :frozen}
+  end
+end
@@ -0,0 +1,34 @@
+defmodule Discord.SortedSet.GroupCounts.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "group_counts/2" do
+    test "counts the tuples per value of an element" do
+      members = [
+        {:admin, "ann"},
+        {:member, "bob"},
+        {:admin, "cid"},
+        {:member, "dee"},
+        {:member, "eve"},
+        {:guest, "fay", :invited}
+      ]
+
+      set = SortedSet.from_enumerable([:not_a_tuple, {} | members])
+
+      assert SortedSet.group_counts(set, 0) == %{admin: 2, member: 3, guest: 1}
+      assert SortedSet.group_counts(set, 2) == %{invited: 1}
+      assert SortedSet.group_counts(set, 1) == Enum.frequencies_by(members, &elem(&1, 1))
+    end
+
+    test "returns the values as they were given" do
+      set = SortedSet.from_enumerable([{"b", 1}, {"a", 2}, {"b", 3}], 5, collation: "en")
+
+      assert SortedSet.group_counts(set, 0) == %{"a" => 1, "b" => 2}
+    end
+
+    test "empty sets count nothing" do
+      assert SortedSet.group_counts(SortedSet.new(), 0) == %{}
+    end
+  end
+end
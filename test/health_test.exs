@@ -0,0 +1,33 @@
+defmodule Discord.SortedSet.Health.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "health/1" do
+    test "summarizes the set" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(1..100), 10)
+
+      health = SortedSet.health(set)
+
+      assert health.size == 100
+      assert health.buckets == 12
+      assert health.min == 1
+      assert health.max == 100
+      assert health.frozen == false
+      assert health.memory > 0
+
+      SortedSet.add(set, 0)
+      SortedSet.freeze(set)
+
+      after_add = SortedSet.health(set)
+
+      assert after_add.min == 0
+      assert after_add.version > health.version
+      assert after_add.frozen == true
+    end
+
+    test "reports nil bounds for an empty set" do
+      assert %{size: 0, min: nil, max: nil} = SortedSet.health(SortedSet.new())
+    end
+  end
+end
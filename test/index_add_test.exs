@@ -5,6 +5,28 @@ defmodule Discord.SortedSet.IndexAdd.Test do
   alias Discord.SortedSet
   alias Discord.SortedSet.Test.Support.Generator
 
+  describe "options" do
+    test "index: :inserted returns the index the item ended up at" do
+      set = SortedSet.from_enumerable([1, 3])
+
+      assert {1, ^set} = SortedSet.index_add(set, 2, index: :inserted)
+      assert {nil, ^set} = SortedSet.index_add(set, 2, index: :inserted)
+    end
+
+    test "index: :displaced returns the index the displaced item was pushed to" do
+      set = SortedSet.from_enumerable([1, 3])
+
+      assert {2, ^set} = SortedSet.index_add(set, 2, index: :displaced)
+      assert {nil, ^set} = SortedSet.index_add(set, 4, index: :displaced)
+      assert {1, ^set} = SortedSet.index_add(set, 0, index: :displaced, hint: 0)
+      assert SortedSet.to_list(set) == [0, 1, 2, 3, 4]
+    end
+
+    test "invalid options are rejected" do
+      assert_raise ArgumentError, fn -> SortedSet.index_add(SortedSet.new(), 1, index: :after) end
+    end
+  end
+
   describe "set behavior" do
     test "single item" do
       assert set = SortedSet.new()
@@ -0,0 +1,44 @@
+defmodule Discord.SortedSet.Infinity.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+  alias Discord.SortedSet.RangeView
+
+  describe "sentinels" do
+    test "sort before and after every other term" do
+      set = SortedSet.from_enumerable([:infinity, "z", {1}, :neg_infinity, 5, :atom])
+
+      assert SortedSet.to_list(set) == [:neg_infinity, 5, :atom, {1}, "z", :infinity]
+      assert SortedSet.find_index(set, :infinity) == 5
+      assert {nil, ^set} = SortedSet.index_add(set, :neg_infinity)
+      assert {5, ^set} = SortedSet.index_remove(set, :infinity)
+    end
+
+    test "sort the same when nested" do
+      set =
+        SortedSet.new()
+        |> SortedSet.add({:score, :infinity})
+        |> SortedSet.add({:score, "high"})
+        |> SortedSet.add({:score, :neg_infinity})
+
+      assert SortedSet.to_list(set) == [
+               {:score, :neg_infinity},
+               {:score, "high"},
+               {:score, :infinity}
+             ]
+    end
+
+    test "make open-ended range bounds" do
+      set = SortedSet.from_enumerable([{:admin, 2}, {:admin, 1}, {:member, 3}, 40, 100, 250])
+
+      admins = RangeView.new(set, {:admin, :neg_infinity}, {:admin, :infinity})
+      assert RangeView.slice(admins, 0, 10) == [{:admin, 1}, {:admin, 2}]
+
+      large = RangeView.new(set, 100, :infinity)
+      assert RangeView.slice(large, 0, 10) == [100, 250, {:admin, 1}, {:admin, 2}, {:member, 3}]
+
+      small = RangeView.new(set, :neg_infinity, 100)
+      assert RangeView.slice(small, 0, 10) == [40, 100]
+    end
+  end
+end
@@ -0,0 +1,41 @@
+defmodule Discord.SortedSet.InterpolationSearch.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "interpolation_search option" do
+    test "finds evenly spread integers" do
+      ids = Enum.map(1..2_000, &(175_928_847_299_117_063 + &1 * 4_194_304))
+      set = SortedSet.from_enumerable(ids, 100, interpolation_search: true)
+
+      assert SortedSet.size(set) == 2_000
+      assert SortedSet.find_index(set, Enum.at(ids, 0)) == 0
+      assert SortedSet.find_index(set, Enum.at(ids, 1_234)) == 1_234
+      assert SortedSet.find_index(set, Enum.at(ids, 1_234) + 1) == nil
+      assert SortedSet.to_list(set) == ids
+    end
+
+    test "keeps unevenly spread integers and other elements in order" do
+      elements = [:atom, "string", {1}] ++ Enum.map(1..200, &(&1 * &1 * &1))
+
+      set =
+        Enum.reduce(Enum.shuffle(elements), SortedSet.new(0, 10, interpolation_search: true), fn
+          element, set -> SortedSet.add(set, element)
+        end)
+
+      assert SortedSet.to_list(set) == Enum.sort(elements)
+      assert SortedSet.find_index(set, 27) == 2
+      assert SortedSet.find_index(set, "string") == 202
+
+      set = SortedSet.remove(set, 27)
+      assert SortedSet.find_index(set, 27) == nil
+      assert SortedSet.find_index(set, 64) == 2
+    end
+
+    test "is only supported by the buckets backend" do
+      assert_raise ArgumentError, fn ->
+        SortedSet.new(0, 10, backend: :btree, interpolation_search: true)
+      end
+    end
+  end
+end
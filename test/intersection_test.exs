@@ -0,0 +1,33 @@
+defmodule Discord.SortedSet.Intersection.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "intersection/2" do
+    test "keeps the elements present in both sets" do
+      a = SortedSet.from_proper_enumerable(Enum.take_every(0..99, 2), 10)
+      b = SortedSet.from_proper_enumerable(Enum.take_every(0..99, 3), 10)
+
+      intersection = SortedSet.intersection(a, b)
+
+      assert SortedSet.to_list(intersection) == Enum.take_every(0..99, 6)
+      assert SortedSet.size(a) == 50
+      assert SortedSet.size(b) == 34
+    end
+
+    test "sets without common elements intersect to an empty set" do
+      a = SortedSet.from_proper_enumerable([1, 2, 3])
+      b = SortedSet.from_proper_enumerable([:a, :b])
+
+      assert SortedSet.size(SortedSet.intersection(a, b)) == 0
+    end
+
+    test "the intersection of a set with itself is a copy" do
+      set = SortedSet.from_proper_enumerable(["a", "b"])
+      intersection = SortedSet.intersection(set, set)
+
+      assert intersection != set
+      assert SortedSet.to_list(intersection) == ["a", "b"]
+    end
+  end
+end
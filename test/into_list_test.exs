@@ -0,0 +1,46 @@
+defmodule Discord.SortedSet.IntoList.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "into_list/1" do
+    test "returns every element in order" do
+      set = SortedSet.from_enumerable([5, 3, 1, 4, 2], 2)
+
+      assert SortedSet.into_list(set) == [1, 2, 3, 4, 5]
+    end
+
+    test "leaves the set empty" do
+      set = SortedSet.from_enumerable([1, 2, 3])
+
+      SortedSet.into_list(set)
+
+      assert SortedSet.size(set) == 0
+      assert SortedSet.to_list(set) == []
+    end
+
+    test "set remains usable after being drained" do
+      set = SortedSet.from_enumerable([1, 2, 3])
+
+      assert SortedSet.into_list(set) == [1, 2, 3]
+
+      set = SortedSet.add(set, 4)
+
+      assert SortedSet.to_list(set) == [4]
+    end
+
+    test "empty set drains to an empty list" do
+      assert SortedSet.into_list(SortedSet.new()) == []
+    end
+
+    test "frozen set can not be drained" do
+      set =
+        [1, 2, 3]
+        |> SortedSet.from_enumerable()
+        |> SortedSet.freeze()
+
+      assert {:error, :frozen} = SortedSet.into_list(set)
+      assert SortedSet.to_list(set) == [1, 2, 3]
+    end
+  end
+end
@@ -0,0 +1,47 @@
+defmodule Discord.SortedSet.Iodata.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "iodata: true" do
+    test "stores iodata as the binary it flattens to" do
+      set = SortedSet.new()
+
+      assert {0, ^set} = SortedSet.index_add(set, ["b", [?e | "e"], ?s], iodata: true)
+      assert {0, ^set} = SortedSet.index_add(set, [?a, "nt" | "s"], iodata: true)
+      assert {nil, ^set} = SortedSet.index_add(set, "bees", iodata: true)
+      assert {nil, ^set} = SortedSet.index_add(set, [["be"], "es"], iodata: true)
+
+      assert SortedSet.to_list(set) == ["ants", "bees"]
+      assert SortedSet.find_index(set, "bees") == 1
+    end
+
+    test "other items are stored as usual" do
+      set = SortedSet.new()
+
+      assert {0, ^set} = SortedSet.index_add(set, [:a, 1], iodata: true)
+      assert {0, ^set} = SortedSet.index_add(set, [300], iodata: true)
+      assert {0, ^set} = SortedSet.index_add(set, 5, iodata: true)
+      assert {nil, ^set} = SortedSet.index_add(set, [300], iodata: true)
+
+      assert SortedSet.to_list(set) == [5, [300], [:a, 1]]
+    end
+
+    test "subscribers are told about the flattened binary" do
+      set = SortedSet.subscribe(SortedSet.new())
+
+      SortedSet.index_add(set, ['ab' | "c"], iodata: true)
+
+      assert_receive {:sorted_set_event, ^set, :added, "abc", 0, _}
+    end
+
+    test "lists are kept as lists without the option" do
+      set = SortedSet.new()
+
+      SortedSet.index_add(set, [?a, ?b], iodata: false)
+
+      assert SortedSet.to_list(set) == [[?a, ?b]]
+      assert SortedSet.index_add(set, ["a" | "b"]) == {:error, :unsupported_type}
+    end
+  end
+end
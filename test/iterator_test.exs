@@ -0,0 +1,78 @@
+defmodule Discord.SortedSet.Iterator.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  setup do
+    {:ok, set: SortedSet.from_proper_enumerable(Enum.map(0..9, &(&1 * 2)))}
+  end
+
+  describe "iterator/2" do
+    test "walks the set in ascending order by default", %{set: set} do
+      iterator = SortedSet.iterator(set)
+
+      assert SortedSet.iterator_next(iterator, 4) == [0, 2, 4, 6]
+      assert SortedSet.iterator_next(iterator, 4) == [8, 10, 12, 14]
+      assert SortedSet.iterator_next(iterator, 4) == [16, 18]
+      assert SortedSet.iterator_next(iterator, 4) == []
+    end
+
+    test "scrolls up from a term", %{set: set} do
+      iterator = SortedSet.iterator(set, from: 7, direction: :desc)
+
+      assert SortedSet.iterator_next(iterator, 2) == [6, 4]
+      assert SortedSet.iterator_next(iterator) == [2]
+      assert SortedSet.iterator_next(iterator, 5) == [0]
+    end
+
+    test "starts at an index", %{set: set} do
+      iterator = SortedSet.iterator(set, at: 8)
+
+      assert SortedSet.iterator_next(iterator, 5) == [16, 18]
+      assert SortedSet.iterator_next(SortedSet.iterator(set, at: 50, direction: :desc)) == [18]
+    end
+
+    test "does not skip or repeat elements when the set changes", %{set: set} do
+      iterator = SortedSet.iterator(set)
+
+      assert SortedSet.iterator_next(iterator, 2) == [0, 2]
+
+      SortedSet.remove(set, 4)
+      SortedSet.add(set, -1)
+      SortedSet.add(set, 3)
+
+      assert SortedSet.iterator_next(iterator, 2) == [3, 6]
+    end
+
+    test "snapshots ignore changes made after they are created", %{set: set} do
+      iterator = SortedSet.iterator(set, snapshot: true, at: 1)
+
+      assert SortedSet.iterator_next(iterator, 2) == [2, 4]
+
+      SortedSet.remove(set, 6)
+      SortedSet.add(set, 5)
+      SortedSet.add(set, 100)
+
+      assert SortedSet.iterator_next(iterator, 100) == [6, 8, 10, 12, 14, 16, 18]
+      assert SortedSet.iterator_next(iterator, 100) == []
+    end
+
+    test "snapshots walk in either direction", %{set: set} do
+      iterator = SortedSet.iterator(set, snapshot: true, from: 7, direction: :desc)
+
+      SortedSet.add(set, 5)
+
+      assert SortedSet.iterator_next(iterator, 2) == [6, 4]
+      assert SortedSet.iterator_next(iterator, 5) == [2, 0]
+    end
+
+    test "rejects conflicting positions", %{set: set} do
+      assert_raise ArgumentError, fn -> SortedSet.iterator(set, from: 1, at: 1) end
+      assert_raise ArgumentError, fn -> SortedSet.iterator(set, direction: :sideways) end
+    end
+
+    test "rejects unsupported terms", %{set: set} do
+      assert SortedSet.iterator(set, from: self()) == {:error, :unsupported_type}
+    end
+  end
+end
@@ -0,0 +1,53 @@
+defmodule Discord.SortedSet.LastModified.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "last_modified/1" do
+    test "new set reports its creation time" do
+      before = System.system_time(:millisecond)
+      set = SortedSet.new()
+
+      assert %{system_time: system_time, idle_time: idle_time} = SortedSet.last_modified(set)
+      assert system_time >= before
+      assert idle_time >= 0
+    end
+
+    test "adding a new item updates the modification time" do
+      set = SortedSet.new()
+      Process.sleep(20)
+
+      assert %{idle_time: idle_before} = SortedSet.last_modified(set)
+      assert idle_before >= 20
+
+      before = System.system_time(:millisecond)
+      assert ^set = SortedSet.add(set, 1)
+
+      assert %{system_time: system_time, idle_time: idle_after} = SortedSet.last_modified(set)
+      assert system_time >= before
+      assert idle_after < idle_before
+    end
+
+    test "removing a present item updates the modification time" do
+      set = SortedSet.add(SortedSet.new(), 1)
+      Process.sleep(20)
+
+      assert ^set = SortedSet.remove(set, 1)
+      assert %{idle_time: idle_time} = SortedSet.last_modified(set)
+      assert idle_time < 20
+    end
+
+    test "no-op operations do not update the modification time" do
+      set = SortedSet.add(SortedSet.new(), 1)
+      assert %{system_time: system_time} = SortedSet.last_modified(set)
+      Process.sleep(20)
+
+      assert ^set = SortedSet.add(set, 1)
+      assert ^set = SortedSet.remove(set, 2)
+      assert SortedSet.to_list(set) == [1]
+
+      assert %{system_time: ^system_time, idle_time: idle_time} = SortedSet.last_modified(set)
+      assert idle_time >= 20
+    end
+  end
+end
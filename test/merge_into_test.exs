@@ -0,0 +1,43 @@
+defmodule Discord.SortedSet.MergeInto.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "merge_into/2" do
+    test "adds the elements of the source" do
+      destination = SortedSet.from_proper_enumerable(Enum.take_every(0..99, 2), 10)
+      source = SortedSet.from_proper_enumerable(Enum.take_every(0..99, 3), 10)
+
+      assert SortedSet.merge_into(destination, source) == destination
+
+      expected = Enum.sort(Enum.uniq(Enum.take_every(0..99, 2) ++ Enum.take_every(0..99, 3)))
+
+      assert SortedSet.to_list(destination) == expected
+      assert SortedSet.size(source) == 34
+    end
+
+    test "merging a set into itself changes nothing" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+
+      assert SortedSet.merge_into(set, set) == set
+      assert SortedSet.to_list(set) == [1, 2, 3]
+    end
+
+    test "respects the max_elements of the destination" do
+      destination = SortedSet.new(10, 10, max_elements: 4)
+      SortedSet.add(destination, 1)
+      source = SortedSet.from_proper_enumerable([1, 2, 3, 4, 5])
+
+      assert SortedSet.merge_into(destination, source) == {:error, :set_full}
+      assert SortedSet.to_list(destination) == [1]
+    end
+
+    test "refuses to change a frozen destination" do
+      destination = SortedSet.from_proper_enumerable([1])
+      SortedSet.freeze(destination)
+
+      assert SortedSet.merge_into(destination, SortedSet.from_proper_enumerable([2])) ==
+               {:error, :frozen}
+    end
+  end
+end
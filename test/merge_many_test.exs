@@ -0,0 +1,38 @@
+defmodule Discord.SortedSet.MergeMany.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "merge_many/1" do
+    test "combines every set into a new set" do
+      shards =
+        for shard <- 0..23 do
+          SortedSet.from_enumerable(Enum.map(0..99, &rem(&1 * (shard + 1), 500)), 10)
+        end
+
+      merged = SortedSet.merge_many(shards)
+
+      expected =
+        shards
+        |> Enum.flat_map(&SortedSet.to_list/1)
+        |> Enum.uniq()
+        |> Enum.sort()
+
+      assert SortedSet.to_list(merged) == expected
+      assert SortedSet.to_list(hd(shards)) == Enum.to_list(0..99)
+    end
+
+    test "a set listed twice is merged once" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+
+      assert SortedSet.to_list(SortedSet.merge_many([set, set])) == [1, 2, 3]
+    end
+
+    test "rejects sets with different collations" do
+      a = SortedSet.from_enumerable(["a"], 500, collation: "de")
+      b = SortedSet.from_enumerable(["b"], 500)
+
+      assert SortedSet.merge_many([a, b]) == {:error, :collation_mismatch}
+    end
+  end
+end
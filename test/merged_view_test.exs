@@ -0,0 +1,56 @@
+defmodule Discord.SortedSet.MergedView.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+  alias Discord.SortedSet.MergedView
+
+  setup do
+    us = SortedSet.from_enumerable([1, 4, 7, 10])
+    eu = SortedSet.from_enumerable([2, 4, 8])
+    asia = SortedSet.from_enumerable([3, 9])
+
+    {:ok, view: MergedView.new([us, eu, asia]), us: us}
+  end
+
+  describe "at/3 and slice/3" do
+    test "read the merged order", %{view: view} do
+      assert MergedView.slice(view, 0, 100) == [1, 2, 3, 4, 7, 8, 9, 10]
+      assert MergedView.slice(view, 2, 3) == [3, 4, 7]
+      assert MergedView.at(view, 4) == 7
+      assert MergedView.at(view, 8, :default) == :default
+    end
+
+    test "see mutations of the sets", %{view: view, us: us} do
+      SortedSet.add(us, 0)
+
+      assert MergedView.at(view, 0) == 0
+    end
+
+    test "an empty view holds nothing" do
+      assert MergedView.slice(MergedView.new([]), 0, 10) == []
+    end
+  end
+
+  describe "iterators" do
+    test "walk the view in pages", %{view: view} do
+      iterator = MergedView.iterator(view)
+
+      assert MergedView.iterator_next(iterator, 3) == [1, 2, 3]
+      assert MergedView.iterator_next(iterator, 3) == [4, 7, 8]
+      assert MergedView.iterator_next(iterator, 3) == [9, 10]
+      assert MergedView.iterator_next(iterator, 3) == []
+    end
+
+    test "walk the view backwards from a term", %{view: view} do
+      iterator = MergedView.iterator(view, from: 8, direction: :desc)
+
+      assert MergedView.iterator_next(iterator, 4) == [8, 7, 4, 3]
+    end
+  end
+
+  test "sets that order their elements differently can not be merged" do
+    view = MergedView.new([SortedSet.new(), SortedSet.new(500, 500, collation: :natural)])
+
+    assert MergedView.slice(view, 0, 10) == {:error, :collation_mismatch}
+  end
+end
@@ -0,0 +1,46 @@
+defmodule Discord.SortedSet.Meta.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "put_meta/2 and get_meta/2" do
+    test "set without metadata returns default" do
+      set = SortedSet.new()
+
+      assert SortedSet.get_meta(set) == nil
+      assert SortedSet.get_meta(set, :default) == :default
+    end
+
+    test "metadata can be attached and retrieved" do
+      set = SortedSet.new()
+
+      assert ^set = SortedSet.put_meta(set, {:guild, 1234, "shard-1"})
+      assert SortedSet.get_meta(set) == {:guild, 1234, "shard-1"}
+    end
+
+    test "putting metadata replaces the previous term" do
+      set = SortedSet.new()
+
+      assert ^set = SortedSet.put_meta(set, 1)
+      assert ^set = SortedSet.put_meta(set, 2)
+      assert SortedSet.get_meta(set) == 2
+    end
+
+    test "metadata does not affect the contents of the set" do
+      set =
+        SortedSet.new()
+        |> SortedSet.add(1)
+        |> SortedSet.put_meta(:meta)
+
+      assert SortedSet.size(set) == 1
+      assert SortedSet.to_list(set) == [1]
+    end
+
+    test "unsupported metadata is rejected" do
+      set = SortedSet.new()
+
+      assert {:error, :unsupported_type} = SortedSet.put_meta(set, self())
+      assert SortedSet.get_meta(set) == nil
+    end
+  end
+end
@@ -0,0 +1,45 @@
+defmodule Discord.SortedSet.MetricsPrometheus.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "metrics_prometheus/2" do
+    test "renders the metrics of the set" do
+      set = SortedSet.from_enumerable(1..10, 4)
+      SortedSet.subscribe(set)
+
+      text = SortedSet.metrics_prometheus(set)
+
+      assert text =~ "# TYPE sorted_set_elements gauge\nsorted_set_elements 10\n"
+      assert text =~ "# TYPE sorted_set_mutations_total counter\n"
+      assert text =~ "\nsorted_set_max_bucket_size 4\n"
+      assert text =~ "\nsorted_set_subscribers 1\n"
+      assert text =~ "\nsorted_set_frozen 0\n"
+    end
+
+    test "attaches the labels to every sample" do
+      set = SortedSet.from_enumerable([1, 2, 3])
+
+      text = SortedSet.metrics_prometheus(set, shard: 3, name: ~s(a "set"))
+
+      samples =
+        text
+        |> String.split("\n", trim: true)
+        |> Enum.reject(&String.starts_with?(&1, "#"))
+
+      assert samples != []
+
+      for sample <- samples do
+        assert sample =~ ~s({shard="3",name="a \\"set\\""})
+      end
+    end
+
+    test "rejects invalid label names" do
+      set = SortedSet.new()
+
+      assert_raise ArgumentError, fn ->
+        SortedSet.metrics_prometheus(set, [{"not-valid", "x"}])
+      end
+    end
+  end
+end
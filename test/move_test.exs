@@ -0,0 +1,92 @@
+defmodule Discord.SortedSet.Move.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "move/3" do
+    test "moves the item and reports both indices" do
+      online = SortedSet.from_enumerable([1, 2, 3])
+      offline = SortedSet.from_enumerable([0, 4])
+
+      assert SortedSet.move(online, offline, 2) == {1, 1}
+
+      assert SortedSet.to_list(online) == [1, 3]
+      assert SortedSet.to_list(offline) == [0, 2, 4]
+    end
+
+    test "an item already in the destination is only removed from the source" do
+      a = SortedSet.from_enumerable([1, 2])
+      b = SortedSet.from_enumerable([2, 3])
+
+      assert SortedSet.move(a, b, 2) == {1, 0}
+      assert SortedSet.to_list(a) == [1]
+      assert SortedSet.to_list(b) == [2, 3]
+    end
+
+    test "missing items are not found" do
+      a = SortedSet.from_enumerable([1])
+      b = SortedSet.new()
+
+      assert SortedSet.move(a, b, 2) == {:error, :not_found}
+      assert SortedSet.move(a, a, 1) == {:error, :same_set}
+    end
+
+    test "leaves both sets untouched when the destination is full" do
+      a = SortedSet.from_enumerable([1, 2])
+      b = SortedSet.from_enumerable([3], 500, max_elements: 1)
+
+      assert SortedSet.move(a, b, 1) == {:error, :set_full}
+      assert SortedSet.to_list(a) == [1, 2]
+      assert SortedSet.to_list(b) == [3]
+    end
+
+    test "leaves both sets untouched when either is frozen" do
+      a = SortedSet.from_enumerable([1, 2])
+      b = SortedSet.freeze(SortedSet.new())
+
+      assert SortedSet.move(a, b, 1) == {:error, :frozen}
+      assert SortedSet.to_list(a) == [1, 2]
+    end
+  end
+
+  describe "move_range/4" do
+    test "moves every item within the bounds" do
+      a = SortedSet.from_enumerable(1..20, 4)
+      b = SortedSet.from_enumerable([0, 100], 4)
+
+      assert SortedSet.move_range(a, b, 5, 14) == 10
+
+      assert SortedSet.to_list(a) == Enum.to_list(1..4) ++ Enum.to_list(15..20)
+      assert SortedSet.to_list(b) == [0] ++ Enum.to_list(5..14) ++ [100]
+      assert SortedSet.size(b) == 12
+    end
+
+    test "merges into a set that overlaps the range" do
+      a = SortedSet.from_enumerable(1..10)
+      b = SortedSet.from_enumerable([4, 6])
+
+      assert SortedSet.move_range(a, b, 3, 6) == 4
+      assert SortedSet.to_list(a) == [1, 2, 7, 8, 9, 10]
+      assert SortedSet.to_list(b) == [3, 4, 5, 6]
+    end
+
+    test "empty ranges move nothing" do
+      a = SortedSet.from_enumerable(1..10)
+      b = SortedSet.new()
+
+      assert SortedSet.move_range(a, b, 20, 30) == 0
+      assert SortedSet.move_range(a, b, 5, 1) == 0
+      assert SortedSet.move_range(a, a, 1, 5) == {:error, :same_set}
+      assert SortedSet.size(a) == 10
+    end
+
+    test "leaves both sets untouched when the destination could overflow" do
+      a = SortedSet.from_enumerable(1..10)
+      b = SortedSet.from_enumerable([], 500, max_elements: 3)
+
+      assert SortedSet.move_range(a, b, 1, 5) == {:error, :set_full}
+      assert SortedSet.size(a) == 10
+      assert SortedSet.size(b) == 0
+    end
+  end
+end
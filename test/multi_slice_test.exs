@@ -0,0 +1,45 @@
+defmodule Discord.SortedSet.MultiSlice.Test do
+  use ExUnit.Case
+  use ExUnitProperties
+
+  alias Discord.SortedSet
+
+  @bucket_size 5
+
+  defp populated_set() do
+    Enum.reduce(1..50, SortedSet.new(SortedSet.default_capacity(), @bucket_size), fn item, set ->
+      SortedSet.add(set, item * 2)
+    end)
+  end
+
+  describe "multi_slice/2" do
+    test "returns one slice per window in the order given" do
+      set = populated_set()
+
+      assert SortedSet.multi_slice(set, [{10, 2}, {0, 3}, {48, 5}]) == [
+               [22, 24],
+               [2, 4, 6],
+               [98, 100]
+             ]
+    end
+
+    test "no windows returns no slices" do
+      assert SortedSet.multi_slice(populated_set(), []) == []
+    end
+
+    test "empty set returns empty slices" do
+      assert SortedSet.multi_slice(SortedSet.new(), [{0, 5}, {10, 1}]) == [[], []]
+    end
+
+    property "every window matches slice/3" do
+      set = populated_set()
+
+      check all windows <- list_of({integer(0..60), integer(0..60)}) do
+        expected =
+          Enum.map(windows, fn {start, amount} -> SortedSet.slice(set, start, amount) end)
+
+        assert SortedSet.multi_slice(set, windows) == expected
+      end
+    end
+  end
+end
@@ -0,0 +1,72 @@
+defmodule Discord.SortedSet.Namespaces.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet.Namespaces
+
+  describe "add/3 and remove/3" do
+    test "namespaces are independent" do
+      namespaces =
+        Namespaces.new(5)
+        |> Namespaces.add(:general, 3)
+        |> Namespaces.add(:general, 1)
+        |> Namespaces.add({:channel, 7}, 2)
+
+      assert Namespaces.to_list(namespaces, :general) == [1, 3]
+      assert Namespaces.to_list(namespaces, {:channel, 7}) == [2]
+      assert Namespaces.to_list(namespaces, :missing) == []
+      assert Namespaces.size(namespaces, :general) == 2
+    end
+
+    test "index variants report the index within the namespace" do
+      namespaces = Namespaces.new()
+
+      assert {0, ^namespaces} = Namespaces.index_add(namespaces, :a, 10)
+      assert {0, ^namespaces} = Namespaces.index_add(namespaces, :b, 20)
+      assert {1, ^namespaces} = Namespaces.index_add(namespaces, :a, 15)
+      assert {nil, ^namespaces} = Namespaces.index_add(namespaces, :a, 15)
+      assert {1, ^namespaces} = Namespaces.index_remove(namespaces, :a, 15)
+      assert {nil, ^namespaces} = Namespaces.index_remove(namespaces, :c, 15)
+    end
+
+    test "max_elements limits every namespace separately" do
+      namespaces = Namespaces.new(5, max_elements: 1)
+
+      assert ^namespaces = Namespaces.add(namespaces, :a, 1)
+      assert Namespaces.add(namespaces, :a, 2) == {:error, :set_full}
+      assert ^namespaces = Namespaces.add(namespaces, :b, 2)
+    end
+
+    test "unsupported namespaces are refused" do
+      assert Namespaces.add(Namespaces.new(), self(), 1) == {:error, :unsupported_type}
+    end
+  end
+
+  describe "namespaces/1" do
+    test "lists the namespaces that hold items" do
+      namespaces =
+        Namespaces.new()
+        |> Namespaces.add(:b, 1)
+        |> Namespaces.add(:a, 1)
+        |> Namespaces.add(:c, 1)
+        |> Namespaces.remove(:c, 1)
+
+      assert Namespaces.namespaces(namespaces) == [:a, :b]
+      assert Namespaces.delete(namespaces, :a) == 1
+      assert Namespaces.delete(namespaces, :a) == 0
+      assert Namespaces.namespaces(namespaces) == [:b]
+    end
+  end
+
+  describe "reads" do
+    test "at, slice and find_index" do
+      namespaces = Enum.reduce(1..20, Namespaces.new(4), &Namespaces.add(&2, :n, &1))
+
+      assert Namespaces.at(namespaces, :n, 0) == 1
+      assert Namespaces.at(namespaces, :n, 20, :default) == :default
+      assert Namespaces.at(namespaces, :other, 0, :default) == :default
+      assert Namespaces.slice(namespaces, :n, 18, 5) == [19, 20]
+      assert Namespaces.find_index(namespaces, :n, 7) == 6
+      assert Namespaces.find_index(namespaces, :other, 7) == nil
+    end
+  end
+end
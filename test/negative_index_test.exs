@@ -0,0 +1,40 @@
+defmodule Discord.SortedSet.NegativeIndex.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "at/3 with a negative index" do
+    test "counts back from the end of the set" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(1..20), 3)
+
+      assert SortedSet.at(set, -1) == 20
+      assert SortedSet.at(set, -5) == 16
+      assert SortedSet.at(set, -20) == 1
+    end
+
+    test "returns the default past the start of the set" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+
+      assert SortedSet.at(set, -4) == nil
+      assert SortedSet.at(set, -4, :none) == :none
+      assert SortedSet.at(SortedSet.new(), -1) == nil
+    end
+
+    test "is reported by the NIF with the original index" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+
+      assert Discord.SortedSet.NifBridge.at(set, -4) == {:error, {:index_out_of_bounds, -4, 3}}
+    end
+  end
+
+  describe "pop_at/3 with a negative index" do
+    test "removes the element counted from the end" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(1..10), 4)
+
+      assert {10, ^set} = SortedSet.pop_at(set, -1)
+      assert {7, ^set} = SortedSet.pop_at(set, -3)
+      assert {nil, ^set} = SortedSet.pop_at(set, -9)
+      assert SortedSet.to_list(set) == [1, 2, 3, 4, 5, 6, 8, 9]
+    end
+  end
+end
@@ -9,4 +9,22 @@ defmodule Discord.SortedSet.New.Test do
       assert is_reference(set)
     end
   end
+
+  describe "new/3" do
+    test "accepts an initial bucket capacity" do
+      set = SortedSet.new(0, 10, initial_bucket_capacity: 8)
+      Enum.each(1..100, &SortedSet.add(set, &1))
+
+      assert SortedSet.to_list(set) == Enum.to_list(1..100)
+    end
+
+    test "rejects option combinations that can not work" do
+      assert_raise ArgumentError, fn -> SortedSet.new(0, 0) end
+      assert_raise ArgumentError, fn -> SortedSet.new(0, 10, initial_bucket_capacity: 11) end
+
+      assert_raise ArgumentError, fn ->
+        SortedSet.new(0, 10, initial_bucket_capacity: 8, backend: :btree)
+      end
+    end
+  end
 end
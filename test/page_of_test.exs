@@ -0,0 +1,38 @@
+defmodule Discord.SortedSet.PageOf.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "page_of/3" do
+    test "finds the page and offset of the element" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(0..99))
+
+      assert SortedSet.page_of(set, 0, 10) == {0, 0, Enum.to_list(0..9)}
+      assert SortedSet.page_of(set, 37, 10) == {3, 7, Enum.to_list(30..39)}
+      assert SortedSet.page_of(set, 99, 30) == {3, 9, Enum.to_list(90..99)}
+      assert SortedSet.page_of(set, 42, 1) == {42, 0, [42]}
+    end
+
+    test "pages match slices" do
+      set = SortedSet.from_proper_enumerable(Enum.map(1..250, &(&1 * 2)))
+
+      {page, offset, items} = SortedSet.page_of(set, 300, 40)
+
+      assert items == SortedSet.slice(set, page * 40, 40)
+      assert Enum.at(items, offset) == 300
+    end
+
+    test "missing elements" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+
+      assert SortedSet.page_of(set, 4, 10) == nil
+      assert SortedSet.page_of(set, self(), 10) == {:error, :unsupported_type}
+    end
+
+    test "refuses invalid page sizes" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+
+      assert_raise ArgumentError, fn -> SortedSet.page_of(set, 1, 0) end
+    end
+  end
+end
@@ -0,0 +1,71 @@
+defmodule Discord.SortedSet.Partition.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "partition/2" do
+    test "splits the set around the pivot" do
+      set = SortedSet.from_enumerable(1..20, 4)
+
+      {lower, upper} = SortedSet.partition(set, 8)
+
+      assert SortedSet.to_list(lower) == Enum.to_list(1..7)
+      assert SortedSet.to_list(upper) == Enum.to_list(8..20)
+    end
+
+    test "pivot does not need to be an element of the set" do
+      set = SortedSet.from_enumerable([2, 4, 6, 8])
+
+      {lower, upper} = SortedSet.partition(set, 5)
+
+      assert SortedSet.to_list(lower) == [2, 4]
+      assert SortedSet.to_list(upper) == [6, 8]
+    end
+
+    test "pivot outside of the set produces an empty half" do
+      {lower, upper} = SortedSet.partition(SortedSet.from_enumerable([1, 2, 3]), 0)
+
+      assert SortedSet.to_list(lower) == []
+      assert SortedSet.to_list(upper) == [1, 2, 3]
+
+      {lower, upper} = SortedSet.partition(SortedSet.from_enumerable([1, 2, 3]), 10)
+
+      assert SortedSet.to_list(lower) == [1, 2, 3]
+      assert SortedSet.to_list(upper) == []
+    end
+
+    test "original set is left empty" do
+      set = SortedSet.from_enumerable([1, 2, 3])
+
+      SortedSet.partition(set, 2)
+
+      assert SortedSet.size(set) == 0
+      assert SortedSet.to_list(set) == []
+    end
+
+    test "new sets are independent and usable" do
+      {lower, upper} = SortedSet.partition(SortedSet.from_enumerable([1, 2, 3, 4]), 3)
+
+      lower = SortedSet.add(lower, 0)
+      upper = SortedSet.add(upper, 5)
+
+      assert SortedSet.to_list(lower) == [0, 1, 2]
+      assert SortedSet.to_list(upper) == [3, 4, 5]
+      assert SortedSet.size(lower) == 3
+      assert SortedSet.size(upper) == 3
+    end
+
+    test "frozen set can not be partitioned" do
+      set = SortedSet.freeze(SortedSet.from_enumerable([1, 2, 3]))
+
+      assert {:error, :frozen} = SortedSet.partition(set, 2)
+      assert SortedSet.to_list(set) == [1, 2, 3]
+    end
+
+    test "unsupported pivot is rejected" do
+      set = SortedSet.from_enumerable([1, 2, 3])
+
+      assert {:error, :unsupported_type} = SortedSet.partition(set, self())
+    end
+  end
+end
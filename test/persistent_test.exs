@@ -0,0 +1,72 @@
+defmodule Discord.SortedSet.Persistent.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+  alias Discord.SortedSet.Persistent
+
+  describe "from_enumerable/2" do
+    test "sorts and deduplicates the terms" do
+      set = Persistent.from_enumerable([3, 1, 2, 3, 1], 5)
+
+      assert Persistent.to_list(set) == [1, 2, 3]
+      assert Persistent.size(set) == 3
+    end
+
+    test "new/1 creates an empty set" do
+      assert Persistent.to_list(Persistent.new()) == []
+    end
+  end
+
+  describe "mutations" do
+    test "add returns a new version and leaves the old one untouched" do
+      v1 = Persistent.from_enumerable(1..10, 4)
+      v2 = Persistent.add(v1, 0)
+      v3 = Persistent.remove(v2, 5)
+
+      assert Persistent.to_list(v1) == Enum.to_list(1..10)
+      assert Persistent.to_list(v2) == Enum.to_list(0..10)
+      assert Persistent.to_list(v3) == [0, 1, 2, 3, 4, 6, 7, 8, 9, 10]
+    end
+
+    test "index variants report the index" do
+      set = Persistent.from_enumerable([1, 3], 4)
+
+      assert {1, set} = Persistent.index_add(set, 2)
+      assert {nil, ^set} = Persistent.index_add(set, 2)
+      assert {2, set} = Persistent.index_remove(set, 3)
+      assert {nil, ^set} = Persistent.index_remove(set, 3)
+      assert Persistent.to_list(set) == [1, 2]
+    end
+
+    test "no-op mutations return the same version" do
+      set = Persistent.from_enumerable([1, 2, 3])
+
+      assert Persistent.add(set, 2) == set
+      assert Persistent.remove(set, 4) == set
+    end
+  end
+
+  describe "reads" do
+    test "at, slice and find_index" do
+      set = Persistent.from_enumerable(1..20, 4)
+
+      assert Persistent.at(set, 0) == 1
+      assert Persistent.at(set, 20, :default) == :default
+      assert Persistent.slice(set, 18, 5) == [19, 20]
+      assert Persistent.find_index(set, 7) == 6
+      assert Persistent.find_index(set, 50) == nil
+    end
+  end
+
+  describe "snapshot/1" do
+    test "captures the contents of a SortedSet" do
+      sorted_set = SortedSet.from_enumerable([1, 2, 3])
+      snapshot = Persistent.snapshot(sorted_set)
+
+      SortedSet.add(sorted_set, 4)
+
+      assert Persistent.to_list(snapshot) == [1, 2, 3]
+      assert SortedSet.to_list(sorted_set) == [1, 2, 3, 4]
+    end
+  end
+end
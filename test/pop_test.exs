@@ -0,0 +1,94 @@
+defmodule Discord.SortedSet.Pop.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "pop_first/2 and pop_last/2" do
+    test "remove and return the ends of the set" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(1..10), 3)
+
+      assert {1, ^set} = SortedSet.pop_first(set)
+      assert {10, ^set} = SortedSet.pop_last(set)
+      assert {2, ^set} = SortedSet.pop_first(set)
+      assert SortedSet.to_list(set) == Enum.to_list(3..9)
+    end
+
+    test "return the default once the set is empty" do
+      set = SortedSet.from_proper_enumerable([:only])
+
+      assert {:only, ^set} = SortedSet.pop_last(set)
+      assert {nil, ^set} = SortedSet.pop_first(set)
+      assert {:none, ^set} = SortedSet.pop_last(set, :none)
+    end
+
+    test "concurrent consumers never pop the same element" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(1..1_000), 50)
+
+      popped =
+        1..4
+        |> Enum.map(fn _ ->
+          Task.async(fn ->
+            Stream.repeatedly(fn -> SortedSet.pop_first(set, :done) end)
+            |> Stream.map(&elem(&1, 0))
+            |> Enum.take_while(&(&1 != :done))
+          end)
+        end)
+        |> Enum.flat_map(&Task.await/1)
+
+      assert Enum.sort(popped) == Enum.to_list(1..1_000)
+      assert SortedSet.size(set) == 0
+    end
+
+    test "notify subscribers of the removal" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+      SortedSet.subscribe(set)
+
+      SortedSet.pop_last(set)
+
+      assert_receive {:sorted_set_event, ^set, :removed, 3, 2, _}
+    end
+
+    test "reject frozen sets" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+      SortedSet.freeze(set)
+
+      assert SortedSet.pop_first(set) == {:error, :frozen}
+    end
+  end
+
+  describe "pop_at/3" do
+    test "removes and returns the element at the index" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(0..19), 4)
+
+      assert {5, ^set} = SortedSet.pop_at(set, 5)
+      assert {6, ^set} = SortedSet.pop_at(set, 5)
+      assert {19, ^set} = SortedSet.pop_at(set, 17)
+      assert SortedSet.to_list(set) == Enum.to_list(0..4) ++ Enum.to_list(7..18)
+    end
+
+    test "returns the default for an index out of bounds" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+
+      assert {nil, ^set} = SortedSet.pop_at(set, 3)
+      assert {:none, ^set} = SortedSet.pop_at(set, 10, :none)
+      assert SortedSet.size(set) == 3
+    end
+
+    test "removes large tuples by rank" do
+      items = Enum.map(1..50, &{&1, String.duplicate("x", 100), [&1, &1]})
+      set = SortedSet.from_proper_enumerable(items, 8)
+
+      assert {{25, _, [25, 25]}, ^set} = SortedSet.pop_at(set, 24)
+      assert SortedSet.find_index(set, {26, String.duplicate("x", 100), [26, 26]}) == 24
+    end
+
+    test "notifies subscribers of the removal" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+      SortedSet.subscribe(set)
+
+      SortedSet.pop_at(set, 1)
+
+      assert_receive {:sorted_set_event, ^set, :removed, 2, 1, _}
+    end
+  end
+end
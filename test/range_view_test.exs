@@ -0,0 +1,56 @@
+defmodule Discord.SortedSet.RangeView.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+  alias Discord.SortedSet.RangeView
+
+  setup do
+    set = SortedSet.from_enumerable([{1, 10}, {1, 20}, {2, 10}, {2, 20}, {2, 30}, {3, 10}])
+
+    {:ok, set: set, view: RangeView.new(set, {2, 0}, {2, 1_000})}
+  end
+
+  describe "reads" do
+    test "are limited to the range", %{view: view} do
+      assert RangeView.size(view) == 3
+      assert RangeView.at(view, 0) == {2, 10}
+      assert RangeView.at(view, 3, :default) == :default
+      assert RangeView.slice(view, 1, 10) == [{2, 20}, {2, 30}]
+    end
+
+    test "see mutations of the set", %{set: set, view: view} do
+      SortedSet.add(set, {2, 40})
+      SortedSet.add(set, {4, 10})
+      SortedSet.remove(set, {2, 10})
+
+      assert RangeView.slice(view, 0, 10) == [{2, 20}, {2, 30}, {2, 40}]
+    end
+
+    test "inverted bounds hold nothing", %{set: set} do
+      view = RangeView.new(set, 3, 1)
+
+      assert RangeView.size(view) == 0
+      assert RangeView.slice(view, 0, 10) == []
+    end
+
+    test "unsupported bounds are refused", %{set: set} do
+      assert RangeView.new(set, self(), 1) == {:error, :unsupported_type}
+    end
+  end
+
+  describe "iterators" do
+    test "stop at the end of the range", %{view: view} do
+      iterator = RangeView.iterator(view)
+
+      assert RangeView.iterator_next(iterator, 2) == [{2, 10}, {2, 20}]
+      assert RangeView.iterator_next(iterator, 2) == [{2, 30}]
+      assert RangeView.iterator_next(iterator, 2) == []
+    end
+
+    test "index into the view", %{view: view} do
+      iterator = RangeView.iterator(view, at: 1, direction: :desc)
+
+      assert RangeView.iterator_next(iterator, 5) == [{2, 20}, {2, 10}]
+    end
+  end
+end
@@ -0,0 +1,41 @@
+defmodule Discord.SortedSet.RemoveAtMany.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "remove_at_many/2" do
+    test "removes the elements at the indexes" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(0..29), 5)
+
+      assert {[0, 3, 10, 11, 12, 13, 14, 29], ^set} =
+               SortedSet.remove_at_many(set, [29, 3, 10, 11, 12, 13, 14, 3, 0, 99])
+
+      assert SortedSet.size(set) == 22
+      assert SortedSet.to_list(set) == Enum.to_list(0..29) -- [0, 3, 10, 11, 12, 13, 14, 29]
+    end
+
+    test "ignores an empty list of indexes" do
+      set = SortedSet.from_proper_enumerable([:a, :b, :c])
+
+      assert SortedSet.remove_at_many(set, []) == {[], set}
+      assert SortedSet.to_list(set) == [:a, :b, :c]
+    end
+
+    test "notifies subscribers from the last index to the first" do
+      set = SortedSet.from_proper_enumerable([:a, :b, :c, :d])
+      SortedSet.subscribe(set)
+
+      assert {[:a, :c], ^set} = SortedSet.remove_at_many(set, [2, 0])
+
+      assert_receive {:sorted_set_event, ^set, :removed, :c, 2, _}
+      assert_receive {:sorted_set_event, ^set, :removed, :a, 0, _}
+    end
+
+    test "refuses to change a frozen set" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+      SortedSet.freeze(set)
+
+      assert SortedSet.remove_at_many(set, [0]) == {:error, :frozen}
+    end
+  end
+end
@@ -0,0 +1,49 @@
+defmodule Discord.SortedSet.RemoveAt.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "remove_at/2" do
+    test "removes the element at the index" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(0..9), 3)
+
+      assert ^set = SortedSet.remove_at(set, 4)
+      assert ^set = SortedSet.remove_at(set, 0)
+      assert SortedSet.to_list(set) == [1, 2, 3, 5, 6, 7, 8, 9]
+    end
+
+    test "removes an element found with find_index" do
+      items = Enum.map(1..20, &{&1, String.duplicate("y", 200)})
+      set = SortedSet.from_proper_enumerable(items, 4)
+
+      index = SortedSet.find_index(set, {12, String.duplicate("y", 200)})
+      SortedSet.remove_at(set, index)
+
+      assert SortedSet.size(set) == 19
+      assert SortedSet.find_index(set, {12, String.duplicate("y", 200)}) == nil
+    end
+
+    test "leaves the set unchanged for an index out of bounds" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+
+      assert ^set = SortedSet.remove_at(set, 3)
+      assert SortedSet.to_list(set) == [1, 2, 3]
+    end
+
+    test "notifies subscribers of the removal" do
+      set = SortedSet.from_proper_enumerable([:a, :b, :c])
+      SortedSet.subscribe(set)
+
+      SortedSet.remove_at(set, 2)
+
+      assert_receive {:sorted_set_event, ^set, :removed, :c, 2, _}
+    end
+
+    test "rejects frozen sets" do
+      set = SortedSet.from_proper_enumerable([1, 2, 3])
+      SortedSet.freeze(set)
+
+      assert SortedSet.remove_at(set, 0) == {:error, :frozen}
+    end
+  end
+end
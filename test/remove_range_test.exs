@@ -0,0 +1,35 @@
+defmodule Discord.SortedSet.RemoveRange.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "remove_range/2" do
+    test "removes what Enum.slice/2 selects" do
+      items = Enum.to_list(1..20)
+
+      for range <- [0..4, -3..-1, 18..25, 20..25, 2..-3//1, 4..2//1, 0..19//5, -7..-1//2] do
+        set = SortedSet.from_proper_enumerable(items, 5)
+
+        assert {removed, ^set} = SortedSet.remove_range(set, range)
+        assert removed == Enum.slice(items, range), inspect(range)
+        assert SortedSet.to_list(set) == items -- removed, inspect(range)
+      end
+    end
+
+    test "notifies subscribers of every removed element" do
+      set = SortedSet.subscribe(SortedSet.from_proper_enumerable([:a, :b, :c, :d]))
+
+      assert {[:c, :d], ^set} = SortedSet.remove_range(set, -2..-1)
+
+      assert_receive {:sorted_set_event, ^set, :removed, :d, 3, _}
+      assert_receive {:sorted_set_event, ^set, :removed, :c, 2, _}
+    end
+
+    test "frozen sets are left alone" do
+      set = SortedSet.freeze(SortedSet.from_proper_enumerable([1, 2, 3]))
+
+      assert {:error, :frozen} = SortedSet.remove_range(set, 0..1)
+      assert SortedSet.to_list(set) == [1, 2, 3]
+    end
+  end
+end
@@ -3,6 +3,7 @@ defmodule Discord.SortedSet.Remove.Test do
   use ExUnitProperties
 
   alias Discord.SortedSet
+  alias Discord.SortedSet.NifBridge
   alias Discord.SortedSet.Test.Support.Generator
 
   describe "remove from empty set" do
@@ -76,4 +77,22 @@ defmodule Discord.SortedSet.Remove.Test do
       end
     end
   end
+
+  describe "missing option" do
+    test "selects the result of removing an item that is not present" do
+      set = SortedSet.new() |> SortedSet.add(1)
+
+      assert NifBridge.remove(set, 2) == {:error, :not_found}
+      assert NifBridge.remove(set, 2, missing: :error) == {:error, :not_found}
+      assert NifBridge.remove(set, 2, missing: :noop) == {:ok, :noop}
+      assert NifBridge.remove(set, 1, missing: :noop) == {:ok, :removed, 0}
+    end
+
+    test "rejects unknown options" do
+      set = SortedSet.new()
+
+      assert_raise ArgumentError, fn -> NifBridge.remove(set, 1, missing: :ignore) end
+      assert_raise ArgumentError, fn -> NifBridge.remove(set, 1, strict: true) end
+    end
+  end
 end
@@ -0,0 +1,66 @@
+defmodule Discord.SortedSet.Repair.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  # Replays a set whose buckets were replaced by the given, possibly broken, buckets
+  defp with_buckets(buckets) do
+    {:sorted_set_repro, version, configuration, extra, _} =
+      SortedSet.new(0, 5)
+      |> SortedSet.export_repro()
+      |> :erlang.binary_to_term()
+
+    {:sorted_set_repro, version, configuration, extra, buckets}
+    |> :erlang.term_to_binary()
+    |> SortedSet.import_repro()
+  end
+
+  describe "repair/1" do
+    test "healthy sets report nothing to fix" do
+      set = SortedSet.from_enumerable(1..20, 5)
+
+      assert SortedSet.repair(set) == [
+               unsorted_buckets: 0,
+               duplicates: 0,
+               empty_buckets: 0,
+               overlapping_buckets: 0,
+               recorded_size: 20,
+               size: 20
+             ]
+
+      assert SortedSet.to_list(set) == Enum.to_list(1..20)
+    end
+
+    test "restores a broken set" do
+      set = with_buckets([[3, 1], [], [2, 2, 5], [4]])
+
+      assert SortedSet.repair(set) == [
+               unsorted_buckets: 1,
+               duplicates: 1,
+               empty_buckets: 1,
+               overlapping_buckets: 2,
+               recorded_size: 6,
+               size: 5
+             ]
+
+      assert SortedSet.to_list(set) == [1, 2, 3, 4, 5]
+      assert SortedSet.find_index(set, 4) == 3
+      assert SortedSet.size(set) == 5
+    end
+
+    test "leaves frozen sets alone" do
+      set = SortedSet.freeze(with_buckets([[3, 1], [2, 2, 5]]))
+
+      assert SortedSet.repair(set) == {:error, :frozen}
+
+      set = SortedSet.thaw(set)
+
+      assert [unsorted_buckets: 1, duplicates: 1] ++ _ = SortedSet.repair(set)
+      assert SortedSet.to_list(set) == [1, 2, 3, 5]
+    end
+
+    test "rejects bad references" do
+      assert SortedSet.repair(make_ref()) == {:error, :bad_reference}
+    end
+  end
+end
@@ -0,0 +1,39 @@
+defmodule Discord.SortedSet.Repro.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "export_repro/1 and import_repro/1" do
+    test "round trip the state of the set" do
+      set =
+        [3, 1, 2, "a", :b, {1, 2}]
+        |> SortedSet.from_enumerable(3, max_elements: 10)
+        |> SortedSet.put_meta({:shard, 3})
+        |> SortedSet.freeze()
+
+      bundle = SortedSet.export_repro(set)
+      imported = SortedSet.import_repro(bundle)
+
+      assert SortedSet.to_list(imported) == SortedSet.to_list(set)
+      assert SortedSet.get_meta(imported) == {:shard, 3}
+      assert SortedSet.add(imported, 4) == {:error, :frozen}
+      assert SortedSet.export_repro(imported) == bundle
+    end
+
+    test "the bundle is readable with binary_to_term" do
+      set = SortedSet.from_enumerable([1, 2, 3], 2)
+
+      assert {:sorted_set_repro, 1, {2, _, nil, :buckets, false}, {false, []}, buckets} =
+               :erlang.binary_to_term(SortedSet.export_repro(set))
+
+      assert List.flatten(buckets) == [1, 2, 3]
+    end
+
+    test "rejects other binaries" do
+      assert SortedSet.import_repro(:erlang.term_to_binary({:not, :a, :repro})) ==
+               {:error, :invalid_repro}
+
+      assert SortedSet.import_repro("garbage") == {:error, :invalid_repro}
+    end
+  end
+end
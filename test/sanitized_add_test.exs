@@ -0,0 +1,61 @@
+defmodule Discord.SortedSet.SanitizedAdd.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "sanitized_add/3" do
+    test "skips unsupported nested terms" do
+      set = SortedSet.new()
+
+      assert {0, 2, ^set} = SortedSet.sanitized_add(set, {:event, self(), [1, 2.5, 3]}, :skip)
+      assert SortedSet.to_list(set) == [{:event, [1, 3]}]
+    end
+
+    test "replaces unsupported nested terms with a placeholder" do
+      set = SortedSet.new()
+
+      assert {0, 1, ^set} =
+               SortedSet.sanitized_add(set, {:event, self(), "join"}, {:replace, :redacted})
+
+      assert {nil, 1, ^set} =
+               SortedSet.sanitized_add(set, {:event, self(), "join"}, {:replace, :redacted})
+
+      assert SortedSet.to_list(set) == [{:event, :redacted, "join"}]
+    end
+
+    test "reports nothing for supported items" do
+      set = SortedSet.new()
+
+      assert {0, 0, ^set} = SortedSet.sanitized_add(set, {:event, "join"}, :skip)
+    end
+
+    test "refuses items that are unsupported themselves" do
+      set = SortedSet.new()
+
+      assert SortedSet.sanitized_add(set, self(), :skip) == {:error, :unsupported_type}
+      assert SortedSet.size(set) == 0
+    end
+  end
+
+  describe "index_add/3 unsupported option" do
+    test "rejects by default and when asked to" do
+      set = SortedSet.new()
+
+      assert SortedSet.index_add(set, {self()}, []) == {:error, :unsupported_type}
+      assert SortedSet.index_add(set, {self()}, unsupported: :reject) ==
+               {:error, :unsupported_type}
+      assert {0, ^set} = SortedSet.index_add(set, {1, self()}, unsupported: :skip)
+      assert SortedSet.to_list(set) == [{1}]
+    end
+
+    test "rejects invalid policies" do
+      set = SortedSet.new()
+
+      assert_raise ArgumentError, fn ->
+        SortedSet.index_add(set, {self()}, unsupported: {:replace, "redacted"})
+      end
+
+      assert_raise ArgumentError, fn -> SortedSet.index_add(set, {self()}, unsupported: :drop) end
+    end
+  end
+end
@@ -0,0 +1,72 @@
+defmodule Discord.SortedSet.Sharded.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet.Sharded
+
+  defp filled do
+    Enum.reduce([25, 3, 12, 1, 30, 15, 7], Sharded.new([10, 20]), &Sharded.add(&2, &1))
+  end
+
+  describe "new/3" do
+    test "refuses pivots that are not strictly increasing" do
+      assert Sharded.new([20, 10]) == {:error, :unordered_pivots}
+      assert Sharded.new([1, 1]) == {:error, :unordered_pivots}
+      assert Sharded.new([self()]) == {:error, :unsupported_type}
+    end
+
+    test "refuses single owner shards" do
+      assert_raise ArgumentError, fn -> Sharded.new([10], single_owner: true) end
+    end
+  end
+
+  describe "add/2 and remove/2" do
+    test "items go to the shard holding their range" do
+      set = filled()
+
+      assert Sharded.shard_sizes(set) == [3, 2, 2]
+      assert Sharded.size(set) == 7
+
+      set = Sharded.remove(set, 12)
+
+      assert Sharded.shard_sizes(set) == [3, 1, 2]
+      assert ^set = Sharded.remove(set, 12)
+      assert ^set = Sharded.add(set, 20)
+      assert Sharded.shard_sizes(set) == [3, 1, 3]
+    end
+
+    test "max_elements limits each shard" do
+      set = Sharded.new([10], max_elements: 1)
+
+      assert ^set = Sharded.add(set, 1)
+      assert ^set = Sharded.add(set, 11)
+      assert Sharded.add(set, 2) == {:error, :set_full}
+    end
+  end
+
+  describe "reads" do
+    test "see the shards as one set" do
+      set = filled()
+
+      assert Sharded.to_list(set) == [1, 3, 7, 12, 15, 25, 30]
+      assert Sharded.at(set, 3) == 12
+      assert Sharded.at(set, 7, :missing) == :missing
+      assert Sharded.slice(set, 2, 3) == [7, 12, 15]
+      assert Sharded.slice(set, 5, 10) == [25, 30]
+      assert Sharded.find_index(set, 15) == 4
+      assert Sharded.find_index(set, 30) == 6
+      assert Sharded.find_index(set, 14) == nil
+    end
+
+    test "follow the order of the options" do
+      set =
+        [{10}]
+        |> Sharded.new(comparator: [{0, :desc}])
+        |> Sharded.add({5})
+        |> Sharded.add({50})
+        |> Sharded.add({10})
+
+      assert Sharded.shard_sizes(set) == [1, 2]
+      assert Sharded.to_list(set) == [{50}, {10}, {5}]
+    end
+  end
+end
@@ -0,0 +1,46 @@
+defmodule Discord.SortedSet.SingleOwner.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "single_owner option" do
+    test "the owner can use the set" do
+      set = SortedSet.new(500, 5, single_owner: true)
+
+      assert ^set = SortedSet.add(set, 2)
+      assert ^set = SortedSet.add(set, 1)
+      assert SortedSet.find_index(set, 2) == 1
+      assert ^set = SortedSet.remove(set, 1)
+      assert SortedSet.to_list(set) == [2]
+    end
+
+    test "other processes are refused" do
+      set = SortedSet.from_enumerable([1, 2, 3], 500, single_owner: true)
+      parent = self()
+
+      spawn(fn ->
+        send(parent, {:add, SortedSet.add(set, 4)})
+        send(parent, {:to_list, SortedSet.to_list(set)})
+      end)
+
+      assert_receive {:add, {:error, :not_owner}}
+      assert_receive {:to_list, {:error, :not_owner}}
+      assert SortedSet.to_list(set) == [1, 2, 3]
+    end
+
+    test "async operations are refused" do
+      set = SortedSet.from_enumerable([1, 2, 3], 500, single_owner: true)
+
+      assert set |> SortedSet.async_to_list() |> SortedSet.await() == {:error, :not_owner}
+    end
+
+    test "sets are shared by default" do
+      set = SortedSet.from_enumerable([1, 2, 3])
+      parent = self()
+
+      spawn(fn -> send(parent, {:to_list, SortedSet.to_list(set)}) end)
+
+      assert_receive {:to_list, [1, 2, 3]}
+    end
+  end
+end
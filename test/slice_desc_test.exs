@@ -0,0 +1,49 @@
+defmodule Discord.SortedSet.SliceDesc.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "slice_desc/3" do
+    test "pages through the set from the greatest element" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(1..100), 7)
+
+      assert SortedSet.slice_desc(set, 0, 5) == [100, 99, 98, 97, 96]
+      assert SortedSet.slice_desc(set, 5, 5) == [95, 94, 93, 92, 91]
+    end
+
+    test "matches a reversed slice across many buckets" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(1..1_000), 7)
+
+      for item <- Enum.take_every(1..1_000, 3) do
+        SortedSet.remove(set, item)
+      end
+
+      expected =
+        set
+        |> SortedSet.to_list()
+        |> Enum.reverse()
+        |> Enum.slice(100, 50)
+
+      assert SortedSet.slice_desc(set, 100, 50) == expected
+    end
+
+    test "stops at the least element" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(1..10), 3)
+
+      assert SortedSet.slice_desc(set, 7, 10) == [3, 2, 1]
+      assert SortedSet.slice_desc(set, 10, 10) == []
+      assert SortedSet.slice_desc(SortedSet.new(), 0, 10) == []
+    end
+
+    test "rejects a negative start or amount" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(1..10), 3)
+
+      assert SortedSet.slice_desc(set, -1, 5) == {:error, :invalid_range}
+      assert SortedSet.slice_desc(set, 0, -5) == {:error, :invalid_range}
+    end
+
+    test "rejects a bad reference" do
+      assert SortedSet.slice_desc(make_ref(), 0, 5) == {:error, :bad_reference}
+    end
+  end
+end
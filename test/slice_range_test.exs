@@ -0,0 +1,36 @@
+defmodule Discord.SortedSet.SliceRange.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "slice/2" do
+    test "slices like Enum.slice/2" do
+      items = Enum.to_list(1..20)
+      set = SortedSet.from_proper_enumerable(items, 5)
+
+      ranges = [
+        0..4,
+        3..3,
+        5..100,
+        18..25,
+        20..25,
+        -3..-1,
+        -30..2,
+        2..-3//1,
+        -1..-5//1,
+        4..2//1,
+        0..19//5,
+        1..-1//3,
+        -7..-1//2
+      ]
+
+      for range <- ranges do
+        assert SortedSet.slice(set, range) == Enum.slice(items, range), inspect(range)
+      end
+    end
+
+    test "empty sets slice to empty lists" do
+      assert SortedSet.slice(SortedSet.new(), 0..-1//1) == []
+    end
+  end
+end
@@ -0,0 +1,40 @@
+defmodule Discord.SortedSet.SliceStep.Test do
+  use ExUnit.Case
+  use ExUnitProperties
+
+  alias Discord.SortedSet
+
+  @bucket_size 5
+
+  defp populated_set() do
+    Enum.reduce(1..50, SortedSet.new(SortedSet.default_capacity(), @bucket_size), fn item, set ->
+      SortedSet.add(set, item * 2)
+    end)
+  end
+
+  describe "slice_step/4" do
+    test "returns every step-th item starting at start" do
+      set = populated_set()
+
+      assert SortedSet.slice_step(set, 0, 50, 10) == [2, 22, 42, 62, 82]
+      assert SortedSet.slice_step(set, 3, 7, 3) == [8, 14, 20]
+      assert SortedSet.slice_step(set, 48, 10, 4) == [98]
+      assert SortedSet.slice_step(set, 60, 10, 4) == []
+    end
+
+    test "rejects steps that are not positive" do
+      assert SortedSet.slice_step(populated_set(), 0, 10, 0) == {:error, :invalid_range}
+      assert SortedSet.slice_step(populated_set(), 0, 10, -1) == {:error, :invalid_range}
+    end
+
+    property "matches a sampled slice/3" do
+      set = populated_set()
+
+      check all start <- integer(0..60), amount <- integer(0..60), step <- integer(1..20) do
+        expected = set |> SortedSet.slice(start, amount) |> Enum.take_every(step)
+
+        assert SortedSet.slice_step(set, start, amount, step) == expected
+      end
+    end
+  end
+end
@@ -97,4 +97,37 @@ defmodule Discord.SortedSet.Slice.Test do
       assert SortedSet.slice(populated_set(), 7, 10) == [16, 18]
     end
   end
+
+  describe "negative start" do
+    test "counts back from the end of the set" do
+      assert SortedSet.slice(populated_set(), -1, 2) == [18]
+      assert SortedSet.slice(populated_set(), -4, 2) == [12, 14]
+      assert SortedSet.slice(populated_set(), -9, 3) == [2, 4, 6]
+    end
+
+    test "past the beginning of the set is the empty list" do
+      assert SortedSet.slice(populated_set(), -10, 3) == []
+      assert SortedSet.slice(empty_set(), -1, 1) == []
+    end
+  end
+
+  describe "invalid ranges" do
+    test "negative amount" do
+      assert SortedSet.slice(populated_set(), 0, -2) == {:error, :invalid_range}
+      assert SortedSet.slice(populated_set(), -1, -2) == {:error, :invalid_range}
+    end
+
+    test "range end that overflows" do
+      max = 18_446_744_073_709_551_615
+
+      assert SortedSet.slice(populated_set(), max, 1) == {:error, :invalid_range}
+      assert SortedSet.slice(populated_set(), 0, max + 1) == {:error, :invalid_range}
+      assert SortedSet.slice(populated_set(), max, 0) == []
+    end
+
+    test "multi_slice rejects the call if any window is invalid" do
+      assert SortedSet.multi_slice(populated_set(), [{0, 1}, {-1, 1}]) ==
+               {:error, :invalid_range}
+    end
+  end
 end
@@ -0,0 +1,63 @@
+defmodule Discord.SortedSet.StableIds.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "id_of/2" do
+    test "ids do not change when other elements are added or removed" do
+      set = SortedSet.new(500, 5, stable_ids: true)
+
+      SortedSet.add(set, 100)
+      id = SortedSet.id_of(set, 100)
+
+      Enum.each(1..50, &SortedSet.add(set, &1))
+      SortedSet.remove(set, 1)
+
+      assert SortedSet.id_of(set, 100) == id
+      assert SortedSet.get_by_id(set, id) == 100
+    end
+
+    test "removed elements lose their id" do
+      set = SortedSet.new(500, 5, stable_ids: true)
+
+      SortedSet.add(set, :a)
+      id = SortedSet.id_of(set, :a)
+      SortedSet.remove(set, :a)
+
+      assert SortedSet.id_of(set, :a) == nil
+      assert SortedSet.get_by_id(set, id, :missing) == :missing
+
+      SortedSet.add(set, :a)
+      assert SortedSet.id_of(set, :a) > id
+    end
+
+    test "sets built from an enumerable assign ids" do
+      set = SortedSet.from_enumerable([3, 1, 2], 500, stable_ids: true)
+
+      ids = Enum.map([1, 2, 3], &SortedSet.id_of(set, &1))
+
+      assert Enum.all?(ids, &is_integer/1)
+      assert Enum.uniq(ids) == ids
+    end
+
+    test "sets without stable ids report an error" do
+      set = SortedSet.from_enumerable([1])
+
+      assert SortedSet.id_of(set, 1) == {:error, :stable_ids_disabled}
+      assert SortedSet.get_by_id(set, 1) == {:error, :stable_ids_disabled}
+    end
+  end
+
+  describe "events" do
+    test "events include the id of the element" do
+      set = SortedSet.subscribe(SortedSet.new(500, 5, stable_ids: true))
+
+      SortedSet.add(set, 5)
+      assert_receive {:sorted_set_event, ^set, :added, 5, 0, 1, id}
+      assert SortedSet.id_of(set, 5) == id
+
+      SortedSet.remove(set, 5)
+      assert_receive {:sorted_set_event, ^set, :removed, 5, 0, 2, ^id}
+    end
+  end
+end
@@ -0,0 +1,90 @@
+defmodule Discord.SortedSet.Subscribe.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "subscribe/2" do
+    test "subscriber receives an event for every add and remove" do
+      set = SortedSet.subscribe(SortedSet.new())
+
+      SortedSet.add(set, 5)
+      assert_receive {:sorted_set_event, ^set, :added, 5, 0, 1}
+
+      SortedSet.add(set, 3)
+      assert_receive {:sorted_set_event, ^set, :added, 3, 0, 2}
+
+      SortedSet.remove(set, 5)
+      assert_receive {:sorted_set_event, ^set, :removed, 5, 1, 3}
+    end
+
+    test "no-op operations do not produce events" do
+      set = SortedSet.from_enumerable([1, 2, 3])
+      SortedSet.subscribe(set)
+
+      SortedSet.add(set, 2)
+      SortedSet.remove(set, 10)
+
+      refute_receive {:sorted_set_event, _, _, _, _, _}
+    end
+
+    test "indexed operations produce events" do
+      set = SortedSet.subscribe(SortedSet.from_enumerable([1, 3]))
+
+      assert {1, ^set} = SortedSet.index_add(set, 2)
+      assert_receive {:sorted_set_event, ^set, :added, 2, 1, _}
+
+      assert {0, ^set} = SortedSet.index_remove(set, 1)
+      assert_receive {:sorted_set_event, ^set, :removed, 1, 0, _}
+    end
+
+    test "every subscriber receives the event" do
+      set = SortedSet.new()
+      parent = self()
+
+      subscriber =
+        spawn(fn ->
+          receive do
+            event -> send(parent, {:forwarded, event})
+          end
+        end)
+
+      SortedSet.subscribe(set)
+      SortedSet.subscribe(set, subscriber)
+
+      SortedSet.add(set, 1)
+
+      assert_receive {:sorted_set_event, ^set, :added, 1, 0, _}
+      assert_receive {:forwarded, {:sorted_set_event, ^set, :added, 1, 0, _}}
+    end
+
+    test "subscribing twice delivers a single event" do
+      set = SortedSet.new()
+
+      SortedSet.subscribe(set)
+      SortedSet.subscribe(set)
+
+      SortedSet.add(set, 1)
+
+      assert_receive {:sorted_set_event, ^set, :added, 1, 0, _}
+      refute_receive {:sorted_set_event, _, _, _, _, _}
+    end
+  end
+
+  describe "unsubscribe/2" do
+    test "unsubscribed processes no longer receive events" do
+      set = SortedSet.subscribe(SortedSet.new())
+
+      assert ^set = SortedSet.unsubscribe(set)
+
+      SortedSet.add(set, 1)
+
+      refute_receive {:sorted_set_event, _, _, _, _, _}
+    end
+
+    test "unsubscribing a process that is not subscribed has no effect" do
+      set = SortedSet.new()
+
+      assert ^set = SortedSet.unsubscribe(set)
+    end
+  end
+end
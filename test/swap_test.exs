@@ -0,0 +1,81 @@
+defmodule Discord.SortedSet.Swap.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "swap/2" do
+    test "exchanges the contents of two sets" do
+      a = SortedSet.from_enumerable([1, 2, 3])
+      b = SortedSet.from_enumerable([10, 20])
+
+      assert ^a = SortedSet.swap(a, b)
+
+      assert SortedSet.to_list(a) == [10, 20]
+      assert SortedSet.size(a) == 2
+
+      assert SortedSet.to_list(b) == [1, 2, 3]
+      assert SortedSet.size(b) == 3
+    end
+
+    test "replacement is visible through references held elsewhere" do
+      live = SortedSet.from_enumerable([1, 2, 3])
+      parent = self()
+
+      reader =
+        spawn(fn ->
+          receive do
+            :read -> send(parent, {:read, SortedSet.to_list(live)})
+          end
+        end)
+
+      replacement = SortedSet.from_enumerable([4, 5, 6])
+      assert ^live = SortedSet.swap(live, replacement)
+
+      send(reader, :read)
+      assert_receive {:read, [4, 5, 6]}
+    end
+
+    test "metadata stays with the reference" do
+      a = SortedSet.put_meta(SortedSet.new(), :a)
+      b = SortedSet.put_meta(SortedSet.add(SortedSet.new(), 1), :b)
+
+      assert ^a = SortedSet.swap(a, b)
+
+      assert SortedSet.get_meta(a) == :a
+      assert SortedSet.get_meta(b) == :b
+      assert SortedSet.to_list(a) == [1]
+    end
+
+    test "swapping a set with itself is a no-op" do
+      a = SortedSet.from_enumerable([1, 2, 3])
+
+      assert ^a = SortedSet.swap(a, a)
+      assert SortedSet.to_list(a) == [1, 2, 3]
+    end
+
+    test "frozen sets can not be swapped" do
+      a = SortedSet.from_enumerable([1, 2, 3])
+      b = SortedSet.freeze(SortedSet.from_enumerable([4]))
+
+      assert {:error, :frozen} = SortedSet.swap(a, b)
+      assert {:error, :frozen} = SortedSet.swap(b, a)
+
+      assert SortedSet.to_list(a) == [1, 2, 3]
+      assert SortedSet.to_list(b) == [4]
+    end
+
+    test "sets that disagree on single_owner or coalesce_writes can not be swapped" do
+      shared = SortedSet.from_enumerable([1, 2, 3])
+      owned = SortedSet.from_enumerable([4], 500, single_owner: true)
+      coalesced = SortedSet.from_enumerable([5], 500, coalesce_writes: true)
+
+      assert {:error, :configuration_mismatch} = SortedSet.swap(shared, owned)
+      assert {:error, :configuration_mismatch} = SortedSet.swap(coalesced, shared)
+      assert {:error, :configuration_mismatch} = SortedSet.swap(owned, coalesced)
+
+      assert SortedSet.to_list(shared) == [1, 2, 3]
+      assert SortedSet.to_list(owned) == [4]
+      assert SortedSet.to_list(coalesced) == [5]
+    end
+  end
+end
@@ -0,0 +1,31 @@
+defmodule Discord.SortedSet.ToListReverse.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "to_list_reverse/1" do
+    test "lists the elements in descending order" do
+      set = SortedSet.from_enumerable([5, :b, 1, "c", {1, 2}], 2)
+
+      assert SortedSet.to_list_reverse(set) == ["c", {1, 2}, :b, 5, 1]
+    end
+
+    test "matches a reversed to_list across many buckets" do
+      set = SortedSet.from_proper_enumerable(Enum.to_list(1..1_000), 7)
+
+      for item <- Enum.take_every(1..1_000, 3) do
+        SortedSet.remove(set, item)
+      end
+
+      assert SortedSet.to_list_reverse(set) == Enum.reverse(SortedSet.to_list(set))
+    end
+
+    test "is empty for an empty set" do
+      assert SortedSet.to_list_reverse(SortedSet.new()) == []
+    end
+
+    test "rejects a bad reference" do
+      assert SortedSet.to_list_reverse(make_ref()) == {:error, :bad_reference}
+    end
+  end
+end
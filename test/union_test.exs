@@ -0,0 +1,39 @@
+defmodule Discord.SortedSet.Union.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "union/2" do
+    test "merges both sets into a new set" do
+      odd = Enum.take_every(1..100, 2)
+      a = SortedSet.from_proper_enumerable(odd, 10)
+      b = SortedSet.from_proper_enumerable(Enum.to_list(50..150), 10)
+
+      union = SortedSet.union(a, b)
+
+      assert union != a
+      assert union != b
+      assert SortedSet.to_list(union) == Enum.filter(1..49, &(&1 in odd)) ++ Enum.to_list(50..150)
+      assert SortedSet.size(a) == 50
+      assert SortedSet.size(b) == 101
+    end
+
+    test "the union of a set with itself is a copy" do
+      set = SortedSet.from_proper_enumerable([:a, :b, :c])
+      union = SortedSet.union(set, set)
+
+      SortedSet.add(union, :d)
+
+      assert SortedSet.to_list(union) == [:a, :b, :c, :d]
+      assert SortedSet.to_list(set) == [:a, :b, :c]
+    end
+
+    test "respects the max_elements of the first set" do
+      a = SortedSet.new(10, 10, max_elements: 3)
+      SortedSet.add(a, 1)
+      b = SortedSet.from_proper_enumerable([2, 3, 4])
+
+      assert SortedSet.union(a, b) == {:error, :set_full}
+    end
+  end
+end
@@ -0,0 +1,28 @@
+defmodule Discord.SortedSet.Zip.Test do
+  use ExUnit.Case
+
+  alias Discord.SortedSet
+
+  describe "zip/4" do
+    test "pairs the items at the same indexes" do
+      a = SortedSet.from_enumerable([1, 2, 3, 4])
+      b = SortedSet.from_enumerable([:a, :b, :c])
+
+      assert SortedSet.zip(a, b, 0, 10) == [{1, :a}, {2, :b}, {3, :c}]
+      assert SortedSet.zip(a, b, 1, 1) == [{2, :b}]
+      assert SortedSet.zip(a, b, 5, 1) == []
+    end
+
+    test "a set can be zipped with itself" do
+      a = SortedSet.from_enumerable([1, 2])
+
+      assert SortedSet.zip(a, a, 0, 2) == [{1, 1}, {2, 2}]
+    end
+
+    test "rejects invalid ranges" do
+      a = SortedSet.new()
+
+      assert SortedSet.zip(a, a, -1, 2) == {:error, :invalid_range}
+    end
+  end
+end